@@ -9,6 +9,7 @@ use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use rusqlite::Connection;
 
 use openkoi::core::token_optimizer::estimate_tokens;
+use openkoi::core::tokenizer::Heuristic;
 use openkoi::memory::compaction::compact;
 use openkoi::memory::embeddings::{cosine_similarity, normalize, text_similarity};
 use openkoi::memory::recall::{recall, HistoryRecall};
@@ -100,6 +101,7 @@ fn bench_recall(c: &mut Criterion) {
                 "Fix a type error in the parser",
                 Some("coding"),
                 2000,
+                &Heuristic,
             )
             .expect("recall");
         })
@@ -112,6 +114,7 @@ fn bench_recall(c: &mut Criterion) {
                 "Add logging to the server",
                 Some("coding"),
                 500,
+                &Heuristic,
             )
             .expect("recall");
         })
@@ -119,8 +122,14 @@ fn bench_recall(c: &mut Criterion) {
 
     group.bench_function("recall_no_category", |b| {
         b.iter(|| {
-            let _result: HistoryRecall =
-                recall(black_box(&store), "General task description", None, 2000).expect("recall");
+            let _result: HistoryRecall = recall(
+                black_box(&store),
+                "General task description",
+                None,
+                2000,
+                &Heuristic,
+            )
+            .expect("recall");
         })
     });
 
@@ -0,0 +1,134 @@
+// src/acp/connection.rs — JSON-RPC over newline-delimited stdio
+//
+// A writer task owns the output stream, so notifications can be queued
+// from synchronous progress callbacks. A reader task routes responses to
+// the requests we sent the editor and hands everything else (its requests
+// and notifications) to the server loop.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot};
+
+use super::protocol::RpcError;
+
+type Pending = Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>;
+
+/// The agent's side of an ACP connection.
+pub struct Connection {
+    outgoing: mpsc::UnboundedSender<Value>,
+    pending: Pending,
+    next_id: AtomicI64,
+}
+
+impl Connection {
+    /// Start the reader and writer tasks. The receiver yields the editor's
+    /// requests and notifications; it closes when the input ends.
+    pub fn start(
+        reader: impl AsyncRead + Unpin + Send + 'static,
+        mut writer: impl AsyncWrite + Unpin + Send + 'static,
+    ) -> (Arc<Self>, mpsc::UnboundedReceiver<Value>) {
+        let (outgoing, mut queue) = mpsc::unbounded_channel::<Value>();
+        tokio::spawn(async move {
+            while let Some(message) = queue.recv().await {
+                let mut line = message.to_string();
+                line.push('\n');
+                if writer.write_all(line.as_bytes()).await.is_err() || writer.flush().await.is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        let connection = Arc::new(Self {
+            outgoing,
+            pending: Mutex::new(HashMap::new()),
+            next_id: AtomicI64::new(1),
+        });
+        let (incoming, inbox) = mpsc::unbounded_channel();
+        let reader_side = connection.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let message: Value = match serde_json::from_str(&line) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        tracing::debug!("Ignoring unreadable ACP message: {}", e);
+                        continue;
+                    }
+                };
+                if message.get("method").is_some() {
+                    if incoming.send(message).is_err() {
+                        break;
+                    }
+                } else {
+                    reader_side.resolve(&message);
+                }
+            }
+        });
+        (connection, inbox)
+    }
+
+    /// Send a notification to the editor.
+    pub fn notify(&self, method: &str, params: Value) {
+        let _ = self.outgoing.send(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        }));
+    }
+
+    /// Send a request to the editor and wait for its result.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(id, tx);
+        }
+        self.outgoing
+            .send(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            }))
+            .map_err(|_| anyhow!("editor connection closed"))?;
+        rx.await
+            .map_err(|_| anyhow!("editor connection closed"))?
+            .map_err(|e| anyhow!("{method}: {e}"))
+    }
+
+    /// Answer one of the editor's requests.
+    pub fn respond(&self, id: Value, result: Result<Value, RpcError>) {
+        let message = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error.to_json() }),
+        };
+        let _ = self.outgoing.send(message);
+    }
+
+    fn resolve(&self, message: &Value) {
+        let Some(id) = message.get("id").and_then(Value::as_i64) else {
+            return;
+        };
+        let waiter = self.pending.lock().ok().and_then(|mut p| p.remove(&id));
+        if let Some(waiter) = waiter {
+            let outcome = match message.get("error") {
+                Some(error) => Err(error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("request failed")
+                    .to_string()),
+                None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+            };
+            let _ = waiter.send(outcome);
+        }
+    }
+}
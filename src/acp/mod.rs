@@ -0,0 +1,651 @@
+// src/acp/mod.rs — Agent Client Protocol server
+//
+// `openkoi acp` speaks ACP (JSON-RPC over stdio) so editors such as Zed can
+// run the iteration engine as their agent. Each session gets its own tool
+// registry: the editor's buffers for file access, the MCP servers the editor
+// passes in, then the tools configured for openkoi itself. Prompt turns run
+// concurrently with the message loop so `session/cancel` and the editor's
+// answers to our own requests keep flowing while a task runs.
+
+pub mod connection;
+pub mod protocol;
+pub mod tools;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::Notify;
+
+use crate::cli::chat::remember_exchange;
+use crate::core::escalation::EscalationPolicy;
+use crate::core::instructions::ProjectInstructions;
+use crate::core::orchestrator::{Orchestrator, SessionContext};
+use crate::core::safety::SafetyChecker;
+use crate::core::tokenizer;
+use crate::core::types::{IterationEngineConfig, ProgressEvent, TaskInput};
+use crate::index::CodeIndex;
+use crate::infra::config::{Config, McpServerConfig};
+use crate::learner::skill_selector::SkillSelector;
+use crate::lsp::LspManager;
+use crate::memory::recall::{self, HistoryRecall};
+use crate::memory::store::Store;
+use crate::patterns::event_logger::{EventLogger, EventType, UsageEvent};
+use crate::plugins::mcp::McpManager;
+use crate::provider::roles::ModelRoles;
+use crate::provider::{ModelProvider, ModelRef};
+use crate::skills::embedding::SkillEmbedder;
+use crate::skills::registry::SkillRegistry;
+use crate::soul::loader::{self, Soul};
+use crate::tools::policy::ToolPolicy;
+use crate::tools::{ToolProvider, ToolRegistry};
+use connection::Connection;
+use protocol::RpcError;
+use tools::{AcpApprover, EditorFiles, FsCapabilities};
+
+/// Everything a prompt turn needs that outlives the session.
+pub struct Agent {
+    provider: Arc<dyn ModelProvider>,
+    model_ref: ModelRef,
+    config: Config,
+    store: Option<Arc<Mutex<Store>>>,
+    /// Tool providers from openkoi's own registry, shared by all sessions.
+    base_tools: Vec<Arc<dyn ToolProvider>>,
+    code_index: Option<Arc<CodeIndex>>,
+    lsp: Option<Arc<LspManager>>,
+    soul: Soul,
+    instructions: ProjectInstructions,
+    skill_registry: Arc<SkillRegistry>,
+    embedder: SkillEmbedder,
+    selector: SkillSelector,
+    root: PathBuf,
+}
+
+impl Agent {
+    pub fn new(
+        provider: Arc<dyn ModelProvider>,
+        model_ref: &ModelRef,
+        config: &Config,
+        store: Option<Arc<Mutex<Store>>>,
+        tools: Option<&ToolRegistry>,
+        code_index: Option<Arc<CodeIndex>>,
+        lsp: Option<Arc<LspManager>>,
+    ) -> Result<Self> {
+        let root = std::env::current_dir()?.canonicalize()?;
+        Ok(Self {
+            embedder: SkillEmbedder::new(provider.clone(), &config.skills.embeddings),
+            provider,
+            model_ref: model_ref.clone(),
+            config: config.clone(),
+            store,
+            base_tools: tools.map(|t| t.providers().to_vec()).unwrap_or_default(),
+            code_index,
+            lsp,
+            soul: loader::load_soul(),
+            instructions: ProjectInstructions::discover(&root),
+            skill_registry: Arc::new(SkillRegistry::new()),
+            selector: SkillSelector::with_experiments(config.skills.experiments.clone()),
+            root,
+        })
+    }
+}
+
+/// Serve ACP on stdin/stdout until the editor closes the connection.
+pub async fn run_acp(
+    provider: Arc<dyn ModelProvider>,
+    model_ref: &ModelRef,
+    config: &Config,
+    store: Option<Arc<Mutex<Store>>>,
+    tools: Option<&ToolRegistry>,
+    code_index: Option<Arc<CodeIndex>>,
+    lsp: Option<Arc<LspManager>>,
+) -> Result<()> {
+    let mut agent = Agent::new(provider, model_ref, config, store, tools, code_index, lsp)?;
+    if let Some(registry) = Arc::get_mut(&mut agent.skill_registry) {
+        agent
+            .embedder
+            .embed_registry(registry, agent.store.as_deref())
+            .await;
+    }
+    serve(tokio::io::stdin(), tokio::io::stdout(), Arc::new(agent)).await
+}
+
+struct Session {
+    registry: Arc<ToolRegistry>,
+    mcp: Option<Arc<tokio::sync::Mutex<McpManager>>>,
+    /// Summary of earlier exchanges, fed back as conversation history.
+    history: Arc<Mutex<String>>,
+    /// Set while a prompt turn runs; notified by `session/cancel`.
+    running: Option<Arc<Notify>>,
+}
+
+struct Server {
+    agent: Arc<Agent>,
+    connection: Arc<Connection>,
+    fs: FsCapabilities,
+    sessions: HashMap<String, Session>,
+}
+
+/// Serve ACP over any byte streams.
+pub async fn serve(
+    reader: impl AsyncRead + Unpin + Send + 'static,
+    writer: impl AsyncWrite + Unpin + Send + 'static,
+    agent: Arc<Agent>,
+) -> Result<()> {
+    let (connection, mut inbox) = Connection::start(reader, writer);
+    let mut server = Server {
+        agent,
+        connection,
+        fs: FsCapabilities::default(),
+        sessions: HashMap::new(),
+    };
+    let mut turns = FuturesUnordered::new();
+
+    loop {
+        tokio::select! {
+            message = inbox.recv() => {
+                let Some(message) = message else { break };
+                if let Some(turn) = server.handle(message).await {
+                    turns.push(turn);
+                }
+            }
+            Some(session_id) = turns.next(), if !turns.is_empty() => {
+                if let Some(session) = server.sessions.get_mut(&session_id) {
+                    session.running = None;
+                }
+            }
+        }
+    }
+
+    for session in server.sessions.values() {
+        if let Some(ref mcp) = session.mcp {
+            mcp.lock().await.shutdown_all().await;
+        }
+    }
+    Ok(())
+}
+
+type Turn = std::pin::Pin<Box<dyn std::future::Future<Output = String>>>;
+
+impl Server {
+    /// Handle one message from the editor. A `session/prompt` request
+    /// returns the turn to run; everything else is answered here.
+    async fn handle(&mut self, message: Value) -> Option<Turn> {
+        let method = message["method"].as_str().unwrap_or_default().to_string();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let Some(id) = message.get("id").cloned() else {
+            if method == "session/cancel" {
+                self.cancel(&params);
+            }
+            return None;
+        };
+
+        let result = match method.as_str() {
+            "initialize" => Ok(self.initialize(&params)),
+            "authenticate" => Ok(json!({})),
+            "session/new" => self.new_session(&params).await,
+            "session/prompt" => match self.prompt(id.clone(), &params) {
+                Ok(turn) => return Some(turn),
+                Err(e) => Err(e),
+            },
+            _ => Err(RpcError::method_not_found(&method)),
+        };
+        self.connection.respond(id, result);
+        None
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        self.fs = FsCapabilities::from_client(&params["clientCapabilities"]);
+        json!({
+            "protocolVersion": protocol::PROTOCOL_VERSION,
+            "agentCapabilities": {
+                "loadSession": false,
+                "promptCapabilities": {
+                    "image": false,
+                    "audio": false,
+                    "embeddedContext": true,
+                },
+                "mcpCapabilities": { "http": false, "sse": false },
+            },
+            "authMethods": [],
+            "agentInfo": {
+                "name": "openkoi",
+                "title": "OpenKoi",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    async fn new_session(&mut self, params: &Value) -> Result<Value, RpcError> {
+        // Config, memory and the code index belong to the directory openkoi
+        // started in, so a session can't move to another project.
+        let cwd = params["cwd"]
+            .as_str()
+            .ok_or_else(|| RpcError::invalid_params("missing 'cwd'"))?;
+        let same_project = Path::new(cwd)
+            .canonicalize()
+            .is_ok_and(|cwd| cwd == self.agent.root);
+        if !same_project {
+            return Err(RpcError::invalid_params(format!(
+                "openkoi acp is serving {}; start it in {} for this project",
+                self.agent.root.display(),
+                cwd
+            )));
+        }
+
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let approver = AcpApprover::new(self.connection.clone(), &session_id);
+        let policy = ToolPolicy::load(&self.agent.config.permissions, &self.agent.root)
+            .map_err(RpcError::internal)?
+            .with_approver(Arc::new(approver));
+
+        let mut registry = ToolRegistry::new();
+        registry.set_policy(policy);
+        if self.fs.read || self.fs.write {
+            registry.register(Arc::new(EditorFiles::new(
+                self.connection.clone(),
+                &session_id,
+                &self.agent.root,
+                self.fs,
+            )));
+        }
+        let servers = mcp_servers(&params["mcpServers"]);
+        let mcp = if servers.is_empty() {
+            None
+        } else {
+            let mut manager = McpManager::new();
+            manager
+                .start_all(&servers)
+                .await
+                .map_err(RpcError::internal)?;
+            let manager = Arc::new(tokio::sync::Mutex::new(manager));
+            for provider in McpManager::tool_providers(&manager).await {
+                registry.register(provider);
+            }
+            Some(manager)
+        };
+        for provider in &self.agent.base_tools {
+            registry.register(provider.clone());
+        }
+        let connection = self.connection.clone();
+        let id = session_id.clone();
+        registry.set_progress(Arc::new(move |event| {
+            if let Some(update) = protocol::progress_update(&event) {
+                connection.notify("session/update", protocol::session_update(&id, update));
+            }
+        }));
+        tracing::debug!("ACP session {}: {} tool(s)", session_id, registry.len());
+
+        self.sessions.insert(
+            session_id.clone(),
+            Session {
+                registry: Arc::new(registry),
+                mcp,
+                history: Arc::new(Mutex::new(String::new())),
+                running: None,
+            },
+        );
+        Ok(json!({ "sessionId": session_id }))
+    }
+
+    fn cancel(&self, params: &Value) {
+        let session = params["sessionId"]
+            .as_str()
+            .and_then(|id| self.sessions.get(id));
+        if let Some(cancel) = session.and_then(|s| s.running.as_ref()) {
+            cancel.notify_one();
+        }
+    }
+
+    fn prompt(&mut self, request_id: Value, params: &Value) -> Result<Turn, RpcError> {
+        let session_id = params["sessionId"].as_str().unwrap_or_default().to_string();
+        let session = self
+            .sessions
+            .get_mut(&session_id)
+            .ok_or_else(|| RpcError::invalid_params(format!("unknown session '{session_id}'")))?;
+        if session.running.is_some() {
+            return Err(RpcError::invalid_params(
+                "a prompt is already running in this session",
+            ));
+        }
+        let blocks = params["prompt"].as_array().cloned().unwrap_or_default();
+        let text = protocol::prompt_text(&blocks);
+        if text.trim().is_empty() {
+            return Err(RpcError::invalid_params("prompt has no text"));
+        }
+
+        let cancel = Arc::new(Notify::new());
+        session.running = Some(cancel.clone());
+        let turn = PromptTurn {
+            agent: self.agent.clone(),
+            connection: self.connection.clone(),
+            session_id,
+            registry: session.registry.clone(),
+            history: session.history.clone(),
+        };
+        Ok(Box::pin(async move {
+            let result = tokio::select! {
+                result = turn.run(&text) => result,
+                _ = cancel.notified() => Ok(json!({ "stopReason": "cancelled" })),
+            };
+            turn.connection.respond(request_id, result);
+            turn.session_id
+        }))
+    }
+}
+
+/// One `session/prompt`: a full task run with the session's tools.
+struct PromptTurn {
+    agent: Arc<Agent>,
+    connection: Arc<Connection>,
+    session_id: String,
+    registry: Arc<ToolRegistry>,
+    history: Arc<Mutex<String>>,
+}
+
+impl PromptTurn {
+    async fn run(&self, text: &str) -> Result<Value, RpcError> {
+        let agent = &self.agent;
+        let config = &agent.config;
+        let task = TaskInput::new(text);
+        let engine_config = IterationEngineConfig::from(&config.iteration);
+        let safety = SafetyChecker::from_config(&config.iteration, &config.safety);
+
+        let task_embedding = agent
+            .embedder
+            .embed_task(&task.description, agent.skill_registry.all())
+            .await;
+        let ranked_skills = {
+            let store_guard = agent.store.as_ref().and_then(|s| s.lock().ok());
+            agent.selector.select(
+                &task.description,
+                task_embedding.as_deref(),
+                task.category.as_deref(),
+                agent.skill_registry.all(),
+                store_guard.as_deref(),
+            )
+        };
+        let recall = {
+            let store_guard = agent.store.as_ref().and_then(|s| s.lock().ok());
+            match store_guard.as_deref() {
+                Some(s) => {
+                    let counter = tokenizer::for_provider_model(
+                        agent.provider.as_ref(),
+                        &agent.model_ref.model,
+                    );
+                    recall::recall(
+                        s,
+                        text,
+                        task.category.as_deref(),
+                        engine_config.token_budget / 10,
+                        counter.as_ref(),
+                    )
+                    .unwrap_or_default()
+                }
+                None => HistoryRecall::default(),
+            }
+        };
+        let history = self.history.lock().map(|h| h.clone()).unwrap_or_default();
+
+        let ctx = SessionContext {
+            soul: agent.soul.clone(),
+            instructions: agent.instructions.clone(),
+            code_index: agent.code_index.clone(),
+            lsp: agent.lsp.clone(),
+            ranked_skills,
+            recall,
+            tools: self.registry.defs().to_vec(),
+            skill_registry: agent.skill_registry.clone(),
+            conversation_history: (!history.is_empty()).then_some(history),
+        };
+
+        let connection = self.connection.clone();
+        let session_id = self.session_id.clone();
+        let thoughts: Box<dyn Fn(ProgressEvent) + Send> = Box::new(move |event| {
+            if let Some(update) = protocol::progress_update(&event) {
+                connection.notify(
+                    "session/update",
+                    protocol::session_update(&session_id, update),
+                );
+            }
+        });
+        let progress = crate::core::state::state_writer_progress(
+            task.id.clone(),
+            task.description.clone(),
+            Some(thoughts),
+        );
+        let mut orchestrator = Orchestrator::new(
+            agent.provider.clone(),
+            ModelRoles::from_config(
+                agent.model_ref.clone(),
+                config.models.executor.as_deref(),
+                config.models.evaluator.as_deref(),
+                config.models.planner.as_deref(),
+                config.models.embedder.as_deref(),
+            ),
+            engine_config,
+            safety,
+            agent.skill_registry.clone(),
+            agent.store.clone(),
+        )
+        .with_escalation(EscalationPolicy::for_provider(
+            &config.models,
+            &agent.provider,
+        ))
+        .with_progress(progress);
+
+        let result = orchestrator
+            .run(task, &ctx, Some(self.registry.as_ref()))
+            .await
+            .map_err(RpcError::internal)?;
+
+        self.connection.notify(
+            "session/update",
+            protocol::session_update(
+                &self.session_id,
+                protocol::text_chunk("agent_message_chunk", &result.output.content),
+            ),
+        );
+        if let Ok(mut history) = self.history.lock() {
+            remember_exchange(&mut history, text, &result.output.content);
+        }
+        if let Some(ref s) = agent.store {
+            if let Ok(locked) = s.lock() {
+                let _ = EventLogger::new(&locked).log(&UsageEvent {
+                    event_type: EventType::Task,
+                    channel: "acp".into(),
+                    description: text.to_string(),
+                    category: None,
+                    skills_used: result.skills_used.clone(),
+                    score: Some(result.final_score as f32),
+                });
+            }
+        }
+        Ok(json!({ "stopReason": protocol::stop_reason(&result) }))
+    }
+}
+
+/// Stdio MCP servers from `session/new`. HTTP and SSE servers aren't
+/// advertised, so they're skipped.
+fn mcp_servers(servers: &Value) -> Vec<McpServerConfig> {
+    let Some(servers) = servers.as_array() else {
+        return Vec::new();
+    };
+    servers
+        .iter()
+        .filter_map(|server| {
+            let name = server["name"].as_str()?;
+            let Some(command) = server["command"].as_str() else {
+                tracing::warn!("Skipping MCP server '{}': only stdio is supported", name);
+                return None;
+            };
+            let strings = |value: &Value| -> Vec<String> {
+                value
+                    .as_array()
+                    .map(|items| {
+                        items
+                            .iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            };
+            let env = server["env"]
+                .as_array()
+                .map(|vars| {
+                    vars.iter()
+                        .filter_map(|var| {
+                            Some((
+                                var["name"].as_str()?.to_string(),
+                                var["value"].as_str()?.to_string(),
+                            ))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(McpServerConfig {
+                name: name.to_string(),
+                command: command.to_string(),
+                args: strings(&server["args"]),
+                env,
+                transport: "stdio".into(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::errors::OpenKoiError;
+    use crate::provider::{ChatChunk, ChatRequest, ChatResponse, ModelInfo};
+    use async_trait::async_trait;
+    use futures::Stream;
+    use std::pin::Pin;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    struct OfflineProvider;
+
+    #[async_trait]
+    impl ModelProvider for OfflineProvider {
+        fn id(&self) -> &str {
+            "offline"
+        }
+        fn name(&self) -> &str {
+            "Offline"
+        }
+        fn models(&self) -> Vec<ModelInfo> {
+            vec![]
+        }
+        async fn chat(&self, _req: ChatRequest) -> Result<ChatResponse, OpenKoiError> {
+            Err(OpenKoiError::NoProvider)
+        }
+        async fn chat_stream(
+            &self,
+            _req: ChatRequest,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatChunk, OpenKoiError>> + Send>>, OpenKoiError>
+        {
+            Err(OpenKoiError::NoProvider)
+        }
+        async fn embed(&self, _texts: &[&str]) -> Result<Vec<Vec<f32>>, OpenKoiError> {
+            Err(OpenKoiError::NoProvider)
+        }
+    }
+
+    #[test]
+    fn test_mcp_servers() {
+        let servers = mcp_servers(&json!([
+            {"name": "fs", "command": "mcp-fs", "args": ["--root", "."],
+             "env": [{"name": "TOKEN", "value": "x"}]},
+            {"type": "http", "name": "remote", "url": "https://example.com/mcp", "headers": []},
+        ]));
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].args, vec!["--root", "."]);
+        assert_eq!(servers[0].env.get("TOKEN").map(String::as_str), Some("x"));
+    }
+
+    #[tokio::test]
+    async fn test_session_handshake() {
+        let agent = Agent::new(
+            Arc::new(OfflineProvider),
+            &ModelRef::new("offline", "none"),
+            &Config::default(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let root = agent.root.clone();
+        let (agent_io, editor_io) = tokio::io::duplex(64 * 1024);
+        let (read, write) = tokio::io::split(agent_io);
+        let server = serve(read, write, Arc::new(agent));
+
+        let (read, mut write) = tokio::io::split(editor_io);
+        let mut lines = BufReader::new(read).lines();
+        let call = |id: i64, method: &str, params: Value| {
+            let request = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+            format!("{request}\n")
+        };
+        let editor = async move {
+            let requests = [
+                call(
+                    1,
+                    "initialize",
+                    json!({"protocolVersion": 1, "clientCapabilities": {}}),
+                ),
+                call(2, "session/new", json!({"cwd": "/", "mcpServers": []})),
+                call(3, "session/new", json!({"cwd": root, "mcpServers": []})),
+                call(4, "session/load", json!({})),
+            ];
+            for request in &requests {
+                write.write_all(request.as_bytes()).await.unwrap();
+            }
+
+            let mut responses = HashMap::new();
+            while responses.len() < requests.len() {
+                let line = lines.next_line().await.unwrap().unwrap();
+                let message: Value = serde_json::from_str(&line).unwrap();
+                responses.insert(message["id"].as_i64().unwrap(), message);
+            }
+            assert_eq!(responses[&1]["result"]["protocolVersion"], 1);
+            assert_eq!(
+                responses[&1]["result"]["agentCapabilities"]["promptCapabilities"]
+                    ["embeddedContext"],
+                true
+            );
+            assert_eq!(responses[&2]["error"]["code"], -32602);
+            assert!(responses[&3]["result"]["sessionId"].is_string());
+            assert_eq!(responses[&4]["error"]["code"], -32601);
+
+            // The turn streams its updates, then answers the prompt request.
+            let session_id = responses[&3]["result"]["sessionId"].clone();
+            let prompt = call(
+                5,
+                "session/prompt",
+                json!({"sessionId": session_id, "prompt": [{"type": "text", "text": "hello"}]}),
+            );
+            write.write_all(prompt.as_bytes()).await.unwrap();
+            let mut updates = Vec::new();
+            loop {
+                let line = lines.next_line().await.unwrap().unwrap();
+                let message: Value = serde_json::from_str(&line).unwrap();
+                if message["id"] == 5 {
+                    assert_eq!(message["result"]["stopReason"], "end_turn");
+                    break;
+                }
+                assert_eq!(message["method"], "session/update");
+                assert_eq!(message["params"]["sessionId"], session_id);
+                updates.push(message["params"]["update"]["sessionUpdate"].clone());
+            }
+            assert_eq!(updates.last().unwrap(), "agent_message_chunk");
+        };
+        // The editor closing its end stops the server.
+        let (served, ()) = tokio::join!(server, editor);
+        served.unwrap();
+    }
+}
@@ -0,0 +1,255 @@
+// src/acp/protocol.rs — ACP message shapes
+//
+// Builders for the JSON the Agent Client Protocol expects, and the mapping
+// from orchestrator `ProgressEvent`s to `session/update` notifications.
+
+use serde_json::{json, Value};
+
+use crate::core::types::{IterationDecision, ProgressEvent, TaskResult};
+use crate::tools::overlay::{file_path_arg, is_file_write_tool};
+
+/// The ACP major version this server speaks.
+pub const PROTOCOL_VERSION: u64 = 1;
+
+/// Tool output beyond this many characters is cut from `tool_call_update`.
+const MAX_TOOL_OUTPUT_CHARS: usize = 4000;
+
+/// A JSON-RPC error response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn method_not_found(method: &str) -> Self {
+        Self {
+            code: -32601,
+            message: format!("method not found: {method}"),
+        }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: -32602,
+            message: message.into(),
+        }
+    }
+
+    pub fn internal(message: impl std::fmt::Display) -> Self {
+        Self {
+            code: -32603,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({ "code": self.code, "message": self.message })
+    }
+}
+
+/// The task text for a prompt turn. Text blocks are kept as they are;
+/// embedded resources are appended as fenced file contents and resource
+/// links as references. Images and audio are not advertised, so skipped.
+pub fn prompt_text(blocks: &[Value]) -> String {
+    let mut parts = Vec::new();
+    for block in blocks {
+        match block.get("type").and_then(Value::as_str) {
+            Some("text") => {
+                if let Some(text) = block.get("text").and_then(Value::as_str) {
+                    parts.push(text.to_string());
+                }
+            }
+            Some("resource") => {
+                let resource = &block["resource"];
+                let uri = resource["uri"].as_str().unwrap_or_default();
+                if let Some(text) = resource.get("text").and_then(Value::as_str) {
+                    parts.push(format!("File {}:\n```\n{}\n```", display_uri(uri), text));
+                }
+            }
+            Some("resource_link") => {
+                let uri = block["uri"].as_str().unwrap_or_default();
+                parts.push(format!("Referenced file: {}", display_uri(uri)));
+            }
+            _ => {}
+        }
+    }
+    parts.join("\n\n")
+}
+
+fn display_uri(uri: &str) -> &str {
+    uri.strip_prefix("file://").unwrap_or(uri)
+}
+
+/// `session/update` params.
+pub fn session_update(session_id: &str, update: Value) -> Value {
+    json!({ "sessionId": session_id, "update": update })
+}
+
+/// An `agent_message_chunk` or `agent_thought_chunk` update.
+pub fn text_chunk(kind: &str, text: &str) -> Value {
+    json!({
+        "sessionUpdate": kind,
+        "content": { "type": "text", "text": text },
+    })
+}
+
+/// The update for a progress event, if the editor should see it. Per-
+/// iteration progress becomes agent thoughts; registry tool events become
+/// live tool calls. `ToolCall` summaries are left out since the registry
+/// already reported each call.
+pub fn progress_update(event: &ProgressEvent) -> Option<Value> {
+    match event {
+        ProgressEvent::ToolStarted {
+            call_id,
+            name,
+            args,
+        } => {
+            let mut update = json!({
+                "sessionUpdate": "tool_call",
+                "toolCallId": call_id,
+                "title": tool_title(name, args),
+                "kind": tool_kind(name),
+                "status": "in_progress",
+                "rawInput": args,
+            });
+            if let Some(path) = file_path_arg(args) {
+                update["locations"] = json!([{ "path": path }]);
+            }
+            Some(update)
+        }
+        ProgressEvent::ToolFinished {
+            call_id,
+            output,
+            failed,
+            ..
+        } => {
+            let text = match output.char_indices().nth(MAX_TOOL_OUTPUT_CHARS) {
+                Some((cut, _)) => format!("{}\n[truncated]", &output[..cut]),
+                None => output.clone(),
+            };
+            Some(json!({
+                "sessionUpdate": "tool_call_update",
+                "toolCallId": call_id,
+                "status": if *failed { "failed" } else { "completed" },
+                "content": [{ "type": "content", "content": { "type": "text", "text": text } }],
+            }))
+        }
+        _ => event
+            .summary()
+            .map(|text| text_chunk("agent_thought_chunk", &(text + "\n"))),
+    }
+}
+
+/// Tool call title: the tool name, plus its target file when it has one.
+pub fn tool_title(name: &str, args: &Value) -> String {
+    match file_path_arg(args) {
+        Some(path) => format!("{name} {path}"),
+        None => name.to_string(),
+    }
+}
+
+/// ACP tool kind, guessed from the tool name.
+pub fn tool_kind(name: &str) -> &'static str {
+    let lower = name.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+    if has(&["delete", "remove"]) {
+        "delete"
+    } else if has(&["move", "rename"]) {
+        "move"
+    } else if is_file_write_tool(name) {
+        "edit"
+    } else if has(&["search", "find", "grep", "references", "symbol"]) {
+        "search"
+    } else if has(&["read", "view", "get_file", "list", "hover", "definition"]) {
+        "read"
+    } else if has(&["run", "exec", "shell", "bash", "command"]) {
+        "execute"
+    } else if has(&["fetch", "http", "web", "url"]) {
+        "fetch"
+    } else {
+        "other"
+    }
+}
+
+/// Why a prompt turn ended.
+pub fn stop_reason(result: &TaskResult) -> &'static str {
+    match result.decision {
+        IterationDecision::AbortBudget => "max_tokens",
+        _ => "end_turn",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_text() {
+        let blocks = vec![
+            json!({"type": "text", "text": "Fix the parser"}),
+            json!({"type": "resource", "resource": {
+                "uri": "file:///repo/src/parse.rs", "text": "fn parse() {}", "mimeType": "text/x-rust"
+            }}),
+            json!({"type": "resource_link", "uri": "file:///repo/README.md", "name": "README.md"}),
+            json!({"type": "image", "data": "...", "mimeType": "image/png"}),
+        ];
+        assert_eq!(
+            prompt_text(&blocks),
+            "Fix the parser\n\nFile /repo/src/parse.rs:\n```\nfn parse() {}\n```\n\n\
+             Referenced file: /repo/README.md"
+        );
+    }
+
+    #[test]
+    fn test_progress_update() {
+        let started = progress_update(&ProgressEvent::ToolStarted {
+            call_id: "c1".into(),
+            name: "write_file".into(),
+            args: json!({"path": "src/lib.rs", "content": "x"}),
+        })
+        .unwrap();
+        assert_eq!(started["sessionUpdate"], "tool_call");
+        assert_eq!(started["kind"], "edit");
+        assert_eq!(started["title"], "write_file src/lib.rs");
+        assert_eq!(started["locations"][0]["path"], "src/lib.rs");
+
+        let finished = progress_update(&ProgressEvent::ToolFinished {
+            call_id: "c1".into(),
+            name: "write_file".into(),
+            output: "Error: denied".into(),
+            failed: true,
+        })
+        .unwrap();
+        assert_eq!(finished["status"], "failed");
+        assert_eq!(finished["content"][0]["content"]["text"], "Error: denied");
+
+        let thought = progress_update(&ProgressEvent::IterationEnd {
+            iteration: 1,
+            score: 0.72,
+            decision: IterationDecision::Continue,
+            cost_so_far: 0.03,
+        })
+        .unwrap();
+        assert_eq!(thought["sessionUpdate"], "agent_thought_chunk");
+        assert!(thought["content"]["text"]
+            .as_str()
+            .unwrap()
+            .contains("scored 0.72"));
+        assert!(progress_update(&ProgressEvent::ToolCall {
+            name: "x".into(),
+            iteration: 1
+        })
+        .is_none());
+    }
+
+    #[test]
+    fn test_tool_kind() {
+        assert_eq!(tool_kind("read_file"), "read");
+        assert_eq!(tool_kind("edit_file"), "edit");
+        assert_eq!(tool_kind("filesystem__move_file"), "move");
+        assert_eq!(tool_kind("lsp_find_references"), "search");
+        assert_eq!(tool_kind("run_command"), "execute");
+        assert_eq!(tool_kind("slack_send"), "other");
+    }
+}
@@ -0,0 +1,388 @@
+// src/acp/tools.rs — Editor-backed file tools and permission prompts
+//
+// When the editor advertises `fs` capabilities, `read_file`, `write_file`
+// and `edit_file` go through its buffers (unsaved changes included) instead
+// of the disk. "Ask" permission rules become `session/request_permission`
+// requests shown in the editor.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::connection::Connection;
+use super::protocol::{tool_kind, tool_title};
+use crate::provider::ToolDef;
+use crate::security::redact::Redactor;
+use crate::tools::policy::{Approval, ApprovalRequest, Approver};
+use crate::tools::{ToolEffect, ToolProvider, ToolSource};
+
+/// What the editor lets the agent do with its files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FsCapabilities {
+    pub read: bool,
+    pub write: bool,
+}
+
+impl FsCapabilities {
+    /// From `initialize`'s `clientCapabilities`.
+    pub fn from_client(capabilities: &Value) -> Self {
+        let flag = |name: &str| {
+            capabilities
+                .pointer(&format!("/fs/{name}"))
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+        };
+        Self {
+            read: flag("readTextFile"),
+            write: flag("writeTextFile"),
+        }
+    }
+}
+
+/// File tools served by the editor.
+pub struct EditorFiles {
+    connection: Arc<Connection>,
+    session_id: String,
+    root: PathBuf,
+    fs: FsCapabilities,
+}
+
+impl EditorFiles {
+    pub fn new(
+        connection: Arc<Connection>,
+        session_id: &str,
+        root: &Path,
+        fs: FsCapabilities,
+    ) -> Self {
+        Self {
+            connection,
+            session_id: session_id.to_string(),
+            root: root.to_path_buf(),
+            fs,
+        }
+    }
+
+    /// ACP wants absolute paths; the model usually gives project-relative ones.
+    fn absolute(&self, args: &Value) -> Result<PathBuf> {
+        let raw = args
+            .get("path")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("missing 'path'"))?;
+        Ok(self.root.join(raw))
+    }
+
+    async fn read(&self, path: &Path, line: Option<u64>, limit: Option<u64>) -> Result<String> {
+        let mut params = json!({
+            "sessionId": self.session_id,
+            "path": path,
+        });
+        if let Some(line) = line {
+            params["line"] = json!(line);
+        }
+        if let Some(limit) = limit {
+            params["limit"] = json!(limit);
+        }
+        let result = self.connection.request("fs/read_text_file", params).await?;
+        result
+            .get("content")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .ok_or_else(|| anyhow!("editor returned no content for {}", path.display()))
+    }
+
+    async fn write(&self, path: &Path, content: &str) -> Result<()> {
+        self.connection
+            .request(
+                "fs/write_text_file",
+                json!({
+                    "sessionId": self.session_id,
+                    "path": path,
+                    "content": content,
+                }),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ToolProvider for EditorFiles {
+    fn id(&self) -> &str {
+        "editor"
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::Builtin
+    }
+
+    fn tools(&self) -> Vec<ToolDef> {
+        let mut tools = Vec::new();
+        if self.fs.read {
+            tools.push(ToolDef {
+                name: "read_file".into(),
+                description: "Read a file as it is open in the editor (unsaved changes \
+                              included). Optionally start at a 1-based line and limit the \
+                              number of lines."
+                    .into(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Project-relative or absolute path"},
+                        "line": {"type": "integer"},
+                        "limit": {"type": "integer"}
+                    },
+                    "required": ["path"]
+                }),
+            });
+        }
+        if self.fs.write {
+            tools.push(ToolDef {
+                name: "write_file".into(),
+                description: "Write a whole file through the editor, creating it if needed.".into(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string"},
+                        "content": {"type": "string"}
+                    },
+                    "required": ["path", "content"]
+                }),
+            });
+        }
+        if self.fs.read && self.fs.write {
+            tools.push(ToolDef {
+                name: "edit_file".into(),
+                description: "Replace one exact occurrence of old_text with new_text in a \
+                              file open in the editor."
+                    .into(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string"},
+                        "old_text": {"type": "string"},
+                        "new_text": {"type": "string"}
+                    },
+                    "required": ["path", "old_text", "new_text"]
+                }),
+            });
+        }
+        tools
+    }
+
+    fn effect(&self, tool: &str) -> ToolEffect {
+        if tool == "read_file" {
+            ToolEffect::ReadOnly
+        } else {
+            ToolEffect::Mutating
+        }
+    }
+
+    async fn call(&self, tool: &str, args: Value) -> Result<String> {
+        let path = self.absolute(&args)?;
+        let text = |key: &str| {
+            args.get(key)
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("missing '{key}'"))
+        };
+        match tool {
+            "read_file" => {
+                let line = args.get("line").and_then(Value::as_u64);
+                let limit = args.get("limit").and_then(Value::as_u64);
+                self.read(&path, line, limit).await
+            }
+            "write_file" => {
+                let content = text("content")?;
+                self.write(&path, content).await?;
+                Ok(format!(
+                    "Wrote {} ({} bytes)",
+                    path.display(),
+                    content.len()
+                ))
+            }
+            "edit_file" => {
+                let (old, new) = (text("old_text")?, text("new_text")?);
+                let current = self.read(&path, None, None).await?;
+                match current.matches(old).count() {
+                    0 => bail!("old_text not found in {}", path.display()),
+                    1 => {}
+                    n => bail!(
+                        "old_text appears {n} times in {}; include more context",
+                        path.display()
+                    ),
+                }
+                self.write(&path, &current.replacen(old, new, 1)).await?;
+                Ok(format!("Edited {}", path.display()))
+            }
+            _ => bail!("unknown tool '{tool}'"),
+        }
+    }
+}
+
+/// Asks the editor's user about calls the permission policy marks "ask".
+/// "Always allow" approves the tool for the rest of the session.
+pub struct AcpApprover {
+    connection: Arc<Connection>,
+    session_id: String,
+    always: tokio::sync::Mutex<HashSet<String>>,
+}
+
+impl AcpApprover {
+    pub fn new(connection: Arc<Connection>, session_id: &str) -> Self {
+        Self {
+            connection,
+            session_id: session_id.to_string(),
+            always: tokio::sync::Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl Approver for AcpApprover {
+    async fn approve(&self, request: &ApprovalRequest) -> Approval {
+        let mut always = self.always.lock().await;
+        if always.contains(&request.tool) {
+            return Approval::Approved;
+        }
+
+        let args = Redactor::global().redact_value(&request.args);
+        let mut title = tool_title(&request.tool, &args);
+        if let Some(ref reason) = request.reason {
+            title.push_str(&format!(" ({reason})"));
+        }
+        let call_id = request
+            .call_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let params = json!({
+            "sessionId": self.session_id,
+            "toolCall": {
+                "toolCallId": call_id,
+                "title": title,
+                "kind": tool_kind(&request.tool),
+                "status": "pending",
+                "rawInput": args,
+            },
+            "options": [
+                {"optionId": "allow_once", "name": "Allow", "kind": "allow_once"},
+                {"optionId": "allow_always", "name": "Always allow", "kind": "allow_always"},
+                {"optionId": "reject_once", "name": "Reject", "kind": "reject_once"},
+            ],
+        });
+        let response = match self
+            .connection
+            .request("session/request_permission", params)
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("Permission request failed: {}", e);
+                return Approval::Unavailable;
+            }
+        };
+        let selected = response
+            .pointer("/outcome/optionId")
+            .and_then(Value::as_str)
+            .filter(|_| response.pointer("/outcome/outcome") == Some(&json!("selected")));
+        match selected {
+            Some("allow_once") => Approval::Approved,
+            Some("allow_always") => {
+                always.insert(request.tool.clone());
+                Approval::Approved
+            }
+            _ => Approval::Rejected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    /// An editor that serves one in-memory file and approves "allow_always".
+    fn fake_editor() -> (Arc<Connection>, Arc<std::sync::Mutex<String>>) {
+        let (agent_io, editor_io) = tokio::io::duplex(64 * 1024);
+        let (agent_read, agent_write) = tokio::io::split(agent_io);
+        let (connection, _inbox) = Connection::start(agent_read, agent_write);
+        let file = Arc::new(std::sync::Mutex::new("fn main() {}\n".to_string()));
+        let buffer = file.clone();
+        tokio::spawn(async move {
+            let (read, mut write) = tokio::io::split(editor_io);
+            let mut lines = BufReader::new(read).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let message: Value = serde_json::from_str(&line).unwrap();
+                let result = match message["method"].as_str() {
+                    Some("fs/read_text_file") => {
+                        json!({"content": buffer.lock().unwrap().clone()})
+                    }
+                    Some("fs/write_text_file") => {
+                        *buffer.lock().unwrap() =
+                            message["params"]["content"].as_str().unwrap().to_string();
+                        Value::Null
+                    }
+                    Some("session/request_permission") => {
+                        json!({"outcome": {"outcome": "selected", "optionId": "allow_always"}})
+                    }
+                    _ => continue,
+                };
+                let reply = json!({"jsonrpc": "2.0", "id": message["id"], "result": result});
+                write
+                    .write_all(format!("{reply}\n").as_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+        (connection, file)
+    }
+
+    #[tokio::test]
+    async fn test_editor_files_round_trip() {
+        let (connection, file) = fake_editor();
+        let fs = FsCapabilities::from_client(
+            &json!({"fs": {"readTextFile": true, "writeTextFile": true}}),
+        );
+        let tools = EditorFiles::new(connection, "s1", Path::new("/repo"), fs);
+        assert_eq!(tools.tools().len(), 3);
+
+        let read = tools
+            .call("read_file", json!({"path": "src/main.rs"}))
+            .await
+            .unwrap();
+        assert_eq!(read, "fn main() {}\n");
+        tools
+            .call(
+                "edit_file",
+                json!({"path": "src/main.rs", "old_text": "{}", "new_text": "{ run() }"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(*file.lock().unwrap(), "fn main() { run() }\n");
+        assert!(tools
+            .call(
+                "edit_file",
+                json!({"path": "src/main.rs", "old_text": "missing", "new_text": ""}),
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_approver_remembers_always() {
+        let (connection, _) = fake_editor();
+        let approver = AcpApprover::new(connection, "s1");
+        let request = ApprovalRequest {
+            tool: "run_command".into(),
+            provider: "shell".into(),
+            source: ToolSource::Mcp,
+            args: json!({"command": "cargo test"}),
+            reason: None,
+            call_id: Some("c1".into()),
+        };
+        assert_eq!(approver.approve(&request).await, Approval::Approved);
+        assert!(approver.always.lock().await.contains("run_command"));
+    }
+}
@@ -0,0 +1,246 @@
+// src/api/approvals.rs — Approval requests for daemon and API runs
+//
+// Nobody sits at a terminal in the daemon, so "ask" decisions become
+// pending approvals. Each one fires the `approval.requested` webhook and
+// shows up in the stream of the chat completion that made the call; a
+// client answers with `POST /api/v1/approvals/{id}`. Calls nobody answers
+// before `[api] approval_timeout_seconds` are refused.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use super::webhooks::{self, WebhookEvent};
+use super::{check_auth, ApiState, ErrorResponse};
+use crate::infra::config::WebhookConfig;
+use crate::security::redact::Redactor;
+use crate::tools::policy::{Approval, ApprovalRequest, Approver};
+
+/// Told about each approval requested by calls of the current task.
+pub type ApprovalListener = Arc<dyn Fn(&PendingApproval) + Send + Sync>;
+
+tokio::task_local! {
+    static LISTENER: ApprovalListener;
+}
+
+/// Run `fut` with the approvals its tool calls request reported to
+/// `listener` (e.g. the stream of the completion that runs it).
+pub async fn with_listener<F: Future>(listener: ApprovalListener, fut: F) -> F::Output {
+    LISTENER.scope(listener, fut).await
+}
+
+/// A tool call waiting for a client's answer, as clients see it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingApproval {
+    pub id: String,
+    pub tool: String,
+    pub provider: String,
+    pub source: String,
+    /// Arguments with secrets redacted.
+    pub args: serde_json::Value,
+    pub reason: Option<String>,
+    /// RFC 3339, UTC.
+    pub requested_at: String,
+}
+
+/// Approvals waiting for an answer, shared by the approver and the API.
+#[derive(Default)]
+pub struct ApprovalQueue {
+    pending: Mutex<HashMap<String, (PendingApproval, oneshot::Sender<bool>)>>,
+}
+
+impl ApprovalQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pending approvals, oldest first.
+    pub fn list(&self) -> Vec<PendingApproval> {
+        let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<PendingApproval> = pending.values().map(|(p, _)| p.clone()).collect();
+        list.sort_by(|a, b| a.requested_at.cmp(&b.requested_at));
+        list
+    }
+
+    /// Answer approval `id`. `false` if it isn't pending (answered, timed
+    /// out, or never existed).
+    pub fn answer(&self, id: &str, approved: bool) -> bool {
+        let entry = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+        match entry {
+            Some((_, answer)) => answer.send(approved).is_ok(),
+            None => false,
+        }
+    }
+
+    fn insert(&self, approval: PendingApproval) -> oneshot::Receiver<bool> {
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(approval.id.clone(), (approval, tx));
+        rx
+    }
+
+    fn remove(&self, id: &str) {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+    }
+}
+
+/// Resolves "ask" decisions through the `ApprovalQueue`, waiting up to
+/// `timeout` for a client to answer.
+pub struct QueueApprover {
+    queue: Arc<ApprovalQueue>,
+    timeout: Duration,
+    webhooks: WebhookConfig,
+}
+
+impl QueueApprover {
+    pub fn new(queue: Arc<ApprovalQueue>, timeout: Duration, webhooks: WebhookConfig) -> Self {
+        Self {
+            queue,
+            timeout,
+            webhooks,
+        }
+    }
+}
+
+#[async_trait]
+impl Approver for QueueApprover {
+    async fn approve(&self, request: &ApprovalRequest) -> Approval {
+        let approval = PendingApproval {
+            id: uuid::Uuid::new_v4().to_string(),
+            tool: request.tool.clone(),
+            provider: request.provider.clone(),
+            source: request.source.to_string(),
+            args: Redactor::global().redact_value(&request.args),
+            reason: request.reason.clone(),
+            requested_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let id = approval.id.clone();
+        let answer = self.queue.insert(approval.clone());
+        tracing::info!(tool = %request.tool, approval = %id, "Tool call waiting for approval");
+        let _ = LISTENER.try_with(|listener| listener(&approval));
+        webhooks::fire_webhook(&self.webhooks, WebhookEvent::ApprovalRequested(approval));
+
+        match tokio::time::timeout(self.timeout, answer).await {
+            Ok(Ok(true)) => Approval::Approved,
+            Ok(Ok(false)) => Approval::Rejected,
+            Ok(Err(_)) => Approval::Unavailable,
+            Err(_) => {
+                self.queue.remove(&id);
+                Approval::TimedOut
+            }
+        }
+    }
+}
+
+/// Request body for answering an approval.
+#[derive(Debug, Deserialize)]
+pub struct ApprovalAnswer {
+    pub approved: bool,
+}
+
+/// GET /api/v1/approvals — Tool calls waiting for an answer.
+pub(crate) async fn list_approvals(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PendingApproval>>, (StatusCode, Json<ErrorResponse>)> {
+    check_auth(&state, &headers)?;
+    Ok(Json(state.approvals.list()))
+}
+
+/// POST /api/v1/approvals/{id} — Approve or reject a pending tool call.
+pub(crate) async fn answer_approval(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(body): Json<ApprovalAnswer>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    check_auth(&state, &headers)?;
+    if state.approvals.answer(&id, body.approved) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("No pending approval '{id}'"),
+            }),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::ToolSource;
+    use serde_json::json;
+
+    fn request() -> ApprovalRequest {
+        ApprovalRequest {
+            tool: "deploy".into(),
+            provider: "ops".into(),
+            source: ToolSource::Mcp,
+            args: json!({ "env": "prod" }),
+            reason: Some("deploys need a person".into()),
+            call_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_answered_approval_reaches_the_call() {
+        let queue = Arc::new(ApprovalQueue::new());
+        let approver = QueueApprover::new(
+            queue.clone(),
+            Duration::from_secs(5),
+            WebhookConfig::default(),
+        );
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let listener: ApprovalListener = Arc::new(move |a| sink.lock().unwrap().push(a.id.clone()));
+
+        let answering = async {
+            while queue.list().is_empty() {
+                tokio::task::yield_now().await;
+            }
+            let pending = queue.list();
+            assert_eq!(pending[0].tool, "deploy");
+            assert!(queue.answer(&pending[0].id, true));
+        };
+        let request = request();
+        let (approval, ()) = tokio::join!(
+            with_listener(listener, approver.approve(&request)),
+            answering
+        );
+        assert_eq!(approval, Approval::Approved);
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        assert!(queue.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_approval_times_out() {
+        let queue = Arc::new(ApprovalQueue::new());
+        let approver = QueueApprover::new(
+            queue.clone(),
+            Duration::from_millis(20),
+            WebhookConfig::default(),
+        );
+        assert_eq!(approver.approve(&request()).await, Approval::TimedOut);
+        assert!(queue.list().is_empty());
+        assert!(!queue.answer("missing", true));
+    }
+}
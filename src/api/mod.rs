@@ -1,11 +1,15 @@
 // src/api/mod.rs — Lightweight HTTP API server for external integrations
 //
 // Runs alongside the daemon on localhost:9742 (configurable).
-// Provides task CRUD, status, cost, and cancel endpoints.
+// Provides task CRUD, status, cost, cancel and approval endpoints, plus an
+// OpenAI-compatible chat completions endpoint under /v1.
 // Bearer token auth when configured. CORS headers for local web UIs.
 
+pub mod approvals;
+pub mod openai;
 pub mod webhooks;
 
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 
 use axum::extract::{Path, State};
@@ -16,8 +20,10 @@ use serde::{Deserialize, Serialize};
 use tower_http::cors::CorsLayer;
 
 use crate::core::state::{self, TaskHistoryEntry, TaskState};
-use crate::infra::config::ApiConfig;
+use crate::infra::config::{ApiConfig, TaskProfile};
 use crate::memory::store::Store;
+use approvals::ApprovalQueue;
+use openai::CompletionJob;
 
 /// Shared state for API handlers.
 #[derive(Clone)]
@@ -28,6 +34,16 @@ pub struct ApiState {
     pub task_queue: Arc<Mutex<Vec<TaskRequest>>>,
     /// Set of task IDs that have been requested to cancel.
     pub cancel_requests: Arc<Mutex<Vec<String>>>,
+    /// Chat completions for the daemon to run. `None` when nothing
+    /// can run them; `/v1/chat/completions` then answers 503.
+    pub completions: Option<tokio::sync::mpsc::UnboundedSender<CompletionJob>>,
+    /// One permit per completion allowed to run at once; when none are
+    /// left `/v1/chat/completions` answers 429.
+    pub completion_slots: Arc<tokio::sync::Semaphore>,
+    /// Profiles served as models, from `[api.profiles]`.
+    pub profiles: BTreeMap<String, TaskProfile>,
+    /// Tool calls waiting for a client to approve them.
+    pub approvals: Arc<ApprovalQueue>,
 }
 
 /// Request body for creating a task.
@@ -97,6 +113,10 @@ pub fn build_router(state: ApiState) -> Router {
         .route("/api/v1/status", get(get_status))
         .route("/api/v1/cost", get(get_cost))
         .route("/api/v1/health", get(health))
+        .route("/api/v1/approvals", get(approvals::list_approvals))
+        .route("/api/v1/approvals/{id}", post(approvals::answer_approval))
+        .route("/v1/models", get(openai::list_models))
+        .route("/v1/chat/completions", post(openai::chat_completions))
         .layer(cors)
         .with_state(state)
 }
@@ -374,6 +394,10 @@ mod tests {
             token: None,
             task_queue: Arc::new(Mutex::new(Vec::new())),
             cancel_requests: Arc::new(Mutex::new(Vec::new())),
+            completions: None,
+            completion_slots: Arc::new(tokio::sync::Semaphore::new(4)),
+            profiles: BTreeMap::new(),
+            approvals: Arc::new(ApprovalQueue::new()),
         }
     }
 
@@ -383,6 +407,10 @@ mod tests {
             token: Some(token.to_string()),
             task_queue: Arc::new(Mutex::new(Vec::new())),
             cancel_requests: Arc::new(Mutex::new(Vec::new())),
+            completions: None,
+            completion_slots: Arc::new(tokio::sync::Semaphore::new(4)),
+            profiles: BTreeMap::new(),
+            approvals: Arc::new(ApprovalQueue::new()),
         }
     }
 
@@ -584,6 +612,208 @@ mod tests {
         assert_eq!(json["total_events_24h"], 0);
     }
 
+    /// State whose completions are answered by a stand-in for the daemon.
+    fn test_state_with_completions() -> ApiState {
+        use crate::core::types::{ExecutionOutput, IterationDecision, TaskResult};
+        use crate::provider::TokenUsage;
+        use openai::CompletionEvent;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<CompletionJob>();
+        tokio::spawn(async move {
+            while let Some(job) = rx.recv().await {
+                let _ = job
+                    .events
+                    .send(CompletionEvent::Progress("Iteration 1/3".into()));
+                let usage = TokenUsage {
+                    input_tokens: 120,
+                    output_tokens: 30,
+                    ..Default::default()
+                };
+                let result = TaskResult {
+                    task_id: job.task.id.clone(),
+                    output: ExecutionOutput {
+                        content: format!("answer to {}", job.task.description),
+                        usage: usage.clone(),
+                        tool_calls_made: 0,
+                        files_modified: vec![],
+                        tool_calls: vec![],
+                    },
+                    iterations: job.profile.iterations.unwrap_or(1),
+                    total_tokens: 150,
+                    usage,
+                    cost: 0.0,
+                    learnings_saved: 0,
+                    skills_used: vec![],
+                    final_score: 0.9,
+                    decision: IterationDecision::Accept,
+                    history: vec![],
+                    error: None,
+                };
+                let _ = job.events.send(CompletionEvent::Done(Ok(Box::new(result))));
+            }
+        });
+        let mut state = test_state();
+        state.completions = Some(tx);
+        state.profiles.insert(
+            "quick".into(),
+            TaskProfile {
+                iterations: Some(1),
+                ..Default::default()
+            },
+        );
+        state
+    }
+
+    fn completion_request(body: &str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_models_includes_profiles() {
+        let app = build_router(test_state_with_completions());
+
+        let req = Request::builder()
+            .uri("/v1/models")
+            .body(Body::empty())
+            .unwrap();
+
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let ids: Vec<&str> = json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["openkoi", "quick"]);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion() {
+        let app = build_router(test_state_with_completions());
+
+        let resp = app
+            .oneshot(completion_request(
+                r#"{"model": "quick", "messages": [{"role": "user", "content": "Fix the bug"}]}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["object"], "chat.completion");
+        assert_eq!(json["model"], "quick");
+        assert_eq!(
+            json["choices"][0]["message"]["content"],
+            "answer to Fix the bug"
+        );
+        assert_eq!(json["choices"][0]["finish_reason"], "stop");
+        assert_eq!(json["usage"]["prompt_tokens"], 120);
+        assert_eq!(json["usage"]["completion_tokens"], 30);
+        assert_eq!(json["usage"]["total_tokens"], 150);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_stream() {
+        let app = build_router(test_state_with_completions());
+
+        let resp = app
+            .oneshot(completion_request(
+                r#"{"model": "openkoi", "stream": true, "stream_options": {"include_usage": true},
+                    "messages": [{"role": "user", "content": "Fix the bug"}]}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        let events: Vec<&str> = text
+            .lines()
+            .filter_map(|l| l.strip_prefix("data: "))
+            .collect();
+        assert_eq!(events.last(), Some(&"[DONE]"));
+        let chunks: Vec<serde_json::Value> = events[..events.len() - 1]
+            .iter()
+            .map(|e| serde_json::from_str(e).unwrap())
+            .collect();
+        assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+        assert_eq!(
+            chunks[1]["choices"][0]["delta"]["reasoning_content"],
+            "Iteration 1/3\n"
+        );
+        assert_eq!(
+            chunks[2]["choices"][0]["delta"]["content"],
+            "answer to Fix the bug"
+        );
+        assert_eq!(chunks[3]["choices"][0]["finish_reason"], "stop");
+        assert_eq!(chunks[4]["usage"]["total_tokens"], 150);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_unknown_model() {
+        let app = build_router(test_state_with_completions());
+
+        let resp = app
+            .oneshot(completion_request(
+                r#"{"model": "gpt-4o", "messages": [{"role": "user", "content": "hi"}]}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"]["type"], "invalid_request_error");
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_over_limit() {
+        let mut state = test_state_with_completions();
+        state.completion_slots = Arc::new(tokio::sync::Semaphore::new(1));
+        let running = state.completion_slots.clone().try_acquire_owned().unwrap();
+        let app = build_router(state);
+        let body = r#"{"model": "quick", "messages": [{"role": "user", "content": "hi"}]}"#;
+
+        let resp = app.clone().oneshot(completion_request(body)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        let bytes = axum::body::to_bytes(resp.into_body(), 1024 * 1024)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["error"]["type"], "rate_limit_error");
+
+        drop(running);
+        let resp = app.oneshot(completion_request(body)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_without_runner() {
+        let app = build_router(test_state());
+
+        let resp = app
+            .oneshot(completion_request(
+                r#"{"model": "openkoi", "messages": [{"role": "user", "content": "hi"}]}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
     #[test]
     fn test_task_request_deserialization() {
         let json = r#"{"description": "Fix bug", "category": "bugfix", "max_iterations": 5}"#;
@@ -0,0 +1,372 @@
+// src/api/openai.rs — OpenAI-compatible chat completions
+//
+// `/v1/chat/completions` and `/v1/models` let any OpenAI client get answers
+// from the iteration loop. The request's "model" names a profile from
+// `[api.profiles]`, or "openkoi" for the plain config. Handlers only
+// translate: each request takes one of `completion_slots` (429 when none
+// are left) and becomes a `CompletionJob`. The daemon runs each job as its
+// own local task next to its loop, sending back progress and the result.
+
+use std::convert::Infallible;
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, OwnedSemaphorePermit};
+
+use super::{check_auth, ApiState};
+use crate::cli::chat::remember_exchange;
+use crate::core::types::{IterationDecision, TaskInput, TaskResult};
+use crate::infra::config::TaskProfile;
+
+/// Model name of the profile that uses the config as is.
+pub const DEFAULT_PROFILE: &str = "openkoi";
+
+/// A chat completion for the daemon to run.
+pub struct CompletionJob {
+    pub task: TaskInput,
+    pub profile: TaskProfile,
+    /// Earlier turns of the conversation, summarized.
+    pub history: Option<String>,
+    /// Closed when the client goes away.
+    pub events: mpsc::UnboundedSender<CompletionEvent>,
+    /// Held until the job is dropped, freeing its completion slot.
+    pub slot: OwnedSemaphorePermit,
+}
+
+/// What the daemon reports back for a `CompletionJob`.
+#[derive(Debug)]
+pub enum CompletionEvent {
+    /// One line of iteration progress.
+    Progress(String),
+    Done(Result<Box<TaskResult>, String>),
+}
+
+/// Request body for `POST /v1/chat/completions`. Sampling parameters are
+/// accepted and ignored; the profile decides how the task runs.
+#[derive(Debug, Deserialize)]
+pub struct ChatCompletionRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[serde(default)]
+    pub stream: bool,
+    #[serde(default)]
+    pub stream_options: Option<StreamOptions>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    /// A string, or an array of content parts (only text parts are read).
+    #[serde(default)]
+    pub content: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StreamOptions {
+    #[serde(default)]
+    pub include_usage: bool,
+}
+
+/// GET /v1/models — The built-in profile and every configured one.
+pub(crate) async fn list_models(State(state): State<ApiState>, headers: HeaderMap) -> Response {
+    if let Err(denied) = check_auth(&state, &headers) {
+        return error_response(denied.0, &denied.1.error, "invalid_request_error");
+    }
+    let data: Vec<Value> = std::iter::once(DEFAULT_PROFILE)
+        .chain(state.profiles.keys().map(String::as_str))
+        .map(|id| json!({ "id": id, "object": "model", "created": 0, "owned_by": "openkoi" }))
+        .collect();
+    Json(json!({ "object": "list", "data": data })).into_response()
+}
+
+/// POST /v1/chat/completions — Run the conversation's last user message as
+/// a task and answer with the accepted output.
+pub(crate) async fn chat_completions(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Json(body): Json<ChatCompletionRequest>,
+) -> Response {
+    if let Err(denied) = check_auth(&state, &headers) {
+        return error_response(denied.0, &denied.1.error, "invalid_request_error");
+    }
+    let profile = if body.model == DEFAULT_PROFILE {
+        TaskProfile::default()
+    } else if let Some(profile) = state.profiles.get(&body.model) {
+        profile.clone()
+    } else {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            &format!("The model '{}' does not exist", body.model),
+            "invalid_request_error",
+        );
+    };
+    let (task, history) = match task_from_messages(&body.messages) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            return error_response(StatusCode::BAD_REQUEST, &message, "invalid_request_error")
+        }
+    };
+    let Some(ref jobs) = state.completions else {
+        return error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "No model provider is available to run completions",
+            "server_error",
+        );
+    };
+    let Ok(slot) = state.completion_slots.clone().try_acquire_owned() else {
+        return error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many completions are running; try again later",
+            "rate_limit_error",
+        );
+    };
+
+    let (events, mut rx) = mpsc::unbounded_channel();
+    let job = CompletionJob {
+        task,
+        profile,
+        history,
+        events,
+        slot,
+    };
+    if jobs.send(job).is_err() {
+        return error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The daemon is shutting down",
+            "server_error",
+        );
+    }
+
+    let id = format!("chatcmpl-{}", uuid::Uuid::new_v4().simple());
+    let created = chrono::Utc::now().timestamp();
+    if body.stream {
+        let include_usage = body.stream_options.is_some_and(|o| o.include_usage);
+        let chunks = stream_chunks(rx, id, created, body.model, include_usage);
+        return Sse::new(chunks)
+            .keep_alive(KeepAlive::default())
+            .into_response();
+    }
+
+    while let Some(event) = rx.recv().await {
+        let CompletionEvent::Done(outcome) = event else {
+            continue;
+        };
+        return match outcome.and_then(accepted) {
+            Ok(result) => Json(json!({
+                "id": id,
+                "object": "chat.completion",
+                "created": created,
+                "model": body.model,
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": result.output.content },
+                    "finish_reason": finish_reason(&result),
+                }],
+                "usage": usage(&result),
+            }))
+            .into_response(),
+            Err(message) => error_response(StatusCode::BAD_GATEWAY, &message, "server_error"),
+        };
+    }
+    error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "The task ended without a result",
+        "server_error",
+    )
+}
+
+/// SSE chunks for a streamed completion. Iteration progress is sent as
+/// `reasoning_content` deltas while the loop runs (clients that don't know
+/// the field skip it); the accepted output follows as `content`.
+fn stream_chunks(
+    mut rx: mpsc::UnboundedReceiver<CompletionEvent>,
+    id: String,
+    created: i64,
+    model: String,
+    include_usage: bool,
+) -> impl futures::Stream<Item = Result<Event, Infallible>> {
+    let chunk = move |delta: Value, finish_reason: Value| {
+        json!({
+            "id": id,
+            "object": "chat.completion.chunk",
+            "created": created,
+            "model": model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+        })
+    };
+    async_stream::stream! {
+        yield Ok(data(chunk(json!({ "role": "assistant", "content": "" }), Value::Null)));
+        while let Some(event) = rx.recv().await {
+            match event {
+                CompletionEvent::Progress(line) => {
+                    yield Ok(data(chunk(json!({ "reasoning_content": line + "\n" }), Value::Null)));
+                }
+                CompletionEvent::Done(outcome) => {
+                    match outcome.and_then(accepted) {
+                        Ok(result) => {
+                            let content = json!({ "content": result.output.content });
+                            yield Ok(data(chunk(content, Value::Null)));
+                            yield Ok(data(chunk(json!({}), json!(finish_reason(&result)))));
+                            if include_usage {
+                                let mut last = chunk(json!({}), Value::Null);
+                                last["choices"] = json!([]);
+                                last["usage"] = usage(&result);
+                                yield Ok(data(last));
+                            }
+                        }
+                        Err(message) => {
+                            yield Ok(data(error_body(&message, "server_error")));
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+        yield Ok(Event::default().data("[DONE]"));
+    }
+}
+
+fn data(value: Value) -> Event {
+    Event::default().data(value.to_string())
+}
+
+/// Split OpenAI messages into the task (the last user message, with system
+/// messages as context) and a summary of the turns before it.
+pub fn task_from_messages(messages: &[ChatMessage]) -> Result<(TaskInput, Option<String>), String> {
+    let Some((last, earlier)) = messages.split_last() else {
+        return Err("messages must not be empty".into());
+    };
+    if last.role != "user" {
+        return Err("the last message must be from the user".into());
+    }
+    let description = content_text(&last.content);
+    if description.trim().is_empty() {
+        return Err("the last user message has no text".into());
+    }
+
+    let mut system = Vec::new();
+    let mut history = String::new();
+    let mut pending_user: Option<String> = None;
+    for message in earlier {
+        let text = content_text(&message.content);
+        match message.role.as_str() {
+            "system" | "developer" => system.push(text),
+            "user" => {
+                if let Some(unanswered) = pending_user.take() {
+                    remember_exchange(&mut history, &unanswered, "");
+                }
+                pending_user = Some(text);
+            }
+            "assistant" => {
+                remember_exchange(&mut history, pending_user.as_deref().unwrap_or(""), &text);
+                pending_user = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(unanswered) = pending_user {
+        remember_exchange(&mut history, &unanswered, "");
+    }
+
+    let mut task = TaskInput::new(description);
+    let system: Vec<String> = system.into_iter().filter(|s| !s.is_empty()).collect();
+    if !system.is_empty() {
+        task.context = Some(system.join("\n\n"));
+    }
+    Ok((task, (!history.is_empty()).then_some(history)))
+}
+
+/// Text of a message's content: the string itself, or its text parts.
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter(|p| p["type"] == "text")
+            .filter_map(|p| p["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// A run counts as failed when no iteration produced output.
+fn accepted(result: Box<TaskResult>) -> Result<Box<TaskResult>, String> {
+    let all_failed = result
+        .history
+        .iter()
+        .all(|i| matches!(i.decision, IterationDecision::AbortError));
+    match result.error {
+        Some(ref error) if all_failed => Err(error.clone()),
+        _ => Ok(result),
+    }
+}
+
+fn finish_reason(result: &TaskResult) -> &'static str {
+    match result.decision {
+        IterationDecision::AbortBudget => "length",
+        _ => "stop",
+    }
+}
+
+/// Usage summed over every iteration: executor, evaluator and tool turns.
+fn usage(result: &TaskResult) -> Value {
+    let prompt = result.usage.input_tokens
+        + result.usage.cache_read_tokens
+        + result.usage.cache_write_tokens;
+    let completion = result.usage.output_tokens;
+    json!({
+        "prompt_tokens": prompt,
+        "completion_tokens": completion,
+        "total_tokens": prompt + completion,
+    })
+}
+
+fn error_body(message: &str, kind: &str) -> Value {
+    json!({ "error": { "message": message, "type": kind, "param": null, "code": null } })
+}
+
+fn error_response(status: StatusCode, message: &str, kind: &str) -> Response {
+    (status, Json(error_body(message, kind))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: Value) -> ChatMessage {
+        ChatMessage {
+            role: role.into(),
+            content,
+        }
+    }
+
+    #[test]
+    fn test_task_from_messages() {
+        let messages = vec![
+            message("system", json!("Answer in French.")),
+            message("user", json!("What is a borrow?")),
+            message("assistant", json!("Un emprunt...")),
+            message(
+                "user",
+                json!([{"type": "text", "text": "Show an example"}, {"type": "image_url"}]),
+            ),
+        ];
+        let (task, history) = task_from_messages(&messages).unwrap();
+        assert_eq!(task.description, "Show an example");
+        assert_eq!(task.context.as_deref(), Some("Answer in French."));
+        let history = history.unwrap();
+        assert!(history.contains("What is a borrow?"));
+        assert!(history.contains("Un emprunt..."));
+
+        assert!(task_from_messages(&[]).is_err());
+        assert!(task_from_messages(&[message("assistant", json!("hi"))]).is_err());
+        let (_, history) = task_from_messages(&[message("user", json!("hi"))]).unwrap();
+        assert!(history.is_none());
+    }
+}
@@ -1,12 +1,13 @@
 // src/api/webhooks.rs — Outbound webhook callbacks on lifecycle events
 //
 // Fires HTTP POST requests to configured URLs when tasks complete,
-// fail, or trigger budget warnings. Non-blocking (spawns a tokio task).
+// fail, trigger budget warnings, or wait for a tool call approval. Non-blocking (spawns a tokio task).
 
 use std::sync::LazyLock;
 
 use serde::Serialize;
 
+use super::approvals::PendingApproval;
 use crate::infra::config::WebhookConfig;
 
 /// Shared HTTP client for webhook delivery (avoids creating a new client per call).
@@ -42,6 +43,8 @@ pub enum WebhookEvent {
         budget_limit: f64,
         percentage_used: f64,
     },
+    /// A tool call is waiting for approval.
+    ApprovalRequested(PendingApproval),
 }
 
 /// JSON payload sent to the webhook URL.
@@ -60,6 +63,7 @@ pub fn fire_webhook(config: &WebhookConfig, event: WebhookEvent) {
         WebhookEvent::TaskComplete { .. } => config.on_task_complete.clone(),
         WebhookEvent::TaskFailed { .. } => config.on_task_failed.clone(),
         WebhookEvent::BudgetWarning { .. } => config.on_budget_warning.clone(),
+        WebhookEvent::ApprovalRequested(_) => config.on_approval_requested.clone(),
     };
 
     let Some(url) = url else {
@@ -120,6 +124,10 @@ fn build_payload(event: &WebhookEvent) -> WebhookPayload {
                 "percentage_used": percentage_used,
             }),
         ),
+        WebhookEvent::ApprovalRequested(approval) => (
+            "approval.requested",
+            serde_json::to_value(approval).unwrap_or_default(),
+        ),
     };
 
     WebhookPayload {
@@ -210,6 +218,24 @@ mod tests {
         assert_eq!(payload.data["percentage_used"], 92.5);
     }
 
+    #[test]
+    fn test_build_payload_approval_requested() {
+        let event = WebhookEvent::ApprovalRequested(PendingApproval {
+            id: "a-1".into(),
+            tool: "deploy".into(),
+            provider: "ops".into(),
+            source: "mcp".into(),
+            args: serde_json::json!({ "env": "prod" }),
+            reason: None,
+            requested_at: "2026-01-01T00:00:00Z".into(),
+        });
+
+        let payload = build_payload(&event);
+        assert_eq!(payload.event, "approval.requested");
+        assert_eq!(payload.data["id"], "a-1");
+        assert_eq!(payload.data["args"]["env"], "prod");
+    }
+
     #[test]
     fn test_fire_webhook_no_url_configured() {
         // With no URLs configured, fire_webhook should just return without panicking
@@ -0,0 +1,181 @@
+// src/cli/audit.rs — Query and verify the tool-call audit log
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::security::audit::{AuditLog, AuditRecord};
+
+/// Filters for `openkoi audit`.
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub task: Option<String>,
+    /// Tool name, or integration id for messages.
+    pub tool: Option<String>,
+    pub channel: Option<String>,
+    /// RFC 3339 timestamp or `YYYY-MM-DD`.
+    pub since: Option<String>,
+    pub limit: usize,
+}
+
+/// Print matching audit records, or verify the hash chain.
+pub async fn run_audit(query: AuditQuery, verify: bool, json: bool) -> anyhow::Result<()> {
+    let log = AuditLog::new(AuditLog::default_path());
+
+    if verify {
+        let result = log.verify()?;
+        match result.broken {
+            None => {
+                println!(
+                    "Audit log intact: {} record(s) in {}",
+                    result.records,
+                    log.path().display()
+                );
+                return Ok(());
+            }
+            Some((line, reason)) => {
+                anyhow::bail!(
+                    "Audit log chain broken at line {} of {}: {} ({} record(s) verified before it)",
+                    line,
+                    log.path().display(),
+                    reason,
+                    result.records
+                );
+            }
+        }
+    }
+
+    let since = query.since.as_deref().map(parse_since).transpose()?;
+    let records = log.read()?;
+    let mut matching: Vec<&AuditRecord> = records
+        .iter()
+        .filter(|r| matches(r, &query, since))
+        .collect();
+    if query.limit > 0 && matching.len() > query.limit {
+        matching.drain(..matching.len() - query.limit);
+    }
+
+    if json {
+        for record in matching {
+            println!("{}", serde_json::to_string(record)?);
+        }
+        return Ok(());
+    }
+
+    if matching.is_empty() {
+        println!("No audit records found in {}.", log.path().display());
+        return Ok(());
+    }
+    for record in matching {
+        println!("{}", format_record(record));
+    }
+    Ok(())
+}
+
+fn matches(record: &AuditRecord, query: &AuditQuery, since: Option<DateTime<Utc>>) -> bool {
+    if let Some(ref task) = query.task {
+        if !record
+            .task_id
+            .as_deref()
+            .is_some_and(|t| t.starts_with(task.as_str()))
+        {
+            return false;
+        }
+    }
+    if let Some(ref tool) = query.tool {
+        if record.tool.as_deref() != Some(tool.as_str()) && record.provider != *tool {
+            return false;
+        }
+    }
+    if let Some(ref channel) = query.channel {
+        if record.channel.as_deref() != Some(channel.as_str()) {
+            return false;
+        }
+    }
+    if let Some(since) = since {
+        let at = DateTime::parse_from_rfc3339(&record.timestamp).map(|t| t.with_timezone(&Utc));
+        if !at.is_ok_and(|t| t >= since) {
+            return false;
+        }
+    }
+    true
+}
+
+fn parse_since(value: &str) -> anyhow::Result<DateTime<Utc>> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(value) {
+        return Ok(t.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        anyhow::anyhow!(
+            "Invalid --since '{}': expected YYYY-MM-DD or RFC 3339",
+            value
+        )
+    })?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+}
+
+fn format_record(r: &AuditRecord) -> String {
+    let target = match (&r.tool, &r.channel) {
+        (Some(tool), _) => format!("{} ({} {})", tool, r.source, r.provider),
+        (None, Some(channel)) => format!("{} -> {}", r.provider, channel),
+        (None, None) => r.provider.clone(),
+    };
+    let task = match (&r.task_id, r.iteration) {
+        (Some(id), Some(i)) => format!(" task {} #{}", &id[..id.len().min(8)], i),
+        (Some(id), None) => format!(" task {}", &id[..id.len().min(8)]),
+        _ => String::new(),
+    };
+    let rule = r
+        .rule
+        .as_ref()
+        .map(|rule| format!(" [{}]", rule))
+        .unwrap_or_default();
+    format!(
+        "{:>5}  {}  {:<9} {:<7} {}{}  {}ms{}\n       args: {}",
+        r.seq, r.timestamp, r.kind, r.outcome, target, task, r.duration_ms, rule, r.args
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::audit::AuditKind;
+
+    #[test]
+    fn test_filters() {
+        let record = AuditRecord {
+            kind: AuditKind::Message,
+            provider: "slack".into(),
+            channel: Some("#ops".into()),
+            task_id: Some("abcdef-123".into()),
+            timestamp: "2026-10-01T12:00:00+00:00".into(),
+            ..Default::default()
+        };
+        let query = |q: AuditQuery| {
+            matches(
+                &record,
+                &q,
+                q.since.as_deref().map(|s| parse_since(s).unwrap()),
+            )
+        };
+
+        assert!(query(AuditQuery::default()));
+        assert!(query(AuditQuery {
+            tool: Some("slack".into()),
+            channel: Some("#ops".into()),
+            task: Some("abcdef".into()),
+            ..Default::default()
+        }));
+        assert!(!query(AuditQuery {
+            channel: Some("#general".into()),
+            ..Default::default()
+        }));
+        assert!(query(AuditQuery {
+            since: Some("2026-10-01".into()),
+            ..Default::default()
+        }));
+        assert!(!query(AuditQuery {
+            since: Some("2026-10-02".into()),
+            ..Default::default()
+        }));
+        assert!(parse_since("yesterday").is_err());
+    }
+}
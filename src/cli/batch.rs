@@ -0,0 +1,818 @@
+// src/cli/batch.rs — Run a manifest of tasks across working directories
+//
+// Each entry runs as its own `openkoi` process in the entry's directory, so
+// it gets its own orchestrator, tools, MCP servers and language servers.
+// Entries run with bounded parallelism under a shared cost cap; the
+// aggregate report is rewritten after every entry so `--resume` can pick up
+// the ones that did not succeed.
+
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Instant;
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use super::run::RunOutcome;
+use super::{Cli, OutputFormat};
+use crate::infra::config::Config;
+
+/// Entries are not started with less budget than this.
+const MIN_ENTRY_BUDGET_USD: f64 = 0.01;
+/// Lines of a failed entry's stderr kept in the report.
+const ERROR_TAIL_LINES: usize = 20;
+
+/// A `tasks.yaml` manifest.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchManifest {
+    /// Entries running at once (default 4).
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    /// Spending cap for the whole batch, in USD.
+    #[serde(default)]
+    pub max_cost_usd: Option<f64>,
+    /// Settings for entries that don't set their own.
+    #[serde(default)]
+    pub defaults: EntrySettings,
+    pub tasks: Vec<BatchEntry>,
+}
+
+/// Per-entry settings, also used as manifest-wide defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EntrySettings {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub iterations: Option<u8>,
+    #[serde(default)]
+    pub quality: Option<f32>,
+    /// Evaluator skill to judge the entry with.
+    #[serde(default)]
+    pub evaluator: Option<String>,
+    /// Spending cap for one entry (default: `safety.max_cost_usd`).
+    #[serde(default)]
+    pub max_cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchEntry {
+    /// Name in the report, and the key `--resume` matches on (default:
+    /// the entry's position, starting at 1).
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(alias = "task")]
+    pub description: String,
+    /// Working directory, relative to the manifest (default: its directory).
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+    #[serde(flatten)]
+    pub settings: EntrySettings,
+}
+
+impl BatchManifest {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        let manifest: Self =
+            serde_yml::from_str(&text).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        if manifest.tasks.is_empty() {
+            anyhow::bail!("{} lists no tasks", path.display());
+        }
+        Ok(manifest)
+    }
+
+    /// Entries with defaults applied and directories resolved against `base`.
+    fn jobs(
+        &self,
+        base: &Path,
+        cli_model: Option<&str>,
+        cli_evaluator: Option<&str>,
+        config: &Config,
+    ) -> anyhow::Result<Vec<Job>> {
+        let mut seen = HashSet::new();
+        let mut jobs = Vec::with_capacity(self.tasks.len());
+        for (i, entry) in self.tasks.iter().enumerate() {
+            let id = entry.id.clone().unwrap_or_else(|| (i + 1).to_string());
+            if !seen.insert(id.clone()) {
+                anyhow::bail!("duplicate task id '{id}'");
+            }
+            let dir = base.join(entry.dir.as_deref().unwrap_or(Path::new(".")));
+            if !dir.is_dir() {
+                anyhow::bail!("task '{id}': {} is not a directory", dir.display());
+            }
+            let own = &entry.settings;
+            let defaults = &self.defaults;
+            jobs.push(Job {
+                id,
+                description: entry.description.clone(),
+                dir,
+                model: own
+                    .model
+                    .clone()
+                    .or_else(|| defaults.model.clone())
+                    .or_else(|| cli_model.map(String::from)),
+                iterations: own.iterations.or(defaults.iterations),
+                quality: own
+                    .quality
+                    .or(defaults.quality)
+                    .unwrap_or(config.iteration.quality_threshold),
+                evaluator: own
+                    .evaluator
+                    .clone()
+                    .or_else(|| defaults.evaluator.clone())
+                    .or_else(|| cli_evaluator.map(String::from)),
+                max_cost_usd: own
+                    .max_cost_usd
+                    .or(defaults.max_cost_usd)
+                    .unwrap_or(config.safety.max_cost_usd),
+            });
+        }
+        Ok(jobs)
+    }
+}
+
+/// One entry, ready to run.
+#[derive(Debug, Clone)]
+struct Job {
+    id: String,
+    description: String,
+    dir: PathBuf,
+    model: Option<String>,
+    iterations: Option<u8>,
+    quality: f32,
+    evaluator: Option<String>,
+    max_cost_usd: f64,
+}
+
+impl Job {
+    /// Arguments for the child `openkoi` process.
+    fn args(&self, budget: f64, config: Option<&Path>, patch: Option<&Path>) -> Vec<String> {
+        let mut args = vec![
+            "--quiet".to_string(),
+            "--output".into(),
+            "json".into(),
+            "-q".into(),
+            self.quality.to_string(),
+            "--fail-under".into(),
+            self.quality.to_string(),
+            "--max-cost".into(),
+            format!("{budget:.4}"),
+        ];
+        if let Some(ref model) = self.model {
+            args.extend(["-m".into(), model.clone()]);
+        }
+        if let Some(iterations) = self.iterations {
+            args.extend(["-i".into(), iterations.to_string()]);
+        }
+        if let Some(ref evaluator) = self.evaluator {
+            args.extend(["--evaluator".into(), evaluator.clone()]);
+        }
+        if let Some(config) = config {
+            args.extend(["--config".into(), config.to_string_lossy().into_owned()]);
+        }
+        if let Some(patch) = patch {
+            args.extend(["--patch".into(), patch.to_string_lossy().into_owned()]);
+        }
+        args.extend(["--".into(), self.description.clone()]);
+        args
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryStatus {
+    Accepted,
+    BelowThreshold,
+    BudgetExceeded,
+    ProviderError,
+    /// The run errored before producing a result.
+    Failed,
+    /// Not started: the batch's cost cap was used up.
+    Skipped,
+}
+
+impl From<RunOutcome> for EntryStatus {
+    fn from(outcome: RunOutcome) -> Self {
+        match outcome {
+            RunOutcome::Accepted => EntryStatus::Accepted,
+            RunOutcome::BelowThreshold => EntryStatus::BelowThreshold,
+            RunOutcome::BudgetExceeded => EntryStatus::BudgetExceeded,
+            RunOutcome::ProviderError => EntryStatus::ProviderError,
+        }
+    }
+}
+
+impl std::fmt::Display for EntryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            EntryStatus::Accepted => "accepted",
+            EntryStatus::BelowThreshold => "below threshold",
+            EntryStatus::BudgetExceeded => "budget exceeded",
+            EntryStatus::ProviderError => "provider error",
+            EntryStatus::Failed => "failed",
+            EntryStatus::Skipped => "skipped",
+        };
+        f.pad(name)
+    }
+}
+
+/// Result of one entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryReport {
+    pub id: String,
+    pub dir: String,
+    pub description: String,
+    pub status: EntryStatus,
+    pub score: Option<f64>,
+    pub iterations: u8,
+    pub cost: f64,
+    pub files_changed: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub duration_secs: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl EntryReport {
+    fn empty(job: &Job, status: EntryStatus) -> Self {
+        Self {
+            id: job.id.clone(),
+            dir: job.dir.display().to_string(),
+            description: job.description.clone(),
+            status,
+            score: None,
+            iterations: 0,
+            cost: 0.0,
+            files_changed: 0,
+            lines_added: 0,
+            lines_removed: 0,
+            duration_secs: 0.0,
+            error: None,
+        }
+    }
+}
+
+/// Aggregate report, written next to the manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub manifest: String,
+    pub entries: Vec<EntryReport>,
+    pub total_cost: f64,
+    pub accepted: usize,
+    pub unsuccessful: usize,
+}
+
+impl BatchReport {
+    fn new(manifest: &Path, entries: Vec<EntryReport>) -> Self {
+        let total_cost = entries.iter().map(|e| e.cost).sum();
+        let accepted = entries
+            .iter()
+            .filter(|e| e.status == EntryStatus::Accepted)
+            .count();
+        Self {
+            manifest: manifest.display().to_string(),
+            total_cost,
+            accepted,
+            unsuccessful: entries.len() - accepted,
+            entries,
+        }
+    }
+
+    fn write(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// Shared spending cap. Every running entry holds a reservation, settled
+/// to its actual cost when it finishes, so the batch stays under the cap.
+#[derive(Debug)]
+struct CostCap {
+    limit: Option<f64>,
+    spent: f64,
+    reserved: f64,
+}
+
+impl CostCap {
+    fn new(limit: Option<f64>, spent: f64) -> Self {
+        Self {
+            limit,
+            spent,
+            reserved: 0.0,
+        }
+    }
+
+    /// Budget for the next entry, or `None` while the cap is committed.
+    fn reserve(&mut self, per_entry: f64) -> Option<f64> {
+        let budget = match self.limit {
+            Some(limit) => per_entry.min(limit - self.spent - self.reserved),
+            None => per_entry,
+        };
+        if budget < MIN_ENTRY_BUDGET_USD {
+            return None;
+        }
+        self.reserved += budget;
+        Some(budget)
+    }
+
+    fn settle(&mut self, budget: f64, cost: f64) {
+        self.reserved -= budget;
+        self.spent += cost;
+    }
+}
+
+/// Options for `openkoi batch`.
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub manifest: PathBuf,
+    pub concurrency: Option<usize>,
+    pub resume: bool,
+    pub report: Option<PathBuf>,
+}
+
+/// Default report path: `tasks.yaml` → `tasks.report.json`.
+pub fn default_report_path(manifest: &Path) -> PathBuf {
+    manifest.with_extension("report.json")
+}
+
+/// Run `openkoi batch`.
+pub async fn run_batch(options: BatchOptions, cli: &Cli, config: &Config) -> anyhow::Result<()> {
+    let manifest = BatchManifest::load(&options.manifest)?;
+    let base = options
+        .manifest
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .canonicalize()?;
+    let jobs = manifest.jobs(
+        &base,
+        cli.model.as_deref(),
+        cli.evaluator.as_deref(),
+        config,
+    )?;
+    let report_path = options
+        .report
+        .clone()
+        .unwrap_or_else(|| default_report_path(&options.manifest));
+
+    // Entries accepted in the previous run are kept as they are.
+    let mut results: Vec<Option<EntryReport>> = vec![None; jobs.len()];
+    if options.resume {
+        let text = std::fs::read_to_string(&report_path).map_err(|e| {
+            anyhow::anyhow!("no report to resume at {}: {e}", report_path.display())
+        })?;
+        let previous: BatchReport = serde_json::from_str(&text)?;
+        for entry in previous.entries {
+            if entry.status != EntryStatus::Accepted {
+                continue;
+            }
+            if let Some(i) = jobs.iter().position(|j| j.id == entry.id) {
+                results[i] = Some(entry);
+            }
+        }
+    }
+    let mut pending: VecDeque<usize> = (0..jobs.len()).filter(|&i| results[i].is_none()).collect();
+
+    let patch_dir =
+        (cli.dry_run || cli.patch.is_some()).then(|| report_path.with_extension("patches"));
+    if let Some(ref dir) = patch_dir {
+        std::fs::create_dir_all(dir)?;
+    }
+    let config_path = match cli.config {
+        Some(ref path) => Some(Path::new(path).canonicalize()?),
+        None => None,
+    };
+    let exe = std::env::current_exe()?;
+    let concurrency = options
+        .concurrency
+        .or(manifest.concurrency)
+        .unwrap_or(4)
+        .max(1);
+    let already_spent = results.iter().flatten().map(|e| e.cost).sum();
+    let mut cap = CostCap::new(cli.max_cost.or(manifest.max_cost_usd), already_spent);
+
+    if !cli.quiet {
+        eprintln!(
+            "[batch] {} of {} task(s) to run, {} at a time",
+            pending.len(),
+            jobs.len(),
+            concurrency
+        );
+    }
+
+    let total = pending.len();
+    let mut finished = 0;
+    let mut running = FuturesUnordered::new();
+    loop {
+        while running.len() < concurrency {
+            let Some(&i) = pending.front() else { break };
+            let Some(budget) = cap.reserve(jobs[i].max_cost_usd) else {
+                break;
+            };
+            pending.pop_front();
+            let job = jobs[i].clone();
+            let patch = patch_dir
+                .as_ref()
+                .map(|d| d.join(format!("{}.patch", slug::slugify(&job.id))));
+            let args = job.args(budget, config_path.as_deref(), patch.as_deref());
+            let exe = exe.clone();
+            running.push(async move {
+                let report = run_entry(&exe, &job, &args).await;
+                (i, budget, report)
+            });
+        }
+        let Some((i, budget, report)) = running.next().await else {
+            break;
+        };
+        cap.settle(budget, report.cost);
+        finished += 1;
+        if !cli.quiet {
+            eprintln!("[batch {finished}/{total}] {}", summary_line(&report));
+        }
+        results[i] = Some(report);
+        let report = BatchReport::new(
+            &options.manifest,
+            results.iter().flatten().cloned().collect(),
+        );
+        report.write(&report_path)?;
+    }
+
+    // Whatever is left never got a budget.
+    for i in pending {
+        let mut skipped = EntryReport::empty(&jobs[i], EntryStatus::Skipped);
+        skipped.error = Some("batch cost cap reached".into());
+        results[i] = Some(skipped);
+    }
+    let report = BatchReport::new(&options.manifest, results.into_iter().flatten().collect());
+    report.write(&report_path)?;
+
+    match cli.output {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&report)?)
+        }
+        _ => print!("{}", render_report(&report)),
+    }
+    if !cli.quiet {
+        eprintln!("[batch] report written to {}", report_path.display());
+    }
+    if report.unsuccessful > 0 {
+        anyhow::bail!(
+            "{} of {} task(s) did not succeed; rerun them with `openkoi batch {} --resume`",
+            report.unsuccessful,
+            report.entries.len(),
+            options.manifest.display()
+        );
+    }
+    Ok(())
+}
+
+/// Run one entry as a child process and read its JSON result.
+async fn run_entry(exe: &Path, job: &Job, args: &[String]) -> EntryReport {
+    let started = Instant::now();
+    let mut report = EntryReport::empty(job, EntryStatus::Failed);
+    let result = run_json(exe, &job.dir, args).await;
+    report.duration_secs = started.elapsed().as_secs_f64();
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            report.error = Some(e);
+            return report;
+        }
+    };
+
+    if let Some(outcome) = result
+        .get("outcome")
+        .and_then(|o| serde_json::from_value::<RunOutcome>(o.clone()).ok())
+    {
+        report.status = outcome.into();
+    }
+    report.score = result.get("final_score").and_then(|v| v.as_f64());
+    report.iterations = result
+        .get("iterations")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u8;
+    report.cost = result.get("cost").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    report.error = result
+        .get("error")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    // Dry runs report the patch; otherwise the files are on disk.
+    let stats = match result.get("diff").and_then(|d| d.as_str()) {
+        Some(diff) => diff_stats(diff),
+        None => worktree_stats(&job.dir, &modified_files(&result)).await,
+    };
+    (
+        report.files_changed,
+        report.lines_added,
+        report.lines_removed,
+    ) = stats;
+    report
+}
+
+/// Run `openkoi` with `args` in `dir` and parse its `--output json`
+/// result. On failure the error is the tail of its stderr.
+pub(crate) async fn run_json(
+    exe: &Path,
+    dir: &Path,
+    args: &[String],
+) -> Result<serde_json::Value, String> {
+    let output = tokio::process::Command::new(exe)
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("could not start {}: {e}", exe.display()))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    serde_json::from_str(stdout.trim()).map_err(|_| {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let lines: Vec<&str> = stderr.trim().lines().collect();
+        let tail = lines[lines.len().saturating_sub(ERROR_TAIL_LINES)..].join("\n");
+        if tail.is_empty() {
+            format!("exited with {}", output.status)
+        } else {
+            tail
+        }
+    })
+}
+
+/// Every file any iteration reported modifying.
+fn modified_files(result: &serde_json::Value) -> Vec<String> {
+    let mut files: Vec<String> = result
+        .get("history")
+        .and_then(|h| h.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|it| it.get("files_modified").and_then(|f| f.as_array()))
+        .flatten()
+        .filter_map(|f| f.as_str().map(String::from))
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Files, added and removed lines in a unified diff.
+pub fn diff_stats(diff: &str) -> (usize, usize, usize) {
+    let mut files = 0;
+    let (mut added, mut removed) = (0, 0);
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            files += 1;
+        } else if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        } else if line.starts_with('+') {
+            added += 1;
+        } else if line.starts_with('-') {
+            removed += 1;
+        }
+    }
+    (files, added, removed)
+}
+
+/// Line counts for `files` against the last commit, when `dir` is a git
+/// checkout; otherwise just the file count.
+async fn worktree_stats(dir: &Path, files: &[String]) -> (usize, usize, usize) {
+    if files.is_empty() {
+        return (0, 0, 0);
+    }
+    let output = tokio::process::Command::new("git")
+        .args(["diff", "--numstat", "HEAD", "--"])
+        .args(files)
+        .current_dir(dir)
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => {
+            let (added, removed) = numstat_totals(&String::from_utf8_lossy(&output.stdout));
+            (files.len(), added, removed)
+        }
+        _ => (files.len(), 0, 0),
+    }
+}
+
+/// Sum of `git diff --numstat` output (binary files count as zero lines).
+pub fn numstat_totals(numstat: &str) -> (usize, usize) {
+    numstat.lines().fold((0, 0), |(added, removed), line| {
+        let mut cols = line.split('\t');
+        let a = cols
+            .next()
+            .and_then(|c| c.parse::<usize>().ok())
+            .unwrap_or(0);
+        let r = cols
+            .next()
+            .and_then(|c| c.parse::<usize>().ok())
+            .unwrap_or(0);
+        (added + a, removed + r)
+    })
+}
+
+fn summary_line(entry: &EntryReport) -> String {
+    let mut line = format!("{}: {}", entry.id, entry.status);
+    if let Some(score) = entry.score {
+        line.push_str(&format!(" score={score:.2}"));
+    }
+    line.push_str(&format!(
+        " ${:.2} ({} file(s), +{} -{})",
+        entry.cost, entry.files_changed, entry.lines_added, entry.lines_removed
+    ));
+    line
+}
+
+pub fn render_report(report: &BatchReport) -> String {
+    let width = report
+        .entries
+        .iter()
+        .map(|e| e.id.chars().count())
+        .max()
+        .unwrap_or(2)
+        .max(2);
+    let mut out = format!(
+        "{:<width$}  {:<16} {:>5} {:>8} {:>6} {:>8} {:>8}\n",
+        "ID", "STATUS", "SCORE", "COST", "FILES", "ADDED", "REMOVED"
+    );
+    for e in &report.entries {
+        let score = e
+            .score
+            .map(|s| format!("{s:.2}"))
+            .unwrap_or_else(|| "-".into());
+        out.push_str(&format!(
+            "{:<width$}  {:<16} {:>5} {:>8} {:>6} {:>8} {:>8}\n",
+            e.id,
+            e.status,
+            score,
+            format!("${:.2}", e.cost),
+            e.files_changed,
+            format!("+{}", e.lines_added),
+            format!("-{}", e.lines_removed),
+        ));
+    }
+    out.push_str(&format!(
+        "\n{} of {} accepted, total cost ${:.2}\n",
+        report.accepted,
+        report.entries.len(),
+        report.total_cost
+    ));
+    for e in report.entries.iter().filter(|e| e.error.is_some()) {
+        let error = e.error.as_deref().unwrap_or_default();
+        let first = error.lines().last().unwrap_or_default();
+        out.push_str(&format!("  {}: {}\n", e.id, first));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+concurrency: 2
+max_cost_usd: 5
+defaults:
+  model: anthropic/claude-sonnet-4
+  iterations: 2
+tasks:
+  - id: billing
+    description: Migrate to the v2 client
+    dir: .
+    quality: 0.9
+    evaluator: code-review
+  - task: Migrate to the v2 client
+    model: openai/gpt-5
+"#;
+
+    #[test]
+    fn test_manifest_jobs() {
+        let manifest: BatchManifest = serde_yml::from_str(MANIFEST).unwrap();
+        assert_eq!(manifest.concurrency, Some(2));
+        let dir = tempfile::tempdir().unwrap();
+        let jobs = manifest
+            .jobs(dir.path(), None, None, &Config::default())
+            .unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].id, "billing");
+        assert_eq!(jobs[0].model.as_deref(), Some("anthropic/claude-sonnet-4"));
+        assert_eq!(jobs[0].iterations, Some(2));
+        assert!((jobs[0].quality - 0.9).abs() < f32::EPSILON);
+        assert_eq!(jobs[1].id, "2");
+        assert_eq!(jobs[1].model.as_deref(), Some("openai/gpt-5"));
+        assert!((jobs[1].quality - 0.8).abs() < f32::EPSILON);
+        assert!((jobs[1].max_cost_usd - 2.0).abs() < f64::EPSILON);
+
+        let args = jobs[0].args(1.5, None, None);
+        assert_eq!(
+            args,
+            [
+                "--quiet",
+                "--output",
+                "json",
+                "-q",
+                "0.9",
+                "--fail-under",
+                "0.9",
+                "--max-cost",
+                "1.5000",
+                "-m",
+                "anthropic/claude-sonnet-4",
+                "-i",
+                "2",
+                "--evaluator",
+                "code-review",
+                "--",
+                "Migrate to the v2 client",
+            ]
+        );
+
+        // The child parses them back into the same settings.
+        use clap::Parser;
+        let mut job = jobs[1].clone();
+        job.description = "--dry-run is not a flag here".into();
+        let cli = Cli::try_parse_from(
+            std::iter::once("openkoi".to_string()).chain(job.args(0.25, None, None)),
+        )
+        .unwrap();
+        assert_eq!(cli.task, ["--dry-run is not a flag here"]);
+        assert!(!cli.dry_run);
+        assert_eq!(cli.output, OutputFormat::Json);
+        assert_eq!(cli.max_cost, Some(0.25));
+        assert_eq!(cli.model.as_deref(), Some("openai/gpt-5"));
+
+        let mut missing = manifest.clone();
+        missing.tasks[1].dir = Some("no-such-dir".into());
+        assert!(missing
+            .jobs(dir.path(), None, None, &Config::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_cost_cap_reservations() {
+        let mut cap = CostCap::new(Some(5.0), 1.0);
+        assert_eq!(cap.reserve(2.0), Some(2.0));
+        assert_eq!(cap.reserve(2.0), Some(2.0));
+        // 1.0 spent + 4.0 reserved: nothing left until one finishes.
+        assert_eq!(cap.reserve(2.0), None);
+        cap.settle(2.0, 0.5);
+        assert_eq!(cap.reserve(2.0), Some(1.5));
+
+        let mut unlimited = CostCap::new(None, 100.0);
+        assert_eq!(unlimited.reserve(2.0), Some(2.0));
+    }
+
+    #[test]
+    fn test_diff_stats() {
+        let diff = "\
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,2 +1,2 @@
+-old
++new
++more
+ same
+diff --git a/b.txt b/b.txt
+--- /dev/null
++++ b/b.txt
+@@ -0,0 +1 @@
++x
+";
+        assert_eq!(diff_stats(diff), (2, 3, 1));
+        assert_eq!(
+            numstat_totals("3\t1\tsrc/a.rs\n-\t-\timg.png\n10\t0\tb.txt\n"),
+            (13, 1)
+        );
+    }
+
+    #[test]
+    fn test_report_summary() {
+        let job = Job {
+            id: "billing".into(),
+            description: "d".into(),
+            dir: PathBuf::from("/srv/billing"),
+            model: None,
+            iterations: None,
+            quality: 0.8,
+            evaluator: None,
+            max_cost_usd: 2.0,
+        };
+        let mut accepted = EntryReport::empty(&job, EntryStatus::Accepted);
+        accepted.score = Some(0.91);
+        accepted.cost = 0.4;
+        let mut failed = EntryReport::empty(&job, EntryStatus::Failed);
+        failed.id = "search".into();
+        failed.error = Some("error: Provider 'x' not available".into());
+
+        let report = BatchReport::new(Path::new("tasks.yaml"), vec![accepted, failed]);
+        assert_eq!(report.accepted, 1);
+        assert_eq!(report.unsuccessful, 1);
+        let text = render_report(&report);
+        assert!(text.contains("1 of 2 accepted, total cost $0.40"));
+        assert!(text.contains("search: error: Provider 'x' not available"));
+        assert_eq!(
+            default_report_path(Path::new("ops/tasks.yaml")),
+            PathBuf::from("ops/tasks.report.json")
+        );
+    }
+}
@@ -0,0 +1,826 @@
+// src/cli/bench.rs — Benchmark suites across models, skills, souls and configs
+//
+// Every case runs under every variant, `repeat` times, as its own `openkoi`
+// process inside a disposable copy of the case's fixture. The variant's soul
+// and skills are dropped into the copy as workspace files, and memory is
+// disabled so repeats don't learn from each other. After each run the
+// case's checks are evaluated in the copy; results are summarised per
+// variant and saved so the dashboard can show trends between runs.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use super::batch::run_json;
+use super::{Cli, OutputFormat};
+use crate::infra::config::Config;
+use crate::memory::store::{BenchResultRow, Store};
+use crate::security::sandbox::{self, SandboxCommand};
+use crate::tools::overlay::Workspace;
+
+/// A case's `tests` command is killed after this long.
+const TEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// A `suite.yaml` file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BenchSuite {
+    /// Name results are saved under (default: the file name).
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Runs of each case under each variant (default 1).
+    #[serde(default)]
+    pub repeat: Option<u32>,
+    /// Runs at once (default 1, so timings stay comparable).
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+    /// Explicitly listed variants.
+    #[serde(default)]
+    pub variants: Vec<Variant>,
+    /// Variants generated as the cross product of its lists.
+    #[serde(default)]
+    pub matrix: Option<Matrix>,
+    pub cases: Vec<BenchCase>,
+}
+
+/// One configuration to run every case under.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Variant {
+    /// Column name in the results (required for listed variants).
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub iterations: Option<u8>,
+    #[serde(default)]
+    pub quality: Option<f32>,
+    /// Evaluator skill to judge runs with.
+    #[serde(default)]
+    pub evaluator: Option<String>,
+    /// Config file passed as `--config`.
+    #[serde(default)]
+    pub config: Option<PathBuf>,
+    /// Soul file installed as the workspace `.openkoi/SOUL.md`.
+    #[serde(default)]
+    pub soul: Option<PathBuf>,
+    /// Skill directories installed under `.agents/skills/`.
+    #[serde(default)]
+    pub skills: Vec<PathBuf>,
+    /// Evaluator skill directories installed under `.agents/evaluators/`.
+    #[serde(default)]
+    pub evaluators: Vec<PathBuf>,
+}
+
+/// Lists to cross. An empty list leaves that setting at its default.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Matrix {
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// Each entry is one set of skill directories (`[]` for none).
+    #[serde(default)]
+    pub skills: Vec<Vec<PathBuf>>,
+    #[serde(default)]
+    pub souls: Vec<PathBuf>,
+    #[serde(default)]
+    pub configs: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BenchCase {
+    pub id: String,
+    #[serde(alias = "prompt")]
+    pub task: String,
+    /// Directory copied into each run's workspace (default: start empty).
+    #[serde(default)]
+    pub fixture: Option<PathBuf>,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+/// What a run must achieve to pass.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Expectations {
+    /// Shell command that must exit 0 in the workspace afterwards.
+    #[serde(default)]
+    pub tests: Option<String>,
+    /// Strings the final output must contain.
+    #[serde(default)]
+    pub output_contains: Vec<String>,
+    /// Strings each file (relative to the workspace) must contain.
+    #[serde(default)]
+    pub files_contain: BTreeMap<String, Vec<String>>,
+    /// Minimum final evaluator score.
+    #[serde(default)]
+    pub min_score: Option<f64>,
+}
+
+impl BenchSuite {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        let suite: Self =
+            serde_yml::from_str(&text).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+        if suite.cases.is_empty() {
+            anyhow::bail!("{} lists no cases", path.display());
+        }
+        let mut seen = HashSet::new();
+        for case in &suite.cases {
+            if !seen.insert(case.id.as_str()) {
+                anyhow::bail!("duplicate case id '{}'", case.id);
+            }
+        }
+        Ok(suite)
+    }
+
+    /// Listed variants followed by the matrix, or a single `default`
+    /// variant when the suite has neither.
+    pub fn expand_variants(&self) -> anyhow::Result<Vec<Variant>> {
+        let mut variants = self.variants.clone();
+        if let Some(ref matrix) = self.matrix {
+            variants.extend(matrix.expand());
+        }
+        if variants.is_empty() {
+            variants.push(Variant {
+                name: "default".into(),
+                ..Default::default()
+            });
+        }
+        let mut seen = HashSet::new();
+        for variant in &variants {
+            if variant.name.is_empty() {
+                anyhow::bail!("every listed variant needs a name");
+            }
+            if !seen.insert(variant.name.as_str()) {
+                anyhow::bail!("duplicate variant '{}'", variant.name);
+            }
+        }
+        Ok(variants)
+    }
+}
+
+impl Matrix {
+    /// Cross product, named by joining the parts that vary.
+    fn expand(&self) -> Vec<Variant> {
+        let models: Vec<Option<&String>> = options(&self.models);
+        let skills: Vec<Option<&Vec<PathBuf>>> = options(&self.skills);
+        let souls: Vec<Option<&PathBuf>> = options(&self.souls);
+        let configs: Vec<Option<&PathBuf>> = options(&self.configs);
+
+        let mut variants = Vec::new();
+        for model in &models {
+            for skill_set in &skills {
+                for soul in &souls {
+                    for config in &configs {
+                        let mut parts = Vec::new();
+                        if let Some(model) = model {
+                            parts.push(model.to_string());
+                        }
+                        if let Some(skill_set) = skill_set {
+                            parts.push(if skill_set.is_empty() {
+                                "no-skills".into()
+                            } else {
+                                skill_set
+                                    .iter()
+                                    .map(|p| stem(p))
+                                    .collect::<Vec<_>>()
+                                    .join("+")
+                            });
+                        }
+                        if let Some(soul) = soul {
+                            parts.push(stem(soul));
+                        }
+                        if let Some(config) = config {
+                            parts.push(stem(config));
+                        }
+                        variants.push(Variant {
+                            name: if parts.is_empty() {
+                                "default".into()
+                            } else {
+                                parts.join(" ")
+                            },
+                            model: model.cloned(),
+                            skills: skill_set.cloned().unwrap_or_default(),
+                            soul: soul.cloned(),
+                            config: config.cloned(),
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+        }
+        variants
+    }
+}
+
+/// `[None]` for an empty list, so it drops out of the product.
+fn options<T>(list: &[T]) -> Vec<Option<&T>> {
+    if list.is_empty() {
+        vec![None]
+    } else {
+        list.iter().map(Some).collect()
+    }
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// One run of one case under one variant.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunRecord {
+    pub case: String,
+    pub variant: String,
+    /// 1-based.
+    pub attempt: u32,
+    pub passed: bool,
+    pub score: Option<f64>,
+    pub iterations: u32,
+    pub tokens: u64,
+    pub cost: f64,
+    pub duration_ms: u64,
+    /// Checks that failed, or why the run produced no result.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
+}
+
+/// Aggregates for one variant across all cases and attempts.
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantSummary {
+    pub variant: String,
+    pub runs: usize,
+    pub passed: usize,
+    pub pass_rate: f64,
+    /// Mean over runs that were scored.
+    pub mean_score: Option<f64>,
+    pub mean_iterations: f64,
+    pub mean_tokens: f64,
+    pub total_cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub suite: String,
+    pub run_id: String,
+    pub variants: Vec<VariantSummary>,
+    pub runs: Vec<RunRecord>,
+}
+
+impl BenchReport {
+    fn new(suite: String, run_id: String, variants: &[Variant], runs: Vec<RunRecord>) -> Self {
+        let variants = variants.iter().map(|v| summarize(&v.name, &runs)).collect();
+        Self {
+            suite,
+            run_id,
+            variants,
+            runs,
+        }
+    }
+}
+
+fn summarize(variant: &str, runs: &[RunRecord]) -> VariantSummary {
+    let runs: Vec<&RunRecord> = runs.iter().filter(|r| r.variant == variant).collect();
+    let count = runs.len();
+    let passed = runs.iter().filter(|r| r.passed).count();
+    let scores: Vec<f64> = runs.iter().filter_map(|r| r.score).collect();
+    let mean = |total: f64| {
+        if count == 0 {
+            0.0
+        } else {
+            total / count as f64
+        }
+    };
+    VariantSummary {
+        variant: variant.to_string(),
+        runs: count,
+        passed,
+        pass_rate: mean(passed as f64),
+        mean_score: (!scores.is_empty()).then(|| scores.iter().sum::<f64>() / scores.len() as f64),
+        mean_iterations: mean(runs.iter().map(|r| r.iterations as f64).sum()),
+        mean_tokens: mean(runs.iter().map(|r| r.tokens as f64).sum()),
+        total_cost: runs.iter().map(|r| r.cost).sum(),
+    }
+}
+
+/// Options for `openkoi bench`.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub suite: PathBuf,
+    pub repeat: Option<u32>,
+    pub concurrency: Option<usize>,
+}
+
+/// One scheduled run.
+#[derive(Debug, Clone)]
+struct Job {
+    case: usize,
+    variant: usize,
+    attempt: u32,
+}
+
+/// Run `openkoi bench`.
+pub async fn run_bench(
+    options: BenchOptions,
+    cli: &Cli,
+    config: &Config,
+    store: Option<Arc<Mutex<Store>>>,
+) -> anyhow::Result<()> {
+    let suite = BenchSuite::load(&options.suite)?;
+    let base = options
+        .suite
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .canonicalize()?;
+    let name = suite.name.clone().unwrap_or_else(|| stem(&options.suite));
+    let mut variants = suite.expand_variants()?;
+    for variant in &mut variants {
+        resolve_paths(variant, &base);
+        if variant.model.is_none() {
+            variant.model = cli.model.clone();
+        }
+        if variant.evaluator.is_none() {
+            variant.evaluator = cli.evaluator.clone();
+        }
+    }
+    let mut cases = suite.cases.clone();
+    for case in &mut cases {
+        if let Some(ref fixture) = case.fixture {
+            let fixture = base.join(fixture);
+            if !fixture.is_dir() {
+                anyhow::bail!(
+                    "case '{}': {} is not a directory",
+                    case.id,
+                    fixture.display()
+                );
+            }
+            case.fixture = Some(fixture);
+        }
+    }
+    let repeat = options.repeat.or(suite.repeat).unwrap_or(1).max(1);
+    let concurrency = options
+        .concurrency
+        .or(suite.concurrency)
+        .unwrap_or(1)
+        .max(1);
+
+    let mut jobs = Vec::new();
+    for case in 0..cases.len() {
+        for variant in 0..variants.len() {
+            for attempt in 1..=repeat {
+                jobs.push(Job {
+                    case,
+                    variant,
+                    attempt,
+                });
+            }
+        }
+    }
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let exe = std::env::current_exe()?;
+    if !cli.quiet {
+        eprintln!(
+            "[bench] {}: {} case(s) x {} variant(s) x {} = {} run(s)",
+            name,
+            cases.len(),
+            variants.len(),
+            repeat,
+            jobs.len()
+        );
+    }
+
+    let total = jobs.len();
+    let mut pending = jobs.into_iter();
+    let mut running = FuturesUnordered::new();
+    let mut runs = Vec::with_capacity(total);
+    loop {
+        while running.len() < concurrency {
+            let Some(job) = pending.next() else { break };
+            let case = cases[job.case].clone();
+            let variant = variants[job.variant].clone();
+            let args = child_args(&case, &variant, config, cli.max_cost);
+            let exe = exe.clone();
+            running.push(async move { run_case(&exe, &case, &variant, job.attempt, &args).await });
+        }
+        let Some(record) = running.next().await else {
+            break;
+        };
+        if let Some(ref store) = store {
+            if let Ok(store) = store.lock() {
+                let _ = store.insert_bench_result(&BenchResultRow {
+                    run_id: run_id.clone(),
+                    suite: name.clone(),
+                    variant: record.variant.clone(),
+                    case_id: record.case.clone(),
+                    attempt: record.attempt as i32,
+                    passed: record.passed,
+                    score: record.score,
+                    iterations: record.iterations as i32,
+                    tokens: record.tokens as i64,
+                    cost_usd: record.cost,
+                    duration_ms: record.duration_ms as i64,
+                    failures: (!record.failures.is_empty()).then(|| record.failures.join("\n")),
+                });
+            }
+        }
+        if !cli.quiet {
+            eprintln!(
+                "[bench {}/{}] {}",
+                runs.len() + 1,
+                total,
+                summary_line(&record)
+            );
+        }
+        runs.push(record);
+    }
+
+    let report = BenchReport::new(name, run_id, &variants, runs);
+    match cli.output {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string_pretty(&report)?)
+        }
+        _ => print!("{}", render_report(&report, &cases)),
+    }
+    Ok(())
+}
+
+fn resolve_paths(variant: &mut Variant, base: &Path) {
+    let resolve = |p: &mut PathBuf| *p = base.join(&*p);
+    variant.config.iter_mut().for_each(resolve);
+    variant.soul.iter_mut().for_each(resolve);
+    variant.skills.iter_mut().for_each(resolve);
+    variant.evaluators.iter_mut().for_each(resolve);
+}
+
+/// Arguments for the child `openkoi` process.
+fn child_args(
+    case: &BenchCase,
+    variant: &Variant,
+    config: &Config,
+    max_cost: Option<f64>,
+) -> Vec<String> {
+    let quality = variant
+        .quality
+        .unwrap_or(config.iteration.quality_threshold);
+    let mut args = vec![
+        "--quiet".to_string(),
+        "--output".into(),
+        "json".into(),
+        "--no-memory".into(),
+        "-q".into(),
+        quality.to_string(),
+    ];
+    if let Some(ref model) = variant.model {
+        args.extend(["-m".into(), model.clone()]);
+    }
+    if let Some(iterations) = variant.iterations {
+        args.extend(["-i".into(), iterations.to_string()]);
+    }
+    if let Some(ref evaluator) = variant.evaluator {
+        args.extend(["--evaluator".into(), evaluator.clone()]);
+    }
+    if let Some(ref config) = variant.config {
+        args.extend(["--config".into(), config.to_string_lossy().into_owned()]);
+    }
+    if let Some(max_cost) = max_cost {
+        args.extend(["--max-cost".into(), max_cost.to_string()]);
+    }
+    args.extend(["--".into(), case.task.clone()]);
+    args
+}
+
+/// Run one case under one variant in a fresh workspace and check it.
+async fn run_case(
+    exe: &Path,
+    case: &BenchCase,
+    variant: &Variant,
+    attempt: u32,
+    args: &[String],
+) -> RunRecord {
+    let started = Instant::now();
+    let mut record = RunRecord {
+        case: case.id.clone(),
+        variant: variant.name.clone(),
+        attempt,
+        passed: false,
+        score: None,
+        iterations: 0,
+        tokens: 0,
+        cost: 0.0,
+        duration_ms: 0,
+        failures: Vec::new(),
+    };
+
+    let workspace = match prepare_workspace(case, variant) {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            record.failures.push(format!("workspace setup failed: {e}"));
+            return record;
+        }
+    };
+    let result = run_json(exe, workspace.path(), args).await;
+    record.duration_ms = started.elapsed().as_millis() as u64;
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            let last = e.lines().last().unwrap_or_default().to_string();
+            record.failures.push(format!("run failed: {last}"));
+            return record;
+        }
+    };
+
+    record.score = result.get("final_score").and_then(|v| v.as_f64());
+    record.iterations = result
+        .get("iterations")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32;
+    record.tokens = result
+        .get("total_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    record.cost = result.get("cost").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    if let Some(error) = result.get("error").and_then(|v| v.as_str()) {
+        record.failures.push(format!("run aborted: {error}"));
+    }
+    let output = result
+        .pointer("/output/content")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    record.failures.extend(check_output(
+        &case.expect,
+        output,
+        record.score,
+        workspace.path(),
+    ));
+    if let Some(ref command) = case.expect.tests {
+        if let Err(failure) = run_tests(command, workspace.path()).await {
+            record.failures.push(failure);
+        }
+    }
+    record.passed = record.failures.is_empty();
+    record
+}
+
+/// Copy the fixture and install the variant's soul and skills.
+fn prepare_workspace(case: &BenchCase, variant: &Variant) -> anyhow::Result<Workspace> {
+    let workspace = match case.fixture {
+        Some(ref fixture) => Workspace::create(fixture)?,
+        None => Workspace::empty()?,
+    };
+    if let Some(ref soul) = variant.soul {
+        workspace.add(soul, Path::new(".openkoi/SOUL.md"))?;
+    }
+    for (dir, skills) in [
+        (".agents/skills", &variant.skills),
+        (".agents/evaluators", &variant.evaluators),
+    ] {
+        for skill in skills {
+            let name = skill
+                .file_name()
+                .ok_or_else(|| anyhow::anyhow!("bad skill path {}", skill.display()))?;
+            workspace.add(skill, &Path::new(dir).join(name))?;
+        }
+    }
+    Ok(workspace)
+}
+
+/// Failed string and score expectations.
+fn check_output(
+    expect: &Expectations,
+    output: &str,
+    score: Option<f64>,
+    workspace: &Path,
+) -> Vec<String> {
+    let mut failures = Vec::new();
+    for needle in &expect.output_contains {
+        if !output.contains(needle.as_str()) {
+            failures.push(format!("output lacks {needle:?}"));
+        }
+    }
+    for (file, needles) in &expect.files_contain {
+        match std::fs::read_to_string(workspace.join(file)) {
+            Ok(text) => {
+                for needle in needles {
+                    if !text.contains(needle.as_str()) {
+                        failures.push(format!("{file} lacks {needle:?}"));
+                    }
+                }
+            }
+            Err(_) => failures.push(format!("{file} is missing")),
+        }
+    }
+    if let Some(min) = expect.min_score {
+        match score {
+            Some(score) if score >= min => {}
+            Some(score) => failures.push(format!("score {score:.2} below {min:.2}")),
+            None => failures.push(format!("not scored (needs {min:.2})")),
+        }
+    }
+    failures
+}
+
+/// Run the case's test command in the workspace, inside the sandbox.
+async fn run_tests(command: &str, dir: &Path) -> Result<(), String> {
+    let cmd = SandboxCommand::new("sh")
+        .args(["-c", command])
+        .current_dir(dir)
+        .fetches_dependencies()
+        .timeout(TEST_TIMEOUT);
+    match sandbox::run(cmd).await {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(format!("tests failed: `{command}` ({})", output.status)),
+        Err(e) => Err(format!("tests could not run: {e}")),
+    }
+}
+
+fn summary_line(record: &RunRecord) -> String {
+    let mut line = format!(
+        "{} [{}] #{}: {}",
+        record.case,
+        record.variant,
+        record.attempt,
+        if record.passed { "passed" } else { "failed" }
+    );
+    if let Some(score) = record.score {
+        line.push_str(&format!(" score={score:.2}"));
+    }
+    line.push_str(&format!(" ${:.2}", record.cost));
+    if let Some(failure) = record.failures.first() {
+        line.push_str(&format!(" ({failure})"));
+    }
+    line
+}
+
+pub fn render_report(report: &BenchReport, cases: &[BenchCase]) -> String {
+    let width = report
+        .variants
+        .iter()
+        .map(|v| v.variant.chars().count())
+        .max()
+        .unwrap_or(7)
+        .max(7);
+    let mut out = format!(
+        "{:<width$}  {:>9} {:>6} {:>6} {:>8} {:>8}\n",
+        "VARIANT", "PASS", "SCORE", "ITERS", "TOKENS", "COST"
+    );
+    for v in &report.variants {
+        let score = v
+            .mean_score
+            .map(|s| format!("{s:.2}"))
+            .unwrap_or_else(|| "-".into());
+        out.push_str(&format!(
+            "{:<width$}  {:>9} {:>6} {:>6.1} {:>8.0} {:>8}\n",
+            v.variant,
+            format!("{:.0}% {}/{}", v.pass_rate * 100.0, v.passed, v.runs),
+            score,
+            v.mean_iterations,
+            v.mean_tokens,
+            format!("${:.2}", v.total_cost),
+        ));
+    }
+
+    // Passes per case and variant.
+    if report.variants.len() > 1 || cases.len() > 1 {
+        let case_width = cases
+            .iter()
+            .map(|c| c.id.chars().count())
+            .max()
+            .unwrap_or(4)
+            .max(4);
+        out.push_str(&format!("\n{:<case_width$}", "CASE"));
+        for v in &report.variants {
+            out.push_str(&format!("  {}", v.variant));
+        }
+        out.push('\n');
+        for case in cases {
+            out.push_str(&format!("{:<case_width$}", case.id));
+            for v in &report.variants {
+                let runs: Vec<&RunRecord> = report
+                    .runs
+                    .iter()
+                    .filter(|r| r.case == case.id && r.variant == v.variant)
+                    .collect();
+                let passed = runs.iter().filter(|r| r.passed).count();
+                let cell = format!("{passed}/{}", runs.len());
+                out.push_str(&format!("  {:>w$}", cell, w = v.variant.chars().count()));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUITE: &str = r#"
+name: api
+repeat: 3
+variants:
+  - name: baseline
+    model: anthropic/claude-sonnet-4
+matrix:
+  models: [openai/gpt-5, ollama/llama3.3]
+  skills: [[], [skills/rust-style, skills/tests-first]]
+cases:
+  - id: health
+    prompt: Add a /health endpoint
+    fixture: fixtures/api
+    expect:
+      tests: cargo test
+      output_contains: [health]
+      files_contain:
+        src/main.rs: ["/health"]
+      min_score: 0.8
+"#;
+
+    #[test]
+    fn test_suite_variants() {
+        let suite: BenchSuite = serde_yml::from_str(SUITE).unwrap();
+        assert_eq!(suite.repeat, Some(3));
+        assert_eq!(suite.cases[0].task, "Add a /health endpoint");
+        let variants = suite.expand_variants().unwrap();
+        let names: Vec<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "baseline",
+                "openai/gpt-5 no-skills",
+                "openai/gpt-5 rust-style+tests-first",
+                "ollama/llama3.3 no-skills",
+                "ollama/llama3.3 rust-style+tests-first",
+            ]
+        );
+        assert_eq!(variants[2].skills.len(), 2);
+        assert_eq!(variants[2].model.as_deref(), Some("openai/gpt-5"));
+
+        let bare: BenchSuite = serde_yml::from_str("cases: [{id: a, task: Do it}]").unwrap();
+        assert_eq!(bare.expand_variants().unwrap()[0].name, "default");
+        let unnamed: BenchSuite =
+            serde_yml::from_str("variants: [{model: x/y}]\ncases: [{id: a, task: Do it}]").unwrap();
+        assert!(unnamed.expand_variants().is_err());
+    }
+
+    #[test]
+    fn test_check_output() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "route(\"/health\")").unwrap();
+        let expect = Expectations {
+            output_contains: vec!["done".into()],
+            files_contain: BTreeMap::from([
+                ("main.rs".to_string(), vec!["/health".to_string()]),
+                ("lib.rs".to_string(), vec!["x".to_string()]),
+            ]),
+            min_score: Some(0.8),
+            ..Default::default()
+        };
+        assert_eq!(
+            check_output(&expect, "all done", Some(0.9), dir.path()),
+            ["lib.rs is missing"]
+        );
+        let failures = check_output(&expect, "nothing", Some(0.5), dir.path());
+        assert_eq!(failures.len(), 3);
+        assert!(failures.contains(&"score 0.50 below 0.80".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_run_tests() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(run_tests("true", dir.path()).await.is_ok());
+        assert!(run_tests("exit 3", dir.path()).await.is_err());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let run = |variant: &str, passed: bool, score: Option<f64>| RunRecord {
+            case: "a".into(),
+            variant: variant.into(),
+            attempt: 1,
+            passed,
+            score,
+            iterations: 2,
+            tokens: 1000,
+            cost: 0.5,
+            duration_ms: 10,
+            failures: Vec::new(),
+        };
+        let runs = vec![
+            run("x", true, Some(0.9)),
+            run("x", false, None),
+            run("y", true, Some(0.7)),
+        ];
+        let summary = summarize("x", &runs);
+        assert_eq!((summary.runs, summary.passed), (2, 1));
+        assert!((summary.pass_rate - 0.5).abs() < 1e-9);
+        assert_eq!(summary.mean_score, Some(0.9));
+        assert!((summary.total_cost - 1.0).abs() < 1e-9);
+        assert_eq!(summarize("z", &runs).runs, 0);
+    }
+}
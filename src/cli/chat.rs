@@ -3,21 +3,26 @@
 use std::sync::Arc;
 use std::sync::Mutex;
 
+use crate::core::escalation::EscalationPolicy;
+use crate::core::instructions::ProjectInstructions;
 use crate::core::orchestrator::{Orchestrator, SessionContext};
 use crate::core::safety::SafetyChecker;
+use crate::core::tokenizer;
 use crate::core::types::{IterationEngineConfig, TaskInput};
+use crate::index::CodeIndex;
 use crate::infra::config::Config;
-use crate::integrations::registry::IntegrationRegistry;
 use crate::learner::skill_selector::SkillSelector;
+use crate::lsp::LspManager;
 use crate::memory::recall::{self, HistoryRecall};
 use crate::memory::store::Store;
 use crate::patterns::event_logger::{EventLogger, EventType, UsageEvent};
 use crate::patterns::miner::PatternMiner;
-use crate::plugins::mcp::McpManager;
 use crate::provider::roles::ModelRoles;
-use crate::provider::{ModelProvider, ModelRef, ToolDef};
+use crate::provider::{ModelProvider, ModelRef};
+use crate::skills::embedding::SkillEmbedder;
 use crate::skills::registry::SkillRegistry;
 use crate::soul::loader;
+use crate::tools::ToolRegistry;
 
 /// Mutable session state that slash commands can modify.
 struct ChatState {
@@ -49,9 +54,9 @@ pub async fn run_chat(
     model_ref: &ModelRef,
     config: &Config,
     store: Option<Arc<Mutex<Store>>>,
-    mcp_tools: Vec<ToolDef>,
-    mcp_manager: Option<&mut McpManager>,
-    integrations: Option<&IntegrationRegistry>,
+    tools: Option<&ToolRegistry>,
+    code_index: Option<Arc<CodeIndex>>,
+    lsp: Option<Arc<LspManager>>,
     quiet: bool,
 ) -> anyhow::Result<()> {
     let memory_count = store
@@ -69,8 +74,14 @@ pub async fn run_chat(
 
     // Load soul and skills once for the session
     let soul = loader::load_soul();
-    let skill_registry = Arc::new(SkillRegistry::new());
-    let selector = SkillSelector::new();
+    let instructions = ProjectInstructions::discover(std::path::Path::new("."));
+    let embedder = SkillEmbedder::new(provider.clone(), &config.skills.embeddings);
+    let mut skill_registry = SkillRegistry::new();
+    embedder
+        .embed_registry(&mut skill_registry, store.as_deref())
+        .await;
+    let skill_registry = Arc::new(skill_registry);
+    let selector = SkillSelector::with_experiments(config.skills.experiments.clone());
 
     let mut state = ChatState {
         model_ref: model_ref.clone(),
@@ -83,9 +94,6 @@ pub async fn run_chat(
         conversation_summary: String::new(),
     };
 
-    // We need to reborrow mcp_manager across loop iterations.
-    let mut mcp = mcp_manager;
-
     while let Some(input) = read_input() {
         let trimmed = input.trim();
 
@@ -112,10 +120,14 @@ pub async fn run_chat(
         engine_config.quality_threshold = state.quality_threshold;
         let safety = SafetyChecker::from_config(&config.iteration, &config.safety);
 
+        let task_embedding = embedder
+            .embed_task(&task.description, skill_registry.all())
+            .await;
         let ranked_skills = {
             let store_guard = store.as_ref().and_then(|s| s.lock().ok());
             selector.select(
                 &task.description,
+                task_embedding.as_deref(),
                 task.category.as_deref(),
                 skill_registry.all(),
                 store_guard.as_deref(),
@@ -127,8 +139,16 @@ pub async fn run_chat(
             match store_guard.as_deref() {
                 Some(s) => {
                     let token_budget = engine_config.token_budget / 10;
-                    recall::recall(s, trimmed, task.category.as_deref(), token_budget)
-                        .unwrap_or_default()
+                    let counter =
+                        tokenizer::for_provider_model(provider.as_ref(), &state.model_ref.model);
+                    recall::recall(
+                        s,
+                        trimmed,
+                        task.category.as_deref(),
+                        token_budget,
+                        counter.as_ref(),
+                    )
+                    .unwrap_or_default()
                 }
                 None => HistoryRecall::default(),
             }
@@ -136,9 +156,12 @@ pub async fn run_chat(
 
         let ctx = SessionContext {
             soul: soul.clone(),
+            instructions: instructions.clone(),
+            code_index: code_index.clone(),
+            lsp: lsp.clone(),
             ranked_skills,
             recall,
-            tools: mcp_tools.clone(),
+            tools: tools.map(|t| t.defs().to_vec()).unwrap_or_default(),
             skill_registry: skill_registry.clone(),
             conversation_history: if state.conversation_summary.is_empty() {
                 None
@@ -147,6 +170,7 @@ pub async fn run_chat(
             },
         };
 
+        let escalation = EscalationPolicy::for_provider(&config.models, &provider);
         let mut orchestrator = Orchestrator::new(
             provider.clone(),
             ModelRoles::from_config(
@@ -160,7 +184,8 @@ pub async fn run_chat(
             safety,
             skill_registry.clone(),
             store.clone(),
-        );
+        )
+        .with_escalation(escalation);
         {
             let inner: Option<Box<dyn Fn(crate::core::types::ProgressEvent) + Send>> = if !quiet {
                 Some(Box::new(super::progress::terminal_progress()))
@@ -175,9 +200,7 @@ pub async fn run_chat(
             orchestrator = orchestrator.with_progress(progress);
         }
 
-        let mcp_ref = mcp.as_deref_mut();
-
-        match orchestrator.run(task, &ctx, mcp_ref, integrations).await {
+        match orchestrator.run(task, &ctx, tools).await {
             Ok(result) => {
                 println!("{}", result.output.content);
                 state.total_cost += result.cost;
@@ -192,35 +215,11 @@ pub async fn run_chat(
                 });
 
                 // Accumulate conversation history for cross-message context.
-                // Truncate the response to keep the summary compact.
-                let response_summary = if result.output.content.len() > 500 {
-                    let end = result
-                        .output
-                        .content
-                        .char_indices()
-                        .nth(500)
-                        .map(|(i, _)| i)
-                        .unwrap_or(result.output.content.len());
-                    format!("{}...", &result.output.content[..end])
-                } else {
-                    result.output.content.clone()
-                };
-                state.conversation_summary.push_str(&format!(
-                    "User: {}\nAssistant: {}\n\n",
-                    trimmed, response_summary,
-                ));
-                // Cap the conversation summary to prevent unbounded growth (~8K chars)
-                const MAX_SUMMARY_LEN: usize = 8000;
-                if state.conversation_summary.len() > MAX_SUMMARY_LEN {
-                    // Trim from the front, keeping the most recent exchanges
-                    let trim_point = state.conversation_summary.len() - MAX_SUMMARY_LEN;
-                    let safe_trim = state.conversation_summary[trim_point..]
-                        .find("\n\n")
-                        .map(|i| trim_point + i + 2)
-                        .unwrap_or(trim_point);
-                    state.conversation_summary =
-                        state.conversation_summary[safe_trim..].to_string();
-                }
+                remember_exchange(
+                    &mut state.conversation_summary,
+                    trimmed,
+                    &result.output.content,
+                );
 
                 // Log usage event
                 if let Some(ref s) = store {
@@ -250,6 +249,33 @@ pub async fn run_chat(
     Ok(())
 }
 
+/// Append one exchange to a running conversation summary. The response is
+/// truncated and the summary capped (~8K chars, oldest exchanges dropped)
+/// so it stays compact.
+pub(crate) fn remember_exchange(summary: &mut String, input: &str, output: &str) {
+    let response_summary = match output.char_indices().nth(500) {
+        Some((end, _)) => format!("{}...", &output[..end]),
+        None => output.to_string(),
+    };
+    summary.push_str(&format!(
+        "User: {}\nAssistant: {}\n\n",
+        input, response_summary,
+    ));
+    const MAX_SUMMARY_LEN: usize = 8000;
+    if summary.len() > MAX_SUMMARY_LEN {
+        // Trim from the front, keeping the most recent exchanges
+        let mut trim_point = summary.len() - MAX_SUMMARY_LEN;
+        while !summary.is_char_boundary(trim_point) {
+            trim_point += 1;
+        }
+        let safe_trim = summary[trim_point..]
+            .find("\n\n")
+            .map(|i| trim_point + i + 2)
+            .unwrap_or(trim_point);
+        summary.drain(..safe_trim);
+    }
+}
+
 fn read_input() -> Option<String> {
     use std::io::{self, BufRead, Write};
 
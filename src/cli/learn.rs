@@ -1,12 +1,19 @@
 // src/cli/learn.rs — Pattern review, skill approval, and soul evolution
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use super::LearnAction;
+use crate::infra::config::{Config, SkillsConfig};
 use crate::infra::paths;
+use crate::learner::experiment::{self, Verdict};
+use crate::learner::skill_selector::{self, SkillSelector};
+use crate::learner::types::Signal;
 use crate::memory::schema;
 use crate::memory::store::Store;
 use crate::provider::resolver;
+use crate::skills::community::{self, Fetched, Scope, Trust};
+use crate::skills::embedding::SkillEmbedder;
+use crate::skills::registry::SkillRegistry;
 use crate::soul::{evolution::SoulEvolution, loader};
 
 /// Handle the `openkoi learn` command.
@@ -16,8 +23,46 @@ pub async fn run_learn(action: Option<LearnAction>) -> anyhow::Result<()> {
         Some(LearnAction::List) => {
             show_patterns().await?;
         }
-        Some(LearnAction::Install { name }) => {
-            install_skill(&name).await?;
+        Some(LearnAction::Install {
+            source,
+            sha256,
+            subdir,
+            project,
+            locked,
+            yes,
+        }) => {
+            let scope = scope(project)?;
+            match source {
+                _ if locked => install_locked(&scope).await?,
+                Some(source) => {
+                    let options = InstallOptions {
+                        sha256,
+                        subdir,
+                        yes,
+                    };
+                    install_skill(&source, &scope, options).await?;
+                }
+                None => anyhow::bail!("Give a skill to install, or --locked"),
+            }
+        }
+        Some(LearnAction::Update { name, project, yes }) => {
+            update_skills(name.as_deref(), &scope(project)?, yes).await?;
+        }
+        Some(LearnAction::Remove { name, project }) => {
+            if community::remove(&name, &scope(project)?)? {
+                println!("  Removed: {}", name);
+            } else {
+                println!("Skill '{}' is not installed.", name);
+            }
+        }
+        Some(LearnAction::Experiments) => {
+            show_experiments()?;
+        }
+        Some(LearnAction::Promote { variant, force }) => {
+            promote_variant(&variant, force)?;
+        }
+        Some(LearnAction::Why { task, category }) => {
+            explain_selection(&task, category.as_deref()).await?;
         }
         Some(LearnAction::EvolveSoul) => {
             evolve_soul().await?;
@@ -26,7 +71,7 @@ pub async fn run_learn(action: Option<LearnAction>) -> anyhow::Result<()> {
             // Interactive: let the user pick what to do
             let options = vec![
                 "list          — View detected patterns and proposed skills",
-                "install       — Install a proposed or community skill",
+                "install       — Install a proposed skill",
                 "evolve-soul   — Propose soul evolution from learnings",
             ];
             let choice = inquire::Select::new("Learn action:", options)
@@ -223,35 +268,215 @@ fn copy_dir_contents(src: &std::path::Path, dst: &std::path::Path) -> anyhow::Re
     Ok(())
 }
 
-/// Install a skill by name. For now, this moves a proposed skill to user skills.
-/// In the future, this could fetch from a community registry.
-async fn install_skill(name: &str) -> anyhow::Result<()> {
-    // Check if it's a proposed skill first
-    let proposed_path = paths::proposed_skills_dir().join(name);
-    if proposed_path.exists() {
-        approve_proposed_skill(name)?;
+/// Options for installing a community skill.
+#[derive(Default)]
+struct InstallOptions {
+    sha256: Option<String>,
+    subdir: Option<String>,
+    yes: bool,
+}
+
+fn scope(project: bool) -> anyhow::Result<Scope> {
+    Ok(if project {
+        Scope::Project(std::env::current_dir()?)
+    } else {
+        Scope::User
+    })
+}
+
+fn skills_config() -> SkillsConfig {
+    Config::load().map(|c| c.skills).unwrap_or_default()
+}
+
+/// Install a skill. Bare names are proposed skills first, then index
+/// entries; anything else is fetched from its source, checked, shown and
+/// confirmed before it is installed.
+async fn install_skill(spec: &str, scope: &Scope, options: InstallOptions) -> anyhow::Result<()> {
+    if community::Origin::parse(spec).is_none() {
+        // Check if it's a proposed skill first
+        let proposed_path = paths::proposed_skills_dir().join(spec);
+        if proposed_path.exists() {
+            approve_proposed_skill(spec)?;
+            return Ok(());
+        }
+
+        // Check if it's already installed
+        let user_path = paths::user_skills_dir().join(spec);
+        if user_path.exists() {
+            println!(
+                "Skill '{}' is already installed at {}",
+                spec,
+                user_path.display()
+            );
+            return Ok(());
+        }
+    }
+
+    let config = skills_config();
+    let mut resolved = community::resolve(spec, &config).await?;
+    if let Some(subdir) = options.subdir {
+        resolved.subdir = Some(subdir);
+    }
+    if let Some(sha256) = options.sha256 {
+        if resolved
+            .sha256
+            .as_ref()
+            .is_some_and(|pinned| *pinned != sha256)
+        {
+            anyhow::bail!("--sha256 does not match the index entry for '{}'", spec);
+        }
+        resolved.sha256 = Some(sha256);
+    }
+
+    eprintln!("Fetching {}...", resolved.origin.source());
+    let fetched = community::fetch(&resolved).await?;
+    let trust = community::verify(&fetched, &resolved, &config)?;
+    preview(&fetched, trust);
+    if !confirm(&format!("Install '{}'?", fetched.name), options.yes) {
+        println!("  Not installed.");
+        return Ok(());
+    }
+    let target = community::install(&fetched, &resolved, scope)?;
+    println!("  Installed: {}", fetched.name);
+    println!("  Saved to {}", target.display());
+    println!("  Pinned in {}", scope.lockfile().display());
+    Ok(())
+}
+
+/// Install every skill the lockfile pins, at the pinned commit and digest.
+async fn install_locked(scope: &Scope) -> anyhow::Result<()> {
+    let lock = community::Lockfile::load(&scope.lockfile())?;
+    if lock.skills.is_empty() {
+        println!("No skills pinned in {}", scope.lockfile().display());
         return Ok(());
     }
+    // The pinned digest is what was reviewed (and signature-checked) when
+    // the skill was first installed.
+    let config = SkillsConfig {
+        require_signature: false,
+        ..skills_config()
+    };
+    for (name, entry) in &lock.skills {
+        if community::is_current(name, entry, scope) {
+            println!("  {:<24} up to date", name);
+            continue;
+        }
+        let mut pinned = entry.pinned()?;
+        pinned.name = Some(name.clone());
+        let fetched = community::fetch(&pinned).await?;
+        community::verify(&fetched, &pinned, &config)?;
+        community::install(&fetched, &pinned, scope)?;
+        println!("  {:<24} installed {}", name, short(&entry.sha256));
+    }
+    Ok(())
+}
 
-    // Check if it's already installed
-    let user_path = paths::user_skills_dir().join(name);
-    if user_path.exists() {
+/// Fetch each locked skill's spec again and install the ones that changed.
+async fn update_skills(name: Option<&str>, scope: &Scope, yes: bool) -> anyhow::Result<()> {
+    let lock = community::Lockfile::load(&scope.lockfile())?;
+    let entries: Vec<_> = match name {
+        Some(name) => match lock.skills.get(name) {
+            Some(entry) => vec![(name.to_string(), entry.clone())],
+            None => anyhow::bail!(
+                "Skill '{}' is not pinned in {}",
+                name,
+                scope.lockfile().display()
+            ),
+        },
+        None => lock.skills.into_iter().collect(),
+    };
+    if entries.is_empty() {
+        println!("No community skills installed.");
+        return Ok(());
+    }
+
+    let config = skills_config();
+    for (name, entry) in entries {
+        let mut resolved = community::resolve(&entry.spec, &config).await?;
+        resolved.name = Some(name.clone());
+        if resolved.subdir.is_none() {
+            resolved.subdir = entry.subdir.clone();
+        }
+        let fetched = community::fetch(&resolved).await?;
+        if fetched.sha256 == entry.sha256 {
+            println!("  {:<24} up to date", name);
+            continue;
+        }
+        let trust = community::verify(&fetched, &resolved, &config)?;
         println!(
-            "Skill '{}' is already installed at {}",
+            "  {:<24} {} -> {}",
             name,
-            user_path.display()
+            short(&entry.sha256),
+            short(&fetched.sha256)
         );
-        return Ok(());
+        preview(&fetched, trust);
+        if !confirm(&format!("Update '{}'?", name), yes) {
+            println!("  Skipped.");
+            continue;
+        }
+        community::install(&fetched, &resolved, scope)?;
+        println!("  Updated: {}", name);
     }
+    Ok(())
+}
 
-    // Not a local skill — community registry is not yet available
-    println!("Skill '{}' not found in proposed skills.", name);
+/// Show what is about to be installed: the SKILL.md, what it needs from the
+/// environment, and how it was verified.
+fn preview(fetched: &Fetched, trust: Trust) {
+    println!("--- {}/SKILL.md ---", fetched.name);
+    println!("{}", fetched.skill_md.trim_end());
+    println!("--- end ---");
     println!();
-    println!("Community skill registry is not yet available.");
-    println!("To create a custom skill, add a SKILL.md to:");
-    println!("  {}", paths::user_skills_dir().join(name).display());
 
-    Ok(())
+    let bins = fetched
+        .metadata
+        .requires_bins
+        .as_deref()
+        .unwrap_or_default();
+    if !bins.is_empty() {
+        println!("  Requires binaries:");
+        for bin in bins {
+            let found = if which::which(bin).is_ok() {
+                "found"
+            } else {
+                "missing"
+            };
+            println!("    {:<20} {}", bin, found);
+        }
+    }
+    let envs = fetched.metadata.requires_env.as_deref().unwrap_or_default();
+    if !envs.is_empty() {
+        println!("  Requires environment:");
+        for env in envs {
+            let set = if std::env::var(env).is_ok() {
+                "set"
+            } else {
+                "not set"
+            };
+            println!("    {:<20} {}", env, set);
+        }
+    }
+    if let Some(ref rev) = fetched.rev {
+        println!("  Commit:  {}", rev);
+    }
+    let trust = match trust {
+        Trust::Signed => "signed by a trusted key",
+        Trust::Pinned => "matches the pinned sha256",
+        Trust::Unverified => "not pinned or signed",
+    };
+    println!("  SHA-256: {} ({})", fetched.sha256, trust);
+    println!();
+}
+
+fn confirm(prompt: &str, yes: bool) -> bool {
+    yes || inquire::Confirm::new(prompt)
+        .with_default(false)
+        .prompt()
+        .unwrap_or(false)
+}
+
+fn short(sha256: &str) -> &str {
+    &sha256[..sha256.len().min(12)]
 }
 
 /// Interactive skill install: list available proposed skills and let user pick one.
@@ -268,7 +493,7 @@ async fn install_skill_interactive() -> anyhow::Result<()> {
 
     if entries.is_empty() {
         println!("No proposed skills found to install.");
-        println!("Community skill registry is not yet available.");
+        println!("Install a community skill with `openkoi learn install <name|url|path>`.");
         return Ok(());
     }
 
@@ -282,7 +507,214 @@ async fn install_skill_interactive() -> anyhow::Result<()> {
         .prompt()
         .map_err(|_| anyhow::anyhow!("Selection cancelled"))?;
 
-    install_skill(&choice).await
+    install_skill(&choice, &Scope::User, InstallOptions::default()).await
+}
+
+fn open_store() -> anyhow::Result<Option<Store>> {
+    let db_path = paths::db_path();
+    if !db_path.exists() {
+        return Ok(None);
+    }
+    let conn = rusqlite::Connection::open(&db_path)?;
+    schema::run_migrations(&conn)?;
+    Ok(Some(Store::new(conn)))
+}
+
+/// Print each skill's variants against it, per category and overall.
+fn show_experiments() -> anyhow::Result<()> {
+    let registry = SkillRegistry::new();
+    let rows = match open_store()? {
+        Some(store) => store.query_all_skill_effectiveness()?,
+        None => Vec::new(),
+    };
+    let config = Config::load().unwrap_or_default().skills.experiments;
+    let experiments = experiment::report(registry.all(), &rows, &config);
+    if experiments.is_empty() {
+        println!("No skill variants found.");
+        println!();
+        println!("Add a variant by copying a skill and naming it <skill>@<variant>");
+        println!("in its SKILL.md. Tasks then try both and record which one ran.");
+        return Ok(());
+    }
+
+    for exp in &experiments {
+        println!("{}", exp.base);
+        println!(
+            "  {:<24} {:<12} {:>16} {:>16} {:>9}  verdict",
+            "variant", "category", "incumbent", "variant", "P(better)"
+        );
+        for c in &exp.comparisons {
+            println!(
+                "  {:<24} {:<12} {:>9.2} ({:>4}) {:>9.2} ({:>4}) {:>8.0}%  {}",
+                c.variant.name,
+                c.category,
+                c.incumbent.avg_score,
+                c.incumbent.samples,
+                c.variant.avg_score,
+                c.variant.samples,
+                c.probability * 100.0,
+                c.verdict,
+            );
+        }
+        println!();
+    }
+    println!(
+        "Promote needs {:.0}% confidence and {} runs on each side (openkoi learn promote <variant>).",
+        config.confidence * 100.0,
+        config.min_samples
+    );
+    Ok(())
+}
+
+/// Promote a variant that beats its incumbent overall.
+fn promote_variant(variant: &str, force: bool) -> anyhow::Result<()> {
+    let registry = SkillRegistry::new();
+    let store =
+        open_store()?.ok_or_else(|| anyhow::anyhow!("No database found. Run some tasks first."))?;
+    let config = Config::load().unwrap_or_default().skills.experiments;
+    let rows = store.query_all_skill_effectiveness()?;
+    let experiments = experiment::report(registry.all(), &rows, &config);
+    let overall = experiments
+        .iter()
+        .find_map(|e| e.overall(variant))
+        .ok_or_else(|| anyhow::anyhow!("'{}' is not a skill variant", variant))?;
+
+    if overall.verdict != Verdict::Promote && !force {
+        anyhow::bail!(
+            "'{}' has not beaten '{}' yet: {:.2} over {} runs vs {:.2} over {} runs, {:.0}% likely better \
+             (needs {:.0}% and {} runs each; --force to promote anyway)",
+            variant,
+            overall.incumbent.name,
+            overall.variant.avg_score,
+            overall.variant.samples,
+            overall.incumbent.avg_score,
+            overall.incumbent.samples,
+            overall.probability * 100.0,
+            config.confidence * 100.0,
+            config.min_samples,
+        );
+    }
+
+    let archive = paths::skills_dir().join("archive");
+    let dir = experiment::promote(&registry, variant, &store, &archive)?;
+    println!("  Promoted: {} -> {}", variant, overall.incumbent.name);
+    println!("  Saved to {}", dir.display());
+    println!("  Previous version archived in {}", archive.display());
+    Ok(())
+}
+
+/// Rank skills for a task the way a run would, showing what each signal
+/// adds to every skill's score.
+async fn explain_selection(task: &str, category: Option<&str>) -> anyhow::Result<()> {
+    let config = Config::load().unwrap_or_default();
+    let store = open_store()?.map(Mutex::new);
+    let mut registry = SkillRegistry::new();
+
+    let providers = resolver::discover_providers().await;
+    let provider = resolver::pick_default_model(&providers)
+        .and_then(|model| resolver::find_provider(&providers, &model.provider).cloned());
+    let task_embedding = match provider {
+        Some(provider) => {
+            let embedder = SkillEmbedder::new(provider, &config.skills.embeddings);
+            let embedded = embedder.embed_registry(&mut registry, store.as_ref()).await;
+            let task_embedding = embedder.embed_task(task, registry.all()).await;
+            let status = if !config.skills.embeddings.enabled {
+                "off ([skills.embeddings] enabled = false)".to_string()
+            } else if task_embedding.is_none() {
+                format!("unavailable from {}", embedder.provider_id())
+            } else {
+                format!("{} ({} skills embedded)", embedder.provider_id(), embedded)
+            };
+            println!("Embeddings: {}", status);
+            task_embedding
+        }
+        None => {
+            println!("Embeddings: no provider available");
+            None
+        }
+    };
+    println!("Category:   {}", category.unwrap_or("(none)"));
+    println!();
+
+    let selector = SkillSelector::with_experiments(config.skills.experiments.clone());
+    let store_guard = store.as_ref().and_then(|s| s.lock().ok());
+    let store = store_guard.as_deref();
+    let selected: Vec<String> = selector
+        .select(
+            task,
+            task_embedding.as_deref(),
+            category,
+            registry.all(),
+            store,
+        )
+        .into_iter()
+        .map(|rs| rs.skill.name)
+        .collect();
+    let ranked = selector.explain(
+        task,
+        task_embedding.as_deref(),
+        category,
+        registry.all(),
+        store,
+    );
+
+    let (scored, unscored): (Vec<_>, Vec<_>) =
+        ranked.into_iter().partition(|rs| !rs.signals.is_empty());
+    if scored.is_empty() {
+        println!("No skill matches this task.");
+    }
+    for (i, rs) in scored.iter().enumerate() {
+        let mark = if selected.contains(&rs.skill.name) {
+            "selected"
+        } else {
+            ""
+        };
+        println!(
+            "{:>2}. {:<32} {:>5.2}  {}",
+            i + 1,
+            rs.skill.name,
+            rs.score,
+            mark
+        );
+        for signal in &rs.signals {
+            println!(
+                "      {:<46} {:>+6.2}",
+                describe_signal(signal),
+                skill_selector::contribution(signal)
+            );
+        }
+    }
+    if !unscored.is_empty() {
+        println!();
+        println!(
+            "{} more skill(s) had no signal for this task.",
+            unscored.len()
+        );
+    }
+    println!();
+    println!(
+        "Skills scoring over 0.1 (or named in the task) are selected, best 5 first; \
+         one of each skill's variants runs."
+    );
+    Ok(())
+}
+
+fn describe_signal(signal: &Signal) -> String {
+    match signal {
+        Signal::Effectiveness {
+            category,
+            avg_score,
+            sample_count,
+        } => format!(
+            "effectiveness {:.2} over {} run(s) in {}",
+            avg_score, sample_count, category
+        ),
+        Signal::SemanticMatch { similarity } => format!("semantic similarity {:.2}", similarity),
+        Signal::ExplicitRequest => "named in the task".into(),
+        Signal::RecallSuggestion => "category match".into(),
+        Signal::UserApproved { confidence } => format!("approved pattern ({:.2})", confidence),
+        Signal::Experiment { base, arms } => format!("picked from {} versions of {}", arms, base),
+    }
 }
 
 /// Propose soul evolution by analyzing accumulated learnings,
@@ -1,5 +1,8 @@
 // src/cli/mod.rs — CLI definition (clap derive)
 
+pub mod audit;
+pub mod batch;
+pub mod bench;
 pub mod chat;
 pub mod connect;
 pub mod export;
@@ -7,11 +10,12 @@ pub mod init;
 pub mod learn;
 pub mod migrate;
 pub mod progress;
+pub mod review;
 pub mod run;
 pub mod status;
 pub mod update;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "openkoi", about = "Self-iterating AI agent", version)]
@@ -48,14 +52,123 @@ pub struct Cli {
     #[arg(long)]
     pub config: Option<String>,
 
+    /// Keep file changes in memory and print them as a diff instead of
+    /// writing them (other file-changing tools are refused)
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Like --dry-run, but write the accepted changes to FILE as a patch
+    /// for `git apply`
+    #[arg(long, value_name = "FILE")]
+    pub patch: Option<String>,
+
+    /// What to print on stdout: the task result, the full result as JSON,
+    /// progress events and the result as JSON lines (`ndjson`), or
+    /// (`patch`) only the accepted changes as a patch, leaving the working
+    /// tree untouched
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
+    /// Don't recall from or save to memory (history, learnings, usage)
+    #[arg(long)]
+    pub no_memory: bool,
+
+    /// Evaluator skill to judge the task with (default: chosen by category)
+    #[arg(long, value_name = "NAME")]
+    pub evaluator: Option<String>,
+
+    /// Spending cap for this run in USD (overrides safety.max_cost_usd)
+    #[arg(long, value_name = "USD")]
+    pub max_cost: Option<f64>,
+
+    /// Exit non-zero when the final score is below this (3: below, 4:
+    /// budget or time limit hit first); provider failures always exit 5
+    #[arg(long, value_name = "SCORE")]
+    pub fail_under: Option<f32>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Ndjson,
+    Patch,
+}
+
+/// Output format of `openkoi review`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReviewFormat {
+    Text,
+    Json,
+    Sarif,
+    Markdown,
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Interactive chat session
     Chat,
+    /// Serve the Agent Client Protocol on stdio for editors such as Zed
+    Acp,
+    /// Run every task in a manifest, each in its own working directory
+    Batch {
+        /// Manifest file (YAML) listing the tasks
+        manifest: String,
+        /// Tasks running at once (default: the manifest's, or 4)
+        #[arg(short = 'j', long)]
+        concurrency: Option<usize>,
+        /// Re-run only the tasks that were not accepted in the last report
+        #[arg(long)]
+        resume: bool,
+        /// Report file (default: next to the manifest, as NAME.report.json)
+        #[arg(long, value_name = "FILE")]
+        report: Option<String>,
+    },
+    /// Run a benchmark suite across models, skills, souls and configs
+    Bench {
+        /// Suite file (YAML) listing the cases and variants
+        suite: String,
+        /// Runs of each case under each variant (default: the suite's, or 1)
+        #[arg(short = 'n', long)]
+        repeat: Option<u32>,
+        /// Runs at once (default: the suite's, or 1)
+        #[arg(short = 'j', long)]
+        concurrency: Option<usize>,
+    },
+    /// Score existing changes without executing anything: the uncommitted
+    /// diff by default, or a commit range, pull request, patch or files
+    Review {
+        /// Commit range (`main..feature`), or a base ref to compare HEAD with
+        range: Option<String>,
+        /// Review only the staged changes
+        #[arg(long, conflicts_with_all = ["range", "pr", "patch_file", "files"])]
+        staged: bool,
+        /// Review a GitHub pull request (number or URL; needs `gh`)
+        #[arg(long, conflicts_with_all = ["range", "patch_file", "files"])]
+        pr: Option<String>,
+        /// Review a patch file
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["range", "files"])]
+        patch_file: Option<String>,
+        /// Review these files as they are, rather than a diff
+        #[arg(long, num_args = 1.., conflicts_with = "range")]
+        files: Vec<String>,
+        /// Report format
+        #[arg(long, value_enum, default_value_t = ReviewFormat::Text)]
+        format: ReviewFormat,
+        /// Evaluator skill to judge with (default: chosen from the files)
+        #[arg(long, value_name = "NAME")]
+        evaluator: Option<String>,
+        /// Skip tests, linters and language-server diagnostics
+        #[arg(long)]
+        no_checks: bool,
+        /// Hand the findings to the iteration loop to fix them
+        #[arg(long)]
+        fix: bool,
+    },
     /// Review learned patterns and proposed skills
     Learn {
         #[command(subcommand)]
@@ -108,6 +221,30 @@ pub enum Commands {
         #[arg(long)]
         check: bool,
     },
+    /// Show or verify the audit log of tool calls and outbound messages
+    Audit {
+        /// Only records for this task id (prefix match)
+        #[arg(long)]
+        task: Option<String>,
+        /// Only calls to this tool, or messages sent through this integration
+        #[arg(long)]
+        tool: Option<String>,
+        /// Only messages posted to this channel
+        #[arg(long)]
+        channel: Option<String>,
+        /// Only records at or after this time (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+        /// Show at most this many of the latest records (0 = all)
+        #[arg(short = 'n', long, default_value = "50")]
+        limit: usize,
+        /// Check the hash chain instead of listing records
+        #[arg(long)]
+        verify: bool,
+        /// Print records as JSON lines
+        #[arg(long)]
+        json: bool,
+    },
     /// Disconnect / logout from a provider or integration
     Disconnect {
         /// Provider or integration to disconnect — interactive picker if omitted
@@ -150,10 +287,63 @@ pub enum Commands {
 pub enum LearnAction {
     /// List detected patterns
     List,
-    /// Install a community skill
+    /// Install a proposed skill, or a community skill from a git URL,
+    /// tarball URL, path or index name
     Install {
-        /// Skill name or URL
+        /// Skill name, git URL (`url#rev` to pin), .tar.gz URL or directory
+        #[arg(required_unless_present = "locked")]
+        source: Option<String>,
+        /// Expected SHA-256 of the skill's files
+        #[arg(long)]
+        sha256: Option<String>,
+        /// Directory inside the repository or archive that holds SKILL.md
+        #[arg(long)]
+        subdir: Option<String>,
+        /// Install into .agents/skills and pin in .openkoi/skills.lock
+        #[arg(long)]
+        project: bool,
+        /// Install exactly what the lockfile pins
+        #[arg(long, conflicts_with_all = ["source", "sha256", "subdir"])]
+        locked: bool,
+        /// Install without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Update installed community skills from their sources
+    Update {
+        /// Skill to update (all when omitted)
+        name: Option<String>,
+        /// Update the project's skills instead of the user's
+        #[arg(long)]
+        project: bool,
+        /// Update without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Remove an installed community skill and unpin it
+    Remove {
         name: String,
+        /// Remove from the project's skills instead of the user's
+        #[arg(long)]
+        project: bool,
+    },
+    /// Compare skill variants (name@variant) with the skills they vary
+    Experiments,
+    /// Replace a skill with its variant once the variant wins
+    Promote {
+        /// Variant to promote, e.g. release-notes@v2
+        variant: String,
+        /// Promote even without a confident result
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show how skills rank for a task and why
+    Why {
+        /// Task description, as you would pass it to `openkoi`
+        task: String,
+        /// Task category, for effectiveness history and category matches
+        #[arg(long)]
+        category: Option<String>,
     },
     /// Propose soul evolution from accumulated learnings
     EvolveSoul,
@@ -26,6 +26,8 @@ pub fn terminal_progress() -> impl Fn(ProgressEvent) + Send + 'static {
         ProgressEvent::ToolCall { name, iteration } => {
             eprintln!("[iter {}]   tool: {}", iteration, name);
         }
+        // Reported once per iteration through `ToolCall` instead.
+        ProgressEvent::ToolStarted { .. } | ProgressEvent::ToolFinished { .. } => {}
         ProgressEvent::IterationEnd {
             iteration,
             score,
@@ -40,6 +42,14 @@ pub fn terminal_progress() -> impl Fn(ProgressEvent) + Send + 'static {
         ProgressEvent::SafetyWarning { message } => {
             eprintln!("[safety] {}", message);
         }
+        ProgressEvent::Escalated {
+            iteration,
+            from,
+            to,
+            reason,
+        } => {
+            eprintln!("[iter {}] model {} -> {} ({})", iteration, from, to, reason);
+        }
         ProgressEvent::Complete {
             iterations,
             total_tokens,
@@ -54,6 +64,16 @@ pub fn terminal_progress() -> impl Fn(ProgressEvent) + Send + 'static {
     }
 }
 
+/// Build a progress callback that writes each event to stdout as one JSON
+/// object per line (`{"event": "iteration_end", ...}`), for `--output ndjson`.
+pub fn ndjson_progress() -> impl Fn(ProgressEvent) + Send + 'static {
+    move |event| {
+        if let Ok(line) = serde_json::to_string(&event) {
+            println!("{line}");
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,6 +103,10 @@ mod tests {
                 ProgressEvent::ToolCall { name, iteration } => {
                     format!("[iter {}]   tool: {}", iteration, name)
                 }
+                ProgressEvent::ToolStarted { name, .. } => format!("[tool] {}", name),
+                ProgressEvent::ToolFinished { name, failed, .. } => {
+                    format!("[tool] {} failed={}", name, failed)
+                }
                 ProgressEvent::IterationEnd {
                     iteration,
                     score,
@@ -93,6 +117,12 @@ mod tests {
                     iteration, score, decision, cost_so_far,
                 ),
                 ProgressEvent::SafetyWarning { message } => format!("[safety] {}", message),
+                ProgressEvent::Escalated {
+                    iteration,
+                    from,
+                    to,
+                    reason,
+                } => format!("[iter {}] model {} -> {} ({})", iteration, from, to, reason),
                 ProgressEvent::Complete {
                     iterations,
                     total_tokens,
@@ -108,6 +138,27 @@ mod tests {
         (cb, log)
     }
 
+    #[test]
+    fn test_ndjson_event_shape() {
+        let line = serde_json::to_value(ProgressEvent::IterationEnd {
+            iteration: 2,
+            score: 0.5,
+            decision: IterationDecision::AcceptBest,
+            cost_so_far: 0.25,
+        })
+        .unwrap();
+        assert_eq!(
+            line,
+            serde_json::json!({
+                "event": "iteration_end",
+                "iteration": 2,
+                "score": 0.5,
+                "decision": "accept_best",
+                "cost_so_far": 0.25,
+            })
+        );
+    }
+
     #[test]
     fn test_plan_ready_format() {
         let (cb, log) = capturing_progress();
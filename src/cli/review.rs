@@ -0,0 +1,794 @@
+// src/cli/review.rs — Review existing changes with the evaluator stack
+//
+// Collects a diff (working tree, staged, commit range, pull request or
+// patch file) or a list of files, runs `EvaluatorFramework` on it without
+// executing anything, and reports scored dimensions and findings as text,
+// JSON, SARIF or Markdown. `--fix` hands the findings to the iteration loop.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde_json::json;
+
+use super::run::{self, ChangeMode, Reporting, RunOutcome};
+use super::ReviewFormat;
+use crate::core::instructions::ProjectInstructions;
+use crate::core::token_optimizer::{TokenOptimizer, INSTRUCTIONS_TOKEN_BUDGET};
+use crate::core::types::{DimensionScore, ExecutionOutput, Finding, Severity, TaskInput};
+use crate::evaluator::EvaluatorFramework;
+use crate::index::CodeIndex;
+use crate::infra::config::Config;
+use crate::lsp::LspManager;
+use crate::memory::store::Store;
+use crate::provider::roles::ModelRoles;
+use crate::provider::{ModelProvider, ModelRef, TokenUsage};
+use crate::skills::registry::SkillRegistry;
+use crate::tools::overlay::Workspace;
+use crate::tools::ToolRegistry;
+
+/// Largest diff (in bytes) sent to the evaluator; the rest is cut.
+const MAX_REVIEW_BYTES: usize = 100_000;
+
+/// What to review.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewSource {
+    /// Uncommitted changes (staged and unstaged) against HEAD.
+    WorkingTree,
+    Staged,
+    /// A commit range (`a..b`, `a...b`), or a base to compare HEAD with.
+    Range(String),
+    /// A GitHub pull request number or URL (fetched with `gh`).
+    PullRequest(String),
+    Patch(PathBuf),
+    Files(Vec<String>),
+}
+
+impl ReviewSource {
+    fn describe(&self) -> String {
+        match self {
+            ReviewSource::WorkingTree => "the uncommitted changes".into(),
+            ReviewSource::Staged => "the staged changes".into(),
+            ReviewSource::Range(range) if range.contains("..") => format!("commits {range}"),
+            ReviewSource::Range(base) => format!("the changes on HEAD since {base}"),
+            ReviewSource::PullRequest(pr) => format!("pull request {pr}"),
+            ReviewSource::Patch(path) => format!("the patch {}", path.display()),
+            ReviewSource::Files(files) => files.join(", "),
+        }
+    }
+}
+
+/// Options for `openkoi review`.
+#[derive(Debug, Clone)]
+pub struct ReviewOptions {
+    pub source: ReviewSource,
+    pub format: ReviewFormat,
+    /// Evaluator skill to judge with (default: chosen from the files).
+    pub evaluator: Option<String>,
+    /// Skip tests, linters and language-server diagnostics.
+    pub no_checks: bool,
+    /// Hand the findings to the iteration loop afterwards.
+    pub fix: bool,
+    /// Iteration budget and quality threshold for `fix`.
+    pub max_iterations: u8,
+    pub quality_threshold: f32,
+}
+
+/// The evaluation of a review, as rendered.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReviewReport {
+    pub source: String,
+    pub files: Vec<String>,
+    pub evaluator: String,
+    pub score: f32,
+    pub tests_passed: bool,
+    pub static_analysis_passed: bool,
+    pub dimensions: Vec<DimensionScore>,
+    pub findings: Vec<Finding>,
+    pub suggestion: String,
+    pub usage: TokenUsage,
+}
+
+/// A directory holding the reviewed version of the code, for tests and
+/// linters. Cleaned up on drop.
+enum Checkout {
+    /// The current directory already has it.
+    Current,
+    /// `git worktree` at the reviewed revision.
+    Worktree(PathBuf),
+    /// A copy of the project with the patch applied.
+    Patched(Workspace),
+}
+
+impl Checkout {
+    fn path(&self) -> &Path {
+        match self {
+            Checkout::Current => Path::new("."),
+            Checkout::Worktree(path) => path,
+            Checkout::Patched(workspace) => workspace.path(),
+        }
+    }
+}
+
+impl Drop for Checkout {
+    fn drop(&mut self) {
+        if let Checkout::Worktree(path) = self {
+            let _ = std::process::Command::new("git")
+                .args(["worktree", "remove", "--force"])
+                .arg(&*path)
+                .output();
+        }
+    }
+}
+
+/// The changes under review.
+struct Collected {
+    content: String,
+    files: Vec<String>,
+    checkout: Checkout,
+}
+
+/// Run `openkoi review`.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_review(
+    options: ReviewOptions,
+    provider: Arc<dyn ModelProvider>,
+    model_ref: &ModelRef,
+    config: &Config,
+    store: Option<Arc<Mutex<Store>>>,
+    tools: Option<&ToolRegistry>,
+    code_index: Option<Arc<CodeIndex>>,
+    lsp: Option<Arc<LspManager>>,
+    changes: &ChangeMode,
+    quiet: bool,
+) -> anyhow::Result<RunOutcome> {
+    let collected = collect(&options.source, !options.no_checks).await?;
+    if collected.content.trim().is_empty() {
+        anyhow::bail!("Nothing to review in {}", options.source.describe());
+    }
+    if !quiet {
+        eprintln!(
+            "[review] {} ({} file(s))",
+            options.source.describe(),
+            collected.files.len()
+        );
+    }
+
+    let report = evaluate(
+        &options,
+        &collected,
+        provider.clone(),
+        model_ref,
+        config,
+        lsp.clone(),
+    )
+    .await?;
+    drop(collected);
+
+    let rendered = match options.format {
+        ReviewFormat::Text => render_text(&report),
+        ReviewFormat::Markdown => render_markdown(&report),
+        ReviewFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+        ReviewFormat::Sarif => serde_json::to_string_pretty(&render_sarif(&report))? + "\n",
+    };
+    print!("{rendered}");
+
+    if !options.fix {
+        return Ok(RunOutcome::Accepted);
+    }
+    if report.findings.is_empty() {
+        if !quiet {
+            eprintln!("[review] no findings to fix");
+        }
+        return Ok(RunOutcome::Accepted);
+    }
+    if !quiet {
+        eprintln!(
+            "[review] handing {} finding(s) to the fix loop",
+            report.findings.len()
+        );
+    }
+    run::run_task(
+        &fix_task(&report),
+        provider,
+        model_ref,
+        config,
+        options.max_iterations,
+        options.quality_threshold,
+        store,
+        tools,
+        code_index,
+        lsp,
+        options.evaluator.as_deref(),
+        changes,
+        &Reporting::default(),
+        quiet,
+    )
+    .await
+}
+
+async fn evaluate(
+    options: &ReviewOptions,
+    collected: &Collected,
+    provider: Arc<dyn ModelProvider>,
+    model_ref: &ModelRef,
+    config: &Config,
+    lsp: Option<Arc<LspManager>>,
+) -> anyhow::Result<ReviewReport> {
+    let source = options.source.describe();
+    let mut task = TaskInput::new(format!(
+        "Review {source}. Judge the changes as a code reviewer would: correctness, \
+         safety, and fit with the surrounding code. Report problems with file:line \
+         locations."
+    ));
+    task.category = infer_category(&collected.files);
+
+    let roles = ModelRoles::from_config(
+        model_ref.clone(),
+        config.models.executor.as_deref(),
+        config.models.evaluator.as_deref(),
+        config.models.planner.as_deref(),
+        config.models.embedder.as_deref(),
+    );
+    let dir = collected.checkout.path();
+    let mut evaluator = EvaluatorFramework::new(
+        Arc::new(SkillRegistry::new()),
+        provider,
+        roles.evaluator.model.clone(),
+    )
+    .with_project_dir(dir)
+    .with_builtin_checks(!options.no_checks);
+    if let Some(ref name) = options.evaluator {
+        evaluator = evaluator.with_evaluator_skill(name.clone());
+    }
+    evaluator.set_conventions(TokenOptimizer::new().fit_instructions(
+        &ProjectInstructions::discover(dir),
+        &task,
+        &collected.files,
+        INSTRUCTIONS_TOKEN_BUDGET,
+    ));
+    // Language servers must see the reviewed code, not the working tree.
+    let scratch_lsp = match (&collected.checkout, lsp) {
+        (Checkout::Current, lsp) => lsp,
+        (_, Some(_)) if !options.no_checks => LspManager::detect(dir, &config.lsp),
+        _ => None,
+    };
+    evaluator.set_lsp(scratch_lsp.clone());
+
+    let output = ExecutionOutput {
+        content: collected.content.clone(),
+        usage: TokenUsage::default(),
+        tool_calls_made: 0,
+        files_modified: collected.files.clone(),
+        tool_calls: vec![],
+    };
+    let evaluation = evaluator.evaluate(&task, &output).await;
+    if !matches!(collected.checkout, Checkout::Current) {
+        if let Some(ref lsp) = scratch_lsp {
+            lsp.shutdown_all().await;
+        }
+    }
+    let evaluation = evaluation?;
+
+    Ok(ReviewReport {
+        source,
+        files: collected.files.clone(),
+        evaluator: evaluation.evaluator_skill,
+        score: evaluation.score,
+        tests_passed: evaluation.tests_passed,
+        static_analysis_passed: evaluation.static_analysis_passed,
+        dimensions: evaluation.dimensions,
+        findings: evaluation.findings,
+        suggestion: evaluation.suggestion,
+        usage: evaluation.usage,
+    })
+}
+
+/// Gather the diff or files for `source`. With `checkout`, also prepare a
+/// directory holding the reviewed code for tests and linters.
+async fn collect(source: &ReviewSource, checkout: bool) -> anyhow::Result<Collected> {
+    let (content, head) = match source {
+        ReviewSource::WorkingTree => (git(&["diff", "HEAD"]).await?, None),
+        ReviewSource::Staged => (git(&["diff", "--cached"]).await?, None),
+        ReviewSource::Range(range) => {
+            let (spec, head) = range_spec(range);
+            (git(&["diff", &spec]).await?, head)
+        }
+        ReviewSource::PullRequest(pr) => {
+            let diff = command("gh", &["pr", "diff", pr]).await?;
+            let number = pr.rsplit('/').next().unwrap_or(pr);
+            let fetched = checkout
+                && git(&["fetch", "--quiet", "origin", &format!("pull/{number}/head")])
+                    .await
+                    .is_ok();
+            if checkout && !fetched {
+                tracing::warn!("Could not fetch {}; tests and linters are skipped", pr);
+            }
+            (diff, fetched.then(|| "FETCH_HEAD".to_string()))
+        }
+        ReviewSource::Patch(path) => {
+            let diff = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+            let files = diff_files(&diff);
+            let checkout = if checkout {
+                Checkout::Patched(apply_to_copy(path).await?)
+            } else {
+                Checkout::Current
+            };
+            return Ok(Collected {
+                content: cap(diff),
+                files,
+                checkout,
+            });
+        }
+        ReviewSource::Files(files) => {
+            let mut content = String::new();
+            for file in files {
+                let text =
+                    std::fs::read_to_string(file).map_err(|e| anyhow::anyhow!("{file}: {e}"))?;
+                let fence = Path::new(file)
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or("");
+                content.push_str(&format!("### {file}\n```{fence}\n{text}\n```\n\n"));
+            }
+            return Ok(Collected {
+                content: cap(content),
+                files: files.clone(),
+                checkout: Checkout::Current,
+            });
+        }
+    };
+
+    let checkout = match head {
+        Some(rev) if checkout => worktree(&rev).await?,
+        _ => Checkout::Current,
+    };
+    Ok(Collected {
+        files: diff_files(&content),
+        content: cap(content),
+        checkout,
+    })
+}
+
+/// `git diff` argument for a range, and the revision it ends at when that
+/// isn't HEAD. A bare ref means "HEAD since that ref".
+fn range_spec(range: &str) -> (String, Option<String>) {
+    let Some((_, head)) = range.split_once("..") else {
+        return (format!("{range}...HEAD"), None);
+    };
+    let head = head.trim_start_matches('.');
+    let head = (!head.is_empty() && head != "HEAD").then(|| head.to_string());
+    (range.to_string(), head)
+}
+
+async fn git(args: &[&str]) -> anyhow::Result<String> {
+    command("git", args).await
+}
+
+async fn command(program: &str, args: &[&str]) -> anyhow::Result<String> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to run {program}: {e}"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{program} {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+async fn worktree(rev: &str) -> anyhow::Result<Checkout> {
+    let path = std::env::temp_dir().join(format!("openkoi-review-{}", uuid::Uuid::new_v4()));
+    let path_str = path.to_string_lossy().into_owned();
+    git(&["worktree", "add", "--detach", "--quiet", &path_str, rev]).await?;
+    Ok(Checkout::Worktree(path))
+}
+
+async fn apply_to_copy(patch: &Path) -> anyhow::Result<Workspace> {
+    let patch = patch.canonicalize()?;
+    let workspace = Workspace::create(Path::new("."))?;
+    let output = tokio::process::Command::new("git")
+        .arg("apply")
+        .arg(&patch)
+        .current_dir(workspace.path())
+        .output()
+        .await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "patch does not apply to the working tree: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(workspace)
+}
+
+fn cap(mut content: String) -> String {
+    if content.len() > MAX_REVIEW_BYTES {
+        let kept = crate::util::truncate_str(&content, MAX_REVIEW_BYTES).len();
+        let dropped = content.len() - kept;
+        content.truncate(kept);
+        content.push_str(&format!("\n[... {dropped} more bytes not shown]\n"));
+    }
+    content
+}
+
+/// Files a unified diff changes (new paths; deletions are left out).
+pub fn diff_files(diff: &str) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for line in diff.lines() {
+        let Some(path) = line.strip_prefix("+++ ") else {
+            continue;
+        };
+        let path = path.split('\t').next().unwrap_or(path).trim();
+        if path == "/dev/null" {
+            continue;
+        }
+        let path = path.strip_prefix("b/").unwrap_or(path).to_string();
+        if !files.contains(&path) {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// Task category for choosing an evaluator skill from the changed files.
+pub fn infer_category(files: &[String]) -> Option<String> {
+    if files.is_empty() {
+        return None;
+    }
+    let lower: Vec<String> = files.iter().map(|f| f.to_lowercase()).collect();
+    let all = |pred: &dyn Fn(&str) -> bool| lower.iter().all(|f| pred(f));
+    let category = if lower
+        .iter()
+        .any(|f| f.ends_with(".sql") || f.contains("migration"))
+    {
+        "sql"
+    } else if all(&|f| f.ends_with(".md") || f.ends_with(".rst") || f.ends_with(".txt")) {
+        "docs"
+    } else if all(&|f| f.contains("test") || f.contains("spec")) {
+        "test"
+    } else {
+        "code"
+    };
+    Some(category.to_string())
+}
+
+/// `path:line[:col]` from a finding location.
+fn split_location(location: &str) -> (String, Option<u32>, Option<u32>) {
+    let leading = |s: &str| -> Option<u32> {
+        let digits: String = s.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse().ok()
+    };
+    let mut parts = location.splitn(3, ':');
+    let path = parts.next().unwrap_or(location).trim().to_string();
+    let line = parts.next().and_then(leading);
+    let column = parts.next().and_then(leading);
+    match line {
+        Some(_) => (path, line, column),
+        None => (location.trim().to_string(), None, None),
+    }
+}
+
+fn severity_label(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Blocker => "BLOCKER",
+        Severity::Important => "IMPORTANT",
+        Severity::Suggestion => "SUGGESTION",
+    }
+}
+
+fn sorted_findings(report: &ReviewReport) -> Vec<&Finding> {
+    let rank = |s: &Severity| match s {
+        Severity::Blocker => 0,
+        Severity::Important => 1,
+        Severity::Suggestion => 2,
+    };
+    let mut findings: Vec<&Finding> = report.findings.iter().collect();
+    findings.sort_by_key(|f| rank(&f.severity));
+    findings
+}
+
+pub fn render_text(report: &ReviewReport) -> String {
+    let mut out = format!(
+        "Review of {}: score {:.2} ({})\n\n",
+        report.source, report.score, report.evaluator
+    );
+    for dim in &report.dimensions {
+        out.push_str(&format!("  {:<20} {:.2}\n", dim.dimension, dim.score));
+    }
+    if report.findings.is_empty() {
+        out.push_str("\nNo findings.\n");
+    } else {
+        out.push_str(&format!("\n{} finding(s):\n", report.findings.len()));
+        for f in sorted_findings(report) {
+            let location = f
+                .location
+                .as_deref()
+                .map(|l| format!(" {l}"))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "  [{}]{} {}\n",
+                severity_label(&f.severity),
+                location,
+                f.title
+            ));
+            if f.description != f.title {
+                out.push_str(&format!("      {}\n", f.description));
+            }
+            if let Some(ref fix) = f.fix {
+                out.push_str(&format!("      fix: {fix}\n"));
+            }
+        }
+    }
+    if !report.suggestion.is_empty() {
+        out.push_str(&format!("\n{}\n", report.suggestion));
+    }
+    out
+}
+
+pub fn render_markdown(report: &ReviewReport) -> String {
+    let mut out = format!(
+        "## Review: {}\n\n**Score:** {:.2} · **Evaluator:** `{}`\n\n",
+        report.source, report.score, report.evaluator
+    );
+    out.push_str("| Dimension | Score |\n|---|---|\n");
+    for dim in &report.dimensions {
+        out.push_str(&format!("| {} | {:.2} |\n", dim.dimension, dim.score));
+    }
+    out.push_str("\n### Findings\n\n");
+    if report.findings.is_empty() {
+        out.push_str("No findings.\n");
+    }
+    for f in sorted_findings(report) {
+        let location = f
+            .location
+            .as_deref()
+            .map(|l| format!(" — `{l}`"))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "- **{}** {}{}\n",
+            severity_label(&f.severity),
+            f.title,
+            location
+        ));
+        if f.description != f.title {
+            out.push_str(&format!("  {}\n", f.description));
+        }
+        if let Some(ref fix) = f.fix {
+            out.push_str(&format!("  *Fix:* {fix}\n"));
+        }
+    }
+    if !report.suggestion.is_empty() {
+        out.push_str(&format!("\n{}\n", report.suggestion));
+    }
+    out
+}
+
+/// SARIF 2.1.0 log with one rule per dimension.
+pub fn render_sarif(report: &ReviewReport) -> serde_json::Value {
+    let mut rules: Vec<&str> = report
+        .findings
+        .iter()
+        .map(|f| f.dimension.as_str())
+        .collect();
+    rules.sort_unstable();
+    rules.dedup();
+
+    let results: Vec<serde_json::Value> = report
+        .findings
+        .iter()
+        .map(|f| {
+            let level = match f.severity {
+                Severity::Blocker => "error",
+                Severity::Important => "warning",
+                Severity::Suggestion => "note",
+            };
+            let text = if f.description.is_empty() || f.description == f.title {
+                f.title.clone()
+            } else {
+                format!("{}: {}", f.title, f.description)
+            };
+            let mut result = json!({
+                "ruleId": f.dimension,
+                "level": level,
+                "message": { "text": text },
+            });
+            if let Some((path, line, column)) = f.location.as_deref().map(split_location) {
+                let mut region = serde_json::Map::new();
+                if let Some(line) = line {
+                    region.insert("startLine".into(), json!(line));
+                }
+                if let Some(column) = column {
+                    region.insert("startColumn".into(), json!(column));
+                }
+                let mut physical = json!({ "artifactLocation": { "uri": path } });
+                if !region.is_empty() {
+                    physical["region"] = serde_json::Value::Object(region);
+                }
+                result["locations"] = json!([{ "physicalLocation": physical }]);
+            }
+            if let Some(ref fix) = f.fix {
+                result["properties"] = json!({ "fix": fix });
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": { "driver": {
+                "name": "openkoi",
+                "version": env!("CARGO_PKG_VERSION"),
+                "rules": rules.iter().map(|r| json!({ "id": r })).collect::<Vec<_>>(),
+            }},
+            "results": results,
+            "properties": { "score": report.score, "evaluator": report.evaluator },
+        }]
+    })
+}
+
+/// Task description that asks the executor to address the findings.
+pub fn fix_task(report: &ReviewReport) -> String {
+    let mut task = format!(
+        "Fix the problems a code review found in {}.\n\nFindings:\n",
+        report.source
+    );
+    for (i, f) in sorted_findings(report).into_iter().enumerate() {
+        let location = f
+            .location
+            .as_deref()
+            .map(|l| format!(" ({l})"))
+            .unwrap_or_default();
+        task.push_str(&format!(
+            "{}. [{}] {}{}\n   {}\n",
+            i + 1,
+            severity_label(&f.severity),
+            f.title,
+            location,
+            f.description
+        ));
+        if let Some(ref fix) = f.fix {
+            task.push_str(&format!("   Suggested fix: {fix}\n"));
+        }
+    }
+    task
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(severity: Severity, location: Option<&str>) -> Finding {
+        Finding {
+            id: "F1".into(),
+            severity,
+            dimension: "correctness".into(),
+            title: "Off-by-one in loop".into(),
+            description: "The loop skips the last element.".into(),
+            location: location.map(String::from),
+            fix: Some("Use ..= instead of ..".into()),
+        }
+    }
+
+    fn report() -> ReviewReport {
+        ReviewReport {
+            source: "the staged changes".into(),
+            files: vec!["src/lib.rs".into()],
+            evaluator: "code-review".into(),
+            score: 0.7,
+            tests_passed: true,
+            static_analysis_passed: true,
+            dimensions: vec![DimensionScore {
+                dimension: "correctness".into(),
+                score: 0.6,
+                weight: 0.4,
+            }],
+            findings: vec![
+                finding(Severity::Suggestion, None),
+                finding(Severity::Blocker, Some("src/lib.rs:12:5")),
+            ],
+            suggestion: String::new(),
+            usage: TokenUsage::default(),
+        }
+    }
+
+    #[test]
+    fn test_diff_files_and_category() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1 +1 @@
+-a
++b
+diff --git a/old.txt b/old.txt
+--- a/old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+diff --git a/db/migrations/1.sql b/db/migrations/1.sql
+new file mode 100644
+--- /dev/null
++++ b/db/migrations/1.sql
+@@ -0,0 +1 @@
++create table t();
+";
+        let files = diff_files(diff);
+        assert_eq!(files, vec!["src/lib.rs", "db/migrations/1.sql"]);
+        assert_eq!(infer_category(&files).as_deref(), Some("sql"));
+        assert_eq!(
+            infer_category(&["README.md".into()]).as_deref(),
+            Some("docs")
+        );
+        assert_eq!(
+            infer_category(&["tests/api_test.rs".into()]).as_deref(),
+            Some("test")
+        );
+        assert_eq!(
+            infer_category(&["src/a.rs".into()]).as_deref(),
+            Some("code")
+        );
+        assert_eq!(infer_category(&[]), None);
+    }
+
+    #[test]
+    fn test_range_spec() {
+        assert_eq!(range_spec("main"), ("main...HEAD".into(), None));
+        assert_eq!(range_spec("main..HEAD"), ("main..HEAD".into(), None));
+        assert_eq!(
+            range_spec("main...feature"),
+            ("main...feature".into(), Some("feature".into()))
+        );
+        assert_eq!(
+            split_location("src/a.rs:12:5"),
+            ("src/a.rs".into(), Some(12), Some(5))
+        );
+        assert_eq!(
+            split_location("src/a.rs:12-20"),
+            ("src/a.rs".into(), Some(12), None)
+        );
+        assert_eq!(
+            split_location("somewhere"),
+            ("somewhere".into(), None, None)
+        );
+    }
+
+    #[test]
+    fn test_renderers() {
+        let report = report();
+        let text = render_text(&report);
+        // Blockers are listed first.
+        let blocker = text.find("[BLOCKER] src/lib.rs:12:5").unwrap();
+        let suggestion = text.find("[SUGGESTION] Off-by-one").unwrap();
+        assert!(blocker < suggestion);
+
+        let markdown = render_markdown(&report);
+        assert!(markdown.contains("| correctness | 0.60 |"));
+        assert!(markdown.contains("- **BLOCKER** Off-by-one in loop — `src/lib.rs:12:5`"));
+
+        let sarif = render_sarif(&report);
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1]["level"], "error");
+        let region = &results[1]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 12);
+        assert_eq!(region["startColumn"], 5);
+        assert!(results[0].get("locations").is_none());
+        assert_eq!(
+            sarif["runs"][0]["tool"]["driver"]["rules"][0]["id"],
+            "correctness"
+        );
+
+        let task = fix_task(&report);
+        assert!(task.starts_with("Fix the problems a code review found in the staged changes."));
+        assert!(task.contains("1. [BLOCKER] Off-by-one in loop (src/lib.rs:12:5)"));
+        assert!(task.contains("Suggested fix: Use ..= instead of .."));
+    }
+}
@@ -1,23 +1,135 @@
 // src/cli/run.rs — Default command: run a task
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::Mutex;
 
+use serde::{Deserialize, Serialize};
+
+use super::OutputFormat;
+use crate::core::escalation::EscalationPolicy;
+use crate::core::instructions::ProjectInstructions;
 use crate::core::orchestrator::{Orchestrator, SessionContext};
 use crate::core::safety::SafetyChecker;
-use crate::core::types::{IterationEngineConfig, TaskInput};
+use crate::core::tokenizer;
+use crate::core::types::{IterationDecision, IterationEngineConfig, TaskInput, TaskResult};
+use crate::index::CodeIndex;
 use crate::infra::config::Config;
-use crate::integrations::registry::IntegrationRegistry;
 use crate::learner::skill_selector::SkillSelector;
+use crate::lsp::LspManager;
 use crate::memory::decay;
 use crate::memory::recall::{self, HistoryRecall};
 use crate::memory::store::Store;
 use crate::patterns::event_logger::{EventLogger, EventType, UsageEvent};
-use crate::plugins::mcp::McpManager;
 use crate::provider::roles::ModelRoles;
-use crate::provider::{ModelProvider, ModelRef, ToolDef};
+use crate::provider::{ModelProvider, ModelRef};
+use crate::skills::embedding::SkillEmbedder;
 use crate::skills::registry::SkillRegistry;
 use crate::soul::loader;
+use crate::tools::overlay::{Overlay, Workspace};
+use crate::tools::ToolRegistry;
+
+/// Where a task run's file changes go.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ChangeMode {
+    /// Tools write to the working tree.
+    #[default]
+    Apply,
+    /// Changes are kept in an overlay and printed as a diff.
+    DryRun,
+    /// Changes are kept in an overlay and written as a patch to the file,
+    /// or to stdout (with the task result moved to stderr).
+    Patch(Option<PathBuf>),
+}
+
+impl ChangeMode {
+    pub fn from_cli(cli: &super::Cli) -> Self {
+        if let Some(ref path) = cli.patch {
+            ChangeMode::Patch(Some(PathBuf::from(path)))
+        } else if cli.output == super::OutputFormat::Patch {
+            ChangeMode::Patch(None)
+        } else if cli.dry_run {
+            ChangeMode::DryRun
+        } else {
+            ChangeMode::Apply
+        }
+    }
+
+    /// Whether file writes go to an overlay instead of the working tree.
+    pub fn captures(&self) -> bool {
+        *self != ChangeMode::Apply
+    }
+}
+
+/// How a task run reports its result.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Reporting {
+    pub format: OutputFormat,
+    /// Minimum final score for a successful exit.
+    pub fail_under: Option<f32>,
+}
+
+impl Reporting {
+    pub fn from_cli(cli: &super::Cli) -> Self {
+        Self {
+            format: cli.output,
+            fail_under: cli.fail_under,
+        }
+    }
+}
+
+/// How a task run ended, as reported to scripts through the exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    Accepted,
+    /// The final score is below `--fail-under`.
+    BelowThreshold,
+    /// The token budget or time limit stopped the run below `--fail-under`.
+    BudgetExceeded,
+    /// The provider failed and no iteration met `--fail-under`.
+    ProviderError,
+}
+
+impl RunOutcome {
+    pub fn classify(result: &TaskResult, fail_under: Option<f32>) -> Self {
+        let below = fail_under.is_some_and(|min| result.final_score < f64::from(min));
+        match result.decision {
+            IterationDecision::AbortError if below || fail_under.is_none() => {
+                RunOutcome::ProviderError
+            }
+            IterationDecision::AbortBudget | IterationDecision::AbortTimeout if below => {
+                RunOutcome::BudgetExceeded
+            }
+            _ if below => RunOutcome::BelowThreshold,
+            _ => RunOutcome::Accepted,
+        }
+    }
+
+    /// Process exit code (1 and 2 are left to errors and usage mistakes).
+    pub fn exit_code(self) -> i32 {
+        match self {
+            RunOutcome::Accepted => 0,
+            RunOutcome::BelowThreshold => 3,
+            RunOutcome::BudgetExceeded => 4,
+            RunOutcome::ProviderError => 5,
+        }
+    }
+}
+
+/// `--output json` document, and the last line of `--output ndjson`.
+#[derive(Serialize)]
+struct RunReport<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event: Option<&'static str>,
+    outcome: RunOutcome,
+    exit_code: i32,
+    #[serde(flatten)]
+    result: &'a TaskResult,
+    /// Changes kept out of the working tree (dry run / patch modes).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diff: Option<String>,
+}
 
 /// Execute a task through the iteration engine.
 #[allow(clippy::too_many_arguments)]
@@ -29,13 +141,41 @@ pub async fn run_task(
     max_iterations: u8,
     quality_threshold: f32,
     store: Option<Arc<Mutex<Store>>>,
-    mcp_tools: Vec<ToolDef>,
-    mcp_manager: Option<&mut McpManager>,
-    integrations: Option<&IntegrationRegistry>,
+    tools: Option<&ToolRegistry>,
+    code_index: Option<Arc<CodeIndex>>,
+    lsp: Option<Arc<LspManager>>,
+    evaluator: Option<&str>,
+    changes: &ChangeMode,
+    reporting: &Reporting,
     quiet: bool,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<RunOutcome> {
     let task = TaskInput::new(task_description);
 
+    // Dry run / patch output: writes go to the registry's overlay, and tests,
+    // linters and language servers see a scratch copy with the overlay applied.
+    let overlay = match tools.and_then(|t| t.overlay()) {
+        Some(overlay) => Some(overlay.clone()),
+        None if changes.captures() => Some(Arc::new(Overlay::new(Path::new("."))?)),
+        None => None,
+    };
+    let workspace = match overlay {
+        Some(_) => match Workspace::create(Path::new(".")) {
+            Ok(workspace) => Some(workspace),
+            Err(e) => {
+                tracing::warn!(
+                    "No scratch copy for evaluation ({}); tests see the unchanged tree",
+                    e
+                );
+                None
+            }
+        },
+        None => None,
+    };
+    let lsp = match (&workspace, lsp) {
+        (Some(workspace), Some(_)) => LspManager::detect(workspace.path(), &config.lsp),
+        (_, lsp) => lsp,
+    };
+
     let mut engine_config = IterationEngineConfig::from(&config.iteration);
     engine_config.max_iterations = max_iterations;
     engine_config.quality_threshold = quality_threshold;
@@ -46,15 +186,24 @@ pub async fn run_task(
     let soul = loader::load_soul();
     tracing::debug!("Soul loaded from {}", soul.source);
 
-    // Load skills
-    let skill_registry = Arc::new(SkillRegistry::new());
+    // Load skills and their embeddings
+    let embedder = SkillEmbedder::new(provider.clone(), &config.skills.embeddings);
+    let mut skill_registry = SkillRegistry::new();
+    embedder
+        .embed_registry(&mut skill_registry, store.as_deref())
+        .await;
+    let skill_registry = Arc::new(skill_registry);
+    let task_embedding = embedder
+        .embed_task(&task.description, skill_registry.all())
+        .await;
 
     // Select relevant skills for this task
-    let selector = SkillSelector::new();
+    let selector = SkillSelector::with_experiments(config.skills.experiments.clone());
     let ranked_skills = {
         let store_guard = store.as_ref().and_then(|s| s.lock().ok());
         selector.select(
             &task.description,
+            task_embedding.as_deref(),
             task.category.as_deref(),
             skill_registry.all(),
             store_guard.as_deref(),
@@ -68,8 +217,15 @@ pub async fn run_task(
         match store_guard.as_deref() {
             Some(s) => {
                 let token_budget = engine_config.token_budget / 10; // 10% for recall
-                recall::recall(s, task_description, task.category.as_deref(), token_budget)
-                    .unwrap_or_default()
+                let counter = tokenizer::for_provider_model(provider.as_ref(), &model_ref.model);
+                recall::recall(
+                    s,
+                    task_description,
+                    task.category.as_deref(),
+                    token_budget,
+                    counter.as_ref(),
+                )
+                .unwrap_or_default()
             }
             None => HistoryRecall::default(),
         }
@@ -78,13 +234,17 @@ pub async fn run_task(
 
     let ctx = SessionContext {
         soul,
+        instructions: ProjectInstructions::discover(Path::new(".")),
+        code_index,
+        lsp: lsp.clone(),
         ranked_skills,
         recall,
-        tools: mcp_tools,
+        tools: tools.map(|t| t.defs().to_vec()).unwrap_or_default(),
         skill_registry,
         conversation_history: None,
     };
 
+    let escalation = EscalationPolicy::for_provider(&config.models, &provider);
     let mut orchestrator = Orchestrator::new(
         provider,
         ModelRoles::from_config(
@@ -98,14 +258,32 @@ pub async fn run_task(
         safety,
         ctx.skill_registry.clone(),
         store.clone(),
-    );
+    )
+    .with_escalation(escalation);
+    if let Some(name) = evaluator {
+        orchestrator = orchestrator.with_evaluator_skill(name);
+    }
+    if let Some(ref overlay) = overlay {
+        orchestrator = orchestrator.with_overlay(
+            overlay.clone(),
+            workspace.as_ref().map(|w| w.path().to_path_buf()),
+        );
+    }
 
     {
-        let inner: Option<Box<dyn Fn(crate::core::types::ProgressEvent) + Send>> = if !quiet {
-            Some(Box::new(super::progress::terminal_progress()))
-        } else {
-            None
-        };
+        let terminal = (!quiet).then(super::progress::terminal_progress);
+        let inner: Option<Box<dyn Fn(crate::core::types::ProgressEvent) + Send>> =
+            if reporting.format == OutputFormat::Ndjson {
+                let lines = super::progress::ndjson_progress();
+                Some(Box::new(move |event: crate::core::types::ProgressEvent| {
+                    if let Some(ref terminal) = terminal {
+                        terminal(event.clone());
+                    }
+                    lines(event);
+                }))
+            } else {
+                terminal.map(|t| Box::new(t) as Box<dyn Fn(_) + Send>)
+            };
         let progress = crate::core::state::state_writer_progress(
             task.id.clone(),
             task.description.clone(),
@@ -122,12 +300,57 @@ pub async fn run_task(
         );
     }
 
-    let result = orchestrator
-        .run(task, &ctx, mcp_manager, integrations)
-        .await?;
+    let result = orchestrator.run(task, &ctx, tools).await;
+    if workspace.is_some() {
+        // This manager was started for the scratch copy, not shared.
+        if let Some(ref lsp) = lsp {
+            lsp.shutdown_all().await;
+        }
+    }
+    let result = result?;
 
     // Display result
-    println!("{}", result.output.content);
+    let outcome = RunOutcome::classify(&result, reporting.fail_under);
+    match reporting.format {
+        OutputFormat::Json | OutputFormat::Ndjson => {
+            let diff = match overlay {
+                Some(ref overlay) => {
+                    let diff = overlay.diff()?;
+                    if let ChangeMode::Patch(Some(path)) = changes {
+                        write_patch(path, &diff, quiet)?;
+                    }
+                    Some(diff)
+                }
+                None => None,
+            };
+            let ndjson = reporting.format == OutputFormat::Ndjson;
+            let report = RunReport {
+                event: ndjson.then_some("result"),
+                outcome,
+                exit_code: outcome.exit_code(),
+                result: &result,
+                diff,
+            };
+            if ndjson {
+                println!("{}", serde_json::to_string(&report)?);
+            } else {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+        }
+        _ => {
+            if *changes == ChangeMode::Patch(None) {
+                eprintln!("{}", result.output.content);
+            } else {
+                println!("{}", result.output.content);
+            }
+            if let Some(ref overlay) = overlay {
+                emit_changes(overlay, changes, quiet)?;
+            }
+        }
+    }
+    if let Some(ref error) = result.error {
+        eprintln!("[error] {error}");
+    }
 
     if !quiet && result.learnings_saved > 0 {
         eprintln!("  {} learning(s) saved", result.learnings_saved);
@@ -160,6 +383,38 @@ pub async fn run_task(
         }
     }
 
+    Ok(outcome)
+}
+
+/// Print or write the overlay's changes as the change mode asks.
+fn emit_changes(overlay: &Overlay, changes: &ChangeMode, quiet: bool) -> anyhow::Result<()> {
+    let diff = overlay.diff()?;
+    match changes {
+        ChangeMode::Patch(Some(path)) => write_patch(path, &diff, quiet)?,
+        ChangeMode::Patch(None) => print!("{diff}"),
+        _ => {
+            if !quiet {
+                eprintln!(
+                    "[dry-run] {} file(s) changed; working tree untouched",
+                    diff.matches("diff --git ").count()
+                );
+            }
+            print!("{diff}");
+        }
+    }
+    Ok(())
+}
+
+fn write_patch(path: &Path, diff: &str, quiet: bool) -> anyhow::Result<()> {
+    std::fs::write(path, diff)?;
+    if !quiet {
+        eprintln!(
+            "[patch] {} file(s) changed; wrote {} (apply with `git apply {}`)",
+            diff.matches("diff --git ").count(),
+            path.display(),
+            path.display()
+        );
+    }
     Ok(())
 }
 
@@ -182,3 +437,67 @@ fn check_soul_evolution(store: &Store) {
 fn truncate_task(s: &str, max: usize) -> &str {
     crate::util::truncate_str(s, max)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::ExecutionOutput;
+
+    fn result(score: f64, decision: IterationDecision) -> TaskResult {
+        TaskResult {
+            task_id: "t".into(),
+            output: ExecutionOutput {
+                content: String::new(),
+                usage: Default::default(),
+                tool_calls_made: 0,
+                files_modified: vec![],
+                tool_calls: vec![],
+            },
+            iterations: 1,
+            total_tokens: 0,
+            usage: Default::default(),
+            cost: 0.0,
+            learnings_saved: 0,
+            skills_used: vec![],
+            final_score: score,
+            decision,
+            history: vec![],
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_run_outcome() {
+        use IterationDecision::*;
+        let classify = |score, decision, min| RunOutcome::classify(&result(score, decision), min);
+
+        // Without --fail-under only provider failures are errors.
+        assert_eq!(classify(0.2, AcceptBest, None), RunOutcome::Accepted);
+        assert_eq!(classify(0.2, AbortBudget, None), RunOutcome::Accepted);
+        assert_eq!(classify(0.0, AbortError, None), RunOutcome::ProviderError);
+
+        assert_eq!(classify(0.9, Accept, Some(0.8)), RunOutcome::Accepted);
+        assert_eq!(
+            classify(0.7, AcceptBest, Some(0.8)),
+            RunOutcome::BelowThreshold
+        );
+        assert_eq!(
+            classify(0.7, AbortRegression, Some(0.8)),
+            RunOutcome::BelowThreshold
+        );
+        assert_eq!(
+            classify(0.7, AbortTimeout, Some(0.8)),
+            RunOutcome::BudgetExceeded
+        );
+        assert_eq!(
+            classify(0.0, AbortError, Some(0.8)),
+            RunOutcome::ProviderError
+        );
+        // An earlier iteration already met the bar.
+        assert_eq!(classify(0.85, AbortError, Some(0.8)), RunOutcome::Accepted);
+
+        assert_eq!(RunOutcome::BelowThreshold.exit_code(), 3);
+        assert_eq!(RunOutcome::BudgetExceeded.exit_code(), 4);
+        assert_eq!(RunOutcome::ProviderError.exit_code(), 5);
+    }
+}
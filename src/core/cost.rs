@@ -2,6 +2,7 @@
 
 use std::collections::HashMap;
 
+use super::escalation::Escalation;
 use crate::provider::{ModelInfo, TokenUsage};
 
 /// Pricing snapshot for cost calculation.
@@ -49,6 +50,43 @@ pub struct CostTracker {
     pub by_task: HashMap<String, f64>,
     /// Number of API calls per model.
     pub calls_by_model: HashMap<String, u64>,
+    /// Prompt-cache token counts per task.
+    pub cache_by_task: HashMap<String, CacheStats>,
+    /// Prompt-cache token counts across all calls.
+    pub cache_total: CacheStats,
+    /// Executor model switches, in order.
+    pub escalations: Vec<Escalation>,
+}
+
+/// Prompt-cache token counts for computing hit rates.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    /// Uncached input tokens.
+    pub input_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+}
+
+impl CacheStats {
+    fn add(&mut self, usage: &TokenUsage) {
+        self.input_tokens += usage.input_tokens as u64;
+        self.cache_read_tokens += usage.cache_read_tokens as u64;
+        self.cache_write_tokens += usage.cache_write_tokens as u64;
+    }
+
+    /// Total prompt tokens (uncached + cache reads + cache writes).
+    pub fn prompt_tokens(&self) -> u64 {
+        self.input_tokens + self.cache_read_tokens + self.cache_write_tokens
+    }
+
+    /// Fraction of prompt tokens served from cache (0.0-1.0).
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.prompt_tokens();
+        if total == 0 {
+            return 0.0;
+        }
+        self.cache_read_tokens as f64 / total as f64
+    }
 }
 
 impl Default for CostTracker {
@@ -66,9 +104,18 @@ impl CostTracker {
             tokens_by_model: HashMap::new(),
             by_task: HashMap::new(),
             calls_by_model: HashMap::new(),
+            cache_by_task: HashMap::new(),
+            cache_total: CacheStats::default(),
+            escalations: Vec::new(),
         }
     }
 
+    /// Record an executor model switch so the report can attribute spend
+    /// before and after it.
+    pub fn record_escalation(&mut self, escalation: Escalation) {
+        self.escalations.push(escalation);
+    }
+
     pub fn record(&mut self, model: &str, usage: &TokenUsage) {
         let pricing = Pricing::from_model_name(model);
         self.record_internal(model, usage, &pricing, None, None);
@@ -107,6 +154,18 @@ impl CostTracker {
         self.record_internal(model, usage, pricing, None, None);
     }
 
+    /// Record using explicit `Pricing`, attributed to both a phase and a task.
+    pub fn record_for_task_phase(
+        &mut self,
+        model: &str,
+        usage: &TokenUsage,
+        pricing: &Pricing,
+        phase: &str,
+        task_id: &str,
+    ) {
+        self.record_internal(model, usage, pricing, Some(phase), Some(task_id));
+    }
+
     fn record_internal(
         &mut self,
         model: &str,
@@ -123,7 +182,9 @@ impl CostTracker {
         }
         if let Some(t) = task_id {
             *self.by_task.entry(t.into()).or_default() += cost;
+            self.cache_by_task.entry(t.into()).or_default().add(usage);
         }
+        self.cache_total.add(usage);
         let tokens = self.tokens_by_model.entry(model.into()).or_insert((0, 0));
         tokens.0 += usage.input_tokens as u64;
         tokens.1 += usage.output_tokens as u64;
@@ -162,6 +223,20 @@ impl CostTracker {
         self.tokens_by_model.values().map(|(i, o)| i + o).sum()
     }
 
+    /// Token usage summed over every recorded call.
+    pub fn usage(&self) -> TokenUsage {
+        let (input, output) = self
+            .tokens_by_model
+            .values()
+            .fold((0, 0), |(i, o), (ti, to)| (i + ti, o + to));
+        TokenUsage {
+            input_tokens: input as u32,
+            output_tokens: output as u32,
+            cache_read_tokens: self.cache_total.cache_read_tokens as u32,
+            cache_write_tokens: self.cache_total.cache_write_tokens as u32,
+        }
+    }
+
     /// Total API calls across all models.
     pub fn total_calls(&self) -> u64 {
         self.calls_by_model.values().sum()
@@ -229,6 +304,15 @@ impl CostTracker {
             self.cost_per_1k_output()
         ));
 
+        if self.cache_total.prompt_tokens() > 0 {
+            report.push_str(&format!(
+                "Prompt cache hit rate: {:.1}% ({} read, {} written)\n",
+                self.cache_total.hit_rate() * 100.0,
+                self.cache_total.cache_read_tokens,
+                self.cache_total.cache_write_tokens,
+            ));
+        }
+
         if !self.by_model.is_empty() {
             report.push_str("\nBy Model:\n");
             for entry in self.model_breakdown() {
@@ -255,8 +339,47 @@ impl CostTracker {
             }
         }
 
+        if !self.escalations.is_empty() {
+            report.push_str("\nEscalations:\n");
+            for esc in &self.escalations {
+                report.push_str(&format!(
+                    "  iter {}: {} -> {} ({}), ${:.4} spent before switch\n",
+                    esc.iteration + 1,
+                    esc.from,
+                    esc.to,
+                    esc.reason,
+                    esc.cost_before_usd,
+                ));
+            }
+        }
+
+        if !self.cache_by_task.is_empty() {
+            report.push_str("\nBy Task (prompt cache):\n");
+            let mut tasks: Vec<_> = self.cache_by_task.iter().collect();
+            tasks.sort_by(|a, b| a.0.cmp(b.0));
+            for (task_id, stats) in tasks {
+                report.push_str(&format!(
+                    "  {}: ${:.4}, {:.1}% hit rate ({} read, {} written, {} uncached)\n",
+                    task_id,
+                    self.task_cost(task_id),
+                    stats.hit_rate() * 100.0,
+                    stats.cache_read_tokens,
+                    stats.cache_write_tokens,
+                    stats.input_tokens,
+                ));
+            }
+        }
+
         report
     }
+
+    /// Prompt-cache hit rate for a specific task (0.0 if nothing recorded).
+    pub fn task_cache_hit_rate(&self, task_id: &str) -> f64 {
+        self.cache_by_task
+            .get(task_id)
+            .map(|s| s.hit_rate())
+            .unwrap_or(0.0)
+    }
 }
 
 /// Per-model cost breakdown entry.
@@ -569,6 +692,9 @@ mod tests {
         let sonnet_cost = calculate_cost("claude-sonnet-4", &usage(1000, 500));
         let gpt_cost = calculate_cost("gpt-4.1", &usage(1000, 500));
         assert!((t.total_usd - sonnet_cost - gpt_cost).abs() < 0.0001);
+        let total = t.usage();
+        assert_eq!(total.input_tokens, 3000);
+        assert_eq!(total.output_tokens, 1500);
     }
 
     // ─── Pricing / ModelInfo-based cost tests ───────────────────
@@ -672,4 +798,58 @@ mod tests {
         assert!(t.by_phase.contains_key("execute"));
         assert!(t.by_model.contains_key("gpt-4.1"));
     }
+
+    // ─── Prompt cache stats tests ───────────────────────────────
+
+    #[test]
+    fn test_cache_stats_hit_rate() {
+        let mut t = CostTracker::new();
+        let cached = TokenUsage {
+            input_tokens: 200,
+            output_tokens: 100,
+            cache_read_tokens: 600,
+            cache_write_tokens: 200,
+        };
+        let pricing = Pricing::from_model_name("claude-sonnet-4");
+        t.record_for_task_phase("claude-sonnet-4", &cached, &pricing, "execute", "task-1");
+
+        assert!((t.task_cache_hit_rate("task-1") - 0.6).abs() < 0.001);
+        assert!((t.cache_total.hit_rate() - 0.6).abs() < 0.001);
+        assert!(t.by_phase.contains_key("execute"));
+        assert!(t.task_cost("task-1") > 0.0);
+        assert_eq!(t.task_cache_hit_rate("other"), 0.0);
+    }
+
+    #[test]
+    fn test_analytics_report_includes_cache_hit_rate() {
+        let mut t = CostTracker::new();
+        let cached = TokenUsage {
+            input_tokens: 500,
+            output_tokens: 100,
+            cache_read_tokens: 500,
+            cache_write_tokens: 0,
+        };
+        t.record_for_task("claude-sonnet-4", &cached, "task-1");
+        let report = t.analytics_report();
+        assert!(report.contains("Prompt cache hit rate: 50.0%"));
+        assert!(report.contains("By Task (prompt cache):"));
+        assert!(report.contains("task-1"));
+    }
+
+    #[test]
+    fn test_analytics_report_includes_escalations() {
+        use crate::core::escalation::EscalationReason;
+
+        let mut t = CostTracker::new();
+        t.record_escalation(Escalation {
+            iteration: 1,
+            from: "claude-sonnet-4".into(),
+            to: "claude-opus-4".into(),
+            reason: EscalationReason::Stalled { iterations: 2 },
+            cost_before_usd: 0.05,
+        });
+        let report = t.analytics_report();
+        assert!(report.contains("Escalations:"));
+        assert!(report.contains("iter 2: claude-sonnet-4 -> claude-opus-4"));
+    }
 }
@@ -0,0 +1,328 @@
+// src/core/escalation.rs — Executor model escalation ladder
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::token_optimizer::check_context_fit;
+use super::types::{IterationCycle, IterationDecision, TaskInput};
+use crate::infra::config::{EscalationConfig, ModelsConfig};
+use crate::provider::resolver::resolve_small_model;
+use crate::provider::{ModelInfo, ModelProvider, ModelRef};
+
+/// When and where the orchestrator may move the executor to another model.
+///
+/// All models live on the executor's provider: ladder entries for other
+/// providers are dropped when the policy is built.
+#[derive(Debug, Clone, Default)]
+pub struct EscalationPolicy {
+    /// Model ids ordered from cheapest to strongest.
+    pub ladder: Vec<String>,
+    /// Consecutive non-improving iterations before climbing a rung (0 = never).
+    pub stall_iterations: u8,
+    /// Switch to a larger-context model instead of pruning on overflow.
+    pub on_overflow: bool,
+    /// Model to start short tasks on, if any.
+    pub small_model: Option<String>,
+    pub small_task_max_chars: usize,
+}
+
+impl EscalationPolicy {
+    /// Build the policy for an executor running on `provider`, resolving
+    /// `small_model` against that provider's catalog.
+    pub fn for_provider(models: &ModelsConfig, provider: &Arc<dyn ModelProvider>) -> Self {
+        let small = if models.escalation.start_small {
+            resolve_small_model(
+                std::slice::from_ref(provider),
+                models.small_model.as_deref(),
+            )
+        } else {
+            None
+        };
+        Self::from_config(&models.escalation, provider.id(), small.as_ref())
+    }
+
+    pub fn from_config(
+        cfg: &EscalationConfig,
+        provider_id: &str,
+        small_model: Option<&ModelRef>,
+    ) -> Self {
+        let ladder = cfg
+            .ladder
+            .iter()
+            .filter_map(|entry| match ModelRef::parse(entry) {
+                Some(r) if r.provider == provider_id => Some(r.model),
+                Some(r) => {
+                    tracing::warn!(
+                        "Ignoring escalation ladder entry '{}': executor provider is '{}'",
+                        r,
+                        provider_id
+                    );
+                    None
+                }
+                None => Some(entry.clone()),
+            })
+            .collect();
+
+        let small_model = if cfg.start_small {
+            small_model
+                .filter(|r| r.provider == provider_id)
+                .map(|r| r.model.clone())
+        } else {
+            None
+        };
+
+        Self {
+            ladder,
+            stall_iterations: cfg.after_stalled_iterations,
+            on_overflow: cfg.on_overflow,
+            small_model,
+            small_task_max_chars: cfg.small_task_max_chars,
+        }
+    }
+
+    /// The small model to start `task` on, if the task is short enough.
+    pub fn start_model(&self, task: &TaskInput) -> Option<&str> {
+        let small = self.small_model.as_deref()?;
+        let short =
+            task.context.is_none() && task.description.chars().count() <= self.small_task_max_chars;
+        short.then_some(small)
+    }
+
+    /// The next stronger model after `current`. A task started on the small
+    /// model first returns to `configured` (the executor from `[models]`).
+    pub fn next_rung(&self, current: &str, configured: &str) -> Option<String> {
+        if self.small_model.as_deref() == Some(current) && current != configured {
+            return Some(configured.to_string());
+        }
+        match self.ladder.iter().position(|m| m == current) {
+            Some(pos) => self.ladder.get(pos + 1).cloned(),
+            None => self.ladder.iter().find(|m| *m != current).cloned(),
+        }
+    }
+}
+
+/// Why the executor model changed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EscalationReason {
+    /// The prompt did not fit the current model's context window.
+    ContextOverflow { needed_tokens: u32 },
+    /// Scores stopped improving for this many iterations.
+    Stalled { iterations: u8 },
+    /// The task was short enough to start on the small model.
+    SmallTask,
+}
+
+impl std::fmt::Display for EscalationReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EscalationReason::ContextOverflow { needed_tokens } => {
+                write!(f, "context overflow ({} tokens)", needed_tokens)
+            }
+            EscalationReason::Stalled { iterations } => {
+                write!(f, "no improvement for {} iteration(s)", iterations)
+            }
+            EscalationReason::SmallTask => write!(f, "short task"),
+        }
+    }
+}
+
+/// A recorded executor model switch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Escalation {
+    pub iteration: u8,
+    pub from: String,
+    pub to: String,
+    pub reason: EscalationReason,
+    /// Task cost at the moment of the switch, to compare spend per model.
+    pub cost_before_usd: f64,
+}
+
+/// Pick a catalog model whose window fits `needed_tokens`, preferring the
+/// smallest such window and then the cheapest input price. If none fits,
+/// the largest window above `current_window` is returned.
+pub fn larger_context_model<'a>(
+    models: &'a [ModelInfo],
+    current: &str,
+    current_window: u32,
+    needed_tokens: u32,
+    requires_tools: bool,
+) -> Option<&'a ModelInfo> {
+    let candidates = models.iter().filter(|m| {
+        m.id != current
+            && m.context_window > current_window
+            && (!requires_tools || m.supports_tools)
+    });
+
+    let fitting = candidates
+        .clone()
+        .filter(|m| check_context_fit(needed_tokens, m.context_window) == 0)
+        .min_by(|a, b| {
+            a.context_window.cmp(&b.context_window).then(
+                a.input_price_per_mtok
+                    .partial_cmp(&b.input_price_per_mtok)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+        });
+
+    fitting.or_else(|| candidates.max_by_key(|m| m.context_window))
+}
+
+/// Consecutive evaluated cycles whose score failed to beat the best so far by
+/// `threshold`, counted since the last model switch.
+pub fn stalled_iterations<'a>(
+    cycles: impl IntoIterator<Item = &'a IterationCycle>,
+    threshold: f32,
+) -> u8 {
+    let mut best: Option<f32> = None;
+    let mut stalled = 0u8;
+
+    for cycle in cycles {
+        if cycle.escalation.is_some() {
+            best = None;
+            stalled = 0;
+            // Switches decided at the end of a cycle (`Escalate`) leave that
+            // cycle's score with the previous model; a switch made before
+            // executing scores the new one.
+            if cycle.decision == IterationDecision::Escalate {
+                continue;
+            }
+        }
+        let Some(eval) = &cycle.evaluation else {
+            continue;
+        };
+        match best {
+            Some(b) if eval.score < b + threshold => stalled = stalled.saturating_add(1),
+            _ => stalled = 0,
+        }
+        best = Some(best.map_or(eval.score, |b| b.max(eval.score)));
+    }
+
+    stalled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::Evaluation;
+    use crate::provider::TokenUsage;
+
+    fn model(id: &str, window: u32, price: f64) -> ModelInfo {
+        ModelInfo {
+            id: id.into(),
+            name: id.into(),
+            context_window: window,
+            max_output_tokens: 8192,
+            supports_tools: true,
+            supports_streaming: true,
+            input_price_per_mtok: price,
+            output_price_per_mtok: price,
+            ..Default::default()
+        }
+    }
+
+    fn scored(score: f32) -> IterationCycle {
+        let mut cycle = IterationCycle::new(&TaskInput::new("t"), 0);
+        cycle.evaluation = Some(Evaluation {
+            score,
+            dimensions: vec![],
+            findings: vec![],
+            suggestion: String::new(),
+            usage: TokenUsage::default(),
+            evaluator_skill: "test".into(),
+            tests_passed: true,
+            static_analysis_passed: true,
+        });
+        cycle
+    }
+
+    fn policy(ladder: &[&str]) -> EscalationPolicy {
+        EscalationPolicy {
+            ladder: ladder.iter().map(|s| s.to_string()).collect(),
+            stall_iterations: 2,
+            on_overflow: true,
+            small_model: None,
+            small_task_max_chars: 200,
+        }
+    }
+
+    #[test]
+    fn test_from_config_filters_other_providers() {
+        let cfg = EscalationConfig {
+            ladder: vec![
+                "anthropic/claude-sonnet-4".into(),
+                "openai/gpt-4.1".into(),
+                "anthropic/claude-opus-4".into(),
+            ],
+            start_small: true,
+            ..Default::default()
+        };
+        let small = ModelRef::new("anthropic", "claude-haiku-3.5");
+        let p = EscalationPolicy::from_config(&cfg, "anthropic", Some(&small));
+        assert_eq!(p.ladder, vec!["claude-sonnet-4", "claude-opus-4"]);
+        assert_eq!(p.small_model.as_deref(), Some("claude-haiku-3.5"));
+    }
+
+    #[test]
+    fn test_next_rung() {
+        let p = policy(&["small", "mid", "large"]);
+        assert_eq!(p.next_rung("small", "mid").as_deref(), Some("mid"));
+        assert_eq!(p.next_rung("mid", "mid").as_deref(), Some("large"));
+        assert_eq!(p.next_rung("large", "mid"), None);
+        // Not on the ladder: start at the bottom.
+        assert_eq!(p.next_rung("other", "other").as_deref(), Some("small"));
+    }
+
+    #[test]
+    fn test_next_rung_returns_from_small_model() {
+        let mut p = policy(&["large"]);
+        p.small_model = Some("haiku".into());
+        assert_eq!(p.next_rung("haiku", "sonnet").as_deref(), Some("sonnet"));
+    }
+
+    #[test]
+    fn test_start_model_only_for_short_tasks() {
+        let mut p = policy(&[]);
+        p.small_model = Some("haiku".into());
+        assert_eq!(p.start_model(&TaskInput::new("fix typo")), Some("haiku"));
+        assert_eq!(p.start_model(&TaskInput::new("x".repeat(500))), None);
+    }
+
+    #[test]
+    fn test_larger_context_model_prefers_smallest_fitting() {
+        let models = vec![
+            model("base", 128_000, 1.0),
+            model("big", 400_000, 2.0),
+            model("huge", 1_000_000, 3.0),
+        ];
+        let m = larger_context_model(&models, "base", 128_000, 150_000, true).unwrap();
+        assert_eq!(m.id, "big");
+        let m = larger_context_model(&models, "base", 128_000, 2_000_000, true).unwrap();
+        assert_eq!(m.id, "huge");
+        assert!(larger_context_model(&models, "huge", 1_000_000, 150_000, true).is_none());
+    }
+
+    #[test]
+    fn test_stalled_iterations() {
+        let cycles = vec![scored(0.5), scored(0.52), scored(0.51)];
+        assert_eq!(stalled_iterations(&cycles, 0.05), 2);
+
+        let improving = vec![scored(0.5), scored(0.6), scored(0.7)];
+        assert_eq!(stalled_iterations(&improving, 0.05), 0);
+    }
+
+    #[test]
+    fn test_stalled_iterations_resets_after_escalation() {
+        let mut switched = scored(0.5);
+        switched.decision = IterationDecision::Escalate;
+        switched.escalation = Some(Escalation {
+            iteration: 1,
+            from: "a".into(),
+            to: "b".into(),
+            reason: EscalationReason::Stalled { iterations: 2 },
+            cost_before_usd: 0.0,
+        });
+        let cycles = vec![scored(0.5), scored(0.5), switched, scored(0.5)];
+        assert_eq!(stalled_iterations(&cycles, 0.05), 0);
+    }
+}
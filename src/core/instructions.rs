@@ -0,0 +1,449 @@
+// src/core/instructions.rs — Project instruction files (AGENTS.md and friends)
+//
+// Repository-specific engineering rules: build commands, code style,
+// directories not to touch. Discovered from the working directory:
+//
+//   1. CONTRIBUTING.md at the repository root (lowest precedence)
+//   2. AGENTS.md in every directory from the repository root down to cwd
+//   3. .openkoi/INSTRUCTIONS.md in cwd
+//   4. AGENTS.md in subdirectories of cwd — these apply only to files under
+//      that directory, so they are included when the task mentions such a
+//      path or an earlier iteration modified a file there, and otherwise
+//      listed as pointers the model can read.
+//
+// Later files take precedence. The rendered section is token-budgeted by
+// `TokenOptimizer::fit_instructions`.
+
+use std::path::{Path, PathBuf};
+
+use super::tokenizer::TokenCounter;
+use super::types::{IterationCycle, TaskInput};
+
+pub const AGENTS_FILE: &str = "AGENTS.md";
+pub const PROJECT_INSTRUCTIONS_FILE: &str = ".openkoi/INSTRUCTIONS.md";
+const CONTRIBUTING_FILE: &str = "CONTRIBUTING.md";
+
+/// Per-file cap, matching the soul loader.
+const MAX_FILE_CHARS: usize = 20_000;
+
+/// How deep to look for nested AGENTS.md files below cwd.
+const MAX_NESTED_DEPTH: usize = 6;
+
+/// Directories never searched for nested instruction files.
+const SKIP_DIRS: &[&str] = &["target", "node_modules", "vendor", "dist", "build"];
+
+/// Where an instruction file applies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstructionScope {
+    /// The whole project.
+    Project,
+    /// Files under this directory (relative to cwd, `/`-separated).
+    Directory(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct InstructionFile {
+    /// Path for display, relative to cwd where possible.
+    pub path: PathBuf,
+    pub scope: InstructionScope,
+    pub content: String,
+}
+
+impl InstructionFile {
+    fn heading(&self) -> String {
+        match &self.scope {
+            InstructionScope::Project => format!("## {}\n\n", self.path.display()),
+            InstructionScope::Directory(dir) => {
+                format!("## {} (applies to {}/)\n\n", self.path.display(), dir)
+            }
+        }
+    }
+
+    fn block(&self, content: &str) -> String {
+        format!("{}{}\n\n", self.heading(), content.trim())
+    }
+}
+
+/// Instruction files in precedence order, lowest first.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectInstructions {
+    pub files: Vec<InstructionFile>,
+}
+
+impl ProjectInstructions {
+    /// Discover instruction files for a session started in `cwd`.
+    pub fn discover(cwd: &Path) -> Self {
+        let cwd = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+        let root = repo_root(&cwd);
+        let mut files = Vec::new();
+
+        let mut push = |path: PathBuf, scope: InstructionScope| {
+            if let Some(content) = read_instruction_file(&path) {
+                let display = path
+                    .strip_prefix(&cwd)
+                    .map(Path::to_path_buf)
+                    .unwrap_or_else(|_| path.clone());
+                files.push(InstructionFile {
+                    path: display,
+                    scope,
+                    content,
+                });
+            }
+        };
+
+        push(root.join(CONTRIBUTING_FILE), InstructionScope::Project);
+        let mut dirs: Vec<&Path> = cwd
+            .ancestors()
+            .take_while(|d| d.starts_with(&root))
+            .collect();
+        dirs.reverse();
+        for dir in dirs {
+            push(dir.join(AGENTS_FILE), InstructionScope::Project);
+        }
+        push(
+            cwd.join(PROJECT_INSTRUCTIONS_FILE),
+            InstructionScope::Project,
+        );
+
+        for dir in nested_dirs(&cwd) {
+            let rel = dir
+                .strip_prefix(&cwd)
+                .unwrap_or(&dir)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            push(dir.join(AGENTS_FILE), InstructionScope::Directory(rel));
+        }
+
+        if !files.is_empty() {
+            tracing::debug!("Loaded {} project instruction file(s)", files.len());
+        }
+        Self { files }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Split into files that apply and directory-scoped files that don't:
+    /// a directory's file applies when `task` mentions the directory or one
+    /// of the `touched` paths (files modified so far) is under it.
+    pub fn partition(
+        &self,
+        task: &TaskInput,
+        touched: &[String],
+    ) -> (Vec<&InstructionFile>, Vec<&InstructionFile>) {
+        let text = match &task.context {
+            Some(ctx) => format!("{}\n{}", task.description, ctx),
+            None => task.description.clone(),
+        };
+        self.files.iter().partition(|f| match &f.scope {
+            InstructionScope::Project => true,
+            InstructionScope::Directory(dir) => {
+                mentions_path(&text, dir) || touched.iter().any(|p| is_under(p, dir))
+            }
+        })
+    }
+
+    /// Render the prompt section within `budget` tokens. Lower-precedence
+    /// files are left out first (and listed as pointers); if the most
+    /// specific file alone is over budget it is cut short.
+    pub fn render(
+        &self,
+        task: &TaskInput,
+        touched: &[String],
+        counter: &dyn TokenCounter,
+        budget: u32,
+    ) -> String {
+        if self.files.is_empty() || budget == 0 {
+            return String::new();
+        }
+        let (active, inactive) = self.partition(task, touched);
+        let mut pointers: Vec<&InstructionFile> = inactive;
+
+        // Take files from highest precedence down until the budget is spent.
+        let mut used = 0u32;
+        let mut kept: Vec<String> = Vec::new();
+        for (i, file) in active.iter().enumerate().rev() {
+            let block = file.block(&file.content);
+            let tokens = counter.count(&block);
+            if used + tokens <= budget {
+                used += tokens;
+                kept.push(block);
+            } else if kept.is_empty() {
+                kept.push(file.block(&truncate_to_budget(&file.content, counter, budget)));
+                pointers.extend(active[..i].iter().copied());
+                break;
+            } else {
+                pointers.extend(active[..=i].iter().copied());
+                break;
+            }
+        }
+        kept.reverse();
+
+        let mut section = String::from("# Project Instructions\n\n");
+        section.push_str(
+            "Rules for this repository from its instruction files. Follow them; \
+             where they conflict, later sections take precedence over earlier ones.\n\n",
+        );
+        for block in &kept {
+            section.push_str(block);
+        }
+        if !pointers.is_empty() {
+            section.push_str(
+                "More instruction files were left out here. Read the relevant one \
+                 before changing anything it covers:\n",
+            );
+            for file in pointers {
+                match &file.scope {
+                    InstructionScope::Directory(dir) => {
+                        section.push_str(&format!("- {} ({}/)\n", file.path.display(), dir))
+                    }
+                    InstructionScope::Project => {
+                        section.push_str(&format!("- {}\n", file.path.display()))
+                    }
+                }
+            }
+            section.push('\n');
+        }
+        section
+    }
+}
+
+/// Files modified by the executor in `cycles`, without duplicates.
+pub fn files_touched(cycles: &[IterationCycle]) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for output in cycles.iter().filter_map(|c| c.output.as_ref()) {
+        for file in &output.files_modified {
+            if !files.contains(file) {
+                files.push(file.clone());
+            }
+        }
+    }
+    files
+}
+
+/// Whether `path` (relative to cwd, or absolute under it) lies in `dir`.
+fn is_under(path: &str, dir: &str) -> bool {
+    let mut path = path.replace('\\', "/");
+    if Path::new(&path).is_absolute() {
+        let Ok(cwd) = std::env::current_dir() else {
+            return false;
+        };
+        let cwd = cwd.canonicalize().unwrap_or(cwd);
+        match Path::new(&path).strip_prefix(&cwd) {
+            Ok(rel) => path = rel.to_string_lossy().replace('\\', "/"),
+            Err(_) => return false,
+        }
+    }
+    let path = path.trim_start_matches("./");
+    path.strip_prefix(dir)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Nearest ancestor with a `.git` entry, or `cwd` itself.
+fn repo_root(cwd: &Path) -> PathBuf {
+    cwd.ancestors()
+        .find(|d| d.join(".git").exists())
+        .unwrap_or(cwd)
+        .to_path_buf()
+}
+
+fn read_instruction_file(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    if content.trim().is_empty() {
+        return None;
+    }
+    Some(if content.len() > MAX_FILE_CHARS {
+        content.chars().take(MAX_FILE_CHARS).collect()
+    } else {
+        content
+    })
+}
+
+/// Subdirectories of `cwd` (breadth-first, sorted) that contain an AGENTS.md.
+fn nested_dirs(cwd: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut level = vec![cwd.to_path_buf()];
+    for _ in 0..MAX_NESTED_DEPTH {
+        let mut next = Vec::new();
+        for dir in &level {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+            let mut children: Vec<PathBuf> = entries
+                .flatten()
+                .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                .filter(|e| {
+                    let name = e.file_name();
+                    let name = name.to_string_lossy();
+                    !name.starts_with('.') && !SKIP_DIRS.contains(&name.as_ref())
+                })
+                .map(|e| e.path())
+                .collect();
+            children.sort();
+            for child in children {
+                if child.join(AGENTS_FILE).is_file() {
+                    found.push(child.clone());
+                }
+                next.push(child);
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        level = next;
+    }
+    found
+}
+
+/// Whether `text` names `dir` or a path under it.
+fn mentions_path(text: &str, dir: &str) -> bool {
+    let is_path_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/');
+    text.match_indices(dir).any(|(i, _)| {
+        let head = &text[..i];
+        let rest = &text[i + dir.len()..];
+        let starts = match head.chars().next_back() {
+            None => true,
+            Some(c) => !is_path_char(c) || head.ends_with("./"),
+        };
+        let ends = match rest.chars().next() {
+            None | Some('/') => true,
+            // End of a sentence, not an extension.
+            Some('.') => !rest[1..].starts_with(char::is_alphanumeric),
+            Some(c) => !is_path_char(c),
+        };
+        starts && ends
+    })
+}
+
+/// Cut `content` so its token count fits `budget` (leaving room for the
+/// heading and the truncation note).
+fn truncate_to_budget(content: &str, counter: &dyn TokenCounter, budget: u32) -> String {
+    let target = budget.saturating_sub(50);
+    let mut chars: Vec<char> = content.chars().collect();
+    while !chars.is_empty() && counter.count(&chars.iter().collect::<String>()) > target {
+        let keep = chars.len() * 3 / 4;
+        chars.truncate(keep);
+    }
+    let mut out: String = chars.into_iter().collect();
+    out.push_str("\n\n[... truncated to fit the context budget]");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::tokenizer::Heuristic;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_discover_precedence_and_scopes() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        std::fs::create_dir(root.join(".git")).unwrap();
+        write(&root.join("CONTRIBUTING.md"), "contributing");
+        write(&root.join("AGENTS.md"), "root rules");
+        write(&root.join("app/AGENTS.md"), "app rules");
+        write(&root.join("app/.openkoi/INSTRUCTIONS.md"), "openkoi rules");
+        write(&root.join("app/api/AGENTS.md"), "api rules");
+        write(&root.join("app/node_modules/x/AGENTS.md"), "ignored");
+
+        let found = ProjectInstructions::discover(&root.join("app"));
+        let contents: Vec<&str> = found.files.iter().map(|f| f.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec![
+                "contributing",
+                "root rules",
+                "app rules",
+                "openkoi rules",
+                "api rules"
+            ]
+        );
+        assert_eq!(
+            found.files[4].scope,
+            InstructionScope::Directory("api".into())
+        );
+        assert_eq!(found.files[2].path, PathBuf::from("AGENTS.md"));
+    }
+
+    #[test]
+    fn test_render_scoped_files_follow_task_paths() {
+        let instructions = ProjectInstructions {
+            files: vec![
+                InstructionFile {
+                    path: "AGENTS.md".into(),
+                    scope: InstructionScope::Project,
+                    content: "Run `make check` before finishing.".into(),
+                },
+                InstructionFile {
+                    path: "api/AGENTS.md".into(),
+                    scope: InstructionScope::Directory("api".into()),
+                    content: "Never edit generated handlers.".into(),
+                },
+            ],
+        };
+
+        let other = instructions.render(&TaskInput::new("Fix the README"), &[], &Heuristic, 1000);
+        assert!(other.contains("make check"));
+        assert!(!other.contains("generated handlers"));
+        assert!(other.contains("- api/AGENTS.md (api/)"));
+
+        let task = TaskInput::new("Fix the bug in api/users.rs");
+        let touching = instructions.render(&task, &[], &Heuristic, 1000);
+        assert!(touching.contains("Never edit generated handlers."));
+        assert!(touching.find("make check") < touching.find("generated handlers"));
+
+        // Modifying a file under the directory applies it too.
+        let task = TaskInput::new("Rename the user endpoints");
+        let modified = vec!["./api/users.rs".to_string()];
+        let out = instructions.render(&task, &modified, &Heuristic, 1000);
+        assert!(out.contains("Never edit generated handlers."));
+        let elsewhere = vec!["apidocs/users.md".to_string()];
+        let out = instructions.render(&task, &elsewhere, &Heuristic, 1000);
+        assert!(!out.contains("Never edit generated handlers."));
+    }
+
+    #[test]
+    fn test_render_drops_lowest_precedence_first() {
+        let file = |path: &str, content: String| InstructionFile {
+            path: path.into(),
+            scope: InstructionScope::Project,
+            content,
+        };
+        let instructions = ProjectInstructions {
+            files: vec![
+                file("CONTRIBUTING.md", "c".repeat(2000)),
+                file("AGENTS.md", "agents".into()),
+            ],
+        };
+        let task = TaskInput::new("task");
+        let out = instructions.render(&task, &[], &Heuristic, 200);
+        assert!(out.contains("## AGENTS.md"));
+        assert!(!out.contains("## CONTRIBUTING.md"));
+        assert!(out.contains("- CONTRIBUTING.md"));
+
+        let big = ProjectInstructions {
+            files: vec![file("AGENTS.md", "word ".repeat(4000))],
+        };
+        let out = big.render(&task, &[], &Heuristic, 300);
+        assert!(Heuristic.count(&out) < 400);
+        assert!(out.contains("[... truncated"));
+    }
+
+    #[test]
+    fn test_mentions_path() {
+        assert!(mentions_path("edit api/users.rs", "api"));
+        assert!(mentions_path("look at ./api please", "api"));
+        assert!(mentions_path("the api directory", "api"));
+        assert!(!mentions_path("see rapid/x.rs", "api"));
+        assert!(!mentions_path("src/api/x.rs", "api"));
+        assert!(mentions_path("src/api/x.rs", "src/api"));
+    }
+}
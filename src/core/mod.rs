@@ -1,8 +1,10 @@
 // src/core/mod.rs — Core iteration engine
 
 pub mod cost;
+pub mod escalation;
 pub mod eval_cache;
 pub mod executor;
+pub mod instructions;
 pub mod orchestrator;
 pub mod overflow;
 pub mod safety;
@@ -10,5 +12,6 @@ pub mod state;
 pub mod system_prompt;
 pub mod token_budget;
 pub mod token_optimizer;
+pub mod tokenizer;
 pub mod truncation;
 pub mod types;
@@ -4,27 +4,37 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Instant;
 
-use super::cost::CostTracker;
+use super::cost::{CostTracker, Pricing};
+use super::escalation::{self, Escalation, EscalationPolicy, EscalationReason};
 use super::eval_cache::EvalCache;
 use super::executor::Executor;
+use super::instructions::{self, ProjectInstructions};
 use super::safety::SafetyChecker;
 use super::token_budget::TokenBudget;
-use super::token_optimizer::TokenOptimizer;
+use super::token_optimizer::{check_context_fit, TokenOptimizer, INSTRUCTIONS_TOKEN_BUDGET};
+use super::tokenizer;
 use super::types::*;
 use crate::evaluator::EvaluatorFramework;
-use crate::integrations::registry::IntegrationRegistry;
+use crate::index::CodeIndex;
+use crate::learner::experiment;
 use crate::learner::extractor::LearningExtractor;
 use crate::learner::types::RankedSkill;
+use crate::lsp::LspManager;
 use crate::memory::recall::HistoryRecall;
 use crate::memory::store::Store;
-use crate::plugins::mcp::McpManager;
+use crate::provider::governor;
 use crate::provider::roles::ModelRoles;
-use crate::provider::{ModelInfo, ModelProvider, ToolDef};
+use crate::provider::{ChatRequest, Message, ModelInfo, ModelProvider, TokenUsage, ToolDef};
+use crate::security::{audit, redact};
 use crate::skills::registry::SkillRegistry;
 use crate::soul::loader::Soul;
+use crate::tools::overlay::{Overlay, OverlayFiles};
+use crate::tools::ToolRegistry;
 
 /// The central orchestrator that drives the plan-execute-evaluate-refine loop.
 pub struct Orchestrator {
+    /// Kept for provider-side token counting near the context limit.
+    provider: Arc<dyn ModelProvider>,
     executor: Executor,
     evaluator: EvaluatorFramework,
     extractor: LearningExtractor,
@@ -41,18 +51,33 @@ pub struct Orchestrator {
     /// Resolved ModelInfo for accurate cost tracking (None if model not found in catalog).
     executor_model_info: Option<ModelInfo>,
     evaluator_model_info: Option<ModelInfo>,
+    /// Executor model from `ModelRoles`, before any escalation.
+    configured_executor_id: String,
+    /// Provider model catalog, for picking escalation targets.
+    catalog: Vec<ModelInfo>,
+    escalation: EscalationPolicy,
     /// Optional persistence store for recording task/cycle/finding data.
     /// Uses `std::sync::Mutex` (not tokio) because the lock is never held across
     /// an `.await` — all store operations are short synchronous writes.
     store: Option<Arc<Mutex<Store>>>,
     /// Optional callback for real-time progress events.
     on_progress: Option<Box<dyn Fn(ProgressEvent) + Send>>,
+    /// Dry-run overlay holding the task's file changes, and the scratch copy
+    /// of the project that tests and linters run in.
+    overlay: Option<(Arc<Overlay>, Option<std::path::PathBuf>)>,
 }
 
 /// Everything the orchestrator needs beyond the raw task description.
 /// Assembled by the CLI layer before calling `orchestrator.run()`.
 pub struct SessionContext {
     pub soul: Soul,
+    /// Project instruction files (AGENTS.md etc.) for the session's directory.
+    pub instructions: ProjectInstructions,
+    /// Code index for the session's project; feeds the repository map.
+    pub code_index: Option<Arc<CodeIndex>>,
+    /// Language servers for the session's project; their diagnostics are
+    /// scored by the evaluator.
+    pub lsp: Option<Arc<LspManager>>,
     pub ranked_skills: Vec<RankedSkill>,
     pub recall: HistoryRecall,
     pub tools: Vec<ToolDef>,
@@ -83,34 +108,54 @@ impl Orchestrator {
             .map(|m| m.context_window)
             .unwrap_or(0);
 
+        let token_counter = tokenizer::for_model(executor_model_info.as_ref());
+        tracing::debug!(
+            model = %executor_model_id,
+            tokenizer = token_counter.name(),
+            "Selected token counter"
+        );
+
         Self {
+            provider: provider.clone(),
             executor: Executor::new(provider.clone(), executor_model_id.clone())
                 .with_tool_loop_thresholds(
                     safety.tool_loop_warning,
                     safety.tool_loop_critical,
                     safety.tool_loop_circuit_breaker,
-                ),
+                )
+                .with_tool_parallelism(safety.tool_max_parallel),
             evaluator: EvaluatorFramework::new(
                 skill_registry,
                 provider.clone(),
                 evaluator_model_id.clone(),
             ),
             extractor: LearningExtractor::new(provider, evaluator_model_id.clone()),
-            token_optimizer: TokenOptimizer::new(),
+            token_optimizer: TokenOptimizer::new().with_counter(token_counter),
             eval_cache: EvalCache::new(),
             safety,
             cost_tracker: CostTracker::new(),
             config,
             context_window,
-            executor_model_id,
+            executor_model_id: executor_model_id.clone(),
             evaluator_model_id,
             executor_model_info,
             evaluator_model_info,
+            configured_executor_id: executor_model_id,
+            catalog: models,
+            escalation: EscalationPolicy::default(),
             store,
             on_progress: None,
+            overlay: None,
         }
     }
 
+    /// Enable model escalation (larger context on overflow, stronger model
+    /// on stalls, small model for short tasks). Disabled by default.
+    pub fn with_escalation(mut self, policy: EscalationPolicy) -> Self {
+        self.escalation = policy;
+        self
+    }
+
     /// Override the project directory used for test/lint detection.
     /// Primarily useful in tests to avoid running real `cargo test` etc.
     pub fn with_project_dir(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
@@ -118,6 +163,29 @@ impl Orchestrator {
         self
     }
 
+    /// Judge every iteration with the evaluator skill `name` instead of
+    /// choosing one by task category.
+    pub fn with_evaluator_skill(mut self, name: impl Into<String>) -> Self {
+        self.evaluator = self.evaluator.with_evaluator_skill(name);
+        self
+    }
+
+    /// Run against a dry-run overlay. After each iteration the overlay is
+    /// written into `workspace` (when given), which becomes the directory
+    /// tests and linters run in; at the end the overlay is rolled back to
+    /// the accepted iteration's changes.
+    pub fn with_overlay(
+        mut self,
+        overlay: Arc<Overlay>,
+        workspace: Option<std::path::PathBuf>,
+    ) -> Self {
+        if let Some(ref dir) = workspace {
+            self.evaluator = self.evaluator.with_project_dir(dir.clone());
+        }
+        self.overlay = Some((overlay, workspace));
+        self
+    }
+
     /// Set a callback for real-time progress events.
     /// The callback receives `ProgressEvent` values at key lifecycle transitions.
     pub fn with_progress(mut self, cb: impl Fn(ProgressEvent) + Send + 'static) -> Self {
@@ -132,6 +200,187 @@ impl Orchestrator {
         }
     }
 
+    /// Record cost for an execute or evaluate call, attributed to the task.
+    /// Uses ModelInfo pricing when available (accurate), falling back to
+    /// string-based model name lookup (heuristic).
+    fn record_cost(&mut self, phase: Phase, usage: &TokenUsage, task_id: &str) {
+        let (model_id, info, phase_name) = match phase {
+            Phase::Evaluate => (
+                &self.evaluator_model_id,
+                &self.evaluator_model_info,
+                "evaluate",
+            ),
+            _ => (
+                &self.executor_model_id,
+                &self.executor_model_info,
+                "execute",
+            ),
+        };
+        let pricing = match info {
+            Some(info) => Pricing::from_model_info(info),
+            None => Pricing::from_model_name(model_id),
+        };
+        self.cost_tracker
+            .record_for_task_phase(model_id, usage, &pricing, phase_name, task_id);
+    }
+
+    /// Ask the provider for an exact prompt size when the local count is an
+    /// approximation and close enough to the limit that the error matters.
+    /// Failures are non-fatal: the local estimate is used instead.
+    async fn measure_context(&self, context: &ExecutionContext, tools: &[ToolDef]) -> Option<u32> {
+        if !self.token_optimizer.needs_exact_count(
+            context,
+            self.token_optimizer.count_tools(tools),
+            self.context_window,
+        ) {
+            return None;
+        }
+
+        let messages = if context.messages.is_empty() {
+            vec![Message::user("Begin.")]
+        } else {
+            context.messages.clone()
+        };
+        let request = ChatRequest {
+            model: self.executor_model_id.clone(),
+            messages,
+            tools: tools.to_vec(),
+            system: Some(context.system.clone()),
+            ..Default::default()
+        };
+
+        match self.provider.count_tokens(&request).await {
+            Ok(Some(tokens)) => {
+                tracing::debug!(
+                    estimate = context.token_estimate,
+                    measured = tokens,
+                    "Provider token count near context limit"
+                );
+                Some(tokens)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                tracing::debug!("Provider token count failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Build the iteration context with the current executor's tokenizer.
+    fn build_context(
+        &self,
+        task: &TaskInput,
+        plan: &Plan,
+        cycles: &[IterationCycle],
+        ctx: &SessionContext,
+        repo_map: &str,
+    ) -> ExecutionContext {
+        self.token_optimizer.build_context_with_history(
+            task,
+            plan,
+            cycles,
+            &ctx.soul,
+            &ctx.instructions,
+            &ctx.ranked_skills,
+            &ctx.recall,
+            &ctx.tools,
+            &ctx.skill_registry,
+            ctx.conversation_history.as_deref(),
+            repo_map,
+        )
+    }
+
+    /// Refresh the code index and render the repository map for this
+    /// iteration, ranked against the task, the files touched so far and the
+    /// latest findings.
+    async fn repo_map(
+        &self,
+        task: &TaskInput,
+        cycles: &[IterationCycle],
+        ctx: &SessionContext,
+    ) -> String {
+        let Some(index) = ctx.code_index.as_ref() else {
+            return String::new();
+        };
+        if let Err(e) = index.refresh().await {
+            tracing::debug!("Code index refresh failed: {}", e);
+        }
+        self.token_optimizer.fit_repo_map(
+            index,
+            &repo_map_query(task, cycles),
+            index.config().map_tokens,
+        )
+    }
+
+    /// Move the executor to `to`, refreshing its pricing, context window and
+    /// tokenizer, and record the switch in the cost breakdown.
+    fn switch_executor(&mut self, to: &str, reason: EscalationReason, iteration: u8) -> Escalation {
+        let escalation = Escalation {
+            iteration,
+            from: self.executor_model_id.clone(),
+            to: to.to_string(),
+            reason,
+            cost_before_usd: self.cost_tracker.total_usd,
+        };
+        tracing::info!(
+            from = %escalation.from,
+            to = %escalation.to,
+            "Switching executor model: {}",
+            escalation.reason
+        );
+
+        self.executor.set_model(to.to_string());
+        self.executor_model_id = to.to_string();
+        self.executor_model_info = self.catalog.iter().find(|m| m.id == to).cloned();
+        self.context_window = self
+            .executor_model_info
+            .as_ref()
+            .map(|m| m.context_window)
+            .unwrap_or(0);
+        self.token_optimizer = TokenOptimizer::new()
+            .with_counter(tokenizer::for_model(self.executor_model_info.as_ref()));
+
+        self.cost_tracker.record_escalation(escalation.clone());
+        self.emit(ProgressEvent::Escalated {
+            iteration: iteration + 1,
+            from: escalation.from.clone(),
+            to: escalation.to.clone(),
+            reason: escalation.reason.to_string(),
+        });
+        escalation
+    }
+
+    /// Switch to a catalog model with a larger window when `needed_tokens`
+    /// does not fit the current one. Returns `None` if disabled or no larger
+    /// model exists (the caller then prunes).
+    fn escalate_on_overflow(
+        &mut self,
+        needed_tokens: u32,
+        iteration: u8,
+        requires_tools: bool,
+    ) -> Option<Escalation> {
+        if !self.escalation.on_overflow
+            || self.context_window == 0
+            || check_context_fit(needed_tokens, self.context_window) == 0
+        {
+            return None;
+        }
+        let target = escalation::larger_context_model(
+            &self.catalog,
+            &self.executor_model_id,
+            self.context_window,
+            needed_tokens,
+            requires_tools,
+        )?
+        .id
+        .clone();
+        Some(self.switch_executor(
+            &target,
+            EscalationReason::ContextOverflow { needed_tokens },
+            iteration,
+        ))
+    }
+
     /// Persist a single cycle (and its findings) to the store. Non-fatal on error.
     fn persist_cycle(&self, task_id: &str, cycle: &IterationCycle, iteration: usize) {
         let Some(ref store) = self.store else { return };
@@ -170,17 +419,27 @@ impl Orchestrator {
 
     /// Run the full iteration loop for a task.
     ///
-    /// `mcp` is passed as `Option<&mut McpManager>` so tool calls from the model
-    /// can be dispatched to MCP servers. Pass `None` if no MCP servers are configured.
-    ///
-    /// `integrations` is passed so tool calls for connected apps (Slack, Notion, etc.)
-    /// can be dispatched. Pass `None` if no integrations are connected.
+    /// `tools` dispatches the model's tool calls (MCP servers, integrations,
+    /// plugins, skills). `ctx.tools` should hold its schemas. Pass `None` if
+    /// no tools are available.
     pub async fn run(
         &mut self,
         task: TaskInput,
         ctx: &SessionContext,
-        mut mcp: Option<&mut McpManager>,
-        integrations: Option<&IntegrationRegistry>,
+        tools: Option<&ToolRegistry>,
+    ) -> anyhow::Result<TaskResult> {
+        // Attribute this task's provider calls for fair queuing in the
+        // governor, and keep the secrets it masks only while it runs.
+        let task_key = task.id.clone();
+        let run = redact::with_vault(self.run_task(task, ctx, tools));
+        governor::with_task(task_key, run).await
+    }
+
+    async fn run_task(
+        &mut self,
+        task: TaskInput,
+        ctx: &SessionContext,
+        tools: Option<&ToolRegistry>,
     ) -> anyhow::Result<TaskResult> {
         let start = Instant::now();
 
@@ -197,6 +456,9 @@ impl Orchestrator {
         let mut cycles: Vec<IterationCycle> = Vec::new();
         let mut budget = TokenBudget::new(self.config.token_budget);
         let mut best_idx: Option<usize> = None;
+        // Overlay state after each executed cycle, by cycle index.
+        let mut snapshots: std::collections::HashMap<usize, OverlayFiles> =
+            std::collections::HashMap::new();
 
         // Persist task record
         let task_id = uuid::Uuid::new_v4().to_string();
@@ -206,6 +468,24 @@ impl Orchestrator {
             }
         }
 
+        // The evaluator grades against the same repository rules the
+        // executor is given.
+        self.evaluator
+            .set_conventions(self.token_optimizer.fit_instructions(
+                &ctx.instructions,
+                &task,
+                &[],
+                INSTRUCTIONS_TOKEN_BUDGET,
+            ));
+        self.evaluator.set_lsp(ctx.lsp.clone());
+
+        // Short tasks may start on the small model
+        if let Some(small) = self.escalation.start_model(&task).map(str::to_string) {
+            if small != self.executor_model_id {
+                self.switch_executor(&small, EscalationReason::SmallTask, 0);
+            }
+        }
+
         // Emit plan ready
         self.emit(ProgressEvent::PlanReady {
             steps: plan.steps.len(),
@@ -213,8 +493,10 @@ impl Orchestrator {
         });
 
         // 2. Iteration loop
+        let mut error: Option<String> = None;
         for i in 0..self.config.max_iterations {
             let mut cycle = IterationCycle::new(&task, i);
+            let cost_before = self.cost_tracker.total_usd;
 
             // Emit iteration start
             self.emit(ProgressEvent::IterationStart {
@@ -223,18 +505,27 @@ impl Orchestrator {
             });
 
             // Build context (compressed on iteration 2+, with overflow prevention)
+            let repo_map = self.repo_map(&task, &cycles, ctx).await;
             let context = if self.context_window > 0 {
-                let (ctx, pruned) = self.token_optimizer.build_context_safe(
-                    &task,
-                    &plan,
-                    &cycles,
-                    &ctx.soul,
-                    &ctx.ranked_skills,
-                    &ctx.recall,
-                    &ctx.tools,
-                    &ctx.skill_registry,
+                let mut built = self.build_context(&task, &plan, &cycles, ctx, &repo_map);
+                let mut measured = self.measure_context(&built, &ctx.tools).await;
+                let mut tool_tokens = self.token_optimizer.count_tools(&ctx.tools);
+
+                // Prefer a larger-context model over pruning when one exists
+                let needed = measured.unwrap_or(built.token_estimate + tool_tokens);
+                if let Some(esc) = self.escalate_on_overflow(needed, i, !ctx.tools.is_empty()) {
+                    cycle.escalation = Some(esc);
+                    // Re-count with the new model's tokenizer
+                    built = self.build_context(&task, &plan, &cycles, ctx, &repo_map);
+                    measured = self.measure_context(&built, &ctx.tools).await;
+                    tool_tokens = self.token_optimizer.count_tools(&ctx.tools);
+                }
+
+                let (ctx, pruned) = self.token_optimizer.fit_to_window(
+                    built,
                     self.context_window,
-                    ctx.conversation_history.as_deref(),
+                    measured,
+                    tool_tokens,
                 );
                 if pruned {
                     tracing::info!(
@@ -245,43 +536,19 @@ impl Orchestrator {
                 }
                 ctx
             } else {
-                self.token_optimizer.build_context_with_history(
-                    &task,
-                    &plan,
-                    &cycles,
-                    &ctx.soul,
-                    &ctx.ranked_skills,
-                    &ctx.recall,
-                    &ctx.tools,
-                    &ctx.skill_registry,
-                    ctx.conversation_history.as_deref(),
-                )
+                self.build_context(&task, &plan, &cycles, ctx, &repo_map)
             };
 
-            // Execute (with MCP tool dispatch if available)
-            let mcp_ref = mcp.as_deref_mut();
-            match self
-                .executor
-                .execute(&context, &ctx.tools, mcp_ref, integrations)
-                .await
-            {
+            // Execute (with tool dispatch if available)
+            let executed = audit::with_scope(
+                &task_id,
+                i as usize,
+                self.executor.execute(&context, &ctx.tools, tools),
+            );
+            match executed.await {
                 Ok(output) => {
                     budget.deduct(&output.usage);
-                    // Record cost using ModelInfo pricing when available (accurate),
-                    // falling back to string-based model name lookup (heuristic).
-                    if let Some(ref info) = self.executor_model_info {
-                        self.cost_tracker.record_with_model_info_and_phase(
-                            info,
-                            &output.usage,
-                            "execute",
-                        );
-                    } else {
-                        self.cost_tracker.record_with_phase(
-                            &self.executor_model_id,
-                            &output.usage,
-                            "execute",
-                        );
-                    }
+                    self.record_cost(Phase::Execute, &output.usage, &task_id);
                     // Sync cycle-level usage from output
                     cycle.usage = output.usage.clone();
                     // Emit tool call events
@@ -293,28 +560,65 @@ impl Orchestrator {
                             });
                         }
                     }
+                    // Directory rules for the files just modified apply to
+                    // this evaluation too.
+                    if !output.files_modified.is_empty() && !ctx.instructions.is_empty() {
+                        let mut touched = instructions::files_touched(&cycles);
+                        for file in &output.files_modified {
+                            if !touched.contains(file) {
+                                touched.push(file.clone());
+                            }
+                        }
+                        self.evaluator
+                            .set_conventions(self.token_optimizer.fit_instructions(
+                                &ctx.instructions,
+                                &task,
+                                &touched,
+                                INSTRUCTIONS_TOKEN_BUDGET,
+                            ));
+                    }
                     cycle.output = Some(output);
+                    if let Some((ref overlay, ref workspace)) = self.overlay {
+                        if let Some(dir) = workspace {
+                            if let Err(e) = overlay.sync_to(dir) {
+                                tracing::warn!("Could not update the dry-run workspace: {}", e);
+                            }
+                        }
+                        snapshots.insert(cycles.len(), overlay.snapshot());
+                    }
                 }
                 Err(e) if e.is_context_overflow() => {
-                    tracing::warn!(
-                        "Context overflow on iteration {}: {}. Context will be pruned on next iteration.",
-                        i, e
-                    );
+                    tracing::warn!("Context overflow on iteration {}: {}", i, e);
+                    // The local count fit but the provider disagreed: the model
+                    // needs at least a full window more than we have.
+                    let needed = context.token_estimate.max(self.context_window);
+                    let escalated = self.escalate_on_overflow(needed, i, !ctx.tools.is_empty());
+                    let retry = if escalated.is_some() {
+                        "Retrying with a larger-context model."
+                    } else {
+                        "Retrying with pruned context."
+                    };
                     // Attach a synthetic output so the next iteration's build_context
                     // has something to work with (otherwise delta feedback is empty).
                     cycle.output = Some(ExecutionOutput {
                         content: format!(
-                            "[Context overflow on iteration {}. The context exceeded the model's window. \
-                             Retrying with pruned context.]",
-                            i
+                            "[Context overflow on iteration {}. The context exceeded the model's window. {}]",
+                            i, retry
                         ),
                         usage: crate::provider::TokenUsage::default(),
                         tool_calls_made: 0,
                         files_modified: vec![],
+                        tool_calls: vec![],
                     });
-                    // Don't abort — the next iteration will use build_context_safe
-                    // which prunes proactively. Mark this cycle as needing retry.
-                    cycle.decision = IterationDecision::Continue;
+                    // Don't abort — the next iteration either runs on the larger
+                    // model or prunes proactively via fit_to_window.
+                    cycle.decision = if escalated.is_some() {
+                        IterationDecision::Escalate
+                    } else {
+                        IterationDecision::Continue
+                    };
+                    cycle.escalation = escalated;
+                    cycle.cost = self.cost_tracker.total_usd - cost_before;
                     cycles.push(cycle);
                     if let Some(last) = cycles.last() {
                         self.persist_cycle(&task_id, last, i as usize);
@@ -323,7 +627,9 @@ impl Orchestrator {
                 }
                 Err(e) => {
                     tracing::error!("Execution failed on iteration {}: {}", i, e);
-                    cycle.decision = IterationDecision::AbortBudget;
+                    cycle.decision = IterationDecision::AbortError;
+                    error = Some(e.to_string());
+                    cycle.cost = self.cost_tracker.total_usd - cost_before;
                     cycles.push(cycle);
                     if let Some(last) = cycles.last() {
                         self.persist_cycle(&task_id, last, i as usize);
@@ -348,19 +654,7 @@ impl Orchestrator {
                     {
                         Ok(evaluation) => {
                             budget.deduct(&evaluation.usage);
-                            if let Some(ref info) = self.evaluator_model_info {
-                                self.cost_tracker.record_with_model_info_and_phase(
-                                    info,
-                                    &evaluation.usage,
-                                    "evaluate",
-                                );
-                            } else {
-                                self.cost_tracker.record_with_phase(
-                                    &self.evaluator_model_id,
-                                    &evaluation.usage,
-                                    "evaluate",
-                                );
-                            }
+                            self.record_cost(Phase::Evaluate, &evaluation.usage, &task_id);
                             cycle.evaluation = Some(evaluation);
                         }
                         Err(e) => {
@@ -399,6 +693,7 @@ impl Orchestrator {
                     message: format!("Safety abort: {}", abort_decision),
                 });
                 cycle.decision = abort_decision;
+                cycle.cost = self.cost_tracker.total_usd - cost_before;
                 cycles.push(cycle);
                 if let Some(last) = cycles.last() {
                     self.persist_cycle(&task_id, last, i as usize);
@@ -412,6 +707,29 @@ impl Orchestrator {
                 cycle.decision = IterationDecision::Accept;
             } else if i + 1 >= self.config.max_iterations {
                 cycle.decision = IterationDecision::AcceptBest;
+            } else if cycle.decision == IterationDecision::Continue
+                && self.escalation.stall_iterations > 0
+            {
+                let stalled = escalation::stalled_iterations(
+                    cycles.iter().chain(std::iter::once(&cycle)),
+                    self.config.improvement_threshold,
+                );
+                if stalled >= self.escalation.stall_iterations {
+                    if let Some(next) = self
+                        .escalation
+                        .next_rung(&self.executor_model_id, &self.configured_executor_id)
+                    {
+                        let esc = self.switch_executor(
+                            &next,
+                            EscalationReason::Stalled {
+                                iterations: stalled,
+                            },
+                            i,
+                        );
+                        cycle.escalation = Some(esc);
+                        cycle.decision = IterationDecision::Escalate;
+                    }
+                }
             }
 
             // Track best (>= favors the latest cycle on tie, since later iterations
@@ -434,7 +752,11 @@ impl Orchestrator {
                 cost_so_far: self.cost_tracker.total_usd,
             });
 
-            let should_continue = cycle.decision == IterationDecision::Continue;
+            let should_continue = matches!(
+                cycle.decision,
+                IterationDecision::Continue | IterationDecision::Escalate
+            );
+            cycle.cost = self.cost_tracker.total_usd - cost_before;
             cycles.push(cycle);
             if let Some(last) = cycles.last() {
                 self.persist_cycle(&task_id, last, i as usize);
@@ -495,6 +817,13 @@ impl Orchestrator {
             .or_else(|| cycles.last())
             .ok_or_else(|| anyhow::anyhow!("No iterations completed"))?;
 
+        // Leave the overlay holding the accepted iteration's changes.
+        if let (Some((overlay, _)), Some(idx)) = (&self.overlay, best_idx) {
+            if let Some(files) = snapshots.remove(&idx) {
+                overlay.restore(files);
+            }
+        }
+
         let final_score = best.score() as f64;
         let iterations = cycles.len() as u8;
         let total_tokens = budget.spent();
@@ -511,9 +840,20 @@ impl Orchestrator {
                     total_tokens as i64,
                     cost,
                 );
+                // Only scored results say anything about the skills used
+                if best.evaluation.is_some() {
+                    let category = experiment::category_key(task.category.as_deref());
+                    for rs in &ctx.ranked_skills {
+                        let name = &rs.skill.name;
+                        let base = experiment::base_name(name);
+                        let _ = s.record_skill_run(&task_id, name, base, category, final_score);
+                    }
+                }
             }
         }
 
+        tracing::debug!("{}", self.cost_tracker.analytics_report());
+
         // Emit completion
         self.emit(ProgressEvent::Complete {
             iterations,
@@ -523,14 +863,17 @@ impl Orchestrator {
         });
 
         Ok(TaskResult {
+            task_id,
             output: best.output.clone().unwrap_or(ExecutionOutput {
                 content: "No output generated".into(),
                 usage: crate::provider::TokenUsage::default(),
                 tool_calls_made: 0,
                 files_modified: vec![],
+                tool_calls: vec![],
             }),
             iterations,
             total_tokens,
+            usage: self.cost_tracker.usage(),
             cost,
             learnings_saved,
             skills_used: ctx
@@ -539,6 +882,43 @@ impl Orchestrator {
                 .map(|rs| rs.skill.name.clone())
                 .collect(),
             final_score,
+            decision: cycles
+                .last()
+                .map(|c| c.decision.clone())
+                .unwrap_or(IterationDecision::Continue),
+            history: cycles.iter().map(IterationSummary::from).collect(),
+            error,
         })
     }
 }
+
+/// What the repository map is ranked against: the task, files the executor
+/// has touched, and the latest evaluation's findings.
+fn repo_map_query(task: &TaskInput, cycles: &[IterationCycle]) -> String {
+    let mut query = task.description.clone();
+    if let Some(ref context) = task.context {
+        query.push('\n');
+        query.push_str(context);
+    }
+    for cycle in cycles {
+        if let Some(ref output) = cycle.output {
+            for file in &output.files_modified {
+                query.push('\n');
+                query.push_str(file);
+            }
+        }
+    }
+    if let Some(eval) = cycles.last().and_then(|c| c.evaluation.as_ref()) {
+        for finding in &eval.findings {
+            query.push('\n');
+            query.push_str(&finding.title);
+            query.push('\n');
+            query.push_str(&finding.description);
+            if let Some(ref location) = finding.location {
+                query.push('\n');
+                query.push_str(location);
+            }
+        }
+    }
+    query
+}
@@ -13,6 +13,7 @@ pub struct SafetyChecker {
     pub tool_loop_warning: u32,
     pub tool_loop_critical: u32,
     pub tool_loop_circuit_breaker: u32,
+    pub tool_max_parallel: usize,
 }
 
 impl SafetyChecker {
@@ -30,6 +31,7 @@ impl SafetyChecker {
             tool_loop_warning: safety.tool_loop.warning,
             tool_loop_critical: safety.tool_loop.critical,
             tool_loop_circuit_breaker: safety.tool_loop.circuit_breaker,
+            tool_max_parallel: safety.tool_loop.max_parallel,
         }
     }
 
@@ -105,6 +107,7 @@ mod tests {
             tool_loop_warning: 10,
             tool_loop_critical: 20,
             tool_loop_circuit_breaker: 30,
+            tool_max_parallel: 4,
         }
     }
 
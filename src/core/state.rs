@@ -144,6 +144,7 @@ pub fn state_writer_progress(
                         state.tool_calls.push(name.clone());
                     }
                 }
+                ProgressEvent::ToolStarted { .. } | ProgressEvent::ToolFinished { .. } => return,
                 ProgressEvent::IterationEnd {
                     score,
                     decision,
@@ -161,6 +162,9 @@ pub fn state_writer_progress(
                 ProgressEvent::SafetyWarning { .. } => {
                     state.phase = "safety_warning".to_string();
                 }
+                ProgressEvent::Escalated { to, .. } => {
+                    state.last_decision = format!("escalate:{}", to);
+                }
                 ProgressEvent::Complete {
                     iterations,
                     total_tokens,
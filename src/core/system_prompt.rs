@@ -5,17 +5,22 @@ use crate::learner::types::RankedSkill;
 use crate::memory::recall::HistoryRecall;
 use crate::provider::ToolDef;
 use crate::skills::registry::SkillRegistry;
+use crate::skills::tools::files_note;
 use crate::soul::loader::Soul;
 
 /// Build the complete system prompt injected as the `system` field in every LLM call.
 ///
 /// Sections (in order):
 ///   1. Identity — soul/persona framing
-///   2. Task — what the user wants done
-///   3. Plan — step-by-step approach
-///   4. Skills — relevant skill bodies (Level 2) for top-ranked, summaries (Level 1) for the rest
-///   5. Recall — anti-patterns, learnings, skill recommendations from memory
-///   6. Tools — available MCP/integration tools (names + descriptions)
+///   2. Project instructions — AGENTS.md and friends, pre-rendered and budgeted
+///   3. Skills — relevant skill bodies (Level 2) for top-ranked, summaries (Level 1) for the rest
+///   4. Recall — anti-patterns, learnings, skill recommendations from memory
+///   5. Tools — available MCP/integration tools (names + descriptions)
+///   6. Repository map — ranked outline of the code index for this iteration
+///   7. Task — what the user wants done
+///   8. Plan — step-by-step approach
+///
+/// Sections 1-5 form the stable, cacheable prefix; see `build_system_prompt_layout`.
 pub fn build_system_prompt(
     task: &TaskInput,
     plan: &Plan,
@@ -29,6 +34,7 @@ pub fn build_system_prompt(
         task,
         plan,
         soul,
+        "",
         ranked_skills,
         recall,
         tools,
@@ -40,51 +46,116 @@ pub fn build_system_prompt(
 /// Build the complete system prompt with optional conversation history.
 /// When `conversation_history` is provided, it is included as a section
 /// so the model has context from prior messages in the same chat session.
+/// `instructions` is the rendered project-instructions section (may be empty).
 #[allow(clippy::too_many_arguments)]
 pub fn build_system_prompt_with_history(
     task: &TaskInput,
     plan: &Plan,
     soul: &Soul,
+    instructions: &str,
     ranked_skills: &[RankedSkill],
     recall: &HistoryRecall,
     tools: &[ToolDef],
     skill_registry: &SkillRegistry,
     conversation_history: Option<&str>,
 ) -> String {
-    let mut prompt = String::with_capacity(8192);
+    build_system_prompt_layout(
+        task,
+        plan,
+        soul,
+        instructions,
+        ranked_skills,
+        recall,
+        tools,
+        skill_registry,
+        conversation_history,
+        "",
+    )
+    .into_string()
+}
+
+/// A system prompt split at its prompt-cache boundary.
+///
+/// `stable` holds the sections that do not change between iterations of a
+/// task (and usually not between tasks either), so providers can cache it.
+/// `dynamic` holds everything that may change per task or per iteration.
+#[derive(Debug, Clone)]
+pub struct SystemPromptLayout {
+    pub stable: String,
+    pub dynamic: String,
+}
+
+impl SystemPromptLayout {
+    pub fn into_string(self) -> String {
+        let mut prompt = self.stable;
+        prompt.push_str(&self.dynamic);
+        prompt
+    }
+}
+
+/// Build the system prompt as a cache-friendly layout.
+///
+/// Stable prefix: soul, project instructions, skills, recall, tools.
+/// Dynamic suffix: conversation history, repository map, task, plan (refined
+/// every iteration). `repo_map` is the pre-rendered map section (may be empty).
+#[allow(clippy::too_many_arguments)]
+pub fn build_system_prompt_layout(
+    task: &TaskInput,
+    plan: &Plan,
+    soul: &Soul,
+    instructions: &str,
+    ranked_skills: &[RankedSkill],
+    recall: &HistoryRecall,
+    tools: &[ToolDef],
+    skill_registry: &SkillRegistry,
+    conversation_history: Option<&str>,
+    repo_map: &str,
+) -> SystemPromptLayout {
+    let mut stable = String::with_capacity(8192);
 
     // --- Section 1: Identity (Soul) ---
     // Soul comes first — it frames everything else.
-    append_soul_section(&mut prompt, soul);
+    append_soul_section(&mut stable, soul);
+
+    // Repository rules sit right after identity: they constrain how
+    // everything below is applied.
+    stable.push_str(instructions);
+
+    // --- Section 2: Skills ---
+    append_skills_section(&mut stable, ranked_skills, skill_registry);
+
+    // --- Section 3: Recall ---
+    append_recall_section(&mut stable, recall);
 
-    // --- Section 1b: Conversation History (chat sessions only) ---
+    // --- Section 4: Available Tools ---
+    if !tools.is_empty() {
+        append_tools_section(&mut stable, tools);
+    }
+
+    let mut dynamic = String::with_capacity(2048);
+
+    // --- Section 5: Conversation History (chat sessions only) ---
     if let Some(history) = conversation_history {
         if !history.is_empty() {
-            prompt.push_str("# Conversation History\n\n");
-            prompt.push_str("Previous exchanges in this session:\n\n");
-            prompt.push_str(history);
-            prompt.push_str("\n\n");
+            dynamic.push_str("# Conversation History\n\n");
+            dynamic.push_str("Previous exchanges in this session:\n\n");
+            dynamic.push_str(history);
+            dynamic.push_str("\n\n");
         }
     }
 
-    // --- Section 2: Task ---
-    append_task_section(&mut prompt, task);
-
-    // --- Section 3: Plan ---
-    append_plan_section(&mut prompt, plan);
-
-    // --- Section 4: Skills ---
-    append_skills_section(&mut prompt, ranked_skills, skill_registry);
+    // --- Section 6: Repository Map ---
+    // Re-ranked every iteration against the task and latest findings, so it
+    // lives in the dynamic suffix.
+    dynamic.push_str(repo_map);
 
-    // --- Section 5: Recall ---
-    append_recall_section(&mut prompt, recall);
+    // --- Section 7: Task ---
+    append_task_section(&mut dynamic, task);
 
-    // --- Section 6: Available Tools ---
-    if !tools.is_empty() {
-        append_tools_section(&mut prompt, tools);
-    }
+    // --- Section 8: Plan ---
+    append_plan_section(&mut dynamic, plan);
 
-    prompt
+    SystemPromptLayout { stable, dynamic }
 }
 
 /// Build a lean system prompt for sub-tasks spawned by the orchestrator.
@@ -165,6 +236,11 @@ fn append_skills_section(
                 Ok(body) => {
                     prompt.push_str(&body);
                     prompt.push_str("\n\n");
+                    let files = registry.bundle_files(&rs.skill);
+                    if !files.is_empty() {
+                        prompt.push_str(&files_note(&files));
+                        prompt.push('\n');
+                    }
                 }
                 Err(_) => {
                     // Fall back to Level 1 (description only)
@@ -489,4 +565,105 @@ mod tests {
         assert!(prompt.contains("Recommended Skills"));
         assert!(prompt.contains("code-review, testing"));
     }
+
+    #[test]
+    fn test_instructions_follow_identity_in_stable_prefix() {
+        let soul = Soul {
+            raw: "soul".into(),
+            source: SoulSource::Default,
+        };
+        let task = TaskInput::new("task");
+        let plan = Plan {
+            steps: vec![],
+            estimated_iterations: 1,
+            estimated_tokens: 100,
+        };
+        let recall = HistoryRecall::default();
+        let registry = SkillRegistry::empty();
+        let tools = vec![ToolDef {
+            name: "read_file".into(),
+            description: "Read a file".into(),
+            parameters: serde_json::json!({}),
+        }];
+        let instructions = "# Project Instructions\n\nRun `make check`.\n\n";
+
+        let layout = build_system_prompt_layout(
+            &task,
+            &plan,
+            &soul,
+            instructions,
+            &[],
+            &recall,
+            &tools,
+            &registry,
+            None,
+            "",
+        );
+        let identity = layout.stable.find("# Identity").unwrap();
+        let rules = layout.stable.find("# Project Instructions").unwrap();
+        let tools_at = layout.stable.find("# Available Tools").unwrap();
+        assert!(identity < rules && rules < tools_at);
+    }
+
+    #[test]
+    fn test_layout_stable_prefix_survives_plan_refinement() {
+        let soul = Soul {
+            raw: "soul".into(),
+            source: SoulSource::Default,
+        };
+        let task = TaskInput::new("task");
+        let mut plan = Plan {
+            steps: vec![PlanStep {
+                description: "Step one".into(),
+                tools_needed: vec![],
+            }],
+            estimated_iterations: 2,
+            estimated_tokens: 100,
+        };
+        let recall = HistoryRecall::default();
+        let registry = SkillRegistry::empty();
+        let tools = vec![ToolDef {
+            name: "read_file".into(),
+            description: "Read a file".into(),
+            parameters: serde_json::json!({}),
+        }];
+
+        let first = build_system_prompt_layout(
+            &task,
+            &plan,
+            &soul,
+            "",
+            &[],
+            &recall,
+            &tools,
+            &registry,
+            None,
+            "",
+        );
+        plan.steps.push(PlanStep {
+            description: "Fix: something".into(),
+            tools_needed: vec![],
+        });
+        let second = build_system_prompt_layout(
+            &task,
+            &plan,
+            &soul,
+            "",
+            &[],
+            &recall,
+            &tools,
+            &registry,
+            None,
+            "# Repository Map\n\nsrc/lib.rs\n\n",
+        );
+
+        assert_eq!(first.stable, second.stable);
+        assert_ne!(first.dynamic, second.dynamic);
+        assert!(first.stable.contains("# Available Tools"));
+        assert!(!first.stable.contains("# Task"));
+        assert!(second.dynamic.contains("Fix: something"));
+        // The repository map is re-ranked per iteration: dynamic, before the task.
+        let map = second.dynamic.find("# Repository Map").unwrap();
+        assert!(map < second.dynamic.find("# Task").unwrap());
+    }
 }
@@ -1,7 +1,12 @@
 // src/core/token_optimizer.rs — Context compression, delta feedback, and overflow prevention
 
+use std::sync::Arc;
+
+use super::instructions::ProjectInstructions;
 use super::system_prompt;
+use super::tokenizer::{Heuristic, TokenCounter};
 use super::types::*;
+use crate::index::CodeIndex;
 use crate::learner::types::RankedSkill;
 use crate::memory::recall::HistoryRecall;
 use crate::provider::{Message, Role, ToolDef};
@@ -16,11 +21,20 @@ const CONTEXT_BUFFER_TOKENS: u32 = 20_000;
 /// Only tool results older than this threshold (counting backwards from newest) get pruned.
 const PROTECT_RECENT_TOKENS: u32 = 40_000;
 
+/// Fraction of the usable window above which an approximate count is
+/// checked against the provider's token-counting endpoint.
+const EXACT_COUNT_THRESHOLD: f32 = 0.85;
+
+/// Token budget for the project-instructions section of the system prompt.
+pub const INSTRUCTIONS_TOKEN_BUDGET: u32 = 4_000;
+
 /// Replacement text for pruned tool results.
 const PRUNED_PLACEHOLDER: &str = "[Old tool result cleared]";
 
 /// Manages context window efficiently across iterations.
-pub struct TokenOptimizer;
+pub struct TokenOptimizer {
+    counter: Arc<dyn TokenCounter>,
+}
 
 impl Default for TokenOptimizer {
     fn default() -> Self {
@@ -30,12 +44,26 @@ impl Default for TokenOptimizer {
 
 impl TokenOptimizer {
     pub fn new() -> Self {
-        Self
+        Self {
+            counter: Arc::new(Heuristic),
+        }
+    }
+
+    /// Count tokens with the executor model's tokenizer instead of the
+    /// chars/4 heuristic.
+    pub fn with_counter(mut self, counter: Arc<dyn TokenCounter>) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    pub fn counter(&self) -> &dyn TokenCounter {
+        self.counter.as_ref()
     }
 
     /// Build the smallest possible context for iteration N.
     /// On iteration 0: full system prompt + no messages.
-    /// On iteration 1+: same system prompt (cached by provider) + delta feedback only.
+    /// On iteration 1+: same stable prefix (cached by provider) + refined plan
+    /// + delta feedback only.
     #[allow(clippy::too_many_arguments)]
     pub fn build_context(
         &self,
@@ -53,15 +81,18 @@ impl TokenOptimizer {
             plan,
             cycles,
             soul,
+            &ProjectInstructions::default(),
             ranked_skills,
             recall,
             tools,
             skill_registry,
             None,
+            "",
         )
     }
 
-    /// Build context with optional conversation history (for chat sessions).
+    /// Build context with optional conversation history (for chat sessions),
+    /// project instruction files and a rendered repository map (may be empty).
     #[allow(clippy::too_many_arguments)]
     pub fn build_context_with_history(
         &self,
@@ -69,24 +100,35 @@ impl TokenOptimizer {
         plan: &Plan,
         cycles: &[IterationCycle],
         soul: &Soul,
+        instructions: &ProjectInstructions,
         ranked_skills: &[RankedSkill],
         recall: &HistoryRecall,
         tools: &[ToolDef],
         skill_registry: &SkillRegistry,
         conversation_history: Option<&str>,
+        repo_map: &str,
     ) -> ExecutionContext {
-        // System prompt is the same for every iteration (enables prompt caching).
-        let system = system_prompt::build_system_prompt_with_history(
+        // The stable prefix (soul, skills, recall, tools) is byte-identical for
+        // every iteration so providers can serve it from the prompt cache; only
+        // the task/plan suffix and the delta messages change.
+        let touched = super::instructions::files_touched(cycles);
+        let instructions =
+            self.fit_instructions(instructions, task, &touched, INSTRUCTIONS_TOKEN_BUDGET);
+        let layout = system_prompt::build_system_prompt_layout(
             task,
             plan,
             soul,
+            &instructions,
             ranked_skills,
             recall,
             tools,
             skill_registry,
             conversation_history,
+            repo_map,
         );
-        let system_tokens = estimate_tokens(&system);
+        let cache_prefix_len = layout.stable.len();
+        let system = layout.into_string();
+        let system_tokens = self.counter.count(&system);
 
         match cycles.len() {
             // First iteration: system prompt only, no conversation messages
@@ -94,6 +136,7 @@ impl TokenOptimizer {
                 system,
                 messages: vec![],
                 token_estimate: system_tokens,
+                cache_prefix_len,
             },
             // Subsequent iterations: system prompt + DELTA feedback only
             _ => {
@@ -109,12 +152,16 @@ impl TokenOptimizer {
                     vec![]
                 };
 
-                let msg_tokens: u32 = messages.iter().map(|m| estimate_tokens(&m.content)).sum();
+                let msg_tokens: u32 = messages
+                    .iter()
+                    .map(|m| self.counter.count(&m.content))
+                    .sum();
 
                 ExecutionContext {
                     system,
                     messages,
                     token_estimate: system_tokens + msg_tokens,
+                    cache_prefix_len,
                 }
             }
         }
@@ -139,17 +186,88 @@ impl TokenOptimizer {
         context_window: u32,
         conversation_history: Option<&str>,
     ) -> (ExecutionContext, bool) {
-        let mut ctx = self.build_context_with_history(
+        let ctx = self.build_context_with_history(
             task,
             plan,
             cycles,
             soul,
+            &ProjectInstructions::default(),
             ranked_skills,
             recall,
             tools,
             skill_registry,
             conversation_history,
+            "",
         );
+        self.fit_to_window(ctx, context_window, None, self.count_tools(tools))
+    }
+
+    /// Tokens the tool definitions add to a request. They travel beside the
+    /// system prompt, so `token_estimate` leaves them out.
+    pub fn count_tools(&self, tools: &[ToolDef]) -> u32 {
+        tools
+            .iter()
+            .map(|t| {
+                self.counter
+                    .count(&serde_json::to_string(t).unwrap_or_default())
+            })
+            .sum()
+    }
+
+    /// Render project instructions for `task` and the files it has
+    /// `touched` within `budget` tokens, counted with this optimizer's
+    /// tokenizer. Empty when there are none.
+    pub fn fit_instructions(
+        &self,
+        instructions: &ProjectInstructions,
+        task: &TaskInput,
+        touched: &[String],
+        budget: u32,
+    ) -> String {
+        instructions.render(task, touched, self.counter.as_ref(), budget)
+    }
+
+    /// Render the repository map for `query` within `budget` tokens, counted
+    /// with this optimizer's tokenizer. Empty when the index has nothing or
+    /// can't be read.
+    pub fn fit_repo_map(&self, index: &CodeIndex, query: &str, budget: u32) -> String {
+        index
+            .repo_map(query, self.counter.as_ref(), budget)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Repository map unavailable: {}", e);
+                String::new()
+            })
+    }
+
+    /// Whether `ctx` is close enough to the window that an approximate local
+    /// count should be confirmed with the provider before pruning.
+    pub fn needs_exact_count(
+        &self,
+        ctx: &ExecutionContext,
+        tool_tokens: u32,
+        context_window: u32,
+    ) -> bool {
+        let limit = context_window.saturating_sub(CONTEXT_BUFFER_TOKENS);
+        !self.counter.is_exact()
+            && (ctx.token_estimate + tool_tokens) as f32 >= limit as f32 * EXACT_COUNT_THRESHOLD
+    }
+
+    /// Prune a built context so it fits `context_window`.
+    ///
+    /// `measured` is an exact prompt size from the provider's token-counting
+    /// endpoint; when present it replaces the local count for the fit check.
+    /// `tool_tokens` ([`count_tools`](Self::count_tools)) is reserved for the
+    /// tool definitions, which `measured` already includes. The returned
+    /// `token_estimate` covers the whole request, tools included.
+    /// Returns the context and whether pruning was applied.
+    pub fn fit_to_window(
+        &self,
+        mut ctx: ExecutionContext,
+        context_window: u32,
+        measured: Option<u32>,
+        tool_tokens: u32,
+    ) -> (ExecutionContext, bool) {
+        ctx.token_estimate = measured.unwrap_or(ctx.token_estimate + tool_tokens);
 
         let limit = context_window.saturating_sub(CONTEXT_BUFFER_TOKENS);
         if ctx.token_estimate <= limit {
@@ -158,22 +276,29 @@ impl TokenOptimizer {
 
         // Prune messages to fit within the context window
         tracing::warn!(
-            "Context exceeds limit: {} tokens estimated, {} limit ({}K window - {}K buffer). Pruning.",
+            "Context exceeds limit: {} tokens ({}), {} limit ({}K window - {}K buffer). Pruning.",
             ctx.token_estimate,
+            if measured.is_some() {
+                "provider count"
+            } else {
+                self.counter.name()
+            },
             limit,
             context_window / 1000,
             CONTEXT_BUFFER_TOKENS / 1000,
         );
 
-        ctx.messages = prune_messages(
+        let fixed_tokens = self.counter.count(&ctx.system) + tool_tokens;
+        ctx.messages = prune_messages_with(
             ctx.messages,
-            limit.saturating_sub(estimate_tokens(&ctx.system)),
+            limit.saturating_sub(fixed_tokens),
+            self.counter.as_ref(),
         );
-        ctx.token_estimate = estimate_tokens(&ctx.system)
+        ctx.token_estimate = fixed_tokens
             + ctx
                 .messages
                 .iter()
-                .map(|m| estimate_tokens(&m.content))
+                .map(|m| self.counter.count(&m.content))
                 .sum::<u32>();
 
         (ctx, true)
@@ -254,11 +379,20 @@ impl TokenOptimizer {
 /// 3. For older tool results, replace content with `PRUNED_PLACEHOLDER`.
 /// 4. If still over budget, truncate the oldest messages entirely.
 pub fn prune_messages(messages: Vec<Message>, token_budget: u32) -> Vec<Message> {
+    prune_messages_with(messages, token_budget, &Heuristic)
+}
+
+/// [`prune_messages`] with token counts from a model-specific counter.
+pub fn prune_messages_with(
+    messages: Vec<Message>,
+    token_budget: u32,
+    counter: &dyn TokenCounter,
+) -> Vec<Message> {
     if messages.is_empty() {
         return messages;
     }
 
-    let total_tokens: u32 = messages.iter().map(|m| estimate_tokens(&m.content)).sum();
+    let total_tokens: u32 = messages.iter().map(|m| counter.count(&m.content)).sum();
     if total_tokens <= token_budget {
         return messages;
     }
@@ -272,7 +406,7 @@ pub fn prune_messages(messages: Vec<Message>, token_budget: u32) -> Vec<Message>
     for i in (0..messages.len()).rev() {
         tool_tokens_after[i] = cumulative;
         if messages[i].role == Role::Tool {
-            cumulative += estimate_tokens(&messages[i].content);
+            cumulative += counter.count(&messages[i].content);
         }
     }
 
@@ -282,12 +416,12 @@ pub fn prune_messages(messages: Vec<Message>, token_budget: u32) -> Vec<Message>
         if msg.role == Role::Tool {
             // How many tool result tokens are AFTER this message (newer)?
             let newer_tool_tokens = tool_tokens_after[i];
-            let this_tokens = estimate_tokens(&msg.content);
+            let this_tokens = counter.count(&msg.content);
 
             // If there are enough newer tool tokens to fill the protect threshold,
             // this message is old enough to prune.
             if newer_tool_tokens >= PROTECT_RECENT_TOKENS
-                && this_tokens > estimate_tokens(PRUNED_PLACEHOLDER)
+                && this_tokens > counter.count(PRUNED_PLACEHOLDER)
             {
                 pruned.push(Message {
                     role: msg.role,
@@ -302,7 +436,7 @@ pub fn prune_messages(messages: Vec<Message>, token_budget: u32) -> Vec<Message>
     }
 
     // Check if pruning was enough
-    let new_total: u32 = pruned.iter().map(|m| estimate_tokens(&m.content)).sum();
+    let new_total: u32 = pruned.iter().map(|m| counter.count(&m.content)).sum();
     if new_total <= token_budget {
         return pruned;
     }
@@ -315,7 +449,7 @@ pub fn prune_messages(messages: Vec<Message>, token_budget: u32) -> Vec<Message>
         if dropped >= overshoot {
             break;
         }
-        dropped += estimate_tokens(&msg.content);
+        dropped += counter.count(&msg.content);
         start_idx = i + 1;
     }
 
@@ -343,6 +477,8 @@ pub fn check_context_fit(token_estimate: u32, context_window: u32) -> u32 {
 }
 
 /// Rough token estimate (4 chars ~= 1 token).
+/// Model-aware counting lives in [`super::tokenizer`]; this remains the
+/// fallback when the model is unknown.
 /// Uses character count instead of byte length to avoid overestimating
 /// for multi-byte characters (CJK, emoji, etc.).
 pub fn estimate_tokens(text: &str) -> u32 {
@@ -382,6 +518,7 @@ mod tests {
             usage: crate::provider::TokenUsage::default(),
             tool_calls_made: 0,
             files_modified: vec![],
+            tool_calls: vec![],
         });
         let compressed = optimizer.compress_output(&output);
         assert_eq!(compressed, "short output");
@@ -396,6 +533,7 @@ mod tests {
             usage: crate::provider::TokenUsage::default(),
             tool_calls_made: 0,
             files_modified: vec![],
+            tool_calls: vec![],
         });
         let compressed = optimizer.compress_output(&output);
         assert!(compressed.len() < 2100);
@@ -418,6 +556,7 @@ mod tests {
             usage: crate::provider::TokenUsage::default(),
             tool_calls_made: 0,
             files_modified: vec![],
+            tool_calls: vec![],
         });
         let compressed = optimizer.compress_output(&output);
         assert_eq!(compressed, "y".repeat(2000)); // not truncated at exactly 2000
@@ -657,4 +796,99 @@ mod tests {
         // Exactly at limit (window - buffer)
         assert_eq!(check_context_fit(180_000, 200_000), 0);
     }
+
+    #[test]
+    fn test_build_context_cache_prefix_stable_across_iterations() {
+        use crate::soul::loader::SoulSource;
+
+        let optimizer = TokenOptimizer::new();
+        let task = TaskInput::new("Write a parser");
+        let soul = Soul {
+            raw: "soul".into(),
+            source: SoulSource::Default,
+        };
+        let plan = Plan {
+            steps: vec![PlanStep {
+                description: "Write it".into(),
+                tools_needed: vec![],
+            }],
+            estimated_iterations: 2,
+            estimated_tokens: 1000,
+        };
+        let registry = SkillRegistry::empty();
+        let recall = HistoryRecall::default();
+
+        let first = optimizer.build_context(&task, &plan, &[], &soul, &[], &recall, &[], &registry);
+
+        let mut refined = plan.clone();
+        refined.steps.push(PlanStep {
+            description: "Fix: edge case".into(),
+            tools_needed: vec![],
+        });
+        let second =
+            optimizer.build_context(&task, &refined, &[], &soul, &[], &recall, &[], &registry);
+
+        assert!(first.cache_prefix_len > 0);
+        assert_eq!(first.cache_prefix_len, second.cache_prefix_len);
+        assert_eq!(
+            first.system[..first.cache_prefix_len],
+            second.system[..second.cache_prefix_len]
+        );
+        assert_ne!(first.system, second.system);
+    }
+
+    fn context_with_estimate(tokens: u32) -> ExecutionContext {
+        ExecutionContext {
+            system: "system".into(),
+            messages: vec![Message::user("hello")],
+            token_estimate: tokens,
+            cache_prefix_len: 0,
+        }
+    }
+
+    #[test]
+    fn test_fit_to_window_measured_count_avoids_pruning() {
+        let optimizer = TokenOptimizer::new();
+        // Local estimate says over the limit, the provider says it fits.
+        let ctx = context_with_estimate(190_000);
+        let (ctx, pruned) = optimizer.fit_to_window(ctx, 200_000, Some(150_000), 20_000);
+        assert!(!pruned);
+        assert_eq!(ctx.token_estimate, 150_000);
+        assert_eq!(ctx.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_needs_exact_count_only_near_limit_and_approximate() {
+        let approx = TokenOptimizer::new();
+        assert!(!approx.needs_exact_count(&context_with_estimate(10_000), 0, 200_000));
+        assert!(approx.needs_exact_count(&context_with_estimate(170_000), 0, 200_000));
+        assert!(approx.needs_exact_count(&context_with_estimate(10_000), 160_000, 200_000));
+
+        let exact =
+            TokenOptimizer::new().with_counter(Arc::new(crate::core::tokenizer::Bpe::o200k()));
+        assert!(!exact.needs_exact_count(&context_with_estimate(170_000), 0, 200_000));
+    }
+
+    #[test]
+    fn test_fit_to_window_reserves_tool_tokens() {
+        let optimizer = TokenOptimizer::new();
+        let ctx = ExecutionContext {
+            system: "system".into(),
+            messages: (0..50)
+                .map(|i| Message::user(format!("message {i} ").repeat(400)))
+                .collect(),
+            token_estimate: 0,
+            cache_prefix_len: 0,
+        };
+        let window = CONTEXT_BUFFER_TOKENS + 20_000;
+        let (ctx, pruned) = optimizer.fit_to_window(ctx, window, Some(200_000), 15_000);
+        assert!(pruned);
+        assert!(ctx.token_estimate <= 20_000);
+        let messages: u32 = ctx
+            .messages
+            .iter()
+            .map(|m| optimizer.counter().count(&m.content))
+            .sum();
+        assert!(messages <= 5_000);
+    }
 }
@@ -0,0 +1,323 @@
+// src/core/tokenizer.rs — Model-aware token counting
+
+use std::path::Path;
+use std::sync::Arc;
+
+use tiktoken_rs::CoreBPE;
+
+use super::token_optimizer::estimate_tokens;
+use crate::infra::paths;
+use crate::provider::{ModelInfo, ModelProvider};
+
+/// Counts tokens the way a particular model family's tokenizer does.
+pub trait TokenCounter: Send + Sync {
+    /// Short identifier for logs (e.g. "o200k_base", "approx:claude").
+    fn name(&self) -> &str;
+
+    fn count(&self, text: &str) -> u32;
+
+    /// True when counts match the model's own tokenizer. Approximate counters
+    /// make callers fall back to provider-side counting near the context limit.
+    fn is_exact(&self) -> bool;
+}
+
+/// The original chars/4 heuristic. Used when the model is unknown.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Heuristic;
+
+impl TokenCounter for Heuristic {
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+
+    fn count(&self, text: &str) -> u32 {
+        estimate_tokens(text)
+    }
+
+    fn is_exact(&self) -> bool {
+        false
+    }
+}
+
+/// tiktoken BPE tables (OpenAI families; also a close stand-in for Llama 3).
+pub struct Bpe {
+    name: &'static str,
+    bpe: &'static CoreBPE,
+    exact: bool,
+}
+
+impl Bpe {
+    pub fn o200k() -> Self {
+        Self {
+            name: "o200k_base",
+            bpe: tiktoken_rs::o200k_base_singleton(),
+            exact: true,
+        }
+    }
+
+    pub fn cl100k() -> Self {
+        Self {
+            name: "cl100k_base",
+            bpe: tiktoken_rs::cl100k_base_singleton(),
+            exact: true,
+        }
+    }
+
+    /// Use a table for a model it was not built for; counts are close but
+    /// not exact.
+    fn approximate(mut self) -> Self {
+        self.exact = false;
+        self
+    }
+}
+
+impl TokenCounter for Bpe {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn count(&self, text: &str) -> u32 {
+        self.bpe.encode_with_special_tokens(text).len() as u32
+    }
+
+    fn is_exact(&self) -> bool {
+        self.exact
+    }
+}
+
+/// A HuggingFace `tokenizer.json` (SentencePiece models such as Gemma and
+/// Llama), loaded from the tokenizers data directory.
+pub struct SentencePiece {
+    name: String,
+    tokenizer: tokenizers::Tokenizer,
+}
+
+impl SentencePiece {
+    pub fn from_file(path: &Path) -> Option<Self> {
+        match tokenizers::Tokenizer::from_file(path) {
+            Ok(tokenizer) => Some(Self {
+                name: format!(
+                    "sentencepiece:{}",
+                    path.file_stem().and_then(|s| s.to_str()).unwrap_or("?")
+                ),
+                tokenizer,
+            }),
+            Err(e) => {
+                tracing::warn!("Failed to load tokenizer {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+impl TokenCounter for SentencePiece {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn count(&self, text: &str) -> u32 {
+        match self.tokenizer.encode(text, false) {
+            Ok(encoding) => encoding.len() as u32,
+            Err(_) => estimate_tokens(text),
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+/// Character-ratio estimate calibrated per family. Non-ASCII characters are
+/// counted separately because they rarely merge into multi-char tokens.
+pub struct Approx {
+    name: &'static str,
+    ascii_chars_per_token: f32,
+    tokens_per_non_ascii_char: f32,
+}
+
+impl Approx {
+    /// Claude's tokenizer is denser than cl100k on English and code.
+    pub fn claude() -> Self {
+        Self {
+            name: "approx:claude",
+            ascii_chars_per_token: 3.5,
+            tokens_per_non_ascii_char: 1.0,
+        }
+    }
+
+    /// Gemini's 256K vocabulary packs non-Latin scripts well.
+    pub fn gemini() -> Self {
+        Self {
+            name: "approx:gemini",
+            ascii_chars_per_token: 4.0,
+            tokens_per_non_ascii_char: 0.7,
+        }
+    }
+
+    pub fn generic() -> Self {
+        Self {
+            name: "approx:generic",
+            ascii_chars_per_token: 3.8,
+            tokens_per_non_ascii_char: 1.0,
+        }
+    }
+}
+
+impl TokenCounter for Approx {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn count(&self, text: &str) -> u32 {
+        let (ascii, other) = text.chars().fold((0u32, 0u32), |(a, o), c| {
+            if c.is_ascii() {
+                (a + 1, o)
+            } else {
+                (a, o + 1)
+            }
+        });
+        (ascii as f32 / self.ascii_chars_per_token + other as f32 * self.tokens_per_non_ascii_char)
+            .ceil() as u32
+    }
+
+    fn is_exact(&self) -> bool {
+        false
+    }
+}
+
+/// Pick a counter for a model. `family` comes from `ModelInfo.family`; when
+/// it is absent the model id is matched instead (Ollama ids like "llama3.1:8b").
+pub fn for_family(family: Option<&str>, model_id: &str) -> Arc<dyn TokenCounter> {
+    for_family_in(family, model_id, &paths::tokenizers_dir())
+}
+
+/// Pick a counter from a provider's model catalog, or the heuristic if the
+/// model is not listed.
+pub fn for_model(info: Option<&ModelInfo>) -> Arc<dyn TokenCounter> {
+    match info {
+        Some(m) => for_family(m.family.as_deref(), &m.id),
+        None => Arc::new(Heuristic),
+    }
+}
+
+/// Convenience for call sites that only hold a provider and a model id.
+pub fn for_provider_model(provider: &dyn ModelProvider, model_id: &str) -> Arc<dyn TokenCounter> {
+    let models = provider.models();
+    match models.iter().find(|m| m.id == model_id) {
+        Some(m) => for_model(Some(m)),
+        None => for_family(None, model_id),
+    }
+}
+
+fn for_family_in(
+    family: Option<&str>,
+    model_id: &str,
+    tokenizers_dir: &Path,
+) -> Arc<dyn TokenCounter> {
+    let key = family.unwrap_or(model_id).to_lowercase();
+    // Strip provider prefixes (e.g. "openai/gpt-4.1" from OpenRouter).
+    let key = key.rsplit('/').next().unwrap_or(&key);
+
+    let local = |name: &str| -> Option<Arc<dyn TokenCounter>> {
+        let path = tokenizers_dir.join(format!("{}.json", name));
+        if path.exists() {
+            SentencePiece::from_file(&path).map(|t| Arc::new(t) as Arc<dyn TokenCounter>)
+        } else {
+            None
+        }
+    };
+
+    if key.starts_with("gpt-4o")
+        || key.starts_with("gpt-4.1")
+        || key.starts_with("gpt-5")
+        || key.starts_with("o1")
+        || key.starts_with("o3")
+        || key.starts_with("o4")
+        || key.starts_with("codex")
+    {
+        Arc::new(Bpe::o200k())
+    } else if key.starts_with("gpt-4") || key.starts_with("gpt-3.5") {
+        Arc::new(Bpe::cl100k())
+    } else if key.starts_with("claude") {
+        Arc::new(Approx::claude())
+    } else if key.starts_with("gemma") {
+        local("gemma").unwrap_or_else(|| Arc::new(Approx::gemini()))
+    } else if key.starts_with("gemini") {
+        // Gemma shares Gemini's vocabulary.
+        local("gemma").unwrap_or_else(|| Arc::new(Approx::gemini()))
+    } else if key.starts_with("llama") {
+        local("llama").unwrap_or_else(|| Arc::new(Bpe::cl100k().approximate()))
+    } else if let Some(counter) = key.split(['-', ':', '.']).next().and_then(local) {
+        counter
+    } else {
+        Arc::new(Approx::generic())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(family: Option<&str>, id: &str) -> Arc<dyn TokenCounter> {
+        for_family_in(family, id, Path::new("/nonexistent"))
+    }
+
+    #[test]
+    fn test_openai_families_use_bpe() {
+        assert_eq!(select(Some("gpt-4.1"), "gpt-4.1").name(), "o200k_base");
+        assert_eq!(select(Some("o3"), "o3").name(), "o200k_base");
+        assert_eq!(
+            select(Some("gpt-5.1-codex"), "gpt-5.1-codex").name(),
+            "o200k_base"
+        );
+        assert_eq!(select(None, "gpt-4-turbo").name(), "cl100k_base");
+        assert_eq!(select(None, "openai/gpt-4o-mini").name(), "o200k_base");
+    }
+
+    #[test]
+    fn test_other_families() {
+        assert_eq!(
+            select(Some("claude-sonnet"), "claude-sonnet-4").name(),
+            "approx:claude"
+        );
+        assert_eq!(
+            select(Some("gemini-2.5"), "gemini-2.5-pro").name(),
+            "approx:gemini"
+        );
+        assert_eq!(select(None, "mistral-large").name(), "approx:generic");
+    }
+
+    #[test]
+    fn test_llama_without_local_tokenizer_is_approximate_bpe() {
+        let counter = select(None, "llama3.1:8b");
+        assert_eq!(counter.name(), "cl100k_base");
+        assert!(!counter.is_exact());
+    }
+
+    #[test]
+    fn test_bpe_counts_exactly() {
+        let counter = Bpe::o200k();
+        assert_eq!(counter.count(""), 0);
+        assert_eq!(counter.count("hello world"), 2);
+        assert!(counter.is_exact());
+    }
+
+    #[test]
+    fn test_approx_counts_non_ascii_separately() {
+        let counter = Approx::claude();
+        assert_eq!(counter.count("abcdefg"), 2); // 7 / 3.5
+        assert_eq!(counter.count("日本語"), 3);
+        assert!(!counter.is_exact());
+    }
+
+    #[test]
+    fn test_heuristic_matches_estimate_tokens() {
+        let text = "The quick brown fox";
+        assert_eq!(Heuristic.count(text), estimate_tokens(text));
+    }
+
+    #[test]
+    fn test_for_model_unknown_is_heuristic() {
+        assert_eq!(for_model(None).name(), "heuristic");
+    }
+}
@@ -4,6 +4,7 @@
 // Full output is saved to disk for reference.
 
 use crate::infra::paths;
+use crate::security::redact;
 use std::path::PathBuf;
 
 /// Maximum lines in a tool output before truncation.
@@ -31,8 +32,9 @@ pub struct TruncationResult {
 
 /// Truncate tool output if it exceeds limits.
 ///
-/// When truncated, the full output is saved to `~/.openkoi/tool-output/{hash}.txt`
-/// and the returned content includes a note about the truncation.
+/// When truncated, the full output is saved (with secrets redacted) to
+/// `~/.openkoi/tool-output/{hash}.txt` and the returned content includes a
+/// note about the truncation.
 pub fn truncate_tool_output(content: &str) -> TruncationResult {
     let original_bytes = content.len();
     let original_lines = content.lines().count();
@@ -102,7 +104,7 @@ fn save_full_output(hash: &str, content: &str) -> Option<PathBuf> {
         return None;
     }
     let path = dir.join(format!("{}.txt", hash));
-    if std::fs::write(&path, content).is_ok() {
+    if std::fs::write(&path, redact::redact(content)).is_ok() {
         Some(path)
     } else {
         None
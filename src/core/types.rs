@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+use super::escalation::Escalation;
 use crate::provider::TokenUsage;
 
 /// A single iteration cycle within a task's execution.
@@ -21,6 +22,12 @@ pub struct IterationCycle {
     pub skills_used: Vec<String>,
     pub category: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Executor model switch made during this cycle, if any.
+    #[serde(default)]
+    pub escalation: Option<Escalation>,
+    /// Spend on this cycle (execution and evaluation), in USD.
+    #[serde(default)]
+    pub cost: f64,
 }
 
 impl IterationCycle {
@@ -38,6 +45,8 @@ impl IterationCycle {
             skills_used: Vec::new(),
             category: task.category.clone(),
             created_at: Utc::now(),
+            escalation: None,
+            cost: 0.0,
         }
     }
 
@@ -71,6 +80,7 @@ pub enum Phase {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
 pub enum IterationDecision {
     Continue,
     Accept,
@@ -80,6 +90,8 @@ pub enum IterationDecision {
     AbortBudget,
     AbortTimeout,
     AbortRegression,
+    /// The executor's provider call failed.
+    AbortError,
 }
 
 impl std::fmt::Display for IterationDecision {
@@ -93,6 +105,7 @@ impl std::fmt::Display for IterationDecision {
             IterationDecision::AbortBudget => write!(f, "abort_budget"),
             IterationDecision::AbortTimeout => write!(f, "abort_timeout"),
             IterationDecision::AbortRegression => write!(f, "abort_regression"),
+            IterationDecision::AbortError => write!(f, "abort_error"),
         }
     }
 }
@@ -126,6 +139,21 @@ pub struct ExecutionOutput {
     pub usage: TokenUsage,
     pub tool_calls_made: u32,
     pub files_modified: Vec<String>,
+    /// Per-call timings, in the order the model issued the calls.
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCallRecord>,
+}
+
+/// Timing for one dispatched tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub name: String,
+    pub duration_ms: u64,
+    /// Ran concurrently with other read-only calls from the same turn.
+    pub parallel: bool,
+    /// Refused by the permission policy or the dry-run overlay, or failed.
+    #[serde(default)]
+    pub failed: bool,
 }
 
 /// Result of evaluating an output.
@@ -177,20 +205,67 @@ impl std::fmt::Display for Severity {
 }
 
 /// Final result returned to the user.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TaskResult {
+    pub task_id: String,
     pub output: ExecutionOutput,
     pub iterations: u8,
     pub total_tokens: u32,
+    /// Token usage across every model call in the run.
+    pub usage: TokenUsage,
     pub cost: f64,
     pub learnings_saved: u32,
     pub skills_used: Vec<String>,
     pub final_score: f64,
+    /// Decision of the last iteration (why the loop stopped).
+    pub decision: IterationDecision,
+    /// Every iteration, in order.
+    pub history: Vec<IterationSummary>,
+    /// Error that ended the run early, if the executor failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One iteration as reported in a `TaskResult`.
+#[derive(Debug, Clone, Serialize)]
+pub struct IterationSummary {
+    /// 1-based, as shown in progress output.
+    pub iteration: u8,
+    /// `None` when the iteration was not evaluated.
+    pub score: Option<f32>,
+    pub decision: IterationDecision,
+    pub dimensions: Vec<DimensionScore>,
+    pub findings: Vec<Finding>,
+    pub files_modified: Vec<String>,
+    pub tokens: u32,
+    pub cost: f64,
+}
+
+impl From<&IterationCycle> for IterationSummary {
+    fn from(cycle: &IterationCycle) -> Self {
+        let eval = cycle.evaluation.as_ref();
+        let eval_tokens = eval.map(|e| e.usage.total()).unwrap_or(0);
+        Self {
+            iteration: cycle.iteration + 1,
+            score: eval.map(|e| e.score),
+            decision: cycle.decision.clone(),
+            dimensions: eval.map(|e| e.dimensions.clone()).unwrap_or_default(),
+            findings: eval.map(|e| e.findings.clone()).unwrap_or_default(),
+            files_modified: cycle
+                .output
+                .as_ref()
+                .map(|o| o.files_modified.clone())
+                .unwrap_or_default(),
+            tokens: cycle.usage.total() + eval_tokens,
+            cost: cycle.cost,
+        }
+    }
 }
 
 /// Real-time progress events emitted by the orchestrator during task execution.
 /// These are consumed by the CLI progress renderer (or any callback).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
 pub enum ProgressEvent {
     /// The initial plan has been built.
     PlanReady {
@@ -201,6 +276,21 @@ pub enum ProgressEvent {
     IterationStart { iteration: u8, max_iterations: u8 },
     /// A tool call was made during execution.
     ToolCall { name: String, iteration: u8 },
+    /// The tool registry is dispatching a call (only emitted when a sink is
+    /// installed with `ToolRegistry::set_progress`).
+    ToolStarted {
+        call_id: String,
+        name: String,
+        args: serde_json::Value,
+    },
+    /// A call from `ToolStarted` returned; `failed` covers errors and
+    /// permission refusals.
+    ToolFinished {
+        call_id: String,
+        name: String,
+        output: String,
+        failed: bool,
+    },
     /// An iteration has completed with evaluation results.
     IterationEnd {
         iteration: u8,
@@ -210,6 +300,13 @@ pub enum ProgressEvent {
     },
     /// The safety checker raised a warning or abort.
     SafetyWarning { message: String },
+    /// The executor switched to a different model.
+    Escalated {
+        iteration: u8,
+        from: String,
+        to: String,
+        reason: String,
+    },
     /// The task has completed (final result summary).
     Complete {
         iterations: u8,
@@ -219,6 +316,37 @@ pub enum ProgressEvent {
     },
 }
 
+impl ProgressEvent {
+    /// One-line description of iteration-level progress, for remote
+    /// clients that show it as status text. Plan, tool and completion
+    /// events are reported in their own ways, so they give `None`.
+    pub fn summary(&self) -> Option<String> {
+        match self {
+            Self::IterationStart {
+                iteration,
+                max_iterations,
+            } => Some(format!("Iteration {iteration}/{max_iterations}")),
+            Self::IterationEnd {
+                iteration,
+                score,
+                decision,
+                cost_so_far,
+            } => Some(format!(
+                "Iteration {iteration} scored {score:.2} -> {decision} (${cost_so_far:.2})"
+            )),
+            Self::SafetyWarning { message } => Some(format!("Safety: {message}")),
+            Self::Escalated {
+                from, to, reason, ..
+            } => Some(format!("Switched model {from} -> {to} ({reason})")),
+            Self::PlanReady { .. }
+            | Self::ToolCall { .. }
+            | Self::ToolStarted { .. }
+            | Self::ToolFinished { .. }
+            | Self::Complete { .. } => None,
+        }
+    }
+}
+
 /// A plan for executing a task.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Plan {
@@ -239,6 +367,9 @@ pub struct ExecutionContext {
     pub system: String,
     pub messages: Vec<crate::provider::Message>,
     pub token_estimate: u32,
+    /// Byte length of the stable (cacheable) prefix of `system`.
+    /// Identical across iterations of a task; 0 if unknown.
+    pub cache_prefix_len: usize,
 }
 
 /// Configuration for the iteration engine.
@@ -505,6 +636,7 @@ mod tests {
             },
             tool_calls_made: 2,
             files_modified: vec!["a.rs".into(), "b.rs".into()],
+            tool_calls: vec![],
         };
         let cloned = out.clone();
         assert_eq!(cloned.content, "Hello");
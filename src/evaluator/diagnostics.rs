@@ -0,0 +1,135 @@
+// src/evaluator/diagnostics.rs — Built-in language-server diagnostics
+//
+// Collects the errors and warnings language servers publish for the files
+// an iteration touched. Cheaper than a full lint or test run, so it runs
+// every iteration.
+
+use crate::core::types::*;
+use crate::lsp::protocol::{Diagnostic, DiagnosticSeverity};
+use crate::lsp::LspManager;
+
+/// Result of collecting diagnostics.
+pub struct DiagnosticsResult {
+    pub diagnostics: Vec<Diagnostic>,
+    /// Paths shown relative to the project root.
+    locations: Vec<String>,
+}
+
+impl DiagnosticsResult {
+    /// Diagnostics for `files` (as listed in `ExecutionOutput`). Returns
+    /// `None` when the output touched no files and no document is open.
+    pub async fn collect(lsp: &LspManager, files: &[String]) -> Option<Self> {
+        let diagnostics = lsp.diagnostics(files).await;
+        if diagnostics.is_empty() && files.is_empty() {
+            return None;
+        }
+        let locations = diagnostics
+            .iter()
+            .map(|d| format!("{}:{}:{}", lsp.relative(&d.path), d.line, d.column))
+            .collect();
+        Some(Self {
+            diagnostics,
+            locations,
+        })
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == DiagnosticSeverity::Error)
+            .count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    pub fn to_dimension_score(&self) -> DimensionScore {
+        let errors = self.error_count();
+        let warnings = self.diagnostics.len() - errors;
+
+        // Errors are worth 0.15 deduction each, warnings 0.03
+        let deduction = (errors as f32 * 0.15 + warnings as f32 * 0.03).min(0.8);
+        DimensionScore {
+            dimension: "diagnostics".into(),
+            score: (1.0 - deduction).max(0.2),
+            weight: 0.15,
+        }
+    }
+
+    pub fn as_findings(&self) -> Vec<Finding> {
+        self.diagnostics
+            .iter()
+            .zip(&self.locations)
+            .enumerate()
+            .map(|(i, (d, location))| {
+                let title = match d.source {
+                    Some(ref source) => format!("{source}: {}", first_line(&d.message)),
+                    None => first_line(&d.message).to_string(),
+                };
+                Finding {
+                    id: format!("D{}", i + 1),
+                    severity: match d.severity {
+                        DiagnosticSeverity::Error => Severity::Important,
+                        _ => Severity::Suggestion,
+                    },
+                    dimension: "diagnostics".into(),
+                    title,
+                    description: d.message.clone(),
+                    location: Some(location.clone()),
+                    fix: None,
+                }
+            })
+            .collect()
+    }
+}
+
+fn first_line(message: &str) -> &str {
+    message.lines().next().unwrap_or(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn diagnostic(severity: DiagnosticSeverity, message: &str) -> Diagnostic {
+        Diagnostic {
+            path: PathBuf::from("/p/src/lib.rs"),
+            line: 3,
+            column: 5,
+            severity,
+            message: message.into(),
+            source: Some("rustc".into()),
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_score_and_findings() {
+        let clean = DiagnosticsResult {
+            diagnostics: vec![],
+            locations: vec![],
+        };
+        assert_eq!(clean.to_dimension_score().score, 1.0);
+        assert!(!clean.has_errors());
+
+        let result = DiagnosticsResult {
+            diagnostics: vec![
+                diagnostic(DiagnosticSeverity::Error, "mismatched types\nexpected u32"),
+                diagnostic(DiagnosticSeverity::Warning, "unused variable"),
+            ],
+            locations: vec!["src/lib.rs:3:5".into(), "src/lib.rs:3:5".into()],
+        };
+        let dim = result.to_dimension_score();
+        assert_eq!(dim.dimension, "diagnostics");
+        assert!((dim.score - 0.82).abs() < 1e-6);
+        assert!(result.has_errors());
+
+        let findings = result.as_findings();
+        assert_eq!(findings[0].id, "D1");
+        assert_eq!(findings[0].severity, Severity::Important);
+        assert_eq!(findings[0].title, "rustc: mismatched types");
+        assert_eq!(findings[0].location.as_deref(), Some("src/lib.rs:3:5"));
+        assert_eq!(findings[1].severity, Severity::Suggestion);
+    }
+}
@@ -1,5 +1,6 @@
 // src/evaluator/mod.rs — Evaluator framework
 
+pub mod diagnostics;
 pub mod parser;
 pub mod static_analysis;
 pub mod test_runner;
@@ -8,6 +9,8 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::core::types::*;
+use crate::lsp::LspManager;
+use crate::provider::structured::{chat_structured, StructuredError};
 use crate::provider::{ChatRequest, Message, ModelProvider, TokenUsage};
 use crate::skills::registry::SkillRegistry;
 use crate::skills::types::{DimensionDef, SkillEntry, SkillKind};
@@ -28,6 +31,17 @@ pub struct EvaluatorFramework {
     /// Optional score calibrator for normalizing scores across evaluator types.
     /// When present, all evaluation scores are calibrated before returning.
     calibrator: Option<ScoreCalibrator>,
+    /// Rendered project instructions (AGENTS.md etc.). LLM judges score
+    /// adherence to them. Empty when the project has none.
+    conventions: String,
+    /// Language servers for the project. When present, their diagnostics
+    /// for the files an iteration touched become a scored dimension.
+    lsp: Option<Arc<LspManager>>,
+    /// Evaluator skill to use regardless of task category.
+    evaluator_skill: Option<String>,
+    /// Run tests, static analysis and diagnostics. Off when the project
+    /// directory doesn't hold the code being judged.
+    builtin_checks: bool,
 }
 
 impl EvaluatorFramework {
@@ -44,6 +58,10 @@ impl EvaluatorFramework {
             static_analyzer: static_analysis::StaticAnalyzer::new(),
             project_dir: PathBuf::from("."),
             calibrator: None,
+            conventions: String::new(),
+            lsp: None,
+            evaluator_skill: None,
+            builtin_checks: true,
         }
     }
 
@@ -54,6 +72,31 @@ impl EvaluatorFramework {
         self
     }
 
+    /// Always judge with the evaluator skill `name` (falls back to category
+    /// matching if no such evaluator is installed).
+    pub fn with_evaluator_skill(mut self, name: impl Into<String>) -> Self {
+        self.evaluator_skill = Some(name.into());
+        self
+    }
+
+    /// Enable or disable the built-in checks (tests, static analysis,
+    /// language-server diagnostics). Enabled by default.
+    pub fn with_builtin_checks(mut self, enabled: bool) -> Self {
+        self.builtin_checks = enabled;
+        self
+    }
+
+    /// Set the project instructions the output should follow (rendered by
+    /// `TokenOptimizer::fit_instructions`).
+    pub fn set_conventions(&mut self, conventions: String) {
+        self.conventions = conventions;
+    }
+
+    /// Set the language servers whose diagnostics are scored.
+    pub fn set_lsp(&mut self, lsp: Option<Arc<LspManager>>) {
+        self.lsp = lsp;
+    }
+
     /// Enable score calibration. When enabled, dimension scores from LLM-based
     /// evaluators are normalized using rolling z-score statistics, making scores
     /// from different evaluator types (LLM, tests, lint) more comparable.
@@ -73,29 +116,34 @@ impl EvaluatorFramework {
         let mut tests_passed = true;
         let mut static_passed = true;
 
-        // 1. Built-in: run tests if available (free, no tokens)
-        if let Some(test_result) = self
-            .test_runner
-            .run_if_available(task, &self.project_dir)
-            .await?
-        {
+        // 1. Built-in: language-server diagnostics for touched files (free,
+        //    and faster than a full test run)
+        let lsp = self.lsp.as_ref().filter(|_| self.builtin_checks);
+        if let Some(lsp) = lsp {
+            if let Some(diag) =
+                diagnostics::DiagnosticsResult::collect(lsp, &output.files_modified).await
+            {
+                dimensions.push(diag.to_dimension_score());
+                findings.extend(diag.as_findings());
+                static_passed = !diag.has_errors();
+            }
+        }
+
+        // 2. Built-in: run tests if available (free, no tokens)
+        if let Some(test_result) = self.run_tests(task).await? {
             dimensions.push(test_result.to_dimension_score());
             findings.extend(test_result.failures_as_findings());
             tests_passed = test_result.all_passed;
         }
 
-        // 2. Built-in: run static analysis if applicable (free, no tokens)
-        if let Some(lint_result) = self
-            .static_analyzer
-            .run_if_applicable(task, &self.project_dir)
-            .await?
-        {
+        // 3. Built-in: run static analysis if applicable (free, no tokens)
+        if let Some(lint_result) = self.run_static_analysis(task).await? {
             dimensions.push(lint_result.to_dimension_score());
             findings.extend(lint_result.issues_as_findings());
-            static_passed = lint_result.all_clean;
+            static_passed &= lint_result.all_clean;
         }
 
-        // 3. Skill-based: select and run the best evaluator skill
+        // 4. Skill-based: select and run the best evaluator skill
         let eval_skill = self.select_evaluator_skill(task);
         let evaluator_name = eval_skill
             .as_ref()
@@ -113,6 +161,7 @@ impl EvaluatorFramework {
                 }
                 Err(e) => {
                     tracing::warn!("LLM evaluation failed: {}, using heuristic score", e);
+                    usage = e.usage;
                 }
             }
         }
@@ -188,12 +237,22 @@ impl EvaluatorFramework {
         let mut static_passed = prev_eval.static_analysis_passed;
         let mut usage = TokenUsage::default();
 
-        // Always re-run tests and lint (they're free — no tokens)
-        if let Some(test_result) = self
-            .test_runner
-            .run_if_available(task, &self.project_dir)
-            .await?
-        {
+        // Always re-run diagnostics, tests and lint (they're free — no tokens)
+        let mut diagnostics_clean = true;
+        let lsp = self.lsp.as_ref().filter(|_| self.builtin_checks);
+        if let Some(lsp) = lsp {
+            if let Some(diag) =
+                diagnostics::DiagnosticsResult::collect(lsp, &current_output.files_modified).await
+            {
+                replace_or_add_dimension(&mut dimensions, diag.to_dimension_score());
+                findings.retain(|f| f.dimension != "diagnostics");
+                findings.extend(diag.as_findings());
+                diagnostics_clean = !diag.has_errors();
+                static_passed = diagnostics_clean;
+            }
+        }
+
+        if let Some(test_result) = self.run_tests(task).await? {
             // Replace the "tests" dimension if it exists, otherwise add it
             replace_or_add_dimension(&mut dimensions, test_result.to_dimension_score());
             // Remove old test findings and add new ones
@@ -202,15 +261,11 @@ impl EvaluatorFramework {
             tests_passed = test_result.all_passed;
         }
 
-        if let Some(lint_result) = self
-            .static_analyzer
-            .run_if_applicable(task, &self.project_dir)
-            .await?
-        {
+        if let Some(lint_result) = self.run_static_analysis(task).await? {
             replace_or_add_dimension(&mut dimensions, lint_result.to_dimension_score());
             findings.retain(|f| f.dimension != "static_analysis");
             findings.extend(lint_result.issues_as_findings());
-            static_passed = lint_result.all_clean;
+            static_passed = lint_result.all_clean && diagnostics_clean;
         }
 
         // For LLM-based evaluation, only re-evaluate if there are meaningful changes
@@ -254,6 +309,7 @@ impl EvaluatorFramework {
                             "Incremental LLM evaluation failed: {}, keeping previous scores",
                             e
                         );
+                        usage = e.usage;
                     }
                 }
             }
@@ -291,7 +347,7 @@ impl EvaluatorFramework {
         current: &ExecutionOutput,
         previous: &ExecutionOutput,
         prev_eval: &Evaluation,
-    ) -> anyhow::Result<IncrementalEvalResult> {
+    ) -> Result<IncrementalEvalResult, JudgeError> {
         let skill_body = self.skill_registry.load_body(skill)?;
 
         // Build a focused prompt that shows the diff and asks for delta evaluation
@@ -313,6 +369,7 @@ impl EvaluatorFramework {
             "You are an evaluator performing an INCREMENTAL re-evaluation.\n\
              The output has been revised. Evaluate ONLY what changed.\n\n\
              ## Rubric\n{rubric}\n\n\
+             {conventions}\
              ## Task\n{task}\n\n\
              ## Previous Output (summary)\n{prev}\n\n\
              ## Current Output\n{current}\n\n\
@@ -323,17 +380,15 @@ impl EvaluatorFramework {
              2. Re-score ONLY affected dimensions\n\
              3. Mark which previous findings are now RESOLVED\n\
              4. List any NEW findings\n\n\
-             Respond in this format:\n\
-             SCORES:\n\
-             dimension_name: score\n\
-             ...\n\
-             RESOLVED:\n\
-             finding_id\n\
-             ...\n\
-             NEW_FINDINGS:\n\
-             - [SEVERITY] title: description\n\
-             SUGGESTION: brief improvement guidance",
+             Respond with a JSON object:\n\
+             {{\"scores\": [{{\"dimension\": name, \"score\": 0.0-1.0}}], \
+             \"resolved\": [finding_id, ...], \
+             \"new_findings\": [{{\"severity\": \"BLOCKER|IMPORTANT|SUGGESTION\", \
+             \"dimension\": name, \"title\": ..., \"description\": ..., \
+             \"location\": \"file:line\" or null, \"fix\": ... or null}}], \
+             \"suggestion\": brief improvement guidance}}",
             rubric = skill_body,
+            conventions = self.conventions_section(),
             task = task.description,
             prev = truncate_for_eval(&previous.content, 1000),
             current = current.content,
@@ -341,33 +396,89 @@ impl EvaluatorFramework {
             findings = prev_findings_text,
         );
 
-        let response = self
-            .provider
-            .chat(ChatRequest {
-                model: self.model_id.clone(),
-                messages: vec![Message::user(prompt)],
-                tools: vec![],
-                max_tokens: Some(2000),
-                temperature: Some(0.1),
-                system: None,
-            })
-            .await
-            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        let request = ChatRequest {
+            model: self.model_id.clone(),
+            messages: vec![Message::user(prompt)],
+            max_tokens: Some(2000),
+            temperature: Some(0.1),
+            ..Default::default()
+        };
 
-        let parsed = parse_incremental_eval_response(&response.content, &skill.metadata.dimensions);
+        let dims = &skill.metadata.dimensions;
+        let (parsed, usage) = match chat_structured::<parser::IncrementalEvalReport>(
+            &*self.provider,
+            request,
+        )
+        .await
+        {
+            Ok(resp) => (incremental_from_report(resp.value, dims), resp.usage),
+            Err(StructuredError::Invalid { reason, raw, usage }) => {
+                let report =
+                    parser::IncrementalEvalReport::salvage(&raw).map_err(|e| JudgeError {
+                        message: format!("Incremental evaluation reply unusable ({reason}): {e}"),
+                        usage: usage.clone(),
+                    })?;
+                tracing::warn!(
+                    "Structured incremental evaluation invalid ({}), using its valid parts",
+                    reason
+                );
+                (incremental_from_report(report, dims), usage)
+            }
+            Err(StructuredError::Provider(e)) => return Err(anyhow::anyhow!("{}", e).into()),
+        };
 
         Ok(IncrementalEvalResult {
             dimensions: parsed.dimensions,
             resolved_finding_ids: parsed.resolved_finding_ids,
             new_findings: parsed.new_findings,
-            usage: response.usage,
+            usage,
         })
     }
 
+    async fn run_tests(&self, task: &TaskInput) -> anyhow::Result<Option<test_runner::TestResult>> {
+        if !self.builtin_checks {
+            return Ok(None);
+        }
+        self.test_runner
+            .run_if_available(task, &self.project_dir)
+            .await
+    }
+
+    async fn run_static_analysis(
+        &self,
+        task: &TaskInput,
+    ) -> anyhow::Result<Option<static_analysis::LintResult>> {
+        if !self.builtin_checks {
+            return Ok(None);
+        }
+        self.static_analyzer
+            .run_if_applicable(task, &self.project_dir)
+            .await
+    }
+
+    /// Project instructions plus the request to grade against them.
+    fn conventions_section(&self) -> String {
+        if self.conventions.is_empty() {
+            return String::new();
+        }
+        format!(
+            "{}Score adherence to these project instructions as part of the rubric \
+             and report each violation as a finding.\n\n",
+            self.conventions
+        )
+    }
+
     /// Pick the best evaluator skill based on task category.
     fn select_evaluator_skill(&self, task: &TaskInput) -> Option<SkillEntry> {
         let evaluators = self.skill_registry.get_by_kind(SkillKind::Evaluator);
 
+        if let Some(ref name) = self.evaluator_skill {
+            if let Some(named) = evaluators.iter().find(|e| &e.name == name) {
+                return Some(named.clone());
+            }
+            tracing::warn!("No evaluator skill named '{}'; choosing by category", name);
+        }
+
         // Match by category
         if let Some(cat) = &task.category {
             if let Some(matched) = evaluators
@@ -388,49 +499,85 @@ impl EvaluatorFramework {
         skill: &SkillEntry,
         task: &TaskInput,
         output: &ExecutionOutput,
-    ) -> anyhow::Result<LlmEvalResult> {
+    ) -> Result<LlmEvalResult, JudgeError> {
         let skill_body = self.skill_registry.load_body(skill)?;
 
         let prompt = format!(
             "You are an evaluator. Use the following rubric to evaluate the output.\n\n\
              ## Rubric\n{}\n\n\
+             {}\
              ## Task\n{}\n\n\
              ## Output to evaluate\n{}\n\n\
              Score each dimension 0.0-1.0. List findings with severity.\n\
-             Respond in this format:\n\
-             SCORES:\n\
-             dimension_name: score\n\
-             ...\n\
-             FINDINGS:\n\
-             - [SEVERITY] title: description\n\
-             SUGGESTION: brief improvement guidance",
-            skill_body, task.description, output.content
+             Respond with a JSON object:\n\
+             {{\"scores\": [{{\"dimension\": name, \"score\": 0.0-1.0}}], \
+             \"findings\": [{{\"severity\": \"BLOCKER|IMPORTANT|SUGGESTION\", \
+             \"dimension\": name, \"title\": ..., \"description\": ..., \
+             \"location\": \"file:line\" or null, \"fix\": ... or null}}], \
+             \"suggestion\": brief improvement guidance}}",
+            skill_body,
+            self.conventions_section(),
+            task.description,
+            output.content
         );
 
-        let response = self
-            .provider
-            .chat(ChatRequest {
-                model: self.model_id.clone(),
-                messages: vec![Message::user(prompt)],
-                tools: vec![],
-                max_tokens: Some(2000),
-                temperature: Some(0.1),
-                system: None,
-            })
-            .await
-            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        let request = ChatRequest {
+            model: self.model_id.clone(),
+            messages: vec![Message::user(prompt)],
+            max_tokens: Some(2000),
+            temperature: Some(0.1),
+            ..Default::default()
+        };
 
-        let parsed = parser::parse_eval_response(&response.content, &skill.metadata.dimensions);
+        let dims = &skill.metadata.dimensions;
+        let (parsed, usage) =
+            match chat_structured::<parser::EvalReport>(&*self.provider, request).await {
+                Ok(resp) => (resp.value.into_parsed(dims), resp.usage),
+                Err(StructuredError::Invalid { reason, raw, usage }) => {
+                    let report = parser::EvalReport::salvage(&raw).map_err(|e| JudgeError {
+                        message: format!("Evaluation reply unusable ({reason}): {e}"),
+                        usage: usage.clone(),
+                    })?;
+                    tracing::warn!(
+                        "Structured evaluation invalid ({}), using its valid parts",
+                        reason
+                    );
+                    (report.into_parsed(dims), usage)
+                }
+                Err(StructuredError::Provider(e)) => return Err(anyhow::anyhow!("{}", e).into()),
+            };
 
         Ok(LlmEvalResult {
             dimensions: parsed.dimensions,
             findings: parsed.findings,
             suggestion: parsed.suggestion,
-            usage: response.usage,
+            usage,
         })
     }
 }
 
+/// An LLM judge call that produced nothing usable, with what it still cost.
+#[derive(Debug)]
+struct JudgeError {
+    message: String,
+    usage: TokenUsage,
+}
+
+impl std::fmt::Display for JudgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<anyhow::Error> for JudgeError {
+    fn from(e: anyhow::Error) -> Self {
+        Self {
+            message: e.to_string(),
+            usage: TokenUsage::default(),
+        }
+    }
+}
+
 /// Result from an LLM-based evaluation.
 struct LlmEvalResult {
     dimensions: Vec<DimensionScore>,
@@ -502,129 +649,22 @@ fn truncate_for_eval(text: &str, max_chars: usize) -> &str {
     }
 }
 
-/// Parse an incremental evaluation response from the LLM.
-///
-/// Expected format:
-/// ```text
-/// SCORES:
-/// dimension: 0.85
-/// ...
-/// RESOLVED:
-/// F1
-/// F2
-/// ...
-/// NEW_FINDINGS:
-/// - [BLOCKER] title: description
-/// ...
-/// SUGGESTION: ...
-/// ```
-fn parse_incremental_eval_response(
-    content: &str,
+/// Convert a structured incremental report, numbering new findings `NF1..`.
+fn incremental_from_report(
+    report: parser::IncrementalEvalReport,
     expected_dimensions: &[DimensionDef],
 ) -> ParsedIncrementalEval {
-    let mut dimensions = Vec::new();
-    let mut resolved_finding_ids = Vec::new();
-    let mut new_findings = Vec::new();
-
-    #[derive(PartialEq)]
-    enum Section {
-        None,
-        Scores,
-        Resolved,
-        NewFindings,
-        Suggestion,
-    }
-
-    let mut section = Section::None;
-    let mut finding_counter = 0u32;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        // Section headers
-        if trimmed.starts_with("SCORES:") {
-            section = Section::Scores;
-            continue;
-        } else if trimmed.starts_with("RESOLVED:") {
-            section = Section::Resolved;
-            continue;
-        } else if trimmed.starts_with("NEW_FINDINGS:") || trimmed.starts_with("FINDINGS:") {
-            section = Section::NewFindings;
-            continue;
-        } else if trimmed.starts_with("SUGGESTION:") {
-            section = Section::Suggestion;
-            continue;
-        }
-
-        if trimmed.is_empty() {
-            continue;
-        }
-
-        match section {
-            Section::Scores => {
-                // "dimension_name: 0.85"
-                if let Some((dim, score_str)) = trimmed.split_once(':') {
-                    let dim_name = dim.trim().to_string();
-                    if let Ok(score) = score_str.trim().parse::<f32>() {
-                        let weight = expected_dimensions
-                            .iter()
-                            .find(|d| d.name == dim_name)
-                            .map(|d| d.weight)
-                            .unwrap_or(0.25);
-                        dimensions.push(DimensionScore {
-                            dimension: dim_name,
-                            score: score.clamp(0.0, 1.0),
-                            weight,
-                        });
-                    }
-                }
-            }
-            Section::Resolved => {
-                // Just a finding ID like "F1" or "T1"
-                let id = trimmed.trim_start_matches("- ").trim().to_string();
-                if !id.is_empty() {
-                    resolved_finding_ids.push(id);
-                }
-            }
-            Section::NewFindings => {
-                // "- [SEVERITY] title: description"
-                if trimmed.starts_with("- [") || trimmed.starts_with("-[") {
-                    finding_counter += 1;
-                    let rest = trimmed.trim_start_matches("- [").trim_start_matches("-[");
-
-                    if let Some((severity_str, after)) = rest.split_once(']') {
-                        let severity = match severity_str.trim().to_uppercase().as_str() {
-                            "BLOCKER" => Severity::Blocker,
-                            "IMPORTANT" | "ERROR" => Severity::Important,
-                            _ => Severity::Suggestion,
-                        };
-
-                        let after = after.trim().trim_start_matches(':').trim();
-                        let (title, desc) = if let Some((t, d)) = after.split_once(':') {
-                            (t.trim().to_string(), d.trim().to_string())
-                        } else {
-                            (after.to_string(), after.to_string())
-                        };
-
-                        new_findings.push(Finding {
-                            id: format!("NF{}", finding_counter),
-                            severity,
-                            dimension: "general".into(),
-                            title,
-                            description: desc,
-                            location: None,
-                            fix: None,
-                        });
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
+    let dimensions = parser::score_entries_to_dimensions(report.scores, expected_dimensions);
+    let new_findings = report
+        .new_findings
+        .into_iter()
+        .enumerate()
+        .map(|(i, f)| f.into_finding(format!("NF{}", i + 1)))
+        .collect();
 
     ParsedIncrementalEval {
         dimensions,
-        resolved_finding_ids,
+        resolved_finding_ids: report.resolved,
         new_findings,
     }
 }
@@ -985,81 +1025,25 @@ mod tests {
         assert_eq!(dims[1].dimension, "z");
     }
 
-    // ─── parse_incremental_eval_response tests ──────────────────
+    // ─── incremental_from_report tests ──────────────────────────
 
     #[test]
-    fn test_incremental_parse_full() {
-        let content = "\
-SCORES:
-correctness: 0.95
-style: 0.80
-RESOLVED:
-F1
-F3
-NEW_FINDINGS:
-- [BLOCKER] New issue: something bad
-- [SUGGESTION] Minor fix needed
-SUGGESTION: Keep improving.";
-
+    fn test_incremental_from_salvaged_report() {
+        let raw = r#"{"scores": [{"dimension": "correctness", "score": 0.95},
+                                 {"dimension": "style", "score": 1.5}],
+                      "resolved": ["F1", "F3"],
+                      "new_findings": [{"severity": "BLOCKER", "dimension": "correctness",
+                                        "title": "New issue", "description": "bad",
+                                        "location": null, "fix": null}]}"#;
+        let report = parser::IncrementalEvalReport::salvage(raw).unwrap();
         let dims = vec![dim_def("correctness", 0.5), dim_def("style", 0.5)];
-        let result = parse_incremental_eval_response(content, &dims);
-
-        assert_eq!(result.dimensions.len(), 2);
-        assert!((result.dimensions[0].score - 0.95).abs() < 0.001);
-        assert!((result.dimensions[1].score - 0.80).abs() < 0.001);
+        let result = incremental_from_report(report, &dims);
 
+        assert_eq!(result.dimensions.len(), 1);
+        assert!((result.dimensions[0].weight - 0.5).abs() < 0.001);
         assert_eq!(result.resolved_finding_ids, vec!["F1", "F3"]);
-        assert_eq!(result.new_findings.len(), 2);
-        assert_eq!(result.new_findings[0].severity, Severity::Blocker);
         assert_eq!(result.new_findings[0].id, "NF1");
-        assert_eq!(result.new_findings[1].id, "NF2");
-    }
-
-    #[test]
-    fn test_incremental_parse_empty() {
-        let result = parse_incremental_eval_response("", &[]);
-        assert!(result.dimensions.is_empty());
-        assert!(result.resolved_finding_ids.is_empty());
-        assert!(result.new_findings.is_empty());
-    }
-
-    #[test]
-    fn test_incremental_parse_scores_only() {
-        let content = "SCORES:\naccuracy: 0.88";
-        let dims = vec![dim_def("accuracy", 1.0)];
-        let result = parse_incremental_eval_response(content, &dims);
-        assert_eq!(result.dimensions.len(), 1);
-        assert!((result.dimensions[0].weight - 1.0).abs() < 0.001);
-    }
-
-    #[test]
-    fn test_incremental_parse_unknown_dim_default_weight() {
-        let content = "SCORES:\nunknown_dim: 0.70";
-        let dims = vec![dim_def("accuracy", 1.0)];
-        let result = parse_incremental_eval_response(content, &dims);
-        assert_eq!(result.dimensions.len(), 1);
-        assert!((result.dimensions[0].weight - 0.25).abs() < 0.001); // Default weight
-    }
-
-    #[test]
-    fn test_incremental_parse_resolved_with_dashes() {
-        let content = "RESOLVED:\n- F1\n- F2";
-        let result = parse_incremental_eval_response(content, &[]);
-        assert_eq!(result.resolved_finding_ids, vec!["F1", "F2"]);
-    }
-
-    #[test]
-    fn test_incremental_parse_score_clamping() {
-        let content = "SCORES:\nx: 1.5\ny: -0.5\nz: 0.5";
-        let dims = vec![];
-        let result = parse_incremental_eval_response(content, &dims);
-        // 1.5 clamped to 1.0, -0.5 is negative so parse would fail (not a valid f32 in range check)
-        // Actually, -0.5 parses as f32 fine, then gets clamped to 0.0
-        // and 1.5 parses fine, gets clamped to 1.0
-        let scores: Vec<f32> = result.dimensions.iter().map(|d| d.score).collect();
-        for s in &scores {
-            assert!(*s >= 0.0 && *s <= 1.0);
-        }
+        assert_eq!(result.new_findings[0].severity, Severity::Blocker);
     }
 
     // ─── ScoreCalibrator tests ──────────────────────────────────
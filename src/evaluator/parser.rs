@@ -1,423 +1,399 @@
 // src/evaluator/parser.rs — Parse LLM evaluation responses into structured scores
 
+use serde::Deserialize;
+
 use crate::core::types::*;
+use crate::provider::structured::{extract_json_value, salvage_entries, StructuredOutput};
 use crate::skills::types::DimensionDef;
 
-/// Parsed evaluation result from LLM response text.
+/// An evaluation report converted to scores and findings.
 pub struct ParsedEval {
     pub dimensions: Vec<DimensionScore>,
     pub findings: Vec<Finding>,
     pub suggestion: String,
 }
 
-/// Parse an LLM evaluation response into structured scores and findings.
-///
-/// Expected format:
-/// ```text
-/// SCORES:
-/// dimension_name: 0.85
-/// ...
-/// FINDINGS:
-/// - [BLOCKER] title: description
-/// - [IMPORTANT] title: description
-/// SUGGESTION: brief guidance
-/// ```
-pub fn parse_eval_response(response: &str, expected_dimensions: &[DimensionDef]) -> ParsedEval {
-    let mut dimensions = Vec::new();
-    let mut findings = Vec::new();
-    let mut suggestion = String::new();
-
-    let mut section = Section::None;
-
-    for line in response.lines() {
-        let trimmed = line.trim();
-
-        // Section headers
-        if trimmed.starts_with("SCORES:") || trimmed.starts_with("## Scores") {
-            section = Section::Scores;
-            continue;
-        }
-        if trimmed.starts_with("FINDINGS:") || trimmed.starts_with("## Findings") {
-            section = Section::Findings;
-            continue;
-        }
-        if trimmed.starts_with("SUGGESTION:") || trimmed.starts_with("## Suggestion") {
-            section = Section::Suggestion;
-            let rest = trimmed
-                .strip_prefix("SUGGESTION:")
-                .or_else(|| trimmed.strip_prefix("## Suggestion"))
-                .unwrap_or("")
-                .trim();
-            if !rest.is_empty() {
-                suggestion = rest.to_string();
-            }
-            continue;
-        }
+/// Weight for a scored dimension: the rubric's weight when the name matches
+/// (case-insensitively), otherwise an even share.
+fn dimension_weight(name: &str, expected_dimensions: &[DimensionDef]) -> f32 {
+    expected_dimensions
+        .iter()
+        .find(|d| d.name.eq_ignore_ascii_case(name))
+        .map(|d| d.weight)
+        .unwrap_or(1.0 / expected_dimensions.len().max(1) as f32)
+}
 
-        match section {
-            Section::Scores => {
-                if let Some((name, score)) = parse_score_line(trimmed) {
-                    let weight = expected_dimensions
-                        .iter()
-                        .find(|d| d.name.eq_ignore_ascii_case(&name))
-                        .map(|d| d.weight)
-                        .unwrap_or(1.0 / expected_dimensions.len().max(1) as f32);
-
-                    dimensions.push(DimensionScore {
-                        dimension: name,
-                        score,
-                        weight,
-                    });
-                }
-            }
-            Section::Findings => {
-                if let Some(finding) = parse_finding_line(trimmed, findings.len()) {
-                    findings.push(finding);
-                }
-            }
-            Section::Suggestion => {
-                if !trimmed.is_empty() {
-                    if !suggestion.is_empty() {
-                        suggestion.push(' ');
-                    }
-                    suggestion.push_str(trimmed);
-                }
-            }
-            Section::None => {}
-        }
+/// Map a severity label (including common synonyms) to a `Severity`.
+pub(crate) fn parse_severity(label: &str) -> Option<Severity> {
+    match label.trim().to_uppercase().as_str() {
+        "BLOCKER" => Some(Severity::Blocker),
+        "IMPORTANT" | "MAJOR" | "HIGH" | "ERROR" => Some(Severity::Important),
+        "SUGGESTION" | "MINOR" | "LOW" => Some(Severity::Suggestion),
+        _ => None,
     }
+}
 
-    // If no dimensions were parsed, create defaults from expected
-    if dimensions.is_empty() && !expected_dimensions.is_empty() {
-        for dim in expected_dimensions {
-            dimensions.push(DimensionScore {
-                dimension: dim.name.clone(),
-                score: 0.75, // Default moderate score
-                weight: dim.weight,
-            });
-        }
-    }
+// ─── Structured evaluation output ───────────────────────────────────────────
 
-    ParsedEval {
-        dimensions,
-        findings,
-        suggestion,
-    }
+/// A full evaluation returned as schema-validated JSON.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalReport {
+    pub scores: Vec<ScoreEntry>,
+    pub findings: Vec<FindingEntry>,
+    pub suggestion: String,
 }
 
-enum Section {
-    None,
-    Scores,
-    Findings,
-    Suggestion,
+/// An incremental re-evaluation returned as schema-validated JSON.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IncrementalEvalReport {
+    pub scores: Vec<ScoreEntry>,
+    pub resolved: Vec<String>,
+    pub new_findings: Vec<FindingEntry>,
+    pub suggestion: String,
 }
 
-/// Parse a line like "correctness: 0.85" or "- correctness: 0.85"
-pub(crate) fn parse_score_line(line: &str) -> Option<(String, f32)> {
-    let line = line.trim_start_matches('-').trim();
-    let (name, score_str) = line.split_once(':')?;
-    let name = name.trim().to_string();
-    let score: f32 = score_str.trim().parse().ok()?;
-
-    if !(0.0..=1.0).contains(&score) {
-        return None;
-    }
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScoreEntry {
+    pub dimension: String,
+    pub score: f32,
+}
 
-    Some((name, score))
+#[derive(Debug, Clone, Deserialize)]
+pub struct FindingEntry {
+    pub severity: String,
+    pub dimension: String,
+    pub title: String,
+    pub description: String,
+    pub location: Option<String>,
+    pub fix: Option<String>,
 }
 
-/// Parse a finding line like "- [BLOCKER] title: description"
-pub(crate) fn parse_finding_line(line: &str, index: usize) -> Option<Finding> {
-    let line = line.trim_start_matches('-').trim();
-    if line.is_empty() {
-        return None;
+impl FindingEntry {
+    pub fn into_finding(self, id: String) -> Finding {
+        Finding {
+            id,
+            severity: parse_severity(&self.severity).unwrap_or(Severity::Suggestion),
+            dimension: self.dimension,
+            title: self.title,
+            description: self.description,
+            location: self.location.filter(|l| !l.is_empty()),
+            fix: self.fix.filter(|f| !f.is_empty()),
+        }
     }
+}
 
-    // Try to parse [SEVERITY] title: description
-    if line.starts_with('[') {
-        if let Some(end_bracket) = line.find(']') {
-            let severity_str = &line[1..end_bracket];
-            let rest = line[end_bracket + 1..].trim();
-
-            let severity = match severity_str.to_uppercase().as_str() {
-                "BLOCKER" => Severity::Blocker,
-                "IMPORTANT" | "MAJOR" | "HIGH" => Severity::Important,
-                "SUGGESTION" | "MINOR" | "LOW" => Severity::Suggestion,
-                _ => Severity::Suggestion,
-            };
-
-            let (title, description) = rest
-                .split_once(':')
-                .map(|(t, d)| (t.trim().to_string(), d.trim().to_string()))
-                .unwrap_or_else(|| (rest.to_string(), String::new()));
-
-            return Some(Finding {
-                id: format!("F{}", index + 1),
-                severity,
-                dimension: String::new(),
-                title,
-                description,
-                location: None,
-                fix: None,
-            });
+impl EvalReport {
+    /// Recover what is usable from a reply that failed validation: scores in
+    /// range and findings with a title. Fails when the reply has no JSON
+    /// object or no usable score, rather than guessing scores.
+    pub fn salvage(raw: &str) -> Result<Self, String> {
+        let value = extract_json_value(raw).ok_or("no JSON object found in reply")?;
+        let scores = usable_scores(salvage_entries(&value, "scores"));
+        if scores.is_empty() {
+            return Err("no usable dimension scores in reply".into());
         }
+        Ok(Self {
+            scores,
+            findings: usable_findings(salvage_entries(&value, "findings")),
+            suggestion: salvage_string(&value, "suggestion"),
+        })
     }
 
-    // Fallback: treat entire line as a suggestion
-    Some(Finding {
-        id: format!("F{}", index + 1),
-        severity: Severity::Suggestion,
-        dimension: String::new(),
-        title: line.to_string(),
-        description: String::new(),
-        location: None,
-        fix: None,
-    })
+    /// Weight the scores by the rubric and number the findings.
+    pub fn into_parsed(self, expected_dimensions: &[DimensionDef]) -> ParsedEval {
+        ParsedEval {
+            dimensions: score_entries_to_dimensions(self.scores, expected_dimensions),
+            findings: self
+                .findings
+                .into_iter()
+                .enumerate()
+                .map(|(i, f)| f.into_finding(format!("F{}", i + 1)))
+                .collect(),
+            suggestion: self.suggestion,
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn dim(name: &str, weight: f32) -> DimensionDef {
-        DimensionDef {
-            name: name.to_string(),
-            weight,
-            description: String::new(),
-        }
+impl IncrementalEvalReport {
+    /// Recover what is usable from a reply that failed validation (see
+    /// [`EvalReport::salvage`]). Fails when the reply has no JSON object.
+    pub fn salvage(raw: &str) -> Result<Self, String> {
+        let value = extract_json_value(raw).ok_or("no JSON object found in reply")?;
+        Ok(Self {
+            scores: usable_scores(salvage_entries(&value, "scores")),
+            resolved: salvage_entries(&value, "resolved"),
+            new_findings: usable_findings(salvage_entries(&value, "new_findings")),
+            suggestion: salvage_string(&value, "suggestion"),
+        })
     }
+}
 
-    // ─── parse_score_line tests ─────────────────────────────────
+fn usable_scores(scores: Vec<ScoreEntry>) -> Vec<ScoreEntry> {
+    scores
+        .into_iter()
+        .filter(|s| !s.dimension.trim().is_empty() && (0.0..=1.0).contains(&s.score))
+        .collect()
+}
 
-    #[test]
-    fn test_parse_score_line_basic() {
-        let (name, score) = parse_score_line("correctness: 0.85").unwrap();
-        assert_eq!(name, "correctness");
-        assert!((score - 0.85).abs() < 0.001);
-    }
+fn usable_findings(findings: Vec<FindingEntry>) -> Vec<FindingEntry> {
+    findings
+        .into_iter()
+        .filter(|f| !f.title.trim().is_empty())
+        .collect()
+}
 
-    #[test]
-    fn test_parse_score_line_with_dash() {
-        let (name, score) = parse_score_line("- style: 0.70").unwrap();
-        assert_eq!(name, "style");
-        assert!((score - 0.70).abs() < 0.001);
-    }
+fn salvage_string(value: &serde_json::Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
 
-    #[test]
-    fn test_parse_score_line_zero() {
-        let (name, score) = parse_score_line("security: 0.0").unwrap();
-        assert_eq!(name, "security");
-        assert_eq!(score, 0.0);
-    }
+pub(crate) fn score_entries_to_dimensions(
+    scores: Vec<ScoreEntry>,
+    expected_dimensions: &[DimensionDef],
+) -> Vec<DimensionScore> {
+    scores
+        .into_iter()
+        .map(|s| DimensionScore {
+            weight: dimension_weight(&s.dimension, expected_dimensions),
+            dimension: s.dimension,
+            score: s.score,
+        })
+        .collect()
+}
 
-    #[test]
-    fn test_parse_score_line_one() {
-        let (name, score) = parse_score_line("tests: 1.0").unwrap();
-        assert_eq!(name, "tests");
-        assert_eq!(score, 1.0);
-    }
+fn score_entry_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "dimension": { "type": "string" },
+            "score": { "type": "number", "description": "0.0 (worst) to 1.0 (best)" }
+        },
+        "required": ["dimension", "score"],
+        "additionalProperties": false
+    })
+}
 
-    #[test]
-    fn test_parse_score_line_out_of_range() {
-        assert!(parse_score_line("broken: 1.5").is_none());
-        assert!(parse_score_line("broken: -0.1").is_none());
-    }
+fn finding_entry_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "severity": { "type": "string", "enum": ["BLOCKER", "IMPORTANT", "SUGGESTION"] },
+            "dimension": { "type": "string" },
+            "title": { "type": "string" },
+            "description": { "type": "string" },
+            "location": { "type": ["string", "null"], "description": "file:line when known" },
+            "fix": { "type": ["string", "null"] }
+        },
+        "required": ["severity", "dimension", "title", "description", "location", "fix"],
+        "additionalProperties": false
+    })
+}
 
-    #[test]
-    fn test_parse_score_line_not_a_number() {
-        assert!(parse_score_line("name: not-a-score").is_none());
+fn validate_scores(scores: &[ScoreEntry]) -> Result<(), String> {
+    for s in scores {
+        if s.dimension.trim().is_empty() {
+            return Err("score entry has an empty dimension name".into());
+        }
+        if !(0.0..=1.0).contains(&s.score) {
+            return Err(format!(
+                "score for '{}' is {} but must be between 0.0 and 1.0",
+                s.dimension, s.score
+            ));
+        }
     }
+    Ok(())
+}
 
-    #[test]
-    fn test_parse_score_line_no_colon() {
-        assert!(parse_score_line("just a sentence").is_none());
+fn validate_findings(findings: &[FindingEntry]) -> Result<(), String> {
+    for f in findings {
+        if parse_severity(&f.severity).is_none() {
+            return Err(format!(
+                "finding '{}' has unknown severity '{}' (use BLOCKER, IMPORTANT or SUGGESTION)",
+                f.title, f.severity
+            ));
+        }
+        if f.title.trim().is_empty() {
+            return Err("finding has an empty title".into());
+        }
     }
+    Ok(())
+}
 
-    // ─── parse_finding_line tests ───────────────────────────────
-
-    #[test]
-    fn test_parse_finding_blocker() {
-        let f = parse_finding_line(
-            "[BLOCKER] Missing null check: could panic on empty input",
-            0,
-        )
-        .unwrap();
-        assert_eq!(f.id, "F1");
-        assert_eq!(f.severity, Severity::Blocker);
-        assert_eq!(f.title, "Missing null check");
-        assert_eq!(f.description, "could panic on empty input");
+impl StructuredOutput for EvalReport {
+    fn schema_name() -> &'static str {
+        "evaluation"
     }
 
-    #[test]
-    fn test_parse_finding_important() {
-        let f = parse_finding_line("[IMPORTANT] No error handling: unwrap used", 1).unwrap();
-        assert_eq!(f.id, "F2");
-        assert_eq!(f.severity, Severity::Important);
+    fn json_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "scores": { "type": "array", "items": score_entry_schema() },
+                "findings": { "type": "array", "items": finding_entry_schema() },
+                "suggestion": { "type": "string" }
+            },
+            "required": ["scores", "findings", "suggestion"],
+            "additionalProperties": false
+        })
     }
 
-    #[test]
-    fn test_parse_finding_major() {
-        let f = parse_finding_line("[MAJOR] Buffer overflow risk", 0).unwrap();
-        assert_eq!(f.severity, Severity::Important); // MAJOR maps to Important
+    fn validate(&self) -> Result<(), String> {
+        if self.scores.is_empty() {
+            return Err("at least one dimension score is required".into());
+        }
+        validate_scores(&self.scores)?;
+        validate_findings(&self.findings)
     }
+}
 
-    #[test]
-    fn test_parse_finding_suggestion() {
-        let f = parse_finding_line("[SUGGESTION] Consider using iterators", 2).unwrap();
-        assert_eq!(f.severity, Severity::Suggestion);
+impl StructuredOutput for IncrementalEvalReport {
+    fn schema_name() -> &'static str {
+        "incremental_evaluation"
     }
 
-    #[test]
-    fn test_parse_finding_minor() {
-        let f = parse_finding_line("[MINOR] Variable naming: use snake_case", 0).unwrap();
-        assert_eq!(f.severity, Severity::Suggestion); // MINOR maps to Suggestion
+    fn json_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "scores": { "type": "array", "items": score_entry_schema() },
+                "resolved": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "IDs of previous findings that are now fixed"
+                },
+                "new_findings": { "type": "array", "items": finding_entry_schema() },
+                "suggestion": { "type": "string" }
+            },
+            "required": ["scores", "resolved", "new_findings", "suggestion"],
+            "additionalProperties": false
+        })
     }
 
-    #[test]
-    fn test_parse_finding_no_description() {
-        let f = parse_finding_line("[BLOCKER] Just a title", 0).unwrap();
-        assert_eq!(f.title, "Just a title");
-        assert!(f.description.is_empty());
+    fn validate(&self) -> Result<(), String> {
+        validate_scores(&self.scores)?;
+        validate_findings(&self.new_findings)
     }
+}
 
-    #[test]
-    fn test_parse_finding_with_dash_prefix() {
-        let f = parse_finding_line("- [BLOCKER] Title: Desc", 0).unwrap();
-        assert_eq!(f.severity, Severity::Blocker);
-        assert_eq!(f.title, "Title");
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    #[test]
-    fn test_parse_finding_fallback() {
-        let f = parse_finding_line("Some plain text finding", 5).unwrap();
-        assert_eq!(f.id, "F6");
-        assert_eq!(f.severity, Severity::Suggestion);
-        assert_eq!(f.title, "Some plain text finding");
+    fn dim(name: &str, weight: f32) -> DimensionDef {
+        DimensionDef {
+            name: name.to_string(),
+            weight,
+            description: String::new(),
+        }
     }
 
+    // ─── Structured report tests ────────────────────────────────
+
     #[test]
-    fn test_parse_finding_empty_line() {
-        assert!(parse_finding_line("", 0).is_none());
+    fn test_eval_report_into_parsed() {
+        let json = r#"{
+            "scores": [{"dimension": "Correctness", "score": 0.9}, {"dimension": "style", "score": 0.6}],
+            "findings": [{"severity": "BLOCKER", "dimension": "correctness", "title": "Panic",
+                          "description": "unwrap on None", "location": "src/lib.rs:10", "fix": null}],
+            "suggestion": "Handle the None case."
+        }"#;
+        let report: EvalReport = crate::provider::structured::parse_structured(json).unwrap();
+        let dims = vec![dim("correctness", 0.7), dim("style", 0.3)];
+        let parsed = report.into_parsed(&dims);
+
+        assert_eq!(parsed.dimensions.len(), 2);
+        assert!((parsed.dimensions[0].weight - 0.7).abs() < 0.001);
+        assert_eq!(parsed.findings.len(), 1);
+        assert_eq!(parsed.findings[0].id, "F1");
+        assert_eq!(parsed.findings[0].severity, Severity::Blocker);
+        assert_eq!(
+            parsed.findings[0].location.as_deref(),
+            Some("src/lib.rs:10")
+        );
+        assert!(parsed.findings[0].fix.is_none());
+        assert_eq!(parsed.suggestion, "Handle the None case.");
     }
 
     #[test]
-    fn test_parse_finding_whitespace() {
-        assert!(parse_finding_line("   ", 0).is_none());
+    fn test_eval_report_rejects_out_of_range_score() {
+        let json =
+            r#"{"scores": [{"dimension": "x", "score": 8.5}], "findings": [], "suggestion": ""}"#;
+        let err = crate::provider::structured::parse_structured::<EvalReport>(json).unwrap_err();
+        assert!(err.contains("between 0.0 and 1.0"));
     }
 
-    // ─── parse_eval_response tests ──────────────────────────────
-
     #[test]
-    fn test_parse_eval_full_response() {
-        let response = "\
-SCORES:
-correctness: 0.90
-style: 0.75
-security: 0.60
-FINDINGS:
-- [BLOCKER] SQL injection: User input not sanitized
-- [SUGGESTION] Consider using constants
-SUGGESTION: Fix the SQL injection vulnerability first.";
-
-        let dims = vec![
-            dim("correctness", 0.4),
-            dim("style", 0.3),
-            dim("security", 0.3),
-        ];
-        let result = parse_eval_response(response, &dims);
-
-        assert_eq!(result.dimensions.len(), 3);
-        assert!((result.dimensions[0].score - 0.90).abs() < 0.001);
-        assert!((result.dimensions[0].weight - 0.4).abs() < 0.001);
-        assert!((result.dimensions[1].score - 0.75).abs() < 0.001);
-        assert!((result.dimensions[2].score - 0.60).abs() < 0.001);
-
-        assert_eq!(result.findings.len(), 2);
-        assert_eq!(result.findings[0].severity, Severity::Blocker);
-        assert_eq!(result.findings[1].severity, Severity::Suggestion);
-
-        assert!(result.suggestion.contains("SQL injection"));
+    fn test_eval_report_rejects_unknown_severity() {
+        let json = r#"{"scores": [{"dimension": "x", "score": 0.5}],
+            "findings": [{"severity": "CRITICAL", "dimension": "x", "title": "t",
+                          "description": "", "location": null, "fix": null}],
+            "suggestion": ""}"#;
+        let err = crate::provider::structured::parse_structured::<EvalReport>(json).unwrap_err();
+        assert!(err.contains("unknown severity"));
     }
 
     #[test]
-    fn test_parse_eval_empty_response() {
-        let dims = vec![dim("correctness", 0.5), dim("style", 0.5)];
-        let result = parse_eval_response("", &dims);
-
-        // Should create defaults from expected dimensions
-        assert_eq!(result.dimensions.len(), 2);
-        assert!((result.dimensions[0].score - 0.75).abs() < 0.001);
-        assert!(result.findings.is_empty());
+    fn test_eval_report_requires_scores() {
+        let json = r#"{"scores": [], "findings": [], "suggestion": ""}"#;
+        assert!(crate::provider::structured::parse_structured::<EvalReport>(json).is_err());
     }
 
     #[test]
-    fn test_parse_eval_no_expected_dims() {
-        let result = parse_eval_response("", &[]);
-        assert!(result.dimensions.is_empty());
+    fn test_incremental_report_allows_no_scores() {
+        let json = r#"{"scores": [], "resolved": ["F1"], "new_findings": [], "suggestion": ""}"#;
+        let report: IncrementalEvalReport =
+            crate::provider::structured::parse_structured(json).unwrap();
+        assert_eq!(report.resolved, vec!["F1"]);
     }
 
     #[test]
-    fn test_parse_eval_alternative_headers() {
-        let response = "\
-## Scores
-correctness: 0.85
-## Findings
-- [IMPORTANT] Missing docs
-## Suggestion
-Add documentation.";
-
-        let dims = vec![dim("correctness", 1.0)];
-        let result = parse_eval_response(response, &dims);
-
-        assert_eq!(result.dimensions.len(), 1);
-        assert!((result.dimensions[0].score - 0.85).abs() < 0.001);
-        assert_eq!(result.findings.len(), 1);
-        assert!(result.suggestion.contains("documentation"));
+    fn test_eval_report_salvage_keeps_valid_parts() {
+        let raw = r#"Here you go:
+            {"scores": [{"dimension": "correctness", "score": 0.8},
+                        {"dimension": "style", "score": 8}],
+             "findings": [{"severity": "CRITICAL", "dimension": "x", "title": "Bad",
+                           "description": "d", "location": null, "fix": null},
+                          {"severity": "BLOCKER", "title": ""}],
+             "suggestion": "tighten it"}"#;
+        let report = EvalReport::salvage(raw).unwrap();
+        assert_eq!(report.scores.len(), 1);
+        assert_eq!(report.scores[0].dimension, "correctness");
+        assert_eq!(report.findings.len(), 1);
+        let parsed = report.into_parsed(&[dim("correctness", 1.0)]);
+        assert_eq!(parsed.findings[0].severity, Severity::Suggestion);
+        assert_eq!(parsed.suggestion, "tighten it");
     }
 
     #[test]
-    fn test_parse_eval_unknown_dimension_weight() {
-        let response = "\
-SCORES:
-mystery_dim: 0.80";
-
-        let dims = vec![dim("correctness", 0.5), dim("style", 0.5)];
-        let result = parse_eval_response(response, &dims);
-
-        // Unknown dimension gets 1/len weight
-        assert_eq!(result.dimensions.len(), 1);
-        assert!((result.dimensions[0].weight - 0.5).abs() < 0.001);
+    fn test_eval_report_salvage_fails_without_scores() {
+        let err = EvalReport::salvage("SCORES:\ncorrectness: 0.9").unwrap_err();
+        assert!(err.contains("no JSON object"));
+        let err =
+            EvalReport::salvage(r#"{"scores": [{"dimension": "x", "score": 2}]}"#).unwrap_err();
+        assert!(err.contains("no usable dimension scores"));
     }
 
     #[test]
-    fn test_parse_eval_multiline_suggestion() {
-        let response = "\
-SUGGESTION: First line.
-Second line continues.
-Third line too.
-SCORES:
-x: 0.50";
-
-        let dims = vec![dim("x", 1.0)];
-        let result = parse_eval_response(response, &dims);
-
-        // Suggestion captures lines until next section header
-        assert!(result.suggestion.contains("First line."));
-        // After SCORES: header, parsing switches to scores section
-        assert_eq!(result.dimensions.len(), 1);
+    fn test_incremental_report_salvage() {
+        let raw = r#"{"scores": [{"dimension": "x", "score": 0.9}], "resolved": ["F1", 2],
+                      "new_findings": [{"severity": "IMPORTANT", "dimension": "x",
+                                        "title": "t", "description": "d",
+                                        "location": "a.rs:1", "fix": null}]}"#;
+        let report = IncrementalEvalReport::salvage(raw).unwrap();
+        assert_eq!(report.scores.len(), 1);
+        assert_eq!(report.resolved, vec!["F1"]);
+        assert_eq!(report.new_findings.len(), 1);
+        assert!(report.suggestion.is_empty());
+        assert!(IncrementalEvalReport::salvage("no json here").is_err());
     }
 
     #[test]
-    fn test_parse_eval_case_insensitive_dimension_match() {
-        let response = "\
-SCORES:
-Correctness: 0.90";
-
-        let dims = vec![dim("correctness", 0.5)];
-        let result = parse_eval_response(response, &dims);
-
-        // Should match case-insensitively
-        assert_eq!(result.dimensions.len(), 1);
-        assert!((result.dimensions[0].weight - 0.5).abs() < 0.001);
+    fn test_parse_severity_synonyms() {
+        assert_eq!(parse_severity("major"), Some(Severity::Important));
+        assert_eq!(parse_severity(" low "), Some(Severity::Suggestion));
+        assert_eq!(parse_severity("critical"), None);
     }
 }
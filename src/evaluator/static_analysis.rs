@@ -4,8 +4,8 @@
 // Parses output to derive lint issues with severity and location.
 
 use crate::core::types::*;
+use crate::security::sandbox::{self, SandboxCommand};
 use std::path::Path;
-use tokio::process::Command;
 
 /// Built-in static analyzer that runs lint and type checks.
 pub struct StaticAnalyzer;
@@ -71,10 +71,7 @@ impl StaticAnalyzer {
     /// Run `cargo clippy` and parse output.
     async fn run_cargo_clippy(&self, project_dir: &Path) -> anyhow::Result<Option<LintResult>> {
         // Check if clippy is available
-        let check = Command::new("cargo")
-            .args(["clippy", "--version"])
-            .output()
-            .await;
+        let check = sandbox::run(SandboxCommand::new("cargo").args(["clippy", "--version"])).await;
 
         if check.is_err() || !check.unwrap().status.success() {
             tracing::debug!("cargo clippy not available, skipping");
@@ -83,19 +80,21 @@ impl StaticAnalyzer {
 
         tracing::debug!("Running: cargo clippy in {:?}", project_dir);
 
-        let output = Command::new("cargo")
-            .args([
-                "clippy",
-                "--all-targets",
-                "--message-format=short",
-                "--",
-                "-D",
-                "warnings",
-            ])
-            .env("CARGO_TERM_COLOR", "never")
-            .current_dir(project_dir)
-            .output()
-            .await?;
+        let output = sandbox::run(
+            SandboxCommand::new("cargo")
+                .args([
+                    "clippy",
+                    "--all-targets",
+                    "--message-format=short",
+                    "--",
+                    "-D",
+                    "warnings",
+                ])
+                .env("CARGO_TERM_COLOR", "never")
+                .current_dir(project_dir)
+                .fetches_dependencies(),
+        )
+        .await?;
 
         let stderr = String::from_utf8_lossy(&output.stderr);
         parse_clippy_output(&stderr, output.status.success())
@@ -104,13 +103,13 @@ impl StaticAnalyzer {
     /// Run `ruff check` or `flake8` and parse output.
     async fn run_ruff_or_flake8(&self, project_dir: &Path) -> anyhow::Result<Option<LintResult>> {
         // Try ruff first (faster)
-        let ruff_check = Command::new("ruff").args(["--version"]).output().await;
+        let ruff_check = sandbox::run(SandboxCommand::new("ruff").args(["--version"])).await;
         if ruff_check.is_ok() && ruff_check.unwrap().status.success() {
             return self.run_ruff(project_dir).await;
         }
 
         // Fall back to flake8
-        let flake8_check = Command::new("flake8").args(["--version"]).output().await;
+        let flake8_check = sandbox::run(SandboxCommand::new("flake8").args(["--version"])).await;
         if flake8_check.is_ok() && flake8_check.unwrap().status.success() {
             return self.run_flake8(project_dir).await;
         }
@@ -122,11 +121,12 @@ impl StaticAnalyzer {
     async fn run_ruff(&self, project_dir: &Path) -> anyhow::Result<Option<LintResult>> {
         tracing::debug!("Running: ruff check in {:?}", project_dir);
 
-        let output = Command::new("ruff")
-            .args(["check", "."])
-            .current_dir(project_dir)
-            .output()
-            .await?;
+        let output = sandbox::run(
+            SandboxCommand::new("ruff")
+                .args(["check", "."])
+                .current_dir(project_dir),
+        )
+        .await?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         parse_ruff_output(&stdout, output.status.success())
@@ -135,11 +135,12 @@ impl StaticAnalyzer {
     async fn run_flake8(&self, project_dir: &Path) -> anyhow::Result<Option<LintResult>> {
         tracing::debug!("Running: flake8 in {:?}", project_dir);
 
-        let output = Command::new("flake8")
-            .args([".", "--format=default"])
-            .current_dir(project_dir)
-            .output()
-            .await?;
+        let output = sandbox::run(
+            SandboxCommand::new("flake8")
+                .args([".", "--format=default"])
+                .current_dir(project_dir),
+        )
+        .await?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         // flake8 output format is similar to ruff
@@ -149,7 +150,7 @@ impl StaticAnalyzer {
     /// Run `eslint` and parse output.
     async fn run_eslint(&self, project_dir: &Path) -> anyhow::Result<Option<LintResult>> {
         // Check npx availability
-        let check = Command::new("npx").args(["--version"]).output().await;
+        let check = sandbox::run(SandboxCommand::new("npx").args(["--version"])).await;
         if check.is_err() || !check.unwrap().status.success() {
             tracing::debug!("npx not available, skipping eslint");
             return Ok(None);
@@ -157,11 +158,13 @@ impl StaticAnalyzer {
 
         tracing::debug!("Running: npx eslint in {:?}", project_dir);
 
-        let output = Command::new("npx")
-            .args(["eslint", ".", "--format=compact"])
-            .current_dir(project_dir)
-            .output()
-            .await?;
+        let output = sandbox::run(
+            SandboxCommand::new("npx")
+                .args(["eslint", ".", "--format=compact"])
+                .current_dir(project_dir)
+                .fetches_dependencies(),
+        )
+        .await?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         parse_eslint_output(&stdout, output.status.success())
@@ -4,8 +4,8 @@
 // Parses output to derive pass/fail counts and failure details.
 
 use crate::core::types::*;
+use crate::security::sandbox::{self, SandboxCommand};
 use std::path::Path;
-use tokio::process::Command;
 
 /// Built-in test runner that detects and runs project test suites.
 pub struct TestRunner;
@@ -69,12 +69,14 @@ impl TestRunner {
     async fn run_cargo_test(&self, project_dir: &Path) -> anyhow::Result<Option<TestResult>> {
         tracing::debug!("Running: cargo test in {:?}", project_dir);
 
-        let output = Command::new("cargo")
-            .args(["test", "--", "--format=terse"])
-            .env("CARGO_TERM_COLOR", "never")
-            .current_dir(project_dir)
-            .output()
-            .await?;
+        let output = sandbox::run(
+            SandboxCommand::new("cargo")
+                .args(["test", "--", "--format=terse"])
+                .env("CARGO_TERM_COLOR", "never")
+                .current_dir(project_dir)
+                .fetches_dependencies(),
+        )
+        .await?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -87,11 +89,13 @@ impl TestRunner {
     async fn run_go_test(&self, project_dir: &Path) -> anyhow::Result<Option<TestResult>> {
         tracing::debug!("Running: go test ./... in {:?}", project_dir);
 
-        let output = Command::new("go")
-            .args(["test", "-v", "./..."])
-            .current_dir(project_dir)
-            .output()
-            .await?;
+        let output = sandbox::run(
+            SandboxCommand::new("go")
+                .args(["test", "-v", "./..."])
+                .current_dir(project_dir)
+                .fetches_dependencies(),
+        )
+        .await?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         parse_go_test_output(&stdout, output.status.success())
@@ -101,11 +105,12 @@ impl TestRunner {
     async fn run_pytest(&self, project_dir: &Path) -> anyhow::Result<Option<TestResult>> {
         tracing::debug!("Running: pytest in {:?}", project_dir);
 
-        let output = Command::new("pytest")
-            .args(["--tb=short", "-q"])
-            .current_dir(project_dir)
-            .output()
-            .await?;
+        let output = sandbox::run(
+            SandboxCommand::new("pytest")
+                .args(["--tb=short", "-q"])
+                .current_dir(project_dir),
+        )
+        .await?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         parse_pytest_output(&stdout, output.status.success())
@@ -123,12 +128,14 @@ impl TestRunner {
 
         tracing::debug!("Running: npm test in {:?}", project_dir);
 
-        let output = Command::new("npm")
-            .args(["test", "--", "--passWithNoTests"])
-            .env("CI", "true") // Prevent interactive mode
-            .current_dir(project_dir)
-            .output()
-            .await?;
+        let output = sandbox::run(
+            SandboxCommand::new("npm")
+                .args(["test", "--", "--passWithNoTests"])
+                .env("CI", "true") // Prevent interactive mode
+                .current_dir(project_dir)
+                .fetches_dependencies(),
+        )
+        .await?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -0,0 +1,569 @@
+// src/index/extract.rs — Line-based symbol and import extraction
+//
+// A deliberately shallow parser: one set of anchored regexes per language,
+// matched line by line. It finds top-level (and method-level) definitions and
+// import statements well enough to rank files and answer "where is X
+// defined", without pulling in a grammar per language.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Longest signature kept per symbol.
+const MAX_SIGNATURE_CHARS: usize = 160;
+
+/// Languages the index understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    TypeScript,
+    JavaScript,
+    Go,
+}
+
+impl Language {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Language::Rust),
+            "py" | "pyi" => Some(Language::Python),
+            "ts" | "tsx" | "mts" | "cts" => Some(Language::TypeScript),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Language::JavaScript),
+            "go" => Some(Language::Go),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::TypeScript => "typescript",
+            Language::JavaScript => "javascript",
+            Language::Go => "go",
+        }
+    }
+
+    /// Fence tag for code snippets.
+    pub fn fence(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::TypeScript => "ts",
+            Language::JavaScript => "js",
+            Language::Go => "go",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "rust" => Some(Language::Rust),
+            "python" => Some(Language::Python),
+            "typescript" => Some(Language::TypeScript),
+            "javascript" => Some(Language::JavaScript),
+            "go" => Some(Language::Go),
+            _ => None,
+        }
+    }
+}
+
+/// What a symbol defines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Struct,
+    Enum,
+    Trait,
+    Impl,
+    Class,
+    Interface,
+    Type,
+    Const,
+    Module,
+}
+
+impl SymbolKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Method => "method",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Trait => "trait",
+            SymbolKind::Impl => "impl",
+            SymbolKind::Class => "class",
+            SymbolKind::Interface => "interface",
+            SymbolKind::Type => "type",
+            SymbolKind::Const => "const",
+            SymbolKind::Module => "module",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "function" => SymbolKind::Function,
+            "method" => SymbolKind::Method,
+            "struct" => SymbolKind::Struct,
+            "enum" => SymbolKind::Enum,
+            "trait" => SymbolKind::Trait,
+            "impl" => SymbolKind::Impl,
+            "class" => SymbolKind::Class,
+            "interface" => SymbolKind::Interface,
+            "type" => SymbolKind::Type,
+            "const" => SymbolKind::Const,
+            "module" => SymbolKind::Module,
+            _ => return None,
+        })
+    }
+
+    /// Definitions that introduce a type (ranked above plain functions).
+    pub fn is_type(&self) -> bool {
+        matches!(
+            self,
+            SymbolKind::Struct
+                | SymbolKind::Enum
+                | SymbolKind::Trait
+                | SymbolKind::Class
+                | SymbolKind::Interface
+                | SymbolKind::Type
+        )
+    }
+}
+
+impl std::fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// One definition found in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// 1-based line number.
+    pub line: u32,
+    pub signature: String,
+}
+
+/// One import statement found in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    /// Module path or specifier as written (`crate::memory::store`,
+    /// `os.path`, `./utils`, `net/http`).
+    pub target: String,
+    pub line: u32,
+}
+
+/// Everything extracted from one file.
+#[derive(Debug, Clone, Default)]
+pub struct Extracted {
+    pub symbols: Vec<Symbol>,
+    pub imports: Vec<Import>,
+}
+
+struct Rule {
+    re: Regex,
+    kind: SymbolKind,
+    /// Capture group holding the name.
+    group: usize,
+}
+
+fn rule(pattern: &str, kind: SymbolKind, group: usize) -> Rule {
+    Rule {
+        re: Regex::new(pattern).expect("valid symbol pattern"),
+        kind,
+        group,
+    }
+}
+
+const RUST_VIS: &str = r"^\s*(?:pub(?:\([^)]*\))?\s+)?";
+
+static RUST_RULES: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![
+        rule(
+            &format!(
+                r#"{RUST_VIS}(?:default\s+)?(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?(?:extern\s+"[^"]*"\s+)?fn\s+([A-Za-z_]\w*)"#
+            ),
+            SymbolKind::Function,
+            1,
+        ),
+        rule(
+            &format!(r"{RUST_VIS}struct\s+([A-Za-z_]\w*)"),
+            SymbolKind::Struct,
+            1,
+        ),
+        rule(
+            &format!(r"{RUST_VIS}enum\s+([A-Za-z_]\w*)"),
+            SymbolKind::Enum,
+            1,
+        ),
+        rule(
+            &format!(r"{RUST_VIS}(?:unsafe\s+)?trait\s+([A-Za-z_]\w*)"),
+            SymbolKind::Trait,
+            1,
+        ),
+        rule(
+            &format!(r"{RUST_VIS}type\s+([A-Za-z_]\w*)"),
+            SymbolKind::Type,
+            1,
+        ),
+        rule(
+            &format!(r"{RUST_VIS}(?:const|static)\s+(?:mut\s+)?([A-Z_][A-Z0-9_]*)\s*:"),
+            SymbolKind::Const,
+            1,
+        ),
+        rule(
+            &format!(r"{RUST_VIS}mod\s+([A-Za-z_]\w*)"),
+            SymbolKind::Module,
+            1,
+        ),
+        // `impl Trait for Type` and `impl Type`: the symbol is the type.
+        rule(
+            r"^\s*(?:unsafe\s+)?impl(?:<.*?>)?\s+(?:[\w:]+(?:<.*?>)?\s+for\s+)?(?:[\w]+::)*([A-Za-z_]\w*)",
+            SymbolKind::Impl,
+            1,
+        ),
+    ]
+});
+
+static PYTHON_RULES: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![
+        rule(
+            r"^(?:async\s+)?def\s+([A-Za-z_]\w*)",
+            SymbolKind::Function,
+            1,
+        ),
+        rule(
+            r"^\s+(?:async\s+)?def\s+([A-Za-z_]\w*)",
+            SymbolKind::Method,
+            1,
+        ),
+        rule(r"^\s*class\s+([A-Za-z_]\w*)", SymbolKind::Class, 1),
+        rule(r"^([A-Z_][A-Z0-9_]*)\s*(?::[^=]+)?=", SymbolKind::Const, 1),
+    ]
+});
+
+const JS_EXPORT: &str = r"^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?";
+
+static JS_RULES: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![
+        rule(
+            &format!(r"{JS_EXPORT}(?:async\s+)?function\s*\*?\s*([A-Za-z_$][\w$]*)"),
+            SymbolKind::Function,
+            1,
+        ),
+        rule(
+            &format!(r"{JS_EXPORT}(?:abstract\s+)?class\s+([A-Za-z_$][\w$]*)"),
+            SymbolKind::Class,
+            1,
+        ),
+        rule(
+            &format!(r"{JS_EXPORT}interface\s+([A-Za-z_$][\w$]*)"),
+            SymbolKind::Interface,
+            1,
+        ),
+        rule(
+            &format!(r"{JS_EXPORT}type\s+([A-Za-z_$][\w$]*)\s*(?:<[^=]*>)?\s*="),
+            SymbolKind::Type,
+            1,
+        ),
+        rule(
+            &format!(r"{JS_EXPORT}(?:const\s+)?enum\s+([A-Za-z_$][\w$]*)"),
+            SymbolKind::Enum,
+            1,
+        ),
+        // Arrow functions bound to a name.
+        rule(
+            &format!(
+                r"{JS_EXPORT}(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:\([^)]*\)|[A-Za-z_$][\w$]*)\s*(?::[^=]+)?=>"
+            ),
+            SymbolKind::Function,
+            1,
+        ),
+        rule(
+            r"^\s*export\s+(?:const|let|var)\s+([A-Za-z_$][\w$]*)",
+            SymbolKind::Const,
+            1,
+        ),
+    ]
+});
+
+static GO_RULES: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![
+        rule(r"^func\s+\([^)]*\)\s*([A-Za-z_]\w*)", SymbolKind::Method, 1),
+        rule(r"^func\s+([A-Za-z_]\w*)", SymbolKind::Function, 1),
+        rule(r"^type\s+([A-Za-z_]\w*)\s+struct\b", SymbolKind::Struct, 1),
+        rule(
+            r"^type\s+([A-Za-z_]\w*)\s+interface\b",
+            SymbolKind::Interface,
+            1,
+        ),
+        rule(r"^type\s+([A-Za-z_]\w*)", SymbolKind::Type, 1),
+    ]
+});
+
+static RUST_USE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([\w:]+)").expect("valid use pattern")
+});
+static PY_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:from\s+(\.*[\w.]*)\s+import\b|import\s+([\w.]+(?:\s*,\s*[\w.]+)*))")
+        .expect("valid import pattern")
+});
+static JS_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?:^\s*import\b[^'"]*|^\s*export\b[^'"]*\bfrom\s*|\brequire\(\s*)['"]([^'"]+)['"]"#,
+    )
+    .expect("valid import pattern")
+});
+static GO_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(?:import\s+)?(?:[\w.]+\s+)?"([^"]+)""#).expect("valid import pattern")
+});
+
+/// Extract symbols and imports from `source`.
+pub fn extract(language: Language, source: &str) -> Extracted {
+    let rules: &[Rule] = match language {
+        Language::Rust => &RUST_RULES,
+        Language::Python => &PYTHON_RULES,
+        Language::TypeScript | Language::JavaScript => &JS_RULES,
+        Language::Go => &GO_RULES,
+    };
+
+    let mut out = Extracted::default();
+    let mut in_block_comment = false;
+    let mut in_go_import_block = false;
+
+    for (i, line) in source.lines().enumerate() {
+        let line_no = i as u32 + 1;
+        let trimmed = line.trim_start();
+
+        // C-style block comments (not Python). Good enough for doc blocks.
+        if language != Language::Python {
+            if in_block_comment {
+                if trimmed.contains("*/") {
+                    in_block_comment = false;
+                }
+                continue;
+            }
+            if trimmed.starts_with("/*") {
+                in_block_comment = !trimmed.contains("*/");
+                continue;
+            }
+        }
+        if trimmed.starts_with("//") || (language == Language::Python && trimmed.starts_with('#')) {
+            continue;
+        }
+
+        if let Some(target) = import_target(language, line, &mut in_go_import_block) {
+            out.imports.push(Import {
+                target,
+                line: line_no,
+            });
+            continue;
+        }
+
+        if let Some(rule) = rules.iter().find(|r| r.re.is_match(line)) {
+            if let Some(name) = rule.re.captures(line).and_then(|c| c.get(rule.group)) {
+                out.symbols.push(Symbol {
+                    name: name.as_str().to_string(),
+                    kind: rule.kind,
+                    line: line_no,
+                    signature: signature(line),
+                });
+            }
+        }
+    }
+    out
+}
+
+fn import_target(language: Language, line: &str, in_go_block: &mut bool) -> Option<String> {
+    match language {
+        Language::Rust => RUST_USE
+            .captures(line)
+            .map(|c| c[1].trim_end_matches("::").to_string()),
+        Language::Python => PY_IMPORT.captures(line).map(|c| {
+            c.get(1)
+                .or_else(|| c.get(2))
+                .map(|m| {
+                    // `import a, b` keeps the first module
+                    m.as_str()
+                        .split(',')
+                        .next()
+                        .unwrap_or("")
+                        .trim()
+                        .to_string()
+                })
+                .unwrap_or_default()
+        }),
+        Language::TypeScript | Language::JavaScript => {
+            JS_IMPORT.captures(line).map(|c| c[1].to_string())
+        }
+        Language::Go => {
+            let trimmed = line.trim();
+            if *in_go_block {
+                if trimmed.starts_with(')') {
+                    *in_go_block = false;
+                    return None;
+                }
+                return GO_IMPORT.captures(line).map(|c| c[1].to_string());
+            }
+            if trimmed.starts_with("import (") {
+                *in_go_block = true;
+                return None;
+            }
+            if trimmed.starts_with("import ") {
+                return GO_IMPORT.captures(line).map(|c| c[1].to_string());
+            }
+            None
+        }
+    }
+}
+
+/// The definition line, trimmed of its body opener and capped in length.
+fn signature(line: &str) -> String {
+    let sig = line.trim().trim_end_matches('{').trim_end();
+    crate::util::truncate_str(sig, MAX_SIGNATURE_CHARS).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(e: &Extracted) -> Vec<(&str, SymbolKind)> {
+        e.symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind))
+            .collect()
+    }
+
+    fn targets(e: &Extracted) -> Vec<&str> {
+        e.imports.iter().map(|i| i.target.as_str()).collect()
+    }
+
+    #[test]
+    fn test_extract_rust() {
+        let src = r#"
+use std::sync::Arc;
+use crate::memory::store::{Store, Other};
+
+/// Docs mentioning fn fake() are skipped.
+pub struct Store {
+    conn: Connection,
+}
+
+impl<T: Send> Clone for Wrapper<T> {
+    fn clone(&self) -> Self { todo!() }
+}
+
+impl Store {
+    pub async fn insert(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+pub(crate) const MAX_ROWS: usize = 10;
+pub trait ToolProvider: Send {}
+enum Kind { A }
+mod tests {}
+"#;
+        let e = extract(Language::Rust, src);
+        assert_eq!(
+            names(&e),
+            vec![
+                ("Store", SymbolKind::Struct),
+                ("Wrapper", SymbolKind::Impl),
+                ("clone", SymbolKind::Function),
+                ("Store", SymbolKind::Impl),
+                ("insert", SymbolKind::Function),
+                ("MAX_ROWS", SymbolKind::Const),
+                ("ToolProvider", SymbolKind::Trait),
+                ("Kind", SymbolKind::Enum),
+                ("tests", SymbolKind::Module),
+            ]
+        );
+        assert_eq!(targets(&e), vec!["std::sync::Arc", "crate::memory::store"]);
+        assert_eq!(
+            e.symbols[4].signature,
+            "pub async fn insert(&self) -> anyhow::Result<()>"
+        );
+        assert_eq!(e.symbols[4].line, 15);
+    }
+
+    #[test]
+    fn test_extract_python() {
+        let src = "import os, sys\nfrom .models import User\n\nMAX = 3\n\nclass Repo(Base):\n    def save(self):\n        pass\n\n# def commented(): pass\nasync def main():\n    pass\n";
+        let e = extract(Language::Python, src);
+        assert_eq!(
+            names(&e),
+            vec![
+                ("MAX", SymbolKind::Const),
+                ("Repo", SymbolKind::Class),
+                ("save", SymbolKind::Method),
+                ("main", SymbolKind::Function),
+            ]
+        );
+        assert_eq!(targets(&e), vec!["os", ".models"]);
+    }
+
+    #[test]
+    fn test_extract_typescript() {
+        let src = r#"import { a } from "./utils";
+import React from 'react';
+const fs = require("fs");
+export interface Props { x: number }
+export type Id = string;
+export default class App extends Base {}
+export async function load(id: Id): Promise<void> {}
+export const handler = async (req: Request) => {};
+export const VERSION = "1";
+/*
+function hidden() {}
+*/
+"#;
+        let e = extract(Language::TypeScript, src);
+        assert_eq!(
+            names(&e),
+            vec![
+                ("Props", SymbolKind::Interface),
+                ("Id", SymbolKind::Type),
+                ("App", SymbolKind::Class),
+                ("load", SymbolKind::Function),
+                ("handler", SymbolKind::Function),
+                ("VERSION", SymbolKind::Const),
+            ]
+        );
+        assert_eq!(targets(&e), vec!["./utils", "react", "fs"]);
+    }
+
+    #[test]
+    fn test_extract_go() {
+        let src = "package main\n\nimport (\n\t\"fmt\"\n\tlog \"github.com/x/log\"\n)\nimport \"os\"\n\ntype Server struct {\n}\n\ntype Handler interface {\n}\n\nfunc (s *Server) Start() error {\n}\n\nfunc main() {\n}\n";
+        let e = extract(Language::Go, src);
+        assert_eq!(
+            names(&e),
+            vec![
+                ("Server", SymbolKind::Struct),
+                ("Handler", SymbolKind::Interface),
+                ("Start", SymbolKind::Method),
+                ("main", SymbolKind::Function),
+            ]
+        );
+        assert_eq!(targets(&e), vec!["fmt", "github.com/x/log", "os"]);
+    }
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(
+            Language::from_path(Path::new("src/lib.rs")),
+            Some(Language::Rust)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("web/App.tsx")),
+            Some(Language::TypeScript)
+        );
+        assert_eq!(Language::from_path(Path::new("README.md")), None);
+        assert_eq!(Language::parse(Language::Go.as_str()), Some(Language::Go));
+    }
+}
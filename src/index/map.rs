@@ -0,0 +1,458 @@
+// src/index/map.rs — Ranked, budgeted repository map
+//
+// Scores indexed files against the words of a query (task description plus
+// the latest evaluator findings), adds a small bonus for files many others
+// import, and renders the best ones as `path` + `line: signature` outlines.
+// Symbols the query names exactly also get a short source snippet.
+
+use std::collections::{HashMap, HashSet};
+
+use super::extract::SymbolKind;
+use super::{CodeIndex, IndexedFile, SymbolHit};
+use crate::core::tokenizer::TokenCounter;
+
+/// Most symbols listed under one file.
+const MAX_SYMBOLS_PER_FILE: usize = 12;
+
+/// Most definitions quoted in the snippets section.
+const MAX_SNIPPETS: usize = 3;
+
+/// Longest snippet, in lines.
+const MAX_SNIPPET_LINES: usize = 40;
+
+/// Share of the budget the snippets section may use.
+const SNIPPET_BUDGET_SHARE: f32 = 0.5;
+
+/// Words too common in task descriptions to say anything about a file.
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "that", "this", "from", "into", "when", "then", "than", "should",
+    "would", "could", "make", "add", "fix", "use", "using", "new", "all", "any", "are", "was",
+    "not", "but", "can", "has", "have", "its", "also", "more", "less", "code", "file", "files",
+    "test", "tests", "need", "needs", "instead", "only", "each", "which", "what", "there", "their",
+    "them", "they", "our", "your", "src", "lib", "mod", "impl", "pub", "self", "fn",
+];
+
+/// Words and identifiers pulled from a query.
+#[derive(Debug, Default)]
+pub(crate) struct Terms {
+    /// Lowercased word parts (identifiers split on `_` and case changes).
+    words: HashSet<String>,
+    /// Whole identifiers as written, lowercased, for exact symbol mentions.
+    idents: HashSet<String>,
+}
+
+impl Terms {
+    pub(crate) fn parse(query: &str) -> Self {
+        let mut terms = Terms::default();
+        for token in query.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            if token.len() < 3 || token.chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            let lower = token.to_lowercase();
+            if !STOPWORDS.contains(&lower.as_str()) {
+                terms.idents.insert(lower);
+            }
+            for part in split_identifier(token) {
+                if part.len() >= 3 && !STOPWORDS.contains(&part.as_str()) {
+                    terms.words.insert(part);
+                }
+            }
+        }
+        terms
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// Lowercased parts of an identifier: `insert_task` and `insertTask` both
+/// give `["insert", "task"]`.
+pub(crate) fn split_identifier(ident: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in ident.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower && !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// The module name other files import a file by: its stem, or the parent
+/// directory for `mod.rs` / `__init__.py` / `index.ts`.
+fn module_name(path: &str) -> String {
+    let mut segments = path.rsplit('/');
+    let file = segments.next().unwrap_or(path);
+    let stem = file.split('.').next().unwrap_or(file);
+    match stem {
+        "mod" | "__init__" | "index" | "lib" | "main" => {
+            segments.next().unwrap_or(stem).to_lowercase()
+        }
+        _ => stem.to_lowercase(),
+    }
+}
+
+/// How many import statements across the project name each module.
+fn import_counts(files: &[IndexedFile]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for file in files {
+        for target in &file.imports {
+            let segments: HashSet<String> = target
+                .split([':', '.', '/'])
+                .filter(|s| !s.is_empty())
+                .map(str::to_lowercase)
+                .collect();
+            for segment in segments {
+                *counts.entry(segment).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// A file's relevance to the query, and which of its symbols matched.
+struct Scored<'a> {
+    file: &'a IndexedFile,
+    score: f32,
+    /// Indices into `file.symbols` the query names or overlaps with.
+    matched: Vec<usize>,
+    /// Indices of symbols the query names exactly.
+    exact: Vec<usize>,
+}
+
+fn score_file<'a>(
+    file: &'a IndexedFile,
+    terms: &Terms,
+    imports: &HashMap<String, usize>,
+) -> Scored<'a> {
+    let mut score = 0.0;
+
+    let path_words: HashSet<String> = file
+        .path
+        .split(['/', '.', '-'])
+        .flat_map(split_identifier)
+        .collect();
+    score += 3.0 * terms.words.intersection(&path_words).count() as f32;
+
+    let mut matched = Vec::new();
+    let mut exact = Vec::new();
+    let mut symbol_score = 0.0;
+    for (i, symbol) in file.symbols.iter().enumerate() {
+        if terms.idents.contains(&symbol.name.to_lowercase()) {
+            symbol_score += 5.0;
+            exact.push(i);
+            matched.push(i);
+            continue;
+        }
+        let overlap = split_identifier(&symbol.name)
+            .iter()
+            .filter(|p| terms.words.contains(p.as_str()))
+            .count();
+        if overlap > 0 {
+            symbol_score += overlap as f32;
+            matched.push(i);
+        }
+    }
+    // Many weak matches in one file shouldn't swamp a path or exact match.
+    score += symbol_score.min(15.0);
+
+    let imported = imports.get(&module_name(&file.path)).copied().unwrap_or(0);
+    score += 0.5 * (1.0 + imported as f32).ln();
+
+    Scored {
+        file,
+        score,
+        matched,
+        exact,
+    }
+}
+
+impl CodeIndex {
+    /// Render the repository map for `query` within `budget` tokens.
+    /// Empty when nothing is indexed.
+    pub fn repo_map(
+        &self,
+        query: &str,
+        counter: &dyn TokenCounter,
+        budget: u32,
+    ) -> anyhow::Result<String> {
+        let files = self.load()?;
+        if files.is_empty() || budget == 0 {
+            return Ok(String::new());
+        }
+        let terms = Terms::parse(query);
+        let imports = import_counts(&files);
+
+        let mut ranked: Vec<Scored> = files
+            .iter()
+            .filter(|f| !f.symbols.is_empty())
+            .map(|f| score_file(f, &terms, &imports))
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.file.path.cmp(&b.file.path))
+        });
+
+        let header = "# Repository Map\n\n\
+            Definitions in the project files most relevant to this task \
+            (`line: signature`). Use `find_symbol` and `find_references` to \
+            look up anything not listed.\n\n";
+        let mut used = counter.count(header);
+        if used >= budget {
+            return Ok(String::new());
+        }
+
+        let snippet_budget = ((budget - used) as f32 * SNIPPET_BUDGET_SHARE) as u32;
+        let snippets = if terms.is_empty() {
+            String::new()
+        } else {
+            self.render_snippets(&ranked, counter, snippet_budget)
+        };
+        used += counter.count(&snippets);
+
+        let mut map = String::new();
+        for scored in &ranked {
+            let entry = render_file(scored);
+            let cost = counter.count(&entry);
+            if used + cost > budget {
+                // A smaller entry further down may still fit.
+                continue;
+            }
+            used += cost;
+            map.push_str(&entry);
+        }
+        if map.is_empty() && snippets.is_empty() {
+            return Ok(String::new());
+        }
+
+        let mut out = String::with_capacity(header.len() + map.len() + snippets.len() + 2);
+        out.push_str(header);
+        out.push_str(&map);
+        out.push('\n');
+        out.push_str(&snippets);
+        Ok(out)
+    }
+
+    /// Source of the definitions the query names exactly, best files first.
+    fn render_snippets(
+        &self,
+        ranked: &[Scored],
+        counter: &dyn TokenCounter,
+        budget: u32,
+    ) -> String {
+        let mut out = String::new();
+        let mut used = 0;
+        let mut count = 0;
+
+        'files: for scored in ranked.iter().filter(|s| !s.exact.is_empty()) {
+            let Ok(source) = std::fs::read_to_string(self.root().join(&scored.file.path)) else {
+                continue;
+            };
+            let lines: Vec<&str> = source.lines().collect();
+            for &i in &scored.exact {
+                let symbol = &scored.file.symbols[i];
+                let body = definition_lines(&lines, symbol);
+                if body.is_empty() {
+                    continue;
+                }
+                let snippet = format!(
+                    "### {}:{}\n\n```{}\n{}\n```\n\n",
+                    scored.file.path,
+                    symbol.line,
+                    scored.file.language.fence(),
+                    body.join("\n")
+                );
+                let heading = if out.is_empty() {
+                    "## Relevant Definitions\n\n"
+                } else {
+                    ""
+                };
+                let cost = counter.count(&snippet) + counter.count(heading);
+                if used + cost > budget {
+                    continue;
+                }
+                used += cost;
+                out.push_str(heading);
+                out.push_str(&snippet);
+                count += 1;
+                if count >= MAX_SNIPPETS {
+                    break 'files;
+                }
+            }
+        }
+        out
+    }
+}
+
+/// `path` followed by its most relevant symbols in line order.
+fn render_file(scored: &Scored) -> String {
+    let symbols = &scored.file.symbols;
+    let mut picked: Vec<usize> = scored.matched.clone();
+    // Then types, then other definitions, then methods, up to the cap.
+    let rank = |s: &SymbolHit| match s.kind {
+        k if k.is_type() => 0,
+        SymbolKind::Method => 2,
+        _ => 1,
+    };
+    let mut rest: Vec<usize> = (0..symbols.len()).filter(|i| !picked.contains(i)).collect();
+    rest.sort_by_key(|&i| rank(&symbols[i]));
+    picked.extend(rest);
+    picked.truncate(MAX_SYMBOLS_PER_FILE);
+    picked.sort_by_key(|&i| symbols[i].line);
+
+    let mut out = format!("{}\n", scored.file.path);
+    for i in &picked {
+        let s = &symbols[*i];
+        out.push_str(&format!("  {}: {}\n", s.line, s.signature));
+    }
+    let hidden = symbols.len() - picked.len();
+    if hidden > 0 {
+        out.push_str(&format!("  … {} more\n", hidden));
+    }
+    out
+}
+
+/// The lines of one definition: from its signature to the closing brace (or
+/// the end of the indented block for Python), capped in length.
+fn definition_lines<'a>(lines: &[&'a str], symbol: &SymbolHit) -> Vec<&'a str> {
+    let start = (symbol.line as usize).saturating_sub(1);
+    let Some(first) = lines.get(start) else {
+        return Vec::new();
+    };
+    let indent = first.len() - first.trim_start().len();
+    let braces = !first.trim_end().ends_with(':');
+
+    let mut out = vec![*first];
+    let mut depth: i32 = first.matches('{').count() as i32 - first.matches('}').count() as i32;
+    let mut opened = depth > 0;
+    if braces && (first.trim_end().ends_with(';') || (opened && depth == 0)) {
+        return out;
+    }
+
+    for line in lines.iter().skip(start + 1) {
+        if out.len() >= MAX_SNIPPET_LINES {
+            out.push("    // …");
+            break;
+        }
+        if braces {
+            out.push(line);
+            depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+            opened |= line.contains('{');
+            if opened && depth <= 0 {
+                break;
+            }
+        } else {
+            let trimmed = line.trim_start();
+            if !trimmed.is_empty() && line.len() - trimmed.len() <= indent {
+                break;
+            }
+            out.push(line);
+        }
+    }
+    while out.last().is_some_and(|l| l.trim().is_empty()) {
+        out.pop();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::tokenizer::Heuristic;
+    use crate::index::tests::{project, write};
+    use crate::infra::config::CodeIndexConfig;
+
+    #[test]
+    fn test_split_identifier() {
+        assert_eq!(split_identifier("insert_task"), vec!["insert", "task"]);
+        assert_eq!(split_identifier("insertTask"), vec!["insert", "task"]);
+        assert_eq!(split_identifier("HTTPServer"), vec!["httpserver"]);
+        assert_eq!(split_identifier("ToolProvider"), vec!["tool", "provider"]);
+        assert_eq!(module_name("src/index/mod.rs"), "index");
+        assert_eq!(module_name("web/utils.ts"), "utils");
+    }
+
+    #[test]
+    fn test_repo_map_ranks_relevant_files_first() {
+        let dir = project();
+        let index = CodeIndex::in_memory(dir.path(), &CodeIndexConfig::default()).unwrap();
+        index.update().unwrap();
+
+        let map = index
+            .repo_map(
+                "Make insert_task in the Store reject empty ids",
+                &Heuristic,
+                2_000,
+            )
+            .unwrap();
+        assert!(map.starts_with("# Repository Map"));
+        let store = map.find("src/store.rs\n").unwrap();
+        let types = map.find("src/types.rs\n").unwrap();
+        assert!(store < types, "{map}");
+        assert!(map.contains("  8: pub fn insert_task(&self, task: &Task)"));
+
+        // Exact mentions are quoted in full.
+        assert!(map.contains("## Relevant Definitions"));
+        assert!(map.contains("### src/store.rs:8\n\n```rust\n    pub fn insert_task"));
+        assert!(map.contains("        let _ = task;\n    }\n```"));
+    }
+
+    #[test]
+    fn test_repo_map_respects_budget() {
+        let dir = project();
+        for i in 0..40 {
+            write(
+                &dir.path().join(format!("src/gen/module_{i}.rs")),
+                &format!("pub fn generated_{i}() {{}}\npub struct Generated{i};\n"),
+            );
+        }
+        let index = CodeIndex::in_memory(dir.path(), &CodeIndexConfig::default()).unwrap();
+        index.update().unwrap();
+
+        let full = index.repo_map("store", &Heuristic, 10_000).unwrap();
+        let small = index.repo_map("store", &Heuristic, 200).unwrap();
+        assert!(Heuristic.count(&small) <= 200);
+        assert!(small.len() < full.len());
+        assert!(small.contains("src/store.rs"));
+        assert!(index.repo_map("store", &Heuristic, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_definition_lines_python_block() {
+        let lines = vec![
+            "class Repo:",
+            "    def save(self):",
+            "        return 1",
+            "",
+            "    def load(self):",
+            "        pass",
+        ];
+        let symbol = SymbolHit {
+            path: "repo.py".into(),
+            name: "save".into(),
+            kind: SymbolKind::Method,
+            line: 2,
+            signature: "def save(self):".into(),
+        };
+        assert_eq!(
+            definition_lines(&lines, &symbol),
+            vec!["    def save(self):", "        return 1"]
+        );
+    }
+}
@@ -0,0 +1,647 @@
+// src/index/mod.rs — Local code index
+//
+// Walks the project, extracts symbols and import edges from Rust, Python,
+// TS/JS and Go files (see `extract`), and stores them in the SQLite database
+// alongside memory. Updates are incremental: a file is re-read only when its
+// mtime or size changed, and re-parsed only when its content hash differs.
+// The index backs the repository map in each iteration's prompt (see `map`)
+// and the `find_symbol` / `find_references` tools (see `tools`).
+
+pub mod extract;
+pub mod map;
+pub mod tools;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::UNIX_EPOCH;
+
+use regex::Regex;
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+
+use crate::infra::config::CodeIndexConfig;
+use crate::memory::schema;
+use extract::{Language, SymbolKind};
+
+/// Directories never indexed (hidden directories are skipped as well).
+const SKIP_DIRS: &[&str] = &[
+    "target",
+    "node_modules",
+    "vendor",
+    "dist",
+    "build",
+    "out",
+    "__pycache__",
+    "venv",
+];
+
+/// Result of one incremental update.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexStats {
+    /// Source files currently in the index.
+    pub files: usize,
+    /// Files (re-)parsed by this update.
+    pub parsed: usize,
+    /// Files dropped because they no longer exist.
+    pub removed: usize,
+}
+
+/// A symbol definition returned by `find_symbol`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolHit {
+    pub path: String,
+    pub name: String,
+    pub kind: SymbolKind,
+    pub line: u32,
+    pub signature: String,
+}
+
+/// One line mentioning a name, returned by `find_references`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub path: String,
+    pub line: u32,
+    pub text: String,
+    /// The line is an indexed definition of the name.
+    pub definition: bool,
+}
+
+/// An indexed file with its symbols and imports, as loaded for ranking.
+#[derive(Debug, Clone)]
+pub(crate) struct IndexedFile {
+    pub path: String,
+    pub language: Language,
+    pub symbols: Vec<SymbolHit>,
+    pub imports: Vec<String>,
+}
+
+/// Code index for one project root, stored in the shared database.
+pub struct CodeIndex {
+    root: PathBuf,
+    /// Canonical root path; the key that separates projects in one database.
+    root_key: String,
+    config: CodeIndexConfig,
+    /// `std::sync::Mutex`: every use is a short synchronous query.
+    conn: Mutex<Connection>,
+}
+
+impl CodeIndex {
+    /// Open the index for `root` in the database at `db_path`.
+    pub fn open(db_path: &Path, root: &Path, config: &CodeIndexConfig) -> anyhow::Result<Self> {
+        let conn = Connection::open(db_path)?;
+        conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+        Self::with_connection(conn, root, config)
+    }
+
+    /// Index backed by an in-memory database (for testing).
+    pub fn in_memory(root: &Path, config: &CodeIndexConfig) -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?, root, config)
+    }
+
+    fn with_connection(
+        conn: Connection,
+        root: &Path,
+        config: &CodeIndexConfig,
+    ) -> anyhow::Result<Self> {
+        conn.execute_batch("PRAGMA foreign_keys=ON;")?;
+        schema::run_migrations(&conn)?;
+        let root = root.canonicalize()?;
+        Ok(Self {
+            root_key: root.to_string_lossy().into_owned(),
+            root,
+            config: config.clone(),
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Open and refresh the index for the current directory in the default
+    /// database. None when disabled or when the index can't be opened.
+    pub async fn open_default(config: &CodeIndexConfig) -> Option<Arc<Self>> {
+        if !config.enabled {
+            return None;
+        }
+        let db_path = crate::infra::paths::db_path();
+        if let Some(parent) = db_path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let config = config.clone();
+        let opened =
+            tokio::task::spawn_blocking(move || Self::open(&db_path, Path::new("."), &config))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|r| r);
+        let index = match opened {
+            Ok(index) => Arc::new(index),
+            Err(e) => {
+                tracing::warn!("Code index unavailable: {}", e);
+                return None;
+            }
+        };
+        match index.refresh().await {
+            Ok(stats) => tracing::debug!(
+                files = stats.files,
+                parsed = stats.parsed,
+                removed = stats.removed,
+                "Code index updated"
+            ),
+            Err(e) => tracing::warn!("Code index update failed: {}", e),
+        }
+        Some(index)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn config(&self) -> &CodeIndexConfig {
+        &self.config
+    }
+
+    fn conn(&self) -> anyhow::Result<MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| anyhow::anyhow!("code index lock poisoned"))
+    }
+
+    /// [`update`](Self::update) on the blocking pool. The walk stats every
+    /// file under the root, which can take a while on large repositories.
+    pub async fn refresh(self: &Arc<Self>) -> anyhow::Result<IndexStats> {
+        let index = Arc::clone(self);
+        tokio::task::spawn_blocking(move || index.update()).await?
+    }
+
+    /// Bring the index in line with the files on disk. Blocking; async
+    /// callers use [`refresh`](Self::refresh).
+    pub fn update(&self) -> anyhow::Result<IndexStats> {
+        let found = self.walk();
+        let conn = self.conn()?;
+        let tx = conn.unchecked_transaction()?;
+
+        // path -> (id, mtime_ms, size, hash)
+        let mut known: HashMap<String, (i64, i64, i64, String)> = HashMap::new();
+        {
+            let mut stmt = tx
+                .prepare("SELECT id, path, mtime_ms, size, hash FROM code_files WHERE root = ?1")?;
+            let rows = stmt.query_map(params![self.root_key], |r| {
+                Ok((
+                    r.get::<_, String>(1)?,
+                    (r.get(0)?, r.get(2)?, r.get(3)?, r.get(4)?),
+                ))
+            })?;
+            for row in rows {
+                let (path, entry) = row?;
+                known.insert(path, entry);
+            }
+        }
+
+        let mut stats = IndexStats {
+            files: found.len(),
+            ..Default::default()
+        };
+        let mut seen = HashSet::with_capacity(found.len());
+
+        for file in &found {
+            seen.insert(file.path.as_str());
+            let previous = known.get(&file.path);
+            if previous
+                .is_some_and(|&(_, mtime, size, _)| mtime == file.mtime_ms && size == file.size)
+            {
+                continue;
+            }
+            let Ok(source) = std::fs::read_to_string(self.root.join(&file.path)) else {
+                continue;
+            };
+            let hash = hex::encode(Sha256::digest(source.as_bytes()));
+            if let Some((id, _, _, old_hash)) = previous {
+                if *old_hash == hash {
+                    tx.execute(
+                        "UPDATE code_files SET mtime_ms = ?1, size = ?2 WHERE id = ?3",
+                        params![file.mtime_ms, file.size, id],
+                    )?;
+                    continue;
+                }
+            }
+
+            let extracted = extract::extract(file.language, &source);
+            let file_id: i64 = tx.query_row(
+                "INSERT INTO code_files (root, path, language, hash, mtime_ms, size, lines, indexed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'))
+                 ON CONFLICT(root, path) DO UPDATE SET
+                    language = excluded.language, hash = excluded.hash,
+                    mtime_ms = excluded.mtime_ms, size = excluded.size,
+                    lines = excluded.lines, indexed_at = excluded.indexed_at
+                 RETURNING id",
+                params![
+                    self.root_key,
+                    file.path,
+                    file.language.as_str(),
+                    hash,
+                    file.mtime_ms,
+                    file.size,
+                    source.lines().count() as i64,
+                ],
+                |r| r.get(0),
+            )?;
+            tx.execute(
+                "DELETE FROM code_symbols WHERE file_id = ?1",
+                params![file_id],
+            )?;
+            tx.execute(
+                "DELETE FROM code_imports WHERE file_id = ?1",
+                params![file_id],
+            )?;
+            for symbol in &extracted.symbols {
+                tx.execute(
+                    "INSERT INTO code_symbols (file_id, name, kind, line, signature)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        file_id,
+                        symbol.name,
+                        symbol.kind.as_str(),
+                        symbol.line,
+                        symbol.signature
+                    ],
+                )?;
+            }
+            for import in &extracted.imports {
+                tx.execute(
+                    "INSERT INTO code_imports (file_id, target, line) VALUES (?1, ?2, ?3)",
+                    params![file_id, import.target, import.line],
+                )?;
+            }
+            stats.parsed += 1;
+        }
+
+        for (path, (id, ..)) in &known {
+            if !seen.contains(path.as_str()) {
+                tx.execute("DELETE FROM code_symbols WHERE file_id = ?1", params![id])?;
+                tx.execute("DELETE FROM code_imports WHERE file_id = ?1", params![id])?;
+                tx.execute("DELETE FROM code_files WHERE id = ?1", params![id])?;
+                stats.removed += 1;
+            }
+        }
+
+        tx.commit()?;
+        Ok(stats)
+    }
+
+    /// Source files under the root, in a stable order.
+    fn walk(&self) -> Vec<FoundFile> {
+        let max_size = self.config.max_file_kb.saturating_mul(1024);
+        let mut files = Vec::new();
+        let mut dirs = vec![self.root.clone()];
+
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut entries: Vec<_> = entries.flatten().collect();
+            entries.sort_by_key(|e| e.file_name());
+            // Reverse so subdirectories pop in name order.
+            for entry in entries.into_iter().rev() {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if name.starts_with('.') {
+                    continue;
+                }
+                // Not following symlinks keeps the walk inside the project.
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                let path = entry.path();
+                if file_type.is_dir() {
+                    if !SKIP_DIRS.contains(&name.as_ref()) {
+                        dirs.push(path);
+                    }
+                    continue;
+                }
+                if !file_type.is_file() {
+                    continue;
+                }
+                let Some(language) = Language::from_path(&path) else {
+                    continue;
+                };
+                let Ok(meta) = entry.metadata() else {
+                    continue;
+                };
+                if meta.len() > max_size {
+                    continue;
+                }
+                let mtime_ms = meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_millis() as i64)
+                    .unwrap_or(0);
+                let Ok(rel) = path.strip_prefix(&self.root) else {
+                    continue;
+                };
+                files.push(FoundFile {
+                    path: rel.to_string_lossy().replace('\\', "/"),
+                    language,
+                    mtime_ms,
+                    size: meta.len() as i64,
+                });
+                if files.len() >= self.config.max_files {
+                    tracing::debug!("Code index capped at {} files", self.config.max_files);
+                    return files;
+                }
+            }
+        }
+        files
+    }
+
+    /// Definitions named `name`: exact matches first, then case-insensitive
+    /// substring matches.
+    pub fn find_symbol(
+        &self,
+        name: &str,
+        kind: Option<SymbolKind>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<SymbolHit>> {
+        let pattern = format!(
+            "%{}%",
+            name.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT f.path, s.name, s.kind, s.line, s.signature
+             FROM code_symbols s JOIN code_files f ON f.id = s.file_id
+             WHERE f.root = ?1 AND s.name LIKE ?2 ESCAPE '\\'
+               AND (?3 IS NULL OR s.kind = ?3)
+             ORDER BY s.name = ?4 DESC, lower(s.name) = lower(?4) DESC,
+                      length(s.name), f.path, s.line
+             LIMIT ?5",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                self.root_key,
+                pattern,
+                kind.map(|k| k.as_str()),
+                name,
+                limit as i64
+            ],
+            symbol_hit,
+        )?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Lines in indexed files that mention `name` as a whole word, in path
+    /// order, up to `limit`.
+    pub fn find_references(&self, name: &str, limit: usize) -> anyhow::Result<Vec<Reference>> {
+        static IDENT: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
+            Regex::new(r"^[A-Za-z_$][\w$]*$").expect("valid identifier pattern")
+        });
+        if !IDENT.is_match(name) {
+            anyhow::bail!("'{name}' is not an identifier");
+        }
+        let word = Regex::new(&format!(r"(?:^|[^\w$]){}(?:$|[^\w$])", regex::escape(name)))?;
+
+        let (paths, definitions) = {
+            let conn = self.conn()?;
+            let mut stmt =
+                conn.prepare("SELECT path FROM code_files WHERE root = ?1 ORDER BY path")?;
+            let paths = stmt
+                .query_map(params![self.root_key], |r| r.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut stmt = conn.prepare(
+                "SELECT f.path, s.line FROM code_symbols s JOIN code_files f ON f.id = s.file_id
+                 WHERE f.root = ?1 AND s.name = ?2",
+            )?;
+            let definitions = stmt
+                .query_map(params![self.root_key, name], |r| {
+                    Ok((r.get::<_, String>(0)?, r.get::<_, u32>(1)?))
+                })?
+                .collect::<Result<HashSet<_>, _>>()?;
+            (paths, definitions)
+        };
+
+        let mut refs = Vec::new();
+        for path in paths {
+            let Ok(source) = std::fs::read_to_string(self.root.join(&path)) else {
+                continue;
+            };
+            if !source.contains(name) {
+                continue;
+            }
+            for (i, text) in source.lines().enumerate() {
+                if !word.is_match(text) {
+                    continue;
+                }
+                let line = i as u32 + 1;
+                refs.push(Reference {
+                    definition: definitions.contains(&(path.clone(), line)),
+                    path: path.clone(),
+                    line,
+                    text: crate::util::truncate_str(text.trim(), 200).to_string(),
+                });
+                if refs.len() >= limit {
+                    return Ok(refs);
+                }
+            }
+        }
+        Ok(refs)
+    }
+
+    /// Every indexed file with its symbols and imports.
+    pub(crate) fn load(&self) -> anyhow::Result<Vec<IndexedFile>> {
+        let conn = self.conn()?;
+        let mut files: Vec<IndexedFile> = Vec::new();
+        let mut by_id: HashMap<i64, usize> = HashMap::new();
+
+        let mut stmt = conn
+            .prepare("SELECT id, path, language FROM code_files WHERE root = ?1 ORDER BY path")?;
+        let rows = stmt.query_map(params![self.root_key], |r| {
+            Ok((
+                r.get::<_, i64>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, String>(2)?,
+            ))
+        })?;
+        for row in rows {
+            let (id, path, language) = row?;
+            let Some(language) = Language::parse(&language) else {
+                continue;
+            };
+            by_id.insert(id, files.len());
+            files.push(IndexedFile {
+                path,
+                language,
+                symbols: Vec::new(),
+                imports: Vec::new(),
+            });
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT s.file_id, f.path, s.name, s.kind, s.line, s.signature
+             FROM code_symbols s JOIN code_files f ON f.id = s.file_id
+             WHERE f.root = ?1 ORDER BY s.file_id, s.line",
+        )?;
+        let rows = stmt.query_map(params![self.root_key], |r| {
+            Ok((r.get::<_, i64>(0)?, symbol_hit_at(r, 1)?))
+        })?;
+        for row in rows {
+            let (id, hit) = row?;
+            if let Some(&i) = by_id.get(&id) {
+                files[i].symbols.push(hit);
+            }
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT i.file_id, i.target FROM code_imports i JOIN code_files f ON f.id = i.file_id
+             WHERE f.root = ?1",
+        )?;
+        let rows = stmt.query_map(params![self.root_key], |r| {
+            Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+            let (id, target) = row?;
+            if let Some(&i) = by_id.get(&id) {
+                files[i].imports.push(target);
+            }
+        }
+        Ok(files)
+    }
+}
+
+struct FoundFile {
+    path: String,
+    language: Language,
+    mtime_ms: i64,
+    size: i64,
+}
+
+fn symbol_hit(r: &rusqlite::Row<'_>) -> rusqlite::Result<SymbolHit> {
+    symbol_hit_at(r, 0)
+}
+
+/// Read `path, name, kind, line, signature` starting at column `start`.
+fn symbol_hit_at(r: &rusqlite::Row<'_>, start: usize) -> rusqlite::Result<SymbolHit> {
+    let kind: String = r.get(start + 2)?;
+    Ok(SymbolHit {
+        path: r.get(start)?,
+        name: r.get(start + 1)?,
+        kind: SymbolKind::parse(&kind).unwrap_or(SymbolKind::Function),
+        line: r.get(start + 3)?,
+        signature: r.get(start + 4)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(crate) fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    pub(crate) fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            &root.join("src/store.rs"),
+            "use crate::types::Task;\n\npub struct Store {\n    conn: Connection,\n}\n\nimpl Store {\n    pub fn insert_task(&self, task: &Task) {\n        let _ = task;\n    }\n}\n",
+        );
+        write(
+            &root.join("src/types.rs"),
+            "pub struct Task {\n    pub id: String,\n}\n",
+        );
+        write(
+            &root.join("src/main.rs"),
+            "use crate::store::Store;\nuse crate::types::Task;\n\nfn main() {\n    let store = Store::new();\n    store.insert_task(&Task::default());\n}\n",
+        );
+        write(&root.join("web/app.ts"), "export function render() {}\n");
+        write(&root.join("target/debug/gen.rs"), "fn generated() {}\n");
+        write(&root.join(".hidden/x.py"), "def hidden(): pass\n");
+        write(&root.join("README.md"), "# readme\n");
+        dir
+    }
+
+    #[test]
+    fn test_update_is_incremental() {
+        let dir = project();
+        let index = CodeIndex::in_memory(dir.path(), &CodeIndexConfig::default()).unwrap();
+
+        let stats = index.update().unwrap();
+        assert_eq!(
+            stats,
+            IndexStats {
+                files: 4,
+                parsed: 4,
+                removed: 0
+            }
+        );
+        assert_eq!(index.update().unwrap().parsed, 0);
+
+        // Changed content is re-parsed; deleted files are dropped.
+        write(
+            &dir.path().join("src/types.rs"),
+            "pub struct Task {}\npub enum Status { Done }\n",
+        );
+        std::fs::remove_file(dir.path().join("web/app.ts")).unwrap();
+        let stats = index.update().unwrap();
+        assert_eq!(stats.files, 3);
+        assert_eq!(stats.removed, 1);
+        assert!(stats.parsed <= 1);
+
+        let status = index.find_symbol("Status", None, 10).unwrap();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].path, "src/types.rs");
+        assert!(index.find_symbol("render", None, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_symbol_ranks_exact_first() {
+        let dir = project();
+        let index = CodeIndex::in_memory(dir.path(), &CodeIndexConfig::default()).unwrap();
+        index.update().unwrap();
+
+        let hits = index.find_symbol("Store", None, 10).unwrap();
+        assert_eq!(hits[0].name, "Store");
+        assert_eq!(hits[0].path, "src/store.rs");
+        assert_eq!(hits[0].line, 3);
+
+        let impls = index
+            .find_symbol("Store", Some(SymbolKind::Impl), 10)
+            .unwrap();
+        assert_eq!(impls.len(), 1);
+        assert_eq!(impls[0].line, 7);
+
+        // Substring, case-insensitive, `_` taken literally
+        let hits = index.find_symbol("insert_", None, 10).unwrap();
+        assert_eq!(hits[0].name, "insert_task");
+        assert!(index
+            .find_symbol("insertXtask", None, 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_find_references() {
+        let dir = project();
+        let index = CodeIndex::in_memory(dir.path(), &CodeIndexConfig::default()).unwrap();
+        index.update().unwrap();
+
+        let refs = index.find_references("Task", 50).unwrap();
+        let at: Vec<(&str, u32, bool)> = refs
+            .iter()
+            .map(|r| (r.path.as_str(), r.line, r.definition))
+            .collect();
+        assert_eq!(
+            at,
+            vec![
+                ("src/main.rs", 2, false),
+                ("src/main.rs", 6, false),
+                ("src/store.rs", 1, false),
+                ("src/store.rs", 8, false),
+                ("src/types.rs", 1, true),
+            ]
+        );
+        assert_eq!(index.find_references("Task", 2).unwrap().len(), 2);
+        assert!(index.find_references("a.b", 10).is_err());
+    }
+}
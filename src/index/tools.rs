@@ -0,0 +1,193 @@
+// src/index/tools.rs — Expose the code index to the agent as tools
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::extract::SymbolKind;
+use super::CodeIndex;
+use crate::provider::ToolDef;
+use crate::tools::{ToolEffect, ToolProvider, ToolSource};
+
+pub const FIND_SYMBOL_TOOL: &str = "find_symbol";
+pub const FIND_REFERENCES_TOOL: &str = "find_references";
+
+/// Results returned when the model doesn't ask for a limit.
+const DEFAULT_LIMIT: usize = 30;
+const MAX_LIMIT: usize = 200;
+
+/// `find_symbol` / `find_references` over the project's code index. The
+/// index is refreshed before each call so edits made earlier in the task
+/// are visible.
+pub struct CodeIndexTools {
+    index: Arc<CodeIndex>,
+}
+
+impl CodeIndexTools {
+    pub fn new(index: Arc<CodeIndex>) -> Self {
+        Self { index }
+    }
+}
+
+#[async_trait]
+impl ToolProvider for CodeIndexTools {
+    fn id(&self) -> &str {
+        "code_index"
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::Builtin
+    }
+
+    fn tools(&self) -> Vec<ToolDef> {
+        let kinds = [
+            SymbolKind::Function,
+            SymbolKind::Method,
+            SymbolKind::Struct,
+            SymbolKind::Enum,
+            SymbolKind::Trait,
+            SymbolKind::Impl,
+            SymbolKind::Class,
+            SymbolKind::Interface,
+            SymbolKind::Type,
+            SymbolKind::Const,
+            SymbolKind::Module,
+        ]
+        .map(|k| k.as_str());
+        vec![
+            ToolDef {
+                name: FIND_SYMBOL_TOOL.into(),
+                description: "Find where functions, types, classes and other definitions \
+                              are declared in this project. Exact name matches come first, \
+                              then partial matches."
+                    .into(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "description": "Symbol name or part of it" },
+                        "kind": { "type": "string", "enum": kinds, "description": "Only definitions of this kind" },
+                        "limit": { "type": "integer", "description": "Maximum results (default 30)" }
+                    },
+                    "required": ["name"]
+                }),
+            },
+            ToolDef {
+                name: FIND_REFERENCES_TOOL.into(),
+                description: "Find every line in this project's source files that mentions \
+                              an identifier as a whole word, including its definitions."
+                    .into(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "description": "Exact identifier" },
+                        "limit": { "type": "integer", "description": "Maximum results (default 30)" }
+                    },
+                    "required": ["name"]
+                }),
+            },
+        ]
+    }
+
+    fn effect(&self, _tool: &str) -> ToolEffect {
+        ToolEffect::ReadOnly
+    }
+
+    async fn call(&self, tool: &str, args: Value) -> anyhow::Result<String> {
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("missing 'name'"))?;
+        let limit = args
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|n| (n as usize).clamp(1, MAX_LIMIT))
+            .unwrap_or(DEFAULT_LIMIT);
+
+        if let Err(e) = self.index.refresh().await {
+            tracing::debug!("Code index refresh failed: {}", e);
+        }
+
+        match tool {
+            FIND_SYMBOL_TOOL => {
+                let kind = match args.get("kind").and_then(|v| v.as_str()) {
+                    Some(k) => Some(
+                        SymbolKind::parse(k)
+                            .ok_or_else(|| anyhow::anyhow!("unknown symbol kind '{k}'"))?,
+                    ),
+                    None => None,
+                };
+                let hits = self.index.find_symbol(name, kind, limit)?;
+                if hits.is_empty() {
+                    return Ok(format!("No definitions matching '{name}'."));
+                }
+                let mut out = String::new();
+                for hit in &hits {
+                    out.push_str(&format!(
+                        "{}:{}  {}  {}\n",
+                        hit.path, hit.line, hit.kind, hit.signature
+                    ));
+                }
+                if hits.len() == limit {
+                    out.push_str(&format!("(first {limit} results)\n"));
+                }
+                Ok(out)
+            }
+            FIND_REFERENCES_TOOL => {
+                let refs = self.index.find_references(name, limit)?;
+                if refs.is_empty() {
+                    return Ok(format!("No references to '{name}'."));
+                }
+                let mut out = String::new();
+                for r in &refs {
+                    let marker = if r.definition { "  (definition)" } else { "" };
+                    out.push_str(&format!("{}:{}: {}{}\n", r.path, r.line, r.text, marker));
+                }
+                if refs.len() == limit {
+                    out.push_str(&format!("(first {limit} results)\n"));
+                }
+                Ok(out)
+            }
+            _ => anyhow::bail!("unknown code index tool '{tool}'"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::tests::project;
+    use crate::infra::config::CodeIndexConfig;
+
+    #[tokio::test]
+    async fn test_code_index_tools() {
+        let dir = project();
+        let index = CodeIndex::in_memory(dir.path(), &CodeIndexConfig::default()).unwrap();
+        let tools = CodeIndexTools::new(Arc::new(index));
+        assert_eq!(tools.tools().len(), 2);
+
+        // The first call indexes the project.
+        let out = tools
+            .call(FIND_SYMBOL_TOOL, json!({"name": "Store", "kind": "struct"}))
+            .await
+            .unwrap();
+        assert_eq!(out, "src/store.rs:3  struct  pub struct Store\n");
+
+        let out = tools
+            .call(FIND_REFERENCES_TOOL, json!({"name": "Task", "limit": 1}))
+            .await
+            .unwrap();
+        assert_eq!(
+            out,
+            "src/main.rs:2: use crate::types::Task;\n(first 1 results)\n"
+        );
+
+        assert!(tools
+            .call(FIND_SYMBOL_TOOL, json!({"name": "x", "kind": "bogus"}))
+            .await
+            .is_err());
+        assert!(tools.call(FIND_SYMBOL_TOOL, json!({})).await.is_err());
+    }
+}
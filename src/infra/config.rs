@@ -41,6 +41,46 @@ pub struct Config {
     #[serde(default)]
     pub providers: std::collections::HashMap<String, CustomProviderConfig>,
 
+    /// Per-provider or per-model request limits, shared by every task in the
+    /// process. Keys are "provider" or "provider/model".
+    /// Example:
+    /// ```toml
+    /// [limits.anthropic]
+    /// max_concurrent = 4
+    ///
+    /// [limits."openai/gpt-4.1"]
+    /// tokens_per_minute = 30000
+    /// ```
+    #[serde(default)]
+    pub limits: std::collections::HashMap<String, RateLimitConfig>,
+
+    /// Sandbox for processes the agent starts (test runs, linters, tools).
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+
+    /// Secret detection for prompts, tool output, stored memory and
+    /// integration messages.
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+
+    /// Local code index behind the repository map and symbol lookup tools.
+    #[serde(default)]
+    pub code_index: CodeIndexConfig,
+
+    /// Language servers for navigation tools and per-iteration diagnostics.
+    #[serde(default)]
+    pub lsp: LspConfig,
+
+    /// Allow/ask/deny rules for model tool calls. Project rules in
+    /// `.openkoi/permissions.toml` can only make these stricter.
+    #[serde(default)]
+    pub permissions: PermissionsConfig,
+
+    /// Where `openkoi learn install` looks up skills by name, and which
+    /// signing keys it trusts.
+    #[serde(default)]
+    pub skills: SkillsConfig,
+
     /// Daemon-specific settings (optional section in config.toml).
     #[serde(default)]
     pub daemon: Option<DaemonTomlConfig>,
@@ -74,6 +114,18 @@ pub struct CustomProviderConfig {
     pub display_name: Option<String>,
 }
 
+/// Limits for one provider or provider/model. Unset fields fall back to
+/// limits learned from rate-limit response headers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub max_concurrent: Option<u32>,
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub tokens_per_minute: Option<u32>,
+}
+
 fn default_custom_model() -> String {
     "auto".into()
 }
@@ -86,9 +138,12 @@ pub struct ModelsConfig {
     pub embedder: Option<String>,
     /// Preferred small/fast model for cost-sensitive tasks (title generation, summaries, etc.).
     /// If unset, resolved automatically from available providers using a priority list.
+    /// Short tasks start on it when `[models.escalation] start_small` is set.
     pub small_model: Option<String>,
     #[serde(default)]
     pub fallback: FallbackConfig,
+    #[serde(default)]
+    pub escalation: EscalationConfig,
 }
 
 impl Default for ModelsConfig {
@@ -100,6 +155,7 @@ impl Default for ModelsConfig {
             embedder: Some("openai/text-embedding-3-small".into()),
             small_model: None,
             fallback: FallbackConfig::default(),
+            escalation: EscalationConfig::default(),
         }
     }
 }
@@ -110,6 +166,450 @@ pub struct FallbackConfig {
     pub executor: Vec<String>,
 }
 
+/// `[models.escalation]` — when to move the executor to a different model.
+///
+/// ```toml
+/// [models.escalation]
+/// ladder = ["anthropic/claude-sonnet-4", "anthropic/claude-opus-4"]
+/// after_stalled_iterations = 2
+/// on_overflow = true
+/// start_small = true
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationConfig {
+    /// Executor models ordered from cheapest to strongest ("provider/model").
+    #[serde(default)]
+    pub ladder: Vec<String>,
+    /// Climb one rung after this many consecutive non-improving iterations.
+    /// 0 disables stall escalation.
+    #[serde(default = "default_after_stalled_iterations")]
+    pub after_stalled_iterations: u8,
+    /// On context overflow, switch to a catalog model with a larger window
+    /// instead of pruning.
+    #[serde(default = "default_true")]
+    pub on_overflow: bool,
+    /// Start short tasks on `small_model`; the first stall escalation moves
+    /// back to the regular executor.
+    #[serde(default)]
+    pub start_small: bool,
+    /// Task descriptions up to this many characters count as short.
+    #[serde(default = "default_small_task_max_chars")]
+    pub small_task_max_chars: usize,
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self {
+            ladder: Vec::new(),
+            after_stalled_iterations: default_after_stalled_iterations(),
+            on_overflow: true,
+            start_small: false,
+            small_task_max_chars: default_small_task_max_chars(),
+        }
+    }
+}
+
+fn default_after_stalled_iterations() -> u8 {
+    2
+}
+
+fn default_small_task_max_chars() -> usize {
+    200
+}
+
+/// Tool permission rules. The first matching rule decides; calls no rule
+/// matches get `default`.
+///
+/// ```toml
+/// [permissions]
+/// default = "allow"
+///
+/// [[permissions.rules]]
+/// tool = "slack_send"
+/// args = { channel = "#general" }
+/// action = "deny"
+/// reason = "Never post to #general"
+///
+/// [[permissions.rules]]
+/// tool = "*"
+/// source = "mcp"
+/// action = "ask"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PermissionsConfig {
+    #[serde(default)]
+    pub default: PermissionAction,
+    #[serde(default)]
+    pub rules: Vec<PermissionRule>,
+}
+
+/// Community skill sources.
+///
+/// ```toml
+/// [skills]
+/// indexes = ["https://example.com/openkoi-skills.toml"]
+/// trusted_keys = ["<hex ed25519 public key>"]
+/// require_signature = true
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkillsConfig {
+    /// Index files (https://, file:// or a path), searched in order.
+    #[serde(default)]
+    pub indexes: Vec<String>,
+    /// Hex-encoded ed25519 public keys accepted for index signatures.
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+    /// Refuse index skills without a signature from a trusted key.
+    #[serde(default)]
+    pub require_signature: bool,
+    /// How skill variants (`name@variant`) are tried against the skill
+    /// they are a variant of.
+    #[serde(default)]
+    pub experiments: ExperimentConfig,
+    /// Semantic matching of tasks against skill embeddings.
+    #[serde(default)]
+    pub embeddings: SkillEmbeddingConfig,
+}
+
+/// Skill embeddings, computed when skills load and cached by content hash.
+///
+/// ```toml
+/// [skills.embeddings]
+/// bodies = true
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillEmbeddingConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Embed each skill's body along with its description.
+    #[serde(default)]
+    pub bodies: bool,
+}
+
+impl Default for SkillEmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            bodies: false,
+        }
+    }
+}
+
+/// Exploration over skill variants.
+///
+/// ```toml
+/// [skills.experiments]
+/// policy = "epsilon_greedy"
+/// epsilon = 0.2
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentConfig {
+    #[serde(default)]
+    pub policy: ExplorationPolicy,
+    /// Share of tasks that try a random variant under `epsilon_greedy`.
+    #[serde(default = "default_epsilon")]
+    pub epsilon: f64,
+    /// Probability a variant must have of beating the incumbent before
+    /// `openkoi learn promote` accepts it.
+    #[serde(default = "default_promote_confidence")]
+    pub confidence: f64,
+    /// Runs each side needs before a result counts.
+    #[serde(default = "default_min_samples")]
+    pub min_samples: u32,
+}
+
+impl Default for ExperimentConfig {
+    fn default() -> Self {
+        Self {
+            policy: ExplorationPolicy::default(),
+            epsilon: default_epsilon(),
+            confidence: default_promote_confidence(),
+            min_samples: default_min_samples(),
+        }
+    }
+}
+
+fn default_epsilon() -> f64 {
+    0.1
+}
+
+fn default_promote_confidence() -> f64 {
+    0.95
+}
+
+fn default_min_samples() -> u32 {
+    10
+}
+
+/// How a task picks among a skill and its variants.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExplorationPolicy {
+    /// Sample each side's score distribution and take the best draw.
+    #[default]
+    Thompson,
+    /// Take the best average, except for an `epsilon` share of tasks.
+    EpsilonGreedy,
+    /// Always use the incumbent; variants never run.
+    Off,
+}
+
+/// What happens to a tool call, ordered from least to most restrictive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionAction {
+    #[default]
+    Allow,
+    /// Run only after a person approves it.
+    Ask,
+    /// Return an error to the model without running the tool.
+    Deny,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionRule {
+    /// Glob over the tool name the model sees (e.g. "notion_*").
+    #[serde(default = "default_rule_tool")]
+    pub tool: String,
+    /// Tool source: "integration", "mcp", "plugin", "skill" or "builtin".
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Providing integration, MCP server or plugin id (e.g. "slack").
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Argument name → glob over its value. Every listed argument must
+    /// be present and match.
+    #[serde(default)]
+    pub args: std::collections::HashMap<String, String>,
+    pub action: PermissionAction,
+    /// Shown to the model (deny) or the approver (ask).
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+fn default_rule_tool() -> String {
+    "*".into()
+}
+
+/// Process sandbox settings.
+///
+/// ```toml
+/// [sandbox]
+/// mode = "auto"            # auto | bubblewrap | native | off
+/// network = false
+/// build_network = false    # let test runners and linters fetch dependencies
+/// filesystem = ["read:~/.m2", "rw:~/.cache/pip"]
+/// env = ["RUSTFLAGS"]
+/// memory_mb = 4096
+/// timeout_seconds = 600
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    /// "auto" uses bubblewrap when it works here and the native Linux
+    /// sandbox (Landlock, seccomp, namespaces) otherwise, falling back to
+    /// "off" with a warning when neither can start a process; "off" only
+    /// applies the clean environment and resource limits.
+    #[serde(default = "default_sandbox_mode")]
+    pub mode: String,
+    /// Allow network access.
+    #[serde(default)]
+    pub network: bool,
+    /// Allow network access for test runners and linters even when
+    /// `network` is off, so they can fetch missing dependencies. Off by
+    /// default: these commands run agent-written code (build scripts,
+    /// tests, npm scripts).
+    #[serde(default)]
+    pub build_network: bool,
+    /// Extra grants in plugin capability syntax ("read:/path",
+    /// "rw:~/dir/*"). The working directory is always read-write.
+    #[serde(default)]
+    pub filesystem: Vec<String>,
+    /// Environment variables passed through in addition to the basics
+    /// (PATH, HOME, LANG, ...).
+    #[serde(default)]
+    pub env: Vec<String>,
+    /// CPU time limit per process.
+    #[serde(default)]
+    pub cpu_seconds: Option<u64>,
+    /// Address-space limit per process.
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+    /// Wall-clock limit; the process is killed when it expires.
+    #[serde(default = "default_sandbox_timeout")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            mode: default_sandbox_mode(),
+            network: false,
+            build_network: false,
+            filesystem: Vec::new(),
+            env: Vec::new(),
+            cpu_seconds: None,
+            memory_mb: None,
+            timeout_seconds: default_sandbox_timeout(),
+        }
+    }
+}
+
+/// Secret redaction settings.
+///
+/// ```toml
+/// [redaction]
+/// entropy_threshold = 4.5
+/// patterns = ["acme_[a-z0-9]{32}"]
+/// allow = ["EXAMPLE_KEY_FOR_DOCS"]
+/// unmask_for = ["github"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Also flag long random-looking strings that match no known format.
+    #[serde(default = "default_true")]
+    pub entropy: bool,
+    /// Shannon entropy (bits per char) above which a candidate is a secret.
+    #[serde(default = "default_entropy_threshold")]
+    pub entropy_threshold: f64,
+    /// Extra regular expressions to treat as secrets.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Exact values that are never redacted.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// MCP servers, plugins and "skills" whose tools get the real values of
+    /// masked secrets in their arguments. Built-in tools and integrations
+    /// always do; everything else only sees placeholders.
+    #[serde(default)]
+    pub unmask_for: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            entropy: true,
+            entropy_threshold: default_entropy_threshold(),
+            patterns: Vec::new(),
+            allow: Vec::new(),
+            unmask_for: Vec::new(),
+        }
+    }
+}
+
+/// Code index settings.
+///
+/// ```toml
+/// [code_index]
+/// map_tokens = 3000
+/// max_file_kb = 256
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeIndexConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Token budget for the repository map added to each iteration's prompt.
+    #[serde(default = "default_map_tokens")]
+    pub map_tokens: u32,
+    /// Files larger than this are not indexed.
+    #[serde(default = "default_max_file_kb")]
+    pub max_file_kb: u64,
+    /// Stop indexing after this many files.
+    #[serde(default = "default_max_indexed_files")]
+    pub max_files: usize,
+}
+
+impl Default for CodeIndexConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            map_tokens: default_map_tokens(),
+            max_file_kb: default_max_file_kb(),
+            max_files: default_max_indexed_files(),
+        }
+    }
+}
+
+/// Language server settings. Servers are started on first use for projects
+/// whose markers (Cargo.toml, pyproject.toml, go.mod, package.json, ...) are
+/// present and whose binary is on PATH. They run in the `[sandbox]` with
+/// the project as working directory and no network.
+///
+/// ```toml
+/// [lsp]
+/// diagnostics_timeout_ms = 8000
+///
+/// [lsp.servers.python]
+/// command = "basedpyright-langserver"
+/// args = ["--stdio"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LspConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// How long the evaluator waits for fresh diagnostics after a change.
+    #[serde(default = "default_diagnostics_timeout_ms")]
+    pub diagnostics_timeout_ms: u64,
+    /// Timeout for navigation requests (definition, references, ...).
+    #[serde(default = "default_lsp_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+    /// Command overrides keyed by language: rust, python, go, typescript.
+    #[serde(default)]
+    pub servers: std::collections::HashMap<String, LspServerConfig>,
+}
+
+impl Default for LspConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            diagnostics_timeout_ms: default_diagnostics_timeout_ms(),
+            request_timeout_ms: default_lsp_request_timeout_ms(),
+            servers: std::collections::HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LspServerConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+fn default_diagnostics_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_lsp_request_timeout_ms() -> u64 {
+    15_000
+}
+
+fn default_map_tokens() -> u32 {
+    2_000
+}
+
+fn default_max_file_kb() -> u64 {
+    512
+}
+
+fn default_max_indexed_files() -> usize {
+    20_000
+}
+
+fn default_entropy_threshold() -> f64 {
+    4.5
+}
+
+fn default_sandbox_mode() -> String {
+    "auto".into()
+}
+
+fn default_sandbox_timeout() -> u64 {
+    600
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IterationConfig {
     pub max_iterations: u8,
@@ -158,6 +658,13 @@ pub struct ToolLoopConfig {
     pub warning: u32,
     pub critical: u32,
     pub circuit_breaker: u32,
+    /// Most read-only tool calls from one model turn run at once.
+    #[serde(default = "default_max_parallel_tools")]
+    pub max_parallel: usize,
+}
+
+fn default_max_parallel_tools() -> usize {
+    4
 }
 
 impl Default for ToolLoopConfig {
@@ -166,6 +673,7 @@ impl Default for ToolLoopConfig {
             warning: 10,
             critical: 20,
             circuit_breaker: 30,
+            max_parallel: default_max_parallel_tools(),
         }
     }
 }
@@ -293,6 +801,32 @@ pub struct ApiConfig {
     /// Webhook configuration for lifecycle event callbacks.
     #[serde(default)]
     pub webhooks: WebhookConfig,
+
+    /// Profiles served as models by the OpenAI-compatible endpoint, next to
+    /// the built-in "openkoi" profile (plain `[models]` and `[iteration]`).
+    /// Example:
+    /// ```toml
+    /// [api.profiles.quick]
+    /// executor = "openai/gpt-4.1-mini"
+    /// iterations = 1
+    ///
+    /// [api.profiles.careful]
+    /// evaluator = "anthropic/claude-sonnet-4"
+    /// iterations = 5
+    /// quality_threshold = 0.9
+    /// ```
+    #[serde(default)]
+    pub profiles: std::collections::BTreeMap<String, TaskProfile>,
+
+    /// Chat completions run at once (default: 4). Requests past the limit
+    /// are answered with 429.
+    #[serde(default = "default_max_concurrent_completions")]
+    pub max_concurrent_completions: usize,
+
+    /// How long a tool call that needs approval waits for a client to
+    /// answer it (default: 300). It is refused after that.
+    #[serde(default = "default_approval_timeout")]
+    pub approval_timeout_seconds: u64,
 }
 
 impl Default for ApiConfig {
@@ -302,14 +836,42 @@ impl Default for ApiConfig {
             port: 9742,
             token: None,
             webhooks: WebhookConfig::default(),
+            profiles: std::collections::BTreeMap::new(),
+            max_concurrent_completions: default_max_concurrent_completions(),
+            approval_timeout_seconds: default_approval_timeout(),
         }
     }
 }
 
+/// Models and iteration settings for one run, over the config's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskProfile {
+    /// Executor model ("provider/model").
+    #[serde(default)]
+    pub executor: Option<String>,
+    /// Evaluator model ("provider/model").
+    #[serde(default)]
+    pub evaluator: Option<String>,
+    /// Maximum iterations.
+    #[serde(default)]
+    pub iterations: Option<u8>,
+    /// Score at which an iteration is accepted.
+    #[serde(default)]
+    pub quality_threshold: Option<f32>,
+}
+
 fn default_api_port() -> u16 {
     9742
 }
 
+fn default_max_concurrent_completions() -> usize {
+    4
+}
+
+fn default_approval_timeout() -> u64 {
+    300
+}
+
 /// Outbound webhook URLs fired on lifecycle events.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WebhookConfig {
@@ -324,6 +886,10 @@ pub struct WebhookConfig {
     /// URL to POST when a task fails.
     #[serde(default)]
     pub on_task_failed: Option<String>,
+
+    /// URL to POST when a tool call waits for approval.
+    #[serde(default)]
+    pub on_approval_requested: Option<String>,
 }
 
 impl Config {
@@ -366,6 +932,7 @@ mod tests {
         assert_eq!(tl.warning, 10);
         assert_eq!(tl.critical, 20);
         assert_eq!(tl.circuit_breaker, 30);
+        assert_eq!(tl.max_parallel, 4);
     }
 
     #[test]
@@ -489,6 +1056,129 @@ small_model = "anthropic/claude-haiku-3.5"
         assert!(m.small_model.is_none());
     }
 
+    #[test]
+    fn test_parse_models_escalation() {
+        let toml_str = r#"
+[models.escalation]
+ladder = ["anthropic/claude-sonnet-4", "anthropic/claude-opus-4"]
+after_stalled_iterations = 1
+start_small = true
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let esc = &config.models.escalation;
+        assert_eq!(esc.ladder.len(), 2);
+        assert_eq!(esc.after_stalled_iterations, 1);
+        assert!(esc.on_overflow);
+        assert!(esc.start_small);
+        assert_eq!(esc.small_task_max_chars, 200);
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let toml_str = r#"
+[limits.anthropic]
+max_concurrent = 4
+
+[limits."openai/gpt-4.1"]
+tokens_per_minute = 30000
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.limits["anthropic"].max_concurrent, Some(4));
+        assert_eq!(
+            config.limits["openai/gpt-4.1"].tokens_per_minute,
+            Some(30_000)
+        );
+        assert!(config.limits["openai/gpt-4.1"].max_concurrent.is_none());
+    }
+
+    #[test]
+    fn test_parse_permissions() {
+        let toml_str = r##"
+[permissions]
+default = "ask"
+
+[[permissions.rules]]
+tool = "slack_*"
+args = { channel = "#general" }
+action = "deny"
+
+[[permissions.rules]]
+source = "mcp"
+action = "allow"
+"##;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let p = &config.permissions;
+        assert_eq!(p.default, PermissionAction::Ask);
+        assert_eq!(p.rules[0].action, PermissionAction::Deny);
+        assert_eq!(p.rules[0].args["channel"], "#general");
+        assert_eq!(p.rules[1].tool, "*");
+        assert_eq!(p.rules[1].source.as_deref(), Some("mcp"));
+    }
+
+    #[test]
+    fn test_parse_sandbox() {
+        let toml_str = r#"
+[sandbox]
+mode = "bubblewrap"
+filesystem = ["read:~/.m2"]
+memory_mb = 2048
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.sandbox.mode, "bubblewrap");
+        assert!(!config.sandbox.network);
+        assert_eq!(config.sandbox.filesystem, vec!["read:~/.m2"]);
+        assert_eq!(config.sandbox.memory_mb, Some(2048));
+        assert_eq!(config.sandbox.timeout_seconds, 600);
+    }
+
+    #[test]
+    fn test_parse_redaction() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.redaction.enabled);
+        assert!(config.redaction.entropy);
+
+        let toml_str = r#"
+[redaction]
+entropy = false
+patterns = ["acme_[a-z0-9]{32}"]
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(config.redaction.enabled);
+        assert!(!config.redaction.entropy);
+        assert_eq!(config.redaction.entropy_threshold, 4.5);
+        assert_eq!(config.redaction.patterns, vec!["acme_[a-z0-9]{32}"]);
+    }
+
+    #[test]
+    fn test_parse_code_index() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.code_index.enabled);
+        assert_eq!(config.code_index.map_tokens, 2_000);
+
+        let config: Config = toml::from_str("[code_index]\nenabled = false\n").unwrap();
+        assert!(!config.code_index.enabled);
+        assert_eq!(config.code_index.max_file_kb, 512);
+    }
+
+    #[test]
+    fn test_parse_lsp() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.lsp.enabled);
+        assert_eq!(config.lsp.diagnostics_timeout_ms, 5_000);
+
+        let toml_str = r#"
+[lsp.servers.python]
+command = "basedpyright-langserver"
+args = ["--stdio"]
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.lsp.servers["python"].command,
+            "basedpyright-langserver"
+        );
+        assert_eq!(config.lsp.servers["python"].args, vec!["--stdio"]);
+    }
+
     #[test]
     fn test_parse_plugins_toml() {
         let toml_str = r#"
@@ -598,6 +1288,60 @@ on_budget_warning = "https://example.com/hook/budget"
         assert!(api.webhooks.on_task_failed.is_none());
     }
 
+    #[test]
+    fn test_parse_api_profiles() {
+        let toml_str = r#"
+[api.profiles.quick]
+executor = "openai/gpt-4.1-mini"
+iterations = 1
+
+[api.profiles.careful]
+evaluator = "anthropic/claude-sonnet-4"
+quality_threshold = 0.9
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let profiles = config.api.unwrap().profiles;
+        assert_eq!(profiles.keys().collect::<Vec<_>>(), ["careful", "quick"]);
+        assert_eq!(
+            profiles["quick"].executor.as_deref(),
+            Some("openai/gpt-4.1-mini")
+        );
+        assert_eq!(profiles["quick"].iterations, Some(1));
+        assert_eq!(profiles["careful"].quality_threshold, Some(0.9));
+        assert!(profiles["careful"].executor.is_none());
+    }
+
+    #[test]
+    fn test_parse_skills_config() {
+        let toml_str = r#"
+[skills]
+indexes = ["file:///srv/skills/index.toml"]
+require_signature = true
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.skills.indexes, ["file:///srv/skills/index.toml"]);
+        assert!(config.skills.trusted_keys.is_empty());
+        assert!(config.skills.require_signature);
+        assert!(!Config::default().skills.require_signature);
+        assert_eq!(
+            config.skills.experiments.policy,
+            ExplorationPolicy::Thompson
+        );
+
+        let config: Config =
+            toml::from_str("[skills.experiments]\npolicy = \"epsilon_greedy\"\nepsilon = 0.2\n")
+                .unwrap();
+        let experiments = config.skills.experiments;
+        assert_eq!(experiments.policy, ExplorationPolicy::EpsilonGreedy);
+        assert_eq!(experiments.epsilon, 0.2);
+        assert_eq!(experiments.min_samples, 10);
+
+        assert!(Config::default().skills.embeddings.enabled);
+        let config: Config = toml::from_str("[skills.embeddings]\nbodies = true\n").unwrap();
+        assert!(config.skills.embeddings.enabled);
+        assert!(config.skills.embeddings.bodies);
+    }
+
     #[test]
     fn test_config_without_api_section() {
         let toml_str = "";
@@ -6,28 +6,41 @@
 // the orchestrator and delivers the result back via the same integration.
 // Approved patterns with cron schedules are evaluated every 60 seconds.
 
+use std::path::Path;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
 
 use crate::api;
 use crate::api::webhooks;
+use crate::core::escalation::EscalationPolicy;
+use crate::core::instructions::ProjectInstructions;
 use crate::core::orchestrator::{Orchestrator, SessionContext};
 use crate::core::safety::SafetyChecker;
-use crate::core::types::{IterationEngineConfig, TaskInput, TaskResult};
-use crate::infra::config::Config;
+use crate::core::tokenizer;
+use crate::core::types::{IterationEngineConfig, ProgressEvent, TaskInput, TaskResult};
+use crate::index::CodeIndex;
+use crate::infra::config::{Config, TaskProfile};
 use crate::integrations::registry::IntegrationRegistry;
 use crate::integrations::types::RichMessage;
 use crate::integrations::watcher::{WatchConfig, WatchEvent, WatchEventType, WatcherManager};
 use crate::learner::skill_selector::SkillSelector;
+use crate::lsp::LspManager;
 use crate::memory::recall::{self, HistoryRecall};
 use crate::memory::store::Store;
 use crate::patterns::event_logger::{EventLogger, EventType, UsageEvent};
 use crate::provider::roles::ModelRoles;
-use crate::provider::{ModelProvider, ModelRef, ToolDef};
+use crate::provider::{ModelProvider, ModelRef};
+use crate::security::audit::{self, AuditKind, AuditOutcome, AuditRecord};
+use crate::security::redact;
+use crate::skills::embedding::SkillEmbedder;
 use crate::skills::registry::SkillRegistry;
 use crate::soul::loader;
+use crate::tools::ToolRegistry;
 
 /// Daemon configuration.
 #[derive(Debug, Clone)]
@@ -47,7 +60,107 @@ pub struct DaemonContext {
     pub config: Config,
     pub store: Option<Arc<Mutex<Store>>>,
     pub skill_registry: Arc<SkillRegistry>,
-    pub mcp_tools: Vec<ToolDef>,
+    /// Tools available to daemon tasks (MCP servers, integrations, plugins, skills).
+    pub tools: Arc<ToolRegistry>,
+    /// Code index for the daemon's working directory.
+    pub code_index: Option<Arc<CodeIndex>>,
+    /// Language servers for the daemon's working directory.
+    pub lsp: Option<Arc<LspManager>>,
+    /// Tool calls waiting for approval from an API client.
+    pub approvals: Arc<api::approvals::ApprovalQueue>,
+}
+
+impl DaemonContext {
+    /// Run one task through the orchestrator with the daemon's tools,
+    /// memory and skills. `profile` overrides models and iteration
+    /// settings; `history` is earlier conversation for chat-style callers.
+    pub async fn run_task(
+        &self,
+        task: TaskInput,
+        profile: &TaskProfile,
+        history: Option<String>,
+        progress: Option<Box<dyn Fn(ProgressEvent) + Send>>,
+    ) -> anyhow::Result<TaskResult> {
+        let mut engine_config = IterationEngineConfig::from(&self.config.iteration);
+        if let Some(iterations) = profile.iterations {
+            engine_config.max_iterations = iterations;
+        }
+        if let Some(threshold) = profile.quality_threshold {
+            engine_config.quality_threshold = threshold;
+        }
+        let safety = SafetyChecker::from_config(&self.config.iteration, &self.config.safety);
+
+        // Select relevant skills
+        let task_embedding =
+            SkillEmbedder::new(self.provider.clone(), &self.config.skills.embeddings)
+                .embed_task(&task.description, self.skill_registry.all())
+                .await;
+        let selector = SkillSelector::with_experiments(self.config.skills.experiments.clone());
+        let store_guard = self.store.as_ref().and_then(|s| s.lock().ok());
+        let ranked_skills = selector.select(
+            &task.description,
+            task_embedding.as_deref(),
+            task.category.as_deref(),
+            self.skill_registry.all(),
+            store_guard.as_deref(),
+        );
+
+        // Recall from memory
+        let recall = match store_guard.as_deref() {
+            Some(s) => {
+                let token_budget = engine_config.token_budget / 10;
+                let counter =
+                    tokenizer::for_provider_model(self.provider.as_ref(), &self.model_ref.model);
+                recall::recall(
+                    s,
+                    &task.description,
+                    task.category.as_deref(),
+                    token_budget,
+                    counter.as_ref(),
+                )
+                .unwrap_or_default()
+            }
+            None => HistoryRecall::default(),
+        };
+        drop(store_guard);
+
+        let session_ctx = SessionContext {
+            soul: loader::load_soul(),
+            instructions: ProjectInstructions::discover(Path::new(".")),
+            code_index: self.code_index.clone(),
+            lsp: self.lsp.clone(),
+            ranked_skills,
+            recall,
+            tools: self.tools.defs().to_vec(),
+            skill_registry: self.skill_registry.clone(),
+            conversation_history: history,
+        };
+
+        let models = &self.config.models;
+        let escalation = EscalationPolicy::for_provider(models, &self.provider);
+        let mut orchestrator = Orchestrator::new(
+            self.provider.clone(),
+            ModelRoles::from_config(
+                self.model_ref.clone(),
+                profile.executor.as_deref().or(models.executor.as_deref()),
+                profile.evaluator.as_deref().or(models.evaluator.as_deref()),
+                models.planner.as_deref(),
+                models.embedder.as_deref(),
+            ),
+            engine_config,
+            safety,
+            self.skill_registry.clone(),
+            self.store.clone(),
+        )
+        .with_escalation(escalation);
+        if let Some(progress) = progress {
+            orchestrator = orchestrator.with_progress(progress);
+        }
+
+        orchestrator
+            .run(task, &session_ctx, Some(self.tools.as_ref()))
+            .await
+    }
 }
 
 /// Run the daemon loop — polls integrations and dispatches events.
@@ -57,13 +170,24 @@ pub struct DaemonContext {
 pub async fn run_daemon(
     ctx: DaemonContext,
     registry: Arc<IntegrationRegistry>,
+) -> anyhow::Result<()> {
+    // The orchestrator can't move to another thread, so the loop and the
+    // completions it starts run as local tasks on this one.
+    let local = tokio::task::LocalSet::new();
+    local.run_until(daemon_loop(Rc::new(ctx), registry)).await
+}
+
+async fn daemon_loop(
+    ctx: Rc<DaemonContext>,
+    registry: Arc<IntegrationRegistry>,
 ) -> anyhow::Result<()> {
     tracing::info!("OpenKoi daemon starting...");
 
     // Build watcher configs from integration config
     let watch_configs = build_watch_configs(&ctx.config);
 
-    if watch_configs.is_empty() {
+    let api_config = ctx.config.api.clone().unwrap_or_default();
+    if watch_configs.is_empty() && !api_config.enabled {
         tracing::warn!("No integrations configured for watching. Daemon has nothing to do.");
         println!("No integrations configured. Use `openkoi connect <app>` to set up integrations.");
         return Ok(());
@@ -79,12 +203,15 @@ pub async fn run_daemon(
     let mut event_rx = watcher_manager.start(registry.clone());
 
     // ── Start the HTTP API server if enabled ────────────────────────
-    let api_config = ctx.config.api.clone().unwrap_or_default();
     let webhook_config = api_config.webhooks.clone();
 
     // Shared task queue and cancel set — shared between API server and daemon loop.
     let shared_task_queue: Arc<Mutex<Vec<api::TaskRequest>>> = Arc::new(Mutex::new(Vec::new()));
     let shared_cancel_requests: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    // Chat completions from the API each run as their own task, so they
+    // keep streaming while the loop below awaits other work.
+    let (completion_tx, completion_rx) = mpsc::unbounded_channel();
+    tokio::task::spawn_local(run_completions(ctx.clone(), completion_rx));
 
     if api_config.enabled {
         let api_state = api::ApiState {
@@ -92,6 +219,12 @@ pub async fn run_daemon(
             token: api_config.token.clone(),
             task_queue: shared_task_queue.clone(),
             cancel_requests: shared_cancel_requests.clone(),
+            completions: Some(completion_tx),
+            completion_slots: Arc::new(tokio::sync::Semaphore::new(
+                api_config.max_concurrent_completions,
+            )),
+            profiles: api_config.profiles.clone(),
+            approvals: ctx.approvals.clone(),
         };
 
         let api_cfg = api_config.clone();
@@ -125,7 +258,7 @@ pub async fn run_daemon(
                 handle_watch_event(&event, &ctx, registry.clone(), auto_execute, &webhook_config).await;
             }
             _ = cron_interval.tick() => {
-                run_scheduled_patterns(&ctx, &registry, &webhook_config).await;
+                run_scheduled_patterns(&ctx, &webhook_config).await;
             }
             _ = queue_interval.tick() => {
                 // Drain tasks submitted via the HTTP API
@@ -139,7 +272,7 @@ pub async fn run_daemon(
                 for task_req in tasks {
                     let task_id = task_req.task_id.clone().unwrap_or_default();
                     tracing::info!("API task dequeued [{}]: {}", task_id, truncate(&task_req.description, 80));
-                    match execute_daemon_task(&ctx, &registry, &task_req.description, None).await {
+                    match execute_daemon_task(&ctx, &task_req.description, None).await {
                         Ok(result) => {
                             tracing::info!(
                                 "API task [{}] completed: {} iter, score {:.2}",
@@ -199,6 +332,65 @@ pub async fn run_daemon(
     Ok(())
 }
 
+/// Start each chat completion from the API as a local task as it arrives.
+async fn run_completions(
+    ctx: Rc<DaemonContext>,
+    mut jobs: mpsc::UnboundedReceiver<api::openai::CompletionJob>,
+) {
+    while let Some(job) = jobs.recv().await {
+        let ctx = ctx.clone();
+        tokio::task::spawn_local(async move { run_completion(&ctx, job).await });
+    }
+}
+
+/// Run a chat completion from the API, streaming iteration progress back.
+/// Stops early if the client disconnects.
+async fn run_completion(ctx: &DaemonContext, job: api::openai::CompletionJob) {
+    use api::openai::CompletionEvent;
+
+    let description = job.task.description.clone();
+    let events = job.events.clone();
+    let progress = Box::new(move |event: ProgressEvent| {
+        if let Some(line) = event.summary() {
+            let _ = events.send(CompletionEvent::Progress(line));
+        }
+    });
+    let events = job.events.clone();
+    let approvals: api::approvals::ApprovalListener = Arc::new(move |approval| {
+        let _ = events.send(CompletionEvent::Progress(format!(
+            "Waiting for approval of tool '{}': POST /api/v1/approvals/{}",
+            approval.tool, approval.id
+        )));
+    });
+    let run = api::approvals::with_listener(
+        approvals,
+        ctx.run_task(job.task, &job.profile, job.history, Some(progress)),
+    );
+    let result = tokio::select! {
+        result = run => result,
+        _ = job.events.closed() => {
+            tracing::info!("API completion abandoned by client: {}", truncate(&description, 80));
+            return;
+        }
+    };
+
+    if let (Ok(result), Some(s)) = (&result, &ctx.store) {
+        if let Ok(locked) = s.lock() {
+            let _ = EventLogger::new(&locked).log(&UsageEvent {
+                event_type: EventType::Task,
+                channel: "api".into(),
+                description,
+                category: None,
+                skills_used: result.skills_used.clone(),
+                score: Some(result.final_score as f32),
+            });
+        }
+    }
+    let _ = job.events.send(CompletionEvent::Done(
+        result.map(Box::new).map_err(|e| e.to_string()),
+    ));
+}
+
 /// Parsed command from a mention.
 enum DaemonCommand {
     /// Run a task with the given description.
@@ -447,8 +639,7 @@ async fn handle_watch_event(
                         thread_id: tid.map(String::from),
                     };
 
-                    let result =
-                        execute_daemon_task(ctx, &registry, &task_description, Some(notify)).await;
+                    let result = execute_daemon_task(ctx, &task_description, Some(notify)).await;
 
                     match result {
                         Ok(task_result) => {
@@ -536,50 +727,11 @@ struct NotifyTarget {
 /// sent once after 60 seconds of execution.
 async fn execute_daemon_task(
     ctx: &DaemonContext,
-    registry: &IntegrationRegistry,
     task_description: &str,
     notify: Option<NotifyTarget>,
 ) -> anyhow::Result<TaskResult> {
     tracing::info!("Daemon executing task: {}", truncate(task_description, 80));
 
-    let task = TaskInput::new(task_description);
-
-    let engine_config = IterationEngineConfig::from(&ctx.config.iteration);
-    let safety = SafetyChecker::from_config(&ctx.config.iteration, &ctx.config.safety);
-
-    // Load soul
-    let soul = loader::load_soul();
-
-    // Select relevant skills
-    let selector = SkillSelector::new();
-    let store_guard = ctx.store.as_ref().and_then(|s| s.lock().ok());
-    let ranked_skills = selector.select(
-        &task.description,
-        task.category.as_deref(),
-        ctx.skill_registry.all(),
-        store_guard.as_deref(),
-    );
-
-    // Recall from memory
-    let recall = match store_guard.as_deref() {
-        Some(s) => {
-            let token_budget = engine_config.token_budget / 10;
-            recall::recall(s, task_description, task.category.as_deref(), token_budget)
-                .unwrap_or_default()
-        }
-        None => HistoryRecall::default(),
-    };
-    drop(store_guard);
-
-    let session_ctx = SessionContext {
-        soul,
-        ranked_skills,
-        recall,
-        tools: ctx.mcp_tools.clone(),
-        skill_registry: ctx.skill_registry.clone(),
-        conversation_history: None,
-    };
-
     // Progress notification: send "still working..." once after 60s.
     let notified = Arc::new(AtomicBool::new(false));
     let notify_handle = if let Some(ref target) = notify {
@@ -605,29 +757,13 @@ async fn execute_daemon_task(
         None
     };
 
-    let mut orchestrator = Orchestrator::new(
-        ctx.provider.clone(),
-        ModelRoles::from_config(
-            ctx.model_ref.clone(),
-            ctx.config.models.executor.as_deref(),
-            ctx.config.models.evaluator.as_deref(),
-            ctx.config.models.planner.as_deref(),
-            ctx.config.models.embedder.as_deref(),
-        ),
-        engine_config,
-        safety,
-        ctx.skill_registry.clone(),
-        ctx.store.clone(),
-    );
-
-    let integrations = if registry.list().is_empty() {
-        None
-    } else {
-        Some(registry)
-    };
-
-    let result = orchestrator
-        .run(task, &session_ctx, None, integrations)
+    let result = ctx
+        .run_task(
+            TaskInput::new(task_description),
+            &TaskProfile::default(),
+            None,
+            None,
+        )
         .await;
 
     // Cancel the notify timer if the task finished before 60s
@@ -666,6 +802,7 @@ async fn execute_daemon_task(
 ///
 /// If `thread_id` is provided, replies in-thread. Uses `send_rich()` when the
 /// message has structured fields; falls back to plain `send()` for simple text.
+/// Secrets in `content` are redacted before it is posted.
 async fn deliver_result(
     registry: &IntegrationRegistry,
     integration_id: &str,
@@ -689,12 +826,24 @@ async fn deliver_result(
         return;
     };
 
+    let content = &redact::redact(content);
+    let started = Instant::now();
     let result = if let Some(tid) = thread_id {
         let msg = RichMessage::new(content).in_thread(tid.to_string());
         messaging.send_rich(channel, &msg).await
     } else {
         messaging.send(channel, content).await
     };
+    audit_message(
+        integration_id,
+        channel,
+        thread_id,
+        serde_json::json!({ "text": content }),
+        content,
+        started,
+        result.is_ok(),
+    )
+    .await;
 
     match result {
         Ok(_) => {
@@ -712,6 +861,7 @@ async fn deliver_result(
 }
 
 /// Deliver a rich (structured) result message back to the integration channel.
+/// Secrets in the text, title and fields are redacted before it is posted.
 async fn deliver_rich_result(
     registry: &IntegrationRegistry,
     integration_id: &str,
@@ -734,7 +884,34 @@ async fn deliver_rich_result(
         return;
     };
 
-    match messaging.send_rich(channel, msg).await {
+    let msg = &RichMessage {
+        text: redact::redact(&msg.text),
+        title: msg.title.as_deref().map(redact::redact),
+        fields: msg
+            .fields
+            .iter()
+            .map(|(k, v)| (k.clone(), redact::redact(v)))
+            .collect(),
+        ..msg.clone()
+    };
+    let started = Instant::now();
+    let result = messaging.send_rich(channel, msg).await;
+    audit_message(
+        integration_id,
+        channel,
+        msg.thread_id.as_deref(),
+        serde_json::json!({
+            "title": msg.title,
+            "text": msg.text,
+            "fields": msg.fields,
+        }),
+        &msg.text,
+        started,
+        result.is_ok(),
+    )
+    .await;
+
+    match result {
         Ok(_) => {
             tracing::info!("[{}] Rich result delivered to {}", integration_id, channel);
         }
@@ -749,6 +926,35 @@ async fn deliver_rich_result(
     }
 }
 
+/// Write an outbound integration message to the audit log.
+async fn audit_message(
+    integration_id: &str,
+    channel: &str,
+    thread_id: Option<&str>,
+    content: serde_json::Value,
+    text: &str,
+    started: Instant,
+    delivered: bool,
+) {
+    audit::record(AuditRecord {
+        kind: AuditKind::Message,
+        provider: integration_id.to_string(),
+        source: "integration".to_string(),
+        channel: Some(channel.to_string()),
+        thread_id: thread_id.map(str::to_string),
+        args: audit::redact_args(&content),
+        result_sha256: audit::digest(text),
+        duration_ms: started.elapsed().as_millis() as u64,
+        outcome: if delivered {
+            AuditOutcome::Ok
+        } else {
+            AuditOutcome::Error
+        },
+        ..Default::default()
+    })
+    .await;
+}
+
 /// Evaluate approved patterns with cron schedules and execute matching ones.
 ///
 /// This runs every 60 seconds in the daemon loop.  It loads all patterns
@@ -757,7 +963,6 @@ async fn deliver_rich_result(
 /// minute.  If it does, the pattern's description is executed as a task.
 async fn run_scheduled_patterns(
     ctx: &DaemonContext,
-    registry: &IntegrationRegistry,
     webhook_config: &crate::infra::config::WebhookConfig,
 ) {
     let store_arc = match ctx.store.as_ref() {
@@ -809,7 +1014,7 @@ async fn run_scheduled_patterns(
                 pattern.id
             );
 
-            match execute_daemon_task(ctx, registry, description, None).await {
+            match execute_daemon_task(ctx, description, None).await {
                 Ok(result) => {
                     tracing::info!(
                         "Scheduled pattern '{}' completed ({} chars output)",
@@ -8,6 +8,7 @@ pub fn init_logging(level: &str) {
     fmt()
         .with_env_filter(filter)
         .with_target(false)
+        .with_writer(std::io::stderr)
         .compact()
         .init();
 }
@@ -93,6 +93,11 @@ pub fn rhai_scripts_dir() -> PathBuf {
     plugins_dir().join("scripts")
 }
 
+/// Tokenizer definitions (HuggingFace `tokenizer.json`, named by family)
+pub fn tokenizers_dir() -> PathBuf {
+    data_dir().join("tokenizers")
+}
+
 /// Credentials directory
 pub fn credentials_dir() -> PathBuf {
     config_dir().join("credentials")
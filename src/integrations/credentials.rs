@@ -346,6 +346,7 @@ fn credentials_path() -> PathBuf {
 }
 
 /// Validate a token format without making an API call.
+#[allow(clippy::collapsible_match)]
 pub fn validate_token_format(integration: &str, token: &str) -> Result<(), String> {
     match integration {
         "slack" => {
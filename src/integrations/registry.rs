@@ -1,13 +1,16 @@
 // src/integrations/registry.rs — Integration registry
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
+use crate::integrations::tools::IntegrationTools;
 use crate::integrations::types::Integration;
 use crate::provider::ToolDef;
+use crate::tools::ToolProvider;
 
 /// Registry of connected integrations.
 pub struct IntegrationRegistry {
-    integrations: HashMap<String, Box<dyn Integration>>,
+    integrations: HashMap<String, Arc<dyn Integration>>,
 }
 
 impl Default for IntegrationRegistry {
@@ -26,12 +29,12 @@ impl IntegrationRegistry {
     /// Register an integration.
     pub fn register(&mut self, integration: Box<dyn Integration>) {
         let id = integration.id().to_string();
-        self.integrations.insert(id, integration);
+        self.integrations.insert(id, Arc::from(integration));
     }
 
     /// Get an integration by ID.
     pub fn get(&self, id: &str) -> Option<&dyn Integration> {
-        self.integrations.get(id).map(|b| b.as_ref())
+        self.integrations.get(id).map(|i| i.as_ref())
     }
 
     /// List all registered integration IDs.
@@ -46,4 +49,12 @@ impl IntegrationRegistry {
             .flat_map(|i| super::tools::tools_for_integration(i.as_ref()))
             .collect()
     }
+
+    /// One tool provider per connected integration.
+    pub fn tool_providers(&self) -> Vec<Arc<dyn ToolProvider>> {
+        self.integrations
+            .values()
+            .map(|i| Arc::new(IntegrationTools::new(i.clone())) as Arc<dyn ToolProvider>)
+            .collect()
+    }
 }
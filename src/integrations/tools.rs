@@ -1,9 +1,13 @@
 // src/integrations/tools.rs — Auto-register integration tools for the agent
 
-use serde_json::json;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
 
 use crate::integrations::types::Integration;
 use crate::provider::ToolDef;
+use crate::tools::{ToolEffect, ToolProvider, ToolSource};
 
 /// Generate tool definitions from a connected integration.
 pub fn tools_for_integration(integration: &dyn Integration) -> Vec<ToolDef> {
@@ -116,3 +120,199 @@ pub fn tools_for_integration(integration: &dyn Integration) -> Vec<ToolDef> {
 
     tools
 }
+
+/// Exposes a connected integration's tools (`{id}_send`, `{id}_read_doc`, ...)
+/// to the tool registry.
+pub struct IntegrationTools {
+    integration: Arc<dyn Integration>,
+}
+
+impl IntegrationTools {
+    pub fn new(integration: Arc<dyn Integration>) -> Self {
+        Self { integration }
+    }
+}
+
+#[async_trait]
+impl ToolProvider for IntegrationTools {
+    fn id(&self) -> &str {
+        self.integration.id()
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::Integration
+    }
+
+    fn tools(&self) -> Vec<ToolDef> {
+        tools_for_integration(self.integration.as_ref())
+    }
+
+    fn effect(&self, tool: &str) -> ToolEffect {
+        let action = tool
+            .strip_prefix(self.integration.id())
+            .and_then(|t| t.strip_prefix('_'))
+            .unwrap_or(tool);
+        match action {
+            "read" | "search" | "read_doc" | "list_docs" => ToolEffect::ReadOnly,
+            _ => ToolEffect::Mutating,
+        }
+    }
+
+    async fn call(&self, tool: &str, args: Value) -> anyhow::Result<String> {
+        let integration = self.integration.as_ref();
+        let integration_id = integration.id();
+        // Names come from `tools_for_integration`, so the prefix is exact.
+        let action = tool
+            .strip_prefix(integration_id)
+            .and_then(|t| t.strip_prefix('_'))
+            .unwrap_or(tool);
+
+        let result = match action {
+            "send" => {
+                let target = args.get("target").and_then(|v| v.as_str()).unwrap_or("");
+                let message = args.get("message").and_then(|v| v.as_str()).unwrap_or("");
+                if let Some(msg_adapter) = integration.messaging() {
+                    match msg_adapter.send(target, message).await {
+                        Ok(id) => format!("Message sent successfully (id: {id})"),
+                        Err(e) => format!("Error sending message: {e}"),
+                    }
+                } else {
+                    format!("Error: {integration_id} does not support messaging")
+                }
+            }
+            "read" => {
+                let channel = args.get("channel").and_then(|v| v.as_str()).unwrap_or("");
+                let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as u32;
+                if let Some(msg_adapter) = integration.messaging() {
+                    match msg_adapter.history(channel, limit).await {
+                        Ok(messages) => {
+                            let formatted: Vec<String> = messages
+                                .iter()
+                                .map(|m| format!("[{}] {}: {}", m.timestamp, m.sender, m.content))
+                                .collect();
+                            if formatted.is_empty() {
+                                "No messages found.".to_string()
+                            } else {
+                                formatted.join("\n")
+                            }
+                        }
+                        Err(e) => format!("Error reading messages: {e}"),
+                    }
+                } else {
+                    format!("Error: {integration_id} does not support messaging")
+                }
+            }
+            "read_doc" => {
+                let doc_id = args.get("doc_id").and_then(|v| v.as_str()).unwrap_or("");
+                if let Some(doc_adapter) = integration.document() {
+                    match doc_adapter.read(doc_id).await {
+                        Ok(doc) => {
+                            format!("# {}\n\n{}", doc.title, doc.content)
+                        }
+                        Err(e) => format!("Error reading document: {e}"),
+                    }
+                } else {
+                    format!("Error: {integration_id} does not support documents")
+                }
+            }
+            "write_doc" => {
+                let doc_id = args.get("doc_id").and_then(|v| v.as_str()).unwrap_or("");
+                let content = args.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                if let Some(doc_adapter) = integration.document() {
+                    match doc_adapter.write(doc_id, content).await {
+                        Ok(()) => "Document updated successfully.".to_string(),
+                        Err(e) => format!("Error writing document: {e}"),
+                    }
+                } else {
+                    format!("Error: {integration_id} does not support documents")
+                }
+            }
+            "search" => {
+                let query = args.get("query").and_then(|v| v.as_str()).unwrap_or("");
+                // Try messaging search first, then document search
+                if let Some(msg_adapter) = integration.messaging() {
+                    match msg_adapter.search(query).await {
+                        Ok(messages) => {
+                            let formatted: Vec<String> = messages
+                                .iter()
+                                .map(|m| format!("[{}] {}: {}", m.channel, m.sender, m.content))
+                                .collect();
+                            if formatted.is_empty() {
+                                "No messages found.".to_string()
+                            } else {
+                                formatted.join("\n")
+                            }
+                        }
+                        Err(e) => format!("Search error: {e}"),
+                    }
+                } else if let Some(doc_adapter) = integration.document() {
+                    match doc_adapter.search(query).await {
+                        Ok(refs) => {
+                            let formatted: Vec<String> = refs
+                                .iter()
+                                .map(|r| {
+                                    format!(
+                                        "- {} (id: {}{})",
+                                        r.title,
+                                        r.id,
+                                        r.url
+                                            .as_ref()
+                                            .map(|u| format!(", url: {u}"))
+                                            .unwrap_or_default()
+                                    )
+                                })
+                                .collect();
+                            if formatted.is_empty() {
+                                "No documents found.".to_string()
+                            } else {
+                                formatted.join("\n")
+                            }
+                        }
+                        Err(e) => format!("Search error: {e}"),
+                    }
+                } else {
+                    format!("Error: {integration_id} does not support search")
+                }
+            }
+            "list_docs" => {
+                let folder = args.get("folder").and_then(|v| v.as_str());
+                if let Some(doc_adapter) = integration.document() {
+                    match doc_adapter.list(folder).await {
+                        Ok(refs) => {
+                            let formatted: Vec<String> = refs
+                                .iter()
+                                .map(|r| format!("- {} (id: {})", r.title, r.id))
+                                .collect();
+                            if formatted.is_empty() {
+                                "No documents found.".to_string()
+                            } else {
+                                formatted.join("\n")
+                            }
+                        }
+                        Err(e) => format!("Error listing documents: {e}"),
+                    }
+                } else {
+                    format!("Error: {integration_id} does not support documents")
+                }
+            }
+            "create_doc" => {
+                let title = args
+                    .get("title")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Untitled");
+                let content = args.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                if let Some(doc_adapter) = integration.document() {
+                    match doc_adapter.create(title, content).await {
+                        Ok(id) => format!("Document created successfully (id: {id})"),
+                        Err(e) => format!("Error creating document: {e}"),
+                    }
+                } else {
+                    format!("Error: {integration_id} does not support documents")
+                }
+            }
+            _ => anyhow::bail!("unknown {integration_id} tool '{tool}'"),
+        };
+
+        Ok(result)
+    }
+}
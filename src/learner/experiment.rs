@@ -0,0 +1,563 @@
+// src/learner/experiment.rs — Skill variant experiments
+//
+// A skill named `base@variant` (e.g. `release-notes@v2`) is a variant of
+// `base`. When a task ranks a skill together with its variants, only one
+// of them runs, chosen by the exploration policy from their
+// `skill_effectiveness` rows for the task's category. Each task records
+// the skill it ran, and `report` compares every variant with its
+// incumbent so a clear winner can be promoted.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use crate::infra::config::{ExperimentConfig, ExplorationPolicy};
+use crate::memory::store::{SkillEffectivenessRow, Store};
+use crate::skills::registry::SkillRegistry;
+use crate::skills::types::{SkillEntry, SkillKind};
+
+/// Category runs are recorded under when a task has none.
+pub const GENERAL_CATEGORY: &str = "general";
+
+/// Category name of the comparison pooled over every category.
+pub const ALL_CATEGORIES: &str = "all";
+
+/// Split `base@variant` into its parts.
+pub fn split_variant(name: &str) -> (&str, Option<&str>) {
+    match name.split_once('@') {
+        Some((base, variant)) if !base.is_empty() && !variant.is_empty() => (base, Some(variant)),
+        _ => (name, None),
+    }
+}
+
+/// The skill a name is a variant of (the name itself for incumbents).
+pub fn base_name(name: &str) -> &str {
+    split_variant(name).0
+}
+
+/// The effectiveness category for a task.
+pub fn category_key(category: Option<&str>) -> &str {
+    category.unwrap_or(GENERAL_CATEGORY)
+}
+
+/// One side of an experiment: a skill and how it has scored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arm {
+    pub name: String,
+    pub avg_score: f64,
+    pub samples: u32,
+}
+
+impl Arm {
+    pub fn new(name: impl Into<String>, avg_score: f64, samples: u32) -> Self {
+        Self {
+            name: name.into(),
+            avg_score,
+            samples,
+        }
+    }
+
+    /// An arm from the effectiveness rows; `category` of `ALL_CATEGORIES`
+    /// pools every category, weighted by samples.
+    pub fn from_rows(name: &str, category: &str, rows: &[SkillEffectivenessRow]) -> Self {
+        let (total, samples) = rows
+            .iter()
+            .filter(|r| r.skill_name == name)
+            .filter(|r| category == ALL_CATEGORIES || r.task_category == category)
+            .fold((0.0, 0u32), |(total, samples), r| {
+                let n = r.sample_count.max(0) as u32;
+                (total + r.avg_score * n as f64, samples + n)
+            });
+        let avg_score = if samples > 0 {
+            total / samples as f64
+        } else {
+            0.0
+        };
+        Self::new(name, avg_score, samples)
+    }
+
+    /// Beta posterior over the score, from a uniform prior.
+    fn posterior(&self) -> (f64, f64) {
+        let n = self.samples as f64;
+        let successes = self.avg_score.clamp(0.0, 1.0) * n;
+        (1.0 + successes, 1.0 + n - successes)
+    }
+}
+
+/// Pick the arm a task should run. The incumbent is expected first; it is
+/// always the choice when exploration is off.
+pub fn choose(config: &ExperimentConfig, arms: &[Arm], rng: &mut Rng) -> usize {
+    if arms.len() < 2 {
+        return 0;
+    }
+    match config.policy {
+        ExplorationPolicy::Off => 0,
+        ExplorationPolicy::EpsilonGreedy => {
+            if rng.next_f64() < config.epsilon {
+                return (rng.next_u64() % arms.len() as u64) as usize;
+            }
+            // Untried arms look perfect, so each one runs at least once.
+            argmax(
+                arms.iter()
+                    .map(|arm| if arm.samples == 0 { 1.0 } else { arm.avg_score }),
+            )
+        }
+        ExplorationPolicy::Thompson => argmax(arms.iter().map(|arm| {
+            let (a, b) = arm.posterior();
+            rng.beta(a, b)
+        })),
+    }
+}
+
+/// Index of the largest value; the first one on ties.
+fn argmax(values: impl Iterator<Item = f64>) -> usize {
+    values
+        .enumerate()
+        .fold(
+            (0, f64::MIN),
+            |best, (i, v)| if v > best.1 { (i, v) } else { best },
+        )
+        .0
+}
+
+/// Probability that `variant` scores higher than `incumbent`, from a normal
+/// approximation of their Beta posteriors.
+pub fn probability_better(variant: &Arm, incumbent: &Arm) -> f64 {
+    let moments = |arm: &Arm| {
+        let (a, b) = arm.posterior();
+        let mean = a / (a + b);
+        let var = a * b / ((a + b).powi(2) * (a + b + 1.0));
+        (mean, var)
+    };
+    let (mv, vv) = moments(variant);
+    let (mi, vi) = moments(incumbent);
+    normal_cdf((mv - mi) / (vv + vi).sqrt())
+}
+
+/// Standard normal CDF (Abramowitz and Stegun 7.1.26).
+fn normal_cdf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs() / std::f64::consts::SQRT_2);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-(x * x) / 2.0).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+/// What the numbers say about a variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Not enough runs, or no clear difference yet.
+    Collecting,
+    /// The variant beats the incumbent with the configured confidence.
+    Promote,
+    /// The incumbent beats the variant with the configured confidence.
+    KeepIncumbent,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Verdict::Collecting => "collecting",
+            Verdict::Promote => "promote",
+            Verdict::KeepIncumbent => "keep incumbent",
+        })
+    }
+}
+
+/// A variant against its incumbent in one category.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub category: String,
+    pub incumbent: Arm,
+    pub variant: Arm,
+    /// Probability the variant is better.
+    pub probability: f64,
+    pub verdict: Verdict,
+}
+
+impl Comparison {
+    pub fn new(category: &str, incumbent: Arm, variant: Arm, config: &ExperimentConfig) -> Self {
+        let probability = probability_better(&variant, &incumbent);
+        let enough =
+            incumbent.samples >= config.min_samples && variant.samples >= config.min_samples;
+        let verdict = if !enough {
+            Verdict::Collecting
+        } else if probability >= config.confidence {
+            Verdict::Promote
+        } else if probability <= 1.0 - config.confidence {
+            Verdict::KeepIncumbent
+        } else {
+            Verdict::Collecting
+        };
+        Self {
+            category: category.to_string(),
+            incumbent,
+            variant,
+            probability,
+            verdict,
+        }
+    }
+}
+
+/// A skill with variants, and how each variant compares.
+#[derive(Debug, Clone)]
+pub struct Experiment {
+    pub base: String,
+    /// Per category, then pooled under `ALL_CATEGORIES`, for each variant.
+    pub comparisons: Vec<Comparison>,
+}
+
+impl Experiment {
+    /// The pooled comparison for one variant.
+    pub fn overall(&self, variant: &str) -> Option<&Comparison> {
+        self.comparisons
+            .iter()
+            .find(|c| c.variant.name == variant && c.category == ALL_CATEGORIES)
+    }
+}
+
+/// Every task skill that has variants, compared against its variants.
+pub fn report(
+    skills: &[SkillEntry],
+    rows: &[SkillEffectivenessRow],
+    config: &ExperimentConfig,
+) -> Vec<Experiment> {
+    let mut variants: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for skill in skills.iter().filter(|s| s.kind == SkillKind::Task) {
+        if let (base, Some(_)) = split_variant(&skill.name) {
+            variants.entry(base).or_default().insert(&skill.name);
+        }
+    }
+
+    variants
+        .into_iter()
+        .map(|(base, names)| {
+            let mut comparisons = Vec::new();
+            for variant in names {
+                let categories: BTreeSet<&str> = rows
+                    .iter()
+                    .filter(|r| r.skill_name == base || r.skill_name == variant)
+                    .map(|r| r.task_category.as_str())
+                    .collect();
+                for category in categories.into_iter().chain([ALL_CATEGORIES]) {
+                    comparisons.push(Comparison::new(
+                        category,
+                        Arm::from_rows(base, category, rows),
+                        Arm::from_rows(variant, category, rows),
+                        config,
+                    ));
+                }
+            }
+            Experiment {
+                base: base.to_string(),
+                comparisons,
+            }
+        })
+        .collect()
+}
+
+/// Make `variant` the skill it is a variant of: its files replace the
+/// incumbent's (which are moved under `archive_dir`), and its
+/// effectiveness becomes the incumbent's. Returns the skill's directory.
+pub fn promote(
+    registry: &SkillRegistry,
+    variant: &str,
+    store: &Store,
+    archive_dir: &Path,
+) -> Result<PathBuf> {
+    let (base, Some(_)) = split_variant(variant) else {
+        bail!("'{variant}' is not a variant (expected name@variant)");
+    };
+    let variant_skill = registry
+        .get_by_name(variant)
+        .with_context(|| format!("No skill named '{variant}'"))?;
+    let variant_dir = registry
+        .skill_dir(variant_skill)
+        .with_context(|| format!("'{variant}' has no directory"))?;
+    let incumbent_dir = match registry.get_by_name(base) {
+        Some(incumbent) => registry.skill_dir(incumbent).with_context(|| {
+            format!("'{base}' is bundled; copy it into a skills directory before promoting")
+        })?,
+        None => variant_dir.with_file_name(base),
+    };
+
+    if incumbent_dir.exists() {
+        let stamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+        std::fs::create_dir_all(archive_dir)?;
+        let archived = archive_dir.join(format!("{base}-{stamp}"));
+        std::fs::rename(&incumbent_dir, &archived).or_else(|_| {
+            copy_dir(&incumbent_dir, &archived)?;
+            std::fs::remove_dir_all(&incumbent_dir)
+        })?;
+    }
+    copy_dir(&variant_dir, &incumbent_dir)?;
+    std::fs::remove_dir_all(&variant_dir)?;
+
+    let skill_md = incumbent_dir.join("SKILL.md");
+    let content = std::fs::read_to_string(&skill_md)?;
+    std::fs::write(&skill_md, rename_skill(&content, base))?;
+
+    store.promote_skill_variant(variant, base)?;
+    Ok(incumbent_dir)
+}
+
+/// Set the frontmatter `name:` of a SKILL.md.
+fn rename_skill(content: &str, name: &str) -> String {
+    let mut in_frontmatter = false;
+    let mut renamed = false;
+    let mut lines = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim() == "---" {
+            in_frontmatter = i == 0;
+        } else if in_frontmatter && !renamed && line.starts_with("name:") {
+            lines.push(format!("name: {name}"));
+            renamed = true;
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    let mut out = lines.join("\n");
+    if content.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Small xorshift64* generator for exploration draws.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub fn from_entropy() -> Self {
+        let mut buf = [0u8; 8];
+        let _ = getrandom::getrandom(&mut buf);
+        Self::new(
+            u64::from_le_bytes(buf) ^ chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64,
+        )
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn normal(&mut self) -> f64 {
+        let u1 = self.next_f64().max(f64::MIN_POSITIVE);
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    /// Gamma(shape, 1) by Marsaglia and Tsang; shape is at least 1 here.
+    fn gamma(&mut self, shape: f64) -> f64 {
+        let d = shape - 1.0 / 3.0;
+        let c = 1.0 / (9.0 * d).sqrt();
+        loop {
+            let x = self.normal();
+            let v = (1.0 + c * x).powi(3);
+            if v <= 0.0 {
+                continue;
+            }
+            let u = self.next_f64();
+            if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+                return d * v;
+            }
+        }
+    }
+
+    pub fn beta(&mut self, a: f64, b: f64) -> f64 {
+        let x = self.gamma(a);
+        let y = self.gamma(b);
+        x / (x + y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(skill: &str, category: &str, avg: f64, n: i32) -> SkillEffectivenessRow {
+        SkillEffectivenessRow {
+            skill_name: skill.into(),
+            task_category: category.into(),
+            avg_score: avg,
+            sample_count: n,
+        }
+    }
+
+    fn config(policy: ExplorationPolicy) -> ExperimentConfig {
+        ExperimentConfig {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_split_variant() {
+        assert_eq!(split_variant("review@v2"), ("review", Some("v2")));
+        assert_eq!(split_variant("review"), ("review", None));
+        assert_eq!(split_variant("review@"), ("review@", None));
+        assert_eq!(base_name("review@v2"), "review");
+        assert_eq!(category_key(None), GENERAL_CATEGORY);
+    }
+
+    #[test]
+    fn test_thompson_prefers_better_arm() {
+        let arms = [Arm::new("review", 0.6, 40), Arm::new("review@v2", 0.9, 40)];
+        let mut rng = Rng::new(7);
+        let cfg = config(ExplorationPolicy::Thompson);
+        let picks = (0..200)
+            .filter(|_| choose(&cfg, &arms, &mut rng) == 1)
+            .count();
+        assert!(picks > 190, "picked the better arm {picks}/200 times");
+
+        // With no data both sides get explored.
+        let fresh = [Arm::new("review", 0.0, 0), Arm::new("review@v2", 0.0, 0)];
+        let picks = (0..200)
+            .filter(|_| choose(&cfg, &fresh, &mut rng) == 1)
+            .count();
+        assert!((60..140).contains(&picks), "picked v2 {picks}/200 times");
+    }
+
+    #[test]
+    fn test_epsilon_greedy_and_off() {
+        let arms = [Arm::new("review", 0.8, 20), Arm::new("review@v2", 0.0, 0)];
+        let mut rng = Rng::new(3);
+        let greedy = ExperimentConfig {
+            epsilon: 0.0,
+            ..config(ExplorationPolicy::EpsilonGreedy)
+        };
+        assert_eq!(choose(&greedy, &arms, &mut rng), 1); // untried first
+
+        let tried = [Arm::new("review", 0.8, 20), Arm::new("review@v2", 0.5, 20)];
+        assert_eq!(choose(&greedy, &tried, &mut rng), 0);
+        assert_eq!(choose(&config(ExplorationPolicy::Off), &arms, &mut rng), 0);
+    }
+
+    #[test]
+    fn test_report_verdicts() {
+        let skills: Vec<SkillEntry> = ["review", "review@v2", "lint"]
+            .iter()
+            .map(|name| SkillEntry {
+                name: name.to_string(),
+                kind: SkillKind::Task,
+                description: String::new(),
+                source: crate::skills::types::SkillSource::UserGlobal,
+                path: None,
+                metadata: Default::default(),
+                embedding: None,
+                approved: true,
+            })
+            .collect();
+        let rows = vec![
+            row("review", "rust", 0.55, 30),
+            row("review@v2", "rust", 0.85, 30),
+            row("review", "sql", 0.7, 3),
+            row("review@v2", "sql", 0.9, 2),
+        ];
+        let experiments = report(&skills, &rows, &ExperimentConfig::default());
+        assert_eq!(experiments.len(), 1);
+        let experiment = &experiments[0];
+        assert_eq!(experiment.base, "review");
+        let categories: Vec<&str> = experiment
+            .comparisons
+            .iter()
+            .map(|c| c.category.as_str())
+            .collect();
+        assert_eq!(categories, ["rust", "sql", ALL_CATEGORIES]);
+        assert_eq!(experiment.comparisons[0].verdict, Verdict::Promote);
+        assert_eq!(experiment.comparisons[1].verdict, Verdict::Collecting);
+
+        let overall = experiment.overall("review@v2").unwrap();
+        assert_eq!(overall.incumbent.samples, 33);
+        assert_eq!(overall.verdict, Verdict::Promote);
+
+        let worse = Comparison::new(
+            "rust",
+            Arm::new("review", 0.9, 50),
+            Arm::new("review@v2", 0.5, 50),
+            &ExperimentConfig::default(),
+        );
+        assert_eq!(worse.verdict, Verdict::KeepIncumbent);
+    }
+
+    #[test]
+    fn test_promote_replaces_incumbent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let skills_dir = tmp.path().join("skills");
+        for (dir, body) in [("review", "old"), ("review@v2", "new")] {
+            std::fs::create_dir_all(skills_dir.join(dir)).unwrap();
+            std::fs::write(
+                skills_dir.join(dir).join("SKILL.md"),
+                format!("---\nname: {dir}\ndescription: d\n---\n{body}\n"),
+            )
+            .unwrap();
+        }
+        let mut registry = SkillRegistry::empty();
+        for name in ["review", "review@v2"] {
+            registry.add(SkillEntry {
+                name: name.into(),
+                kind: SkillKind::Task,
+                description: String::new(),
+                source: crate::skills::types::SkillSource::UserGlobal,
+                path: Some(skills_dir.join(name).join("SKILL.md")),
+                metadata: Default::default(),
+                embedding: None,
+                approved: true,
+            });
+        }
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::memory::schema::run_migrations(&conn).unwrap();
+        let store = Store::new(conn);
+        store
+            .upsert_skill_effectiveness("review", "rust", 0.5)
+            .unwrap();
+        store
+            .upsert_skill_effectiveness("review@v2", "rust", 0.9)
+            .unwrap();
+
+        let archive = tmp.path().join("archive");
+        let dir = promote(&registry, "review@v2", &store, &archive).unwrap();
+        let content = std::fs::read_to_string(dir.join("SKILL.md")).unwrap();
+        assert!(content.starts_with("---\nname: review\n"));
+        assert!(content.contains("new"));
+        assert!(!skills_dir.join("review@v2").exists());
+        assert_eq!(std::fs::read_dir(&archive).unwrap().count(), 1);
+
+        let rows = store.query_all_skill_effectiveness().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].skill_name, "review");
+        assert!((rows[0].avg_score - 0.9).abs() < 1e-9);
+
+        assert!(promote(&registry, "review", &store, &archive).is_err());
+    }
+}
@@ -2,9 +2,14 @@
 
 use std::sync::Arc;
 
+use serde::Deserialize;
+
 use super::types::*;
 use crate::core::types::IterationCycle;
 use crate::memory::store::Store;
+use crate::provider::structured::{
+    chat_structured, extract_json_value, salvage_entries, StructuredError, StructuredOutput,
+};
 use crate::provider::{ChatRequest, Message, ModelProvider};
 
 /// Extracts reusable learnings from completed task iterations.
@@ -144,32 +149,38 @@ impl LearningExtractor {
     async fn llm_extract(&self, cycles: &[IterationCycle]) -> anyhow::Result<Vec<Learning>> {
         let summary = self.summarize_cycles(cycles);
 
-        let response = self
-            .provider
-            .chat(ChatRequest {
-                model: self.model_id.clone(),
-                messages: vec![Message::user(format!(
-                    "Extract 1-3 reusable learnings from this task execution. \
-                     Each learning should be a single sentence that would help \
-                     with similar future tasks. Format each as:\n\
-                     TYPE: heuristic|anti_pattern|preference\n\
-                     CONTENT: the learning\n\
-                     CONFIDENCE: 0.0-1.0\n\n{}",
-                    summary
-                ))],
-                max_tokens: Some(500),
-                temperature: Some(0.3),
-                tools: vec![],
-                system: None,
-            })
-            .await
-            .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-        Ok(parse_llm_learnings(
-            &response.content,
-            &cycles[0].task_id,
-            cycles[0].category.as_deref(),
-        ))
+        let request = ChatRequest {
+            model: self.model_id.clone(),
+            messages: vec![Message::user(format!(
+                "Extract 1-3 reusable learnings from this task execution. \
+                 Each learning should be a single sentence that would help \
+                 with similar future tasks. Respond with a JSON object:\n\
+                 {{\"learnings\": [{{\"type\": \"heuristic|anti_pattern|preference\", \
+                 \"content\": the learning, \"confidence\": 0.0-1.0}}]}}\n\n{}",
+                summary
+            ))],
+            max_tokens: Some(500),
+            temperature: Some(0.3),
+            ..Default::default()
+        };
+
+        let task_id = &cycles[0].task_id;
+        let category = cycles[0].category.as_deref();
+
+        match chat_structured::<LearningReport>(&*self.provider, request).await {
+            Ok(resp) => Ok(resp.value.into_learnings(task_id, category)),
+            Err(StructuredError::Invalid { reason, raw, .. }) => {
+                let report = LearningReport::salvage(&raw).ok_or_else(|| {
+                    anyhow::anyhow!("Learning extraction reply unusable ({})", reason)
+                })?;
+                tracing::debug!(
+                    "Structured learnings invalid ({}), using valid entries",
+                    reason
+                );
+                Ok(report.into_learnings(task_id, category))
+            }
+            Err(StructuredError::Provider(e)) => Err(anyhow::anyhow!("{}", e)),
+        }
     }
 
     fn summarize_cycles(&self, cycles: &[IterationCycle]) -> String {
@@ -194,54 +205,119 @@ impl LearningExtractor {
     }
 }
 
-/// Parse LLM-generated learnings from response text.
-fn parse_llm_learnings(response: &str, source_task: &str, category: Option<&str>) -> Vec<Learning> {
-    let mut learnings = Vec::new();
-    let mut current_type: Option<LearningType> = None;
-    let mut current_content: Option<String> = None;
-    let mut current_confidence: f32 = 0.5;
+/// Learnings returned as schema-validated JSON.
+#[derive(Debug, Deserialize)]
+struct LearningReport {
+    learnings: Vec<LearningEntry>,
+}
 
-    for line in response.lines() {
-        let trimmed = line.trim();
+#[derive(Debug, Deserialize)]
+struct LearningEntry {
+    #[serde(rename = "type")]
+    learning_type: String,
+    content: String,
+    confidence: f32,
+}
 
-        if let Some(type_str) = trimmed.strip_prefix("TYPE:") {
-            // Flush previous
-            if let (Some(lt), Some(content)) = (current_type.take(), current_content.take()) {
-                learnings.push(Learning {
-                    learning_type: lt,
-                    content,
-                    category: category.map(String::from),
-                    confidence: current_confidence,
-                    source_task: source_task.to_string(),
-                });
-            }
+impl LearningReport {
+    /// The usable entries of a reply that failed validation: non-empty
+    /// content, confidence clamped to 0.0-1.0. `None` without a JSON object.
+    fn salvage(raw: &str) -> Option<Self> {
+        let value = extract_json_value(raw)?;
+        let learnings = salvage_entries::<LearningEntry>(&value, "learnings")
+            .into_iter()
+            .filter(|l| !l.content.trim().is_empty())
+            .map(|l| LearningEntry {
+                confidence: l.confidence.clamp(0.0, 1.0),
+                ..l
+            })
+            .collect();
+        Some(Self { learnings })
+    }
 
-            current_type = match type_str.trim().to_lowercase().as_str() {
-                "heuristic" => Some(LearningType::Heuristic),
-                "anti_pattern" | "antipattern" => Some(LearningType::AntiPattern),
-                "preference" => Some(LearningType::Preference),
-                _ => Some(LearningType::Heuristic),
-            };
-            current_confidence = 0.5;
-        } else if let Some(content) = trimmed.strip_prefix("CONTENT:") {
-            current_content = Some(content.trim().to_string());
-        } else if let Some(conf) = trimmed.strip_prefix("CONFIDENCE:") {
-            if let Ok(c) = conf.trim().parse::<f32>() {
-                current_confidence = c.clamp(0.0, 1.0);
+    fn into_learnings(self, source_task: &str, category: Option<&str>) -> Vec<Learning> {
+        self.learnings
+            .into_iter()
+            .map(|l| Learning {
+                learning_type: parse_learning_type(&l.learning_type),
+                content: l.content.trim().to_string(),
+                category: category.map(String::from),
+                confidence: l.confidence,
+                source_task: source_task.to_string(),
+            })
+            .collect()
+    }
+}
+
+impl StructuredOutput for LearningReport {
+    fn schema_name() -> &'static str {
+        "learnings"
+    }
+
+    fn json_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "learnings": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "type": { "type": "string", "enum": ["heuristic", "anti_pattern", "preference"] },
+                            "content": { "type": "string" },
+                            "confidence": { "type": "number" }
+                        },
+                        "required": ["type", "content", "confidence"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["learnings"],
+            "additionalProperties": false
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for l in &self.learnings {
+            if l.content.trim().is_empty() {
+                return Err("learning has empty content".into());
+            }
+            if !(0.0..=1.0).contains(&l.confidence) {
+                return Err(format!(
+                    "confidence {} must be between 0.0 and 1.0",
+                    l.confidence
+                ));
             }
         }
+        Ok(())
     }
+}
 
-    // Flush last
-    if let (Some(lt), Some(content)) = (current_type, current_content) {
-        learnings.push(Learning {
-            learning_type: lt,
-            content,
-            category: category.map(String::from),
-            confidence: current_confidence,
-            source_task: source_task.to_string(),
-        });
+fn parse_learning_type(s: &str) -> LearningType {
+    match s.trim().to_lowercase().as_str() {
+        "anti_pattern" | "antipattern" => LearningType::AntiPattern,
+        "preference" => LearningType::Preference,
+        _ => LearningType::Heuristic,
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    learnings
+    #[test]
+    fn test_salvage_learnings() {
+        let raw = r#"{"learnings": [
+            {"type": "anti_pattern", "content": "Don't unwrap in handlers", "confidence": 1.4},
+            {"type": "heuristic", "content": "  ", "confidence": 0.5},
+            {"type": "preference", "content": "missing confidence"}
+        ]}"#;
+        let learnings = LearningReport::salvage(raw)
+            .unwrap()
+            .into_learnings("task-1", Some("code"));
+        assert_eq!(learnings.len(), 1);
+        assert_eq!(learnings[0].learning_type, LearningType::AntiPattern);
+        assert_eq!(learnings[0].confidence, 1.0);
+        assert!(LearningReport::salvage("TYPE: heuristic").is_none());
+    }
 }
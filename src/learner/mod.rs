@@ -1,6 +1,7 @@
 // src/learner/mod.rs — Learning system
 
 pub mod dedup;
+pub mod experiment;
 pub mod extractor;
 pub mod skill_selector;
 pub mod types;
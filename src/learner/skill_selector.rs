@@ -1,12 +1,20 @@
 // src/learner/skill_selector.rs — Multi-signal skill ranking
 
+use super::experiment::{self, Arm, Rng};
 use super::types::*;
+use crate::infra::config::ExperimentConfig;
+use crate::memory::embeddings::cosine_similarity;
 use crate::memory::store::Store;
 use crate::skills::eligibility::is_eligible;
 use crate::skills::types::{SkillEntry, SkillKind};
 
 /// Selects and ranks skills for a given task based on multiple signals.
-pub struct SkillSelector;
+/// A skill and its variants (`name@variant`) compete for one slot; see
+/// `learner::experiment`.
+pub struct SkillSelector {
+    experiments: ExperimentConfig,
+    seed: Option<u64>,
+}
 
 impl Default for SkillSelector {
     fn default() -> Self {
@@ -16,24 +24,91 @@ impl Default for SkillSelector {
 
 impl SkillSelector {
     pub fn new() -> Self {
-        Self
+        Self::with_experiments(ExperimentConfig::default())
+    }
+
+    pub fn with_experiments(experiments: ExperimentConfig) -> Self {
+        Self {
+            experiments,
+            seed: None,
+        }
+    }
+
+    /// Make exploration draws reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
     }
 
     /// Select and rank skills for a task.
-    /// Takes the task description, optional category, all available skills,
-    /// and optional store for historical effectiveness queries.
+    /// Takes the task description and its embedding (if any), optional
+    /// category, all available skills, and optional store for historical
+    /// effectiveness queries.
     pub fn select(
         &self,
         task_description: &str,
+        task_embedding: Option<&[f32]>,
         task_category: Option<&str>,
         all_skills: &[SkillEntry],
         store: Option<&Store>,
     ) -> Vec<RankedSkill> {
-        let eligible: Vec<&SkillEntry> = all_skills
-            .iter()
-            .filter(|s| s.kind == SkillKind::Task && is_eligible(s))
+        let ranked: Vec<RankedSkill> = self
+            .score_all(
+                task_description,
+                task_embedding,
+                task_category,
+                all_skills,
+                store,
+            )
+            .into_iter()
+            .filter(|rs| {
+                rs.score > 0.1
+                    || rs
+                        .signals
+                        .iter()
+                        .any(|s| matches!(s, Signal::ExplicitRequest))
+            })
             .collect();
 
+        let mut ranked = self.pick_variants(ranked, task_category, store);
+        sort_by_score(&mut ranked);
+        ranked.truncate(5);
+        ranked
+    }
+
+    /// Score every eligible skill, best first, without dropping weak
+    /// matches or picking among variants. Backs `openkoi learn why`.
+    pub fn explain(
+        &self,
+        task_description: &str,
+        task_embedding: Option<&[f32]>,
+        task_category: Option<&str>,
+        all_skills: &[SkillEntry],
+        store: Option<&Store>,
+    ) -> Vec<RankedSkill> {
+        let mut ranked = self.score_all(
+            task_description,
+            task_embedding,
+            task_category,
+            all_skills,
+            store,
+        );
+        sort_by_score(&mut ranked);
+        ranked
+    }
+
+    fn score_all(
+        &self,
+        task_description: &str,
+        task_embedding: Option<&[f32]>,
+        task_category: Option<&str>,
+        all_skills: &[SkillEntry],
+        store: Option<&Store>,
+    ) -> Vec<RankedSkill> {
+        let eligible = all_skills
+            .iter()
+            .filter(|s| s.kind == SkillKind::Task && is_eligible(s));
+
         let mut ranked: Vec<RankedSkill> = Vec::new();
 
         for skill in eligible {
@@ -52,15 +127,20 @@ impl SkillSelector {
                 }
             }
 
-            // Signal 2: semantic similarity (if embeddings available)
-            // Note: task embedding would need to be computed upstream;
-            // for now, skip this signal (will be enabled with embedding integration)
-            let _ = &skill.embedding; // acknowledge field exists
+            // Signal 2: semantic similarity between the task and the
+            // skill's embedded description
+            if let (Some(task), Some(embedding)) = (task_embedding, &skill.embedding) {
+                let similarity = cosine_similarity(task, embedding);
+                if similarity > 0.0 {
+                    signals.push(Signal::SemanticMatch { similarity });
+                }
+            }
 
-            // Signal 3: explicit mention in task description
+            // Signal 3: explicit mention in task description (variants
+            // answer to their base name)
             if task_description
                 .to_lowercase()
-                .contains(&skill.name.to_lowercase())
+                .contains(&experiment::base_name(&skill.name).to_lowercase())
             {
                 signals.push(Signal::ExplicitRequest);
             }
@@ -72,44 +152,108 @@ impl SkillSelector {
                 }
             }
 
-            // Composite score
-            let score = composite_score(&signals);
-            if score > 0.1 || signals.iter().any(|s| matches!(s, Signal::ExplicitRequest)) {
-                ranked.push(RankedSkill {
-                    skill: skill.clone(),
-                    score,
-                    signals,
-                });
-            }
+            ranked.push(RankedSkill {
+                skill: skill.clone(),
+                score: composite_score(&signals),
+                signals,
+            });
         }
-
-        ranked.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-        ranked.truncate(5);
         ranked
     }
 }
 
-fn composite_score(signals: &[Signal]) -> f32 {
-    let mut score = 0.0_f32;
-    for signal in signals {
-        score += match signal {
-            Signal::ExplicitRequest => 1.0,
-            Signal::Effectiveness {
-                avg_score,
-                sample_count,
-                ..
-            } => {
-                let confidence = (*sample_count as f32 / 10.0).min(1.0);
-                avg_score * confidence * 0.4
+fn sort_by_score(ranked: &mut [RankedSkill]) {
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+impl SkillSelector {
+    /// Keep one skill from each group of a skill and its variants, chosen
+    /// by the exploration policy. It takes the group's best score so the
+    /// experiment doesn't change where the skill ranks.
+    fn pick_variants(
+        &self,
+        ranked: Vec<RankedSkill>,
+        task_category: Option<&str>,
+        store: Option<&Store>,
+    ) -> Vec<RankedSkill> {
+        let mut groups: Vec<(String, Vec<RankedSkill>)> = Vec::new();
+        for rs in ranked {
+            let base = experiment::base_name(&rs.skill.name).to_string();
+            match groups.iter_mut().find(|(b, _)| *b == base) {
+                Some((_, group)) => group.push(rs),
+                None => groups.push((base, vec![rs])),
             }
-            Signal::SemanticMatch { similarity } => similarity * 0.3,
-            Signal::RecallSuggestion => 0.2,
-            Signal::UserApproved { confidence } => confidence * 0.3,
+        }
+
+        let category = experiment::category_key(task_category);
+        let mut rng = match self.seed {
+            Some(seed) => Rng::new(seed),
+            None => Rng::from_entropy(),
         };
+        let mut picked = Vec::new();
+        for (base, mut group) in groups {
+            if group.len() == 1 {
+                picked.extend(group);
+                continue;
+            }
+            // Incumbent first, then variants by name
+            group.sort_by(|a, b| {
+                (a.skill.name != base, &a.skill.name).cmp(&(b.skill.name != base, &b.skill.name))
+            });
+            let arms: Vec<Arm> = group
+                .iter()
+                .map(|rs| {
+                    let eff = store
+                        .and_then(|s| s.query_skill_effectiveness(&rs.skill.name, category).ok())
+                        .flatten();
+                    match eff {
+                        Some(eff) => Arm::new(
+                            &rs.skill.name,
+                            eff.avg_score,
+                            eff.sample_count.max(0) as u32,
+                        ),
+                        None => Arm::new(&rs.skill.name, 0.0, 0),
+                    }
+                })
+                .collect();
+            let score = group.iter().map(|rs| rs.score).fold(0.0_f32, f32::max);
+            let arm_count = group.len();
+            let mut chosen =
+                group.swap_remove(experiment::choose(&self.experiments, &arms, &mut rng));
+            chosen.score = score;
+            chosen.signals.push(Signal::Experiment {
+                base,
+                arms: arm_count,
+            });
+            picked.push(chosen);
+        }
+        picked
     }
-    score.min(1.0)
+}
+
+/// What one signal adds to a skill's score (before the cap at 1.0).
+pub fn contribution(signal: &Signal) -> f32 {
+    match signal {
+        Signal::ExplicitRequest => 1.0,
+        Signal::Effectiveness {
+            avg_score,
+            sample_count,
+            ..
+        } => {
+            let confidence = (*sample_count as f32 / 10.0).min(1.0);
+            avg_score * confidence * 0.4
+        }
+        Signal::SemanticMatch { similarity } => similarity * 0.3,
+        Signal::RecallSuggestion => 0.2,
+        Signal::UserApproved { confidence } => confidence * 0.3,
+        Signal::Experiment { .. } => 0.0,
+    }
+}
+
+fn composite_score(signals: &[Signal]) -> f32 {
+    signals.iter().map(contribution).sum::<f32>().min(1.0)
 }
@@ -27,6 +27,8 @@ pub enum Signal {
     RecallSuggestion,
     /// Skill was learned from a user-approved pattern
     UserApproved { confidence: f32 },
+    /// Chosen by the exploration policy from a skill and its variants
+    Experiment { base: String, arms: usize },
 }
 
 /// A learning extracted from task execution.
@@ -1,13 +1,16 @@
 // src/lib.rs — Library root for OpenKoi
 
+pub mod acp;
 pub mod api;
 pub mod auth;
 pub mod cli;
 pub mod core;
 pub mod evaluator;
+pub mod index;
 pub mod infra;
 pub mod integrations;
 pub mod learner;
+pub mod lsp;
 pub mod memory;
 pub mod onboarding;
 pub mod patterns;
@@ -16,5 +19,6 @@ pub mod provider;
 pub mod security;
 pub mod skills;
 pub mod soul;
+pub mod tools;
 pub mod tui;
 pub mod util;
@@ -0,0 +1,563 @@
+// src/lsp/client.rs — JSON-RPC client for one language server
+//
+// Messages are framed with `Content-Length` headers. A reader task routes
+// responses to waiting requests, answers the few server-to-client requests
+// servers insist on (`workspace/configuration`, capability registration,
+// progress tokens), and keeps the latest `publishDiagnostics` per document.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::process::Child;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use super::protocol::{path_to_uri, uri_to_path, Diagnostic};
+use crate::security::sandbox::{self, SandboxCommand};
+
+type Writer = Arc<tokio::sync::Mutex<Box<dyn AsyncWrite + Send + Unpin>>>;
+type Pending = Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// Latest diagnostics for one document.
+#[derive(Debug, Clone, Default)]
+struct Published {
+    /// Value of the client's publish counter when these arrived.
+    generation: u64,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Default)]
+struct DiagnosticStore {
+    counter: AtomicU64,
+    by_path: Mutex<HashMap<PathBuf, Published>>,
+}
+
+/// An open document: the version and text last sent to the server.
+struct OpenDocument {
+    version: i64,
+    text: String,
+}
+
+/// A connected, initialized language server.
+pub struct LspClient {
+    name: String,
+    root: PathBuf,
+    writer: Writer,
+    pending: Pending,
+    next_id: AtomicI64,
+    diagnostics: Arc<DiagnosticStore>,
+    documents: tokio::sync::Mutex<HashMap<PathBuf, OpenDocument>>,
+    request_timeout: Duration,
+    reader: JoinHandle<()>,
+    process: Option<tokio::sync::Mutex<Child>>,
+}
+
+impl LspClient {
+    /// Spawn `command` in the sandbox, offline with `root` as its working
+    /// directory, and initialize it. Servers such as rust-analyzer run
+    /// build scripts and proc-macros from the code being edited.
+    pub async fn spawn(
+        name: &str,
+        command: &str,
+        args: &[String],
+        root: &Path,
+        request_timeout: Duration,
+    ) -> Result<Self> {
+        let mut process = sandbox::spawn(
+            SandboxCommand::new(command)
+                .args(args.iter().cloned())
+                .current_dir(root)
+                .offline(),
+        )
+        .map_err(|e| anyhow!("failed to start {command}: {e:#}"))?;
+        let stdin = process
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("Failed to capture stdin"))?;
+        let stdout = process
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("Failed to capture stdout"))?;
+
+        let mut client = Self::connect(name, stdout, stdin, root, request_timeout).await?;
+        client.process = Some(tokio::sync::Mutex::new(process));
+        Ok(client)
+    }
+
+    /// Initialize a server reachable over `reader`/`writer`.
+    pub async fn connect(
+        name: &str,
+        reader: impl AsyncRead + Send + Unpin + 'static,
+        writer: impl AsyncWrite + Send + Unpin + 'static,
+        root: &Path,
+        request_timeout: Duration,
+    ) -> Result<Self> {
+        let writer: Writer = Arc::new(tokio::sync::Mutex::new(Box::new(writer)));
+        let pending: Pending = Arc::default();
+        let diagnostics: Arc<DiagnosticStore> = Arc::default();
+        let reader = tokio::spawn(read_loop(
+            BufReader::new(reader),
+            writer.clone(),
+            pending.clone(),
+            diagnostics.clone(),
+        ));
+
+        let client = Self {
+            name: name.to_string(),
+            root: root.to_path_buf(),
+            writer,
+            pending,
+            next_id: AtomicI64::new(1),
+            diagnostics,
+            documents: tokio::sync::Mutex::new(HashMap::new()),
+            request_timeout,
+            reader,
+            process: None,
+        };
+        client.initialize().await?;
+        Ok(client)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn initialize(&self) -> Result<()> {
+        let root_uri = path_to_uri(&self.root)?;
+        let folder = self
+            .root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.request(
+            "initialize",
+            json!({
+                "processId": std::process::id(),
+                "rootUri": root_uri,
+                "rootPath": self.root.to_string_lossy(),
+                "workspaceFolders": [{ "uri": root_uri, "name": folder }],
+                "clientInfo": { "name": "openkoi", "version": env!("CARGO_PKG_VERSION") },
+                "capabilities": {
+                    "textDocument": {
+                        "synchronization": { "didSave": true },
+                        "publishDiagnostics": {},
+                        "hover": { "contentFormat": ["markdown", "plaintext"] },
+                        "definition": { "linkSupport": true },
+                        "references": {},
+                        "rename": { "prepareSupport": false }
+                    },
+                    "workspace": {
+                        "workspaceEdit": { "documentChanges": true },
+                        "configuration": true,
+                        "workspaceFolders": true
+                    }
+                }
+            }),
+        )
+        .await?;
+        self.notify("initialized", json!({})).await
+    }
+
+    /// Send a request and wait for its result.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .map_err(|_| anyhow!("lsp pending lock poisoned"))?
+            .insert(id, tx);
+        write_message(
+            &self.writer,
+            &json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }),
+        )
+        .await?;
+
+        let outcome = tokio::time::timeout(self.request_timeout, rx).await;
+        if outcome.is_err() {
+            if let Ok(mut pending) = self.pending.lock() {
+                pending.remove(&id);
+            }
+        }
+        match outcome {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(message))) => Err(anyhow!("{} {}: {}", self.name, method, message)),
+            Ok(Err(_)) => Err(anyhow!("{} exited during {}", self.name, method)),
+            Err(_) => Err(anyhow!(
+                "{} did not answer {} within {}s",
+                self.name,
+                method,
+                self.request_timeout.as_secs()
+            )),
+        }
+    }
+
+    pub async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        write_message(
+            &self.writer,
+            &json!({ "jsonrpc": "2.0", "method": method, "params": params }),
+        )
+        .await
+    }
+
+    /// Send the current on-disk text of `path` to the server (open, change
+    /// and save as needed). Returns whether the server saw new content.
+    pub async fn sync_document(&self, path: &Path, language_id: &str) -> Result<bool> {
+        let text = tokio::fs::read_to_string(path).await?;
+        let uri = path_to_uri(path)?;
+        let mut documents = self.documents.lock().await;
+
+        match documents.get_mut(path) {
+            Some(doc) if doc.text == text => return Ok(false),
+            Some(doc) => {
+                doc.version += 1;
+                doc.text = text.clone();
+                let version = doc.version;
+                self.notify(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": uri, "version": version },
+                        "contentChanges": [{ "text": text }]
+                    }),
+                )
+                .await?;
+            }
+            None => {
+                self.notify(
+                    "textDocument/didOpen",
+                    json!({
+                        "textDocument": {
+                            "uri": uri, "languageId": language_id, "version": 1, "text": text
+                        }
+                    }),
+                )
+                .await?;
+                documents.insert(path.to_path_buf(), OpenDocument { version: 1, text });
+            }
+        }
+        // Some servers (rust-analyzer's cargo check) only re-check on save.
+        self.notify(
+            "textDocument/didSave",
+            json!({ "textDocument": { "uri": uri } }),
+        )
+        .await?;
+        Ok(true)
+    }
+
+    /// Paths of documents opened on this server.
+    pub async fn open_documents(&self) -> Vec<PathBuf> {
+        self.documents.lock().await.keys().cloned().collect()
+    }
+
+    /// Current value of the publish counter; pass to `wait_for_diagnostics`.
+    pub fn diagnostics_generation(&self) -> u64 {
+        self.diagnostics.counter.load(Ordering::SeqCst)
+    }
+
+    /// Wait until every path in `paths` has diagnostics published after
+    /// `since`, or until `timeout`. Returns false on timeout.
+    pub async fn wait_for_diagnostics(
+        &self,
+        paths: &[PathBuf],
+        since: u64,
+        timeout: Duration,
+    ) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let fresh = self.diagnostics.by_path.lock().is_ok_and(|map| {
+                paths
+                    .iter()
+                    .all(|p| map.get(p).is_some_and(|d| d.generation > since))
+            });
+            if fresh {
+                return true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    /// Latest diagnostics for `path` (empty if none were published).
+    pub fn diagnostics_for(&self, path: &Path) -> Vec<Diagnostic> {
+        self.diagnostics
+            .by_path
+            .lock()
+            .ok()
+            .and_then(|map| map.get(path).map(|d| d.diagnostics.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Ask the server to shut down, then stop the process.
+    pub async fn shutdown(&self) {
+        let quick = Duration::from_secs(2);
+        let _ = tokio::time::timeout(quick, self.request("shutdown", Value::Null)).await;
+        let _ = self.notify("exit", Value::Null).await;
+        if let Some(ref process) = self.process {
+            let mut process = process.lock().await;
+            if tokio::time::timeout(quick, process.wait()).await.is_err() {
+                let _ = process.kill().await;
+            }
+        }
+        self.reader.abort();
+    }
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+async fn write_message(writer: &Writer, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    let mut writer = writer.lock().await;
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    writer.write_all(&body).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read one framed message. None at end of stream.
+async fn read_message(reader: &mut (impl AsyncBufRead + Unpin)) -> Result<Option<Value>> {
+    let mut length: Option<usize> = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = Some(value.trim().parse()?);
+            }
+        }
+    }
+    let mut body = vec![0; length.unwrap_or(0)];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+async fn read_loop(
+    mut reader: impl AsyncBufRead + Unpin,
+    writer: Writer,
+    pending: Pending,
+    diagnostics: Arc<DiagnosticStore>,
+) {
+    loop {
+        let message = match read_message(&mut reader).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(e) => {
+                tracing::debug!("Language server sent an unreadable message: {}", e);
+                break;
+            }
+        };
+        let method = message.get("method").and_then(Value::as_str);
+        let id = message.get("id").cloned();
+
+        match (method, id) {
+            // Response to one of our requests
+            (None, Some(id)) => {
+                let Some(id) = id.as_i64() else { continue };
+                let waiter = pending.lock().ok().and_then(|mut p| p.remove(&id));
+                if let Some(waiter) = waiter {
+                    let outcome = match message.get("error") {
+                        Some(error) => Err(error
+                            .get("message")
+                            .and_then(Value::as_str)
+                            .unwrap_or("request failed")
+                            .to_string()),
+                        None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                    };
+                    let _ = waiter.send(outcome);
+                }
+            }
+            // Request from the server: give the minimal answer it needs.
+            (Some(method), Some(id)) => {
+                let result = match method {
+                    "workspace/configuration" => {
+                        let items = message
+                            .pointer("/params/items")
+                            .and_then(Value::as_array)
+                            .map_or(0, Vec::len);
+                        Value::Array(vec![Value::Null; items])
+                    }
+                    _ => Value::Null,
+                };
+                let reply = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                if write_message(&writer, &reply).await.is_err() {
+                    break;
+                }
+            }
+            (Some("textDocument/publishDiagnostics"), None) => {
+                let Some(path) = message
+                    .pointer("/params/uri")
+                    .and_then(Value::as_str)
+                    .and_then(uri_to_path)
+                else {
+                    continue;
+                };
+                let items: Vec<Diagnostic> = message
+                    .pointer("/params/diagnostics")
+                    .and_then(Value::as_array)
+                    .map(|items| {
+                        items
+                            .iter()
+                            .filter_map(|d| Diagnostic::from_json(&path, d))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let generation = diagnostics.counter.fetch_add(1, Ordering::SeqCst) + 1;
+                if let Ok(mut map) = diagnostics.by_path.lock() {
+                    map.insert(
+                        path,
+                        Published {
+                            generation,
+                            diagnostics: items,
+                        },
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Fail whatever is still waiting.
+    if let Ok(mut pending) = pending.lock() {
+        for (_, waiter) in pending.drain() {
+            let _ = waiter.send(Err("language server exited".into()));
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::io::{duplex, DuplexStream};
+
+    /// A scripted language server on the other end of an in-memory pipe.
+    /// `handler` maps (method, params) to a result; documents that are
+    /// opened or changed get one warning per line containing "TODO".
+    pub(crate) fn fake_server(
+        handler: impl Fn(&str, &Value) -> Value + Send + 'static,
+    ) -> (DuplexStream, DuplexStream) {
+        let (client_out, server_in) = duplex(1 << 16);
+        let (server_out, client_in) = duplex(1 << 16);
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(server_in);
+            let writer: Writer = Arc::new(tokio::sync::Mutex::new(Box::new(server_out)));
+            while let Ok(Some(msg)) = read_message(&mut reader).await {
+                let method = msg["method"].as_str().unwrap_or_default().to_string();
+                let params = msg.get("params").cloned().unwrap_or(Value::Null);
+                if let Some(id) = msg.get("id") {
+                    let result = match method.as_str() {
+                        "initialize" => {
+                            // Exercise a server-to-client request first.
+                            let ask = json!({"jsonrpc": "2.0", "id": "cfg", "method": "workspace/configuration", "params": {"items": [{}]}});
+                            write_message(&writer, &ask).await.unwrap();
+                            json!({"capabilities": {}})
+                        }
+                        _ => handler(&method, &params),
+                    };
+                    let reply = json!({"jsonrpc": "2.0", "id": id, "result": result});
+                    write_message(&writer, &reply).await.unwrap();
+                    continue;
+                }
+                let doc = match method.as_str() {
+                    "textDocument/didOpen" => params["textDocument"].clone(),
+                    "textDocument/didChange" => json!({
+                        "uri": params["textDocument"]["uri"],
+                        "text": params["contentChanges"][0]["text"],
+                    }),
+                    _ => continue,
+                };
+                let diagnostics: Vec<Value> = doc["text"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .lines()
+                    .enumerate()
+                    .filter(|(_, l)| l.contains("TODO"))
+                    .map(|(i, _)| json!({
+                        "range": {"start": {"line": i, "character": 0}, "end": {"line": i, "character": 4}},
+                        "severity": 2,
+                        "message": "unfinished work",
+                        "source": "fake"
+                    }))
+                    .collect();
+                let publish = json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": {"uri": doc["uri"], "diagnostics": diagnostics}
+                });
+                write_message(&writer, &publish).await.unwrap();
+            }
+        });
+        (client_in, client_out)
+    }
+
+    pub(crate) async fn connect_fake(
+        root: &Path,
+        handler: impl Fn(&str, &Value) -> Value + Send + 'static,
+    ) -> LspClient {
+        let (reader, writer) = fake_server(handler);
+        LspClient::connect("fake", reader, writer, root, Duration::from_secs(5))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests_and_diagnostics() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let file = root.join("a.rs");
+        std::fs::write(&file, "fn main() {}\n").unwrap();
+
+        let client = connect_fake(&root, |method, _| json!({ "echo": method })).await;
+        let result = client
+            .request("textDocument/hover", json!({}))
+            .await
+            .unwrap();
+        assert_eq!(result, json!({"echo": "textDocument/hover"}));
+
+        let since = client.diagnostics_generation();
+        assert!(client.sync_document(&file, "rust").await.unwrap());
+        assert!(
+            client
+                .wait_for_diagnostics(std::slice::from_ref(&file), since, Duration::from_secs(5))
+                .await
+        );
+        assert!(client.diagnostics_for(&file).is_empty());
+
+        // Unchanged text isn't re-sent; changed text is.
+        assert!(!client.sync_document(&file, "rust").await.unwrap());
+        std::fs::write(&file, "fn main() {}\n// TODO\n").unwrap();
+        let since = client.diagnostics_generation();
+        assert!(client.sync_document(&file, "rust").await.unwrap());
+        assert!(
+            client
+                .wait_for_diagnostics(std::slice::from_ref(&file), since, Duration::from_secs(5))
+                .await
+        );
+        let diagnostics = client.diagnostics_for(&file);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 2);
+        assert_eq!(client.open_documents().await, vec![file]);
+    }
+}
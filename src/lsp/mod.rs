@@ -0,0 +1,510 @@
+// src/lsp/mod.rs — Language servers for diagnostics and navigation
+//
+// `LspManager` decides which servers apply to the project (by marker file
+// and an installed binary), starts each one on first use, and answers
+// position-based questions in the 1-based line/column terms the tools and
+// evaluator use.
+
+pub mod client;
+pub mod protocol;
+pub mod tools;
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde_json::json;
+
+use crate::infra::config::LspConfig;
+use client::LspClient;
+use protocol::{
+    apply_edits, hover_text, parse_locations, path_to_uri, resolve_position, utf16_to_column,
+    workspace_edits, Diagnostic, DiagnosticSeverity, Location, Position,
+};
+
+/// A language server that can serve this project.
+#[derive(Debug, Clone)]
+pub struct ServerSpec {
+    /// Language key: rust, python, go, typescript.
+    pub language: &'static str,
+    pub command: String,
+    pub args: Vec<String>,
+    /// File extension → LSP `languageId`.
+    extensions: &'static [(&'static str, &'static str)],
+}
+
+struct BuiltinServer {
+    language: &'static str,
+    command: &'static str,
+    args: &'static [&'static str],
+    markers: &'static [&'static str],
+    extensions: &'static [(&'static str, &'static str)],
+}
+
+const BUILTIN_SERVERS: &[BuiltinServer] = &[
+    BuiltinServer {
+        language: "rust",
+        command: "rust-analyzer",
+        args: &[],
+        markers: &["Cargo.toml"],
+        extensions: &[("rs", "rust")],
+    },
+    BuiltinServer {
+        language: "python",
+        command: "pyright-langserver",
+        args: &["--stdio"],
+        markers: &[
+            "pyproject.toml",
+            "setup.py",
+            "setup.cfg",
+            "requirements.txt",
+            "pyrightconfig.json",
+        ],
+        extensions: &[("py", "python"), ("pyi", "python")],
+    },
+    BuiltinServer {
+        language: "go",
+        command: "gopls",
+        args: &[],
+        markers: &["go.mod"],
+        extensions: &[("go", "go")],
+    },
+    BuiltinServer {
+        language: "typescript",
+        command: "typescript-language-server",
+        args: &["--stdio"],
+        markers: &["tsconfig.json", "jsconfig.json", "package.json"],
+        extensions: &[
+            ("ts", "typescript"),
+            ("mts", "typescript"),
+            ("cts", "typescript"),
+            ("tsx", "typescriptreact"),
+            ("js", "javascript"),
+            ("mjs", "javascript"),
+            ("cjs", "javascript"),
+            ("jsx", "javascriptreact"),
+        ],
+    },
+];
+
+impl ServerSpec {
+    fn language_id(&self, path: &Path) -> Option<&'static str> {
+        let ext = path.extension()?.to_str()?;
+        self.extensions
+            .iter()
+            .find(|(e, _)| *e == ext)
+            .map(|(_, id)| *id)
+    }
+}
+
+/// Servers that apply to `root`: a marker file is present and the command
+/// (built-in or from `[lsp.servers]`) is on PATH.
+pub fn detect_servers(root: &Path, config: &LspConfig) -> Vec<ServerSpec> {
+    BUILTIN_SERVERS
+        .iter()
+        .filter(|b| b.markers.iter().any(|m| root.join(m).exists()))
+        .filter_map(|b| {
+            let (command, args) = match config.servers.get(b.language) {
+                Some(o) => (o.command.clone(), o.args.clone()),
+                None => (
+                    b.command.to_string(),
+                    b.args.iter().map(|a| a.to_string()).collect(),
+                ),
+            };
+            if which::which(&command).is_err() {
+                tracing::debug!("No {} language server ({} not found)", b.language, command);
+                return None;
+            }
+            Some(ServerSpec {
+                language: b.language,
+                command,
+                args,
+                extensions: b.extensions,
+            })
+        })
+        .collect()
+}
+
+/// The project's language servers, started on first use.
+pub struct LspManager {
+    root: PathBuf,
+    specs: Vec<ServerSpec>,
+    clients: tokio::sync::Mutex<HashMap<&'static str, Arc<LspClient>>>,
+    diagnostics_timeout: Duration,
+    request_timeout: Duration,
+}
+
+impl LspManager {
+    pub fn new(root: &Path, specs: Vec<ServerSpec>, config: &LspConfig) -> Result<Self> {
+        Ok(Self {
+            root: root.canonicalize()?,
+            specs,
+            clients: tokio::sync::Mutex::new(HashMap::new()),
+            diagnostics_timeout: Duration::from_millis(config.diagnostics_timeout_ms),
+            request_timeout: Duration::from_millis(config.request_timeout_ms),
+        })
+    }
+
+    /// A manager for the project at `root`, or None when LSP is disabled or
+    /// no server applies.
+    pub fn detect(root: &Path, config: &LspConfig) -> Option<Arc<Self>> {
+        if !config.enabled {
+            return None;
+        }
+        let specs = detect_servers(root, config);
+        if specs.is_empty() {
+            return None;
+        }
+        let names: Vec<&str> = specs.iter().map(|s| s.command.as_str()).collect();
+        tracing::debug!("Language servers available: {}", names.join(", "));
+        match Self::new(root, specs, config) {
+            Ok(manager) => Some(Arc::new(manager)),
+            Err(e) => {
+                tracing::warn!("Language servers disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn servers(&self) -> &[ServerSpec] {
+        &self.specs
+    }
+
+    /// Absolute path for a project-relative (or absolute) `path`, which must
+    /// stay inside the project.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf> {
+        let joined = self.root.join(path);
+        let resolved = joined.canonicalize().map_err(|e| anyhow!("{path}: {e}"))?;
+        if !resolved.starts_with(&self.root) {
+            anyhow::bail!("{path} is outside the project");
+        }
+        Ok(resolved)
+    }
+
+    /// `path` relative to the project root, for display.
+    pub fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn spec_for(&self, path: &Path) -> Option<(&ServerSpec, &'static str)> {
+        self.specs
+            .iter()
+            .find_map(|s| s.language_id(path).map(|id| (s, id)))
+    }
+
+    async fn client(&self, spec: &ServerSpec) -> Result<Arc<LspClient>> {
+        let mut clients = self.clients.lock().await;
+        if let Some(client) = clients.get(spec.language) {
+            return Ok(client.clone());
+        }
+        let client = Arc::new(
+            LspClient::spawn(
+                spec.language,
+                &spec.command,
+                &spec.args,
+                &self.root,
+                self.request_timeout,
+            )
+            .await?,
+        );
+        clients.insert(spec.language, client.clone());
+        Ok(client)
+    }
+
+    /// The client for `path`, with the file's current text synced.
+    async fn client_for(&self, path: &Path) -> Result<Arc<LspClient>> {
+        let (spec, language_id) = self
+            .spec_for(path)
+            .ok_or_else(|| anyhow!("no language server handles {}", self.relative(path)))?;
+        let client = self.client(spec).await?;
+        client.sync_document(path, language_id).await?;
+        Ok(client)
+    }
+
+    /// `textDocument/*` params for a 1-based position given by column or by
+    /// the symbol's occurrence on the line.
+    async fn position_params(
+        &self,
+        path: &Path,
+        line: u32,
+        column: Option<u32>,
+        symbol: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let text = tokio::fs::read_to_string(path).await?;
+        let position = resolve_position(&text, line, column, symbol)?;
+        Ok(json!({
+            "textDocument": { "uri": path_to_uri(path)? },
+            "position": position.to_json(),
+        }))
+    }
+
+    pub async fn goto_definition(
+        &self,
+        path: &Path,
+        line: u32,
+        column: Option<u32>,
+        symbol: Option<&str>,
+    ) -> Result<Vec<Location>> {
+        let client = self.client_for(path).await?;
+        let params = self.position_params(path, line, column, symbol).await?;
+        let result = client.request("textDocument/definition", params).await?;
+        Ok(to_locations(parse_locations(&result)))
+    }
+
+    pub async fn references(
+        &self,
+        path: &Path,
+        line: u32,
+        column: Option<u32>,
+        symbol: Option<&str>,
+    ) -> Result<Vec<Location>> {
+        let client = self.client_for(path).await?;
+        let mut params = self.position_params(path, line, column, symbol).await?;
+        params["context"] = json!({ "includeDeclaration": true });
+        let result = client.request("textDocument/references", params).await?;
+        Ok(to_locations(parse_locations(&result)))
+    }
+
+    pub async fn hover(
+        &self,
+        path: &Path,
+        line: u32,
+        column: Option<u32>,
+        symbol: Option<&str>,
+    ) -> Result<Option<String>> {
+        let client = self.client_for(path).await?;
+        let params = self.position_params(path, line, column, symbol).await?;
+        let result = client.request("textDocument/hover", params).await?;
+        Ok(hover_text(&result))
+    }
+
+    /// Rename the symbol at a position across the project, writing the
+    /// edits to disk. Returns the number of edits per changed file.
+    pub async fn rename(
+        &self,
+        path: &Path,
+        line: u32,
+        column: Option<u32>,
+        symbol: Option<&str>,
+        new_name: &str,
+    ) -> Result<BTreeMap<PathBuf, usize>> {
+        let client = self.client_for(path).await?;
+        let mut params = self.position_params(path, line, column, symbol).await?;
+        params["newName"] = json!(new_name);
+        let result = client.request("textDocument/rename", params).await?;
+        let edits = workspace_edits(&result)?;
+
+        // Compute every file's new text before writing any of them.
+        let mut updated = Vec::with_capacity(edits.len());
+        for (file, file_edits) in &edits {
+            if !file.starts_with(&self.root) {
+                anyhow::bail!("rename touches {} outside the project", file.display());
+            }
+            let text = tokio::fs::read_to_string(file).await?;
+            updated.push((file, apply_edits(&text, file_edits)?));
+        }
+        for (file, text) in updated {
+            tokio::fs::write(file, text).await?;
+            if let Some((_, language_id)) = self.spec_for(file) {
+                client.sync_document(file, language_id).await?;
+            }
+        }
+        Ok(edits.into_iter().map(|(file, e)| (file, e.len())).collect())
+    }
+
+    /// Errors and warnings for `files` (project-relative) plus every
+    /// document already open on the same servers, after giving the servers
+    /// `diagnostics_timeout` to catch up with the files on disk. Servers
+    /// that fail to start are skipped.
+    pub async fn diagnostics(&self, files: &[String]) -> Vec<Diagnostic> {
+        let mut by_server: BTreeMap<&'static str, Vec<(PathBuf, &'static str)>> = BTreeMap::new();
+        for file in files {
+            let Ok(path) = self.resolve(file) else {
+                continue;
+            };
+            if let Some((spec, language_id)) = self.spec_for(&path) {
+                by_server
+                    .entry(spec.language)
+                    .or_default()
+                    .push((path, language_id));
+            }
+        }
+
+        let mut out = Vec::new();
+        for spec in &self.specs {
+            let changed = by_server.remove(spec.language).unwrap_or_default();
+            let client = if changed.is_empty() {
+                // Still report on documents opened earlier in the task.
+                match self.clients.lock().await.get(spec.language) {
+                    Some(client) => client.clone(),
+                    None => continue,
+                }
+            } else {
+                match self.client(spec).await {
+                    Ok(client) => client,
+                    Err(e) => {
+                        tracing::debug!("{} language server unavailable: {}", spec.language, e);
+                        continue;
+                    }
+                }
+            };
+
+            let since = client.diagnostics_generation();
+            let mut synced = Vec::new();
+            for (path, language_id) in &changed {
+                match client.sync_document(path, language_id).await {
+                    Ok(true) => synced.push(path.clone()),
+                    Ok(false) => {}
+                    Err(e) => tracing::debug!("Could not sync {}: {}", path.display(), e),
+                }
+            }
+            if !synced.is_empty()
+                && !client
+                    .wait_for_diagnostics(&synced, since, self.diagnostics_timeout)
+                    .await
+            {
+                tracing::debug!("{} diagnostics still pending after timeout", spec.language);
+            }
+
+            for path in client.open_documents().await {
+                out.extend(
+                    client
+                        .diagnostics_for(&path)
+                        .into_iter()
+                        .filter(|d| d.severity <= DiagnosticSeverity::Warning),
+                );
+            }
+        }
+        out.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+        out
+    }
+
+    /// Stop every running server.
+    pub async fn shutdown_all(&self) {
+        let clients: Vec<_> = self.clients.lock().await.drain().map(|(_, c)| c).collect();
+        for client in clients {
+            client.shutdown().await;
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) async fn insert_client(&self, language: &'static str, client: LspClient) {
+        self.clients.lock().await.insert(language, Arc::new(client));
+    }
+}
+
+/// Wire locations as 1-based character columns, reading each file once.
+fn to_locations(raw: Vec<(PathBuf, Position)>) -> Vec<Location> {
+    let mut texts: HashMap<PathBuf, Option<String>> = HashMap::new();
+    raw.into_iter()
+        .map(|(path, pos)| {
+            let text = texts
+                .entry(path.clone())
+                .or_insert_with(|| std::fs::read_to_string(&path).ok());
+            let column = text
+                .as_deref()
+                .and_then(|t| t.lines().nth(pos.line as usize))
+                .map(|l| utf16_to_column(l, pos.character))
+                .unwrap_or(pos.character + 1);
+            Location {
+                path,
+                line: pos.line + 1,
+                column,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    pub(crate) fn rust_spec() -> ServerSpec {
+        ServerSpec {
+            language: "rust",
+            command: "rust-analyzer".into(),
+            args: vec![],
+            extensions: BUILTIN_SERVERS[0].extensions,
+        }
+    }
+
+    /// A project with `src/lib.rs`, served by the fake server from
+    /// `client::tests`. Definitions point at line 1; renames replace
+    /// `old` at the start of line 1.
+    pub(crate) async fn fake_project() -> (tempfile::TempDir, Arc<LspManager>) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "old();\nfn old() {}\n").unwrap();
+        let manager =
+            LspManager::new(dir.path(), vec![rust_spec()], &LspConfig::default()).unwrap();
+        let uri = path_to_uri(&manager.root().join("src/lib.rs")).unwrap();
+        let handler = move |method: &str, _: &Value| match method {
+            "textDocument/definition" => json!([{
+                "uri": uri,
+                "range": {"start": {"line": 1, "character": 3}, "end": {"line": 1, "character": 6}}
+            }]),
+            "textDocument/rename" => json!({"changes": {uri.clone(): [
+                {"range": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 3}}, "newText": "new"},
+                {"range": {"start": {"line": 1, "character": 3}, "end": {"line": 1, "character": 6}}, "newText": "new"}
+            ]}}),
+            "textDocument/hover" => json!({"contents": {"kind": "markdown", "value": "fn old()"}}),
+            _ => Value::Null,
+        };
+        let client = client::tests::connect_fake(manager.root(), handler).await;
+        manager.insert_client("rust", client).await;
+        (dir, Arc::new(manager))
+    }
+
+    #[tokio::test]
+    async fn test_manager_navigation_and_rename() {
+        let (_dir, manager) = fake_project().await;
+        let lib = manager.resolve("src/lib.rs").unwrap();
+
+        let defs = manager
+            .goto_definition(&lib, 1, None, Some("old"))
+            .await
+            .unwrap();
+        assert_eq!(defs.len(), 1);
+        assert_eq!(manager.relative(&defs[0].path), "src/lib.rs");
+        assert_eq!((defs[0].line, defs[0].column), (2, 4));
+
+        let changed = manager
+            .rename(&lib, 2, None, Some("old"), "new")
+            .await
+            .unwrap();
+        assert_eq!(changed.get(&lib), Some(&2));
+        assert_eq!(
+            std::fs::read_to_string(&lib).unwrap(),
+            "new();\nfn new() {}\n"
+        );
+
+        assert!(manager.resolve("../outside.rs").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_manager_diagnostics() {
+        let (dir, manager) = fake_project().await;
+        std::fs::write(dir.path().join("src/lib.rs"), "// TODO\nfn old() {}\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "TODO").unwrap();
+
+        let diagnostics = manager
+            .diagnostics(&["src/lib.rs".into(), "notes.txt".into()])
+            .await;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 1);
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+
+        // Open documents are still reported when nothing new changed.
+        assert_eq!(manager.diagnostics(&[]).await.len(), 1);
+    }
+}
@@ -0,0 +1,454 @@
+// src/lsp/protocol.rs — LSP message shapes the client needs
+//
+// Positions on the wire are 0-based lines and UTF-16 code-unit columns; the
+// tools and evaluator work with 1-based lines and character columns. This
+// module converts between the two, parses the location/hover/diagnostic
+// payloads, and applies workspace edits to files on disk.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use url::Url;
+
+/// A position on the wire: 0-based line, UTF-16 column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+impl Position {
+    pub fn to_json(self) -> Value {
+        json!({ "line": self.line, "character": self.character })
+    }
+
+    fn from_json(v: &Value) -> Option<Self> {
+        Some(Self {
+            line: v.get("line")?.as_u64()? as u32,
+            character: v.get("character")?.as_u64()? as u32,
+        })
+    }
+}
+
+/// A location in a file: 1-based line and character column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Diagnostic severity, as published by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl DiagnosticSeverity {
+    fn from_code(code: Option<u64>) -> Self {
+        match code {
+            Some(2) => DiagnosticSeverity::Warning,
+            Some(3) => DiagnosticSeverity::Information,
+            Some(4) => DiagnosticSeverity::Hint,
+            // Unset severity is up to the client; treat it as an error.
+            _ => DiagnosticSeverity::Error,
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            DiagnosticSeverity::Error => "error",
+            DiagnosticSeverity::Warning => "warning",
+            DiagnosticSeverity::Information => "info",
+            DiagnosticSeverity::Hint => "hint",
+        })
+    }
+}
+
+/// One published diagnostic, positioned for humans (1-based line, UTF-16
+/// column + 1; close enough for locating the problem).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: u32,
+    pub column: u32,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    /// Reporting tool (`rustc`, `clippy`, `Pyright`, `ts`, ...).
+    pub source: Option<String>,
+}
+
+impl Diagnostic {
+    /// Parse one entry of a `publishDiagnostics` notification for `path`.
+    pub fn from_json(path: &Path, v: &Value) -> Option<Self> {
+        let start = Position::from_json(v.pointer("/range/start")?)?;
+        Some(Self {
+            path: path.to_path_buf(),
+            line: start.line + 1,
+            column: start.character + 1,
+            severity: DiagnosticSeverity::from_code(v.get("severity").and_then(Value::as_u64)),
+            message: v.get("message")?.as_str()?.to_string(),
+            source: v.get("source").and_then(Value::as_str).map(String::from),
+        })
+    }
+}
+
+pub fn path_to_uri(path: &Path) -> anyhow::Result<String> {
+    Url::from_file_path(path)
+        .map(|u| u.to_string())
+        .map_err(|_| anyhow::anyhow!("not an absolute path: {}", path.display()))
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    Url::parse(uri).ok()?.to_file_path().ok()
+}
+
+/// The wire position for a 1-based `line` and either a 1-based character
+/// `column` or the first whole-word occurrence of `symbol` on that line.
+pub fn resolve_position(
+    text: &str,
+    line: u32,
+    column: Option<u32>,
+    symbol: Option<&str>,
+) -> anyhow::Result<Position> {
+    if line == 0 {
+        anyhow::bail!("line numbers start at 1");
+    }
+    let line_text = text
+        .lines()
+        .nth(line as usize - 1)
+        .ok_or_else(|| anyhow::anyhow!("line {line} is past the end of the file"))?;
+
+    let char_index = match (symbol, column) {
+        (Some(symbol), _) if !symbol.is_empty() => find_word(line_text, symbol)
+            .ok_or_else(|| anyhow::anyhow!("'{symbol}' does not appear on line {line}"))?,
+        (_, Some(column)) if column > 0 => column as usize - 1,
+        _ => anyhow::bail!("pass `column` or `symbol` to pick a position on the line"),
+    };
+    Ok(Position {
+        line: line - 1,
+        character: char_to_utf16(line_text, char_index),
+    })
+}
+
+/// Character index of the first occurrence of `word` not embedded in a
+/// longer identifier.
+fn find_word(line: &str, word: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let mut from = 0;
+    while let Some(offset) = line[from..].find(word) {
+        let start = from + offset;
+        let end = start + word.len();
+        let before = line[..start].chars().next_back();
+        let after = line[end..].chars().next();
+        if !before.is_some_and(is_ident) && !after.is_some_and(is_ident) {
+            return Some(line[..start].chars().count());
+        }
+        from = start + word.len().max(1);
+    }
+    None
+}
+
+fn char_to_utf16(line: &str, char_index: usize) -> u32 {
+    line.chars()
+        .take(char_index)
+        .map(|c| c.len_utf16() as u32)
+        .sum()
+}
+
+/// 1-based character column for a UTF-16 offset on `line`.
+pub fn utf16_to_column(line: &str, utf16: u32) -> u32 {
+    let mut units = 0;
+    let mut chars = 0;
+    for c in line.chars() {
+        if units >= utf16 {
+            break;
+        }
+        units += c.len_utf16() as u32;
+        chars += 1;
+    }
+    chars + 1
+}
+
+/// Byte offset in `text` of a wire position (clamped to the line end).
+fn byte_offset(text: &str, pos: Position) -> Option<usize> {
+    let mut offset = 0;
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        if i as u32 == pos.line {
+            let content = line.trim_end_matches(['\n', '\r']);
+            let mut units = 0;
+            for (byte, c) in content.char_indices() {
+                if units >= pos.character {
+                    return Some(offset + byte);
+                }
+                units += c.len_utf16() as u32;
+            }
+            return Some(offset + content.len());
+        }
+        offset += line.len();
+    }
+    // Position just past the last line (e.g. an insertion at EOF).
+    (pos.line as usize == text.split_inclusive('\n').count()).then_some(text.len())
+}
+
+/// Locations from a definition/references result: `Location`,
+/// `Location[]`, or `LocationLink[]`. Columns stay as UTF-16 + 1 here;
+/// callers with the file text refine them with `utf16_to_column`.
+pub fn parse_locations(result: &Value) -> Vec<(PathBuf, Position)> {
+    let items: Vec<&Value> = match result {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![result],
+        _ => Vec::new(),
+    };
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let (uri, range) = match item.get("targetUri") {
+                Some(uri) => (
+                    uri,
+                    item.get("targetSelectionRange")
+                        .or_else(|| item.get("targetRange"))?,
+                ),
+                None => (item.get("uri")?, item.get("range")?),
+            };
+            let path = uri_to_path(uri.as_str()?)?;
+            Some((path, Position::from_json(range.get("start")?)?))
+        })
+        .collect()
+}
+
+/// Plain text of a hover result (`MarkupContent`, `MarkedString` or an
+/// array of them). None when the server has nothing to say.
+pub fn hover_text(result: &Value) -> Option<String> {
+    fn marked(v: &Value) -> Option<String> {
+        match v {
+            Value::String(s) => Some(s.clone()),
+            Value::Object(o) => {
+                let value = o.get("value")?.as_str()?;
+                match o.get("language").and_then(Value::as_str) {
+                    Some(lang) => Some(format!("```{lang}\n{value}\n```")),
+                    None => Some(value.to_string()),
+                }
+            }
+            _ => None,
+        }
+    }
+    let contents = result.get("contents")?;
+    let text = match contents {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(marked)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        other => marked(other)?,
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// A text edit on the wire.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub start: Position,
+    pub end: Position,
+    pub new_text: String,
+}
+
+/// Text edits per file from a `WorkspaceEdit` (`changes` or
+/// `documentChanges`). Resource operations (create/rename/delete) are
+/// refused rather than half-applied.
+pub fn workspace_edits(edit: &Value) -> anyhow::Result<BTreeMap<PathBuf, Vec<TextEdit>>> {
+    let mut out: BTreeMap<PathBuf, Vec<TextEdit>> = BTreeMap::new();
+    let mut push = |uri: &str, edits: &Value| -> anyhow::Result<()> {
+        let path = uri_to_path(uri).ok_or_else(|| anyhow::anyhow!("unsupported URI {uri}"))?;
+        for e in edits.as_array().into_iter().flatten() {
+            let range = e
+                .get("range")
+                .ok_or_else(|| anyhow::anyhow!("edit without range"))?;
+            let (Some(start), Some(end)) = (
+                range.get("start").and_then(Position::from_json),
+                range.get("end").and_then(Position::from_json),
+            ) else {
+                anyhow::bail!("malformed edit range");
+            };
+            out.entry(path.clone()).or_default().push(TextEdit {
+                start,
+                end,
+                new_text: e
+                    .get("newText")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string(),
+            });
+        }
+        Ok(())
+    };
+
+    if let Some(changes) = edit.get("documentChanges").and_then(Value::as_array) {
+        for change in changes {
+            if let Some(kind) = change.get("kind").and_then(Value::as_str) {
+                anyhow::bail!(
+                    "language server asked for a '{kind}' file operation, which isn't supported"
+                );
+            }
+            let uri = change
+                .pointer("/textDocument/uri")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow::anyhow!("document change without a URI"))?;
+            push(uri, change.get("edits").unwrap_or(&Value::Null))?;
+        }
+    } else if let Some(changes) = edit.get("changes").and_then(Value::as_object) {
+        for (uri, edits) in changes {
+            push(uri, edits)?;
+        }
+    }
+    Ok(out)
+}
+
+/// Apply `edits` to `text`. Edits must not overlap.
+pub fn apply_edits(text: &str, edits: &[TextEdit]) -> anyhow::Result<String> {
+    let mut spans = Vec::with_capacity(edits.len());
+    for edit in edits {
+        let (Some(start), Some(end)) = (byte_offset(text, edit.start), byte_offset(text, edit.end))
+        else {
+            anyhow::bail!("edit range is outside the file");
+        };
+        if end < start {
+            anyhow::bail!("edit range ends before it starts");
+        }
+        spans.push((start, end, edit.new_text.as_str()));
+    }
+    spans.sort_by_key(|&(start, end, _)| (start, end));
+    if spans.windows(2).any(|w| w[1].0 < w[0].1) {
+        anyhow::bail!("overlapping edits");
+    }
+
+    let mut out = text.to_string();
+    for (start, end, new_text) in spans.into_iter().rev() {
+        out.replace_range(start..end, new_text);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_position_by_symbol_and_column() {
+        let text = "fn main() {\n    let café = Store::new();\n}\n";
+        // `Store` sits after a two-byte, one-UTF-16-unit character.
+        let pos = resolve_position(text, 2, None, Some("Store")).unwrap();
+        assert_eq!(
+            pos,
+            Position {
+                line: 1,
+                character: 15
+            }
+        );
+        let pos = resolve_position(text, 2, Some(9), None).unwrap();
+        assert_eq!(pos.character, 8);
+
+        assert!(resolve_position(text, 2, None, Some("Stor")).is_err());
+        assert!(resolve_position(text, 9, Some(1), None).is_err());
+        assert!(resolve_position(text, 2, None, None).is_err());
+        assert_eq!(utf16_to_column("    let café = Store", 15), 16);
+    }
+
+    #[test]
+    fn test_parse_locations_and_links() {
+        let loc = json!({
+            "uri": "file:///repo/src/a.rs",
+            "range": {"start": {"line": 4, "character": 2}, "end": {"line": 4, "character": 5}}
+        });
+        let link = json!([{
+            "targetUri": "file:///repo/src/b.rs",
+            "targetRange": {"start": {"line": 0, "character": 0}, "end": {"line": 9, "character": 1}},
+            "targetSelectionRange": {"start": {"line": 1, "character": 7}, "end": {"line": 1, "character": 9}}
+        }]);
+        assert_eq!(
+            parse_locations(&loc),
+            vec![(
+                PathBuf::from("/repo/src/a.rs"),
+                Position {
+                    line: 4,
+                    character: 2
+                }
+            )]
+        );
+        assert_eq!(
+            parse_locations(&link)[0].1,
+            Position {
+                line: 1,
+                character: 7
+            }
+        );
+        assert!(parse_locations(&Value::Null).is_empty());
+    }
+
+    #[test]
+    fn test_hover_text() {
+        let markup =
+            json!({"contents": {"kind": "markdown", "value": "```rust\nfn new() -> Store\n```"}});
+        assert_eq!(
+            hover_text(&markup).unwrap(),
+            "```rust\nfn new() -> Store\n```"
+        );
+        let marked = json!({"contents": [{"language": "python", "value": "def f()"}, "Docs"]});
+        assert_eq!(
+            hover_text(&marked).unwrap(),
+            "```python\ndef f()\n```\n\nDocs"
+        );
+        assert!(hover_text(&json!({"contents": ""})).is_none());
+    }
+
+    #[test]
+    fn test_workspace_edit_roundtrip() {
+        let edit = json!({
+            "documentChanges": [{
+                "textDocument": {"uri": "file:///repo/a.rs", "version": 1},
+                "edits": [
+                    {"range": {"start": {"line": 0, "character": 7}, "end": {"line": 0, "character": 12}}, "newText": "Repo"},
+                    {"range": {"start": {"line": 1, "character": 4}, "end": {"line": 1, "character": 9}}, "newText": "Repo"}
+                ]
+            }]
+        });
+        let edits = workspace_edits(&edit).unwrap();
+        let file = edits.get(Path::new("/repo/a.rs")).unwrap();
+        let text = "struct Store;\n    Store::new()\n";
+        assert_eq!(
+            apply_edits(text, file).unwrap(),
+            "struct Repo;\n    Repo::new()\n"
+        );
+
+        let create = json!({"documentChanges": [{"kind": "create", "uri": "file:///repo/b.rs"}]});
+        assert!(workspace_edits(&create).is_err());
+
+        let overlapping = vec![file[0].clone(), file[0].clone()];
+        assert!(apply_edits(text, &overlapping).is_err());
+    }
+
+    #[test]
+    fn test_diagnostic_from_json() {
+        let d = Diagnostic::from_json(
+            Path::new("/repo/a.py"),
+            &json!({
+                "range": {"start": {"line": 2, "character": 4}, "end": {"line": 2, "character": 8}},
+                "severity": 2,
+                "message": "unused variable",
+                "source": "Pyright"
+            }),
+        )
+        .unwrap();
+        assert_eq!(d.line, 3);
+        assert_eq!(d.column, 5);
+        assert_eq!(d.severity, DiagnosticSeverity::Warning);
+        assert_eq!(d.source.as_deref(), Some("Pyright"));
+    }
+}
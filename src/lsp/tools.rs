@@ -0,0 +1,244 @@
+// src/lsp/tools.rs — Expose language-server navigation to the agent as tools
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::protocol::Location;
+use super::LspManager;
+use crate::provider::ToolDef;
+use crate::tools::{ToolEffect, ToolProvider, ToolSource};
+
+pub const GOTO_DEFINITION_TOOL: &str = "goto_definition";
+pub const FIND_REFERENCES_TOOL: &str = "find_references";
+pub const HOVER_TOOL: &str = "hover";
+pub const RENAME_SYMBOL_TOOL: &str = "rename_symbol";
+
+/// Results listed before the rest are summarized.
+const MAX_LOCATIONS: usize = 100;
+
+/// Compiler-accurate navigation and renames through the project's language
+/// servers. Positions are a file, a 1-based line, and either the symbol's
+/// name on that line or a 1-based column.
+pub struct LspTools {
+    manager: Arc<LspManager>,
+}
+
+impl LspTools {
+    pub fn new(manager: Arc<LspManager>) -> Self {
+        Self { manager }
+    }
+
+    fn format_locations(&self, locations: &[Location], empty: &str) -> String {
+        if locations.is_empty() {
+            return empty.to_string();
+        }
+        let mut texts = std::collections::HashMap::new();
+        let mut out = String::new();
+        for loc in locations.iter().take(MAX_LOCATIONS) {
+            let text: &Option<String> = texts
+                .entry(loc.path.clone())
+                .or_insert_with(|| std::fs::read_to_string(&loc.path).ok());
+            let line = text
+                .as_deref()
+                .and_then(|t| t.lines().nth(loc.line as usize - 1))
+                .map(str::trim)
+                .unwrap_or("");
+            out.push_str(&format!(
+                "{}:{}:{}: {}\n",
+                self.manager.relative(&loc.path),
+                loc.line,
+                loc.column,
+                line
+            ));
+        }
+        if locations.len() > MAX_LOCATIONS {
+            out.push_str(&format!("... {} more\n", locations.len() - MAX_LOCATIONS));
+        }
+        out
+    }
+}
+
+fn position_schema(extra: Value) -> Value {
+    let mut properties = json!({
+        "path": { "type": "string", "description": "File path relative to the project root" },
+        "line": { "type": "integer", "description": "1-based line number" },
+        "symbol": { "type": "string", "description": "Identifier on that line (first whole-word occurrence)" },
+        "column": { "type": "integer", "description": "1-based column, if symbol is not given" }
+    });
+    let mut required = vec![json!("path"), json!("line")];
+    if let Value::Object(extra) = extra {
+        for (name, schema) in extra {
+            required.push(json!(name));
+            properties[&name] = schema;
+        }
+    }
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+#[async_trait]
+impl ToolProvider for LspTools {
+    fn id(&self) -> &str {
+        "lsp"
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::Builtin
+    }
+
+    fn tools(&self) -> Vec<ToolDef> {
+        let languages: Vec<&str> = self.manager.servers().iter().map(|s| s.language).collect();
+        let served = languages.join(", ");
+        vec![
+            ToolDef {
+                name: GOTO_DEFINITION_TOOL.into(),
+                description: format!(
+                    "Jump to where the symbol at a position is defined, as resolved by \
+                     the language server ({served})."
+                ),
+                parameters: position_schema(json!({})),
+            },
+            ToolDef {
+                name: FIND_REFERENCES_TOOL.into(),
+                description: format!(
+                    "List every use of the symbol at a position, resolved by the language \
+                     server ({served}). Unlike a text search, this ignores unrelated \
+                     identifiers with the same name."
+                ),
+                parameters: position_schema(json!({})),
+            },
+            ToolDef {
+                name: HOVER_TOOL.into(),
+                description: format!(
+                    "Show the type signature and documentation of the symbol at a position \
+                     ({served})."
+                ),
+                parameters: position_schema(json!({})),
+            },
+            ToolDef {
+                name: RENAME_SYMBOL_TOOL.into(),
+                description: format!(
+                    "Rename the symbol at a position everywhere it is used and write the \
+                     changes to disk ({served})."
+                ),
+                parameters: position_schema(json!({
+                    "new_name": { "type": "string", "description": "New identifier" }
+                })),
+            },
+        ]
+    }
+
+    fn effect(&self, tool: &str) -> ToolEffect {
+        match tool {
+            RENAME_SYMBOL_TOOL => ToolEffect::Mutating,
+            _ => ToolEffect::ReadOnly,
+        }
+    }
+
+    async fn call(&self, tool: &str, args: Value) -> anyhow::Result<String> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("missing 'path'"))?;
+        let line = args
+            .get("line")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| anyhow::anyhow!("missing 'line'"))? as u32;
+        let column = args
+            .get("column")
+            .and_then(|v| v.as_u64())
+            .map(|c| c as u32);
+        let symbol = args
+            .get("symbol")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
+        if column.is_none() && symbol.is_none() {
+            anyhow::bail!("give either 'symbol' or 'column'");
+        }
+        let file = self.manager.resolve(path)?;
+
+        match tool {
+            GOTO_DEFINITION_TOOL => {
+                let defs = self
+                    .manager
+                    .goto_definition(&file, line, column, symbol)
+                    .await?;
+                Ok(self.format_locations(&defs, "No definition found."))
+            }
+            FIND_REFERENCES_TOOL => {
+                let refs = self.manager.references(&file, line, column, symbol).await?;
+                Ok(self.format_locations(&refs, "No references found."))
+            }
+            HOVER_TOOL => Ok(self
+                .manager
+                .hover(&file, line, column, symbol)
+                .await?
+                .unwrap_or_else(|| "No information at that position.".into())),
+            RENAME_SYMBOL_TOOL => {
+                let new_name = args
+                    .get("new_name")
+                    .and_then(|v| v.as_str())
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .ok_or_else(|| anyhow::anyhow!("missing 'new_name'"))?;
+                let changed = self
+                    .manager
+                    .rename(&file, line, column, symbol, new_name)
+                    .await?;
+                if changed.is_empty() {
+                    return Ok("Nothing to rename.".into());
+                }
+                let total: usize = changed.values().sum();
+                let mut out = format!(
+                    "Renamed to '{new_name}': {total} edit(s) in {} file(s)\n",
+                    changed.len()
+                );
+                for (file, count) in &changed {
+                    out.push_str(&format!("  {} ({count})\n", self.manager.relative(file)));
+                }
+                Ok(out)
+            }
+            _ => anyhow::bail!("unknown language server tool '{tool}'"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::tests::fake_project;
+
+    #[tokio::test]
+    async fn test_lsp_tools() {
+        let (_dir, manager) = fake_project().await;
+        let tools = LspTools::new(manager);
+        assert_eq!(tools.tools().len(), 4);
+        assert_eq!(tools.effect(RENAME_SYMBOL_TOOL), ToolEffect::Mutating);
+        assert_eq!(tools.effect(HOVER_TOOL), ToolEffect::ReadOnly);
+
+        let out = tools
+            .call(
+                GOTO_DEFINITION_TOOL,
+                json!({"path": "src/lib.rs", "line": 1, "symbol": "old"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(out, "src/lib.rs:2:4: fn old() {}\n");
+
+        let out = tools
+            .call(
+                HOVER_TOOL,
+                json!({"path": "src/lib.rs", "line": 1, "column": 1}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(out, "fn old()");
+
+        assert!(tools
+            .call(HOVER_TOOL, json!({"path": "src/lib.rs", "line": 1}))
+            .await
+            .is_err());
+    }
+}
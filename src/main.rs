@@ -2,11 +2,18 @@
 
 use clap::Parser;
 
+use openkoi::api::approvals::{ApprovalQueue, QueueApprover};
+use openkoi::cli::review::{ReviewOptions, ReviewSource};
+use openkoi::cli::run::{ChangeMode, Reporting, RunOutcome};
 use openkoi::cli::{Cli, Commands, DaemonAction};
+use openkoi::index::tools::CodeIndexTools;
+use openkoi::index::CodeIndex;
 use openkoi::infra::config::Config;
 use openkoi::infra::logger;
 use openkoi::integrations::credentials::IntegrationCredentials;
 use openkoi::integrations::registry::IntegrationRegistry;
+use openkoi::lsp::tools::LspTools;
+use openkoi::lsp::LspManager;
 use openkoi::memory::schema;
 use openkoi::memory::store::Store;
 use openkoi::plugins::hooks::HookExecutor;
@@ -15,7 +22,13 @@ use openkoi::plugins::rhai_host::{RhaiExposedFunctions, RhaiHost};
 use openkoi::plugins::wasm::WasmPluginManager;
 use openkoi::provider::resolver;
 use openkoi::provider::{ModelProvider, ModelRef};
+use openkoi::security::audit::AuditLog;
 use openkoi::security::permissions;
+use openkoi::skills::registry::SkillRegistry;
+use openkoi::skills::tools::SkillTools;
+use openkoi::tools::overlay::Overlay;
+use openkoi::tools::policy::{TerminalApprover, ToolPolicy};
+use openkoi::tools::ToolRegistry;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -34,11 +47,14 @@ async fn run() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
     // Load config (falls back to defaults if no config.toml)
-    let config = if let Some(ref path) = cli.config {
+    let mut config = if let Some(ref path) = cli.config {
         Config::load_from(std::path::Path::new(path))?
     } else {
         Config::load()?
     };
+    openkoi::security::sandbox::Sandbox::configure(&config.sandbox);
+    openkoi::security::redact::Redactor::configure(&config.redaction);
+    openkoi::security::audit::install(AuditLog::new(AuditLog::default_path()));
 
     // Dispatch subcommands that don't need a provider
     match &cli.command {
@@ -96,6 +112,24 @@ async fn run() -> anyhow::Result<()> {
         Some(Commands::Learn { action }) => {
             return openkoi::cli::learn::run_learn(action.clone()).await;
         }
+        Some(Commands::Audit {
+            task,
+            tool,
+            channel,
+            since,
+            limit,
+            verify,
+            json,
+        }) => {
+            let query = openkoi::cli::audit::AuditQuery {
+                task: task.clone(),
+                tool: tool.clone(),
+                channel: channel.clone(),
+                since: since.clone(),
+                limit: *limit,
+            };
+            return openkoi::cli::audit::run_audit(query, *verify, *json).await;
+        }
         Some(Commands::Disconnect { app }) => {
             return openkoi::cli::connect::run_disconnect(app.as_deref()).await;
         }
@@ -123,9 +157,41 @@ async fn run() -> anyhow::Result<()> {
         Some(Commands::Update { version, check }) => {
             return openkoi::cli::update::run_update(version.clone(), *check).await;
         }
+        Some(Commands::Batch {
+            manifest,
+            concurrency,
+            resume,
+            report,
+        }) => {
+            // Each task runs as its own process; --max-cost caps the batch.
+            let options = openkoi::cli::batch::BatchOptions {
+                manifest: manifest.into(),
+                concurrency: *concurrency,
+                resume: *resume,
+                report: report.as_ref().map(Into::into),
+            };
+            return openkoi::cli::batch::run_batch(options, &cli, &config).await;
+        }
+        Some(Commands::Bench {
+            suite,
+            repeat,
+            concurrency,
+        }) => {
+            let options = openkoi::cli::bench::BenchOptions {
+                suite: suite.into(),
+                repeat: *repeat,
+                concurrency: *concurrency,
+            };
+            let store = if cli.no_memory { None } else { init_store() };
+            return openkoi::cli::bench::run_bench(options, &cli, &config, store).await;
+        }
         _ => {}
     }
 
+    if let Some(max_cost) = cli.max_cost {
+        config.safety.max_cost_usd = max_cost;
+    }
+
     // Commands that need a provider: ensure onboarding, then resolve
     let discovered = openkoi::onboarding::ensure_ready().await?;
 
@@ -183,7 +249,7 @@ async fn run() -> anyhow::Result<()> {
     };
 
     // Initialize database (create if needed, run migrations)
-    let store = init_store();
+    let store = if cli.no_memory { None } else { init_store() };
 
     // Run decay on learnings at startup
     if let Some(ref s) = store {
@@ -193,10 +259,10 @@ async fn run() -> anyhow::Result<()> {
     }
 
     // Start MCP tool servers
-    let (mcp_tools, mut mcp_manager) = init_mcp(&config).await;
+    let mcp_manager = Arc::new(tokio::sync::Mutex::new(init_mcp(&config).await));
 
     // Initialize integration adapters from stored credentials
-    let (integration_tools, integration_registry) = init_integrations(&config);
+    let integration_registry = init_integrations(&config);
 
     // Initialize WASM plugins and Rhai scripts
     let hook_executor = init_plugins(&config);
@@ -206,48 +272,132 @@ async fn run() -> anyhow::Result<()> {
         tracing::info!("Plugins: {}", hook_executor.status_summary());
     }
 
-    // Merge MCP tools + integration tools
-    let mut all_tools = mcp_tools;
-    all_tools.extend(integration_tools);
-
-    // Wrap registry in Option for passing to orchestrator
-    let integrations = if integration_registry.list().is_empty() {
-        None
-    } else {
-        Some(integration_registry)
-    };
+    // One registry for every tool source
+    let policy = ToolPolicy::load(&config.permissions, std::path::Path::new("."))?
+        .with_approver(Arc::new(TerminalApprover::new()));
+    let code_index = CodeIndex::open_default(&config.code_index).await;
+    let lsp = LspManager::detect(std::path::Path::new("."), &config.lsp);
+    let mut tools = init_tools(
+        &mcp_manager,
+        &integration_registry,
+        &hook_executor,
+        code_index.clone(),
+        lsp.clone(),
+        policy,
+    )
+    .await;
+    let changes = ChangeMode::from_cli(&cli);
+    let reporting = Reporting::from_cli(&cli);
+    if changes.captures() {
+        tools.set_overlay(Arc::new(Overlay::new(std::path::Path::new("."))?));
+    }
+    let tools = if tools.is_empty() { None } else { Some(tools) };
 
     // Dispatch
     match cli.command {
         Some(Commands::Chat) => {
-            let mcp = if mcp_manager.has_servers() {
-                Some(&mut mcp_manager)
-            } else {
-                None
-            };
+            if changes.captures() {
+                anyhow::bail!("--dry-run, --patch and --output patch apply to task runs, not chat");
+            }
+            if reporting != Reporting::default() {
+                anyhow::bail!("--output and --fail-under apply to task runs, not chat");
+            }
             let result = openkoi::cli::chat::run_chat(
                 provider,
                 &model_ref,
                 &config,
                 store.clone(),
-                all_tools,
-                mcp,
-                integrations.as_ref(),
+                tools.as_ref(),
+                code_index,
+                lsp.clone(),
                 cli.quiet,
             )
             .await;
-            mcp_manager.shutdown_all().await;
+            mcp_manager.lock().await.shutdown_all().await;
+            if let Some(ref lsp) = lsp {
+                lsp.shutdown_all().await;
+            }
+            result
+        }
+        Some(Commands::Acp) => {
+            if changes.captures() {
+                anyhow::bail!("--dry-run, --patch and --output patch apply to task runs, not acp");
+            }
+            if reporting != Reporting::default() {
+                anyhow::bail!("--output and --fail-under apply to task runs, not acp");
+            }
+            let result = openkoi::acp::run_acp(
+                provider,
+                &model_ref,
+                &config,
+                store.clone(),
+                tools.as_ref(),
+                code_index,
+                lsp.clone(),
+            )
+            .await;
+            mcp_manager.lock().await.shutdown_all().await;
+            if let Some(ref lsp) = lsp {
+                lsp.shutdown_all().await;
+            }
             result
         }
+        Some(Commands::Review {
+            range,
+            staged,
+            pr,
+            patch_file,
+            files,
+            format,
+            evaluator,
+            no_checks,
+            fix,
+        }) => {
+            let source = if staged {
+                ReviewSource::Staged
+            } else if let Some(pr) = pr {
+                ReviewSource::PullRequest(pr)
+            } else if let Some(patch) = patch_file {
+                ReviewSource::Patch(patch.into())
+            } else if !files.is_empty() {
+                ReviewSource::Files(files)
+            } else if let Some(range) = range {
+                ReviewSource::Range(range)
+            } else {
+                ReviewSource::WorkingTree
+            };
+            let options = ReviewOptions {
+                source,
+                format,
+                evaluator,
+                no_checks,
+                fix,
+                max_iterations: cli.iterate,
+                quality_threshold: cli.quality,
+            };
+            let result = openkoi::cli::review::run_review(
+                options,
+                provider,
+                &model_ref,
+                &config,
+                store.clone(),
+                tools.as_ref(),
+                code_index,
+                lsp.clone(),
+                &changes,
+                cli.quiet,
+            )
+            .await;
+            mcp_manager.lock().await.shutdown_all().await;
+            if let Some(ref lsp) = lsp {
+                lsp.shutdown_all().await;
+            }
+            result.map(exit_with)
+        }
         _ => {
             // Default: run task
             let task = build_task_input(&cli)?;
 
-            let mcp = if mcp_manager.has_servers() {
-                Some(&mut mcp_manager)
-            } else {
-                None
-            };
             let result = openkoi::cli::run::run_task(
                 &task,
                 provider,
@@ -256,18 +406,32 @@ async fn run() -> anyhow::Result<()> {
                 cli.iterate,
                 cli.quality,
                 store.clone(),
-                all_tools,
-                mcp,
-                integrations.as_ref(),
+                tools.as_ref(),
+                code_index,
+                lsp.clone(),
+                cli.evaluator.as_deref(),
+                &changes,
+                &reporting,
                 cli.quiet,
             )
             .await;
-            mcp_manager.shutdown_all().await;
-            result
+            mcp_manager.lock().await.shutdown_all().await;
+            if let Some(ref lsp) = lsp {
+                lsp.shutdown_all().await;
+            }
+            result.map(exit_with)
         }
     }
 }
 
+/// Exit with the run's status code unless it was accepted.
+fn exit_with(outcome: RunOutcome) {
+    let code = outcome.exit_code();
+    if code != 0 {
+        std::process::exit(code);
+    }
+}
+
 /// Initialize the SQLite store, running migrations if needed.
 /// Returns None if the database can't be opened (non-fatal for first run).
 fn init_store() -> Option<Arc<Mutex<Store>>> {
@@ -297,40 +461,73 @@ fn init_store() -> Option<Arc<Mutex<Store>>> {
     }
 }
 
-/// Start MCP tool servers and return their tool definitions + the manager.
-async fn init_mcp(config: &Config) -> (Vec<openkoi::provider::ToolDef>, McpManager) {
+/// Start MCP tool servers and return the manager.
+async fn init_mcp(config: &Config) -> McpManager {
     let mut manager = McpManager::new();
 
     if config.plugins.mcp.is_empty() {
         // Also try auto-discovery from .mcp.json
         let discovered = openkoi::plugins::mcp::discover_mcp_json(std::path::Path::new("."));
         if discovered.is_empty() {
-            return (vec![], manager);
+            return manager;
         }
         match manager.start_all(&discovered).await {
             Ok(()) => {
                 let tools = manager.all_tools();
                 tracing::info!("MCP (auto-discovered): {} tool(s) available", tools.len());
-                return (tools, manager);
             }
             Err(e) => {
                 tracing::warn!("MCP auto-discovery failed: {}", e);
-                return (vec![], manager);
             }
         }
+        return manager;
     }
 
     match manager.start_all(&config.plugins.mcp).await {
         Ok(()) => {
             let tools = manager.all_tools();
             tracing::info!("MCP: {} tool(s) available", tools.len());
-            (tools, manager)
         }
         Err(e) => {
             tracing::warn!("MCP initialization failed: {}", e);
-            (vec![], manager)
         }
     }
+    manager
+}
+
+/// Build the tool registry. Registration order decides which source keeps a
+/// bare name on collision: language-server tools, then code index tools (so
+/// the index's text search becomes `code_index__find_references` when a
+/// language server is available), then integrations, then MCP servers, then
+/// plugins, then skills.
+async fn init_tools(
+    mcp: &Arc<tokio::sync::Mutex<McpManager>>,
+    integrations: &IntegrationRegistry,
+    plugins: &HookExecutor,
+    code_index: Option<Arc<CodeIndex>>,
+    lsp: Option<Arc<LspManager>>,
+    policy: ToolPolicy,
+) -> ToolRegistry {
+    let mut registry = ToolRegistry::new();
+    registry.set_policy(policy);
+    if let Some(lsp) = lsp {
+        registry.register(Arc::new(LspTools::new(lsp)));
+    }
+    if let Some(index) = code_index {
+        registry.register(Arc::new(CodeIndexTools::new(index)));
+    }
+    for provider in integrations.tool_providers() {
+        registry.register(provider);
+    }
+    for provider in McpManager::tool_providers(mcp).await {
+        registry.register(provider);
+    }
+    for provider in plugins.tool_providers() {
+        registry.register(provider);
+    }
+    registry.register(Arc::new(SkillTools::new(Arc::new(SkillRegistry::new()))));
+    tracing::debug!("Tool registry: {} tool(s)", registry.len());
+    registry
 }
 
 /// Build the task description from CLI args and/or stdin.
@@ -416,6 +613,18 @@ async fn run_doctor(config: &Config) -> anyhow::Result<()> {
         }
     }
 
+    // Sandbox for test runs and tool processes
+    let sandbox = openkoi::security::sandbox::Sandbox::global();
+    eprintln!(
+        "  Sandbox: {} (network {})",
+        sandbox.backend(),
+        if sandbox.policy().network {
+            "on"
+        } else {
+            "off"
+        }
+    );
+
     // Check MCP servers
     if !config.plugins.mcp.is_empty() {
         eprint!("  Checking MCP servers... ");
@@ -529,8 +738,9 @@ async fn run_daemon_command(action: Option<DaemonAction>, config: &Config) -> an
             }
 
             // Initialize integration registry for the daemon
-            let (_tools, registry) = init_integrations(config);
-            if registry.list().is_empty() {
+            let registry = init_integrations(config);
+            let api_enabled = config.api.as_ref().is_none_or(|api| api.enabled);
+            if registry.list().is_empty() && !api_enabled {
                 println!("No integrations connected. Run `openkoi connect <app>` first.");
                 return Ok(());
             }
@@ -551,12 +761,43 @@ async fn run_daemon_command(action: Option<DaemonAction>, config: &Config) -> an
             // Initialize store
             let store = init_store();
 
-            // Initialize MCP tools
-            let (mcp_tools, _mcp_manager) = init_mcp(config).await;
+            // Initialize MCP servers, plugins and the tool registry
+            let mcp_manager = Arc::new(tokio::sync::Mutex::new(init_mcp(config).await));
+            // "ask" rules wait for an API client to answer; without the API
+            // nobody can, so they refuse.
+            let approvals = Arc::new(ApprovalQueue::new());
+            let mut policy = ToolPolicy::load(&config.permissions, std::path::Path::new("."))?;
+            let api_config = config.api.clone().unwrap_or_default();
+            if api_config.enabled {
+                policy = policy.with_approver(Arc::new(QueueApprover::new(
+                    approvals.clone(),
+                    std::time::Duration::from_secs(api_config.approval_timeout_seconds),
+                    api_config.webhooks.clone(),
+                )));
+            }
+            let code_index = CodeIndex::open_default(&config.code_index).await;
+            let lsp = LspManager::detect(std::path::Path::new("."), &config.lsp);
+            let tools = Arc::new(
+                init_tools(
+                    &mcp_manager,
+                    &registry,
+                    &init_plugins(config),
+                    code_index.clone(),
+                    lsp.clone(),
+                    policy,
+                )
+                .await,
+            );
 
             // Skill registry
-            let skill_registry =
-                std::sync::Arc::new(openkoi::skills::registry::SkillRegistry::new());
+            let mut skill_registry = openkoi::skills::registry::SkillRegistry::new();
+            openkoi::skills::embedding::SkillEmbedder::new(
+                provider.clone(),
+                &config.skills.embeddings,
+            )
+            .embed_registry(&mut skill_registry, store.as_deref())
+            .await;
+            let skill_registry = std::sync::Arc::new(skill_registry);
 
             // Build daemon context
             let daemon_ctx = daemon::DaemonContext {
@@ -565,7 +806,10 @@ async fn run_daemon_command(action: Option<DaemonAction>, config: &Config) -> an
                 config: config.clone(),
                 store: store.clone(),
                 skill_registry,
-                mcp_tools,
+                tools,
+                code_index,
+                lsp: lsp.clone(),
+                approvals,
             };
 
             // Write PID file
@@ -574,6 +818,9 @@ async fn run_daemon_command(action: Option<DaemonAction>, config: &Config) -> an
 
             let registry = std::sync::Arc::new(registry);
             let result = daemon::run_daemon(daemon_ctx, registry).await;
+            if let Some(ref lsp) = lsp {
+                lsp.shutdown_all().await;
+            }
 
             // Clean up PID file on exit
             daemon::remove_pid_file();
@@ -654,13 +901,12 @@ fn run_startup_decay(store: &Store, config: &Config) {
 }
 
 /// Initialize integration adapters from stored credentials.
-/// Returns the auto-registered tools and the registry.
-fn init_integrations(config: &Config) -> (Vec<openkoi::provider::ToolDef>, IntegrationRegistry) {
+fn init_integrations(config: &Config) -> IntegrationRegistry {
     let creds = match IntegrationCredentials::load() {
         Ok(c) => c,
         Err(e) => {
             tracing::debug!("No integration credentials found: {}", e);
-            return (vec![], IntegrationRegistry::new());
+            return IntegrationRegistry::new();
         }
     };
 
@@ -794,8 +1040,7 @@ fn init_integrations(config: &Config) -> (Vec<openkoi::provider::ToolDef>, Integ
         );
     }
 
-    let tools = registry.all_tools();
-    (tools, registry)
+    registry
 }
 
 /// Initialize WASM plugins and Rhai scripts from config.
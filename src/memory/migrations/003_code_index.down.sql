@@ -0,0 +1,8 @@
+-- 003_code_index.down.sql — Remove the code index
+
+DROP INDEX IF EXISTS idx_code_imports_file;
+DROP INDEX IF EXISTS idx_code_symbols_file;
+DROP INDEX IF EXISTS idx_code_symbols_name;
+DROP TABLE IF EXISTS code_imports;
+DROP TABLE IF EXISTS code_symbols;
+DROP TABLE IF EXISTS code_files;
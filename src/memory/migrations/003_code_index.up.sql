@@ -0,0 +1,39 @@
+-- 003_code_index.up.sql — Code index for repository maps and symbol lookup
+--
+-- Files are keyed by project root + relative path so one database can hold
+-- indexes for several checkouts. Rows are refreshed incrementally: a file is
+-- re-parsed only when its mtime/size changed and its content hash differs.
+
+CREATE TABLE IF NOT EXISTS code_files (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    root        TEXT NOT NULL,
+    path        TEXT NOT NULL,
+    language    TEXT NOT NULL,
+    hash        TEXT NOT NULL,
+    mtime_ms    INTEGER NOT NULL,
+    size        INTEGER NOT NULL,
+    lines       INTEGER NOT NULL,
+    indexed_at  TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (root, path)
+);
+
+-- Top-level definitions: functions, types, impls, classes, ...
+CREATE TABLE IF NOT EXISTS code_symbols (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_id     INTEGER NOT NULL REFERENCES code_files(id) ON DELETE CASCADE,
+    name        TEXT NOT NULL,
+    kind        TEXT NOT NULL,
+    line        INTEGER NOT NULL,
+    signature   TEXT NOT NULL
+);
+
+-- Import edges, as written in the source (module path or specifier)
+CREATE TABLE IF NOT EXISTS code_imports (
+    file_id     INTEGER NOT NULL REFERENCES code_files(id) ON DELETE CASCADE,
+    target      TEXT NOT NULL,
+    line        INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_code_symbols_name ON code_symbols(name);
+CREATE INDEX IF NOT EXISTS idx_code_symbols_file ON code_symbols(file_id);
+CREATE INDEX IF NOT EXISTS idx_code_imports_file ON code_imports(file_id);
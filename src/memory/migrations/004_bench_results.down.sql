@@ -0,0 +1,5 @@
+-- 004_bench_results.down.sql — Remove benchmark results
+
+DROP INDEX IF EXISTS idx_bench_results_suite;
+DROP INDEX IF EXISTS idx_bench_results_run;
+DROP TABLE IF EXISTS bench_results;
@@ -0,0 +1,25 @@
+-- 004_bench_results.up.sql — Benchmark suite results
+--
+-- One row per run of a case under a variant. Rows from one `openkoi bench`
+-- invocation share a run_id, so runs of the same suite and variant can be
+-- compared over time.
+
+CREATE TABLE IF NOT EXISTS bench_results (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id      TEXT NOT NULL,
+    suite       TEXT NOT NULL,
+    variant     TEXT NOT NULL,
+    case_id     TEXT NOT NULL,
+    attempt     INTEGER NOT NULL,
+    passed      INTEGER NOT NULL,
+    score       REAL,
+    iterations  INTEGER NOT NULL,
+    tokens      INTEGER NOT NULL,
+    cost_usd    REAL NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    failures    TEXT,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_bench_results_run ON bench_results(run_id);
+CREATE INDEX IF NOT EXISTS idx_bench_results_suite ON bench_results(suite, variant, created_at);
@@ -0,0 +1,4 @@
+-- 005_skill_runs.down.sql — Remove per-task skill runs
+
+DROP INDEX IF EXISTS idx_skill_runs_base;
+DROP TABLE IF EXISTS skill_runs;
@@ -0,0 +1,17 @@
+-- 005_skill_runs.up.sql — Which skills (and skill variants) each task ran
+--
+-- `skill_effectiveness` keeps the running average per skill and category;
+-- these rows keep the individual runs behind it, so a variant experiment
+-- can be audited task by task.
+
+CREATE TABLE IF NOT EXISTS skill_runs (
+    task_id     TEXT NOT NULL REFERENCES tasks(id),
+    skill_name  TEXT NOT NULL,
+    base_name   TEXT NOT NULL,
+    category    TEXT NOT NULL,
+    score       REAL NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (task_id, skill_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_skill_runs_base ON skill_runs(base_name, category);
@@ -0,0 +1,3 @@
+-- 006_skill_embeddings.down.sql — Remove cached skill embeddings
+
+DROP TABLE IF EXISTS skill_embeddings;
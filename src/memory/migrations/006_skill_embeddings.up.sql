@@ -0,0 +1,13 @@
+-- 006_skill_embeddings.up.sql — Cached skill embeddings
+--
+-- Keyed by a hash of the embedded text and the embedding model, so a skill
+-- is only re-embedded when its description (or body) changes.
+
+CREATE TABLE IF NOT EXISTS skill_embeddings (
+    content_hash TEXT NOT NULL,
+    model        TEXT NOT NULL,
+    embedding    BLOB NOT NULL,
+    dimensions   INTEGER NOT NULL,
+    created_at   TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (content_hash, model)
+);
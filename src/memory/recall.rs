@@ -1,6 +1,6 @@
 // src/memory/recall.rs — Token-budgeted recall
 
-use crate::core::token_optimizer::estimate_tokens;
+use crate::core::tokenizer::TokenCounter;
 use crate::memory::store::{LearningRow, Store};
 
 /// Result of recalling relevant history for a task.
@@ -14,12 +14,14 @@ pub struct HistoryRecall {
     pub tokens_used: u32,
 }
 
-/// Recall relevant history within a token budget.
+/// Recall relevant history within a token budget, measured with the
+/// executor model's `counter`.
 pub fn recall(
     store: &Store,
     _task_description: &str,
     _task_category: Option<&str>,
     token_budget: u32,
+    counter: &dyn TokenCounter,
 ) -> anyhow::Result<HistoryRecall> {
     let mut used_tokens: u32 = 0;
     let mut recall = HistoryRecall::default();
@@ -27,7 +29,7 @@ pub fn recall(
     // Priority 1: Anti-patterns (cheap, high-value)
     if let Ok(anti_patterns) = store.query_learnings_by_type("anti_pattern", 5) {
         for ap in anti_patterns {
-            let tokens = estimate_tokens(&ap.content);
+            let tokens = counter.count(&ap.content);
             if used_tokens + tokens > token_budget {
                 break;
             }
@@ -40,7 +42,7 @@ pub fn recall(
     if let Some(cat) = _task_category {
         if let Ok(skills) = store.query_top_skills_for_category(cat, 3) {
             for s in skills {
-                let tokens = estimate_tokens(&s.skill_name) + 10;
+                let tokens = counter.count(&s.skill_name) + 10;
                 if used_tokens + tokens > token_budget {
                     break;
                 }
@@ -53,7 +55,7 @@ pub fn recall(
     // Priority 3: Relevant learnings (medium cost)
     if let Ok(learnings) = store.query_learnings_by_type("heuristic", 5) {
         for l in learnings {
-            let tokens = estimate_tokens(&l.content);
+            let tokens = counter.count(&l.content);
             if used_tokens + tokens > token_budget {
                 break;
             }
@@ -24,6 +24,30 @@ const MIGRATIONS: &[Migration] = &[
         up: include_str!("migrations/002_vector_search.up.sql"),
         down: include_str!("migrations/002_vector_search.down.sql"),
     },
+    Migration {
+        version: 3,
+        name: "code_index",
+        up: include_str!("migrations/003_code_index.up.sql"),
+        down: include_str!("migrations/003_code_index.down.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "bench_results",
+        up: include_str!("migrations/004_bench_results.up.sql"),
+        down: include_str!("migrations/004_bench_results.down.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "skill_runs",
+        up: include_str!("migrations/005_skill_runs.up.sql"),
+        down: include_str!("migrations/005_skill_runs.down.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "skill_embeddings",
+        up: include_str!("migrations/006_skill_embeddings.up.sql"),
+        down: include_str!("migrations/006_skill_embeddings.down.sql"),
+    },
 ];
 
 /// Run all pending migrations.
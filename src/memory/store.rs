@@ -3,7 +3,12 @@
 use chrono::Utc;
 use rusqlite::{params, Connection};
 
+use crate::security::redact::{redact, redact_opt};
+
 /// Low-level SQLite operations for all data types.
+///
+/// Free-text columns (task descriptions, findings, learnings, memory chunks,
+/// usage descriptions) are passed through the secret redactor on insert.
 pub struct Store {
     conn: Connection,
 }
@@ -55,7 +60,7 @@ impl Store {
         self.conn.execute(
             "INSERT INTO tasks (id, description, category, session_id, created_at)
              VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![id, description, category, session_id, now],
+            params![id, redact(description), category, session_id, now],
         )?;
         Ok(())
     }
@@ -144,10 +149,10 @@ impl Store {
                 cycle_id,
                 severity,
                 dimension,
-                title,
-                description,
+                redact(title),
+                redact_opt(description),
                 location,
-                fix
+                redact_opt(fix)
             ],
         )?;
         Ok(())
@@ -172,7 +177,7 @@ impl Store {
             params![
                 id,
                 learning_type,
-                content,
+                redact(content),
                 category,
                 confidence,
                 source_task,
@@ -367,6 +372,104 @@ impl Store {
         }
     }
 
+    /// Every skill's effectiveness in every category.
+    pub fn query_all_skill_effectiveness(&self) -> anyhow::Result<Vec<SkillEffectivenessRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT skill_name, task_category, avg_score, sample_count
+             FROM skill_effectiveness
+             ORDER BY skill_name, task_category",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(SkillEffectivenessRow {
+                skill_name: row.get(0)?,
+                task_category: row.get(1)?,
+                avg_score: row.get(2)?,
+                sample_count: row.get(3)?,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
+    /// Record that a task ran a skill (or skill variant) and fold its final
+    /// score into the skill's effectiveness for the category.
+    pub fn record_skill_run(
+        &self,
+        task_id: &str,
+        skill_name: &str,
+        base_name: &str,
+        category: &str,
+        score: f64,
+    ) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO skill_runs (task_id, skill_name, base_name, category, score)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![task_id, skill_name, base_name, category, score],
+        )?;
+        self.upsert_skill_effectiveness(skill_name, category, score)
+    }
+
+    /// Make a promoted variant's effectiveness the base skill's own: the
+    /// base's rows are replaced by the variant's.
+    pub fn promote_skill_variant(&self, variant: &str, base: &str) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM skill_effectiveness WHERE skill_name = ?1",
+            params![base],
+        )?;
+        tx.execute(
+            "UPDATE skill_effectiveness SET skill_name = ?1 WHERE skill_name = ?2",
+            params![base, variant],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // ─── Skill embeddings ───────────────────────────────────────
+
+    /// Cached embedding for a skill's text, keyed by content hash and model.
+    pub fn get_skill_embedding(
+        &self,
+        content_hash: &str,
+        model: &str,
+    ) -> anyhow::Result<Option<Vec<f32>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT embedding FROM skill_embeddings
+             WHERE content_hash = ?1 AND model = ?2",
+        )?;
+        let mut rows =
+            stmt.query_map(params![content_hash, model], |row| row.get::<_, Vec<u8>>(0))?;
+        match rows.next() {
+            Some(blob) => Ok(Some(
+                blob?
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put_skill_embedding(
+        &self,
+        content_hash: &str,
+        model: &str,
+        embedding: &[f32],
+    ) -> anyhow::Result<()> {
+        let blob: Vec<u8> = embedding.iter().flat_map(|x| x.to_le_bytes()).collect();
+        self.conn.execute(
+            "INSERT OR REPLACE INTO skill_embeddings (content_hash, model, embedding, dimensions)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![content_hash, model, blob, embedding.len() as i64],
+        )?;
+        Ok(())
+    }
+
     pub fn query_top_skills_for_category(
         &self,
         category: &str,
@@ -402,7 +505,7 @@ impl Store {
         self.conn.execute(
             "INSERT INTO memory_chunks (id, source, text, created_at)
              VALUES (?1, ?2, ?3, ?4)",
-            params![id, source, text, now],
+            params![id, source, redact(text), now],
         )?;
         Ok(())
     }
@@ -432,7 +535,7 @@ impl Store {
                 id,
                 event_type,
                 channel,
-                description,
+                redact_opt(description),
                 category,
                 skills_used,
                 score,
@@ -497,7 +600,7 @@ impl Store {
             params![
                 id,
                 pattern_type,
-                description,
+                redact(description),
                 frequency,
                 trigger_json,
                 confidence,
@@ -571,6 +674,66 @@ impl Store {
         Ok(result)
     }
 
+    // -- Benchmarks --
+
+    pub fn insert_bench_result(&self, row: &BenchResultRow) -> anyhow::Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO bench_results (run_id, suite, variant, case_id, attempt, passed,
+             score, iterations, tokens, cost_usd, duration_ms, failures, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                row.run_id,
+                row.suite,
+                row.variant,
+                row.case_id,
+                row.attempt,
+                row.passed,
+                row.score,
+                row.iterations,
+                row.tokens,
+                row.cost_usd,
+                row.duration_ms,
+                redact_opt(row.failures.as_deref()),
+                now
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Per-variant totals of the latest benchmark runs, newest first.
+    pub fn query_bench_runs(&self, limit: u32) -> anyhow::Result<Vec<BenchRunRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT run_id, suite, variant, MIN(created_at), COUNT(*), SUM(passed),
+             AVG(score), AVG(iterations), AVG(tokens), SUM(cost_usd)
+             FROM bench_results
+             GROUP BY run_id, suite, variant
+             ORDER BY MIN(created_at) DESC, variant
+             LIMIT ?1",
+        )?;
+
+        let rows = stmt.query_map(params![limit], |row| {
+            Ok(BenchRunRow {
+                run_id: row.get(0)?,
+                suite: row.get(1)?,
+                variant: row.get(2)?,
+                started_at: row.get(3)?,
+                runs: row.get(4)?,
+                passed: row.get(5)?,
+                avg_score: row.get(6)?,
+                avg_iterations: row.get(7)?,
+                avg_tokens: row.get(8)?,
+                total_cost: row.get(9)?,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        Ok(result)
+    }
+
     /// Get a reference to the underlying connection (for advanced queries).
     pub fn conn(&self) -> &Connection {
         &self.conn
@@ -627,3 +790,34 @@ pub struct UsagePatternRow {
     pub proposed_skill: Option<String>,
     pub status: Option<String>,
 }
+
+#[derive(Debug, Clone)]
+pub struct BenchResultRow {
+    pub run_id: String,
+    pub suite: String,
+    pub variant: String,
+    pub case_id: String,
+    pub attempt: i32,
+    pub passed: bool,
+    pub score: Option<f64>,
+    pub iterations: i32,
+    pub tokens: i64,
+    pub cost_usd: f64,
+    pub duration_ms: i64,
+    /// Failed checks, one per line.
+    pub failures: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchRunRow {
+    pub run_id: String,
+    pub suite: String,
+    pub variant: String,
+    pub started_at: String,
+    pub runs: i64,
+    pub passed: i64,
+    pub avg_score: Option<f64>,
+    pub avg_iterations: f64,
+    pub avg_tokens: f64,
+    pub total_cost: f64,
+}
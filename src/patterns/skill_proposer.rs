@@ -3,9 +3,13 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
 use crate::infra::paths;
 use crate::patterns::miner::DetectedPattern;
+use crate::provider::structured::{
+    chat_structured, extract_json_value, salvage_entries, StructuredError, StructuredOutput,
+};
 use crate::provider::{ChatRequest, Message, ModelProvider};
 
 /// Proposes new skills from detected patterns.
@@ -43,34 +47,134 @@ impl SkillProposer {
         })
     }
 
-    /// Use the planner model to generate a SKILL.md from a pattern.
+    /// Use the planner model to draft a skill from a pattern and render it
+    /// as SKILL.md.
     async fn generate_skill_md(&self, pattern: &DetectedPattern) -> Result<String> {
-        let response = self
-            .model
-            .chat(ChatRequest {
-                messages: vec![Message::user(format!(
-                    "Generate a SKILL.md file for a recurring task pattern.\n\n\
-                     Pattern: {desc}\n\
-                     Type: {ptype}\n\
-                     Frequency: {freq}\n\
-                     Confidence: {conf:.2}\n\
-                     Samples: {samples}\n\n\
-                     Output a complete SKILL.md with YAML frontmatter and instructions.\n\
-                     The frontmatter should include: name, description, category, trigger.\n\
-                     The body should contain step-by-step instructions for the agent.",
-                    desc = pattern.description,
-                    ptype = pattern.pattern_type.as_str(),
-                    freq = pattern.frequency.as_deref().unwrap_or("unknown"),
-                    conf = pattern.confidence,
-                    samples = pattern.sample_count,
-                ))],
-                max_tokens: Some(1500),
-                temperature: Some(0.3),
-                ..Default::default()
-            })
-            .await?;
-
-        Ok(response.content)
+        let request = ChatRequest {
+            messages: vec![Message::user(format!(
+                "Draft a skill for a recurring task pattern.\n\n\
+                 Pattern: {desc}\n\
+                 Type: {ptype}\n\
+                 Frequency: {freq}\n\
+                 Confidence: {conf:.2}\n\
+                 Samples: {samples}\n\n\
+                 Respond with a JSON object:\n\
+                 {{\"description\": one sentence saying when to use the skill, \
+                 \"categories\": [task categories], \
+                 \"instructions\": markdown body with step-by-step instructions for the agent}}",
+                desc = pattern.description,
+                ptype = pattern.pattern_type.as_str(),
+                freq = pattern.frequency.as_deref().unwrap_or("unknown"),
+                conf = pattern.confidence,
+                samples = pattern.sample_count,
+            ))],
+            max_tokens: Some(1500),
+            temperature: Some(0.3),
+            ..Default::default()
+        };
+
+        let draft = match chat_structured::<SkillDraft>(&*self.model, request).await {
+            Ok(resp) => resp.value,
+            Err(StructuredError::Invalid { reason, raw, .. }) => SkillDraft::salvage(&raw)
+                .ok_or_else(|| anyhow::anyhow!("Skill proposal reply unusable ({})", reason))?,
+            Err(StructuredError::Provider(e)) => return Err(e.into()),
+        };
+        draft.to_skill_md(&slugify(&pattern.description))
+    }
+}
+
+/// A proposed skill returned as schema-validated JSON.
+#[derive(Debug, Deserialize)]
+struct SkillDraft {
+    description: String,
+    categories: Vec<String>,
+    instructions: String,
+}
+
+/// Frontmatter written for proposed skills (see `skills::frontmatter`).
+#[derive(Serialize)]
+struct ProposedFrontmatter<'a> {
+    name: &'a str,
+    kind: &'static str,
+    description: &'a str,
+    metadata: ProposedMetadata<'a>,
+}
+
+#[derive(Serialize)]
+struct ProposedMetadata<'a> {
+    categories: &'a [String],
+}
+
+impl SkillDraft {
+    /// The usable parts of a reply that failed validation; `None` when it
+    /// has no JSON object or no instructions.
+    fn salvage(raw: &str) -> Option<Self> {
+        let value = extract_json_value(raw)?;
+        let text = |key: &str| {
+            value
+                .get(key)
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        };
+        let instructions = text("instructions");
+        if instructions.is_empty() {
+            return None;
+        }
+        Some(Self {
+            description: text("description"),
+            categories: salvage_entries(&value, "categories"),
+            instructions,
+        })
+    }
+
+    fn to_skill_md(&self, name: &str) -> Result<String> {
+        let frontmatter = serde_yml::to_string(&ProposedFrontmatter {
+            name,
+            kind: "task",
+            description: self.description.trim(),
+            metadata: ProposedMetadata {
+                categories: &self.categories,
+            },
+        })?;
+        Ok(format!(
+            "---\n{}---\n\n{}\n",
+            frontmatter,
+            self.instructions.trim()
+        ))
+    }
+}
+
+impl StructuredOutput for SkillDraft {
+    fn schema_name() -> &'static str {
+        "skill_proposal"
+    }
+
+    fn json_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "description": { "type": "string" },
+                "categories": { "type": "array", "items": { "type": "string" } },
+                "instructions": {
+                    "type": "string",
+                    "description": "Markdown body of SKILL.md, without frontmatter"
+                }
+            },
+            "required": ["description", "categories", "instructions"],
+            "additionalProperties": false
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.description.trim().is_empty() {
+            return Err("description is empty".into());
+        }
+        if self.instructions.trim().is_empty() {
+            return Err("instructions are empty".into());
+        }
+        Ok(())
     }
 }
 
@@ -85,3 +189,39 @@ fn slugify(s: &str) -> String {
         .collect::<Vec<_>>()
         .join("-")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skills::frontmatter::parse_skill_md;
+
+    #[test]
+    fn test_skill_draft_renders_loadable_skill_md() {
+        let draft = SkillDraft {
+            description: "Use for weekly: release notes".into(),
+            categories: vec!["docs".into()],
+            instructions: "1. Collect merged PRs\n2. Group by area".into(),
+        };
+        let md = draft.to_skill_md("weekly-release-notes").unwrap();
+        let (fm, body) = parse_skill_md(&md).unwrap();
+        assert_eq!(fm.name.as_deref(), Some("weekly-release-notes"));
+        assert_eq!(
+            fm.description.as_deref(),
+            Some("Use for weekly: release notes")
+        );
+        assert_eq!(
+            fm.metadata.unwrap().categories,
+            Some(vec!["docs".to_string()])
+        );
+        assert!(body.starts_with("1. Collect merged PRs"));
+    }
+
+    #[test]
+    fn test_skill_draft_salvage() {
+        let draft =
+            SkillDraft::salvage(r#"{"instructions": "Do it", "categories": ["a", 1]}"#).unwrap();
+        assert_eq!(draft.categories, vec!["a"]);
+        assert!(SkillDraft::salvage(r#"{"description": "x"}"#).is_none());
+        assert!(SkillDraft::salvage("---\nname: x\n---").is_none());
+    }
+}
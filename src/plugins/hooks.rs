@@ -4,8 +4,11 @@
 // Each hook point represents a stage in the agent lifecycle where
 // plugins can observe or modify behavior.
 
+use std::sync::Arc;
+
 use crate::plugins::rhai_host::RhaiHost;
 use crate::plugins::wasm::WasmPluginManager;
+use crate::tools::ToolProvider;
 
 /// Hook points in the agent lifecycle.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -71,13 +74,16 @@ impl std::fmt::Display for Hook {
 /// and dispatches hook calls to both in order: Rhai first (lightweight), then WASM.
 pub struct HookExecutor {
     wasm: Option<WasmPluginManager>,
-    rhai: Option<RhaiHost>,
+    rhai: Option<Arc<RhaiHost>>,
 }
 
 impl HookExecutor {
     /// Create a new HookExecutor with the given plugin managers.
     pub fn new(wasm: Option<WasmPluginManager>, rhai: Option<RhaiHost>) -> Self {
-        Self { wasm, rhai }
+        Self {
+            wasm,
+            rhai: rhai.map(Arc::new),
+        }
     }
 
     /// Create an empty executor with no plugins.
@@ -152,7 +158,19 @@ impl HookExecutor {
 
     /// Get reference to the Rhai host (for direct operations).
     pub fn rhai(&self) -> Option<&RhaiHost> {
-        self.rhai.as_ref()
+        self.rhai.as_deref()
+    }
+
+    /// Tool providers for plugins and scripts that declare tools.
+    pub fn tool_providers(&self) -> Vec<Arc<dyn ToolProvider>> {
+        let mut providers = Vec::new();
+        if let Some(ref rhai_host) = self.rhai {
+            providers.extend(RhaiHost::tool_providers(rhai_host));
+        }
+        if let Some(ref wasm_mgr) = self.wasm {
+            providers.extend(wasm_mgr.tool_providers());
+        }
+        providers
     }
 }
 
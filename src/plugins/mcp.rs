@@ -3,22 +3,26 @@
 use std::collections::HashMap;
 use std::path::Path;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use serde_json::{json, Value};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{oneshot, Mutex};
 
 use crate::infra::config::McpServerConfig;
 use crate::provider::ToolDef;
+use crate::tools::{ToolEffect, ToolProvider, ToolSource, NAMESPACE_SEPARATOR};
 
 /// A single MCP tool server subprocess.
 pub struct McpToolServer {
     pub name: String,
     pub tools: Vec<McpTool>,
-    process: Child,
-    stdin: ChildStdin,
-    stdout: BufReader<ChildStdout>,
+    process: Mutex<Child>,
+    client: McpClient,
 }
 
 /// A tool exposed by an MCP server.
@@ -27,6 +31,8 @@ pub struct McpTool {
     pub name: String,
     pub description: String,
     pub input_schema: Value,
+    /// The server's `readOnlyHint` annotation.
+    pub read_only: bool,
 }
 
 impl McpTool {
@@ -42,7 +48,7 @@ impl McpTool {
 
 /// Manages all MCP tool servers.
 pub struct McpManager {
-    servers: HashMap<String, McpToolServer>,
+    servers: HashMap<String, Arc<McpToolServer>>,
 }
 
 impl Default for McpManager {
@@ -75,10 +81,11 @@ impl McpManager {
                             tools.len()
                         );
                         server.tools = tools;
-                        self.servers.insert(cfg.name.clone(), server);
+                        self.servers.insert(cfg.name.clone(), Arc::new(server));
                     }
                     Err(e) => {
                         tracing::warn!("MCP server '{}' initialization failed: {}", cfg.name, e);
+                        server.shutdown().await.ok();
                     }
                 },
                 Err(e) => {
@@ -98,17 +105,28 @@ impl McpManager {
     }
 
     /// Route a tool call to the correct server.
-    pub async fn call(&mut self, server: &str, tool: &str, args: Value) -> Result<Value> {
+    pub async fn call(&self, server: &str, tool: &str, args: Value) -> Result<Value> {
         let srv = self
             .servers
-            .get_mut(server)
+            .get(server)
             .ok_or_else(|| anyhow!("MCP server '{}' not found", server))?;
         srv.call_tool(tool, args).await
     }
 
+    /// One tool provider per running server. Providers talk to their server
+    /// directly, so calls never wait on `manager`.
+    pub async fn tool_providers(manager: &Arc<Mutex<McpManager>>) -> Vec<Arc<dyn ToolProvider>> {
+        let guard = manager.lock().await;
+        guard
+            .servers
+            .values()
+            .map(|s| Arc::new(McpServerTools { server: s.clone() }) as Arc<dyn ToolProvider>)
+            .collect()
+    }
+
     /// Graceful shutdown: send shutdown notification, wait, kill.
     pub async fn shutdown_all(&mut self) {
-        for (name, mut server) in self.servers.drain() {
+        for (name, server) in self.servers.drain() {
             if let Err(e) = server.shutdown().await {
                 tracing::warn!("MCP server '{}' shutdown error: {}", name, e);
             }
@@ -116,6 +134,56 @@ impl McpManager {
     }
 }
 
+/// Exposes one MCP server's tools (as `server__tool`) to the tool registry.
+pub struct McpServerTools {
+    server: Arc<McpToolServer>,
+}
+
+#[async_trait]
+impl ToolProvider for McpServerTools {
+    fn id(&self) -> &str {
+        &self.server.name
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::Mcp
+    }
+
+    fn tools(&self) -> Vec<ToolDef> {
+        self.server
+            .tools
+            .iter()
+            .map(|t| t.to_tool_def(&self.server.name))
+            .collect()
+    }
+
+    fn effect(&self, tool: &str) -> ToolEffect {
+        let read_only = self
+            .server
+            .tools
+            .iter()
+            .any(|t| t.read_only && t.to_tool_def(&self.server.name).name == tool);
+        if read_only {
+            ToolEffect::ReadOnly
+        } else {
+            ToolEffect::Mutating
+        }
+    }
+
+    async fn call(&self, tool: &str, args: Value) -> Result<String> {
+        let bare = tool
+            .strip_prefix(self.server.name.as_str())
+            .and_then(|t| t.strip_prefix(NAMESPACE_SEPARATOR))
+            .unwrap_or(tool);
+        let result = self.server.call_tool(bare, args).await?;
+        // MCP returns a JSON Value; convert to string for the model
+        Ok(match result.as_str() {
+            Some(s) => s.to_string(),
+            None => serde_json::to_string_pretty(&result).unwrap_or_default(),
+        })
+    }
+}
+
 impl McpToolServer {
     /// Spawn an MCP server subprocess.
     async fn spawn(cfg: &McpServerConfig) -> Result<Self> {
@@ -123,7 +191,8 @@ impl McpToolServer {
         cmd.args(&cfg.args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::null());
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
 
         // Pass configured env vars
         for (k, v) in &cfg.env {
@@ -144,47 +213,30 @@ impl McpToolServer {
         Ok(Self {
             name: cfg.name.clone(),
             tools: Vec::new(),
-            process,
-            stdin,
-            stdout: BufReader::new(stdout),
+            process: Mutex::new(process),
+            client: McpClient::new(stdin, stdout),
         })
     }
 
     /// Initialize the MCP server and discover available tools.
     async fn initialize(&mut self) -> Result<Vec<McpTool>> {
-        // Send initialize request
-        let init_req = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "initialize",
-            "params": {
-                "protocolVersion": "2024-11-05",
-                "capabilities": {},
-                "clientInfo": {
-                    "name": "openkoi",
-                    "version": env!("CARGO_PKG_VERSION")
-                }
-            }
-        });
-        self.send_request(&init_req).await?;
-        let _init_resp = self.read_response().await?;
-
-        // Send initialized notification
-        let initialized = json!({
-            "jsonrpc": "2.0",
-            "method": "notifications/initialized"
-        });
-        self.send_request(&initialized).await?;
+        let _init_resp = self
+            .client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "openkoi",
+                        "version": env!("CARGO_PKG_VERSION")
+                    }
+                }),
+            )
+            .await?;
+        self.client.notify("notifications/initialized").await?;
 
-        // List tools
-        let list_req = json!({
-            "jsonrpc": "2.0",
-            "id": 2,
-            "method": "tools/list",
-            "params": {}
-        });
-        self.send_request(&list_req).await?;
-        let list_resp = self.read_response().await?;
+        let list_resp = self.client.request("tools/list", json!({})).await?;
 
         let tools = list_resp
             .get("result")
@@ -201,6 +253,10 @@ impl McpToolServer {
                                 .unwrap_or("")
                                 .to_string(),
                             input_schema: v.get("inputSchema").cloned().unwrap_or(json!({})),
+                            read_only: v
+                                .pointer("/annotations/readOnlyHint")
+                                .and_then(|h| h.as_bool())
+                                .unwrap_or(false),
                         })
                     })
                     .collect()
@@ -210,39 +266,146 @@ impl McpToolServer {
         Ok(tools)
     }
 
-    /// Call a tool on the MCP server.
-    pub async fn call_tool(&mut self, name: &str, params: Value) -> Result<Value> {
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": 3,
-            "method": "tools/call",
-            "params": { "name": name, "arguments": params }
-        });
-        self.send_request(&request).await?;
-        let response = self.read_response().await?;
+    /// Call a tool on the MCP server. Calls may overlap; responses are
+    /// matched to callers by request id.
+    pub async fn call_tool(&self, name: &str, params: Value) -> Result<Value> {
+        let response = self
+            .client
+            .request("tools/call", json!({ "name": name, "arguments": params }))
+            .await?;
         Ok(response.get("result").cloned().unwrap_or(json!(null)))
     }
 
     /// Graceful shutdown.
-    async fn shutdown(&mut self) -> Result<()> {
+    async fn shutdown(&self) -> Result<()> {
+        self.client.close();
         // Try to kill the process
-        self.process.kill().await.ok();
+        self.process.lock().await.kill().await.ok();
         Ok(())
     }
+}
 
-    async fn send_request(&mut self, request: &Value) -> Result<()> {
-        let line = serde_json::to_string(request)?;
-        self.stdin.write_all(line.as_bytes()).await?;
-        self.stdin.write_all(b"\n").await?;
-        self.stdin.flush().await?;
-        Ok(())
+/// Waiters by request id; `None` once the connection is closed.
+type PendingRequests = Arc<std::sync::Mutex<Option<HashMap<u64, oneshot::Sender<Value>>>>>;
+
+/// JSON-RPC over a line-delimited stream pair. Requests get unique ids; a
+/// reader task hands each response to the caller waiting on its id, so
+/// several requests can be in flight at once.
+struct McpClient {
+    writer: Mutex<Box<dyn AsyncWrite + Send + Unpin>>,
+    pending: PendingRequests,
+    next_id: AtomicU64,
+    reader: tokio::task::JoinHandle<()>,
+}
+
+impl McpClient {
+    fn new(
+        writer: impl AsyncWrite + Send + Unpin + 'static,
+        reader: impl AsyncRead + Send + Unpin + 'static,
+    ) -> Self {
+        let pending: PendingRequests = Arc::new(std::sync::Mutex::new(Some(HashMap::new())));
+        let reader = tokio::spawn(Self::read_responses(
+            BufReader::new(reader),
+            pending.clone(),
+        ));
+        Self {
+            writer: Mutex::new(Box::new(writer)),
+            pending,
+            next_id: AtomicU64::new(1),
+            reader,
+        }
     }
 
-    async fn read_response(&mut self) -> Result<Value> {
+    /// Route responses to their requests until the stream closes, then fail
+    /// everything still waiting.
+    async fn read_responses(
+        mut reader: BufReader<impl AsyncRead + Unpin>,
+        pending: PendingRequests,
+    ) {
         let mut line = String::new();
-        self.stdout.read_line(&mut line).await?;
-        let response: Value = serde_json::from_str(&line)?;
-        Ok(response)
+        loop {
+            line.clear();
+            match reader.read_line(&mut line).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            // Notifications and server-initiated requests have no waiter.
+            let Some(id) = message.get("id").and_then(Value::as_u64) else {
+                continue;
+            };
+            if message.get("method").is_some() {
+                continue;
+            }
+            let waiter = pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_mut()
+                .and_then(|waiters| waiters.remove(&id));
+            if let Some(tx) = waiter {
+                let _ = tx.send(message);
+            }
+        }
+        pending.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
+
+    /// Send a request and wait for its response.
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        match self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_mut()
+        {
+            Some(waiters) => waiters.insert(id, tx),
+            None => return Err(anyhow!("MCP server closed the connection")),
+        };
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        if let Err(e) = self.send(&request).await {
+            if let Some(waiters) = self
+                .pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_mut()
+            {
+                waiters.remove(&id);
+            }
+            return Err(e);
+        }
+        rx.await
+            .map_err(|_| anyhow!("MCP server closed the connection"))
+    }
+
+    async fn notify(&self, method: &str) -> Result<()> {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method }))
+            .await
+    }
+
+    async fn send(&self, message: &Value) -> Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        let mut writer = self.writer.lock().await;
+        writer.write_all(line.as_bytes()).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    /// Stop reading; waiting requests fail.
+    fn close(&self) {
+        self.reader.abort();
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
     }
 }
 
@@ -302,3 +465,58 @@ pub fn discover_mcp_json(project_root: &Path) -> Vec<McpServerConfig> {
         })
         .unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_client_matches_out_of_order_responses() {
+        let (client_side, server_side) = tokio::io::duplex(4096);
+        let (client_read, client_write) = tokio::io::split(client_side);
+        let (server_read, mut server_write) = tokio::io::split(server_side);
+        let client = McpClient::new(client_write, client_read);
+
+        // Answer both requests only once both have arrived, newest first:
+        // the first call can only finish if the second is in flight too.
+        let server = tokio::spawn(async move {
+            let mut lines = BufReader::new(server_read).lines();
+            let mut ids = Vec::new();
+            while ids.len() < 2 {
+                let line = lines.next_line().await.unwrap().unwrap();
+                let request: Value = serde_json::from_str(&line).unwrap();
+                ids.push(request["id"].clone());
+            }
+            for id in ids.iter().rev() {
+                let response = json!({"jsonrpc": "2.0", "id": id, "result": id});
+                let line = format!("{response}\n");
+                server_write.write_all(line.as_bytes()).await.unwrap();
+            }
+        });
+
+        let (a, b) = tokio::join!(
+            client.request("tools/call", json!({})),
+            client.request("tools/call", json!({})),
+        );
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert_eq!(a["result"], a["id"]);
+        assert_eq!(b["result"], b["id"]);
+        assert_ne!(a["id"], b["id"]);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_client_fails_pending_requests_on_close() {
+        let (client_side, server_side) = tokio::io::duplex(4096);
+        let (client_read, client_write) = tokio::io::split(client_side);
+        let client = McpClient::new(client_write, client_read);
+        let server = tokio::spawn(async move {
+            let mut lines = BufReader::new(server_side).lines();
+            lines.next_line().await.unwrap();
+            // Dropping the stream closes the connection.
+        });
+        let err = client.request("tools/list", json!({})).await.unwrap_err();
+        assert!(err.to_string().contains("closed"));
+        server.await.unwrap();
+    }
+}
@@ -5,10 +5,14 @@
 // Trust level: Medium — no I/O unless explicitly exposed by host.
 
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use async_trait::async_trait;
 use rhai::{Dynamic, Engine, Scope, AST};
 
 use crate::plugins::hooks::Hook;
+use crate::provider::ToolDef;
+use crate::tools::{ToolEffect, ToolProvider, ToolSource};
 
 // ---------------------------------------------------------------------------
 // Configuration for which host APIs to expose
@@ -49,6 +53,10 @@ struct LoadedScript {
     name: String,
     ast: AST,
     hooks: Vec<String>,
+    /// Tools declared by the script's `tools()` function.
+    tools: Vec<ToolDef>,
+    /// Declared tools marked `read_only: true`.
+    read_only: Vec<String>,
 }
 
 impl RhaiHost {
@@ -88,17 +96,133 @@ impl RhaiHost {
         // named after hook points.
         let hooks = discover_hook_functions(&ast);
 
+        let (tools, read_only) = self.discover_tools(&name, &ast);
+
         tracing::info!(
-            "Loaded Rhai script: {} ({} hooks: [{}])",
+            "Loaded Rhai script: {} ({} hooks: [{}], {} tools)",
             name,
             hooks.len(),
-            hooks.join(", ")
+            hooks.join(", "),
+            tools.len()
         );
 
-        self.scripts.push(LoadedScript { name, ast, hooks });
+        self.scripts.push(LoadedScript {
+            name,
+            ast,
+            hooks,
+            tools,
+            read_only,
+        });
         Ok(())
     }
 
+    /// Read tool declarations from a script's `tools()` function, which
+    /// returns an array of `#{ name, description, parameters, read_only }`
+    /// maps. Each tool is implemented by a script function of the same name
+    /// taking the arguments map. Returns the tools and the read-only names.
+    fn discover_tools(&self, script: &str, ast: &AST) -> (Vec<ToolDef>, Vec<String>) {
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == "tools" && f.params.is_empty())
+        {
+            return (Vec::new(), Vec::new());
+        }
+        let declared = match self
+            .engine
+            .call_fn::<Dynamic>(&mut Scope::new(), ast, "tools", ())
+        {
+            Ok(d) => dynamic_to_json(&d),
+            Err(e) => {
+                tracing::warn!("Rhai script '{}' tools() failed: {}", script, e);
+                return (Vec::new(), Vec::new());
+            }
+        };
+
+        let tools: Vec<ToolDef> = declared
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|t| {
+                let name = t.get("name")?.as_str()?.to_string();
+                if !ast.iter_functions().any(|f| f.name == name) {
+                    tracing::warn!(
+                        "Rhai script '{}' declares tool '{}' but defines no such function",
+                        script,
+                        name
+                    );
+                    return None;
+                }
+                Some(ToolDef {
+                    description: t
+                        .get("description")
+                        .and_then(|d| d.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    parameters: t
+                        .get("parameters")
+                        .cloned()
+                        .unwrap_or_else(|| serde_json::json!({"type": "object"})),
+                    name,
+                })
+            })
+            .collect();
+
+        let read_only = declared
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|t| t.get("read_only").and_then(|r| r.as_bool()) == Some(true))
+            .filter_map(|t| t.get("name")?.as_str().map(str::to_string))
+            .filter(|name| tools.iter().any(|d| &d.name == name))
+            .collect();
+
+        (tools, read_only)
+    }
+
+    /// Call a script-defined tool. Strings are returned as-is; other values
+    /// are serialized as JSON.
+    pub fn call_tool(
+        &self,
+        script: &str,
+        tool: &str,
+        args: &serde_json::Value,
+    ) -> anyhow::Result<String> {
+        let loaded = self
+            .scripts
+            .iter()
+            .find(|s| s.name == script)
+            .ok_or_else(|| anyhow::anyhow!("Rhai script '{}' not loaded", script))?;
+        let result = self
+            .engine
+            .call_fn::<Dynamic>(
+                &mut Scope::new(),
+                &loaded.ast,
+                tool,
+                (json_to_dynamic(args),),
+            )
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(match dynamic_to_json(&result) {
+            serde_json::Value::String(s) => s,
+            other => serde_json::to_string_pretty(&other).unwrap_or_default(),
+        })
+    }
+
+    /// One tool provider per script that declares tools.
+    pub fn tool_providers(host: &Arc<RhaiHost>) -> Vec<Arc<dyn ToolProvider>> {
+        host.scripts
+            .iter()
+            .filter(|s| !s.tools.is_empty())
+            .map(|s| {
+                Arc::new(RhaiScriptTools {
+                    host: host.clone(),
+                    script: s.name.clone(),
+                    tools: s.tools.clone(),
+                    read_only: s.read_only.clone(),
+                }) as Arc<dyn ToolProvider>
+            })
+            .collect()
+    }
+
     /// Run a hook on all loaded scripts that define a function for it.
     pub fn run_hook(&self, hook: &Hook, context: &serde_json::Value) -> anyhow::Result<()> {
         let hook_str = hook.as_str();
@@ -206,6 +330,43 @@ impl RhaiHost {
     }
 }
 
+/// Exposes a Rhai script's declared tools to the tool registry.
+pub struct RhaiScriptTools {
+    host: Arc<RhaiHost>,
+    script: String,
+    tools: Vec<ToolDef>,
+    read_only: Vec<String>,
+}
+
+#[async_trait]
+impl ToolProvider for RhaiScriptTools {
+    fn id(&self) -> &str {
+        &self.script
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::Plugin
+    }
+
+    fn tools(&self) -> Vec<ToolDef> {
+        self.tools.clone()
+    }
+
+    fn effect(&self, tool: &str) -> ToolEffect {
+        if self.read_only.iter().any(|t| t == tool) {
+            ToolEffect::ReadOnly
+        } else {
+            ToolEffect::Mutating
+        }
+    }
+
+    async fn call(&self, tool: &str, args: serde_json::Value) -> anyhow::Result<String> {
+        // Scripts are bounded by the engine's operation limit, so running
+        // them inline is fine.
+        self.host.call_tool(&self.script, tool, &args)
+    }
+}
+
 impl Default for RhaiHost {
     fn default() -> Self {
         Self::with_defaults()
@@ -345,6 +506,29 @@ fn json_to_dynamic(value: &serde_json::Value) -> Dynamic {
     }
 }
 
+/// Convert a Rhai Dynamic back to a serde_json::Value.
+fn dynamic_to_json(value: &Dynamic) -> serde_json::Value {
+    if value.is_unit() {
+        serde_json::Value::Null
+    } else if let Some(b) = value.clone().try_cast::<bool>() {
+        serde_json::Value::Bool(b)
+    } else if let Some(i) = value.clone().try_cast::<i64>() {
+        serde_json::Value::from(i)
+    } else if let Some(f) = value.clone().try_cast::<f64>() {
+        serde_json::Value::from(f)
+    } else if let Some(arr) = value.clone().try_cast::<rhai::Array>() {
+        serde_json::Value::Array(arr.iter().map(dynamic_to_json).collect())
+    } else if let Some(map) = value.clone().try_cast::<rhai::Map>() {
+        serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.to_string(), dynamic_to_json(v)))
+                .collect(),
+        )
+    } else {
+        serde_json::Value::String(value.to_string())
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -401,6 +585,37 @@ fn before_execute(ctx) {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_rhai_script_tools() {
+        let mut host = RhaiHost::with_defaults();
+
+        let script = r#"
+fn tools() {
+    [#{ name: "shout", description: "Uppercase text", read_only: true,
+        parameters: #{ type: "object", properties: #{ text: #{ type: "string" } } } }]
+}
+
+fn shout(args) {
+    to_upper(args.text)
+}
+"#;
+
+        host.load_script_str(Path::new("loud.rhai"), script)
+            .unwrap();
+        let host = Arc::new(host);
+        let providers = RhaiHost::tool_providers(&host);
+        assert_eq!(providers.len(), 1);
+        let tools = providers[0].tools();
+        assert_eq!(tools[0].name, "shout");
+        assert_eq!(tools[0].parameters["type"], "object");
+        assert_eq!(providers[0].effect("shout"), ToolEffect::ReadOnly);
+
+        let out = host
+            .call_tool("loud", "shout", &serde_json::json!({"text": "hi"}))
+            .unwrap();
+        assert_eq!(out, "HI");
+    }
+
     #[test]
     fn test_rhai_host_missing_hook() {
         let mut host = RhaiHost::with_defaults();
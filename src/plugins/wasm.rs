@@ -4,13 +4,20 @@
 // declare what it needs in its TOML manifest, and the user approves on install.
 // Trust level: Low — sandboxed, explicit capabilities required.
 
+use async_trait::async_trait;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 use wasmtime::*;
 
 use crate::plugins::hooks::Hook;
+use crate::provider::ToolDef;
+use crate::tools::{ToolEffect, ToolProvider, ToolSource};
+
+/// Fuel granted per hook or tool call (prevents infinite loops).
+const FUEL_PER_CALL: u64 = 1_000_000;
 
 // ---------------------------------------------------------------------------
 // Capability model
@@ -68,23 +75,35 @@ impl FsGrant {
             return false;
         }
 
-        // Expand ~ to home directory
-        let expanded = if self.pattern.starts_with('~') {
+        // Use glob matching
+        if let Ok(pattern) = glob::Pattern::new(&self.expanded_pattern()) {
+            pattern.matches(path)
+        } else {
+            false
+        }
+    }
+
+    /// The pattern with a leading `~` expanded to the home directory.
+    fn expanded_pattern(&self) -> String {
+        if self.pattern.starts_with('~') {
             let home = crate::infra::paths::dirs_home();
             home.join(self.pattern.trim_start_matches("~/"))
                 .to_string_lossy()
                 .to_string()
         } else {
             self.pattern.clone()
-        };
-
-        // Use glob matching
-        if let Ok(pattern) = glob::Pattern::new(&expanded) {
-            pattern.matches(path)
-        } else {
-            false
         }
     }
+
+    /// The directory a grant covers: the pattern up to its first glob
+    /// component (`~/.cargo/**` → `$HOME/.cargo`). Used where access is
+    /// granted per directory tree rather than per path (process sandboxes).
+    pub fn root(&self) -> PathBuf {
+        Path::new(&self.expanded_pattern())
+            .components()
+            .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+            .collect()
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -99,6 +118,29 @@ pub struct PluginManifest {
     pub capabilities: ManifestCapabilities,
     #[serde(default)]
     pub hooks: Vec<String>,
+    #[serde(default)]
+    pub tools: Vec<ManifestTool>,
+}
+
+/// A tool a plugin exposes to the model.
+///
+/// The plugin exports `alloc(len: i32) -> i32` and `tool_<name>(ptr: i32,
+/// len: i32) -> i64`, which receives the JSON arguments and returns the
+/// result string packed as `(ptr << 32) | len`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_tool_parameters")]
+    pub parameters: serde_json::Value,
+    /// Safe to run in parallel with other read-only calls.
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+fn default_tool_parameters() -> serde_json::Value {
+    serde_json::json!({ "type": "object" })
 }
 
 #[derive(Debug, Deserialize)]
@@ -172,6 +214,9 @@ pub struct WasmPlugin {
     pub version: String,
     pub description: String,
     pub hooks: Vec<String>,
+    pub tools: Vec<ToolDef>,
+    /// Declared tools marked `read_only`.
+    pub read_only_tools: Vec<String>,
     store: Store<PluginState>,
     instance: Instance,
 }
@@ -232,11 +277,37 @@ impl WasmPlugin {
         // Create wasmtime engine and sandbox
         let (store, instance) = WasmSandbox::instantiate(&wasm_bytes, &caps)?;
 
+        let tools = manifest
+            .as_ref()
+            .map(|m| {
+                m.tools
+                    .iter()
+                    .map(|t| ToolDef {
+                        name: t.name.clone(),
+                        description: t.description.clone(),
+                        parameters: t.parameters.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let read_only_tools = manifest
+            .as_ref()
+            .map(|m| {
+                m.tools
+                    .iter()
+                    .filter(|t| t.read_only)
+                    .map(|t| t.name.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Ok(Self {
             name,
             version,
             description,
             hooks,
+            tools,
+            read_only_tools,
             store,
             instance,
         })
@@ -305,6 +376,36 @@ impl WasmPlugin {
         Ok(())
     }
 
+    /// Call a manifest-declared tool via the `alloc` / `tool_<name>` exports.
+    pub fn call_tool(&mut self, tool: &str, args_json: &str) -> anyhow::Result<String> {
+        self.store.set_fuel(FUEL_PER_CALL)?;
+
+        let memory = self
+            .instance
+            .get_memory(&mut self.store, "memory")
+            .ok_or_else(|| anyhow::anyhow!("plugin '{}' exports no memory", self.name))?;
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&mut self.store, "alloc")?;
+        let func = self
+            .instance
+            .get_typed_func::<(i32, i32), i64>(&mut self.store, &format!("tool_{tool}"))?;
+
+        let input = args_json.as_bytes();
+        let ptr = alloc.call(&mut self.store, input.len() as i32)?;
+        memory.write(&mut self.store, ptr as usize, input)?;
+
+        let packed = func.call(&mut self.store, (ptr, input.len() as i32))? as u64;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let mut out = vec![0u8; out_len];
+        memory.read(&self.store, out_ptr, &mut out)?;
+
+        for msg in self.drain_logs() {
+            tracing::info!(target: "wasm_plugin", "[{}] {}", self.name, msg);
+        }
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
     /// Drain the log buffer (messages written by the plugin via the `log` host function).
     pub fn drain_logs(&mut self) -> Vec<String> {
         std::mem::take(&mut self.store.data_mut().log_buffer)
@@ -356,7 +457,7 @@ impl WasmSandbox {
         let mut store = Store::new(&engine, state);
 
         // Give plugins a fuel budget (prevents infinite loops)
-        store.set_fuel(1_000_000)?;
+        store.set_fuel(FUEL_PER_CALL)?;
 
         // Compile the module
         let module = Module::new(&engine, wasm_bytes)?;
@@ -487,7 +588,7 @@ impl WasmSandbox {
 
 /// Manages all loaded WASM plugins.
 pub struct WasmPluginManager {
-    plugins: Vec<WasmPlugin>,
+    plugins: Vec<Arc<Mutex<WasmPlugin>>>,
 }
 
 impl WasmPluginManager {
@@ -533,7 +634,7 @@ impl WasmPluginManager {
                         plugin.version,
                         plugin.hooks.len()
                     );
-                    manager.plugins.push(plugin);
+                    manager.plugins.push(Arc::new(Mutex::new(plugin)));
                 }
                 Err(e) => {
                     tracing::warn!(
@@ -550,7 +651,8 @@ impl WasmPluginManager {
 
     /// Run a hook on all plugins that subscribe to it.
     pub fn run_hook(&mut self, hook: &Hook, context_json: &str) -> anyhow::Result<()> {
-        for plugin in &mut self.plugins {
+        for plugin in &self.plugins {
+            let mut plugin = plugin.lock().unwrap_or_else(|e| e.into_inner());
             if plugin.handles_hook(hook) {
                 if let Err(e) = plugin.run_hook(hook, context_json) {
                     tracing::warn!(
@@ -577,8 +679,30 @@ impl WasmPluginManager {
     }
 
     /// List loaded plugin names.
-    pub fn plugin_names(&self) -> Vec<&str> {
-        self.plugins.iter().map(|p| p.name.as_str()).collect()
+    pub fn plugin_names(&self) -> Vec<String> {
+        self.plugins
+            .iter()
+            .map(|p| p.lock().unwrap_or_else(|e| e.into_inner()).name.clone())
+            .collect()
+    }
+
+    /// One tool provider per plugin that declares tools in its manifest.
+    pub fn tool_providers(&self) -> Vec<Arc<dyn ToolProvider>> {
+        self.plugins
+            .iter()
+            .filter_map(|p| {
+                let guard = p.lock().unwrap_or_else(|e| e.into_inner());
+                if guard.tools.is_empty() {
+                    return None;
+                }
+                Some(Arc::new(WasmPluginTools {
+                    name: guard.name.clone(),
+                    tools: guard.tools.clone(),
+                    read_only: guard.read_only_tools.clone(),
+                    plugin: p.clone(),
+                }) as Arc<dyn ToolProvider>)
+            })
+            .collect()
     }
 
     /// Check if any plugins are loaded.
@@ -587,6 +711,51 @@ impl WasmPluginManager {
     }
 }
 
+/// Exposes a WASM plugin's manifest-declared tools to the tool registry.
+pub struct WasmPluginTools {
+    name: String,
+    tools: Vec<ToolDef>,
+    read_only: Vec<String>,
+    plugin: Arc<Mutex<WasmPlugin>>,
+}
+
+#[async_trait]
+impl ToolProvider for WasmPluginTools {
+    fn id(&self) -> &str {
+        &self.name
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::Plugin
+    }
+
+    fn tools(&self) -> Vec<ToolDef> {
+        self.tools.clone()
+    }
+
+    fn effect(&self, tool: &str) -> ToolEffect {
+        if self.read_only.iter().any(|t| t == tool) {
+            ToolEffect::ReadOnly
+        } else {
+            ToolEffect::Mutating
+        }
+    }
+
+    async fn call(&self, tool: &str, args: serde_json::Value) -> anyhow::Result<String> {
+        let plugin = self.plugin.clone();
+        let tool = tool.to_string();
+        let args = args.to_string();
+        // Fuel-bounded, but still CPU work: keep it off the async workers.
+        tokio::task::spawn_blocking(move || {
+            plugin
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .call_tool(&tool, &args)
+        })
+        .await?
+    }
+}
+
 impl Default for WasmPluginManager {
     fn default() -> Self {
         Self::new()
@@ -669,6 +838,14 @@ mod tests {
         assert!(grant.allows("/tmp/test/foo.txt", true));
     }
 
+    #[test]
+    fn test_fs_grant_root_strips_globs() {
+        let grant = FsGrant::parse("rw:/data/cache/**/*.bin").unwrap();
+        assert_eq!(grant.root(), PathBuf::from("/data/cache"));
+        let grant = FsGrant::parse("read:/usr").unwrap();
+        assert_eq!(grant.root(), PathBuf::from("/usr"));
+    }
+
     #[test]
     fn test_url_matches_pattern() {
         assert!(url_matches_pattern(
@@ -713,6 +890,30 @@ environment = ["TEST_TOKEN"]
         assert_eq!(caps.filesystem[0].access, FsAccess::Read);
     }
 
+    #[test]
+    fn test_plugin_manifest_tools() {
+        let toml_str = r#"
+[plugin]
+name = "jira"
+version = "0.1.0"
+
+[[tools]]
+name = "create_issue"
+description = "Create a Jira issue"
+parameters = { type = "object", properties = { title = { type = "string" } }, required = ["title"] }
+
+[[tools]]
+name = "ping"
+read_only = true
+"#;
+        let manifest: PluginManifest = toml::from_str(toml_str).unwrap();
+        assert_eq!(manifest.tools.len(), 2);
+        assert_eq!(manifest.tools[0].parameters["required"][0], "title");
+        assert_eq!(manifest.tools[1].parameters["type"], "object");
+        assert!(!manifest.tools[0].read_only);
+        assert!(manifest.tools[1].read_only);
+    }
+
     #[test]
     fn test_wasm_capabilities_default() {
         let caps = WasmCapabilities::default();
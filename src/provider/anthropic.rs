@@ -6,6 +6,7 @@ use futures::StreamExt;
 use reqwest_eventsource::{Event, RequestBuilderExt};
 use std::pin::Pin;
 
+use super::governor::Governor;
 use super::{
     ChatChunk, ChatRequest, ChatResponse, ModelInfo, ModelProvider, Role, StopReason, TokenUsage,
     ToolCallDelta,
@@ -76,6 +77,13 @@ impl AnthropicProvider {
             })
             .collect();
 
+        let mut messages = messages;
+        if request.cache.as_ref().is_some_and(|c| c.conversation_tail) {
+            if let Some(last) = messages.last_mut() {
+                mark_cache_breakpoint(last);
+            }
+        }
+
         let mut body = serde_json::json!({
             "model": request.model,
             "messages": messages,
@@ -83,29 +91,63 @@ impl AnthropicProvider {
         });
 
         if let Some(system) = &request.system {
-            body["system"] = serde_json::json!([{
-                "type": "text",
-                "text": system,
-                "cache_control": { "type": "ephemeral" }
-            }]);
+            body["system"] = match &request.cache {
+                // Breakpoint at the end of the stable prefix; the dynamic
+                // suffix (task, plan) follows uncached.
+                Some(hints) => {
+                    let (stable, dynamic) = hints.split_system(system);
+                    let mut blocks = vec![serde_json::json!({
+                        "type": "text",
+                        "text": stable,
+                        "cache_control": { "type": "ephemeral" }
+                    })];
+                    if !dynamic.is_empty() {
+                        blocks.push(serde_json::json!({ "type": "text", "text": dynamic }));
+                    }
+                    serde_json::json!(blocks)
+                }
+                None => serde_json::json!([{
+                    "type": "text",
+                    "text": system,
+                    "cache_control": { "type": "ephemeral" }
+                }]),
+            };
         }
 
         if let Some(temp) = request.temperature {
             body["temperature"] = serde_json::json!(temp);
         }
 
-        if !request.tools.is_empty() {
-            let tools: Vec<serde_json::Value> = request
-                .tools
-                .iter()
-                .map(|t| {
-                    serde_json::json!({
-                        "name": t.name,
-                        "description": t.description,
-                        "input_schema": t.parameters,
-                    })
+        let mut tools: Vec<serde_json::Value> = request
+            .tools
+            .iter()
+            .map(|t| {
+                serde_json::json!({
+                    "name": t.name,
+                    "description": t.description,
+                    "input_schema": t.parameters,
                 })
-                .collect();
+            })
+            .collect();
+
+        // Anthropic has no JSON mode: structured output is a forced tool call
+        // whose input is the requested object.
+        if let Some(schema) = &request.response_schema {
+            tools.push(serde_json::json!({
+                "name": schema.name,
+                "description": super::structured::FORCED_TOOL_DESCRIPTION,
+                "input_schema": schema.schema,
+            }));
+            body["tool_choice"] = serde_json::json!({ "type": "tool", "name": schema.name });
+        }
+
+        if request.cache.as_ref().is_some_and(|c| c.tools) {
+            if let Some(last) = tools.last_mut() {
+                last["cache_control"] = serde_json::json!({ "type": "ephemeral" });
+            }
+        }
+
+        if !tools.is_empty() {
             body["tools"] = serde_json::json!(tools);
         }
 
@@ -113,6 +155,21 @@ impl AnthropicProvider {
     }
 }
 
+/// Put a cache breakpoint on the last content block of a message, converting
+/// plain-string content to a single text block first.
+fn mark_cache_breakpoint(message: &mut serde_json::Value) {
+    let content = &mut message["content"];
+    if let Some(text) = content.as_str() {
+        if text.is_empty() {
+            return;
+        }
+        *content = serde_json::json!([{ "type": "text", "text": text }]);
+    }
+    if let Some(last) = content.as_array_mut().and_then(|blocks| blocks.last_mut()) {
+        last["cache_control"] = serde_json::json!({ "type": "ephemeral" });
+    }
+}
+
 #[async_trait]
 impl ModelProvider for AnthropicProvider {
     fn id(&self) -> &str {
@@ -198,6 +255,8 @@ impl ModelProvider for AnthropicProvider {
                 retriable: e.is_timeout() || e.is_connect(),
             })?;
 
+        Governor::global().observe_headers("anthropic", &request.model, response.headers());
+
         let status = response.status();
         if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
             let retry_after = response
@@ -228,7 +287,7 @@ impl ModelProvider for AnthropicProvider {
                 retriable: false,
             })?;
 
-        let content = resp["content"]
+        let mut content = resp["content"]
             .as_array()
             .unwrap_or(&vec![])
             .iter()
@@ -237,11 +296,24 @@ impl ModelProvider for AnthropicProvider {
             .collect::<Vec<_>>()
             .join("");
 
+        // Unwrap the forced structured-output tool call back into content.
+        let schema_name = request.response_schema.as_ref().map(|s| s.name.as_str());
+        if let Some(name) = schema_name {
+            if let Some(block) = resp["content"].as_array().and_then(|blocks| {
+                blocks
+                    .iter()
+                    .find(|c| c["type"] == "tool_use" && c["name"] == name)
+            }) {
+                content = block["input"].to_string();
+            }
+        }
+
         let tool_calls = resp["content"]
             .as_array()
             .unwrap_or(&vec![])
             .iter()
             .filter(|c| c["type"] == "tool_use")
+            .filter(|c| schema_name.is_none_or(|name| c["name"] != name))
             .map(|c| super::ToolCall {
                 id: c["id"].as_str().unwrap_or("").to_string(),
                 name: c["name"].as_str().unwrap_or("").to_string(),
@@ -438,6 +510,51 @@ impl ModelProvider for AnthropicProvider {
         Ok(Box::pin(stream))
     }
 
+    async fn count_tokens(&self, request: &ChatRequest) -> Result<Option<u32>, OpenKoiError> {
+        let mut body = self.build_request_body(request);
+        if let Some(obj) = body.as_object_mut() {
+            // The counting endpoint rejects generation parameters.
+            obj.remove("max_tokens");
+            obj.remove("temperature");
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/count_tokens", self.api_url()))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| OpenKoiError::Provider {
+                provider: "anthropic".into(),
+                message: e.to_string(),
+                retriable: e.is_timeout() || e.is_connect(),
+            })?;
+
+        Governor::global().observe_headers("anthropic", &request.model, response.headers());
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response.text().await.unwrap_or_default();
+            return Err(OpenKoiError::Provider {
+                provider: "anthropic".into(),
+                message: format!("HTTP {}: {}", status, error_body),
+                retriable: status.is_server_error(),
+            });
+        }
+
+        let resp: serde_json::Value =
+            response.json().await.map_err(|e| OpenKoiError::Provider {
+                provider: "anthropic".into(),
+                message: format!("Failed to parse response: {}", e),
+                retriable: false,
+            })?;
+
+        Ok(resp["input_tokens"].as_u64().map(|n| n as u32))
+    }
+
     async fn embed(&self, _texts: &[&str]) -> Result<Vec<Vec<f32>>, OpenKoiError> {
         // Anthropic doesn't have an embedding API
         Err(OpenKoiError::Provider {
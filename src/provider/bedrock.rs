@@ -53,7 +53,7 @@ impl BedrockProvider {
 
     /// Build the Bedrock Converse API request body from a ChatRequest.
     fn build_converse_body(&self, request: &ChatRequest) -> serde_json::Value {
-        let messages: Vec<serde_json::Value> = request
+        let mut messages: Vec<serde_json::Value> = request
             .messages
             .iter()
             .filter(|m| m.role != Role::System)
@@ -72,6 +72,18 @@ impl BedrockProvider {
             })
             .collect();
 
+        // Converse marks cache breakpoints with `cachePoint` blocks placed
+        // after the content to be cached.
+        let cache = request.cache.as_ref();
+        if cache.is_some_and(|c| c.conversation_tail) {
+            if let Some(content) = messages
+                .last_mut()
+                .and_then(|m| m["content"].as_array_mut())
+            {
+                content.push(serde_json::json!({ "cachePoint": { "type": "default" } }));
+            }
+        }
+
         let mut body = serde_json::json!({
             "messages": messages,
         });
@@ -85,7 +97,20 @@ impl BedrockProvider {
                 .map(|m| m.content.as_str())
         });
         if let Some(sys) = system_text {
-            body["system"] = serde_json::json!([{ "text": sys }]);
+            body["system"] = match cache {
+                Some(hints) => {
+                    let (stable, dynamic) = hints.split_system(sys);
+                    let mut blocks = vec![
+                        serde_json::json!({ "text": stable }),
+                        serde_json::json!({ "cachePoint": { "type": "default" } }),
+                    ];
+                    if !dynamic.is_empty() {
+                        blocks.push(serde_json::json!({ "text": dynamic }));
+                    }
+                    serde_json::json!(blocks)
+                }
+                None => serde_json::json!([{ "text": sys }]),
+            };
         }
 
         // Inference config
@@ -100,6 +125,20 @@ impl BedrockProvider {
             body["inferenceConfig"] = serde_json::Value::Object(inference);
         }
 
+        // Converse has no JSON mode: structured output is a forced tool call.
+        if let Some(schema) = &request.response_schema {
+            body["toolConfig"] = serde_json::json!({
+                "tools": [{
+                    "toolSpec": {
+                        "name": schema.name,
+                        "description": super::structured::FORCED_TOOL_DESCRIPTION,
+                        "inputSchema": { "json": schema.schema },
+                    }
+                }],
+                "toolChoice": { "tool": { "name": schema.name } },
+            });
+        }
+
         body
     }
 
@@ -307,12 +346,21 @@ impl ModelProvider for BedrockProvider {
             })?;
 
         // Bedrock Converse response: { output: { message: { content: [{ text: "..." }] } }, usage: { ... }, stopReason }
-        let content = resp["output"]["message"]["content"]
-            .as_array()
-            .and_then(|arr| arr.first())
-            .and_then(|c| c["text"].as_str())
-            .unwrap_or("")
-            .to_string();
+        let structured = request.response_schema.as_ref().and_then(|schema| {
+            resp["output"]["message"]["content"]
+                .as_array()?
+                .iter()
+                .find(|c| c["toolUse"]["name"] == schema.name.as_str())
+                .map(|c| c["toolUse"]["input"].to_string())
+        });
+        let content = structured.unwrap_or_else(|| {
+            resp["output"]["message"]["content"]
+                .as_array()
+                .and_then(|arr| arr.first())
+                .and_then(|c| c["text"].as_str())
+                .unwrap_or("")
+                .to_string()
+        });
 
         let usage = TokenUsage {
             input_tokens: resp["usage"]["inputTokens"].as_u64().unwrap_or(0) as u32,
@@ -648,4 +696,50 @@ mod tests {
         assert!(body["system"].is_array());
         assert!(body["inferenceConfig"]["maxTokens"].is_number());
     }
+
+    #[test]
+    fn test_converse_body_forces_structured_output_tool() {
+        let provider = BedrockProvider::new("key".into(), "secret".into(), None, None, None);
+        let request = ChatRequest {
+            model: "anthropic.claude-sonnet-4-20250514-v1:0".into(),
+            messages: vec![super::super::Message::user("Evaluate")],
+            response_schema: Some(super::super::ResponseSchema {
+                name: "evaluation".into(),
+                schema: serde_json::json!({"type": "object"}),
+            }),
+            ..Default::default()
+        };
+
+        let body = provider.build_converse_body(&request);
+        assert_eq!(
+            body["toolConfig"]["tools"][0]["toolSpec"]["name"],
+            "evaluation"
+        );
+        assert_eq!(
+            body["toolConfig"]["toolChoice"]["tool"]["name"],
+            "evaluation"
+        );
+    }
+
+    #[test]
+    fn test_converse_body_cache_points() {
+        let provider = BedrockProvider::new("key".into(), "secret".into(), None, None, None);
+        let request = ChatRequest {
+            model: "anthropic.claude-sonnet-4-20250514-v1:0".into(),
+            messages: vec![super::super::Message::user("Hello")],
+            system: Some("stable prefix|task".into()),
+            cache: Some(super::super::CacheHints {
+                system_prefix_len: "stable prefix|".len(),
+                tools: true,
+                conversation_tail: true,
+            }),
+            ..Default::default()
+        };
+
+        let body = provider.build_converse_body(&request);
+        assert_eq!(body["system"][0]["text"], "stable prefix|");
+        assert!(body["system"][1]["cachePoint"].is_object());
+        assert_eq!(body["system"][2]["text"], "task");
+        assert!(body["messages"][0]["content"][1]["cachePoint"].is_object());
+    }
 }
@@ -712,6 +712,9 @@ impl GithubCopilotProvider {
                 .collect();
             body["tools"] = serde_json::json!(tools);
         }
+        if let Some(schema) = &request.response_schema {
+            body["response_format"] = super::structured::openai_response_format(schema);
+        }
 
         body
     }
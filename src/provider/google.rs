@@ -12,6 +12,9 @@ use super::{
 };
 use crate::infra::errors::OpenKoiError;
 
+/// Model used for `embed`.
+const EMBEDDING_MODEL: &str = "text-embedding-004";
+
 pub struct GoogleProvider {
     api_key: String,
     client: reqwest::Client,
@@ -65,6 +68,11 @@ impl GoogleProvider {
         if let Some(temp) = request.temperature {
             gen_config["temperature"] = serde_json::json!(temp);
         }
+        if let Some(schema) = &request.response_schema {
+            gen_config["responseMimeType"] = serde_json::json!("application/json");
+            gen_config["responseSchema"] =
+                super::structured::gemini_response_schema(&schema.schema);
+        }
         if gen_config != serde_json::json!({}) {
             body["generationConfig"] = gen_config;
         }
@@ -365,13 +373,62 @@ impl ModelProvider for GoogleProvider {
         Ok(Box::pin(stream))
     }
 
+    async fn count_tokens(&self, request: &ChatRequest) -> Result<Option<u32>, OpenKoiError> {
+        let mut inner = self.build_request_body(request);
+        inner["model"] = serde_json::json!(format!("models/{}", request.model));
+        let body = serde_json::json!({ "generateContentRequest": inner });
+
+        let url = format!(
+            "{}/models/{}:countTokens?key={}",
+            self.base_url(),
+            request.model,
+            self.api_key,
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .header("content-type", "application/json")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| OpenKoiError::Provider {
+                provider: "google".into(),
+                message: e.to_string(),
+                retriable: e.is_timeout() || e.is_connect(),
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response.text().await.unwrap_or_default();
+            return Err(OpenKoiError::Provider {
+                provider: "google".into(),
+                message: format!("HTTP {}: {}", status, error_body),
+                retriable: status.is_server_error(),
+            });
+        }
+
+        let resp: serde_json::Value =
+            response.json().await.map_err(|e| OpenKoiError::Provider {
+                provider: "google".into(),
+                message: format!("Failed to parse response: {}", e),
+                retriable: false,
+            })?;
+
+        Ok(resp["totalTokens"].as_u64().map(|n| n as u32))
+    }
+
+    fn embedding_model(&self) -> Option<&str> {
+        Some(EMBEDDING_MODEL)
+    }
+
     async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, OpenKoiError> {
         // Gemini embedding endpoint: models/text-embedding-004:batchEmbedContents
         let requests: Vec<serde_json::Value> = texts
             .iter()
             .map(|text| {
                 serde_json::json!({
-                    "model": "models/text-embedding-004",
+                    "model": format!("models/{EMBEDDING_MODEL}"),
                     "content": { "parts": [{ "text": text }] },
                 })
             })
@@ -382,8 +439,9 @@ impl ModelProvider for GoogleProvider {
         });
 
         let url = format!(
-            "{}/models/text-embedding-004:batchEmbedContents?key={}",
+            "{}/models/{}:batchEmbedContents?key={}",
             self.base_url(),
+            EMBEDDING_MODEL,
             self.api_key,
         );
 
@@ -0,0 +1,769 @@
+// src/provider/governor.rs — Process-wide concurrency and rate-limit governor
+//
+// Every provider call in the process (daemon tasks, API-queued tasks, chat)
+// goes through one `Governor`, keyed by provider/model. Requests wait for a
+// permit instead of tripping 429s; limits come from config and are learned
+// from rate-limit response headers.
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use reqwest::header::HeaderMap;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use super::{ChatChunk, ChatRequest, ChatResponse, ModelInfo, ModelProvider};
+use crate::core::token_optimizer::estimate_tokens;
+use crate::infra::config::RateLimitConfig;
+use crate::infra::errors::OpenKoiError;
+
+/// Concurrent requests per provider/model when nothing is configured.
+const DEFAULT_MAX_CONCURRENT: u32 = 8;
+
+/// Sliding window for request and token rates.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Task label used for calls made outside `with_task`.
+const DEFAULT_TASK: &str = "default";
+
+tokio::task_local! {
+    static CURRENT_TASK: String;
+}
+
+/// Run `fut` with its provider calls attributed to `task` for fair queuing.
+pub async fn with_task<F: Future>(task: String, fut: F) -> F::Output {
+    CURRENT_TASK.scope(task, fut).await
+}
+
+fn current_task() -> String {
+    CURRENT_TASK
+        .try_with(|t| t.clone())
+        .unwrap_or_else(|_| DEFAULT_TASK.to_string())
+}
+
+/// Shared limiter registry. Use [`Governor::global`] outside tests.
+pub struct Governor {
+    limiters: Mutex<HashMap<String, Arc<Limiter>>>,
+    config: RwLock<HashMap<String, RateLimitConfig>>,
+}
+
+impl Default for Governor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Governor {
+    pub fn new() -> Self {
+        Self {
+            limiters: Mutex::new(HashMap::new()),
+            config: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The process-wide governor.
+    pub fn global() -> &'static Governor {
+        static GOVERNOR: OnceLock<Governor> = OnceLock::new();
+        GOVERNOR.get_or_init(Governor::new)
+    }
+
+    /// Apply `[limits]` from config. Keys are "provider" or "provider/model";
+    /// the more specific key wins.
+    pub fn configure(&self, limits: &HashMap<String, RateLimitConfig>) {
+        if let Ok(mut config) = self.config.write() {
+            *config = limits.clone();
+        }
+        if let Ok(limiters) = self.limiters.lock() {
+            for (key, limiter) in limiters.iter() {
+                let (provider, model) = key.split_once('/').unwrap_or((key.as_str(), ""));
+                let configured = self.configured(provider, model);
+                if let Ok(mut state) = limiter.state.lock() {
+                    state.configured = configured;
+                }
+                limiter.notify.notify_waiters();
+            }
+        }
+    }
+
+    fn configured(&self, provider: &str, model: &str) -> RateLimitConfig {
+        let Ok(config) = self.config.read() else {
+            return RateLimitConfig::default();
+        };
+        config
+            .get(&format!("{}/{}", provider, model))
+            .or_else(|| config.get(provider))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn limiter(&self, provider: &str, model: &str) -> Arc<Limiter> {
+        let key = format!("{}/{}", provider, model);
+        let mut limiters = self.limiters.lock().unwrap_or_else(|e| e.into_inner());
+        limiters
+            .entry(key)
+            .or_insert_with(|| {
+                Arc::new(Limiter {
+                    state: Mutex::new(KeyState::new(self.configured(provider, model))),
+                    notify: Notify::new(),
+                })
+            })
+            .clone()
+    }
+
+    /// Wait for a slot for a request expected to use `estimated_tokens`.
+    ///
+    /// Waiters are served fair-share: the task with the fewest requests in
+    /// flight goes first, then arrival order.
+    pub async fn acquire(&self, provider: &str, model: &str, estimated_tokens: u32) -> Permit {
+        let limiter = self.limiter(provider, model);
+        let task = current_task();
+        let ticket = limiter.lock().enqueue(task.clone());
+        let _queued = QueueGuard {
+            limiter: limiter.clone(),
+            ticket,
+        };
+
+        loop {
+            let notified = limiter.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let admission = {
+                let mut state = limiter.lock();
+                let now = Instant::now();
+                state.prune(now);
+                if state.next_waiter() != Some(ticket) {
+                    Admission::WaitForRelease
+                } else {
+                    match state.admission(now, estimated_tokens) {
+                        Admission::Grant => {
+                            let entry = state.grant(ticket, &task, now, estimated_tokens);
+                            drop(state);
+                            // The queue head changed; let the next waiter check.
+                            limiter.notify.notify_waiters();
+                            return Permit {
+                                limiter: limiter.clone(),
+                                task,
+                                entry,
+                            };
+                        }
+                        other => other,
+                    }
+                }
+            };
+
+            match admission {
+                Admission::WaitFor(delay) => {
+                    tokio::select! {
+                        _ = &mut notified => {}
+                        _ = tokio::time::sleep(delay) => {}
+                    }
+                }
+                _ => notified.await,
+            }
+        }
+    }
+
+    /// Fold rate-limit response headers into the learned limits.
+    pub fn observe_headers(&self, provider: &str, model: &str, headers: &HeaderMap) {
+        let learned = parse_rate_limit_headers(headers);
+        if learned.is_empty() {
+            return;
+        }
+        let limiter = self.limiter(provider, model);
+        let mut state = limiter.lock();
+        if learned.requests_per_minute.is_some() {
+            state.learned_rpm = learned.requests_per_minute;
+        }
+        if learned.tokens_per_minute.is_some() {
+            state.learned_tpm = learned.tokens_per_minute;
+        }
+        if let Some(wait) = learned.cooldown {
+            state.cool_down(Instant::now() + wait);
+        }
+    }
+
+    /// Pause a provider/model after a 429.
+    pub fn observe_rate_limited(&self, provider: &str, model: &str, retry_after: Duration) {
+        let limiter = self.limiter(provider, model);
+        limiter.lock().cool_down(Instant::now() + retry_after);
+    }
+}
+
+/// Held for the duration of a provider call. Dropping it frees the slot.
+pub struct Permit {
+    limiter: Arc<Limiter>,
+    task: String,
+    entry: u64,
+}
+
+impl Permit {
+    /// Replace the estimated token count with the actual usage.
+    pub fn record_tokens(&self, tokens: u32) {
+        let mut state = self.limiter.lock();
+        if let Some(e) = state.window.iter_mut().find(|e| e.id == self.entry) {
+            e.tokens = tokens;
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.limiter.lock().release(&self.task);
+        self.limiter.notify.notify_waiters();
+    }
+}
+
+/// Removes a waiter that gave up (its future was dropped) from the queue.
+struct QueueGuard {
+    limiter: Arc<Limiter>,
+    ticket: u64,
+}
+
+impl Drop for QueueGuard {
+    fn drop(&mut self) {
+        self.limiter
+            .lock()
+            .waiters
+            .retain(|w| w.ticket != self.ticket);
+        self.limiter.notify.notify_waiters();
+    }
+}
+
+struct Limiter {
+    state: Mutex<KeyState>,
+    notify: Notify,
+}
+
+impl Limiter {
+    fn lock(&self) -> std::sync::MutexGuard<'_, KeyState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Admission {
+    Grant,
+    WaitFor(Duration),
+    WaitForRelease,
+}
+
+struct WindowEntry {
+    at: Instant,
+    id: u64,
+    tokens: u32,
+}
+
+struct Waiter {
+    ticket: u64,
+    task: String,
+}
+
+struct KeyState {
+    configured: RateLimitConfig,
+    learned_rpm: Option<u32>,
+    learned_tpm: Option<u32>,
+    cooldown_until: Option<Instant>,
+    in_flight: u32,
+    in_flight_by_task: HashMap<String, u32>,
+    window: VecDeque<WindowEntry>,
+    waiters: Vec<Waiter>,
+    next_id: u64,
+}
+
+impl KeyState {
+    fn new(configured: RateLimitConfig) -> Self {
+        Self {
+            configured,
+            learned_rpm: None,
+            learned_tpm: None,
+            cooldown_until: None,
+            in_flight: 0,
+            in_flight_by_task: HashMap::new(),
+            window: VecDeque::new(),
+            waiters: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    fn max_concurrent(&self) -> u32 {
+        self.configured
+            .max_concurrent
+            .unwrap_or(DEFAULT_MAX_CONCURRENT)
+            .max(1)
+    }
+
+    /// The tighter of the configured and learned limits.
+    fn rpm(&self) -> Option<u32> {
+        min_option(self.configured.requests_per_minute, self.learned_rpm)
+    }
+
+    fn tpm(&self) -> Option<u32> {
+        min_option(self.configured.tokens_per_minute, self.learned_tpm)
+    }
+
+    fn enqueue(&mut self, task: String) -> u64 {
+        let ticket = self.next_id;
+        self.next_id += 1;
+        self.waiters.push(Waiter { ticket, task });
+        ticket
+    }
+
+    /// Fair-share pick: fewest in-flight requests for the task, then FIFO.
+    fn next_waiter(&self) -> Option<u64> {
+        self.waiters
+            .iter()
+            .min_by_key(|w| {
+                (
+                    self.in_flight_by_task.get(&w.task).copied().unwrap_or(0),
+                    w.ticket,
+                )
+            })
+            .map(|w| w.ticket)
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while let Some(front) = self.window.front() {
+            if now.duration_since(front.at) >= RATE_WINDOW {
+                self.window.pop_front();
+            } else {
+                break;
+            }
+        }
+        if self.cooldown_until.is_some_and(|until| until <= now) {
+            self.cooldown_until = None;
+        }
+    }
+
+    fn admission(&self, now: Instant, estimated_tokens: u32) -> Admission {
+        if let Some(until) = self.cooldown_until {
+            return Admission::WaitFor(until - now);
+        }
+        if self.in_flight >= self.max_concurrent() {
+            return Admission::WaitForRelease;
+        }
+        let oldest_expiry = || {
+            self.window
+                .front()
+                .map(|e| RATE_WINDOW.saturating_sub(now.duration_since(e.at)))
+                .unwrap_or_default()
+        };
+        if let Some(rpm) = self.rpm() {
+            if self.window.len() as u32 >= rpm.max(1) {
+                return Admission::WaitFor(oldest_expiry());
+            }
+        }
+        if let Some(tpm) = self.tpm() {
+            let used: u64 = self.window.iter().map(|e| e.tokens as u64).sum();
+            // An empty window always admits, so a request larger than the
+            // whole budget still runs (alone) instead of waiting forever.
+            if !self.window.is_empty() && used + estimated_tokens as u64 > tpm as u64 {
+                return Admission::WaitFor(oldest_expiry());
+            }
+        }
+        Admission::Grant
+    }
+
+    fn grant(&mut self, ticket: u64, task: &str, now: Instant, tokens: u32) -> u64 {
+        self.waiters.retain(|w| w.ticket != ticket);
+        self.in_flight += 1;
+        *self.in_flight_by_task.entry(task.to_string()).or_default() += 1;
+        self.window.push_back(WindowEntry {
+            at: now,
+            id: ticket,
+            tokens,
+        });
+        ticket
+    }
+
+    fn release(&mut self, task: &str) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+        if let Some(count) = self.in_flight_by_task.get_mut(task) {
+            *count -= 1;
+            if *count == 0 {
+                self.in_flight_by_task.remove(task);
+            }
+        }
+    }
+
+    fn cool_down(&mut self, until: Instant) {
+        if self.cooldown_until.is_none_or(|current| until > current) {
+            self.cooldown_until = Some(until);
+        }
+    }
+}
+
+fn min_option(a: Option<u32>, b: Option<u32>) -> Option<u32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+// ─── Header parsing ─────────────────────────────────────────────────────────
+
+/// Limits advertised by a provider response.
+#[derive(Debug, Default, PartialEq)]
+pub struct LearnedLimits {
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+    /// Set when a quota is exhausted (or `retry-after` is present).
+    pub cooldown: Option<Duration>,
+}
+
+impl LearnedLimits {
+    fn is_empty(&self) -> bool {
+        *self == LearnedLimits::default()
+    }
+}
+
+/// Parse OpenAI-style (`x-ratelimit-*`) and Anthropic-style
+/// (`anthropic-ratelimit-*`) rate-limit headers.
+pub fn parse_rate_limit_headers(headers: &HeaderMap) -> LearnedLimits {
+    let get = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let number = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|n| get(n).and_then(|v| v.trim().parse::<u32>().ok()))
+    };
+    let reset = |names: &[&str]| names.iter().find_map(|n| get(n).and_then(parse_reset));
+
+    let mut learned = LearnedLimits {
+        requests_per_minute: number(&[
+            "x-ratelimit-limit-requests",
+            "anthropic-ratelimit-requests-limit",
+        ]),
+        tokens_per_minute: number(&[
+            "x-ratelimit-limit-tokens",
+            "anthropic-ratelimit-tokens-limit",
+            "anthropic-ratelimit-input-tokens-limit",
+        ]),
+        cooldown: None,
+    };
+
+    let mut cooldowns = Vec::new();
+    if number(&[
+        "x-ratelimit-remaining-requests",
+        "anthropic-ratelimit-requests-remaining",
+    ]) == Some(0)
+    {
+        cooldowns.extend(reset(&[
+            "x-ratelimit-reset-requests",
+            "anthropic-ratelimit-requests-reset",
+        ]));
+    }
+    if number(&[
+        "x-ratelimit-remaining-tokens",
+        "anthropic-ratelimit-tokens-remaining",
+        "anthropic-ratelimit-input-tokens-remaining",
+    ]) == Some(0)
+    {
+        cooldowns.extend(reset(&[
+            "x-ratelimit-reset-tokens",
+            "anthropic-ratelimit-tokens-reset",
+            "anthropic-ratelimit-input-tokens-reset",
+        ]));
+    }
+    if let Some(secs) = get("retry-after").and_then(|v| v.trim().parse::<f64>().ok()) {
+        cooldowns.push(Duration::from_secs_f64(secs.max(0.0)));
+    }
+    learned.cooldown = cooldowns.into_iter().max();
+    learned
+}
+
+/// Reset times are either durations ("6m0s", "1.5s", "250ms") or RFC 3339
+/// timestamps (Anthropic).
+fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(at) = chrono::DateTime::parse_from_rfc3339(value) {
+        let delta = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+        return Some(delta.to_std().unwrap_or_default());
+    }
+    parse_duration_spec(value)
+}
+
+fn parse_duration_spec(value: &str) -> Option<Duration> {
+    let mut total = 0.0f64;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (num, tail) = rest.split_at(split);
+        let n: f64 = num.parse().ok()?;
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        total += match unit {
+            "ms" => n / 1000.0,
+            "s" | "" => n,
+            "m" => n * 60.0,
+            "h" => n * 3600.0,
+            _ => return None,
+        };
+        rest = tail;
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+// ─── Provider wrapper ───────────────────────────────────────────────────────
+
+/// Routes `chat` and `chat_stream` through the global governor.
+///
+/// Sits inside `RetryProvider` so every retry attempt waits for a permit.
+pub struct GovernedProvider {
+    inner: Arc<dyn ModelProvider>,
+    governor: &'static Governor,
+}
+
+impl GovernedProvider {
+    pub fn new(inner: Arc<dyn ModelProvider>) -> Self {
+        Self {
+            inner,
+            governor: Governor::global(),
+        }
+    }
+}
+
+/// Expected tokens for a request: prompt estimate plus the output cap.
+fn estimate_request_tokens(request: &ChatRequest) -> u32 {
+    let prompt: u32 = request
+        .messages
+        .iter()
+        .map(|m| estimate_tokens(&m.content))
+        .sum::<u32>()
+        + request.system.as_deref().map(estimate_tokens).unwrap_or(0);
+    prompt + request.max_tokens.unwrap_or(0)
+}
+
+#[async_trait]
+impl ModelProvider for GovernedProvider {
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn models(&self) -> Vec<ModelInfo> {
+        self.inner.models()
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, OpenKoiError> {
+        let model = request.model.clone();
+        let permit = self
+            .governor
+            .acquire(self.inner.id(), &model, estimate_request_tokens(&request))
+            .await;
+        let result = self.inner.chat(request).await;
+        match &result {
+            Ok(response) => permit.record_tokens(response.usage.total()),
+            Err(OpenKoiError::RateLimited { retry_after_ms, .. }) => {
+                self.governor.observe_rate_limited(
+                    self.inner.id(),
+                    &model,
+                    Duration::from_millis(*retry_after_ms),
+                );
+            }
+            Err(_) => {}
+        }
+        result
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatChunk, OpenKoiError>> + Send>>, OpenKoiError>
+    {
+        let permit = self
+            .governor
+            .acquire(
+                self.inner.id(),
+                &request.model,
+                estimate_request_tokens(&request),
+            )
+            .await;
+        let stream = self.inner.chat_stream(request).await?;
+        // Hold the permit until the caller finishes consuming the stream.
+        Ok(Box::pin(stream.map(move |chunk| {
+            let _ = &permit;
+            chunk
+        })))
+    }
+
+    fn embedding_model(&self) -> Option<&str> {
+        self.inner.embedding_model()
+    }
+
+    async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, OpenKoiError> {
+        self.inner.embed(texts).await
+    }
+
+    async fn count_tokens(&self, request: &ChatRequest) -> Result<Option<u32>, OpenKoiError> {
+        self.inner.count_tokens(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn limits(max_concurrent: Option<u32>, rpm: Option<u32>, tpm: Option<u32>) -> RateLimitConfig {
+        RateLimitConfig {
+            max_concurrent,
+            requests_per_minute: rpm,
+            tokens_per_minute: tpm,
+        }
+    }
+
+    fn governor_with(key: &str, config: RateLimitConfig) -> Governor {
+        let g = Governor::new();
+        g.configure(&HashMap::from([(key.to_string(), config)]));
+        g
+    }
+
+    #[test]
+    fn test_parse_duration_spec() {
+        assert_eq!(parse_duration_spec("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_duration_spec("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(
+            parse_duration_spec("250ms"),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            parse_duration_spec("1.5s"),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(parse_duration_spec("abc"), None);
+    }
+
+    #[test]
+    fn test_parse_openai_headers() {
+        let mut h = HeaderMap::new();
+        h.insert(
+            "x-ratelimit-limit-requests",
+            HeaderValue::from_static("500"),
+        );
+        h.insert(
+            "x-ratelimit-limit-tokens",
+            HeaderValue::from_static("30000"),
+        );
+        h.insert(
+            "x-ratelimit-remaining-tokens",
+            HeaderValue::from_static("0"),
+        );
+        h.insert("x-ratelimit-reset-tokens", HeaderValue::from_static("2s"));
+        let learned = parse_rate_limit_headers(&h);
+        assert_eq!(learned.requests_per_minute, Some(500));
+        assert_eq!(learned.tokens_per_minute, Some(30_000));
+        assert_eq!(learned.cooldown, Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_parse_anthropic_headers() {
+        let mut h = HeaderMap::new();
+        h.insert(
+            "anthropic-ratelimit-requests-limit",
+            HeaderValue::from_static("50"),
+        );
+        h.insert(
+            "anthropic-ratelimit-input-tokens-limit",
+            HeaderValue::from_static("40000"),
+        );
+        h.insert(
+            "anthropic-ratelimit-requests-remaining",
+            HeaderValue::from_static("12"),
+        );
+        let learned = parse_rate_limit_headers(&h);
+        assert_eq!(learned.requests_per_minute, Some(50));
+        assert_eq!(learned.tokens_per_minute, Some(40_000));
+        assert_eq!(learned.cooldown, None);
+    }
+
+    #[test]
+    fn test_parse_no_headers() {
+        assert!(parse_rate_limit_headers(&HeaderMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_specific_key_overrides_provider() {
+        let g = Governor::new();
+        g.configure(&HashMap::from([
+            ("openai".to_string(), limits(Some(2), None, None)),
+            ("openai/gpt-4.1".to_string(), limits(Some(5), None, None)),
+        ]));
+        assert_eq!(g.configured("openai", "gpt-4.1").max_concurrent, Some(5));
+        assert_eq!(g.configured("openai", "o3").max_concurrent, Some(2));
+        assert_eq!(g.configured("anthropic", "x").max_concurrent, None);
+    }
+
+    #[test]
+    fn test_fair_share_prefers_task_with_fewer_in_flight() {
+        let mut state = KeyState::new(RateLimitConfig::default());
+        let now = Instant::now();
+        let a1 = state.enqueue("a".into());
+        state.grant(a1, "a", now, 0);
+        let a2 = state.enqueue("a".into());
+        let b1 = state.enqueue("b".into());
+        // Task "a" already has a request in flight, so "b" goes next even
+        // though it arrived later.
+        assert_eq!(state.next_waiter(), Some(b1));
+        state.grant(b1, "b", now, 0);
+        assert_eq!(state.next_waiter(), Some(a2));
+    }
+
+    #[test]
+    fn test_admission_rpm_and_tpm() {
+        let now = Instant::now();
+        let mut state = KeyState::new(limits(None, Some(1), None));
+        assert_eq!(state.admission(now, 10), Admission::Grant);
+        let t = state.enqueue("a".into());
+        state.grant(t, "a", now, 10);
+        assert!(matches!(state.admission(now, 10), Admission::WaitFor(_)));
+
+        let mut state = KeyState::new(limits(None, None, Some(100)));
+        let t = state.enqueue("a".into());
+        state.grant(t, "a", now, 90);
+        assert!(matches!(state.admission(now, 20), Admission::WaitFor(_)));
+        assert_eq!(state.admission(now, 10), Admission::Grant);
+    }
+
+    #[test]
+    fn test_learned_limit_tightens_configured() {
+        let mut state = KeyState::new(limits(None, Some(100), None));
+        state.learned_rpm = Some(20);
+        assert_eq!(state.rpm(), Some(20));
+    }
+
+    #[tokio::test]
+    async fn test_acquire_respects_max_concurrent() {
+        let g = governor_with("p", limits(Some(1), None, None));
+        let first = g.acquire("p", "m", 0).await;
+
+        let blocked = tokio::time::timeout(Duration::from_millis(50), g.acquire("p", "m", 0)).await;
+        assert!(blocked.is_err());
+
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_millis(200), g.acquire("p", "m", 0)).await;
+        assert!(second.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_leaves_queue() {
+        let g = governor_with("p", limits(Some(1), None, None));
+        let first = g.acquire("p", "m", 0).await;
+        let _ = tokio::time::timeout(Duration::from_millis(20), g.acquire("p", "m", 0)).await;
+        drop(first);
+        // The timed-out waiter must not block the queue head.
+        let next = tokio::time::timeout(Duration::from_millis(200), g.acquire("p", "m", 0)).await;
+        assert!(next.is_ok());
+    }
+}
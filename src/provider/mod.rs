@@ -5,14 +5,17 @@ pub mod bedrock;
 pub mod fallback;
 pub mod github_copilot;
 pub mod google;
+pub mod governor;
 pub mod model_cache;
 pub mod ollama;
 pub mod openai;
 pub mod openai_compat;
 pub mod openai_oauth;
+pub mod redaction;
 pub mod resolver;
 pub mod retry;
 pub mod roles;
+pub mod structured;
 
 use async_trait::async_trait;
 use futures::Stream;
@@ -36,6 +39,18 @@ pub trait ModelProvider: Send + Sync {
     ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatChunk, OpenKoiError>> + Send>>, OpenKoiError>;
 
     async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, OpenKoiError>;
+
+    /// The model `embed` uses, when the provider has one. Vectors from
+    /// different models don't compare, so caches key on it.
+    fn embedding_model(&self) -> Option<&str> {
+        None
+    }
+
+    /// Exact prompt size for `request` from the provider's own token-counting
+    /// endpoint. `Ok(None)` when the provider has no free counting endpoint.
+    async fn count_tokens(&self, _request: &ChatRequest) -> Result<Option<u32>, OpenKoiError> {
+        Ok(None)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,6 +131,51 @@ pub struct ChatRequest {
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
     pub system: Option<String>,
+    /// When set, the model must reply with a single JSON object matching this
+    /// schema. Providers map it to their native structured-output mechanism.
+    pub response_schema: Option<ResponseSchema>,
+    /// Where to place prompt-cache breakpoints. `None` keeps each provider's
+    /// default behaviour.
+    pub cache: Option<CacheHints>,
+}
+
+/// Prompt-caching hints for providers with explicit cache breakpoints
+/// (Anthropic, Bedrock). Providers with automatic prefix caching (OpenAI,
+/// Gemini) benefit from the stable layout alone and ignore these.
+#[derive(Debug, Clone, Default)]
+pub struct CacheHints {
+    /// Byte length of the stable prefix of `ChatRequest::system`. A breakpoint
+    /// is placed at this boundary; 0 means the whole system prompt is stable.
+    pub system_prefix_len: usize,
+    /// Place a breakpoint after the tool definitions.
+    pub tools: bool,
+    /// Place a breakpoint on the last message, so tool-loop rounds within one
+    /// iteration reuse everything before it.
+    pub conversation_tail: bool,
+}
+
+impl CacheHints {
+    /// Split `system` at the stable-prefix boundary. Falls back to treating
+    /// the whole prompt as stable when the boundary is 0, out of range, or not
+    /// on a char boundary.
+    pub fn split_system<'a>(&self, system: &'a str) -> (&'a str, &'a str) {
+        let n = self.system_prefix_len;
+        if n == 0 || n >= system.len() || !system.is_char_boundary(n) {
+            (system, "")
+        } else {
+            system.split_at(n)
+        }
+    }
+}
+
+/// A named JSON schema for structured-output requests.
+///
+/// Mapped to OpenAI `response_format`, Gemini `responseSchema`, a forced
+/// tool call on Anthropic/Bedrock, and Ollama `format`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseSchema {
+    pub name: String,
+    pub schema: serde_json::Value,
 }
 
 #[derive(Debug, Clone)]
@@ -408,6 +468,27 @@ mod tests {
         assert!(m.tool_calls.is_empty());
     }
 
+    // ─── CacheHints tests ───────────────────────────────────────
+
+    #[test]
+    fn test_cache_hints_split_system() {
+        let hints = CacheHints {
+            system_prefix_len: 6,
+            ..Default::default()
+        };
+        assert_eq!(hints.split_system("stabledynamic"), ("stable", "dynamic"));
+    }
+
+    #[test]
+    fn test_cache_hints_split_system_out_of_range() {
+        let hints = CacheHints {
+            system_prefix_len: 100,
+            ..Default::default()
+        };
+        assert_eq!(hints.split_system("short"), ("short", ""));
+        assert_eq!(CacheHints::default().split_system("all"), ("all", ""));
+    }
+
     // ─── StopReason tests ───────────────────────────────────────
 
     #[test]
@@ -10,6 +10,9 @@ use super::{
 };
 use crate::infra::errors::OpenKoiError;
 
+/// Model used for `embed`.
+const EMBEDDING_MODEL: &str = "nomic-embed-text";
+
 pub struct OllamaProvider {
     base_url: String,
     client: reqwest::Client,
@@ -139,6 +142,9 @@ impl ModelProvider for OllamaProvider {
         if let Some(temp) = request.temperature {
             body["options"] = serde_json::json!({ "temperature": temp });
         }
+        if let Some(schema) = &request.response_schema {
+            body["format"] = schema.schema.clone();
+        }
 
         let response = self
             .client
@@ -221,6 +227,9 @@ impl ModelProvider for OllamaProvider {
         if let Some(temp) = request.temperature {
             body["options"] = serde_json::json!({ "temperature": temp });
         }
+        if let Some(schema) = &request.response_schema {
+            body["format"] = schema.schema.clone();
+        }
 
         let response = self
             .client
@@ -332,11 +341,15 @@ impl ModelProvider for OllamaProvider {
         Ok(Box::pin(stream))
     }
 
+    fn embedding_model(&self) -> Option<&str> {
+        Some(EMBEDDING_MODEL)
+    }
+
     async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, OpenKoiError> {
         let mut results = Vec::new();
         for text in texts {
             let body = serde_json::json!({
-                "model": "nomic-embed-text",
+                "model": EMBEDDING_MODEL,
                 "prompt": text,
             });
             let response = self
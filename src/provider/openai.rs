@@ -6,12 +6,16 @@ use futures::StreamExt;
 use reqwest_eventsource::{Event, RequestBuilderExt};
 use std::pin::Pin;
 
+use super::governor::Governor;
 use super::{
     ChatChunk, ChatRequest, ChatResponse, ModelInfo, ModelProvider, Role, StopReason, TokenUsage,
     ToolCallDelta,
 };
 use crate::infra::errors::OpenKoiError;
 
+/// Model used for `embed`.
+const EMBEDDING_MODEL: &str = "text-embedding-3-small";
+
 pub struct OpenAIProvider {
     api_key: String,
     client: reqwest::Client,
@@ -36,6 +40,22 @@ impl OpenAIProvider {
     }
 }
 
+/// Parse an OpenAI `usage` object. `prompt_tokens` includes automatically
+/// cached prefix tokens, reported separately in `prompt_tokens_details`; they
+/// are split out so cache reads are priced at the cache rate.
+fn parse_usage(usage: &serde_json::Value) -> TokenUsage {
+    let prompt = usage["prompt_tokens"].as_u64().unwrap_or(0) as u32;
+    let cached = usage["prompt_tokens_details"]["cached_tokens"]
+        .as_u64()
+        .unwrap_or(0) as u32;
+    TokenUsage {
+        input_tokens: prompt.saturating_sub(cached),
+        output_tokens: usage["completion_tokens"].as_u64().unwrap_or(0) as u32,
+        cache_read_tokens: cached,
+        cache_write_tokens: 0,
+    }
+}
+
 #[async_trait]
 impl ModelProvider for OpenAIProvider {
     fn id(&self) -> &str {
@@ -174,6 +194,9 @@ impl ModelProvider for OpenAIProvider {
                 .collect();
             body["tools"] = serde_json::json!(tools);
         }
+        if let Some(schema) = &request.response_schema {
+            body["response_format"] = super::structured::openai_response_format(schema);
+        }
 
         let response = self
             .client
@@ -188,6 +211,8 @@ impl ModelProvider for OpenAIProvider {
                 retriable: e.is_timeout() || e.is_connect(),
             })?;
 
+        Governor::global().observe_headers("openai", &request.model, response.headers());
+
         let status = response.status();
         if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
             return Err(OpenKoiError::RateLimited {
@@ -232,12 +257,7 @@ impl ModelProvider for OpenAIProvider {
             })
             .collect();
 
-        let usage = TokenUsage {
-            input_tokens: resp["usage"]["prompt_tokens"].as_u64().unwrap_or(0) as u32,
-            output_tokens: resp["usage"]["completion_tokens"].as_u64().unwrap_or(0) as u32,
-            cache_read_tokens: 0,
-            cache_write_tokens: 0,
-        };
+        let usage = parse_usage(&resp["usage"]);
 
         let stop_reason = match choice["finish_reason"].as_str() {
             Some("stop") => StopReason::EndTurn,
@@ -327,6 +347,9 @@ impl ModelProvider for OpenAIProvider {
                 .collect();
             body["tools"] = serde_json::json!(tools);
         }
+        if let Some(schema) = &request.response_schema {
+            body["response_format"] = super::structured::openai_response_format(schema);
+        }
 
         let request_builder = self
             .client
@@ -377,16 +400,7 @@ impl ModelProvider for OpenAIProvider {
 
                         // Extract usage (sent in the final chunk when stream_options.include_usage is true)
                         let usage = if parsed["usage"].is_object() && !parsed["usage"].is_null() {
-                            Some(TokenUsage {
-                                input_tokens: parsed["usage"]["prompt_tokens"]
-                                    .as_u64()
-                                    .unwrap_or(0) as u32,
-                                output_tokens: parsed["usage"]["completion_tokens"]
-                                    .as_u64()
-                                    .unwrap_or(0) as u32,
-                                cache_read_tokens: 0,
-                                cache_write_tokens: 0,
-                            })
+                            Some(parse_usage(&parsed["usage"]))
                         } else {
                             None
                         };
@@ -415,9 +429,13 @@ impl ModelProvider for OpenAIProvider {
         Ok(Box::pin(stream))
     }
 
+    fn embedding_model(&self) -> Option<&str> {
+        Some(EMBEDDING_MODEL)
+    }
+
     async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, OpenKoiError> {
         let body = serde_json::json!({
-            "model": "text-embedding-3-small",
+            "model": EMBEDDING_MODEL,
             "input": texts,
         });
 
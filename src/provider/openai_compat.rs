@@ -9,6 +9,7 @@ use futures::StreamExt;
 use reqwest_eventsource::{Event, RequestBuilderExt};
 use std::pin::Pin;
 
+use super::governor::Governor;
 use super::model_cache;
 use super::{
     ChatChunk, ChatRequest, ChatResponse, ModelInfo, ModelProvider, Role, StopReason, TokenUsage,
@@ -269,6 +270,11 @@ impl ModelProvider for OpenAICompatProvider {
         if let Some(temp) = request.temperature {
             body["temperature"] = serde_json::json!(temp);
         }
+        // Compatible backends vary in JSON-schema support; JSON mode is the
+        // common denominator and the prompt carries the schema itself.
+        if request.response_schema.is_some() {
+            body["response_format"] = serde_json::json!({ "type": "json_object" });
+        }
 
         let response = self
             .client
@@ -283,6 +289,8 @@ impl ModelProvider for OpenAICompatProvider {
                 retriable: e.is_timeout(),
             })?;
 
+        Governor::global().observe_headers(&self.id_str, &request.model, response.headers());
+
         if !response.status().is_success() {
             let error_body = response.text().await.unwrap_or_default();
             return Err(OpenKoiError::Provider {
@@ -373,6 +381,11 @@ impl ModelProvider for OpenAICompatProvider {
         if let Some(temp) = request.temperature {
             body["temperature"] = serde_json::json!(temp);
         }
+        // Compatible backends vary in JSON-schema support; JSON mode is the
+        // common denominator and the prompt carries the schema itself.
+        if request.response_schema.is_some() {
+            body["response_format"] = serde_json::json!({ "type": "json_object" });
+        }
 
         let provider_id = self.id_str.clone();
 
@@ -97,6 +97,9 @@ impl OpenAICodexProvider {
                 .collect();
             body["tools"] = serde_json::json!(tools);
         }
+        if let Some(schema) = &request.response_schema {
+            body["text"] = super::structured::responses_text_format(schema);
+        }
 
         body
     }
@@ -6,10 +6,12 @@ use super::anthropic::AnthropicProvider;
 use super::bedrock::BedrockProvider;
 use super::github_copilot::GithubCopilotProvider;
 use super::google::GoogleProvider;
+use super::governor::{GovernedProvider, Governor};
 use super::ollama::OllamaProvider;
 use super::openai::OpenAIProvider;
 use super::openai_compat::OpenAICompatProvider;
 use super::openai_oauth::OpenAICodexProvider;
+use super::redaction::RedactingProvider;
 use super::retry::RetryProvider;
 use super::{ModelProvider, ModelRef};
 use crate::auth::AuthStore;
@@ -277,10 +279,17 @@ pub async fn discover_providers_with_config(config: &Config) -> Vec<Arc<dyn Mode
         providers.push(Arc::new(ollama));
     }
 
-    // Wrap every provider with retry logic for resilience against transient failures.
+    // Route every provider through the shared governor (concurrency and
+    // rate limits across all tasks), mask secrets in what is sent, then wrap
+    // with retry logic for resilience against transient failures.
+    Governor::global().configure(&config.limits);
     let providers: Vec<Arc<dyn ModelProvider>> = providers
         .into_iter()
-        .map(|p| Arc::new(RetryProvider::new(p)) as Arc<dyn ModelProvider>)
+        .map(|p| {
+            let governed: Arc<dyn ModelProvider> = Arc::new(GovernedProvider::new(p));
+            let masked: Arc<dyn ModelProvider> = Arc::new(RedactingProvider::new(governed));
+            Arc::new(RetryProvider::new(masked)) as Arc<dyn ModelProvider>
+        })
         .collect();
 
     providers
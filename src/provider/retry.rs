@@ -203,6 +203,15 @@ impl ModelProvider for RetryProvider {
         }))
     }
 
+    async fn count_tokens(&self, request: &ChatRequest) -> Result<Option<u32>, OpenKoiError> {
+        // Best-effort measurement: callers fall back to local counts on error.
+        self.inner.count_tokens(request).await
+    }
+
+    fn embedding_model(&self) -> Option<&str> {
+        self.inner.embedding_model()
+    }
+
     async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, OpenKoiError> {
         // Embed is typically idempotent — retry on transient failures
         let mut last_error = None;
@@ -0,0 +1,336 @@
+// src/provider/structured.rs — Structured-output (JSON schema) requests
+
+use serde::de::DeserializeOwned;
+
+use super::{ChatRequest, Message, ModelProvider, ResponseSchema, TokenUsage};
+use crate::infra::errors::OpenKoiError;
+
+/// A type that a model can be asked to return as schema-validated JSON.
+pub trait StructuredOutput: DeserializeOwned {
+    /// Schema name sent to providers (must match `^[a-zA-Z0-9_-]+$`).
+    fn schema_name() -> &'static str;
+
+    /// JSON schema describing the expected object.
+    ///
+    /// Schemas should be OpenAI strict-mode compatible: every property listed
+    /// in `required` and `additionalProperties: false` on every object.
+    fn json_schema() -> serde_json::Value;
+
+    /// Semantic checks beyond what the schema can express (ranges, non-empty).
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn response_schema() -> ResponseSchema {
+        ResponseSchema {
+            name: Self::schema_name().to_string(),
+            schema: Self::json_schema(),
+        }
+    }
+}
+
+/// A validated structured reply plus the tokens spent obtaining it
+/// (including any repair attempt).
+#[derive(Debug)]
+pub struct StructuredResponse<T> {
+    pub value: T,
+    pub usage: TokenUsage,
+}
+
+/// Why a structured request did not produce a valid value.
+#[derive(Debug)]
+pub enum StructuredError {
+    /// The provider call itself failed.
+    Provider(OpenKoiError),
+    /// The model replied, but the reply still failed validation after the
+    /// repair retry. `raw` is the last reply so callers can fall back to
+    /// free-form parsing without another call.
+    Invalid {
+        reason: String,
+        raw: String,
+        usage: TokenUsage,
+    },
+}
+
+impl std::fmt::Display for StructuredError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StructuredError::Provider(e) => write!(f, "{}", e),
+            StructuredError::Invalid { reason, .. } => {
+                write!(f, "invalid structured output: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StructuredError {}
+
+/// Send `request` with `T`'s schema attached and parse the reply into `T`.
+///
+/// If the reply does not parse or fails `T::validate`, one repair turn is sent
+/// containing the validation error. A second failure returns
+/// `StructuredError::Invalid` with the raw reply.
+pub async fn chat_structured<T: StructuredOutput>(
+    provider: &dyn ModelProvider,
+    mut request: ChatRequest,
+) -> Result<StructuredResponse<T>, StructuredError> {
+    request.response_schema = Some(T::response_schema());
+
+    let first = provider
+        .chat(request.clone())
+        .await
+        .map_err(StructuredError::Provider)?;
+    let mut usage = first.usage.clone();
+
+    let reason = match parse_structured::<T>(&first.content) {
+        Ok(value) => return Ok(StructuredResponse { value, usage }),
+        Err(reason) => reason,
+    };
+
+    tracing::debug!(
+        "Structured output '{}' failed validation ({}), sending repair turn",
+        T::schema_name(),
+        reason
+    );
+
+    request.messages.push(Message::assistant(first.content));
+    request.messages.push(Message::user(repair_prompt(&reason)));
+
+    let second = provider
+        .chat(request)
+        .await
+        .map_err(StructuredError::Provider)?;
+    add_usage(&mut usage, &second.usage);
+
+    match parse_structured::<T>(&second.content) {
+        Ok(value) => Ok(StructuredResponse { value, usage }),
+        Err(reason) => Err(StructuredError::Invalid {
+            reason,
+            raw: second.content,
+            usage,
+        }),
+    }
+}
+
+/// Parse and validate a model reply as `T`.
+pub fn parse_structured<T: StructuredOutput>(content: &str) -> Result<T, String> {
+    let json = extract_json(content).ok_or_else(|| "no JSON object found in reply".to_string())?;
+    let value: T = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    value.validate()?;
+    Ok(value)
+}
+
+/// Locate the JSON object in a reply, tolerating code fences and prose
+/// around it (models without native schema support often add both).
+pub fn extract_json(content: &str) -> Option<&str> {
+    let start = content.find('{')?;
+    let end = content.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    Some(&content[start..=end])
+}
+
+/// The JSON object in a reply that failed validation, for callers that
+/// salvage its usable parts instead of discarding the whole reply.
+pub fn extract_json_value(content: &str) -> Option<serde_json::Value> {
+    serde_json::from_str(extract_json(content)?)
+        .ok()
+        .filter(serde_json::Value::is_object)
+}
+
+/// The entries of the array `value[key]` that deserialize as `T`; the rest
+/// are skipped.
+pub fn salvage_entries<T: DeserializeOwned>(value: &serde_json::Value, key: &str) -> Vec<T> {
+    value
+        .get(key)
+        .and_then(serde_json::Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| serde_json::from_value(item.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn repair_prompt(reason: &str) -> String {
+    format!(
+        "Your previous reply did not match the required JSON schema: {}\n\
+         Reply again with only the corrected JSON object, no prose or code fences.",
+        reason
+    )
+}
+
+fn add_usage(total: &mut TokenUsage, usage: &TokenUsage) {
+    total.input_tokens += usage.input_tokens;
+    total.output_tokens += usage.output_tokens;
+    total.cache_read_tokens += usage.cache_read_tokens;
+    total.cache_write_tokens += usage.cache_write_tokens;
+}
+
+// ─── Provider mappings ──────────────────────────────────────────────────────
+
+/// OpenAI Chat Completions `response_format` (strict JSON schema).
+pub(crate) fn openai_response_format(schema: &ResponseSchema) -> serde_json::Value {
+    serde_json::json!({
+        "type": "json_schema",
+        "json_schema": {
+            "name": schema.name,
+            "schema": schema.schema,
+            "strict": true,
+        }
+    })
+}
+
+/// OpenAI Responses API `text.format` (strict JSON schema).
+pub(crate) fn responses_text_format(schema: &ResponseSchema) -> serde_json::Value {
+    serde_json::json!({
+        "format": {
+            "type": "json_schema",
+            "name": schema.name,
+            "schema": schema.schema,
+            "strict": true,
+        }
+    })
+}
+
+/// Convert a JSON schema to the OpenAPI subset Gemini accepts for
+/// `responseSchema`: no `additionalProperties`, and `["T", "null"]` unions
+/// expressed as `nullable: true`.
+pub(crate) fn gemini_response_schema(schema: &serde_json::Value) -> serde_json::Value {
+    match schema {
+        serde_json::Value::Object(map) => {
+            let mut out = serde_json::Map::new();
+            for (key, value) in map {
+                match key.as_str() {
+                    "additionalProperties" => {}
+                    "type" => {
+                        if let Some(types) = value.as_array() {
+                            let non_null: Vec<&serde_json::Value> = types
+                                .iter()
+                                .filter(|t| t.as_str() != Some("null"))
+                                .collect();
+                            if non_null.len() < types.len() {
+                                out.insert("nullable".into(), serde_json::json!(true));
+                            }
+                            if let Some(first) = non_null.first() {
+                                out.insert("type".into(), (*first).clone());
+                            }
+                        } else {
+                            out.insert(key.clone(), value.clone());
+                        }
+                    }
+                    _ => {
+                        out.insert(key.clone(), gemini_response_schema(value));
+                    }
+                }
+            }
+            serde_json::Value::Object(out)
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(gemini_response_schema).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+/// Description used for the forced tool that carries structured output on
+/// providers without a native JSON mode (Anthropic, Bedrock).
+pub(crate) const FORCED_TOOL_DESCRIPTION: &str =
+    "Return the final answer as structured data matching this schema.";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Sample {
+        score: f32,
+    }
+
+    impl StructuredOutput for Sample {
+        fn schema_name() -> &'static str {
+            "sample"
+        }
+
+        fn json_schema() -> serde_json::Value {
+            serde_json::json!({
+                "type": "object",
+                "properties": { "score": { "type": "number" } },
+                "required": ["score"],
+                "additionalProperties": false,
+            })
+        }
+
+        fn validate(&self) -> Result<(), String> {
+            if (0.0..=1.0).contains(&self.score) {
+                Ok(())
+            } else {
+                Err(format!("score {} out of range 0.0-1.0", self.score))
+            }
+        }
+    }
+
+    #[test]
+    fn test_extract_json_plain() {
+        assert_eq!(extract_json(r#"{"a":1}"#), Some(r#"{"a":1}"#));
+    }
+
+    #[test]
+    fn test_extract_json_fenced() {
+        let text = "Here you go:\n```json\n{\"a\": 1}\n```\n";
+        assert_eq!(extract_json(text), Some("{\"a\": 1}"));
+    }
+
+    #[test]
+    fn test_extract_json_none() {
+        assert!(extract_json("no json here").is_none());
+        assert!(extract_json("} backwards {").is_none());
+    }
+
+    #[test]
+    fn test_parse_structured_valid() {
+        let s: Sample = parse_structured(r#"{"score": 0.8}"#).unwrap();
+        assert!((s.score - 0.8).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_parse_structured_validation_error() {
+        let err = parse_structured::<Sample>(r#"{"score": 3.0}"#).unwrap_err();
+        assert!(err.contains("out of range"));
+    }
+
+    #[test]
+    fn test_parse_structured_missing_field() {
+        assert!(parse_structured::<Sample>(r#"{"other": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_gemini_schema_strips_unsupported_keys() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "location": { "type": ["string", "null"] },
+                "items": { "type": "array", "items": { "type": "object", "additionalProperties": false } }
+            },
+            "additionalProperties": false,
+        });
+        let out = gemini_response_schema(&schema);
+        assert!(out.get("additionalProperties").is_none());
+        assert_eq!(out["properties"]["location"]["type"], "string");
+        assert_eq!(out["properties"]["location"]["nullable"], true);
+        assert!(out["properties"]["items"]["items"]
+            .get("additionalProperties")
+            .is_none());
+    }
+
+    #[test]
+    fn test_openai_response_format_shape() {
+        let rf = openai_response_format(&Sample::response_schema());
+        assert_eq!(rf["type"], "json_schema");
+        assert_eq!(rf["json_schema"]["name"], "sample");
+        assert_eq!(rf["json_schema"]["strict"], true);
+    }
+}
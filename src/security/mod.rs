@@ -1,3 +1,6 @@
 // src/security/mod.rs — Security module
 
+pub mod audit;
 pub mod permissions;
+pub mod redact;
+pub mod sandbox;
@@ -0,0 +1,900 @@
+// src/security/sandbox.rs — Sandboxed execution for agent-started processes
+//
+// Test runs, linters and command-running tools go through `Sandbox::output`
+// instead of spawning directly; long-running servers use `Sandbox::spawn`. On Linux the process is confined with
+// bubblewrap when it is installed, or natively with Landlock (filesystem),
+// seccomp (dangerous syscalls) and user/network namespaces otherwise. Every
+// backend clears the environment and applies rlimits and a wall-clock
+// timeout. Filesystem access uses the plugin capability grants (`FsGrant`).
+
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+use anyhow::Context;
+use tokio::process::{Child, Command};
+
+use crate::infra::config::SandboxConfig;
+use crate::plugins::wasm::{FsAccess, FsGrant};
+
+/// Environment variables always passed through to sandboxed processes.
+const BASE_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "TERM",
+    "TMPDIR",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+    "GOPATH",
+    "GOROOT",
+    "VIRTUAL_ENV",
+];
+
+/// System and toolchain locations every sandbox can read. Package-manager
+/// caches are writable so builds can fetch dependencies. Missing paths are
+/// skipped.
+const DEFAULT_GRANTS: &[&str] = &[
+    "read:/usr",
+    "read:/bin",
+    "read:/sbin",
+    "read:/lib",
+    "read:/lib32",
+    "read:/lib64",
+    "read:/etc",
+    "read:/opt",
+    "read:/nix/store",
+    "read:~/.cargo",
+    "read:~/.rustup",
+    "read:~/.local",
+    "read:~/go",
+    "read:~/.nvm",
+    "read:~/.pyenv",
+    "rw:~/.cargo/registry",
+    "rw:~/.cargo/git",
+    "rw:~/go/pkg/mod",
+    "rw:~/.npm",
+    "rw:~/.yarn",
+    "rw:~/.cache",
+];
+
+/// How processes are confined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SandboxBackend {
+    /// `bwrap` at the given path: private mount, PID, IPC and network
+    /// namespaces with only the granted paths bound in.
+    Bubblewrap(PathBuf),
+    /// Landlock + seccomp + user/network namespaces, applied in the child
+    /// before exec (Linux only).
+    Native,
+    /// No confinement beyond the clean environment, rlimits and timeout.
+    Off,
+}
+
+impl std::fmt::Display for SandboxBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SandboxBackend::Bubblewrap(path) => write!(f, "bubblewrap ({})", path.display()),
+            SandboxBackend::Native => write!(f, "native (landlock, seccomp, namespaces)"),
+            SandboxBackend::Off => write!(f, "off"),
+        }
+    }
+}
+
+impl SandboxBackend {
+    /// Resolve a `[sandbox] mode` against what this machine supports.
+    pub fn detect(mode: &str) -> Self {
+        let bwrap = || {
+            if cfg!(target_os = "linux") {
+                which::which("bwrap").ok()
+            } else {
+                None
+            }
+        };
+        let native = || {
+            if cfg!(target_os = "linux") {
+                SandboxBackend::Native
+            } else {
+                SandboxBackend::Off
+            }
+        };
+        match mode {
+            "off" => SandboxBackend::Off,
+            "native" => native(),
+            "bubblewrap" => bwrap().map(SandboxBackend::Bubblewrap).unwrap_or_else(|| {
+                tracing::warn!("Sandbox mode 'bubblewrap' requested but bwrap is not installed");
+                native()
+            }),
+            other => {
+                if other != "auto" {
+                    tracing::warn!("Unknown sandbox mode '{}', using auto", other);
+                }
+                static AUTO: OnceLock<SandboxBackend> = OnceLock::new();
+                AUTO.get_or_init(|| {
+                    let candidates = bwrap()
+                        .map(SandboxBackend::Bubblewrap)
+                        .into_iter()
+                        .chain([native()]);
+                    for backend in candidates {
+                        if backend.works() {
+                            return backend;
+                        }
+                    }
+                    tracing::warn!(
+                        "No working sandbox here (bubblewrap missing or failing, user namespaces \
+                         unavailable); test runs and tools run unconfined"
+                    );
+                    SandboxBackend::Off
+                })
+                .clone()
+            }
+        }
+    }
+
+    /// Whether this backend can start a process on this machine with
+    /// networking off (the strictest setting).
+    fn works(&self) -> bool {
+        let mut probe = match self {
+            SandboxBackend::Bubblewrap(bwrap) => {
+                let mut c = std::process::Command::new(bwrap);
+                c.args([
+                    "--unshare-all",
+                    "--ro-bind",
+                    "/",
+                    "/",
+                    "--",
+                    "sh",
+                    "-c",
+                    ":",
+                ]);
+                c
+            }
+            SandboxBackend::Native => {
+                let mut c = std::process::Command::new("sh");
+                c.args(["-c", ":"]);
+                #[cfg(target_os = "linux")]
+                // SAFETY: only makes a syscall between fork and exec.
+                unsafe {
+                    use std::os::unix::process::CommandExt;
+                    c.pre_exec(|| {
+                        if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                        Ok(())
+                    });
+                }
+                c
+            }
+            SandboxBackend::Off => return true,
+        };
+        probe
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+}
+
+/// What a sandboxed process may touch.
+#[derive(Debug, Clone)]
+pub struct SandboxPolicy {
+    /// Filesystem grants on top of the working directory (always read-write).
+    pub grants: Vec<FsGrant>,
+    pub network: bool,
+    /// Extra environment variables passed through from the parent.
+    pub env: Vec<String>,
+    /// Network for commands marked `fetches_dependencies`.
+    pub build_network: bool,
+    pub cpu_seconds: Option<u64>,
+    pub memory_mb: Option<u64>,
+    pub timeout: Duration,
+}
+
+impl SandboxPolicy {
+    pub fn from_config(cfg: &SandboxConfig) -> Self {
+        let grants = DEFAULT_GRANTS
+            .iter()
+            .copied()
+            .chain(cfg.filesystem.iter().map(String::as_str))
+            .filter_map(FsGrant::parse)
+            .collect();
+        Self {
+            grants,
+            network: cfg.network,
+            env: cfg.env.clone(),
+            build_network: cfg.build_network,
+            cpu_seconds: cfg.cpu_seconds,
+            memory_mb: cfg.memory_mb,
+            timeout: Duration::from_secs(cfg.timeout_seconds.max(1)),
+        }
+    }
+
+    /// Variables from the parent environment that the child may see.
+    fn passthrough_env(&self) -> Vec<(String, String)> {
+        BASE_ENV
+            .iter()
+            .copied()
+            .chain(self.env.iter().map(String::as_str))
+            .filter_map(|key| std::env::var(key).ok().map(|v| (key.to_string(), v)))
+            .collect()
+    }
+}
+
+/// A process to run inside the sandbox.
+#[derive(Debug, Clone)]
+pub struct SandboxCommand {
+    program: String,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    dir: PathBuf,
+    grants: Vec<FsGrant>,
+    fetches_dependencies: bool,
+    offline: bool,
+    timeout: Option<Duration>,
+}
+
+impl SandboxCommand {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            dir: PathBuf::from("."),
+            grants: Vec::new(),
+            fetches_dependencies: false,
+            offline: false,
+            timeout: None,
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set a variable in the child's (otherwise clean) environment.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Working directory; it is the one path the process may always write.
+    pub fn current_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Let this process read `path` on top of the policy's grants.
+    pub fn allow_read(mut self, path: impl AsRef<Path>) -> Self {
+        self.grants.push(FsGrant {
+            pattern: path.as_ref().to_string_lossy().to_string(),
+            access: FsAccess::Read,
+        });
+        self
+    }
+
+    /// Mark a test runner or linter, which gets network access to fetch
+    /// dependencies when `[sandbox] build_network` is on.
+    pub fn fetches_dependencies(mut self) -> Self {
+        self.fetches_dependencies = true;
+        self
+    }
+
+    /// No network for this process, even when `[sandbox] network` is on.
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    /// Wall-clock limit for this process instead of the policy's.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Runs agent-initiated processes under a `SandboxPolicy`.
+pub struct Sandbox {
+    policy: SandboxPolicy,
+    backend: SandboxBackend,
+}
+
+fn global_slot() -> &'static RwLock<Arc<Sandbox>> {
+    static SANDBOX: OnceLock<RwLock<Arc<Sandbox>>> = OnceLock::new();
+    SANDBOX.get_or_init(|| RwLock::new(Arc::new(Sandbox::from_config(&SandboxConfig::default()))))
+}
+
+impl Sandbox {
+    pub fn new(policy: SandboxPolicy, backend: SandboxBackend) -> Self {
+        Self { policy, backend }
+    }
+
+    pub fn from_config(cfg: &SandboxConfig) -> Self {
+        Self::new(
+            SandboxPolicy::from_config(cfg),
+            SandboxBackend::detect(&cfg.mode),
+        )
+    }
+
+    /// The process-wide sandbox (defaults until `configure` is called).
+    pub fn global() -> Arc<Sandbox> {
+        global_slot()
+            .read()
+            .map(|s| s.clone())
+            .unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Apply `[sandbox]` from config to the process-wide sandbox.
+    pub fn configure(cfg: &SandboxConfig) {
+        let sandbox = Arc::new(Self::from_config(cfg));
+        tracing::debug!(backend = %sandbox.backend, network = cfg.network, "Configured sandbox");
+        match global_slot().write() {
+            Ok(mut slot) => *slot = sandbox,
+            Err(e) => *e.into_inner() = sandbox,
+        }
+    }
+
+    pub fn backend(&self) -> &SandboxBackend {
+        &self.backend
+    }
+
+    pub fn policy(&self) -> &SandboxPolicy {
+        &self.policy
+    }
+
+    /// Run `cmd` to completion and capture its output. Fails if the process
+    /// cannot be confined as configured or exceeds the timeout.
+    pub async fn output(&self, cmd: SandboxCommand) -> anyhow::Result<Output> {
+        let (mut command, policy) = self.command(&cmd)?;
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let child = command
+            .spawn()
+            .with_context(|| format!("failed to start '{}' in sandbox", cmd.program))?;
+        let pid = child.id();
+
+        match tokio::time::timeout(policy.timeout, child.wait_with_output()).await {
+            Ok(output) => Ok(output?),
+            Err(_) => {
+                // The child leads its own process group; take down anything
+                // it spawned too.
+                #[cfg(unix)]
+                if let Some(pid) = pid {
+                    unsafe {
+                        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+                    }
+                }
+                #[cfg(not(unix))]
+                let _ = pid;
+                anyhow::bail!(
+                    "'{}' timed out after {}s",
+                    cmd.program,
+                    policy.timeout.as_secs()
+                )
+            }
+        }
+    }
+
+    /// Start `cmd` as a long-running process talking over piped stdin and
+    /// stdout, such as a language server. The wall-clock timeout does not
+    /// apply; the process is killed when the `Child` is dropped.
+    pub fn spawn(&self, cmd: SandboxCommand) -> anyhow::Result<Child> {
+        let (mut command, _) = self.command(&cmd)?;
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        command
+            .spawn()
+            .with_context(|| format!("failed to start '{}' in sandbox", cmd.program))
+    }
+
+    /// The confined command for `cmd`, without stdio set up, and the policy
+    /// it runs under.
+    fn command(
+        &self,
+        cmd: &SandboxCommand,
+    ) -> anyhow::Result<(Command, std::borrow::Cow<'_, SandboxPolicy>)> {
+        let dir = cmd.dir.canonicalize().unwrap_or_else(|_| cmd.dir.clone());
+        let needs_network =
+            cmd.fetches_dependencies && self.policy.build_network && !self.policy.network;
+        let drops_network = cmd.offline && self.policy.network;
+        let policy =
+            if cmd.grants.is_empty() && !needs_network && !drops_network && cmd.timeout.is_none() {
+                std::borrow::Cow::Borrowed(&self.policy)
+            } else {
+                let mut policy = self.policy.clone();
+                policy.grants.extend(cmd.grants.iter().cloned());
+                policy.network = (policy.network || needs_network) && !cmd.offline;
+                if let Some(timeout) = cmd.timeout {
+                    policy.timeout = timeout;
+                }
+                std::borrow::Cow::Owned(policy)
+            };
+
+        let mut command = match &self.backend {
+            SandboxBackend::Bubblewrap(bwrap) => {
+                let mut c = Command::new(bwrap);
+                c.args(bwrap_args(&policy, &dir))
+                    .arg("--")
+                    .arg(&cmd.program)
+                    .args(&cmd.args);
+                c
+            }
+            SandboxBackend::Native | SandboxBackend::Off => {
+                let mut c = Command::new(&cmd.program);
+                c.args(&cmd.args);
+                c
+            }
+        };
+        command
+            .current_dir(&dir)
+            .env_clear()
+            .envs(policy.passthrough_env())
+            .envs(cmd.env.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+            .kill_on_drop(true);
+
+        #[cfg(unix)]
+        self.confine(&policy, &mut command, &dir)?;
+
+        tracing::debug!(
+            program = %cmd.program,
+            dir = %dir.display(),
+            backend = %self.backend,
+            "Spawning sandboxed process"
+        );
+        Ok((command, policy))
+    }
+
+    /// Install the pre-exec hook: new process group, rlimits and, for the
+    /// native backend, the Landlock/seccomp/namespace confinement.
+    #[cfg(unix)]
+    fn confine(
+        &self,
+        policy: &SandboxPolicy,
+        command: &mut Command,
+        dir: &Path,
+    ) -> anyhow::Result<()> {
+        let cpu_seconds = policy.cpu_seconds;
+        let memory_bytes = policy.memory_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+
+        #[cfg(target_os = "linux")]
+        let mut native = match self.backend {
+            SandboxBackend::Native => Some(linux::Confinement::build(policy, dir)?),
+            _ => None,
+        };
+        #[cfg(not(target_os = "linux"))]
+        let _ = dir;
+
+        // SAFETY: the hook runs between fork and exec and only makes
+        // syscalls; everything it needs was prepared in the parent.
+        unsafe {
+            command.pre_exec(move || {
+                if libc::setpgid(0, 0) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if let Some(secs) = cpu_seconds {
+                    set_rlimit(libc::RLIMIT_CPU, secs)?;
+                }
+                if let Some(bytes) = memory_bytes {
+                    set_rlimit(libc::RLIMIT_AS, bytes)?;
+                }
+                #[cfg(target_os = "linux")]
+                if let Some(confinement) = native.as_mut() {
+                    confinement.apply()?;
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Run `cmd` in the process-wide sandbox.
+pub async fn run(cmd: SandboxCommand) -> anyhow::Result<Output> {
+    Sandbox::global().output(cmd).await
+}
+
+/// Start the long-running `cmd` in the process-wide sandbox.
+pub fn spawn(cmd: SandboxCommand) -> anyhow::Result<Child> {
+    Sandbox::global().spawn(cmd)
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+fn set_rlimit(resource: RlimitResource, value: u64) -> std::io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    };
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Directories a grant maps to, with their access. Grants whose root does
+/// not exist are dropped.
+fn grant_roots(policy: &SandboxPolicy) -> Vec<(PathBuf, FsAccess)> {
+    policy
+        .grants
+        .iter()
+        .map(|g| (g.root(), g.access))
+        .filter(|(root, _)| !root.as_os_str().is_empty() && root.exists())
+        .collect()
+}
+
+/// `bwrap` arguments for `policy` with `dir` as the writable working
+/// directory. The command itself follows `--`.
+fn bwrap_args(policy: &SandboxPolicy, dir: &Path) -> Vec<String> {
+    let mut args: Vec<String> = ["--die-with-parent", "--new-session", "--unshare-all"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    if policy.network {
+        args.push("--share-net".into());
+    }
+    for (flag, path) in [("--dev", "/dev"), ("--proc", "/proc"), ("--tmpfs", "/tmp")] {
+        args.extend([flag.to_string(), path.to_string()]);
+    }
+
+    for (root, access) in grant_roots(policy) {
+        let root_str = root.to_string_lossy().to_string();
+        // Merged-/usr systems link /bin, /lib, ... into /usr; recreate the
+        // link instead of binding the target twice.
+        if let Ok(target) = std::fs::read_link(&root) {
+            args.extend([
+                "--symlink".into(),
+                target.to_string_lossy().to_string(),
+                root_str,
+            ]);
+            continue;
+        }
+        let flag = match access {
+            FsAccess::Read => "--ro-bind",
+            FsAccess::Write | FsAccess::ReadWrite => "--bind",
+        };
+        args.extend([flag.to_string(), root_str.clone(), root_str]);
+    }
+
+    let dir = dir.to_string_lossy().to_string();
+    args.extend(["--bind".into(), dir.clone(), dir.clone()]);
+    args.extend(["--chdir".into(), dir]);
+    args
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::collections::BTreeMap;
+    use std::ffi::CStr;
+    use std::io;
+    use std::path::Path;
+
+    use landlock::{
+        Access, AccessFs, PathBeneath, PathFd, Ruleset, RulesetAttr, RulesetCreated,
+        RulesetCreatedAttr, ABI,
+    };
+    use seccompiler::{BpfProgram, SeccompAction, SeccompFilter, TargetArch};
+
+    use super::{grant_roots, FsAccess, SandboxPolicy};
+
+    /// Highest Landlock ABI we ask for; older kernels get a best-effort
+    /// subset.
+    const LANDLOCK_ABI: ABI = ABI::V5;
+
+    /// Syscalls a sandboxed process never needs: mounts, namespaces,
+    /// tracing, kernel modules and keyrings. They fail with EPERM.
+    const DENIED_SYSCALLS: &[libc::c_long] = &[
+        libc::SYS_mount,
+        libc::SYS_umount2,
+        libc::SYS_pivot_root,
+        libc::SYS_setns,
+        libc::SYS_unshare,
+        libc::SYS_ptrace,
+        libc::SYS_process_vm_readv,
+        libc::SYS_process_vm_writev,
+        libc::SYS_kexec_load,
+        libc::SYS_init_module,
+        libc::SYS_finit_module,
+        libc::SYS_delete_module,
+        libc::SYS_bpf,
+        libc::SYS_perf_event_open,
+        libc::SYS_userfaultfd,
+        libc::SYS_keyctl,
+        libc::SYS_add_key,
+        libc::SYS_request_key,
+        libc::SYS_reboot,
+        libc::SYS_swapon,
+        libc::SYS_swapoff,
+        libc::SYS_acct,
+    ];
+
+    /// Confinement prepared in the parent and applied in the child.
+    pub(super) struct Confinement {
+        ruleset: Option<RulesetCreated>,
+        seccomp: Option<BpfProgram>,
+        /// Set when networking is off: move into fresh user and network
+        /// namespaces, mapping our uid/gid to themselves.
+        id_maps: Option<(String, String)>,
+    }
+
+    impl Confinement {
+        pub(super) fn build(policy: &SandboxPolicy, dir: &Path) -> anyhow::Result<Self> {
+            let mut rules = grant_roots(policy);
+            rules.push((dir.to_path_buf(), FsAccess::ReadWrite));
+            for (path, access) in [
+                ("/dev", FsAccess::ReadWrite),
+                ("/tmp", FsAccess::ReadWrite),
+                ("/proc", FsAccess::Read),
+            ] {
+                rules.push((path.into(), access));
+            }
+
+            let mut ruleset = Ruleset::default()
+                .handle_access(AccessFs::from_all(LANDLOCK_ABI))?
+                .create()?;
+            for (path, access) in rules {
+                let Ok(fd) = PathFd::new(&path) else {
+                    continue;
+                };
+                let rights = match access {
+                    FsAccess::Read => AccessFs::from_read(LANDLOCK_ABI),
+                    FsAccess::Write => AccessFs::from_write(LANDLOCK_ABI),
+                    FsAccess::ReadWrite => AccessFs::from_all(LANDLOCK_ABI),
+                };
+                ruleset = ruleset.add_rule(PathBeneath::new(fd, rights))?;
+            }
+
+            let seccomp = match TargetArch::try_from(std::env::consts::ARCH) {
+                Ok(arch) => {
+                    let rules: BTreeMap<i64, Vec<seccompiler::SeccompRule>> = DENIED_SYSCALLS
+                        .iter()
+                        // `c_long` is already i64 on the 64-bit targets seccompiler supports.
+                        .map(|&nr| {
+                            #[allow(clippy::useless_conversion)]
+                            let nr = i64::from(nr);
+                            (nr, Vec::new())
+                        })
+                        .collect();
+                    let filter = SeccompFilter::new(
+                        rules,
+                        SeccompAction::Allow,
+                        SeccompAction::Errno(libc::EPERM as u32),
+                        arch,
+                    )?;
+                    Some(BpfProgram::try_from(filter)?)
+                }
+                Err(_) => {
+                    tracing::warn!(
+                        "No seccomp filter for {}; syscalls are not restricted",
+                        std::env::consts::ARCH
+                    );
+                    None
+                }
+            };
+
+            let id_maps = (!policy.network).then(|| {
+                let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+                (format!("{uid} {uid} 1"), format!("{gid} {gid} 1"))
+            });
+
+            Ok(Self {
+                ruleset: Some(ruleset),
+                seccomp,
+                id_maps,
+            })
+        }
+
+        /// Runs in the forked child before exec.
+        pub(super) fn apply(&mut self) -> io::Result<()> {
+            if let Some((uid_map, gid_map)) = &self.id_maps {
+                if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) } != 0 {
+                    let err = io::Error::last_os_error();
+                    return Err(io::Error::new(
+                        err.kind(),
+                        format!(
+                            "cannot disable network ({err}); enable user namespaces, \
+                             install bubblewrap, or set [sandbox] network = true"
+                        ),
+                    ));
+                }
+                write_proc(c"/proc/self/setgroups", b"deny")?;
+                write_proc(c"/proc/self/uid_map", uid_map.as_bytes())?;
+                write_proc(c"/proc/self/gid_map", gid_map.as_bytes())?;
+            }
+            if let Some(ruleset) = self.ruleset.take() {
+                ruleset.restrict_self().map_err(io::Error::other)?;
+            }
+            if let Some(program) = &self.seccomp {
+                seccompiler::apply_filter(program).map_err(io::Error::other)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn write_proc(path: &CStr, data: &[u8]) -> io::Result<()> {
+        unsafe {
+            let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let written = libc::write(fd, data.as_ptr().cast(), data.len());
+            libc::close(fd);
+            if written < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> SandboxPolicy {
+        SandboxPolicy::from_config(&SandboxConfig::default())
+    }
+
+    #[test]
+    fn test_policy_includes_default_and_configured_grants() {
+        let cfg = SandboxConfig {
+            filesystem: vec!["rw:/data/out/*".into()],
+            ..Default::default()
+        };
+        let policy = SandboxPolicy::from_config(&cfg);
+        assert!(policy.grants.iter().any(|g| g.pattern == "/usr"));
+        let extra = policy.grants.last().unwrap();
+        assert_eq!(extra.access, FsAccess::ReadWrite);
+        assert_eq!(extra.root(), PathBuf::from("/data/out"));
+    }
+
+    #[test]
+    fn test_bwrap_args_isolate_network_by_default() {
+        let dir = std::env::temp_dir();
+        let args = bwrap_args(&policy(), &dir);
+        assert!(args.contains(&"--unshare-all".to_string()));
+        assert!(!args.contains(&"--share-net".to_string()));
+        let dir = dir.to_string_lossy().to_string();
+        assert!(args.ends_with(&["--chdir".to_string(), dir]));
+
+        let mut open = policy();
+        open.network = true;
+        assert!(bwrap_args(&open, Path::new("/")).contains(&"--share-net".to_string()));
+    }
+
+    #[test]
+    fn test_bwrap_args_bind_grants_by_access() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ro = tmp.path().join("ro");
+        let rw = tmp.path().join("rw");
+        std::fs::create_dir_all(&ro).unwrap();
+        std::fs::create_dir_all(&rw).unwrap();
+        let mut policy = policy();
+        policy.grants = vec![
+            FsGrant::parse(&format!("read:{}/*", ro.display())).unwrap(),
+            FsGrant::parse(&format!("rw:{}", rw.display())).unwrap(),
+            FsGrant::parse("read:/definitely/missing").unwrap(),
+        ];
+        let args = bwrap_args(&policy, tmp.path()).join(" ");
+        assert!(args.contains(&format!("--ro-bind {0} {0}", ro.display())));
+        assert!(args.contains(&format!("--bind {0} {0}", rw.display())));
+        assert!(!args.contains("/definitely/missing"));
+    }
+
+    #[test]
+    fn test_detect_off() {
+        assert_eq!(SandboxBackend::detect("off"), SandboxBackend::Off);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_auto_backend_can_start_processes() {
+        let sandbox = Sandbox::new(policy(), SandboxBackend::detect("auto"));
+        let out = sandbox
+            .output(SandboxCommand::new("sh").args(["-c", "echo ok"]))
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "ok");
+    }
+
+    #[test]
+    fn test_package_caches_are_writable() {
+        let policy = policy();
+        for cache in ["~/.cargo/registry", "~/.npm", "~/.cache"] {
+            let grant = policy
+                .grants
+                .iter()
+                .find(|g| g.pattern == cache)
+                .unwrap_or_else(|| panic!("no grant for {cache}"));
+            assert_eq!(grant.access, FsAccess::ReadWrite, "{cache}");
+        }
+        assert!(!policy.build_network);
+        assert!(!policy.network);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_output_uses_clean_env() {
+        std::env::set_var("OPENKOI_SANDBOX_TEST_SECRET", "leaked");
+        let sandbox = Sandbox::new(policy(), SandboxBackend::Off);
+        let out = sandbox
+            .output(
+                SandboxCommand::new("sh")
+                    .args(["-c", "echo ${OPENKOI_SANDBOX_TEST_SECRET:-unset} $EXTRA"])
+                    .env("EXTRA", "set"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "unset set");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_output_times_out() {
+        let mut policy = policy();
+        policy.timeout = Duration::from_millis(100);
+        let sandbox = Sandbox::new(policy, SandboxBackend::Off);
+        let err = sandbox
+            .output(SandboxCommand::new("sleep").arg("5"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_spawn_pipes_stdio() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let sandbox = Sandbox::new(policy(), SandboxBackend::Off);
+        let mut child = sandbox.spawn(SandboxCommand::new("cat").offline()).unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(b"ping").await.unwrap();
+        drop(stdin);
+        let mut out = String::new();
+        child
+            .stdout
+            .take()
+            .unwrap()
+            .read_to_string(&mut out)
+            .await
+            .unwrap();
+        assert_eq!(out, "ping");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_timeout_overrides_policy() {
+        let sandbox = Sandbox::new(policy(), SandboxBackend::Off);
+        let err = sandbox
+            .output(
+                SandboxCommand::new("sleep")
+                    .arg("5")
+                    .timeout(Duration::from_millis(100)),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+}
@@ -0,0 +1,982 @@
+// src/skills/community.rs — Installing skills from git, tarballs, paths and indexes
+//
+// A source is fetched into a scratch directory, hashed and checked against
+// any pinned digest or signature before anything is installed. The digest
+// covers every file's relative path and contents, so it is the same for a
+// git checkout, a tarball and a local copy of one skill. Installs are
+// recorded in a lockfile that `--locked` replays exactly.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::frontmatter::{frontmatter_to_metadata, parse_skill_md};
+use super::types::SkillMetadata;
+use crate::infra::config::SkillsConfig;
+use crate::infra::paths;
+
+/// Where a skill's files come from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    /// A git repository, optionally at a branch, tag or commit (`url#rev`).
+    Git { url: String, rev: Option<String> },
+    /// A `.tar.gz` archive over https:// or file://.
+    Tarball(String),
+    /// A directory on disk.
+    Path(PathBuf),
+}
+
+impl Origin {
+    /// Parse a source. Bare names return `None`; they are looked up in the
+    /// configured indexes.
+    pub fn parse(spec: &str) -> Option<Origin> {
+        let spec = spec.trim();
+        if let Some(url) = spec.strip_prefix("git+") {
+            return Some(git_origin(url));
+        }
+        let (base, _) = spec.split_once('#').unwrap_or((spec, ""));
+        let lower = base.to_ascii_lowercase();
+        let is_url = lower.starts_with("https://") || lower.starts_with("http://");
+        let is_file_url = lower.starts_with("file://");
+        if lower.starts_with("git@")
+            || lower.starts_with("ssh://")
+            || lower.starts_with("git://")
+            || (lower.ends_with(".git") && !is_file_url)
+        {
+            return Some(git_origin(spec));
+        }
+        if (is_url || is_file_url) && (lower.ends_with(".tar.gz") || lower.ends_with(".tgz")) {
+            return Some(Origin::Tarball(spec.to_string()));
+        }
+        if is_file_url {
+            return url::Url::parse(spec)
+                .ok()
+                .and_then(|u| u.to_file_path().ok())
+                .map(Origin::Path);
+        }
+        if is_url {
+            return Some(git_origin(spec));
+        }
+        if spec.contains('/')
+            || spec.contains('\\')
+            || spec.starts_with('.')
+            || spec.starts_with('~')
+        {
+            let path = match spec.strip_prefix("~/") {
+                Some(rest) => paths::dirs_home().join(rest),
+                None => PathBuf::from(spec),
+            };
+            return Some(Origin::Path(path));
+        }
+        None
+    }
+
+    /// The source as a string that `parse` reads back, without the git rev.
+    pub fn source(&self) -> String {
+        match self {
+            Origin::Git { url, .. } => match Origin::parse(url) {
+                Some(Origin::Git { .. }) => url.clone(),
+                _ => format!("git+{url}"),
+            },
+            Origin::Tarball(url) => url.clone(),
+            Origin::Path(path) => path.display().to_string(),
+        }
+    }
+}
+
+fn git_origin(spec: &str) -> Origin {
+    match spec.split_once('#') {
+        Some((url, rev)) if !rev.is_empty() => Origin::Git {
+            url: url.to_string(),
+            rev: Some(rev.to_string()),
+        },
+        Some((url, _)) => Origin::Git {
+            url: url.to_string(),
+            rev: None,
+        },
+        None => Origin::Git {
+            url: spec.to_string(),
+            rev: None,
+        },
+    }
+}
+
+/// One skill in an index file.
+///
+/// ```toml
+/// [skills.rust-review]
+/// source = "https://github.com/acme/skills.git#v1.2.0"
+/// subdir = "rust-review"
+/// sha256 = "…"
+/// signature = "…"   # hex ed25519 signature of the sha256 string
+/// description = "Reviews Rust code for ownership and error handling"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexEntry {
+    /// Git URL, tarball URL or path. Relative paths are resolved against
+    /// the directory of a local index.
+    pub source: String,
+    #[serde(default)]
+    pub subdir: Option<String>,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct IndexFile {
+    #[serde(default)]
+    skills: BTreeMap<String, IndexEntry>,
+}
+
+/// Read an index from https://, file:// or a path.
+pub async fn load_index(location: &str) -> Result<BTreeMap<String, IndexEntry>> {
+    let (text, base) = match Origin::parse(location) {
+        Some(Origin::Path(path)) => (
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read index {}", path.display()))?,
+            path.parent().map(Path::to_path_buf),
+        ),
+        _ if location.starts_with("http://") || location.starts_with("https://") => {
+            let bytes = download(location).await?;
+            (
+                String::from_utf8(bytes).context("Index is not UTF-8")?,
+                None,
+            )
+        }
+        _ => (
+            std::fs::read_to_string(location)
+                .with_context(|| format!("Failed to read index {location}"))?,
+            Path::new(location).parent().map(Path::to_path_buf),
+        ),
+    };
+    let mut index: IndexFile =
+        toml::from_str(&text).with_context(|| format!("Invalid index {location}"))?;
+    if let Some(base) = base {
+        for entry in index.skills.values_mut() {
+            if let Some(Origin::Path(path)) = Origin::parse(&entry.source) {
+                if path.is_relative() {
+                    let path = path.strip_prefix(".").unwrap_or(&path);
+                    entry.source = base.join(path).display().to_string();
+                }
+            }
+        }
+    }
+    Ok(index.skills)
+}
+
+/// A source to fetch, with what the result must match.
+#[derive(Debug, Clone)]
+pub struct Resolved {
+    /// What was asked for: an index name or a source. `update` resolves it again.
+    pub spec: String,
+    /// The index name, when the skill came from an index.
+    pub name: Option<String>,
+    pub origin: Origin,
+    pub subdir: Option<String>,
+    pub sha256: Option<String>,
+    pub signature: Option<String>,
+}
+
+/// Turn a source or an index name into something to fetch.
+pub async fn resolve(spec: &str, config: &SkillsConfig) -> Result<Resolved> {
+    if let Some(origin) = Origin::parse(spec) {
+        return Ok(Resolved {
+            spec: spec.to_string(),
+            name: None,
+            origin,
+            subdir: None,
+            sha256: None,
+            signature: None,
+        });
+    }
+    if config.indexes.is_empty() {
+        bail!(
+            "'{spec}' is not a URL or path, and no skill indexes are configured \
+             (set [skills] indexes in config.toml)"
+        );
+    }
+    for location in &config.indexes {
+        let index = load_index(location).await?;
+        if let Some(entry) = index.get(spec) {
+            let origin = Origin::parse(&entry.source).with_context(|| {
+                format!("Index {location}: '{}' is not a valid source", entry.source)
+            })?;
+            return Ok(Resolved {
+                spec: spec.to_string(),
+                name: Some(spec.to_string()),
+                origin,
+                subdir: entry.subdir.clone(),
+                sha256: entry.sha256.clone(),
+                signature: entry.signature.clone(),
+            });
+        }
+    }
+    bail!("Skill '{spec}' is not in any configured index")
+}
+
+/// A skill fetched into a scratch directory, not yet installed.
+#[derive(Debug)]
+pub struct Fetched {
+    scratch: PathBuf,
+    /// The directory holding SKILL.md.
+    pub dir: PathBuf,
+    pub name: String,
+    pub skill_md: String,
+    pub metadata: SkillMetadata,
+    pub sha256: String,
+    /// The commit a git source was checked out at.
+    pub rev: Option<String>,
+}
+
+impl Drop for Fetched {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.scratch);
+    }
+}
+
+/// Fetch a resolved source and read its SKILL.md.
+pub async fn fetch(resolved: &Resolved) -> Result<Fetched> {
+    let scratch = std::env::temp_dir().join(format!("openkoi-skill-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&scratch)?;
+    match fetch_into(resolved, &scratch).await {
+        Ok(fetched) => Ok(fetched),
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&scratch);
+            Err(e)
+        }
+    }
+}
+
+async fn fetch_into(resolved: &Resolved, scratch: &Path) -> Result<Fetched> {
+    let root = scratch.join("src");
+    let mut rev = None;
+    match &resolved.origin {
+        Origin::Git { url, rev: wanted } => {
+            rev = Some(clone(url, wanted.as_deref(), &root).await?);
+        }
+        Origin::Tarball(url) => {
+            let bytes = match url::Url::parse(url)
+                .ok()
+                .filter(|u| u.scheme() == "file")
+                .and_then(|u| u.to_file_path().ok())
+            {
+                Some(path) => std::fs::read(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?,
+                None => download(url).await?,
+            };
+            unpack(&bytes, &root)?;
+        }
+        Origin::Path(path) => {
+            if !path.is_dir() {
+                bail!("{} is not a directory", path.display());
+            }
+            std::fs::create_dir_all(&root)?;
+            copy_tree(path, &root)?;
+        }
+    }
+
+    let dir = match &resolved.subdir {
+        Some(subdir) => {
+            let relative = Path::new(subdir);
+            if relative
+                .components()
+                .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+            {
+                bail!("subdir '{subdir}' must be a relative path inside the source");
+            }
+            root.join(relative)
+        }
+        None => root,
+    };
+    let skill_path = dir.join("SKILL.md");
+    let skill_md = std::fs::read_to_string(&skill_path)
+        .with_context(|| format!("No SKILL.md in {}", resolved.origin.source()))?;
+    let (frontmatter, _) = parse_skill_md(&skill_md).context("Invalid SKILL.md")?;
+    let metadata = frontmatter_to_metadata(&frontmatter);
+    let name = resolved
+        .name
+        .clone()
+        .or(frontmatter.name)
+        .or_else(|| {
+            let subdir = resolved.subdir.as_deref()?;
+            Path::new(subdir)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+        })
+        .context("SKILL.md has no name")?;
+    check_name(&name)?;
+
+    Ok(Fetched {
+        scratch: scratch.to_path_buf(),
+        sha256: tree_digest(&dir)?,
+        dir,
+        name,
+        skill_md,
+        metadata,
+        rev,
+    })
+}
+
+/// Clone `url` into `dest` at `rev` (or the default branch) and return the
+/// commit it ended up at. The `.git` directory is removed afterwards.
+async fn clone(url: &str, rev: Option<&str>, dest: &Path) -> Result<String> {
+    let git = |args: &[&str], cwd: Option<&Path>| {
+        let mut cmd = tokio::process::Command::new("git");
+        cmd.args(args)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(std::process::Stdio::null());
+        if let Some(cwd) = cwd {
+            cmd.current_dir(cwd);
+        }
+        cmd.output()
+    };
+    let dest_str = dest.to_string_lossy();
+    let output = git(&["clone", "--quiet", "--", url, &dest_str], None)
+        .await
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git clone {url} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    if let Some(rev) = rev {
+        let output = git(&["checkout", "--quiet", rev, "--"], Some(dest)).await?;
+        if !output.status.success() {
+            bail!(
+                "git checkout {rev} failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+    }
+    let output = git(&["rev-parse", "HEAD"], Some(dest)).await?;
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    std::fs::remove_dir_all(dest.join(".git"))?;
+    Ok(commit)
+}
+
+async fn download(url: &str) -> Result<Vec<u8>> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .header(
+            "User-Agent",
+            format!("openkoi/{}", env!("CARGO_PKG_VERSION")),
+        )
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        bail!("GET {url} returned {}", response.status());
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Unpack a `.tar.gz` into `dest`. A single top-level directory, as in
+/// release archives, is stripped.
+fn unpack(bytes: &[u8], dest: &Path) -> Result<()> {
+    let staging = dest.with_extension("unpack");
+    std::fs::create_dir_all(&staging)?;
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+    archive
+        .unpack(&staging)
+        .context("Failed to unpack archive")?;
+
+    let entries: Vec<_> = std::fs::read_dir(&staging)?.collect::<std::io::Result<_>>()?;
+    let top = match entries.as_slice() {
+        [only] if only.file_type()?.is_dir() => only.path(),
+        _ => staging.clone(),
+    };
+    std::fs::rename(&top, dest)?;
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    Ok(())
+}
+
+/// SHA-256 over every file under `dir`, in path order: the relative path
+/// (with `/` separators), a NUL byte, the length as a little-endian u64,
+/// then the contents. Symlinks are rejected.
+pub fn tree_digest(dir: &Path) -> Result<String> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    files.sort();
+    let mut hasher = Sha256::new();
+    for relative in files {
+        let mut contents = Vec::new();
+        std::fs::File::open(dir.join(&relative))?.read_to_end(&mut contents)?;
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let kind = entry.file_type()?;
+        let path = entry.path();
+        if kind.is_symlink() {
+            bail!(
+                "{} is a symlink; skills must be plain files",
+                path.display()
+            );
+        }
+        if kind.is_dir() {
+            if entry.file_name() != ".git" {
+                collect_files(root, &path, files)?;
+            }
+            continue;
+        }
+        let relative = path.strip_prefix(root)?;
+        let parts: Vec<_> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        files.push(parts.join("/"));
+    }
+    Ok(())
+}
+
+fn copy_tree(from: &Path, to: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let kind = entry.file_type()?;
+        let target = to.join(entry.file_name());
+        if kind.is_symlink() {
+            bail!(
+                "{} is a symlink; skills must be plain files",
+                entry.path().display()
+            );
+        }
+        if kind.is_dir() {
+            if entry.file_name() == ".git" {
+                continue;
+            }
+            std::fs::create_dir_all(&target)?;
+            copy_tree(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!("'{name}' is not a valid skill name");
+    }
+    Ok(())
+}
+
+/// How a fetched skill was checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trust {
+    /// Signed by a trusted key (and so also pinned).
+    Signed,
+    /// Matches a pinned sha256.
+    Pinned,
+    /// Nothing to check against.
+    Unverified,
+}
+
+/// Check a fetched skill against the pinned digest and signature. A
+/// signature is over the sha256 hex string and must come from one of
+/// `config.trusted_keys`.
+pub fn verify(fetched: &Fetched, resolved: &Resolved, config: &SkillsConfig) -> Result<Trust> {
+    let mut trust = Trust::Unverified;
+    if let Some(ref expected) = resolved.sha256 {
+        if !expected.eq_ignore_ascii_case(&fetched.sha256) {
+            bail!(
+                "sha256 mismatch for '{}': expected {expected}, got {}",
+                fetched.name,
+                fetched.sha256
+            );
+        }
+        trust = Trust::Pinned;
+    }
+    match resolved.signature {
+        Some(ref signature) if !config.trusted_keys.is_empty() => {
+            if resolved.sha256.is_none() {
+                bail!("'{}' is signed but has no sha256 to check", fetched.name);
+            }
+            check_signature(&fetched.sha256, signature, &config.trusted_keys)
+                .with_context(|| format!("Signature check failed for '{}'", fetched.name))?;
+            trust = Trust::Signed;
+        }
+        _ if config.require_signature => {
+            bail!(
+                "'{}' has no signature from a trusted key, and [skills] require_signature is set",
+                fetched.name
+            );
+        }
+        _ => {}
+    }
+    Ok(trust)
+}
+
+fn check_signature(sha256: &str, signature: &str, keys: &[String]) -> Result<()> {
+    use ring::signature::{UnparsedPublicKey, ED25519};
+
+    let signature = hex::decode(signature.trim()).context("signature is not hex")?;
+    for key in keys {
+        let key = hex::decode(key.trim()).context("trusted key is not hex")?;
+        if UnparsedPublicKey::new(&ED25519, key)
+            .verify(sha256.to_ascii_lowercase().as_bytes(), &signature)
+            .is_ok()
+        {
+            return Ok(());
+        }
+    }
+    bail!("no trusted key matches the signature")
+}
+
+/// Where skills are installed and pinned.
+#[derive(Debug, Clone)]
+pub enum Scope {
+    /// The managed skills directory, pinned in `skills/skills.lock`.
+    User,
+    /// `<root>/.agents/skills`, pinned in `<root>/.openkoi/skills.lock` so a
+    /// team can commit both.
+    Project(PathBuf),
+}
+
+impl Scope {
+    pub fn skills_dir(&self) -> PathBuf {
+        match self {
+            Scope::User => paths::managed_skills_dir(),
+            Scope::Project(root) => root.join(".agents/skills"),
+        }
+    }
+
+    pub fn lockfile(&self) -> PathBuf {
+        match self {
+            Scope::User => paths::skills_dir().join("skills.lock"),
+            Scope::Project(root) => root.join(".openkoi/skills.lock"),
+        }
+    }
+}
+
+/// Installed community skills with the exact source and digest of each.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub skills: BTreeMap<String, LockEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockEntry {
+    /// What was asked for; `update` resolves it again.
+    pub spec: String,
+    /// The resolved source.
+    pub source: String,
+    /// The git commit, for git sources.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subdir: Option<String>,
+    pub sha256: String,
+}
+
+impl LockEntry {
+    /// The pinned source: the same commit and digest as when it was locked.
+    pub fn pinned(&self) -> Result<Resolved> {
+        let origin = match Origin::parse(&self.source) {
+            Some(Origin::Git { url, .. }) => Origin::Git {
+                url,
+                rev: self.rev.clone(),
+            },
+            Some(origin) => origin,
+            None => bail!("'{}' is not a valid source", self.source),
+        };
+        Ok(Resolved {
+            spec: self.spec.clone(),
+            name: None,
+            origin,
+            subdir: self.subdir.clone(),
+            sha256: Some(self.sha256.clone()),
+            signature: None,
+        })
+    }
+}
+
+impl Lockfile {
+    /// Load a lockfile; a missing file is an empty one.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => {
+                toml::from_str(&text).with_context(|| format!("Invalid {}", path.display()))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let body = toml::to_string_pretty(self)?;
+        std::fs::write(
+            path,
+            format!(
+                "# Written by `openkoi learn`. Commit it to pin skills for everyone.\n\n{body}"
+            ),
+        )?;
+        Ok(())
+    }
+}
+
+/// Copy a fetched skill into the scope, replacing any earlier version, and
+/// pin it in the lockfile. Returns the install directory.
+pub fn install(fetched: &Fetched, resolved: &Resolved, scope: &Scope) -> Result<PathBuf> {
+    let skills_dir = scope.skills_dir();
+    std::fs::create_dir_all(&skills_dir)?;
+    let target = skills_dir.join(&fetched.name);
+    let staging = skills_dir.join(format!(".{}.installing", fetched.name));
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    std::fs::create_dir_all(&staging)?;
+    if let Err(e) = copy_tree(&fetched.dir, &staging) {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    // Keep the earlier version aside until the new one is in place and
+    // pinned, so a failure leaves the skill and lockfile as they were.
+    let backup = skills_dir.join(format!(".{}.previous", fetched.name));
+    if backup.exists() {
+        std::fs::remove_dir_all(&backup)?;
+    }
+    let had_previous = target.exists();
+    let swapped = (|| -> Result<()> {
+        if had_previous {
+            std::fs::rename(&target, &backup)?;
+        }
+        std::fs::rename(&staging, &target)?;
+        pin(fetched, resolved, scope)
+    })();
+    if let Err(e) = swapped {
+        if backup.exists() {
+            let _ = std::fs::remove_dir_all(&target);
+            if let Err(restore) = std::fs::rename(&backup, &target) {
+                tracing::warn!("Could not restore {}: {}", target.display(), restore);
+            }
+        } else if !had_previous {
+            let _ = std::fs::remove_dir_all(&target);
+        }
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    if had_previous {
+        if let Err(e) = std::fs::remove_dir_all(&backup) {
+            tracing::warn!("Could not remove {}: {}", backup.display(), e);
+        }
+    }
+    Ok(target)
+}
+
+/// Record a fetched skill in the scope's lockfile.
+fn pin(fetched: &Fetched, resolved: &Resolved, scope: &Scope) -> Result<()> {
+    let lock_path = scope.lockfile();
+    let mut lock = Lockfile::load(&lock_path)?;
+    lock.skills.insert(
+        fetched.name.clone(),
+        LockEntry {
+            spec: resolved.spec.clone(),
+            source: resolved.origin.source(),
+            rev: fetched.rev.clone(),
+            subdir: resolved.subdir.clone(),
+            sha256: fetched.sha256.clone(),
+        },
+    );
+    lock.save(&lock_path)
+}
+
+/// Remove an installed skill and its lockfile entry. Returns false when
+/// neither existed.
+pub fn remove(name: &str, scope: &Scope) -> Result<bool> {
+    check_name(name)?;
+    let target = scope.skills_dir().join(name);
+    let existed = target.exists();
+    if existed {
+        std::fs::remove_dir_all(&target)?;
+    }
+    let lock_path = scope.lockfile();
+    let mut lock = Lockfile::load(&lock_path)?;
+    let locked = lock.skills.remove(name).is_some();
+    if locked {
+        lock.save(&lock_path)?;
+    }
+    Ok(existed || locked)
+}
+
+/// Whether the installed copy of `name` matches the digest it is pinned to.
+pub fn is_current(name: &str, entry: &LockEntry, scope: &Scope) -> bool {
+    let dir = scope.skills_dir().join(name);
+    dir.is_dir() && tree_digest(&dir).is_ok_and(|digest| digest == entry.sha256)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn write_skill(dir: &Path, name: &str) {
+        std::fs::create_dir_all(dir.join("scripts")).unwrap();
+        std::fs::write(
+            dir.join("SKILL.md"),
+            format!(
+                "---\nname: {name}\ndescription: Test skill\nmetadata:\n  requires_bins: [git]\n  requires_env: [GITHUB_TOKEN]\n---\n# {name}\n"
+            ),
+        )
+        .unwrap();
+        std::fs::write(dir.join("scripts/check.sh"), "echo ok\n").unwrap();
+    }
+
+    fn file_url(path: &Path) -> String {
+        url::Url::from_file_path(path).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_parse_origin() {
+        assert_eq!(
+            Origin::parse("https://github.com/acme/skills.git#v1"),
+            Some(Origin::Git {
+                url: "https://github.com/acme/skills.git".into(),
+                rev: Some("v1".into())
+            })
+        );
+        assert!(matches!(
+            Origin::parse("git@github.com:acme/skills.git"),
+            Some(Origin::Git { rev: None, .. })
+        ));
+        assert!(matches!(
+            Origin::parse("https://github.com/acme/skills"),
+            Some(Origin::Git { .. })
+        ));
+        assert_eq!(
+            Origin::parse("https://example.com/s.tar.gz"),
+            Some(Origin::Tarball("https://example.com/s.tar.gz".into()))
+        );
+        assert_eq!(
+            Origin::parse("file:///srv/skills/review"),
+            Some(Origin::Path("/srv/skills/review".into()))
+        );
+        assert_eq!(
+            Origin::parse("./review"),
+            Some(Origin::Path("./review".into()))
+        );
+        assert_eq!(Origin::parse("rust-review"), None);
+
+        let local_git = Origin::Git {
+            url: "/srv/repo".into(),
+            rev: None,
+        };
+        assert_eq!(local_git.source(), "git+/srv/repo");
+        assert!(matches!(
+            Origin::parse(&local_git.source()),
+            Some(Origin::Git { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tree_digest_ignores_location() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        write_skill(a.path(), "review");
+        write_skill(b.path(), "review");
+        std::fs::create_dir_all(b.path().join(".git")).unwrap();
+        std::fs::write(b.path().join(".git/HEAD"), "ref").unwrap();
+        assert_eq!(
+            tree_digest(a.path()).unwrap(),
+            tree_digest(b.path()).unwrap()
+        );
+
+        std::fs::write(b.path().join("scripts/check.sh"), "echo changed\n").unwrap();
+        assert_ne!(
+            tree_digest(a.path()).unwrap(),
+            tree_digest(b.path()).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_from_file_index() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("index/review");
+        write_skill(&source, "review");
+        let digest = tree_digest(&source).unwrap();
+
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let signature = hex::encode(key.sign(digest.as_bytes()).as_ref());
+
+        let index_path = tmp.path().join("index/index.toml");
+        std::fs::write(
+            &index_path,
+            format!(
+                "[skills.review]\nsource = \"./review\"\nsha256 = \"{digest}\"\nsignature = \"{signature}\"\n\n\
+                 [skills.tampered]\nsource = \"./review\"\nsha256 = \"{}\"\n",
+                "0".repeat(64)
+            ),
+        )
+        .unwrap();
+        let mut config = SkillsConfig {
+            indexes: vec![file_url(&index_path)],
+            trusted_keys: vec![hex::encode(key.public_key().as_ref())],
+            require_signature: false,
+            ..Default::default()
+        };
+
+        let resolved = resolve("review", &config).await.unwrap();
+        assert_eq!(resolved.name.as_deref(), Some("review"));
+        let fetched = fetch(&resolved).await.unwrap();
+        assert_eq!(fetched.sha256, digest);
+        assert_eq!(
+            fetched.metadata.requires_bins.as_deref(),
+            Some(&["git".to_string()][..])
+        );
+        assert_eq!(verify(&fetched, &resolved, &config).unwrap(), Trust::Signed);
+
+        let scope = Scope::Project(tmp.path().join("project"));
+        let installed = install(&fetched, &resolved, &scope).unwrap();
+        assert!(installed.join("scripts/check.sh").is_file());
+        let lock = Lockfile::load(&scope.lockfile()).unwrap();
+        let entry = &lock.skills["review"];
+        assert_eq!(entry.spec, "review");
+        assert_eq!(entry.sha256, digest);
+        assert!(is_current("review", entry, &scope));
+
+        // The lockfile replays to the same digest.
+        let pinned = entry.pinned().unwrap();
+        let refetched = fetch(&pinned).await.unwrap();
+        assert_eq!(verify(&refetched, &pinned, &config).unwrap(), Trust::Pinned);
+
+        let tampered = resolve("tampered", &config).await.unwrap();
+        let fetched = fetch(&tampered).await.unwrap();
+        assert!(verify(&fetched, &tampered, &config).is_err());
+
+        config.trusted_keys = vec![hex::encode([7u8; 32])];
+        let fetched = fetch(&resolved).await.unwrap();
+        assert!(verify(&fetched, &resolved, &config).is_err());
+
+        config.trusted_keys.clear();
+        config.require_signature = true;
+        assert!(verify(&fetched, &resolved, &config).is_err());
+
+        assert!(resolve("missing", &config).await.is_err());
+        assert!(remove("review", &scope).unwrap());
+        assert!(!scope.skills_dir().join("review").exists());
+        assert!(Lockfile::load(&scope.lockfile()).unwrap().skills.is_empty());
+        assert!(!remove("review", &scope).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_failed_reinstall_keeps_previous_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("review");
+        write_skill(&source, "review");
+        let resolved = Resolved {
+            spec: file_url(&source),
+            name: None,
+            origin: Origin::parse(&file_url(&source)).unwrap(),
+            subdir: None,
+            sha256: None,
+            signature: None,
+        };
+        let fetched = fetch(&resolved).await.unwrap();
+        let scope = Scope::Project(tmp.path().join("project"));
+        let installed = install(&fetched, &resolved, &scope).unwrap();
+        std::fs::write(installed.join("scripts/check.sh"), "echo v1\n").unwrap();
+
+        // An unreadable lockfile fails the reinstall after the swap.
+        std::fs::remove_file(scope.lockfile()).unwrap();
+        std::fs::create_dir_all(scope.lockfile()).unwrap();
+        assert!(install(&fetched, &resolved, &scope).is_err());
+        assert_eq!(
+            std::fs::read_to_string(installed.join("scripts/check.sh")).unwrap(),
+            "echo v1\n"
+        );
+        let leftovers: Vec<_> = std::fs::read_dir(scope.skills_dir())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(leftovers, vec![std::ffi::OsString::from("review")]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_tarball_strips_top_directory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("skills-1.0/lint");
+        write_skill(&source, "lint");
+
+        let archive_path = tmp.path().join("skills.tar.gz");
+        let file = std::fs::File::create(&archive_path).unwrap();
+        let mut builder =
+            tar::Builder::new(flate2::write::GzEncoder::new(file, Default::default()));
+        builder
+            .append_dir_all("skills-1.0", tmp.path().join("skills-1.0"))
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let resolved = Resolved {
+            spec: file_url(&archive_path),
+            name: None,
+            origin: Origin::parse(&file_url(&archive_path)).unwrap(),
+            subdir: Some("lint".into()),
+            sha256: Some(tree_digest(&source).unwrap()),
+            signature: None,
+        };
+        let fetched = fetch(&resolved).await.unwrap();
+        assert_eq!(fetched.name, "lint");
+        let config = SkillsConfig::default();
+        assert_eq!(verify(&fetched, &resolved, &config).unwrap(), Trust::Pinned);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_git_records_commit() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("repo");
+        write_skill(&repo, "deploy");
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(&repo)
+                .env("GIT_AUTHOR_NAME", "t")
+                .env("GIT_AUTHOR_EMAIL", "t@example.com")
+                .env("GIT_COMMITTER_NAME", "t")
+                .env("GIT_COMMITTER_EMAIL", "t@example.com")
+                .output()
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        };
+        if git(&["init", "--quiet"]).is_err() {
+            return; // git is not installed
+        }
+        git(&["add", "-A"]).unwrap();
+        git(&["commit", "--quiet", "-m", "init"]).unwrap();
+        let head = git(&["rev-parse", "HEAD"]).unwrap();
+
+        let spec = format!("git+{}", repo.display());
+        let resolved = resolve(&spec, &SkillsConfig::default()).await.unwrap();
+        let fetched = fetch(&resolved).await.unwrap();
+        assert_eq!(fetched.name, "deploy");
+        assert_eq!(fetched.rev.as_deref(), Some(head.as_str()));
+        assert_eq!(fetched.sha256, tree_digest(&repo).unwrap());
+        assert!(!fetched.dir.join(".git").exists());
+    }
+}
@@ -0,0 +1,282 @@
+// src/skills/embedding.rs — Skill embeddings for semantic selection
+
+use std::sync::{Arc, Mutex};
+
+use sha2::{Digest, Sha256};
+
+use super::registry::SkillRegistry;
+use super::types::{SkillEntry, SkillKind};
+use crate::infra::config::SkillEmbeddingConfig;
+use crate::memory::store::Store;
+use crate::provider::ModelProvider;
+
+/// Most body text embedded for one skill, in bytes.
+const MAX_BODY_BYTES: usize = 8 * 1024;
+
+/// Embeds skills and tasks with one provider, so their vectors compare.
+/// Each provider picks its own embedding model; cached vectors are keyed
+/// by the provider id and that model.
+pub struct SkillEmbedder {
+    provider: Arc<dyn ModelProvider>,
+    config: SkillEmbeddingConfig,
+}
+
+impl SkillEmbedder {
+    pub fn new(provider: Arc<dyn ModelProvider>, config: &SkillEmbeddingConfig) -> Self {
+        Self {
+            provider,
+            config: config.clone(),
+        }
+    }
+
+    pub fn provider_id(&self) -> &str {
+        self.provider.id()
+    }
+
+    /// Model column of the embedding cache, e.g. `openai/text-embedding-3-small`.
+    fn cache_key(&self) -> String {
+        match self.provider.embedding_model() {
+            Some(model) => format!("{}/{}", self.provider.id(), model),
+            None => self.provider.id().to_string(),
+        }
+    }
+
+    /// Give every task skill in the registry an embedding. Vectors cached
+    /// for unchanged text are reused and the rest go out in one request.
+    /// Returns how many skills ended up with an embedding; a provider
+    /// without embeddings leaves them all without one.
+    pub async fn embed_registry(
+        &self,
+        registry: &mut SkillRegistry,
+        store: Option<&Mutex<Store>>,
+    ) -> usize {
+        if !self.config.enabled {
+            return 0;
+        }
+        let model = self.cache_key();
+        let mut found: Vec<(String, Vec<f32>)> = Vec::new();
+        let mut pending: Vec<(String, String, String)> = Vec::new();
+        {
+            let store_guard = store.and_then(|s| s.lock().ok());
+            for skill in registry.all().iter().filter(|s| s.kind == SkillKind::Task) {
+                let text = skill_text(registry, skill, self.config.bodies);
+                let hash = content_hash(&text);
+                let cached = store_guard
+                    .as_deref()
+                    .and_then(|s| s.get_skill_embedding(&hash, &model).ok())
+                    .flatten();
+                match cached {
+                    Some(vector) => found.push((skill.name.clone(), vector)),
+                    None => pending.push((skill.name.clone(), hash, text)),
+                }
+            }
+        } // store_guard dropped here
+
+        if !pending.is_empty() {
+            let texts: Vec<&str> = pending.iter().map(|(_, _, text)| text.as_str()).collect();
+            match self.provider.embed(&texts).await {
+                Ok(vectors) if vectors.len() == pending.len() => {
+                    let store_guard = store.and_then(|s| s.lock().ok());
+                    for ((name, hash, _), vector) in pending.into_iter().zip(vectors) {
+                        if vector.is_empty() {
+                            continue;
+                        }
+                        if let Some(s) = store_guard.as_deref() {
+                            if let Err(e) = s.put_skill_embedding(&hash, &model, &vector) {
+                                tracing::debug!("Failed to cache embedding for {name}: {e}");
+                            }
+                        }
+                        found.push((name, vector));
+                    }
+                }
+                Ok(vectors) => tracing::debug!(
+                    "{} returned {} embeddings for {} skills",
+                    model,
+                    vectors.len(),
+                    pending.len()
+                ),
+                Err(e) => tracing::debug!("Skill embeddings unavailable: {e}"),
+            }
+        }
+
+        let count = found.len();
+        for (name, vector) in found {
+            registry.set_embedding(&name, vector);
+        }
+        count
+    }
+
+    /// Embed a task description to compare with skill embeddings. `None`
+    /// when no skill has an embedding or the provider can't embed.
+    pub async fn embed_task(&self, description: &str, skills: &[SkillEntry]) -> Option<Vec<f32>> {
+        if !self.config.enabled || !skills.iter().any(|s| s.embedding.is_some()) {
+            return None;
+        }
+        match self.provider.embed(&[description]).await {
+            Ok(mut vectors) => vectors.pop().filter(|v| !v.is_empty()),
+            Err(e) => {
+                tracing::debug!("Task embedding unavailable: {e}");
+                None
+            }
+        }
+    }
+}
+
+/// The text embedded for a skill: name and description, plus the start of
+/// its body when `bodies` is set.
+pub fn skill_text(registry: &SkillRegistry, skill: &SkillEntry, bodies: bool) -> String {
+    let mut text = format!("{}: {}", skill.name, skill.description);
+    if bodies {
+        if let Ok(body) = registry.load_body(skill) {
+            let mut end = body.len().min(MAX_BODY_BYTES);
+            while !body.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.push_str("\n\n");
+            text.push_str(body[..end].trim());
+        }
+    }
+    text
+}
+
+/// Cache key for embedded text.
+pub fn content_hash(text: &str) -> String {
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::errors::OpenKoiError;
+    use crate::memory::schema;
+    use crate::provider::{ChatChunk, ChatRequest, ChatResponse, ModelInfo};
+    use crate::skills::types::{SkillMetadata, SkillSource};
+    use async_trait::async_trait;
+    use futures::Stream;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Embeds text as counts of a few keywords.
+    struct KeywordEmbedder {
+        calls: AtomicUsize,
+        model: &'static str,
+    }
+
+    #[async_trait]
+    impl ModelProvider for KeywordEmbedder {
+        fn id(&self) -> &str {
+            "keywords"
+        }
+        fn name(&self) -> &str {
+            "Keywords"
+        }
+        fn models(&self) -> Vec<ModelInfo> {
+            vec![]
+        }
+        async fn chat(&self, _req: ChatRequest) -> Result<ChatResponse, OpenKoiError> {
+            Err(OpenKoiError::NoProvider)
+        }
+        async fn chat_stream(
+            &self,
+            _req: ChatRequest,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatChunk, OpenKoiError>> + Send>>, OpenKoiError>
+        {
+            Err(OpenKoiError::NoProvider)
+        }
+        fn embedding_model(&self) -> Option<&str> {
+            Some(self.model)
+        }
+        async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, OpenKoiError> {
+            self.calls.fetch_add(texts.len(), Ordering::SeqCst);
+            Ok(texts
+                .iter()
+                .map(|t| {
+                    ["sql", "release", "test"]
+                        .iter()
+                        .map(|k| t.matches(k).count() as f32)
+                        .collect()
+                })
+                .collect())
+        }
+    }
+
+    fn skill(name: &str, description: &str) -> SkillEntry {
+        SkillEntry {
+            name: name.into(),
+            kind: SkillKind::Task,
+            description: description.into(),
+            source: SkillSource::UserGlobal,
+            path: None,
+            metadata: SkillMetadata::default(),
+            embedding: None,
+            approved: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_are_cached_by_content() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        schema::run_migrations(&conn).unwrap();
+        let store = Mutex::new(Store::new(conn));
+        let provider = Arc::new(KeywordEmbedder {
+            calls: AtomicUsize::new(0),
+            model: "kw-1",
+        });
+        let embedder = SkillEmbedder::new(provider.clone(), &SkillEmbeddingConfig::default());
+
+        let mut registry = SkillRegistry::empty();
+        registry.add(skill("migrations", "Write sql migrations"));
+        registry.add(skill("release-notes", "Draft release notes"));
+        assert_eq!(
+            embedder.embed_registry(&mut registry, Some(&store)).await,
+            2
+        );
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            registry.get_by_name("migrations").unwrap().embedding,
+            Some(vec![1.0, 0.0, 0.0])
+        );
+
+        // Unchanged text comes from the cache; changed text is embedded again
+        let mut registry = SkillRegistry::empty();
+        registry.add(skill("migrations", "Write sql migrations"));
+        registry.add(skill("release-notes", "Draft release notes and test plans"));
+        assert_eq!(
+            embedder.embed_registry(&mut registry, Some(&store)).await,
+            2
+        );
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+
+        let task = embedder.embed_task("fix the sql", registry.all()).await;
+        assert_eq!(task, Some(vec![1.0, 0.0, 0.0]));
+
+        let off = SkillEmbedder::new(
+            provider.clone(),
+            &SkillEmbeddingConfig {
+                enabled: false,
+                bodies: false,
+            },
+        );
+        assert_eq!(off.embed_task("fix the sql", registry.all()).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_embedding_cache_is_per_model() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        schema::run_migrations(&conn).unwrap();
+        let store = Mutex::new(Store::new(conn));
+        let mut registry = SkillRegistry::empty();
+        registry.add(skill("migrations", "Write sql migrations"));
+
+        // A new embedding model misses the cache; the same one hits it
+        for (model, embedded) in [("kw-1", 1), ("kw-2", 1), ("kw-2", 0)] {
+            let provider = Arc::new(KeywordEmbedder {
+                calls: AtomicUsize::new(0),
+                model,
+            });
+            let embedder = SkillEmbedder::new(provider.clone(), &SkillEmbeddingConfig::default());
+            embedder.embed_registry(&mut registry, Some(&store)).await;
+            assert_eq!(provider.calls.load(Ordering::SeqCst), embedded, "{model}");
+        }
+    }
+}
@@ -1,7 +1,10 @@
 // src/skills/mod.rs — Skill system
 
+pub mod community;
 pub mod eligibility;
+pub mod embedding;
 pub mod frontmatter;
 pub mod loader;
 pub mod registry;
+pub mod tools;
 pub mod types;
@@ -1,11 +1,15 @@
 // src/skills/registry.rs — Skill registry
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use super::frontmatter::parse_skill_md;
 use super::loader;
 use super::types::*;
 
+/// Most bundle files listed for one skill.
+const MAX_BUNDLE_FILES: usize = 200;
+
 /// Central registry of all loaded skills.
 pub struct SkillRegistry {
     skills: Vec<SkillEntry>,
@@ -55,6 +59,13 @@ impl SkillRegistry {
         &self.skills
     }
 
+    /// Attach an embedding to the named skill.
+    pub fn set_embedding(&mut self, name: &str, embedding: Vec<f32>) {
+        if let Some(skill) = self.skills.iter_mut().find(|s| s.name == name) {
+            skill.embedding = Some(embedding);
+        }
+    }
+
     /// Load the body (markdown content after frontmatter) of a skill.
     pub fn load_body(&self, skill: &SkillEntry) -> anyhow::Result<String> {
         // Check cache first
@@ -108,6 +119,25 @@ impl SkillRegistry {
         Err(anyhow::anyhow!("Skill body not found for '{}'", skill.name))
     }
 
+    /// The directory a skill was loaded from. Bundled skills have none.
+    pub fn skill_dir(&self, skill: &SkillEntry) -> Option<PathBuf> {
+        skill.path.as_deref()?.parent().map(Path::to_path_buf)
+    }
+
+    /// Files shipped next to a skill's SKILL.md (scripts, templates,
+    /// references), as sorted relative paths. Hidden entries are skipped.
+    pub fn bundle_files(&self, skill: &SkillEntry) -> Vec<String> {
+        let Some(dir) = self.skill_dir(skill) else {
+            return Vec::new();
+        };
+        let mut files = Vec::new();
+        collect_bundle_files(&dir, &dir, &mut files);
+        files.retain(|f| f != "SKILL.md");
+        files.sort();
+        files.truncate(MAX_BUNDLE_FILES);
+        files
+    }
+
     /// Count of skills by kind.
     pub fn count(&self, kind: SkillKind) -> usize {
         self.skills.iter().filter(|s| s.kind == kind).count()
@@ -118,3 +148,24 @@ impl SkillRegistry {
         self.skills.push(skill);
     }
 }
+
+fn collect_bundle_files(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            collect_bundle_files(root, &path, files);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let parts: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            files.push(parts.join("/"));
+        }
+    }
+}
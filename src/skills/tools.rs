@@ -0,0 +1,493 @@
+// src/skills/tools.rs — Expose skills to the agent as tools
+//
+// Besides `load_skill`, skills that ship files next to their SKILL.md get
+// `skill_read_file` and `skill_run_script`. Both take the skill's name and
+// a path inside its directory; scripts run in the sandbox with the
+// project as working directory, after the skill's `requires_bins` and
+// `requires_env` are checked.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::registry::SkillRegistry;
+use super::types::{SkillEntry, SkillKind};
+use crate::provider::ToolDef;
+use crate::security::sandbox::{self, SandboxCommand};
+use crate::tools::{ToolEffect, ToolProvider, ToolSource};
+
+/// Name of the tool that loads a skill's instructions.
+pub const LOAD_SKILL_TOOL: &str = "load_skill";
+/// Name of the tool that reads a file from a skill's directory.
+pub const SKILL_READ_FILE_TOOL: &str = "skill_read_file";
+/// Name of the tool that runs a script from a skill's directory.
+pub const SKILL_RUN_SCRIPT_TOOL: &str = "skill_run_script";
+
+/// Largest file `skill_read_file` returns in full.
+const MAX_READ_BYTES: usize = 64 * 1024;
+
+/// Interpreters for scripts that are not executable themselves.
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("sh", "sh"),
+    ("bash", "bash"),
+    ("py", "python3"),
+    ("js", "node"),
+    ("rb", "ruby"),
+];
+
+/// How a skill's bundled files are described to the model, after its body.
+pub fn files_note(files: &[String]) -> String {
+    let mut note = format!(
+        "Files in this skill (read with `{SKILL_READ_FILE_TOOL}`, run scripts with `{SKILL_RUN_SCRIPT_TOOL}`):\n"
+    );
+    for file in files {
+        note.push_str(&format!("- {file}\n"));
+    }
+    note
+}
+
+/// Lets the model pull in the full body of any approved task skill, not just
+/// the ones ranked into the system prompt.
+pub struct SkillTools {
+    registry: Arc<SkillRegistry>,
+}
+
+impl SkillTools {
+    pub fn new(registry: Arc<SkillRegistry>) -> Self {
+        Self { registry }
+    }
+
+    fn available(&self) -> Vec<&SkillEntry> {
+        self.registry
+            .all()
+            .iter()
+            .filter(|s| s.kind == SkillKind::Task && s.is_approved())
+            .collect()
+    }
+
+    /// Approved task skills with files besides SKILL.md.
+    fn bundled(&self) -> Vec<&SkillEntry> {
+        self.available()
+            .into_iter()
+            .filter(|s| !self.registry.bundle_files(s).is_empty())
+            .collect()
+    }
+
+    fn find<'a>(
+        &self,
+        skills: Vec<&'a SkillEntry>,
+        args: &Value,
+    ) -> anyhow::Result<&'a SkillEntry> {
+        let name = args
+            .get("skill")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("missing 'skill'"))?;
+        skills
+            .into_iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| anyhow::anyhow!("no approved skill with files named '{name}'"))
+    }
+
+    fn dir(&self, skill: &SkillEntry) -> anyhow::Result<PathBuf> {
+        let dir = self
+            .registry
+            .skill_dir(skill)
+            .ok_or_else(|| anyhow::anyhow!("skill '{}' has no directory", skill.name))?;
+        Ok(dir.canonicalize()?)
+    }
+
+    /// Resolve `args.<key>` to a file inside the skill's directory.
+    fn file(&self, skill: &SkillEntry, args: &Value, key: &str) -> anyhow::Result<PathBuf> {
+        let relative = args
+            .get(key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("missing '{key}'"))?;
+        if Path::new(relative)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            anyhow::bail!("'{relative}' must be a relative path inside the skill");
+        }
+        let dir = self.dir(skill)?;
+        let path = dir
+            .join(relative)
+            .canonicalize()
+            .map_err(|_| anyhow::anyhow!("skill '{}' has no file '{relative}'", skill.name))?;
+        // Symlinks must not lead out of the skill.
+        if !path.starts_with(&dir) || !path.is_file() {
+            anyhow::bail!("skill '{}' has no file '{relative}'", skill.name);
+        }
+        Ok(path)
+    }
+
+    fn read_file(&self, args: &Value) -> anyhow::Result<String> {
+        let skill = self.find(self.bundled(), args)?;
+        let path = self.file(skill, args, "path")?;
+        let bytes = std::fs::read(&path)?;
+        let text = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_READ_BYTES)]);
+        if bytes.len() > MAX_READ_BYTES {
+            return Ok(format!(
+                "{text}\n[truncated: showing {MAX_READ_BYTES} of {} bytes]",
+                bytes.len()
+            ));
+        }
+        Ok(text.into_owned())
+    }
+
+    async fn run_script(&self, args: &Value) -> anyhow::Result<String> {
+        let skill = self.find(self.bundled(), args)?;
+        let script = self.file(skill, args, "script")?;
+        check_requirements(skill)?;
+
+        let program = match interpreter(&script) {
+            Some(interpreter) => interpreter.to_string(),
+            None if is_executable(&script) => script.to_string_lossy().to_string(),
+            None => anyhow::bail!(
+                "'{}' is not executable and has no known interpreter",
+                script.display()
+            ),
+        };
+        let script_args: Vec<String> = args
+            .get("args")
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .map(|v| {
+                        v.as_str()
+                            .map(String::from)
+                            .unwrap_or_else(|| v.to_string())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let skill_dir = self.dir(skill)?;
+        let mut cmd = SandboxCommand::new(program)
+            .allow_read(&skill_dir)
+            .env("SKILL_DIR", skill_dir.to_string_lossy());
+        if interpreter(&script).is_some() {
+            cmd = cmd.arg(script.to_string_lossy());
+        }
+        for env in skill.metadata.requires_env.iter().flatten() {
+            if let Ok(value) = std::env::var(env) {
+                cmd = cmd.env(env.as_str(), value);
+            }
+        }
+        let output = sandbox::run(cmd.args(script_args)).await?;
+
+        let mut result = match output.status.code() {
+            Some(code) => format!("exit status: {code}\n"),
+            None => "exit status: killed\n".to_string(),
+        };
+        for (label, stream) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+            let text = String::from_utf8_lossy(stream);
+            if !text.trim().is_empty() {
+                result.push_str(&format!("--- {label} ---\n{}\n", text.trim_end()));
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Fail with what is missing when the skill's binaries or variables are not
+/// available.
+fn check_requirements(skill: &SkillEntry) -> anyhow::Result<()> {
+    let missing_bins: Vec<&str> = skill
+        .metadata
+        .requires_bins
+        .iter()
+        .flatten()
+        .filter(|bin| which::which(bin).is_err())
+        .map(String::as_str)
+        .collect();
+    let missing_env: Vec<&str> = skill
+        .metadata
+        .requires_env
+        .iter()
+        .flatten()
+        .filter(|env| std::env::var(env).is_err())
+        .map(String::as_str)
+        .collect();
+    if !missing_bins.is_empty() {
+        anyhow::bail!(
+            "skill '{}' needs binaries that are not installed: {}",
+            skill.name,
+            missing_bins.join(", ")
+        );
+    }
+    if !missing_env.is_empty() {
+        anyhow::bail!(
+            "skill '{}' needs environment variables that are not set: {}",
+            skill.name,
+            missing_env.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn interpreter(script: &Path) -> Option<&'static str> {
+    let ext = script.extension()?.to_str()?;
+    INTERPRETERS
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, interpreter)| *interpreter)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    false
+}
+
+#[async_trait]
+impl ToolProvider for SkillTools {
+    fn id(&self) -> &str {
+        "skills"
+    }
+
+    fn source(&self) -> ToolSource {
+        ToolSource::Skill
+    }
+
+    fn tools(&self) -> Vec<ToolDef> {
+        let skills = self.available();
+        if skills.is_empty() {
+            return Vec::new();
+        }
+        let names: Vec<&str> = skills.iter().map(|s| s.name.as_str()).collect();
+        let mut defs = vec![ToolDef {
+            name: LOAD_SKILL_TOOL.into(),
+            description: "Load the full instructions of a skill by name.".into(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "enum": names, "description": "Skill name" }
+                },
+                "required": ["name"]
+            }),
+        }];
+
+        let bundled: Vec<&str> = self.bundled().iter().map(|s| s.name.as_str()).collect();
+        if bundled.is_empty() {
+            return defs;
+        }
+        defs.push(ToolDef {
+            name: SKILL_READ_FILE_TOOL.into(),
+            description: "Read a template, reference or script shipped with a skill.".into(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "skill": { "type": "string", "enum": bundled, "description": "Skill name" },
+                    "path": { "type": "string", "description": "Path relative to the skill's directory" }
+                },
+                "required": ["skill", "path"]
+            }),
+        });
+        defs.push(ToolDef {
+            name: SKILL_RUN_SCRIPT_TOOL.into(),
+            description: "Run a script shipped with a skill, in the project directory. \
+                          SKILL_DIR is set to the skill's directory."
+                .into(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "skill": { "type": "string", "enum": bundled, "description": "Skill name" },
+                    "script": { "type": "string", "description": "Script path relative to the skill's directory" },
+                    "args": { "type": "array", "items": { "type": "string" }, "description": "Arguments" }
+                },
+                "required": ["skill", "script"]
+            }),
+        });
+        defs
+    }
+
+    fn effect(&self, tool: &str) -> ToolEffect {
+        match tool {
+            SKILL_RUN_SCRIPT_TOOL => ToolEffect::Mutating,
+            _ => ToolEffect::ReadOnly,
+        }
+    }
+
+    async fn call(&self, tool: &str, args: Value) -> anyhow::Result<String> {
+        match tool {
+            LOAD_SKILL_TOOL => {}
+            SKILL_READ_FILE_TOOL => return self.read_file(&args),
+            SKILL_RUN_SCRIPT_TOOL => return self.run_script(&args).await,
+            _ => anyhow::bail!("unknown skill tool '{tool}'"),
+        }
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("missing 'name'"))?;
+        let skill = self
+            .available()
+            .into_iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| anyhow::anyhow!("no approved task skill named '{name}'"))?;
+        let body = self.registry.load_body(skill)?;
+        let files = self.registry.bundle_files(skill);
+        if files.is_empty() {
+            return Ok(format!("# {}\n\n{}", skill.name, body));
+        }
+        Ok(format!(
+            "# {}\n\n{}\n\n{}",
+            skill.name,
+            body,
+            files_note(&files)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skills::types::{SkillMetadata, SkillSource};
+
+    fn skill(name: &str, source: SkillSource, approved: bool) -> SkillEntry {
+        SkillEntry {
+            name: name.into(),
+            kind: SkillKind::Task,
+            description: String::new(),
+            source,
+            path: None,
+            metadata: SkillMetadata::default(),
+            embedding: None,
+            approved,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_skill_only_offers_approved_skills() {
+        let mut registry = SkillRegistry::empty();
+        registry.add(skill("self-iterate", SkillSource::OpenKoiBundled, false));
+        registry.add(skill("proposed", SkillSource::PatternProposed, false));
+        let tools = SkillTools::new(Arc::new(registry));
+
+        let defs = tools.tools();
+        assert_eq!(defs.len(), 1);
+        assert_eq!(
+            defs[0].parameters["properties"]["name"]["enum"],
+            json!(["self-iterate"])
+        );
+
+        let body = tools
+            .call(LOAD_SKILL_TOOL, json!({"name": "self-iterate"}))
+            .await
+            .unwrap();
+        assert!(body.starts_with("# self-iterate"));
+        assert!(tools
+            .call(LOAD_SKILL_TOOL, json!({"name": "proposed"}))
+            .await
+            .is_err());
+    }
+
+    fn bundle(dir: &Path, requires_bins: Option<Vec<String>>) -> SkillEntry {
+        std::fs::create_dir_all(dir.join("scripts")).unwrap();
+        std::fs::create_dir_all(dir.join("templates")).unwrap();
+        std::fs::write(
+            dir.join("SKILL.md"),
+            "---\nname: release\n---\nRun scripts/notes.sh",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("scripts/notes.sh"),
+            "echo \"notes for $1\"\ncat \"$SKILL_DIR/templates/notes.md\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("templates/notes.md"), "## Changes\n").unwrap();
+        let mut skill = skill("release", SkillSource::UserGlobal, false);
+        skill.path = Some(dir.join("SKILL.md"));
+        skill.metadata.requires_bins = requires_bins;
+        skill
+    }
+
+    #[tokio::test]
+    async fn test_bundle_files_are_listed_and_readable() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut registry = SkillRegistry::empty();
+        registry.add(bundle(tmp.path(), None));
+        registry.add(skill("self-iterate", SkillSource::OpenKoiBundled, false));
+        let tools = SkillTools::new(Arc::new(registry));
+
+        let defs = tools.tools();
+        assert_eq!(defs.len(), 3);
+        assert_eq!(
+            defs[1].parameters["properties"]["skill"]["enum"],
+            json!(["release"])
+        );
+        assert_eq!(tools.effect(SKILL_READ_FILE_TOOL), ToolEffect::ReadOnly);
+        assert_eq!(tools.effect(SKILL_RUN_SCRIPT_TOOL), ToolEffect::Mutating);
+
+        let body = tools
+            .call(LOAD_SKILL_TOOL, json!({"name": "release"}))
+            .await
+            .unwrap();
+        assert!(body.contains("- scripts/notes.sh\n- templates/notes.md"));
+
+        let template = tools
+            .call(
+                SKILL_READ_FILE_TOOL,
+                json!({"skill": "release", "path": "templates/notes.md"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(template, "## Changes\n");
+        for path in ["../SKILL.md", "/etc/passwd", "missing.md", "templates"] {
+            assert!(tools
+                .call(
+                    SKILL_READ_FILE_TOOL,
+                    json!({"skill": "release", "path": path})
+                )
+                .await
+                .is_err());
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_script_checks_requirements() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut registry = SkillRegistry::empty();
+        registry.add(bundle(tmp.path(), Some(vec!["sh".into()])));
+        let tools = SkillTools::new(Arc::new(registry));
+        let output = tools
+            .call(
+                SKILL_RUN_SCRIPT_TOOL,
+                json!({"skill": "release", "script": "scripts/notes.sh", "args": ["v2"]}),
+            )
+            .await
+            .unwrap();
+        assert!(output.starts_with("exit status: 0"));
+        assert!(output.contains("notes for v2"));
+        assert!(output.contains("## Changes"));
+
+        let missing = tempfile::tempdir().unwrap();
+        let mut registry = SkillRegistry::empty();
+        registry.add(bundle(
+            missing.path(),
+            Some(vec!["openkoi-no-such-binary".into()]),
+        ));
+        let tools = SkillTools::new(Arc::new(registry));
+        let err = tools
+            .call(
+                SKILL_RUN_SCRIPT_TOOL,
+                json!({"skill": "release", "script": "scripts/notes.sh"}),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("openkoi-no-such-binary"));
+    }
+
+    #[test]
+    fn test_no_tool_without_skills() {
+        let tools = SkillTools::new(Arc::new(SkillRegistry::empty()));
+        assert!(tools.tools().is_empty());
+    }
+}
@@ -0,0 +1,603 @@
+// src/tools/overlay.rs — In-memory file overlay for dry runs and patch output
+//
+// When an overlay is installed on the `ToolRegistry`, calls to file-writing
+// tools are applied here instead of reaching the tool, reads of overlaid
+// files are answered from here, and any other mutating tool is refused
+// (its effects couldn't be captured). The working tree is never written.
+// At the end the overlay renders a `git apply`-able unified diff.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+use similar::TextDiff;
+
+use super::{ToolEffect, NAMESPACE_SEPARATOR};
+
+/// Names of file-writing tools. Matched exactly: a fragment such as
+/// `write` would also catch tools like `slack_write_message`.
+const FILE_WRITE_TOOLS: &[&str] = &[
+    "write_file",
+    "write_text_file",
+    "create_file",
+    "edit_file",
+    "patch_file",
+    "str_replace_editor",
+    "str_replace_based_edit_tool",
+    "str_replace",
+    "write",
+    "save_file",
+    "append_file",
+    "insert_text",
+];
+
+/// Tools whose output is a file's contents.
+const FILE_READ_TOOLS: &[&str] = &[
+    "read_file",
+    "read_text_file",
+    "view_file",
+    "get_file_contents",
+];
+
+/// Argument names that carry the target file.
+const PATH_ARGS: &[&str] = &["path", "file_path", "file", "filename", "target_file"];
+
+/// Argument names that carry a whole file's new content.
+const CONTENT_ARGS: &[&str] = &["content", "contents", "text", "file_text", "data"];
+
+/// Directories left out of a `Workspace` copy: version control and build
+/// output that would be regenerated anyway.
+const WORKSPACE_SKIP: &[&str] = &[".git", "target"];
+
+/// Dependency directories linked (not copied) into a `Workspace`.
+const WORKSPACE_LINK: &[&str] = &["node_modules", ".venv", "venv"];
+
+/// Bare, lower-cased tool name (MCP and collision prefixes stripped).
+fn bare_name(tool: &str) -> String {
+    tool.rsplit(NAMESPACE_SEPARATOR)
+        .next()
+        .unwrap_or(tool)
+        .to_lowercase()
+}
+
+/// Whether `tool` is a known file-writing tool.
+pub fn is_file_write_tool(tool: &str) -> bool {
+    FILE_WRITE_TOOLS.contains(&bare_name(tool).as_str())
+}
+
+/// The target file of a tool call, from the usual argument names.
+pub fn file_path_arg(args: &Value) -> Option<&str> {
+    PATH_ARGS
+        .iter()
+        .find_map(|key| args.get(*key).and_then(|v| v.as_str()))
+}
+
+fn string_arg<'a>(args: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|key| args.get(*key).and_then(|v| v.as_str()))
+}
+
+/// Files as changed by the task, keyed by project-relative path.
+pub type OverlayFiles = BTreeMap<PathBuf, String>;
+
+/// Captured file writes on top of the project at `root`.
+pub struct Overlay {
+    root: PathBuf,
+    files: Mutex<OverlayFiles>,
+}
+
+impl Overlay {
+    pub fn new(root: &Path) -> Result<Self> {
+        Ok(Self {
+            root: root.canonicalize()?,
+            files: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, OverlayFiles> {
+        self.files.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Copy of the current overlay, for `restore`.
+    pub fn snapshot(&self) -> OverlayFiles {
+        self.lock().clone()
+    }
+
+    pub fn restore(&self, files: OverlayFiles) {
+        *self.lock() = files;
+    }
+
+    /// Project-relative paths of changed files.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.lock().keys().cloned().collect()
+    }
+
+    /// Project-relative form of a tool's path argument. Paths that leave
+    /// the project are refused.
+    pub fn relative(&self, raw: &str) -> Result<PathBuf> {
+        let path = Path::new(raw);
+        let path = if path.is_absolute() {
+            path.strip_prefix(&self.root)
+                .map_err(|_| anyhow!("{raw} is outside the project"))?
+        } else {
+            path
+        };
+        let mut out = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(part) => out.push(part),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if !out.pop() {
+                        bail!("{raw} is outside the project");
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => {
+                    bail!("{raw} is outside the project")
+                }
+            }
+        }
+        if out.as_os_str().is_empty() {
+            bail!("'{raw}' is not a file path");
+        }
+        Ok(out)
+    }
+
+    /// Current text of `rel`: the overlay's version, else the file on disk.
+    fn current(&self, rel: &Path) -> Result<Option<String>> {
+        if let Some(text) = self.lock().get(rel) {
+            return Ok(Some(text.clone()));
+        }
+        match std::fs::read_to_string(self.root.join(rel)) {
+            Ok(text) => Ok(Some(text)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(anyhow!("{}: {e}", rel.display())),
+        }
+    }
+
+    fn put(&self, rel: PathBuf, text: String) -> String {
+        let lines = text.lines().count();
+        let message = format!(
+            "Wrote {} ({lines} lines). Dry run: the change is recorded, not written to disk.",
+            rel.display()
+        );
+        self.lock().insert(rel, text);
+        message
+    }
+
+    /// Handle a tool call against the overlay. `None` means the call should
+    /// go to the tool as usual (reads of files the overlay hasn't touched,
+    /// and read-only tools).
+    pub fn intercept(
+        &self,
+        tool: &str,
+        effect: ToolEffect,
+        args: &Value,
+    ) -> Option<Result<String>> {
+        let name = bare_name(tool);
+        if FILE_READ_TOOLS.contains(&name.as_str()) {
+            let rel = self.relative(file_path_arg(args)?).ok()?;
+            return self.lock().get(&rel).cloned().map(Ok);
+        }
+        if is_file_write_tool(tool) {
+            return Some(self.apply_write(&name, args));
+        }
+        (effect == ToolEffect::Mutating).then(|| {
+            Err(anyhow!(
+                "unavailable during a dry run: its changes can't be captured. \
+                 Use a file-writing tool instead"
+            ))
+        })
+    }
+
+    fn apply_write(&self, name: &str, args: &Value) -> Result<String> {
+        let raw = file_path_arg(args).ok_or_else(|| anyhow!("missing file path"))?;
+        let rel = self.relative(raw)?;
+
+        if name.contains("str_replace_editor") || args.get("command").is_some() {
+            return match args.get("command").and_then(|v| v.as_str()) {
+                Some("view") => self
+                    .current(&rel)?
+                    .ok_or_else(|| anyhow!("{raw} does not exist")),
+                Some("create") => {
+                    let text = string_arg(args, CONTENT_ARGS).unwrap_or_default();
+                    Ok(self.put(rel, text.to_string()))
+                }
+                Some("str_replace") => self.replace(rel, args),
+                Some("insert") => self.insert(rel, args),
+                Some(other) => bail!("command '{other}' isn't supported during a dry run"),
+                None => bail!("missing 'command'"),
+            };
+        }
+        if name.contains("append") {
+            let extra = string_arg(args, CONTENT_ARGS).ok_or_else(|| anyhow!("missing content"))?;
+            let text = self.current(&rel)?.unwrap_or_default() + extra;
+            return Ok(self.put(rel, text));
+        }
+        if name.contains("insert") {
+            return self.insert(rel, args);
+        }
+        if name.contains("edit_file") || name.contains("str_replace") {
+            return self.replace(rel, args);
+        }
+        if name.contains("patch") {
+            bail!("patch-style edits aren't supported during a dry run; write the whole file");
+        }
+        let text = string_arg(args, CONTENT_ARGS).ok_or_else(|| anyhow!("missing content"))?;
+        Ok(self.put(rel, text.to_string()))
+    }
+
+    /// Search-and-replace edits: an `edits` array of `{oldText, newText}`
+    /// (MCP filesystem server) or a single `old_str`/`new_str` pair.
+    fn replace(&self, rel: PathBuf, args: &Value) -> Result<String> {
+        const OLD: &[&str] = &["oldText", "old_text", "old_str", "old_string"];
+        const NEW: &[&str] = &["newText", "new_text", "new_str", "new_string"];
+        let mut text = self
+            .current(&rel)?
+            .ok_or_else(|| anyhow!("{} does not exist", rel.display()))?;
+        let replace_all = args
+            .get("replace_all")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let single = [args.clone()];
+        let edits = match args.get("edits").and_then(|v| v.as_array()) {
+            Some(edits) => edits.as_slice(),
+            None => &single,
+        };
+        for edit in edits {
+            let old = string_arg(edit, OLD).ok_or_else(|| anyhow!("edit without old text"))?;
+            let new = string_arg(edit, NEW).unwrap_or_default();
+            match text.matches(old).count() {
+                0 => bail!("old text not found in {}", rel.display()),
+                1 => text = text.replacen(old, new, 1),
+                n if replace_all => {
+                    tracing::debug!("Replacing {} occurrences in {}", n, rel.display());
+                    text = text.replace(old, new);
+                }
+                n => bail!(
+                    "old text appears {n} times in {}; include more context",
+                    rel.display()
+                ),
+            }
+        }
+        Ok(self.put(rel, text))
+    }
+
+    /// Insert text after a 1-based line (0 = at the top).
+    fn insert(&self, rel: PathBuf, args: &Value) -> Result<String> {
+        let line = args
+            .get("insert_line")
+            .or_else(|| args.get("line"))
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| anyhow!("missing 'insert_line'"))? as usize;
+        let new = string_arg(args, &["new_str", "text", "content"])
+            .ok_or_else(|| anyhow!("missing text to insert"))?;
+        let text = self
+            .current(&rel)?
+            .ok_or_else(|| anyhow!("{} does not exist", rel.display()))?;
+
+        let mut lines: Vec<String> = text.split_inclusive('\n').map(String::from).collect();
+        if line > lines.len() {
+            bail!("{} has only {} lines", rel.display(), lines.len());
+        }
+        let mut inserted = new.to_string();
+        if !inserted.ends_with('\n') {
+            inserted.push('\n');
+        }
+        // Inserting after a last line without a newline would join them.
+        if let Some(previous) = line.checked_sub(1).and_then(|i| lines.get_mut(i)) {
+            if !previous.ends_with('\n') {
+                previous.push('\n');
+            }
+        }
+        lines.insert(line, inserted);
+        let out = lines.concat();
+        Ok(self.put(rel, out))
+    }
+
+    /// Unified diff of every changed file against the working tree, with
+    /// `a/`/`b/` prefixes so `git apply` accepts it. Empty when nothing
+    /// changed.
+    pub fn diff(&self) -> Result<String> {
+        let mut out = String::new();
+        for (rel, new) in self.lock().iter() {
+            let original = match std::fs::read_to_string(self.root.join(rel)) {
+                Ok(text) => Some(text),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => bail!("{}: {e}", rel.display()),
+            };
+            if original.as_deref() == Some(new.as_str()) {
+                continue;
+            }
+            let path = rel.to_string_lossy().replace('\\', "/");
+            out.push_str(&format!("diff --git a/{path} b/{path}\n"));
+            let old_header = if original.is_some() {
+                format!("a/{path}")
+            } else {
+                out.push_str("new file mode 100644\n");
+                "/dev/null".to_string()
+            };
+            let old = original.unwrap_or_default();
+            let diff = TextDiff::from_lines(old.as_str(), new.as_str());
+            out.push_str(
+                &diff
+                    .unified_diff()
+                    .context_radius(3)
+                    .header(&old_header, &format!("b/{path}"))
+                    .to_string(),
+            );
+        }
+        Ok(out)
+    }
+
+    /// Write the overlaid files into `dir` (a `Workspace`).
+    pub fn sync_to(&self, dir: &Path) -> Result<()> {
+        for (rel, text) in self.lock().iter() {
+            let target = dir.join(rel);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(target, text)?;
+        }
+        Ok(())
+    }
+}
+
+/// A scratch copy of the project for tests and linters to run in while the
+/// working tree stays untouched. Removed on drop.
+pub struct Workspace {
+    path: PathBuf,
+}
+
+impl Workspace {
+    /// Copy `root` into a new temporary directory.
+    pub fn create(root: &Path) -> Result<Self> {
+        let workspace = Self::empty()?;
+        copy_tree(&root.canonicalize()?, &workspace.path)?;
+        Ok(workspace)
+    }
+
+    /// A new, empty temporary directory.
+    pub fn empty() -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("openkoi-dry-run-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+
+    /// Copy a file or directory into the workspace at `rel`.
+    pub fn add(&self, source: &Path, rel: &Path) -> Result<()> {
+        let target = self.path.join(rel);
+        if source.is_dir() {
+            std::fs::create_dir_all(&target)?;
+            return copy_tree(source, &target);
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(source, target)?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+fn copy_tree(from: &Path, to: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name_str = name.to_string_lossy();
+        let source = entry.path();
+        let target = to.join(&name);
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            if WORKSPACE_SKIP.contains(&name_str.as_ref()) {
+                continue;
+            }
+            if WORKSPACE_LINK.contains(&name_str.as_ref()) {
+                link(&source, &target)?;
+                continue;
+            }
+            std::fs::create_dir_all(&target)?;
+            copy_tree(&source, &target)?;
+        } else if file_type.is_symlink() {
+            link(&std::fs::read_link(&source)?, &target)?;
+        } else {
+            std::fs::copy(&source, &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn link(source: &Path, target: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(source, target)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn link(_source: &Path, _target: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn project() -> (tempfile::TempDir, Overlay) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        let overlay = Overlay::new(dir.path()).unwrap();
+        (dir, overlay)
+    }
+
+    #[test]
+    fn test_writes_are_captured_not_written() {
+        let (dir, overlay) = project();
+        let write = json!({"path": "src/new.rs", "content": "pub fn c() {}\n"});
+        assert!(overlay
+            .intercept("fs__write_file", ToolEffect::Mutating, &write)
+            .unwrap()
+            .is_ok());
+        let edit = json!({"path": "./src/lib.rs", "edits": [{"oldText": "fn b()", "newText": "fn bee()"}]});
+        assert!(overlay
+            .intercept("edit_file", ToolEffect::Mutating, &edit)
+            .unwrap()
+            .is_ok());
+        let insert = json!({"command": "insert", "path": "src/lib.rs", "insert_line": 0, "new_str": "// top"});
+        assert!(overlay
+            .intercept("str_replace_editor", ToolEffect::Mutating, &insert)
+            .unwrap()
+            .is_ok());
+
+        assert!(!dir.path().join("src/new.rs").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/lib.rs")).unwrap(),
+            "fn a() {}\nfn b() {}\n"
+        );
+        // Reads see the overlay; untouched files fall through to the tool.
+        let read = overlay
+            .intercept(
+                "read_file",
+                ToolEffect::ReadOnly,
+                &json!({"path": "src/lib.rs"}),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(read, "// top\nfn a() {}\nfn bee() {}\n");
+        assert!(overlay
+            .intercept(
+                "read_file",
+                ToolEffect::ReadOnly,
+                &json!({"path": "Cargo.toml"})
+            )
+            .is_none());
+        assert!(overlay
+            .intercept("search", ToolEffect::ReadOnly, &json!({}))
+            .is_none());
+
+        // Mutating tools the overlay can't model are refused.
+        assert!(overlay
+            .intercept("run_command", ToolEffect::Mutating, &json!({}))
+            .unwrap()
+            .is_err());
+        assert!(overlay
+            .intercept(
+                "write_file",
+                ToolEffect::Mutating,
+                &json!({"path": "../x", "content": ""})
+            )
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn test_file_write_tools_match_exactly() {
+        assert!(is_file_write_tool("write_file"));
+        assert!(is_file_write_tool("filesystem__Write"));
+        assert!(is_file_write_tool("str_replace_editor"));
+        assert!(!is_file_write_tool("slack_write_message"));
+        assert!(!is_file_write_tool("rewrite_summary"));
+        assert!(!is_file_write_tool("write_file_later"));
+    }
+
+    #[test]
+    fn test_diff_is_git_applicable() {
+        let (dir, overlay) = project();
+        overlay
+            .intercept(
+                "str_replace",
+                ToolEffect::Mutating,
+                &json!({"path": "src/lib.rs", "old_str": "fn a() {}", "new_str": "fn a() -> u8 { 1 }"}),
+            )
+            .unwrap()
+            .unwrap();
+        overlay
+            .intercept(
+                "create_file",
+                ToolEffect::Mutating,
+                &json!({"file_path": "README.md", "text": "hi\n"}),
+            )
+            .unwrap()
+            .unwrap();
+
+        let diff = overlay.diff().unwrap();
+        assert_eq!(
+            diff,
+            "diff --git a/README.md b/README.md\n\
+             new file mode 100644\n\
+             --- /dev/null\n\
+             +++ b/README.md\n\
+             @@ -0,0 +1 @@\n\
+             +hi\n\
+             diff --git a/src/lib.rs b/src/lib.rs\n\
+             --- a/src/lib.rs\n\
+             +++ b/src/lib.rs\n\
+             @@ -1,2 +1,2 @@\n\
+             -fn a() {}\n\
+             +fn a() -> u8 { 1 }\n \
+             fn b() {}\n"
+        );
+
+        // Restoring an earlier snapshot drops later changes.
+        let before = Overlay::new(dir.path()).unwrap().snapshot();
+        overlay.restore(before);
+        assert_eq!(overlay.diff().unwrap(), "");
+    }
+
+    #[test]
+    fn test_workspace_copy_and_sync() {
+        let (dir, overlay) = project();
+        std::fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        std::fs::write(dir.path().join("target/debug/big"), "x").unwrap();
+        overlay
+            .intercept(
+                "write_file",
+                ToolEffect::Mutating,
+                &json!({"path": "src/lib.rs", "content": "changed\n"}),
+            )
+            .unwrap()
+            .unwrap();
+
+        let workspace = Workspace::create(dir.path()).unwrap();
+        assert!(!workspace.path().join("target").exists());
+        overlay.sync_to(workspace.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(workspace.path().join("src/lib.rs")).unwrap(),
+            "changed\n"
+        );
+        let path = workspace.path().to_path_buf();
+        drop(workspace);
+        assert!(!path.exists());
+
+        let workspace = Workspace::empty().unwrap();
+        workspace
+            .add(&dir.path().join("src"), Path::new(".agents/skills/src"))
+            .unwrap();
+        workspace
+            .add(
+                &dir.path().join("src/lib.rs"),
+                Path::new(".openkoi/SOUL.md"),
+            )
+            .unwrap();
+        assert!(workspace.path().join(".agents/skills/src/lib.rs").is_file());
+        assert!(workspace.path().join(".openkoi/SOUL.md").is_file());
+    }
+}
@@ -0,0 +1,570 @@
+// src/tools/policy.rs — Allow/ask/deny rules for tool calls
+//
+// Every call through the `ToolRegistry` is checked against the permission
+// rules before it reaches a provider. Rules come from `[permissions]` in
+// config.toml and from the project (`.openkoi/permissions.toml`); within
+// each the first match wins, and the stricter of the two decisions applies,
+// so a repository can tighten the user's policy but never loosen it. "ask"
+// goes to an `Approver` (a terminal prompt in interactive runs, the editor
+// over ACP, API clients in the daemon); denied or unapproved calls return a
+// structured error to the model instead of running.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::ToolSource;
+use crate::infra::config::{PermissionAction, PermissionRule, PermissionsConfig};
+
+/// Project-level rules, relative to the working directory.
+pub const PROJECT_PERMISSIONS_FILE: &str = ".openkoi/permissions.toml";
+
+/// A tool call as the policy sees it.
+#[derive(Debug, Clone, Copy)]
+pub struct ToolCallInfo<'a> {
+    /// Name the model used (after collision qualification).
+    pub tool: &'a str,
+    pub source: ToolSource,
+    /// Id of the provider serving the tool (integration, MCP server, plugin).
+    pub provider: &'a str,
+    pub args: &'a Value,
+    /// Id the registry reports the call under (`ProgressEvent::ToolStarted`).
+    pub call_id: Option<&'a str>,
+}
+
+/// Where a rule was defined, for error messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleOrigin {
+    Project,
+    Config,
+}
+
+/// The outcome of matching a call against the rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub action: PermissionAction,
+    pub reason: Option<String>,
+    /// The matching rule ("project rule 1"), or `None` for the default.
+    pub rule: Option<String>,
+}
+
+/// A call waiting for a person's approval.
+#[derive(Debug, Clone)]
+pub struct ApprovalRequest {
+    pub tool: String,
+    pub provider: String,
+    pub source: ToolSource,
+    pub args: Value,
+    pub reason: Option<String>,
+    /// Id of the call as reported to progress sinks, when dispatched by the
+    /// registry.
+    pub call_id: Option<String>,
+}
+
+/// The approver's answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Approval {
+    Approved,
+    Rejected,
+    /// Nobody can be asked (non-interactive run, daemon without the API).
+    Unavailable,
+    /// Nobody answered in time.
+    TimedOut,
+}
+
+/// Resolves "ask" decisions.
+#[async_trait]
+pub trait Approver: Send + Sync {
+    async fn approve(&self, request: &ApprovalRequest) -> Approval;
+}
+
+/// Approver for non-interactive runs: every request is unanswerable.
+pub struct NoApprover;
+
+#[async_trait]
+impl Approver for NoApprover {
+    async fn approve(&self, _request: &ApprovalRequest) -> Approval {
+        Approval::Unavailable
+    }
+}
+
+/// Prompts on the terminal. Prompts are serialized so concurrent calls ask
+/// one at a time; "always" approves a tool for the rest of the session.
+#[derive(Default)]
+pub struct TerminalApprover {
+    always: tokio::sync::Mutex<HashSet<String>>,
+}
+
+impl TerminalApprover {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Approver for TerminalApprover {
+    async fn approve(&self, request: &ApprovalRequest) -> Approval {
+        use std::io::IsTerminal;
+        if !std::io::stdin().is_terminal() {
+            return Approval::Unavailable;
+        }
+
+        let mut always = self.always.lock().await;
+        if always.contains(&request.tool) {
+            return Approval::Approved;
+        }
+
+        let args = serde_json::to_string_pretty(&request.args).unwrap_or_default();
+        let mut message = format!(
+            "Allow {} tool '{}' ({})?",
+            request.source, request.tool, request.provider
+        );
+        if let Some(reason) = &request.reason {
+            message.push_str(&format!(" [{}]", reason));
+        }
+        let answer = tokio::task::spawn_blocking(move || {
+            eprintln!();
+            eprintln!("  Arguments: {}", args);
+            let options = vec!["Yes", "No", "Always for this tool"];
+            inquire::Select::new(&message, options).prompt().ok()
+        })
+        .await
+        .ok()
+        .flatten();
+
+        match answer {
+            Some("Yes") => Approval::Approved,
+            Some("Always for this tool") => {
+                always.insert(request.tool.clone());
+                Approval::Approved
+            }
+            _ => Approval::Rejected,
+        }
+    }
+}
+
+/// Permission rules plus the approver for "ask".
+pub struct ToolPolicy {
+    rules: Vec<(RuleOrigin, PermissionRule)>,
+    default: PermissionAction,
+    /// The project file's `default`, if it set one.
+    project_default: Option<PermissionAction>,
+    approver: Arc<dyn Approver>,
+}
+
+impl Default for ToolPolicy {
+    /// Allow everything (no rules configured).
+    fn default() -> Self {
+        Self::new(&PermissionsConfig::default())
+    }
+}
+
+impl ToolPolicy {
+    /// Rules from config only.
+    pub fn new(config: &PermissionsConfig) -> Self {
+        let rules = config
+            .rules
+            .iter()
+            .map(|r| (RuleOrigin::Config, r.clone()))
+            .collect();
+        warn_invalid_globs(&config.rules);
+        Self {
+            rules,
+            default: config.default,
+            project_default: None,
+            approver: Arc::new(NoApprover),
+        }
+    }
+
+    /// Config rules plus `project_dir/.openkoi/permissions.toml` (same shape
+    /// as the `[permissions]` section). The project file is part of the
+    /// repository, so its rules and `default` only count where they are
+    /// stricter than the config's decision.
+    pub fn load(config: &PermissionsConfig, project_dir: &Path) -> anyhow::Result<Self> {
+        let mut policy = Self::new(config);
+        let path = project_dir.join(PROJECT_PERMISSIONS_FILE);
+        if !path.exists() {
+            return Ok(policy);
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let table: toml::Table = toml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?;
+        let has_default = table.contains_key("default");
+        let project: PermissionsConfig = table
+            .try_into()
+            .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?;
+
+        warn_invalid_globs(&project.rules);
+        policy
+            .rules
+            .extend(project.rules.into_iter().map(|r| (RuleOrigin::Project, r)));
+        if has_default {
+            policy.project_default = Some(project.default);
+        }
+        tracing::debug!(
+            "Loaded {} tool permission rule(s) from {}",
+            policy.rules.len(),
+            path.display()
+        );
+        Ok(policy)
+    }
+
+    pub fn with_approver(mut self, approver: Arc<dyn Approver>) -> Self {
+        self.approver = approver;
+        self
+    }
+
+    /// Match `call` against the rules: the stricter of the config and
+    /// project decisions, preferring the config's on a tie.
+    pub fn decide(&self, call: &ToolCallInfo) -> Decision {
+        let config = self
+            .first_match(RuleOrigin::Config, call)
+            .unwrap_or(Decision {
+                action: self.default,
+                reason: None,
+                rule: None,
+            });
+        let project = self.first_match(RuleOrigin::Project, call).or_else(|| {
+            self.project_default.map(|action| Decision {
+                action,
+                reason: None,
+                rule: None,
+            })
+        });
+        match project {
+            Some(project) if project.action > config.action => project,
+            _ => config,
+        }
+    }
+
+    fn first_match(&self, origin: RuleOrigin, call: &ToolCallInfo) -> Option<Decision> {
+        let label = match origin {
+            RuleOrigin::Project => "project",
+            RuleOrigin::Config => "config",
+        };
+        self.rules
+            .iter()
+            .filter(|(o, _)| *o == origin)
+            .enumerate()
+            .find(|(_, (_, rule))| rule_matches(rule, call))
+            .map(|(i, (_, rule))| Decision {
+                action: rule.action,
+                reason: rule.reason.clone(),
+                rule: Some(format!("{} rule {}", label, i + 1)),
+            })
+    }
+
+    /// Decide and, for "ask", consult the approver. `Err` carries the
+    /// structured error to return to the model.
+    pub async fn authorize(&self, call: &ToolCallInfo<'_>) -> Result<(), String> {
+        let decision = self.decide(call);
+        self.enforce(call, &decision).await
+    }
+
+    /// Apply an earlier [`decide`](Self::decide) result to `call`.
+    pub async fn enforce(
+        &self,
+        call: &ToolCallInfo<'_>,
+        decision: &Decision,
+    ) -> Result<(), String> {
+        match decision.action {
+            PermissionAction::Allow => Ok(()),
+            PermissionAction::Deny => {
+                tracing::info!(tool = call.tool, rule = ?decision.rule, "Tool call denied by policy");
+                Err(permission_error("permission_denied", call, decision))
+            }
+            PermissionAction::Ask => {
+                let request = ApprovalRequest {
+                    tool: call.tool.to_string(),
+                    provider: call.provider.to_string(),
+                    source: call.source,
+                    args: call.args.clone(),
+                    reason: decision.reason.clone(),
+                    call_id: call.call_id.map(String::from),
+                };
+                match self.approver.approve(&request).await {
+                    Approval::Approved => Ok(()),
+                    Approval::Rejected => {
+                        tracing::info!(tool = call.tool, "Tool call rejected by user");
+                        Err(permission_error("approval_rejected", call, decision))
+                    }
+                    Approval::Unavailable => {
+                        tracing::info!(
+                            tool = call.tool,
+                            "Tool call needs approval; none available"
+                        );
+                        Err(permission_error("approval_required", call, decision))
+                    }
+                    Approval::TimedOut => {
+                        tracing::info!(tool = call.tool, "Tool call approval timed out");
+                        Err(permission_error("approval_timeout", call, decision))
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn warn_invalid_globs(rules: &[PermissionRule]) {
+    for rule in rules {
+        let patterns = std::iter::once(&rule.tool)
+            .chain(rule.provider.as_ref())
+            .chain(rule.args.values());
+        for pattern in patterns {
+            if glob::Pattern::new(pattern).is_err() {
+                tracing::warn!(
+                    "Permission rule pattern '{}' is not a valid glob; matching it literally",
+                    pattern
+                );
+            }
+        }
+    }
+}
+
+fn rule_matches(rule: &PermissionRule, call: &ToolCallInfo) -> bool {
+    glob_matches(&rule.tool, call.tool)
+        && rule
+            .source
+            .as_deref()
+            .is_none_or(|s| s.eq_ignore_ascii_case(&call.source.to_string()))
+        && rule
+            .provider
+            .as_deref()
+            .is_none_or(|p| glob_matches(p, call.provider))
+        && rule.args.iter().all(|(key, pattern)| {
+            call.args
+                .get(key)
+                .is_some_and(|v| glob_matches(pattern, &value_text(v)))
+        })
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    match glob::Pattern::new(pattern) {
+        Ok(p) => p.matches(text),
+        Err(_) => pattern == text,
+    }
+}
+
+/// Strings match as-is; other JSON values by their JSON text.
+fn value_text(value: &Value) -> String {
+    value
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn permission_error(kind: &str, call: &ToolCallInfo, decision: &Decision) -> String {
+    let message = match kind {
+        "permission_denied" => "This tool call is not permitted by the permission policy.",
+        "approval_rejected" => "The user declined this tool call.",
+        "approval_timeout" => "This tool call requires approval and nobody answered in time.",
+        _ => "This tool call requires approval and no one is available to approve it.",
+    };
+    let error = json!({
+        "error": kind,
+        "tool": call.tool,
+        "message": message,
+        "reason": decision.reason,
+        "rule": decision.rule,
+    });
+    format!("Error: {}", error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn rule(tool: &str, action: PermissionAction) -> PermissionRule {
+        PermissionRule {
+            tool: tool.into(),
+            source: None,
+            provider: None,
+            args: HashMap::new(),
+            action,
+            reason: None,
+        }
+    }
+
+    fn call<'a>(tool: &'a str, args: &'a Value) -> ToolCallInfo<'a> {
+        ToolCallInfo {
+            tool,
+            source: ToolSource::Integration,
+            provider: "slack",
+            args,
+            call_id: None,
+        }
+    }
+
+    struct Answer(Approval);
+
+    #[async_trait]
+    impl Approver for Answer {
+        async fn approve(&self, _request: &ApprovalRequest) -> Approval {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let mut general = rule("slack_send", PermissionAction::Deny);
+        general.args.insert("channel".into(), "#general".into());
+        let config = PermissionsConfig {
+            default: PermissionAction::Ask,
+            rules: vec![general, rule("slack_*", PermissionAction::Allow)],
+        };
+        let policy = ToolPolicy::new(&config);
+
+        let args = json!({"channel": "#general", "text": "hi"});
+        let d = policy.decide(&call("slack_send", &args));
+        assert_eq!(d.action, PermissionAction::Deny);
+        assert_eq!(d.rule.as_deref(), Some("config rule 1"));
+
+        let args = json!({"channel": "#random"});
+        assert_eq!(
+            policy.decide(&call("slack_send", &args)).action,
+            PermissionAction::Allow
+        );
+        assert_eq!(
+            policy.decide(&call("notion_write_doc", &args)).action,
+            PermissionAction::Ask
+        );
+    }
+
+    #[test]
+    fn test_source_and_provider_match() {
+        let mut by_source = rule("*", PermissionAction::Deny);
+        by_source.source = Some("mcp".into());
+        let mut by_provider = rule("*", PermissionAction::Ask);
+        by_provider.provider = Some("sla*".into());
+        let policy = ToolPolicy::new(&PermissionsConfig {
+            default: PermissionAction::Allow,
+            rules: vec![by_source, by_provider],
+        });
+        let args = json!({});
+        assert_eq!(
+            policy.decide(&call("slack_read", &args)).action,
+            PermissionAction::Ask
+        );
+        let mcp = ToolCallInfo {
+            source: ToolSource::Mcp,
+            provider: "fs",
+            ..call("fs__write", &args)
+        };
+        assert_eq!(policy.decide(&mcp).action, PermissionAction::Deny);
+    }
+
+    #[test]
+    fn test_project_rules_tighten_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join(".openkoi")).unwrap();
+        std::fs::write(
+            tmp.path().join(PROJECT_PERMISSIONS_FILE),
+            r#"
+default = "ask"
+
+[[rules]]
+tool = "*_write_doc"
+args = { doc_id = "handbook-*" }
+action = "deny"
+reason = "Handbook is read-only"
+"#,
+        )
+        .unwrap();
+        let config = PermissionsConfig {
+            default: PermissionAction::Allow,
+            rules: vec![rule("notion_*", PermissionAction::Allow)],
+        };
+        let policy = ToolPolicy::load(&config, tmp.path()).unwrap();
+
+        let args = json!({"doc_id": "handbook-42"});
+        let d = policy.decide(&call("notion_write_doc", &args));
+        assert_eq!(d.action, PermissionAction::Deny);
+        assert_eq!(d.rule.as_deref(), Some("project rule 1"));
+        assert_eq!(d.reason.as_deref(), Some("Handbook is read-only"));
+
+        // The project's stricter default applies even where a config rule
+        // allows the call.
+        let args = json!({"doc_id": "notes"});
+        assert_eq!(
+            policy.decide(&call("notion_write_doc", &args)).action,
+            PermissionAction::Ask
+        );
+        assert_eq!(
+            policy.decide(&call("email_send", &args)).action,
+            PermissionAction::Ask
+        );
+    }
+
+    #[test]
+    fn test_project_rules_cannot_loosen_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join(".openkoi")).unwrap();
+        std::fs::write(
+            tmp.path().join(PROJECT_PERMISSIONS_FILE),
+            r#"
+default = "allow"
+
+[[rules]]
+tool = "slack_send"
+action = "allow"
+"#,
+        )
+        .unwrap();
+        let config = PermissionsConfig {
+            default: PermissionAction::Ask,
+            rules: vec![rule("slack_send", PermissionAction::Deny)],
+        };
+        let policy = ToolPolicy::load(&config, tmp.path()).unwrap();
+
+        let args = json!({"channel": "#general"});
+        let d = policy.decide(&call("slack_send", &args));
+        assert_eq!(d.action, PermissionAction::Deny);
+        assert_eq!(d.rule.as_deref(), Some("config rule 1"));
+        // A project default cannot loosen the config default either.
+        assert_eq!(
+            policy.decide(&call("notion_write_doc", &args)).action,
+            PermissionAction::Ask
+        );
+    }
+
+    #[tokio::test]
+    async fn test_authorize_routes_ask_to_approver() {
+        let config = PermissionsConfig {
+            default: PermissionAction::Ask,
+            rules: vec![],
+        };
+        let args = json!({});
+        let approved = ToolPolicy::new(&config).with_approver(Arc::new(Answer(Approval::Approved)));
+        assert!(approved.authorize(&call("x", &args)).await.is_ok());
+
+        let rejected = ToolPolicy::new(&config).with_approver(Arc::new(Answer(Approval::Rejected)));
+        let err = rejected.authorize(&call("x", &args)).await.unwrap_err();
+        assert!(err.contains("\"approval_rejected\""));
+
+        let err = ToolPolicy::new(&config)
+            .authorize(&call("x", &args))
+            .await
+            .unwrap_err();
+        assert!(err.contains("\"approval_required\""));
+    }
+
+    #[test]
+    fn test_non_string_args_match_as_json() {
+        let mut r = rule("*", PermissionAction::Deny);
+        r.args.insert("count".into(), "1?".into());
+        let policy = ToolPolicy::new(&PermissionsConfig {
+            default: PermissionAction::Allow,
+            rules: vec![r],
+        });
+        let args = json!({"count": 15});
+        assert_eq!(
+            policy.decide(&call("t", &args)).action,
+            PermissionAction::Deny
+        );
+    }
+}
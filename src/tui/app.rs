@@ -33,16 +33,18 @@ enum Tab {
     Costs,
     Plugins,
     Config,
+    Bench,
 }
 
 impl Tab {
-    const ALL: [Tab; 6] = [
+    const ALL: [Tab; 7] = [
         Tab::Overview,
         Tab::Tasks,
         Tab::Learnings,
         Tab::Costs,
         Tab::Plugins,
         Tab::Config,
+        Tab::Bench,
     ];
 
     fn label(&self) -> &'static str {
@@ -53,6 +55,7 @@ impl Tab {
             Tab::Costs => "Costs",
             Tab::Plugins => "Plugins",
             Tab::Config => "Config",
+            Tab::Bench => "Bench",
         }
     }
 
@@ -216,6 +219,7 @@ fn run_event_loop(
                     KeyCode::Char('4') => app.active_tab = Tab::Costs,
                     KeyCode::Char('5') => app.active_tab = Tab::Plugins,
                     KeyCode::Char('6') => app.active_tab = Tab::Config,
+                    KeyCode::Char('7') => app.active_tab = Tab::Bench,
 
                     // Scrolling
                     KeyCode::Down | KeyCode::Char('j') => app.scroll_down(),
@@ -291,6 +295,7 @@ fn render_tab_content(f: &mut Frame, area: Rect, app: &mut App) {
         Tab::Costs => widgets::costs::render(f, area, &app.data.costs),
         Tab::Plugins => widgets::plugins::render(f, area, &app.data.plugins),
         Tab::Config => widgets::config::render(f, area, &app.data.config_tree, app.config_scroll),
+        Tab::Bench => widgets::bench::render(f, area, &app.data.bench),
     }
 }
 
@@ -300,7 +305,7 @@ fn render_footer(f: &mut Frame, area: Rect, _app: &App) {
         Span::styled(" quit  ", Theme::key_desc()),
         Span::styled("Tab/\u{2190}\u{2192}", Theme::key_hint()),
         Span::styled(" switch  ", Theme::key_desc()),
-        Span::styled("1-6", Theme::key_hint()),
+        Span::styled("1-7", Theme::key_hint()),
         Span::styled(" jump  ", Theme::key_desc()),
         Span::styled("j/k/\u{2191}\u{2193}", Theme::key_hint()),
         Span::styled(" scroll  ", Theme::key_desc()),
@@ -4,7 +4,9 @@
 // data structs consumed by the widget layer.
 
 use crate::infra::config::Config;
-use crate::memory::store::{LearningRow, SkillEffectivenessRow, Store, UsagePatternRow};
+use crate::memory::store::{
+    BenchRunRow, LearningRow, SkillEffectivenessRow, Store, UsagePatternRow,
+};
 
 // ── Snapshot structs ─────────────────────────────────────────────
 
@@ -17,6 +19,7 @@ pub struct DashboardData {
     pub costs: CostsData,
     pub plugins: PluginsData,
     pub config_tree: ConfigTree,
+    pub bench: BenchData,
 }
 
 #[derive(Debug, Default)]
@@ -120,6 +123,21 @@ pub struct ConfigSection {
     pub entries: Vec<(String, String)>,
 }
 
+#[derive(Debug, Default)]
+pub struct BenchData {
+    /// Newest first.
+    pub runs: Vec<BenchRunSummary>,
+}
+
+/// One variant of one `openkoi bench` run.
+#[derive(Debug, Clone)]
+pub struct BenchRunSummary {
+    pub run: BenchRunRow,
+    /// Change in mean score since the previous run of the same suite and
+    /// variant.
+    pub score_delta: Option<f64>,
+}
+
 // ── Fetching ─────────────────────────────────────────────────────
 
 /// Load all dashboard data from Store + Config.
@@ -131,6 +149,7 @@ pub fn fetch_all(store: Option<&Store>, config: &Config) -> DashboardData {
         costs: fetch_costs(store),
         plugins: fetch_plugins(config),
         config_tree: build_config_tree(config),
+        bench: fetch_bench(store),
     }
 }
 
@@ -261,6 +280,37 @@ fn fetch_tasks(store: Option<&Store>) -> TasksData {
     data
 }
 
+fn fetch_bench(store: Option<&Store>) -> BenchData {
+    let Some(store) = store else {
+        return BenchData::default();
+    };
+    let runs = store.query_bench_runs(100).unwrap_or_default();
+    BenchData {
+        runs: with_score_deltas(runs),
+    }
+}
+
+/// Pair each run (newest first) with the next older run of its suite and
+/// variant.
+fn with_score_deltas(runs: Vec<BenchRunRow>) -> Vec<BenchRunSummary> {
+    runs.iter()
+        .enumerate()
+        .map(|(i, run)| {
+            let previous = runs[i + 1..]
+                .iter()
+                .find(|r| r.suite == run.suite && r.variant == run.variant);
+            let score_delta = match (run.avg_score, previous.and_then(|p| p.avg_score)) {
+                (Some(now), Some(before)) => Some(now - before),
+                _ => None,
+            };
+            BenchRunSummary {
+                run: run.clone(),
+                score_delta,
+            }
+        })
+        .collect()
+}
+
 fn fetch_learnings(store: Option<&Store>) -> LearningsData {
     let mut data = LearningsData::default();
     let Some(store) = store else { return data };
@@ -0,0 +1,98 @@
+// src/tui/widgets/bench.rs — Benchmark run history panel (Tab 7).
+
+use ratatui::{
+    layout::{Constraint, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::tui::data::BenchData;
+use crate::tui::theme::Theme;
+
+pub fn render(f: &mut Frame, area: Rect, data: &BenchData) {
+    let block = Block::default()
+        .title(format!(" Bench Runs ({}) ", data.runs.len()))
+        .borders(Borders::ALL)
+        .border_style(Theme::border());
+
+    if data.runs.is_empty() {
+        let p = Paragraph::new(Line::from(Span::styled(
+            "  No benchmark results yet. Run `openkoi bench suite.yaml`.",
+            Theme::text_dim(),
+        )))
+        .block(block);
+        f.render_widget(p, area);
+        return;
+    }
+
+    let header = Row::new(vec![
+        Cell::from("Date").style(Theme::table_header()),
+        Cell::from("Suite").style(Theme::table_header()),
+        Cell::from("Variant").style(Theme::table_header()),
+        Cell::from("Pass").style(Theme::table_header()),
+        Cell::from("Score").style(Theme::table_header()),
+        Cell::from("\u{0394}").style(Theme::table_header()),
+        Cell::from("Iter").style(Theme::table_header()),
+        Cell::from("Tokens").style(Theme::table_header()),
+        Cell::from("Cost").style(Theme::table_header()),
+    ]);
+
+    let rows: Vec<Row> = data
+        .runs
+        .iter()
+        .map(|summary| {
+            let run = &summary.run;
+            let pass_rate = if run.runs > 0 {
+                run.passed as f64 / run.runs as f64
+            } else {
+                0.0
+            };
+            let score_text = run
+                .avg_score
+                .map(|s| format!("{:.2}", s))
+                .unwrap_or_else(|| "-".into());
+            let score_style = run.avg_score.map(Theme::score).unwrap_or(Theme::text_dim());
+            let (delta_text, delta_style) = match summary.score_delta {
+                Some(d) if d > 0.005 => (format!("+{:.2}", d), Theme::success()),
+                Some(d) if d < -0.005 => (format!("{:.2}", d), Theme::error()),
+                Some(_) => ("=".to_string(), Theme::text_dim()),
+                None => ("-".to_string(), Theme::text_dim()),
+            };
+            let date = run.started_at.get(..10).unwrap_or(&run.started_at);
+
+            Row::new(vec![
+                Cell::from(date.to_string()).style(Theme::text_dim()),
+                Cell::from(run.suite.clone()).style(Theme::text()),
+                Cell::from(run.variant.clone()).style(Theme::text()),
+                Cell::from(format!(
+                    "{:.0}% {}/{}",
+                    pass_rate * 100.0,
+                    run.passed,
+                    run.runs
+                ))
+                .style(Theme::confidence(pass_rate)),
+                Cell::from(score_text).style(score_style),
+                Cell::from(delta_text).style(delta_style),
+                Cell::from(format!("{:.1}", run.avg_iterations)).style(Theme::text()),
+                Cell::from(format!("{:.0}", run.avg_tokens)).style(Theme::text_dim()),
+                Cell::from(format!("${:.4}", run.total_cost)).style(Theme::text_dim()),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Min(12),
+        Constraint::Min(20),
+        Constraint::Length(12),
+        Constraint::Length(6),
+        Constraint::Length(6),
+        Constraint::Length(5),
+        Constraint::Length(8),
+        Constraint::Length(9),
+    ];
+
+    let table = Table::new(rows, widths).header(header).block(block);
+    f.render_widget(table, area);
+}
@@ -1,5 +1,6 @@
 // src/tui/widgets/mod.rs — Widget sub-modules for each tab panel.
 
+pub mod bench;
 pub mod config;
 pub mod costs;
 pub mod learnings;
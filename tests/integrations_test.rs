@@ -3,6 +3,7 @@
 use openkoi::integrations::credentials::{validate_token_format, IntegrationCredentials};
 use openkoi::integrations::registry::IntegrationRegistry;
 use openkoi::integrations::types::*;
+use openkoi::tools::ToolRegistry;
 
 use async_trait::async_trait;
 
@@ -205,6 +206,138 @@ fn test_registry_multiple_integrations() {
     assert_eq!(tools.len(), 8);
 }
 
+/// An integration whose id ends in a tool suffix (`_read`), which used to
+/// confuse suffix-based dispatch.
+struct MockReadIntegration;
+
+impl Integration for MockReadIntegration {
+    fn id(&self) -> &str {
+        "feed_read"
+    }
+    fn name(&self) -> &str {
+        "Feed Reader"
+    }
+    fn messaging(&self) -> Option<&dyn MessagingAdapter> {
+        Some(&MockMessagingAdapter)
+    }
+    fn document(&self) -> Option<&dyn DocumentAdapter> {
+        None
+    }
+}
+
+#[tokio::test]
+async fn test_tool_registry_dispatches_integration_tools() {
+    let mut integrations = IntegrationRegistry::new();
+    integrations.register(Box::new(MockMessagingIntegration));
+    integrations.register(Box::new(MockDocIntegration));
+    integrations.register(Box::new(MockReadIntegration));
+
+    let mut tools = ToolRegistry::new();
+    for provider in integrations.tool_providers() {
+        tools.register(provider);
+    }
+    assert_eq!(tools.len(), 11);
+
+    let sent = tools
+        .call(
+            "mock_chat_send",
+            serde_json::json!({"target": "general", "message": "hi"}),
+        )
+        .await;
+    assert_eq!(sent, "Message sent successfully (id: sent-to-general)");
+
+    let doc = tools
+        .call("mock_docs_read_doc", serde_json::json!({"doc_id": "d1"}))
+        .await;
+    assert!(doc.starts_with("# Mock Doc"));
+
+    let read = tools
+        .call("feed_read_read", serde_json::json!({"channel": "news"}))
+        .await;
+    assert!(read.contains("Hello from mock"));
+
+    let unknown = tools.call("feed_read", serde_json::json!({})).await;
+    assert!(unknown.contains("not recognized"));
+}
+
+/// Google Docs stand-in whose documents echo the ID they were read with.
+struct MockGoogleDocs;
+
+#[async_trait]
+impl DocumentAdapter for MockGoogleDocs {
+    async fn read(&self, doc_id: &str) -> anyhow::Result<Document> {
+        Ok(Document {
+            id: doc_id.to_string(),
+            title: doc_id.to_string(),
+            content: String::new(),
+            url: None,
+        })
+    }
+
+    async fn write(&self, _doc_id: &str, _content: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn create(&self, title: &str, _content: &str) -> anyhow::Result<String> {
+        Ok(title.to_string())
+    }
+
+    async fn search(&self, _query: &str) -> anyhow::Result<Vec<DocumentRef>> {
+        Ok(vec![])
+    }
+
+    async fn list(&self, _folder: Option<&str>) -> anyhow::Result<Vec<DocumentRef>> {
+        Ok(vec![])
+    }
+}
+
+impl Integration for MockGoogleDocs {
+    fn id(&self) -> &str {
+        "google_docs"
+    }
+    fn name(&self) -> &str {
+        "Google Docs"
+    }
+    fn messaging(&self) -> Option<&dyn MessagingAdapter> {
+        None
+    }
+    fn document(&self) -> Option<&dyn DocumentAdapter> {
+        Some(self)
+    }
+}
+
+#[tokio::test]
+async fn test_doc_id_round_trips_through_masking() {
+    use openkoi::security::redact::Redactor;
+
+    let mut integrations = IntegrationRegistry::new();
+    integrations.register(Box::new(MockGoogleDocs));
+    let mut tools = ToolRegistry::new();
+    for provider in integrations.tool_providers() {
+        tools.register(provider);
+    }
+
+    // A Doc ID is random enough to look like a secret, so the prompt
+    // carries a placeholder for it, and the tool must get the real ID back.
+    let doc_id = "1BxiMVs0XRA5nFMdKvBdBZjgmUUqptlbs74OgvE2upms";
+    let redactor = Redactor::global();
+    let masked = redactor.mask(&format!("Summarize doc {doc_id}"));
+    assert!(!masked.contains(doc_id));
+    let placeholder = masked.trim_start_matches("Summarize doc ");
+
+    let out = tools
+        .call(
+            "google_docs_read_doc",
+            serde_json::json!({ "doc_id": placeholder }),
+        )
+        .await;
+    assert_eq!(out, format!("# {doc_id}\n\n"));
+
+    // Tool-call arguments echoed back to the model keep the ID as-is.
+    let args = serde_json::json!({ "doc_id": doc_id });
+    assert_eq!(redactor.mask_value(&args), args);
+}
+
 // ---------- Credential tests ----------
 
 #[test]
@@ -361,12 +494,17 @@ async fn test_executor_dispatches_integration_tools() {
         system: "Test".into(),
         messages: vec![],
         token_estimate: 100,
+        cache_prefix_len: 0,
     };
 
-    let tools = registry.all_tools();
+    let mut tool_registry = ToolRegistry::new();
+    for provider in registry.tool_providers() {
+        tool_registry.register(provider);
+    }
+    let tools = tool_registry.defs().to_vec();
 
     let result = executor
-        .execute(&context, &tools, None, Some(&registry))
+        .execute(&context, &tools, Some(&tool_registry))
         .await
         .unwrap();
 
@@ -472,10 +610,11 @@ async fn test_executor_unknown_tool_returns_error() {
         system: "Test".into(),
         messages: vec![],
         token_estimate: 100,
+        cache_prefix_len: 0,
     };
 
     // No tools, no registry
-    let result = executor.execute(&context, &[], None, None).await.unwrap();
+    let result = executor.execute(&context, &[], None).await.unwrap();
     // Should still complete (error is returned to model as tool_result)
     assert!(result.content.contains("Done"));
     assert!(result.tool_calls_made >= 1);
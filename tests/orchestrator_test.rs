@@ -6,9 +6,10 @@ use async_trait::async_trait;
 use futures::Stream;
 use std::pin::Pin;
 
+use openkoi::core::instructions::ProjectInstructions;
 use openkoi::core::orchestrator::{Orchestrator, SessionContext};
 use openkoi::core::safety::SafetyChecker;
-use openkoi::core::types::{IterationEngineConfig, TaskInput};
+use openkoi::core::types::{IterationDecision, IterationEngineConfig, TaskInput};
 use openkoi::infra::config::{IterationConfig, SafetyConfig};
 use openkoi::memory::recall::HistoryRecall;
 use openkoi::provider::roles::ModelRoles;
@@ -98,6 +99,9 @@ fn default_session_context() -> SessionContext {
             raw: "I am a test assistant.".into(),
             source: SoulSource::Default,
         },
+        instructions: ProjectInstructions::default(),
+        code_index: None,
+        lsp: None,
         ranked_skills: vec![],
         recall: HistoryRecall::default(),
         tools: vec![],
@@ -130,12 +134,94 @@ async fn test_orchestrator_single_iteration_accept() {
     let task = TaskInput::new("Say hello");
     let ctx = default_session_context();
 
-    let result = orchestrator.run(task, &ctx, None, None).await.unwrap();
+    let result = orchestrator.run(task, &ctx, None).await.unwrap();
 
     assert_eq!(result.output.content, "Hello, world!");
     assert_eq!(result.iterations, 1);
     assert!(result.total_tokens > 0);
     assert!(result.cost >= 0.0);
+    assert_eq!(result.history.len(), 1);
+    assert_eq!(result.history[0].iteration, 1);
+    assert_eq!(result.history[0].decision, result.decision);
+    assert!(result.error.is_none());
+}
+
+/// A provider whose every call fails.
+struct FailingProvider;
+
+#[async_trait]
+impl ModelProvider for FailingProvider {
+    fn id(&self) -> &str {
+        "mock"
+    }
+
+    fn name(&self) -> &str {
+        "Failing Provider"
+    }
+
+    fn models(&self) -> Vec<ModelInfo> {
+        MockProvider::new("").models()
+    }
+
+    async fn chat(
+        &self,
+        _request: ChatRequest,
+    ) -> Result<ChatResponse, openkoi::infra::errors::OpenKoiError> {
+        Err(openkoi::infra::errors::OpenKoiError::Provider {
+            provider: "mock".into(),
+            message: "invalid API key".into(),
+            retriable: false,
+        })
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ChatChunk, openkoi::infra::errors::OpenKoiError>> + Send>>,
+        openkoi::infra::errors::OpenKoiError,
+    > {
+        MockProvider::new("").chat_stream(request).await
+    }
+
+    async fn embed(
+        &self,
+        _texts: &[&str],
+    ) -> Result<Vec<Vec<f32>>, openkoi::infra::errors::OpenKoiError> {
+        Ok(vec![])
+    }
+}
+
+#[tokio::test]
+async fn test_orchestrator_reports_provider_error() {
+    let config = IterationEngineConfig {
+        max_iterations: 3,
+        ..Default::default()
+    };
+    let safety = SafetyChecker::from_config(&IterationConfig::default(), &SafetyConfig::default());
+    let mut orchestrator = Orchestrator::new(
+        Arc::new(FailingProvider),
+        ModelRoles::from_single(ModelRef::new("mock", "mock-model")),
+        config,
+        safety,
+        Arc::new(SkillRegistry::empty()),
+        None,
+    )
+    .with_project_dir(std::env::temp_dir().join("openkoi_test_nonexistent"));
+
+    let result = orchestrator
+        .run(
+            TaskInput::new("Say hello"),
+            &default_session_context(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.decision, IterationDecision::AbortError);
+    assert_eq!(result.history.len(), 1);
+    assert_eq!(result.history[0].score, None);
+    assert!(result.error.unwrap().contains("invalid API key"));
 }
 
 #[tokio::test]
@@ -168,7 +254,7 @@ async fn test_orchestrator_multiple_iterations() {
     let task = TaskInput::new("Write a complex function");
     let ctx = default_session_context();
 
-    let result = orchestrator.run(task, &ctx, None, None).await.unwrap();
+    let result = orchestrator.run(task, &ctx, None).await.unwrap();
 
     // The mock evaluator scores 0.85 which is below 0.99, so it should run all iterations
     // until max_iterations is exhausted
@@ -203,7 +289,7 @@ async fn test_orchestrator_with_tools_defined() {
         ..default_session_context()
     };
 
-    let result = orchestrator.run(task, &ctx, None, None).await.unwrap();
+    let result = orchestrator.run(task, &ctx, None).await.unwrap();
     assert!(!result.output.content.is_empty());
 }
 
@@ -229,7 +315,7 @@ async fn test_orchestrator_result_includes_skills_used() {
     let task = TaskInput::new("Test");
     let ctx = default_session_context();
 
-    let result = orchestrator.run(task, &ctx, None, None).await.unwrap();
+    let result = orchestrator.run(task, &ctx, None).await.unwrap();
     // With no ranked skills, skills_used should be empty
     assert!(result.skills_used.is_empty());
 }
@@ -333,6 +419,7 @@ async fn test_executor_tool_call_loop_without_mcp() {
         system: "You are a test assistant.".into(),
         messages: vec![],
         token_estimate: 100,
+        cache_prefix_len: 0,
     };
 
     let tools = vec![ToolDef {
@@ -341,13 +428,354 @@ async fn test_executor_tool_call_loop_without_mcp() {
         parameters: serde_json::json!({}),
     }];
 
-    let result = executor
-        .execute(&context, &tools, None, None)
-        .await
-        .unwrap();
+    let result = executor.execute(&context, &tools, None).await.unwrap();
     // Should complete (the mock returns final content on second call)
     assert!(result.content.contains("Found the answer: 42"));
     assert!(result.tool_calls_made >= 1);
     // Usage should accumulate from both rounds
     assert!(result.usage.input_tokens >= 130); // 50 + 80
 }
+
+/// Mock provider with two executor models that records which model served
+/// each request. Replies differ per call so evaluation is never skipped.
+struct LadderProvider {
+    calls: std::sync::atomic::AtomicU32,
+    models_seen: std::sync::Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl ModelProvider for LadderProvider {
+    fn id(&self) -> &str {
+        "mock"
+    }
+
+    fn name(&self) -> &str {
+        "Ladder Mock"
+    }
+
+    fn models(&self) -> Vec<ModelInfo> {
+        ["mock-model", "mock-large"]
+            .iter()
+            .map(|id| ModelInfo {
+                id: (*id).into(),
+                name: (*id).into(),
+                context_window: 128_000,
+                max_output_tokens: 4096,
+                supports_tools: true,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest,
+    ) -> Result<ChatResponse, openkoi::infra::errors::OpenKoiError> {
+        let n = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        self.models_seen.lock().unwrap().push(request.model.clone());
+        Ok(ChatResponse {
+            content: format!("attempt {}", n),
+            tool_calls: vec![],
+            usage: TokenUsage {
+                input_tokens: 100,
+                output_tokens: 50,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+            },
+            stop_reason: StopReason::EndTurn,
+        })
+    }
+
+    async fn chat_stream(
+        &self,
+        _request: ChatRequest,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ChatChunk, openkoi::infra::errors::OpenKoiError>> + Send>>,
+        openkoi::infra::errors::OpenKoiError,
+    > {
+        Err(openkoi::infra::errors::OpenKoiError::Provider {
+            provider: "mock".into(),
+            message: "Streaming not supported in mock".into(),
+            retriable: false,
+        })
+    }
+
+    async fn embed(
+        &self,
+        _texts: &[&str],
+    ) -> Result<Vec<Vec<f32>>, openkoi::infra::errors::OpenKoiError> {
+        Ok(vec![vec![0.1, 0.2, 0.3]])
+    }
+}
+
+#[tokio::test]
+async fn test_orchestrator_escalates_after_stalled_iterations() {
+    use openkoi::core::escalation::EscalationPolicy;
+
+    let provider = Arc::new(LadderProvider {
+        calls: std::sync::atomic::AtomicU32::new(0),
+        models_seen: std::sync::Mutex::new(Vec::new()),
+    });
+
+    let config = IterationEngineConfig {
+        max_iterations: 4,
+        quality_threshold: 0.99,
+        ..Default::default()
+    };
+    let safety = SafetyChecker::from_config(
+        &IterationConfig {
+            max_iterations: 4,
+            ..Default::default()
+        },
+        &SafetyConfig::default(),
+    );
+
+    let mut orchestrator = Orchestrator::new(
+        provider.clone(),
+        ModelRoles::from_single(ModelRef::new("mock", "mock-model")),
+        config,
+        safety,
+        Arc::new(SkillRegistry::empty()),
+        None,
+    )
+    .with_project_dir(std::env::temp_dir().join("openkoi_test_nonexistent"))
+    .with_escalation(EscalationPolicy {
+        ladder: vec!["mock-model".into(), "mock-large".into()],
+        stall_iterations: 1,
+        ..Default::default()
+    });
+
+    let result = orchestrator
+        .run(
+            TaskInput::new("Refactor the parser"),
+            &default_session_context(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    assert!(result.iterations >= 3);
+    let seen = provider.models_seen.lock().unwrap();
+    assert!(seen.iter().any(|m| m == "mock-large"));
+}
+
+/// Mock provider that issues three tool calls in one response (two reads,
+/// then a write) and records the tool results it receives back.
+struct MultiToolCallProvider {
+    call_count: std::sync::atomic::AtomicU32,
+    tool_results: std::sync::Mutex<Vec<(String, String)>>,
+}
+
+#[async_trait]
+impl ModelProvider for MultiToolCallProvider {
+    fn id(&self) -> &str {
+        "mock-multi"
+    }
+    fn name(&self) -> &str {
+        "Mock Multi Tool Provider"
+    }
+    fn models(&self) -> Vec<ModelInfo> {
+        vec![]
+    }
+
+    async fn chat(
+        &self,
+        request: ChatRequest,
+    ) -> Result<ChatResponse, openkoi::infra::errors::OpenKoiError> {
+        let count = self
+            .call_count
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if count == 0 {
+            let call = |id: &str, name: &str| ToolCall {
+                id: id.into(),
+                name: name.into(),
+                arguments: serde_json::json!({}),
+            };
+            return Ok(ChatResponse {
+                content: String::new(),
+                tool_calls: vec![
+                    call("call_1", "read_slow"),
+                    call("call_2", "read_fast"),
+                    call("call_3", "write"),
+                ],
+                usage: TokenUsage::default(),
+                stop_reason: StopReason::ToolUse,
+            });
+        }
+        *self.tool_results.lock().unwrap() = request
+            .messages
+            .iter()
+            .filter(|m| m.role == Role::Tool)
+            .map(|m| {
+                (
+                    m.tool_call_id.clone().unwrap_or_default(),
+                    m.content.clone(),
+                )
+            })
+            .collect();
+        Ok(ChatResponse {
+            content: "done".into(),
+            tool_calls: vec![],
+            usage: TokenUsage::default(),
+            stop_reason: StopReason::EndTurn,
+        })
+    }
+
+    async fn chat_stream(
+        &self,
+        _request: ChatRequest,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<ChatChunk, openkoi::infra::errors::OpenKoiError>> + Send>>,
+        openkoi::infra::errors::OpenKoiError,
+    > {
+        Err(openkoi::infra::errors::OpenKoiError::Provider {
+            provider: "mock-multi".into(),
+            message: "not supported".into(),
+            retriable: false,
+        })
+    }
+
+    async fn embed(
+        &self,
+        _texts: &[&str],
+    ) -> Result<Vec<Vec<f32>>, openkoi::infra::errors::OpenKoiError> {
+        Ok(vec![])
+    }
+}
+
+/// Tools that track how many calls are in flight at once.
+#[derive(Default)]
+struct TrackedTools {
+    in_flight: std::sync::atomic::AtomicU32,
+    max_in_flight: std::sync::atomic::AtomicU32,
+}
+
+#[async_trait]
+impl openkoi::tools::ToolProvider for TrackedTools {
+    fn id(&self) -> &str {
+        "tracked"
+    }
+
+    fn source(&self) -> openkoi::tools::ToolSource {
+        openkoi::tools::ToolSource::Builtin
+    }
+
+    fn tools(&self) -> Vec<ToolDef> {
+        ["read_slow", "read_fast", "write"]
+            .iter()
+            .map(|n| ToolDef {
+                name: n.to_string(),
+                description: String::new(),
+                parameters: serde_json::json!({"type": "object"}),
+            })
+            .collect()
+    }
+
+    fn effect(&self, tool: &str) -> openkoi::tools::ToolEffect {
+        if tool.starts_with("read") {
+            openkoi::tools::ToolEffect::ReadOnly
+        } else {
+            openkoi::tools::ToolEffect::Mutating
+        }
+    }
+
+    async fn call(&self, tool: &str, _args: serde_json::Value) -> anyhow::Result<String> {
+        use std::sync::atomic::Ordering;
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        let delay = if tool == "read_slow" { 100 } else { 10 };
+        tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        Ok(format!("{tool} ok"))
+    }
+}
+
+#[tokio::test]
+async fn test_executor_runs_read_only_tool_calls_concurrently() {
+    use openkoi::core::executor::Executor;
+    use openkoi::core::types::ExecutionContext;
+    use openkoi::tools::ToolRegistry;
+
+    let mock = Arc::new(MultiToolCallProvider {
+        call_count: std::sync::atomic::AtomicU32::new(0),
+        tool_results: std::sync::Mutex::new(vec![]),
+    });
+    let provider: Arc<dyn ModelProvider> = mock.clone();
+    let executor = Executor::new(provider, "mock-multi".into()).with_tool_parallelism(4);
+
+    let tracked = Arc::new(TrackedTools::default());
+    let registry = ToolRegistry::new().with(tracked.clone());
+    let context = ExecutionContext {
+        system: "You are a test assistant.".into(),
+        messages: vec![],
+        token_estimate: 100,
+        cache_prefix_len: 0,
+    };
+
+    let result = executor
+        .execute(&context, registry.defs(), Some(&registry))
+        .await
+        .unwrap();
+    assert_eq!(result.content, "done");
+
+    // Both reads overlapped; the write ran alone afterwards.
+    assert_eq!(
+        tracked
+            .max_in_flight
+            .load(std::sync::atomic::Ordering::SeqCst),
+        2
+    );
+
+    // Results go back to the model in the original call order, even though
+    // the slow read finished last.
+    let results = mock.tool_results.lock().unwrap().clone();
+    assert_eq!(
+        results,
+        vec![
+            ("call_1".to_string(), "read_slow ok".to_string()),
+            ("call_2".to_string(), "read_fast ok".to_string()),
+            ("call_3".to_string(), "write ok".to_string()),
+        ]
+    );
+
+    // Per-call latency is recorded in call order.
+    let names: Vec<_> = result.tool_calls.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["read_slow", "read_fast", "write"]);
+    assert!(result.tool_calls[0].duration_ms >= 100);
+    assert!(result.tool_calls[0].parallel && result.tool_calls[1].parallel);
+    assert!(!result.tool_calls[2].parallel);
+}
+
+#[tokio::test]
+async fn test_orchestrator_charges_unusable_evaluation() {
+    // Every reply is plain text, so the judge's reply can't be salvaged.
+    let provider: Arc<dyn ModelProvider> = Arc::new(MockProvider::new("Hello, world!"));
+    let config = IterationEngineConfig {
+        max_iterations: 1,
+        quality_threshold: 0.8,
+        ..Default::default()
+    };
+    let safety = SafetyChecker::from_config(&IterationConfig::default(), &SafetyConfig::default());
+
+    let mut orchestrator = Orchestrator::new(
+        provider,
+        ModelRoles::from_single(ModelRef::new("mock", "mock-model")),
+        config,
+        safety,
+        Arc::new(SkillRegistry::new()),
+        None,
+    )
+    .with_project_dir(std::env::temp_dir().join("openkoi_test_nonexistent"));
+
+    let result = orchestrator
+        .run(
+            TaskInput::new("Say hello"),
+            &default_session_context(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    // The executor's call plus the judge's.
+    assert!(result.total_tokens >= 300);
+}
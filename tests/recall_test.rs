@@ -1,5 +1,6 @@
 // tests/recall_test.rs — Integration test: memory recall with token budget
 
+use openkoi::core::tokenizer::Heuristic;
 use openkoi::memory::recall::{recall, HistoryRecall};
 use openkoi::memory::schema;
 use openkoi::memory::store::Store;
@@ -70,7 +71,7 @@ fn seeded_store() -> Store {
 fn test_recall_returns_anti_patterns_first() {
     let store = seeded_store();
 
-    let result = recall(&store, "Write a SQL query", Some("sql"), 10000).unwrap();
+    let result = recall(&store, "Write a SQL query", Some("sql"), 10000, &Heuristic).unwrap();
 
     // Anti-patterns should be prioritized
     assert!(
@@ -84,7 +85,7 @@ fn test_recall_returns_anti_patterns_first() {
 fn test_recall_includes_skill_recommendations() {
     let store = seeded_store();
 
-    let result = recall(&store, "Review Rust code", Some("rust"), 10000).unwrap();
+    let result = recall(&store, "Review Rust code", Some("rust"), 10000, &Heuristic).unwrap();
 
     // Should recommend skills effective for the "rust" category
     assert!(
@@ -100,7 +101,7 @@ fn test_recall_includes_skill_recommendations() {
 fn test_recall_includes_learnings() {
     let store = seeded_store();
 
-    let result = recall(&store, "Write code", None, 10000).unwrap();
+    let result = recall(&store, "Write code", None, 10000, &Heuristic).unwrap();
 
     // Should include heuristic learnings
     assert!(
@@ -114,7 +115,7 @@ fn test_recall_respects_token_budget() {
     let store = seeded_store();
 
     // Very small budget — should limit what's returned
-    let result = recall(&store, "Test", None, 5).unwrap();
+    let result = recall(&store, "Test", None, 5, &Heuristic).unwrap();
 
     // With a 5-token budget, we shouldn't be able to fit much
     assert!(
@@ -128,7 +129,7 @@ fn test_recall_respects_token_budget() {
 fn test_recall_with_zero_budget() {
     let store = seeded_store();
 
-    let result = recall(&store, "Test", None, 0).unwrap();
+    let result = recall(&store, "Test", None, 0, &Heuristic).unwrap();
 
     assert_eq!(result.tokens_used, 0);
     assert!(result.anti_patterns.is_empty());
@@ -141,7 +142,7 @@ fn test_recall_empty_store() {
     schema::run_migrations(&conn).unwrap();
     let store = Store::new(conn);
 
-    let result = recall(&store, "Test", Some("code"), 10000).unwrap();
+    let result = recall(&store, "Test", Some("code"), 10000, &Heuristic).unwrap();
 
     assert_eq!(result.tokens_used, 0);
     assert!(result.anti_patterns.is_empty());
@@ -165,7 +166,7 @@ fn test_recall_without_category() {
     let store = seeded_store();
 
     // When no category is provided, skill recommendations are skipped
-    let result = recall(&store, "General task", None, 10000).unwrap();
+    let result = recall(&store, "General task", None, 10000, &Heuristic).unwrap();
 
     assert!(
         result.skill_recommendations.is_empty(),
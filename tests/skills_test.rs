@@ -170,3 +170,122 @@ fn test_bundled_skills_have_descriptions() {
         }
     }
 }
+
+#[test]
+fn test_selector_runs_one_variant_per_skill() {
+    use openkoi::infra::config::{ExperimentConfig, ExplorationPolicy};
+    use openkoi::learner::skill_selector::SkillSelector;
+    use openkoi::learner::types::Signal;
+    use openkoi::memory::{schema, store::Store};
+    use openkoi::skills::types::{SkillEntry, SkillMetadata, SkillSource};
+
+    let skill = |name: &str| SkillEntry {
+        name: name.into(),
+        kind: SkillKind::Task,
+        description: String::new(),
+        source: SkillSource::UserGlobal,
+        path: None,
+        metadata: SkillMetadata {
+            categories: vec!["rust".into()],
+            ..Default::default()
+        },
+        embedding: None,
+        approved: true,
+    };
+    let skills = vec![skill("review"), skill("review@v2"), skill("lint")];
+
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    schema::run_migrations(&conn).unwrap();
+    let store = Store::new(conn);
+    for _ in 0..30 {
+        store
+            .upsert_skill_effectiveness("review", "rust", 0.4)
+            .unwrap();
+        store
+            .upsert_skill_effectiveness("review@v2", "rust", 0.95)
+            .unwrap();
+    }
+
+    let thompson = SkillSelector::new().with_seed(11);
+    let ranked = thompson.select("Review this", None, Some("rust"), &skills, Some(&store));
+    let names: Vec<&str> = ranked.iter().map(|r| r.skill.name.as_str()).collect();
+    assert_eq!(names.len(), 2);
+    assert!(names.contains(&"lint"));
+    assert!(names.contains(&"review@v2"));
+    let picked = ranked.iter().find(|r| r.skill.name == "review@v2").unwrap();
+    assert!(picked
+        .signals
+        .iter()
+        .any(|s| matches!(s, Signal::Experiment { base, arms: 2 } if base == "review")));
+    // The variant answers to its base name
+    assert!(picked
+        .signals
+        .iter()
+        .any(|s| matches!(s, Signal::ExplicitRequest)));
+
+    let off = SkillSelector::with_experiments(ExperimentConfig {
+        policy: ExplorationPolicy::Off,
+        ..Default::default()
+    });
+    let ranked = off.select("Review this", None, Some("rust"), &skills, Some(&store));
+    assert!(ranked.iter().any(|r| r.skill.name == "review"));
+    assert!(!ranked.iter().any(|r| r.skill.name == "review@v2"));
+}
+
+#[test]
+fn test_selector_ranks_by_similarity_and_history() {
+    use openkoi::learner::skill_selector::{contribution, SkillSelector};
+    use openkoi::learner::types::Signal;
+    use openkoi::memory::{schema, store::Store};
+    use openkoi::skills::types::{SkillEntry, SkillMetadata, SkillSource};
+
+    let skill = |name: &str, embedding: Vec<f32>| SkillEntry {
+        name: name.into(),
+        kind: SkillKind::Task,
+        description: String::new(),
+        source: SkillSource::UserGlobal,
+        path: None,
+        metadata: SkillMetadata::default(),
+        embedding: Some(embedding),
+        approved: true,
+    };
+    let skills = vec![
+        skill("sql-migrations", vec![1.0, 0.0]),
+        skill("changelog", vec![0.6, 0.8]),
+        skill("release-notes", vec![0.0, 1.0]),
+    ];
+
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    schema::run_migrations(&conn).unwrap();
+    let store = Store::new(conn);
+    for _ in 0..10 {
+        store
+            .upsert_skill_effectiveness("changelog", "docs", 0.9)
+            .unwrap();
+    }
+
+    let selector = SkillSelector::new();
+    let task = [1.0, 0.0];
+    let ranked = selector.select("Add a column", Some(&task), None, &skills, Some(&store));
+    let names: Vec<&str> = ranked.iter().map(|r| r.skill.name.as_str()).collect();
+    assert_eq!(names, ["sql-migrations", "changelog"]);
+
+    // History in the task's category lifts the weaker semantic match
+    let ranked = selector.select(
+        "Add a column",
+        Some(&task),
+        Some("docs"),
+        &skills,
+        Some(&store),
+    );
+    assert_eq!(ranked[0].skill.name, "changelog");
+
+    // explain keeps every skill and each signal's share of the score
+    let explained = selector.explain("Add a column", Some(&task), None, &skills, None);
+    assert_eq!(explained.len(), 3);
+    let top = &explained[0];
+    assert!(matches!(top.signals[..], [Signal::SemanticMatch { similarity }] if similarity > 0.99));
+    let total: f32 = top.signals.iter().map(contribution).sum();
+    assert!((total - top.score).abs() < 1e-6);
+    assert!(explained[2].signals.is_empty());
+}
@@ -2,7 +2,7 @@
 
 use openkoi::memory::decay::run_decay;
 use openkoi::memory::schema;
-use openkoi::memory::store::Store;
+use openkoi::memory::store::{BenchResultRow, Store};
 use openkoi::patterns::miner::PatternMiner;
 use rusqlite::Connection;
 
@@ -257,6 +257,50 @@ fn test_skill_effectiveness() {
     assert_eq!(top[0].skill_name, "code-review"); // Higher avg score
 }
 
+#[test]
+fn test_record_skill_run() {
+    let store = test_store();
+    store
+        .insert_task("task-1", "Review", Some("rust"), None)
+        .unwrap();
+    store
+        .record_skill_run("task-1", "review@v2", "review", "rust", 0.9)
+        .unwrap();
+
+    let (skill, base): (String, String) = store
+        .conn()
+        .query_row(
+            "SELECT skill_name, base_name FROM skill_runs WHERE task_id = 'task-1'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!((skill.as_str(), base.as_str()), ("review@v2", "review"));
+
+    let eff = store
+        .query_skill_effectiveness("review@v2", "rust")
+        .unwrap()
+        .unwrap();
+    assert_eq!(eff.sample_count, 1);
+    assert_eq!(store.query_all_skill_effectiveness().unwrap().len(), 1);
+}
+
+#[test]
+fn test_skill_embedding_cache() {
+    let store = test_store();
+    assert_eq!(store.get_skill_embedding("abc", "openai").unwrap(), None);
+
+    store
+        .put_skill_embedding("abc", "openai", &[0.5, -1.25, 3.0])
+        .unwrap();
+    assert_eq!(
+        store.get_skill_embedding("abc", "openai").unwrap(),
+        Some(vec![0.5, -1.25, 3.0])
+    );
+    // Vectors from another provider don't mix
+    assert_eq!(store.get_skill_embedding("abc", "ollama").unwrap(), None);
+}
+
 #[test]
 fn test_memory_chunks() {
     let store = test_store();
@@ -342,6 +386,42 @@ fn test_usage_patterns() {
     assert_eq!(patterns[0].sample_count, 5);
 }
 
+#[test]
+fn test_bench_results() {
+    let store = test_store();
+    let row = |variant: &str, attempt: i32, passed: bool, score: f64| BenchResultRow {
+        run_id: "run-1".into(),
+        suite: "migration".into(),
+        variant: variant.into(),
+        case_id: "fix-bug".into(),
+        attempt,
+        passed,
+        score: Some(score),
+        iterations: 2,
+        tokens: 1000,
+        cost_usd: 0.1,
+        duration_ms: 5000,
+        failures: (!passed).then(|| "tests failed".into()),
+    };
+    store
+        .insert_bench_result(&row("sonnet", 1, true, 0.9))
+        .unwrap();
+    store
+        .insert_bench_result(&row("sonnet", 2, false, 0.5))
+        .unwrap();
+    store
+        .insert_bench_result(&row("haiku", 1, true, 0.8))
+        .unwrap();
+
+    let runs = store.query_bench_runs(10).unwrap();
+    assert_eq!(runs.len(), 2);
+    let sonnet = runs.iter().find(|r| r.variant == "sonnet").unwrap();
+    assert_eq!(sonnet.runs, 2);
+    assert_eq!(sonnet.passed, 1);
+    assert!((sonnet.avg_score.unwrap() - 0.7).abs() < 1e-9);
+    assert!((sonnet.total_cost - 0.2).abs() < 1e-9);
+}
+
 #[test]
 fn test_schema_migrations_idempotent() {
     // Running migrations twice should not fail